@@ -0,0 +1,75 @@
+-- Your SQL goes here
+
+-- Scoring formulas are stored as data so that both the record_score SQL function and the rust code
+-- (pointercrate_demonlist::scoring) evaluate the exact same definition. Each formula has an id (its
+-- "version") and consists of a set of position brackets. Inside a bracket, the score for a 100% record
+-- on the demon at position p is
+--
+--     scale * EXP(exponent * (shift - p)) + constant
+--
+-- A non-100% record with progress >= requirement is worth
+--
+--     beaten_score * EXP(LN(partial_base) * (progress - requirement) / (100 - requirement)) / partial_divisor
+CREATE TABLE scoring_formulas (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    partial_base DOUBLE PRECISION NOT NULL CHECK (partial_base > 0),
+    partial_divisor DOUBLE PRECISION NOT NULL CHECK (partial_divisor > 0),
+    active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+-- At most one formula can be active at any time
+CREATE UNIQUE INDEX scoring_formulas_one_active ON scoring_formulas (active) WHERE active;
+
+CREATE TABLE scoring_formula_brackets (
+    formula INTEGER NOT NULL REFERENCES scoring_formulas(id) ON DELETE CASCADE,
+    -- exclusive
+    lower_bound SMALLINT NOT NULL,
+    -- inclusive
+    upper_bound SMALLINT NOT NULL,
+    scale DOUBLE PRECISION NOT NULL,
+    exponent DOUBLE PRECISION NOT NULL,
+    shift DOUBLE PRECISION NOT NULL,
+    constant DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (formula, lower_bound),
+    CHECK (lower_bound < upper_bound)
+);
+
+-- The formula introduced in 20220601192100_extended_list_buff, expressed as data
+INSERT INTO scoring_formulas (name, partial_base, partial_divisor, active) VALUES ('Extended list buff (June 2022)', 5, 10, TRUE);
+
+INSERT INTO scoring_formula_brackets (formula, lower_bound, upper_bound, scale, exponent, shift, constant)
+SELECT id, 55, 150, 56.191, LN(2) * LN(50.0) / 99.0, 54.147 - 3.2, 6.273 FROM scoring_formulas
+UNION ALL
+SELECT id, 35, 55, 212.61, LN(1.036), 1, 25.071 FROM scoring_formulas
+UNION ALL
+SELECT id, 20, 35, 250 - 83.389, LN(1.0099685), 2, -31.152 FROM scoring_formulas
+UNION ALL
+SELECT id, 0, 20, 250 - 100.39, LN(1.168), 1, 100.39 FROM scoring_formulas;
+
+CREATE OR REPLACE FUNCTION record_score_with(the_formula INTEGER, progress FLOAT, demon FLOAT, list_size FLOAT, requirement FLOAT) RETURNS FLOAT AS
+$record_score_with$
+SELECT CASE
+           WHEN progress = 100 THEN
+               brackets.beaten_score
+           WHEN progress < requirement THEN
+               0.0
+           ELSE
+               brackets.beaten_score * EXP(LN(scoring_formulas.partial_base) * (progress - requirement) / (100 - requirement)) / scoring_formulas.partial_divisor
+       END
+FROM scoring_formulas
+LEFT OUTER JOIN LATERAL (
+    SELECT scale * EXP(exponent * (shift - demon)) + constant AS beaten_score
+    FROM scoring_formula_brackets
+    WHERE formula = scoring_formulas.id AND lower_bound < demon AND demon <= upper_bound
+) brackets ON TRUE
+WHERE scoring_formulas.id = the_formula;
+$record_score_with$
+    LANGUAGE SQL STABLE;
+
+CREATE OR REPLACE FUNCTION record_score(progress FLOAT, demon FLOAT, list_size FLOAT, requirement FLOAT) RETURNS FLOAT AS
+$record_score$
+SELECT record_score_with((SELECT id FROM scoring_formulas WHERE active), progress, demon, list_size, requirement);
+$record_score$
+    LANGUAGE SQL STABLE;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP FUNCTION ratelimit_check(TEXT, TEXT, INTEGER, DOUBLE PRECISION);
+DROP TABLE ratelimit_buckets;
@@ -0,0 +1,64 @@
+-- Your SQL goes here
+
+-- Buckets for ratelimits generated by the ratelimits! macro when using RatelimitStorage::Postgres. Each bucket
+-- is tracked via the "generic cell rate algorithm", for which storing the theoretical arrival time of the next
+-- request suffices. Unkeyed limiters use the empty string as key.
+CREATE TABLE ratelimit_buckets (
+    limiter TEXT NOT NULL,
+    key TEXT NOT NULL,
+    theoretical_arrival TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    PRIMARY KEY (limiter, key)
+);
+
+CREATE INDEX ratelimit_buckets_theoretical_arrival_idx ON ratelimit_buckets(theoretical_arrival);
+
+-- Tries to take a token from the given bucket of a limiter allowing `capacity` requests per `period` seconds.
+-- Returns the number of further requests that would be allowed right now, and the number of seconds until the bucket is
+-- full again. If the request is not allowed, retry_after is the number of seconds until the next request will be
+-- allowed, and NULL otherwise.
+--
+-- Buckets whose theoretical arrival time has passed are full again, meaning they behave exactly like buckets that
+-- do not exist. Each call deletes a few of them (skipping any currently locked by concurrent calls), so the table only
+-- ever holds buckets of recently limited clients.
+CREATE OR REPLACE FUNCTION ratelimit_check(the_limiter TEXT, the_key TEXT, capacity INTEGER, period DOUBLE PRECISION,
+                                           OUT remaining INTEGER, OUT reset DOUBLE PRECISION, OUT retry_after DOUBLE PRECISION) AS
+$ratelimit_check$
+DECLARE
+    emission_interval INTERVAL := make_interval(secs => period / capacity);
+    burst_period INTERVAL := make_interval(secs => period);
+    now_utc TIMESTAMP := NOW() AT TIME ZONE 'utc';
+    arrival TIMESTAMP;
+BEGIN
+    DELETE FROM ratelimit_buckets
+    WHERE (limiter, key) IN (
+        SELECT limiter, key FROM ratelimit_buckets
+        WHERE theoretical_arrival < now_utc
+        LIMIT 10
+        FOR UPDATE SKIP LOCKED
+    );
+
+    INSERT INTO ratelimit_buckets (limiter, key, theoretical_arrival) VALUES (the_limiter, the_key, now_utc) ON CONFLICT DO NOTHING;
+
+    SELECT GREATEST(theoretical_arrival, now_utc) INTO arrival
+    FROM ratelimit_buckets
+    WHERE limiter = the_limiter AND key = the_key
+    FOR UPDATE;
+
+    IF arrival + emission_interval - now_utc > burst_period THEN
+        remaining := 0;
+        reset := EXTRACT(EPOCH FROM (arrival - now_utc));
+        retry_after := EXTRACT(EPOCH FROM (arrival + emission_interval - burst_period - now_utc));
+
+        RETURN;
+    END IF;
+
+    arrival := arrival + emission_interval;
+
+    UPDATE ratelimit_buckets SET theoretical_arrival = arrival WHERE limiter = the_limiter AND key = the_key;
+
+    remaining := FLOOR(EXTRACT(EPOCH FROM (burst_period - (arrival - now_utc))) / EXTRACT(EPOCH FROM emission_interval));
+    reset := EXTRACT(EPOCH FROM (arrival - now_utc));
+    retry_after := NULL;
+END;
+$ratelimit_check$
+    LANGUAGE plpgsql;
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+
+DROP TRIGGER member_roles_sync_legacy_permissions ON member_roles;
+DROP FUNCTION sync_legacy_permissions();
+DROP FUNCTION roles_of(INTEGER);
+DROP TABLE member_roles;
+DROP TABLE role_assignments;
+DROP TABLE role_implications;
+DROP TABLE roles;
@@ -0,0 +1,94 @@
+-- Your SQL goes here
+
+-- Roles replace the fixed 16-bit permission bitmask. A user can hold any number of roles, and roles grant other
+-- roles via role_implications. Which roles holders of a role may hand out to other users is given by role_assignments.
+CREATE TABLE roles (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    -- The bit this role occupied in the legacy members.permissions bitmask, if any. The bitmask is kept in sync
+    -- with member_roles so that API consumers and filters relying on it keep working
+    legacy_bit INTEGER UNIQUE CHECK (legacy_bit > 0 AND legacy_bit < 65536 AND legacy_bit & (legacy_bit - 1) = 0),
+    -- Whether pointercrate itself checks for this role (e.g. via the ADMINISTRATOR permission). Such roles cannot be deleted
+    builtin BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE role_implications (
+    role INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    implied INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    PRIMARY KEY (role, implied),
+    CHECK (role <> implied)
+);
+
+CREATE TABLE role_assignments (
+    role INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    assignable INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    PRIMARY KEY (role, assignable)
+);
+
+CREATE TABLE member_roles (
+    member INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    role INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    PRIMARY KEY (member, role)
+);
+
+INSERT INTO roles (name, legacy_bit, builtin) VALUES
+    ('List Helper', 2, TRUE),
+    ('List Moderator', 4, TRUE),
+    ('List Administrator', 8, TRUE),
+    ('Moderator', 8192, TRUE),
+    ('Administrator', 16384, TRUE);
+
+-- Any other bit that is set for some user gets a placeholder role, so that no permissions are lost
+INSERT INTO roles (name, legacy_bit)
+SELECT DISTINCT 'Legacy permission 0x' || to_hex(1 << i), 1 << i
+FROM members, generate_series(0, 15) AS i
+WHERE permissions::INTEGER & (1 << i) <> 0 AND (1 << i) NOT IN (SELECT legacy_bit FROM roles);
+
+INSERT INTO member_roles (member, role)
+SELECT member_id, roles.id
+FROM members
+INNER JOIN roles ON permissions::INTEGER & roles.legacy_bit <> 0;
+
+-- The implication and assignment rules pointercrate used to have compiled into its binary
+INSERT INTO role_implications (role, implied)
+SELECT r.id, i.id
+FROM roles r, roles i
+WHERE (r.name, i.name) IN (('Administrator', 'Moderator'), ('List Administrator', 'List Moderator'), ('List Moderator', 'List Helper'));
+
+INSERT INTO role_assignments (role, assignable)
+SELECT r.id, a.id
+FROM roles r, roles a
+WHERE (r.name, a.name) IN (('Administrator', 'Moderator'), ('Administrator', 'List Administrator'), ('List Administrator', 'List Moderator'), ('List Administrator', 'List Helper'));
+
+CREATE OR REPLACE FUNCTION roles_of(the_member INTEGER) RETURNS TEXT[] AS
+$roles_of$
+SELECT ARRAY(SELECT roles.name FROM member_roles INNER JOIN roles ON roles.id = member_roles.role WHERE member_roles.member = the_member ORDER BY roles.id);
+$roles_of$
+    LANGUAGE SQL STABLE;
+
+CREATE OR REPLACE FUNCTION sync_legacy_permissions() RETURNS TRIGGER AS
+$sync_legacy_permissions$
+DECLARE
+    the_member INTEGER;
+BEGIN
+    IF (TG_OP = 'DELETE') THEN
+        the_member = OLD.member;
+    ELSE
+        the_member = NEW.member;
+    END IF;
+
+    UPDATE members
+    SET permissions = (
+        SELECT COALESCE(BIT_OR(roles.legacy_bit), 0)
+        FROM member_roles
+        INNER JOIN roles ON roles.id = member_roles.role
+        WHERE member_roles.member = the_member
+    )::BIT(16)
+    WHERE member_id = the_member;
+
+    RETURN NULL;
+END;
+$sync_legacy_permissions$
+    LANGUAGE plpgsql;
+
+CREATE TRIGGER member_roles_sync_legacy_permissions AFTER INSERT OR UPDATE OR DELETE ON member_roles FOR EACH ROW EXECUTE PROCEDURE sync_legacy_permissions();
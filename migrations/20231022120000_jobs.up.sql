@@ -0,0 +1,21 @@
+-- Your SQL goes here
+
+-- Durable background jobs, processed by the worker pool in pointercrate_core::job. A job is 'pending' until a worker
+-- claims it, which moves it to 'running' until `locked_until`. Should the worker die before finishing the job, it
+-- becomes claimable again once that lock expires. Failed jobs go back to 'pending' with their `run_at` pushed back,
+-- until `max_attempts` is exhausted, at which point they are moved to 'dead' and only retried manually.
+CREATE TABLE jobs (
+    id SERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    state TEXT NOT NULL DEFAULT 'pending' CHECK (state IN ('pending', 'running', 'succeeded', 'dead')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL CHECK (max_attempts > 0),
+    run_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    locked_until TIMESTAMP WITHOUT TIME ZONE,
+    last_error TEXT,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    finished_at TIMESTAMP WITHOUT TIME ZONE
+);
+
+CREATE INDEX jobs_claimable ON jobs (kind, run_at) WHERE state IN ('pending', 'running');
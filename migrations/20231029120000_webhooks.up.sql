@@ -0,0 +1,31 @@
+-- Your SQL goes here
+
+-- Outbound webhooks. Each webhook receives a signed POST request for every event it subscribes to. The secret is used
+-- as the key of the HMAC-SHA256 signature of each request body. Webhooks in the 'discord' format receive Discord
+-- webhook messages instead of the raw event.
+CREATE TABLE webhooks (
+    id SERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL DEFAULT replace(gen_random_uuid()::text || gen_random_uuid()::text, '-', ''),
+    events TEXT[] NOT NULL,
+    format TEXT NOT NULL DEFAULT 'signed' CHECK (format IN ('signed', 'discord')),
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+-- The delivery log. Each delivery is carried out (and retried) by a background job.
+CREATE TABLE webhook_deliveries (
+    id SERIAL PRIMARY KEY,
+    webhook INTEGER NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- The HTTP status code of the response to the latest attempt, NULL if no response was received
+    response_status SMALLINT,
+    -- The (truncated) response body of the latest attempt, or the reason why it failed
+    response TEXT,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    delivered_at TIMESTAMP WITHOUT TIME ZONE
+);
+
+CREATE INDEX webhook_deliveries_webhook ON webhook_deliveries (webhook);
@@ -0,0 +1,202 @@
+-- This file should undo anything in `up.sql`
+
+-- Only the default list survives. Demons on other lists (and everything attached to them) are lost.
+DELETE FROM records USING demons, lists WHERE records.demon = demons.id AND demons.list = lists.id AND NOT lists.is_default;
+DELETE FROM creators USING demons, lists WHERE creators.demon = demons.id AND demons.list = lists.id AND NOT lists.is_default;
+DELETE FROM demons USING lists WHERE demons.list = lists.id AND NOT lists.is_default;
+
+DROP FUNCTION list_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+
+CREATE FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT name, CASE WHEN t.position IS NULL THEN demons.position ELSE t.position END, requirement, video, thumbnail, verifier, publisher, demons.id, level_id, demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $1 AND position != -1
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+WHERE NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $1)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+DROP FUNCTION subdivision_ranking_of(INTEGER, VARCHAR(2));
+
+CREATE FUNCTION subdivision_ranking_of(country VARCHAR(2))
+    RETURNS TABLE (
+        rank BIGINT,
+        score FLOAT,
+        subdivision_code VARCHAR(3),
+        name TEXT
+    )
+AS
+    $body$
+    SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           iso_code,
+           name
+    FROM (
+        SELECT iso_code, name,
+                SUM(record_score(pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT,
+                                 100::FLOAT, pseudo_records.requirement)) as total_score
+         FROM (
+                  select distinct on (iso_code, demon)
+                      iso_code,
+                      subdivisions.name,
+                      progress,
+                      position,
+                      CASE WHEN demons.position > 75 THEN 100 ELSE requirement END AS requirement
+                  from (
+                           select demon, player, progress
+                           from records
+                           where status_='APPROVED'
+
+                           union
+
+                           select id, verifier, 100
+                           from demons
+                       ) records
+                           inner join demons
+                                      on demons.id = records.demon
+                           inner join players
+                                      on players.id=records.player
+                           inner join subdivisions
+                                      on (iso_code=players.subdivision and players.nationality = nation)
+                  where position <= 150 and not players.banned and nation = country
+                  order by iso_code, demon, progress desc
+              ) AS pseudo_records
+         GROUP BY iso_code, name
+     ) scores;
+    $body$
+LANGUAGE SQL;
+
+DROP VIEW nations_with_score;
+
+CREATE VIEW nations_with_score AS
+    SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           nationalities.iso_country_code,
+           nationalities.nation,
+           nationalities.continent
+    FROM (
+          SELECT nationality,
+                 SUM(record_score(pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT,
+                                  100::FLOAT, pseudo_records.requirement)) as total_score
+          FROM (
+                   select distinct on (nationality, demon)
+                       nationality,
+                       progress,
+                       position,
+                       CASE WHEN demons.position > 75 THEN 100 ELSE requirement END AS requirement
+                   from (
+                       select demon, player, progress
+                       from records
+                       where status_='APPROVED'
+
+                       union
+
+                       select id, verifier, 100
+                       from demons
+                   ) records
+                       inner join demons
+                           on demons.id = records.demon
+                       inner join players
+                           on players.id=records.player
+                       inner join nationalities
+                           on iso_country_code=players.nationality
+                   where position <= 150 and not players.banned
+                   order by nationality, demon, progress desc
+               ) AS pseudo_records
+          GROUP BY nationality
+   ) scores
+INNER JOIN nationalities
+        ON nationalities.iso_country_code = scores.nationality;
+
+DROP VIEW players_with_score;
+
+CREATE VIEW players_with_score AS
+SELECT players.id,
+       players.name,
+       RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent
+FROM
+    (
+        SELECT pseudo_records.player,
+               SUM(record_score(pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)) as total_score
+        FROM (
+                 SELECT player,
+                        progress,
+                        position,
+                        CASE WHEN demons.position > 75 THEN 100 ELSE requirement END AS requirement
+                 FROM records
+                          INNER JOIN demons
+                                     ON demons.id = demon
+                 WHERE demons.position <= 150 AND status_ = 'APPROVED' AND (demons.position <= 75 OR progress = 100)
+
+                 UNION
+
+                 SELECT verifier as player,
+                        CASE WHEN demons.position > 150 THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                        position,
+                        100.0::FLOAT
+                 FROM demons
+
+                 UNION
+
+                 SELECT publisher as player,
+                        0.0::FLOAT as progress,
+                        position,
+                        100.0::FLOAT
+                 FROM demons
+
+                 UNION
+
+                 SELECT creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT
+                 FROM creators
+             ) AS pseudo_records
+        GROUP BY player
+    ) scores
+        INNER JOIN players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+
+ALTER TABLE scoring_formulas ADD COLUMN active BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE scoring_formulas SET active = TRUE WHERE id = (SELECT scoring_formula FROM lists WHERE is_default);
+CREATE UNIQUE INDEX scoring_formulas_one_active ON scoring_formulas (active) WHERE active;
+
+CREATE OR REPLACE FUNCTION record_score(progress FLOAT, demon FLOAT, list_size FLOAT, requirement FLOAT) RETURNS FLOAT AS
+$record_score$
+SELECT record_score_with((SELECT id FROM scoring_formulas WHERE active), progress, demon, list_size, requirement);
+$record_score$
+    LANGUAGE SQL STABLE;
+
+ALTER TABLE demons DROP CONSTRAINT unique_position;
+ALTER TABLE demons ADD CONSTRAINT unique_position UNIQUE (position) DEFERRABLE INITIALLY IMMEDIATE;
+ALTER TABLE demons DROP COLUMN list;
+
+DROP TABLE lists;
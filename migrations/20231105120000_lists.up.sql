@@ -0,0 +1,241 @@
+-- Your SQL goes here
+
+-- Every demon belongs to exactly one list. Positions, list sizes, the scoring formula and all rankings
+-- are scoped to a list. The default list is the one used whenever a request does not explicitly select
+-- a list.
+CREATE TABLE lists (
+    id SERIAL PRIMARY KEY,
+    slug TEXT NOT NULL UNIQUE CHECK (slug ~ '^[a-z0-9]+(-[a-z0-9]+)*$'),
+    name TEXT NOT NULL,
+    list_size SMALLINT NOT NULL CHECK (list_size > 0),
+    extended_list_size SMALLINT NOT NULL,
+    scoring_formula INTEGER NOT NULL REFERENCES scoring_formulas(id),
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    CHECK (list_size <= extended_list_size)
+);
+
+CREATE UNIQUE INDEX lists_one_default ON lists (is_default) WHERE is_default;
+
+-- All existing data becomes the default list. Its sizes are the ones the ranking views used to hardcode.
+INSERT INTO lists (slug, name, list_size, extended_list_size, scoring_formula, is_default)
+SELECT 'demonlist', 'Demonlist', 75, 150, id, TRUE FROM scoring_formulas WHERE active;
+
+ALTER TABLE demons ADD COLUMN list INTEGER REFERENCES lists(id);
+UPDATE demons SET list = (SELECT id FROM lists WHERE is_default);
+ALTER TABLE demons ALTER COLUMN list SET NOT NULL;
+
+ALTER TABLE demons DROP CONSTRAINT unique_position;
+ALTER TABLE demons ADD CONSTRAINT unique_position UNIQUE (list, position) DEFERRABLE INITIALLY IMMEDIATE;
+
+-- The formula in use is now a property of each list
+CREATE OR REPLACE FUNCTION record_score(progress FLOAT, demon FLOAT, list_size FLOAT, requirement FLOAT) RETURNS FLOAT AS
+$record_score$
+SELECT record_score_with((SELECT scoring_formula FROM lists WHERE is_default), progress, demon, list_size, requirement);
+$record_score$
+    LANGUAGE SQL STABLE;
+
+DROP INDEX scoring_formulas_one_active;
+ALTER TABLE scoring_formulas DROP COLUMN active;
+
+DROP VIEW players_with_score;
+
+CREATE VIEW players_with_score AS
+SELECT players.id,
+       players.name,
+       RANK() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent,
+       scores.list
+FROM
+    (
+        SELECT pseudo_records.list,
+               pseudo_records.player,
+               SUM(record_score_with(lists.scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)) as total_score
+        FROM (
+                 SELECT demons.list,
+                        player,
+                        progress,
+                        position,
+                        CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement
+                 FROM records
+                          INNER JOIN demons
+                                     ON demons.id = demon
+                          INNER JOIN lists
+                                     ON lists.id = demons.list
+                 WHERE demons.position <= lists.extended_list_size AND status_ = 'APPROVED' AND (demons.position <= lists.list_size OR progress = 100)
+
+                 UNION
+
+                 SELECT demons.list,
+                        verifier as player,
+                        CASE WHEN demons.position > lists.extended_list_size THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                        position,
+                        100.0::FLOAT
+                 FROM demons
+                          INNER JOIN lists
+                                     ON lists.id = demons.list
+
+                 UNION
+
+                 SELECT list,
+                        publisher as player,
+                        0.0::FLOAT as progress,
+                        position,
+                        100.0::FLOAT
+                 FROM demons
+
+                 UNION
+
+                 SELECT demons.list,
+                        creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT
+                 FROM creators
+                          INNER JOIN demons
+                                     ON demons.id = creators.demon
+             ) AS pseudo_records
+                 INNER JOIN lists
+                            ON lists.id = pseudo_records.list
+        GROUP BY pseudo_records.list, lists.scoring_formula, pseudo_records.player
+    ) scores
+        INNER JOIN players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+
+DROP VIEW nations_with_score;
+
+CREATE VIEW nations_with_score AS
+    SELECT RANK() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           nationalities.iso_country_code,
+           nationalities.nation,
+           nationalities.continent,
+           scores.list
+    FROM (
+          SELECT list,
+                 nationality,
+                 SUM(record_score_with(scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT,
+                                       100::FLOAT, pseudo_records.requirement)) as total_score
+          FROM (
+                   select distinct on (nationality, demon)
+                       demons.list,
+                       lists.scoring_formula,
+                       nationality,
+                       progress,
+                       position,
+                       CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement
+                   from (
+                       select demon, player, progress
+                       from records
+                       where status_='APPROVED'
+
+                       union
+
+                       select id, verifier, 100
+                       from demons
+                   ) records
+                       inner join demons
+                           on demons.id = records.demon
+                       inner join lists
+                           on lists.id = demons.list
+                       inner join players
+                           on players.id=records.player
+                       inner join nationalities
+                           on iso_country_code=players.nationality
+                   where position <= lists.extended_list_size and not players.banned
+                   order by nationality, demon, progress desc
+               ) AS pseudo_records
+          GROUP BY list, scoring_formula, nationality
+   ) scores
+INNER JOIN nationalities
+        ON nationalities.iso_country_code = scores.nationality;
+
+DROP FUNCTION subdivision_ranking_of(VARCHAR(2));
+
+CREATE FUNCTION subdivision_ranking_of(the_list INTEGER, country VARCHAR(2))
+    RETURNS TABLE (
+        rank BIGINT,
+        score FLOAT,
+        subdivision_code VARCHAR(3),
+        name TEXT
+    )
+AS
+    $body$
+    SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           iso_code,
+           name
+    FROM (
+        SELECT iso_code, name,
+                SUM(record_score_with((SELECT scoring_formula FROM lists WHERE id = the_list), pseudo_records.progress::FLOAT,
+                                      pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)) as total_score
+         FROM (
+                  select distinct on (iso_code, demon)
+                      iso_code,
+                      subdivisions.name,
+                      progress,
+                      position,
+                      CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement
+                  from (
+                           select demon, player, progress
+                           from records
+                           where status_='APPROVED'
+
+                           union
+
+                           select id, verifier, 100
+                           from demons
+                       ) records
+                           inner join demons
+                                      on demons.id = records.demon
+                           inner join lists
+                                      on lists.id = demons.list
+                           inner join players
+                                      on players.id=records.player
+                           inner join subdivisions
+                                      on (iso_code=players.subdivision and players.nationality = nation)
+                  where demons.list = the_list and position <= lists.extended_list_size and not players.banned and nation = country
+                  order by iso_code, demon, progress desc
+              ) AS pseudo_records
+         GROUP BY iso_code, name
+     ) scores;
+    $body$
+LANGUAGE SQL;
+
+DROP FUNCTION list_at(TIMESTAMP WITHOUT TIME ZONE);
+
+CREATE FUNCTION list_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT name, CASE WHEN t.position IS NULL THEN demons.position ELSE t.position END, requirement, video, thumbnail, verifier, publisher, demons.id, level_id, demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $2 AND position != -1
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+WHERE demons.list = $1 AND NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $2)
+$$
+    LANGUAGE SQL
+    STABLE;
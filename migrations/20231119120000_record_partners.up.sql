@@ -0,0 +1,265 @@
+-- Your SQL goes here
+
+-- Records on levels played by several players at once (such as 2-player levels) credit all of them. The record's
+-- `player` is the player it was submitted for, all other credited players are the record's partners.
+CREATE TABLE record_partners (
+    record INTEGER NOT NULL REFERENCES records(id) ON DELETE CASCADE,
+    player INTEGER NOT NULL REFERENCES players(id),
+    PRIMARY KEY (record, player)
+);
+
+CREATE INDEX record_partners_player ON record_partners(player);
+
+CREATE TABLE record_partner_additions (
+    record INTEGER NOT NULL,
+    player INTEGER NOT NULL
+) INHERITS (audit_log2);
+
+CREATE FUNCTION audit_record_partner_addition() RETURNS trigger AS $audit_record_partner_addition$
+    BEGIN
+        INSERT INTO record_partner_additions (userid, record, player)
+            (SELECT id, NEW.record, NEW.player
+            FROM active_user LIMIT 1);
+
+        RETURN NEW;
+    END;
+$audit_record_partner_addition$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_partner_addition_trigger AFTER INSERT ON record_partners FOR EACH ROW EXECUTE PROCEDURE audit_record_partner_addition();
+
+CREATE TABLE record_partner_deletions (
+    record INTEGER NOT NULL,
+    player INTEGER NOT NULL
+) INHERITS (audit_log2);
+
+CREATE FUNCTION audit_record_partner_deletion() RETURNS trigger AS $audit_record_partner_deletion$
+    BEGIN
+        INSERT INTO record_partner_deletions (userid, record, player)
+            (SELECT id, OLD.record, OLD.player
+            FROM active_user LIMIT 1);
+
+        RETURN NULL;
+    END;
+$audit_record_partner_deletion$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_partner_deletion_trigger AFTER DELETE ON record_partners FOR EACH ROW EXECUTE PROCEDURE audit_record_partner_deletion();
+
+-- Every player credited with a record, together with the number of players sharing that record
+CREATE VIEW record_holders AS
+SELECT record, player, COUNT(*) OVER (PARTITION BY record) AS holder_count
+FROM (
+    SELECT id AS record, player FROM records
+
+    UNION
+
+    SELECT record, player FROM record_partners
+) holders;
+
+-- Whether every player credited with a record gets its full score, or whether the score is divided between them
+ALTER TABLE scoring_formulas ADD COLUMN divide_shared_credit BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE FUNCTION credit_share(the_formula INTEGER, holder_count BIGINT) RETURNS FLOAT AS
+$credit_share$
+SELECT CASE WHEN divide_shared_credit THEN 1.0 / holder_count ELSE 1.0 END FROM scoring_formulas WHERE id = the_formula;
+$credit_share$
+    LANGUAGE SQL STABLE;
+
+DROP VIEW players_with_score;
+
+CREATE VIEW players_with_score AS
+SELECT players.id,
+       players.name,
+       RANK() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent,
+       scores.list
+FROM
+    (
+        SELECT pseudo_records.list,
+               pseudo_records.player,
+               SUM(record_score_with(lists.scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)
+                   * time_factor(lists.scoring_formula, pseudo_records.demon, pseudo_records.completion_time)
+                   * credit_share(lists.scoring_formula, pseudo_records.holder_count)) as total_score
+        FROM (
+                 SELECT demons.list,
+                        demons.id AS demon,
+                        record_holders.player,
+                        progress,
+                        position,
+                        CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement,
+                        completion_time,
+                        record_holders.holder_count
+                 FROM records
+                          INNER JOIN record_holders
+                                     ON record_holders.record = records.id
+                          INNER JOIN demons
+                                     ON demons.id = demon
+                          INNER JOIN lists
+                                     ON lists.id = demons.list
+                 WHERE demons.position <= lists.extended_list_size AND status_ = 'APPROVED' AND (demons.position <= lists.list_size OR progress = 100)
+
+                 UNION
+
+                 SELECT demons.list,
+                        NULL,
+                        verifier as player,
+                        CASE WHEN demons.position > lists.extended_list_size THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                        position,
+                        100.0::FLOAT,
+                        NULL,
+                        1
+                 FROM demons
+                          INNER JOIN lists
+                                     ON lists.id = demons.list
+
+                 UNION
+
+                 SELECT list,
+                        NULL,
+                        publisher as player,
+                        0.0::FLOAT as progress,
+                        position,
+                        100.0::FLOAT,
+                        NULL,
+                        1
+                 FROM demons
+
+                 UNION
+
+                 SELECT demons.list,
+                        NULL,
+                        creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT,
+                        NULL,
+                        1
+                 FROM creators
+                          INNER JOIN demons
+                                     ON demons.id = creators.demon
+             ) AS pseudo_records
+                 INNER JOIN lists
+                            ON lists.id = pseudo_records.list
+        GROUP BY pseudo_records.list, lists.scoring_formula, pseudo_records.player
+    ) scores
+        INNER JOIN players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+
+DROP VIEW nations_with_score;
+
+CREATE VIEW nations_with_score AS
+    SELECT RANK() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           nationalities.iso_country_code,
+           nationalities.nation,
+           nationalities.continent,
+           scores.list
+    FROM (
+          SELECT list,
+                 nationality,
+                 SUM(record_score_with(scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT,
+                                       100::FLOAT, pseudo_records.requirement)
+                     * time_factor(scoring_formula, pseudo_records.demon, pseudo_records.completion_time)
+                     * credit_share(scoring_formula, pseudo_records.holder_count)) as total_score
+          FROM (
+                   select distinct on (nationality, demon)
+                       demons.list,
+                       lists.scoring_formula,
+                       nationality,
+                       demon,
+                       progress,
+                       position,
+                       CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement,
+                       completion_time,
+                       holder_count
+                   from (
+                       select demon, record_holders.player, progress, completion_time, record_holders.holder_count
+                       from records
+                       inner join record_holders
+                           on record_holders.record = records.id
+                       where status_='APPROVED'
+
+                       union
+
+                       select id, verifier, 100, NULL, 1
+                       from demons
+                   ) records
+                       inner join demons
+                           on demons.id = records.demon
+                       inner join lists
+                           on lists.id = demons.list
+                       inner join players
+                           on players.id=records.player
+                       inner join nationalities
+                           on iso_country_code=players.nationality
+                   where position <= lists.extended_list_size and not players.banned
+                   order by nationality, demon, progress desc, completion_time asc nulls first, holder_count asc
+               ) AS pseudo_records
+          GROUP BY list, scoring_formula, nationality
+   ) scores
+INNER JOIN nationalities
+        ON nationalities.iso_country_code = scores.nationality;
+
+CREATE OR REPLACE FUNCTION subdivision_ranking_of(the_list INTEGER, country VARCHAR(2))
+    RETURNS TABLE (
+        rank BIGINT,
+        score FLOAT,
+        subdivision_code VARCHAR(3),
+        name TEXT
+    )
+AS
+    $body$
+    SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           iso_code,
+           name
+    FROM (
+        SELECT iso_code, name,
+                SUM(record_score_with((SELECT scoring_formula FROM lists WHERE id = the_list), pseudo_records.progress::FLOAT,
+                                      pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)
+                    * time_factor((SELECT scoring_formula FROM lists WHERE id = the_list), pseudo_records.demon, pseudo_records.completion_time)
+                    * credit_share((SELECT scoring_formula FROM lists WHERE id = the_list), pseudo_records.holder_count)) as total_score
+         FROM (
+                  select distinct on (iso_code, demon)
+                      iso_code,
+                      subdivisions.name,
+                      demon,
+                      progress,
+                      position,
+                      CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement,
+                      completion_time,
+                      holder_count
+                  from (
+                           select demon, record_holders.player, progress, completion_time, record_holders.holder_count
+                           from records
+                           inner join record_holders
+                                      on record_holders.record = records.id
+                           where status_='APPROVED'
+
+                           union
+
+                           select id, verifier, 100, NULL, 1
+                           from demons
+                       ) records
+                           inner join demons
+                                      on demons.id = records.demon
+                           inner join lists
+                                      on lists.id = demons.list
+                           inner join players
+                                      on players.id=records.player
+                           inner join subdivisions
+                                      on (iso_code=players.subdivision and players.nationality = nation)
+                  where demons.list = the_list and position <= lists.extended_list_size and not players.banned and nation = country
+                  order by iso_code, demon, progress desc, completion_time asc nulls first, holder_count asc
+              ) AS pseudo_records
+         GROUP BY iso_code, name
+     ) scores;
+    $body$
+LANGUAGE SQL;
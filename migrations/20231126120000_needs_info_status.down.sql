@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+-- Postgres cannot remove values from enums. Turn records that needed more information back into regular submissions
+-- instead, so that nothing uses the value anymore.
+UPDATE records SET status_ = 'SUBMITTED' WHERE status_ = 'NEEDS_INFO';
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Records whose submitter needs to provide additional information (such as raw footage) before they can be
+-- checked. Added in a separate migration since new enum values cannot be used in the transaction adding them.
+ALTER TYPE record_status ADD VALUE 'NEEDS_INFO';
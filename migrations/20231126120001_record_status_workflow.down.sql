@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE records DROP COLUMN rejection_reason;
+
+DROP TABLE rejection_reasons;
+DROP TABLE record_status_transitions;
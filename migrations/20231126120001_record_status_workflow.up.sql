@@ -0,0 +1,27 @@
+-- Your SQL goes here
+
+-- The status changes list staff is allowed to make to a record. A record's status can only be changed from
+-- `from_status` to `to_status` if the pair is listed here.
+CREATE TABLE record_status_transitions (
+    from_status record_status NOT NULL,
+    to_status record_status NOT NULL,
+    PRIMARY KEY (from_status, to_status),
+    CHECK (from_status <> to_status)
+);
+
+-- All transitions between the previously existing states stay allowed. Records that have already been approved or
+-- rejected cannot be sent back to their submitter for more information.
+INSERT INTO record_status_transitions (from_status, to_status)
+SELECT from_status, to_status
+FROM unnest(enum_range(NULL::record_status)) AS from_status, unnest(enum_range(NULL::record_status)) AS to_status
+WHERE from_status <> to_status AND NOT (to_status = 'NEEDS_INFO' AND from_status IN ('APPROVED', 'REJECTED'));
+
+-- The reasons a record can be rejected for, managed by list administrators
+CREATE TABLE rejection_reasons (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE CHECK (name <> ''),
+    description TEXT NOT NULL DEFAULT ''
+);
+
+ALTER TABLE records ADD COLUMN rejection_reason INTEGER REFERENCES rejection_reasons(id);
+ALTER TABLE records ADD CONSTRAINT records_rejection_reason_check CHECK (rejection_reason IS NULL OR status_ = 'REJECTED');
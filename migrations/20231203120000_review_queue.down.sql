@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE completed_reviews;
+DROP TABLE record_reviews;
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+
+-- Submissions currently handed out to a list helper for review. An assignment is only valid until `expires_at`, after
+-- which the submission can be handed out to other helpers again.
+CREATE TABLE record_reviews (
+    record INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+    reviewer INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    assigned_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    expires_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
+
+CREATE INDEX record_reviews_reviewer ON record_reviews(reviewer);
+
+-- Every time list staff moved a submission out of the 'submitted' state. `assigned_at` is only set if the submission was
+-- handed out via the review queue. The record is not a foreign key, since records can be deleted.
+CREATE TABLE completed_reviews (
+    id SERIAL PRIMARY KEY,
+    record INTEGER NOT NULL,
+    reviewer INTEGER NOT NULL REFERENCES members(member_id) ON DELETE CASCADE,
+    outcome record_status NOT NULL,
+    assigned_at TIMESTAMP WITHOUT TIME ZONE,
+    completed_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX completed_reviews_completed_at ON completed_reviews(completed_at);
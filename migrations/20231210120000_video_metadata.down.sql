@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE record_video_metadata;
+DROP TYPE video_flag;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+
+-- Reasons for list helpers to take a closer look at the video of a submission
+CREATE TYPE video_flag AS ENUM ('UPLOADED_BEFORE_DEMON', 'CHANNEL_MISMATCH', 'VIDEO_UNAVAILABLE');
+
+-- Metadata about the video of a record, as reported by the video's host (usually via oEmbed). Not every host reports
+-- everything, so all metadata is optional.
+CREATE TABLE record_video_metadata (
+    record INTEGER PRIMARY KEY REFERENCES records(id) ON DELETE CASCADE,
+    title TEXT,
+    author TEXT,
+    author_url TEXT,
+    uploaded_at TIMESTAMP WITHOUT TIME ZONE,
+    -- in seconds
+    duration INTEGER,
+    flags video_flag[] NOT NULL DEFAULT '{}',
+    fetched_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
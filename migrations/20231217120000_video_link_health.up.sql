@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+-- 'UNKNOWN' means the link's host could not be reached (yet), e.g. because of an outage or rate limiting
+CREATE TYPE link_health AS ENUM ('HEALTHY', 'BROKEN', 'UNKNOWN');
+
+-- The outcome of the last periodic check of a record or demon video. Keyed by the link itself, so that changing a video
+-- automatically discards the outcome of checking the old one.
+CREATE TABLE video_link_checks (
+    video TEXT PRIMARY KEY,
+    health link_health NOT NULL,
+    last_checked TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX video_link_checks_health ON video_link_checks(health);
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+
+-- Approved records that were superseded by a record with more progress (or a faster completion time) of the same player
+-- on the same demon. The superseded record itself is deleted, so `record` and `superseded_by` are not foreign keys.
+-- `superseded_by` is NULL if the record was improved in place (e.g. by editing its progress) rather than replaced.
+-- Entries in here never count towards a player's score.
+CREATE TABLE record_progress_history (
+    id SERIAL PRIMARY KEY,
+    record INTEGER NOT NULL,
+    player INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    demon INTEGER NOT NULL REFERENCES demons(id) ON DELETE CASCADE,
+    progress SMALLINT NOT NULL,
+    completion_time INTEGER,
+    video VARCHAR(200),
+    superseded_by INTEGER,
+    superseded_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX record_progress_history_player_demon ON record_progress_history(player, demon);
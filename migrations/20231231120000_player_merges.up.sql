@@ -0,0 +1,18 @@
+-- Your SQL goes here
+
+-- Every merge of one player into another. `snapshot` holds everything the merge changed or deleted (as it was right before
+-- the merge), which is what reverting the merge restores. Neither player is a foreign key, since the merged player is
+-- deleted by the merge, and the surviving one could be merged into yet another player later on.
+CREATE TABLE player_merges (
+    id SERIAL PRIMARY KEY,
+    player INTEGER NOT NULL,
+    merged INTEGER NOT NULL,
+    merged_name CITEXT NOT NULL,
+    snapshot JSONB NOT NULL,
+    merged_by INTEGER NOT NULL, -- REFERENCES members(member_id)
+    merged_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    reverted_by INTEGER, -- REFERENCES members(member_id)
+    reverted_at TIMESTAMP WITHOUT TIME ZONE
+);
+
+CREATE INDEX player_merges_player ON player_merges(player);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP TRIGGER player_alias_trigger ON players;
+DROP FUNCTION maintain_player_aliases();
+DROP TABLE player_aliases;
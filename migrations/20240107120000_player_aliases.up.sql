@@ -0,0 +1,27 @@
+-- Your SQL goes here
+
+-- Former names of players. Aliases are looked up whenever a player is referred to by name (such as in submissions), so
+-- that referring to a player by an old name doesn't create a new player. The names of players always take precedence
+-- over aliases, and an alias is dropped as soon as some player is given that name.
+CREATE TABLE player_aliases (
+    player INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    alias CITEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX player_aliases_player ON player_aliases(player);
+
+-- Keeps the rename history of each player as aliases
+CREATE FUNCTION maintain_player_aliases() RETURNS trigger AS $maintain_player_aliases$
+    BEGIN
+        DELETE FROM player_aliases WHERE alias = NEW.name;
+
+        IF TG_OP = 'UPDATE' AND OLD.name <> NEW.name THEN
+            INSERT INTO player_aliases (player, alias) VALUES (NEW.id, OLD.name) ON CONFLICT (alias) DO NOTHING;
+        END IF;
+
+        RETURN NEW;
+    END;
+$maintain_player_aliases$ LANGUAGE plpgsql;
+
+CREATE TRIGGER player_alias_trigger AFTER INSERT OR UPDATE OF name ON players FOR EACH ROW EXECUTE PROCEDURE maintain_player_aliases();
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE players DROP COLUMN name_skeleton;
+
+DROP FUNCTION player_name_skeleton(TEXT);
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+
+-- The "skeleton" of a player name. Names with equal skeletons look (nearly) identical, even though they are different
+-- strings, for instance because they contain invisible characters, unicode lookalikes of latin letters (such as
+-- fullwidth letters) or homoglyphs from the Cyrillic and Greek alphabets. Skeletons are compared whenever a submission
+-- would create a new player, to catch would-be duplicates of existing players.
+--
+-- A skeleton is the lowercased NFKC normal form of a name, without whitespace, separators and invisible formatting
+-- characters, and with common Cyrillic and Greek homoglyphs (as well as the digits 0 and 1) replaced by the latin
+-- letters they look like.
+CREATE FUNCTION player_name_skeleton(name TEXT) RETURNS TEXT AS $$
+    SELECT translate(
+        regexp_replace(lower(normalize(name, NFKC)), '[\s_.\u00AD\u180E\u200B-\u200F\u202A-\u202E\u2060-\u2064\uFEFF-]', '', 'g'),
+        'аеорсухіјѕԁһӏԛԝквнмтαβεζηικμορτυχ01',
+        'aeopcyxijsdhlqwkbhmtabezhikmoptyxol'
+    )
+$$ LANGUAGE sql IMMUTABLE STRICT;
+
+ALTER TABLE players ADD COLUMN name_skeleton TEXT GENERATED ALWAYS AS (player_name_skeleton(name::text)) STORED;
+
+CREATE INDEX players_name_skeleton ON players(name_skeleton);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE subdivision_ranking_snapshots;
+DROP TABLE nation_ranking_snapshots;
+DROP TABLE player_ranking_snapshots;
@@ -0,0 +1,32 @@
+-- Your SQL goes here
+
+-- Daily snapshots of the player, nation and subdivision rankings of each list. Rankings are otherwise computed live from
+-- the current state of the list, so these are the only way to tell how a rank or score evolved over time.
+CREATE TABLE player_ranking_snapshots (
+    player INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    list INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    taken_on DATE NOT NULL,
+    rank BIGINT NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (player, list, taken_on)
+);
+
+CREATE TABLE nation_ranking_snapshots (
+    nation VARCHAR(2) NOT NULL REFERENCES nationalities(iso_country_code) ON DELETE CASCADE,
+    list INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    taken_on DATE NOT NULL,
+    rank BIGINT NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (nation, list, taken_on)
+);
+
+CREATE TABLE subdivision_ranking_snapshots (
+    nation VARCHAR(2) NOT NULL,
+    subdivision VARCHAR(3) NOT NULL,
+    list INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    taken_on DATE NOT NULL,
+    rank BIGINT NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+    FOREIGN KEY (subdivision, nation) REFERENCES subdivisions(iso_code, nation) ON DELETE CASCADE,
+    PRIMARY KEY (nation, subdivision, list, taken_on)
+);
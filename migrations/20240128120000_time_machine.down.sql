@@ -0,0 +1,38 @@
+-- This file should undo anything in `up.sql`
+
+DROP FUNCTION nations_with_score_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION players_with_score_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION scorable_records_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION record_partners_at(TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION records_at(TIMESTAMP WITHOUT TIME ZONE);
+
+CREATE OR REPLACE FUNCTION list_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT name, CASE WHEN t.position IS NULL THEN demons.position ELSE t.position END, requirement, video, thumbnail, verifier, publisher, demons.id, level_id, demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT DISTINCT ON (id) id, position
+    FROM demon_modifications
+    WHERE time >= $2 AND position != -1
+    ORDER BY id, time
+) t
+                         ON demons.id = t.id
+WHERE demons.list = $1 AND NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $2)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+DROP INDEX record_modifications_time;
+DROP INDEX demon_modifications_time;
@@ -0,0 +1,341 @@
+-- Your SQL goes here
+
+-- The functions below reconstruct the state of the demonlist at some point in the past from the audit log. Entries in
+-- the `*_modifications` tables hold the values of the changed columns _before_ the change, so the value of a column at
+-- some point in time is the one stored in the first modification changing it afterwards (or the current value, if it
+-- was never changed since). Deleting a record logs a modification holding all its values.
+
+CREATE INDEX demon_modifications_time ON demon_modifications(time);
+CREATE INDEX record_modifications_time ON record_modifications(time);
+
+-- Previously, only positions were reverted
+CREATE OR REPLACE FUNCTION list_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT COALESCE(changes.name, demons.name),
+       COALESCE(changes.position, demons.position),
+       COALESCE(changes.requirement, demons.requirement),
+       COALESCE(changes.video, demons.video),
+       COALESCE(changes.thumbnail, demons.thumbnail),
+       COALESCE(changes.verifier, demons.verifier),
+       COALESCE(changes.publisher, demons.publisher),
+       demons.id,
+       level_id,
+       demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT id,
+           (array_agg(name ORDER BY audit_id) FILTER (WHERE name IS NOT NULL))[1] AS name,
+           (array_agg(position ORDER BY audit_id) FILTER (WHERE position IS NOT NULL AND position != -1))[1] AS position,
+           (array_agg(requirement ORDER BY audit_id) FILTER (WHERE requirement IS NOT NULL))[1] AS requirement,
+           (array_agg(video ORDER BY audit_id) FILTER (WHERE video IS NOT NULL))[1] AS video,
+           (array_agg(thumbnail ORDER BY audit_id) FILTER (WHERE thumbnail IS NOT NULL))[1] AS thumbnail,
+           (array_agg(verifier ORDER BY audit_id) FILTER (WHERE verifier IS NOT NULL))[1] AS verifier,
+           (array_agg(publisher ORDER BY audit_id) FILTER (WHERE publisher IS NOT NULL))[1] AS publisher
+    FROM demon_modifications
+    WHERE time >= $2
+    GROUP BY id
+) changes
+                         ON demons.id = changes.id
+WHERE demons.list = $1 AND NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $2)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- All records (regardless of status) as they were at the given point in time. Completion times are not part of the
+-- audit log, they are only reverted if the faster time superseded the slower one (see `record_progress_history`).
+CREATE FUNCTION records_at(the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      id INTEGER,
+                      progress SMALLINT,
+                      video VARCHAR(200),
+                      status_ RECORD_STATUS,
+                      player INTEGER,
+                      demon INTEGER,
+                      completion_time INTEGER
+                  )
+AS $$
+SELECT ids.id,
+       COALESCE(changes.progress, records.progress),
+       COALESCE(changes.video, records.video),
+       COALESCE(changes.status_, records.status_),
+       COALESCE(changes.player, records.player),
+       COALESCE(changes.demon, records.demon),
+       COALESCE((SELECT completion_time
+                 FROM record_progress_history
+                 WHERE record_progress_history.record = ids.id AND superseded_at >= the_time
+                 ORDER BY superseded_at, record_progress_history.id
+                 LIMIT 1), records.completion_time)
+FROM (
+    SELECT id FROM records
+
+    UNION
+
+    SELECT id FROM record_deletions WHERE time >= the_time
+) ids
+         LEFT OUTER JOIN records
+                         ON records.id = ids.id
+         LEFT OUTER JOIN (
+    SELECT id,
+           (array_agg(progress ORDER BY audit_id) FILTER (WHERE progress IS NOT NULL))[1] AS progress,
+           (array_agg(video ORDER BY audit_id) FILTER (WHERE video IS NOT NULL))[1] AS video,
+           (array_agg(status_ ORDER BY audit_id) FILTER (WHERE status_ IS NOT NULL))[1] AS status_,
+           (array_agg(player ORDER BY audit_id) FILTER (WHERE player IS NOT NULL))[1] AS player,
+           (array_agg(demon ORDER BY audit_id) FILTER (WHERE demon IS NOT NULL))[1] AS demon
+    FROM record_modifications
+    WHERE time >= the_time
+    GROUP BY id
+) changes
+                         ON changes.id = ids.id
+WHERE NOT EXISTS (SELECT 1 FROM record_additions WHERE record_additions.id = ids.id AND time >= the_time)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- All (record, partner)-pairs as they were at the given point in time
+CREATE FUNCTION record_partners_at(the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      record INTEGER,
+                      player INTEGER
+                  )
+AS $$
+SELECT record, player
+FROM (
+    SELECT DISTINCT ON (record, player) record, player, added
+    FROM (
+        SELECT audit_id, record, player, TRUE AS added FROM record_partner_additions WHERE time < the_time
+
+        UNION ALL
+
+        SELECT audit_id, record, player, FALSE FROM record_partner_deletions WHERE time < the_time
+    ) events
+    ORDER BY record, player, audit_id DESC
+) latest
+WHERE added
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- Every player credited with an approved record on the given list at the given point in time, together with everything
+-- needed to score that record. Mirrors the subqueries of the `players_with_score` and `nations_with_score` views, except
+-- that the time factor is computed from the fastest completion time approved at that point in time.
+CREATE FUNCTION scorable_records_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      demon INTEGER,
+                      player INTEGER,
+                      progress SMALLINT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      completion_time INTEGER,
+                      time_factor FLOAT,
+                      holder_count BIGINT
+                  )
+AS $$
+WITH approved AS (
+    SELECT *, MIN(completion_time) OVER (PARTITION BY demon) AS fastest_time FROM records_at(the_time) WHERE status_ = 'APPROVED'
+), holders AS (
+    SELECT record, player, COUNT(*) OVER (PARTITION BY record) AS holder_count
+    FROM (
+        SELECT id AS record, player FROM approved
+
+        UNION
+
+        SELECT record, player FROM record_partners_at(the_time)
+    ) holders
+)
+SELECT demons.id,
+       holders.player,
+       approved.progress,
+       demons.position_,
+       CASE WHEN demons.position_ > lists.list_size THEN 100 ELSE demons.requirement END::SMALLINT,
+       approved.completion_time,
+       CASE
+           WHEN approved.completion_time IS NULL THEN 1.0
+           ELSE POWER(approved.fastest_time::FLOAT / approved.completion_time, scoring_formulas.time_exponent)
+       END,
+       holders.holder_count
+FROM approved
+         INNER JOIN holders
+                    ON holders.record = approved.id
+         INNER JOIN list_at(the_list, the_time) AS demons
+                    ON demons.id = approved.demon
+         INNER JOIN lists
+                    ON lists.id = the_list
+         INNER JOIN scoring_formulas
+                    ON scoring_formulas.id = lists.scoring_formula
+WHERE demons.position_ <= lists.extended_list_size
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- The `players_with_score` view restricted to the given list, as it was at the given point in time. Player nationalities
+-- and bans describe players rather than the list, so the current ones are used.
+CREATE FUNCTION players_with_score_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      id INTEGER,
+                      name CITEXT,
+                      rank BIGINT,
+                      score FLOAT,
+                      index BIGINT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      subdivision VARCHAR(3),
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+WITH demons_then AS (
+    SELECT * FROM list_at(the_list, the_time)
+)
+SELECT players.id,
+       players.name,
+       RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent,
+       the_list
+FROM
+    (
+        SELECT pseudo_records.player,
+               SUM(record_score_with(lists.scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)
+                   * pseudo_records.time_factor
+                   * credit_share(lists.scoring_formula, pseudo_records.holder_count)) as total_score
+        FROM (
+                 SELECT demon,
+                        player,
+                        progress,
+                        position_ AS position,
+                        requirement,
+                        time_factor,
+                        holder_count
+                 FROM scorable_records_at(the_list, the_time) AS records
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+                 WHERE records.position_ <= lists.list_size OR progress = 100
+
+                 UNION
+
+                 SELECT NULL,
+                        verifier as player,
+                        CASE WHEN demons_then.position_ > lists.extended_list_size THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                        position_,
+                        100.0::FLOAT,
+                        1.0,
+                        1
+                 FROM demons_then
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+
+                 UNION
+
+                 SELECT NULL,
+                        publisher as player,
+                        0.0::FLOAT as progress,
+                        position_,
+                        100.0::FLOAT,
+                        1.0,
+                        1
+                 FROM demons_then
+
+                 UNION
+
+                 SELECT NULL,
+                        creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT,
+                        1.0,
+                        1
+                 FROM creators
+                          INNER JOIN demons_then
+                                     ON demons_then.id = creators.demon
+             ) AS pseudo_records
+                 INNER JOIN lists
+                            ON lists.id = the_list
+        GROUP BY pseudo_records.player, lists.scoring_formula
+    ) scores
+        INNER JOIN players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- The `nations_with_score` view restricted to the given list, as it was at the given point in time. Player nationalities
+-- and bans describe players rather than the list, so the current ones are used.
+CREATE FUNCTION nations_with_score_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      rank BIGINT,
+                      score FLOAT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       scores.total_score AS score,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       nationalities.continent,
+       the_list
+FROM (
+      SELECT nationality,
+             SUM(record_score_with(lists.scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT,
+                                   100::FLOAT, pseudo_records.requirement)
+                 * pseudo_records.time_factor
+                 * credit_share(lists.scoring_formula, pseudo_records.holder_count)) as total_score
+      FROM (
+               select distinct on (nationality, demon)
+                   nationality,
+                   demon,
+                   progress,
+                   position,
+                   requirement,
+                   time_factor,
+                   holder_count
+               from (
+                   select demon, player, progress, position_ AS position, requirement, completion_time, time_factor, holder_count
+                   from scorable_records_at(the_list, the_time)
+
+                   union
+
+                   select demons_then.id, verifier, 100, position_,
+                          CASE WHEN position_ > lists.list_size THEN 100 ELSE demons_then.requirement END, NULL, 1.0, 1
+                   from list_at(the_list, the_time) AS demons_then
+                   inner join lists
+                       on lists.id = the_list
+                   where position_ <= lists.extended_list_size
+               ) records
+                   inner join players
+                       on players.id=records.player
+                   inner join nationalities
+                       on iso_country_code=players.nationality
+               where not players.banned
+               order by nationality, demon, progress desc, completion_time asc nulls first, holder_count asc
+           ) AS pseudo_records
+               INNER JOIN lists
+                          ON lists.id = the_list
+      GROUP BY lists.scoring_formula, nationality
+) scores
+INNER JOIN nationalities
+        ON nationalities.iso_country_code = scores.nationality;
+$$
+    LANGUAGE SQL
+    STABLE;
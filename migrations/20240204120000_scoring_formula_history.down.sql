@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP FUNCTION scoring_formula_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP TRIGGER scoring_formula_assignment_trigger ON lists;
+DROP FUNCTION record_scoring_formula_assignment();
+DROP TABLE scoring_formula_assignments;
@@ -0,0 +1,41 @@
+-- Your SQL goes here
+
+-- Which scoring formula each list used over time. `lists.scoring_formula` only holds the current one, so the history
+-- is kept up to date by a trigger on the `lists` table. A formula is in use from its `since` up to the `since` of the
+-- next assignment of the same list.
+CREATE TABLE scoring_formula_assignments (
+    list INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+    formula INTEGER NOT NULL REFERENCES scoring_formulas(id),
+    since TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    PRIMARY KEY (list, since)
+);
+
+-- When formulas were activated before lists existed is not known anymore, so the formulas lists currently use are
+-- assumed to have always been in use
+INSERT INTO scoring_formula_assignments (list, formula, since)
+SELECT id, scoring_formula, '-infinity' FROM lists;
+
+CREATE FUNCTION record_scoring_formula_assignment() RETURNS trigger AS $record_scoring_formula_assignment$
+    BEGIN
+        IF (TG_OP = 'INSERT' OR OLD.scoring_formula <> NEW.scoring_formula) THEN
+            INSERT INTO scoring_formula_assignments (list, formula) VALUES (NEW.id, NEW.scoring_formula)
+                ON CONFLICT (list, since) DO UPDATE SET formula = EXCLUDED.formula;
+        END IF;
+
+        RETURN NEW;
+    END;
+$record_scoring_formula_assignment$ LANGUAGE plpgsql;
+
+CREATE TRIGGER scoring_formula_assignment_trigger AFTER INSERT OR UPDATE OF scoring_formula ON lists FOR EACH ROW EXECUTE PROCEDURE record_scoring_formula_assignment();
+
+-- The formula the given list used at the given point in time. Before a list's first assignment, its first formula is
+-- used.
+CREATE FUNCTION scoring_formula_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE) RETURNS INTEGER AS
+$scoring_formula_at$
+SELECT formula
+FROM scoring_formula_assignments
+WHERE list = the_list
+ORDER BY since <= the_time DESC, CASE WHEN since <= the_time THEN since END DESC, since
+LIMIT 1;
+$scoring_formula_at$
+    LANGUAGE SQL STABLE;
@@ -0,0 +1,685 @@
+-- This file should undo anything in `up.sql`
+
+DROP FUNCTION subdivision_ranking_of(INTEGER, VARCHAR(2));
+DROP VIEW nations_with_score;
+DROP VIEW players_with_score;
+DROP FUNCTION subdivision_ranking_at(INTEGER, VARCHAR(2), TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION nations_with_score_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION players_with_score_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION player_ranking_with(INTEGER, TIMESTAMP WITHOUT TIME ZONE, INTEGER);
+DROP FUNCTION time_factor_with(INTEGER, FLOAT, FLOAT);
+DROP FUNCTION scorable_records_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION players_at(TIMESTAMP WITHOUT TIME ZONE);
+
+CREATE OR REPLACE FUNCTION list_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT COALESCE(changes.name, demons.name),
+       COALESCE(changes.position, demons.position),
+       COALESCE(changes.requirement, demons.requirement),
+       COALESCE(changes.video, demons.video),
+       COALESCE(changes.thumbnail, demons.thumbnail),
+       COALESCE(changes.verifier, demons.verifier),
+       COALESCE(changes.publisher, demons.publisher),
+       demons.id,
+       level_id,
+       demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT id,
+           (array_agg(name ORDER BY audit_id) FILTER (WHERE name IS NOT NULL))[1] AS name,
+           (array_agg(position ORDER BY audit_id) FILTER (WHERE position IS NOT NULL AND position != -1))[1] AS position,
+           (array_agg(requirement ORDER BY audit_id) FILTER (WHERE requirement IS NOT NULL))[1] AS requirement,
+           (array_agg(video ORDER BY audit_id) FILTER (WHERE video IS NOT NULL))[1] AS video,
+           (array_agg(thumbnail ORDER BY audit_id) FILTER (WHERE thumbnail IS NOT NULL))[1] AS thumbnail,
+           (array_agg(verifier ORDER BY audit_id) FILTER (WHERE verifier IS NOT NULL))[1] AS verifier,
+           (array_agg(publisher ORDER BY audit_id) FILTER (WHERE publisher IS NOT NULL))[1] AS publisher
+    FROM demon_modifications
+    WHERE time >= $2
+    GROUP BY id
+) changes
+                         ON demons.id = changes.id
+WHERE demons.list = $1 AND NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $2)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+CREATE OR REPLACE FUNCTION records_at(the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      id INTEGER,
+                      progress SMALLINT,
+                      video VARCHAR(200),
+                      status_ RECORD_STATUS,
+                      player INTEGER,
+                      demon INTEGER,
+                      completion_time INTEGER
+                  )
+AS $$
+SELECT ids.id,
+       COALESCE(changes.progress, records.progress),
+       COALESCE(changes.video, records.video),
+       COALESCE(changes.status_, records.status_),
+       COALESCE(changes.player, records.player),
+       COALESCE(changes.demon, records.demon),
+       COALESCE((SELECT completion_time
+                 FROM record_progress_history
+                 WHERE record_progress_history.record = ids.id AND superseded_at >= the_time
+                 ORDER BY superseded_at, record_progress_history.id
+                 LIMIT 1), records.completion_time)
+FROM (
+    SELECT id FROM records
+
+    UNION
+
+    SELECT id FROM record_deletions WHERE time >= the_time
+) ids
+         LEFT OUTER JOIN records
+                         ON records.id = ids.id
+         LEFT OUTER JOIN (
+    SELECT id,
+           (array_agg(progress ORDER BY audit_id) FILTER (WHERE progress IS NOT NULL))[1] AS progress,
+           (array_agg(video ORDER BY audit_id) FILTER (WHERE video IS NOT NULL))[1] AS video,
+           (array_agg(status_ ORDER BY audit_id) FILTER (WHERE status_ IS NOT NULL))[1] AS status_,
+           (array_agg(player ORDER BY audit_id) FILTER (WHERE player IS NOT NULL))[1] AS player,
+           (array_agg(demon ORDER BY audit_id) FILTER (WHERE demon IS NOT NULL))[1] AS demon
+    FROM record_modifications
+    WHERE time >= the_time
+    GROUP BY id
+) changes
+                         ON changes.id = ids.id
+WHERE NOT EXISTS (SELECT 1 FROM record_additions WHERE record_additions.id = ids.id AND time >= the_time)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+CREATE OR REPLACE FUNCTION record_partners_at(the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      record INTEGER,
+                      player INTEGER
+                  )
+AS $$
+SELECT record, player
+FROM (
+    SELECT DISTINCT ON (record, player) record, player, added
+    FROM (
+        SELECT audit_id, record, player, TRUE AS added FROM record_partner_additions WHERE time < the_time
+
+        UNION ALL
+
+        SELECT audit_id, record, player, FALSE FROM record_partner_deletions WHERE time < the_time
+    ) events
+    ORDER BY record, player, audit_id DESC
+) latest
+WHERE added
+$$
+    LANGUAGE SQL
+    STABLE;
+
+CREATE FUNCTION scorable_records_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      demon INTEGER,
+                      player INTEGER,
+                      progress SMALLINT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      completion_time INTEGER,
+                      time_factor FLOAT,
+                      holder_count BIGINT
+                  )
+AS $$
+WITH approved AS (
+    SELECT *, MIN(completion_time) OVER (PARTITION BY demon) AS fastest_time FROM records_at(the_time) WHERE status_ = 'APPROVED'
+), holders AS (
+    SELECT record, player, COUNT(*) OVER (PARTITION BY record) AS holder_count
+    FROM (
+        SELECT id AS record, player FROM approved
+
+        UNION
+
+        SELECT record, player FROM record_partners_at(the_time)
+    ) holders
+)
+SELECT demons.id,
+       holders.player,
+       approved.progress,
+       demons.position_,
+       CASE WHEN demons.position_ > lists.list_size THEN 100 ELSE demons.requirement END::SMALLINT,
+       approved.completion_time,
+       CASE
+           WHEN approved.completion_time IS NULL THEN 1.0
+           ELSE POWER(approved.fastest_time::FLOAT / approved.completion_time, scoring_formulas.time_exponent)
+       END,
+       holders.holder_count
+FROM approved
+         INNER JOIN holders
+                    ON holders.record = approved.id
+         INNER JOIN list_at(the_list, the_time) AS demons
+                    ON demons.id = approved.demon
+         INNER JOIN lists
+                    ON lists.id = the_list
+         INNER JOIN scoring_formulas
+                    ON scoring_formulas.id = lists.scoring_formula
+WHERE demons.position_ <= lists.extended_list_size
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- The `players_with_score` view restricted to the given list, as it was at the given point in time. Player nationalities
+-- and bans describe players rather than the list, so the current ones are used.
+CREATE FUNCTION players_with_score_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      id INTEGER,
+                      name CITEXT,
+                      rank BIGINT,
+                      score FLOAT,
+                      index BIGINT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      subdivision VARCHAR(3),
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+WITH demons_then AS (
+    SELECT * FROM list_at(the_list, the_time)
+)
+SELECT players.id,
+       players.name,
+       RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent,
+       the_list
+FROM
+    (
+        SELECT pseudo_records.player,
+               SUM(record_score_with(lists.scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)
+                   * pseudo_records.time_factor
+                   * credit_share(lists.scoring_formula, pseudo_records.holder_count)) as total_score
+        FROM (
+                 SELECT demon,
+                        player,
+                        progress,
+                        position_ AS position,
+                        requirement,
+                        time_factor,
+                        holder_count
+                 FROM scorable_records_at(the_list, the_time) AS records
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+                 WHERE records.position_ <= lists.list_size OR progress = 100
+
+                 UNION
+
+                 SELECT NULL,
+                        verifier as player,
+                        CASE WHEN demons_then.position_ > lists.extended_list_size THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                        position_,
+                        100.0::FLOAT,
+                        1.0,
+                        1
+                 FROM demons_then
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+
+                 UNION
+
+                 SELECT NULL,
+                        publisher as player,
+                        0.0::FLOAT as progress,
+                        position_,
+                        100.0::FLOAT,
+                        1.0,
+                        1
+                 FROM demons_then
+
+                 UNION
+
+                 SELECT NULL,
+                        creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT,
+                        1.0,
+                        1
+                 FROM creators
+                          INNER JOIN demons_then
+                                     ON demons_then.id = creators.demon
+             ) AS pseudo_records
+                 INNER JOIN lists
+                            ON lists.id = the_list
+        GROUP BY pseudo_records.player, lists.scoring_formula
+    ) scores
+        INNER JOIN players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- The `nations_with_score` view restricted to the given list, as it was at the given point in time. Player nationalities
+-- and bans describe players rather than the list, so the current ones are used.
+CREATE FUNCTION nations_with_score_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      rank BIGINT,
+                      score FLOAT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       scores.total_score AS score,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       nationalities.continent,
+       the_list
+FROM (
+      SELECT nationality,
+             SUM(record_score_with(lists.scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT,
+                                   100::FLOAT, pseudo_records.requirement)
+                 * pseudo_records.time_factor
+                 * credit_share(lists.scoring_formula, pseudo_records.holder_count)) as total_score
+      FROM (
+               select distinct on (nationality, demon)
+                   nationality,
+                   demon,
+                   progress,
+                   position,
+                   requirement,
+                   time_factor,
+                   holder_count
+               from (
+                   select demon, player, progress, position_ AS position, requirement, completion_time, time_factor, holder_count
+                   from scorable_records_at(the_list, the_time)
+
+                   union
+
+                   select demons_then.id, verifier, 100, position_,
+                          CASE WHEN position_ > lists.list_size THEN 100 ELSE demons_then.requirement END, NULL, 1.0, 1
+                   from list_at(the_list, the_time) AS demons_then
+                   inner join lists
+                       on lists.id = the_list
+                   where position_ <= lists.extended_list_size
+               ) records
+                   inner join players
+                       on players.id=records.player
+                   inner join nationalities
+                       on iso_country_code=players.nationality
+               where not players.banned
+               order by nationality, demon, progress desc, completion_time asc nulls first, holder_count asc
+           ) AS pseudo_records
+               INNER JOIN lists
+                          ON lists.id = the_list
+      GROUP BY lists.scoring_formula, nationality
+) scores
+INNER JOIN nationalities
+        ON nationalities.iso_country_code = scores.nationality;
+$$
+    LANGUAGE SQL
+    STABLE;
+
+CREATE VIEW players_with_score AS
+SELECT players.id,
+       players.name,
+       RANK() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent,
+       scores.list
+FROM
+    (
+        SELECT pseudo_records.list,
+               pseudo_records.player,
+               SUM(record_score_with(lists.scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)
+                   * time_factor(lists.scoring_formula, pseudo_records.demon, pseudo_records.completion_time)
+                   * credit_share(lists.scoring_formula, pseudo_records.holder_count)) as total_score
+        FROM (
+                 SELECT demons.list,
+                        demons.id AS demon,
+                        record_holders.player,
+                        progress,
+                        position,
+                        CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement,
+                        completion_time,
+                        record_holders.holder_count
+                 FROM records
+                          INNER JOIN record_holders
+                                     ON record_holders.record = records.id
+                          INNER JOIN demons
+                                     ON demons.id = demon
+                          INNER JOIN lists
+                                     ON lists.id = demons.list
+                 WHERE demons.position <= lists.extended_list_size AND status_ = 'APPROVED' AND (demons.position <= lists.list_size OR progress = 100)
+
+                 UNION
+
+                 SELECT demons.list,
+                        NULL,
+                        verifier as player,
+                        CASE WHEN demons.position > lists.extended_list_size THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                        position,
+                        100.0::FLOAT,
+                        NULL,
+                        1
+                 FROM demons
+                          INNER JOIN lists
+                                     ON lists.id = demons.list
+
+                 UNION
+
+                 SELECT list,
+                        NULL,
+                        publisher as player,
+                        0.0::FLOAT as progress,
+                        position,
+                        100.0::FLOAT,
+                        NULL,
+                        1
+                 FROM demons
+
+                 UNION
+
+                 SELECT demons.list,
+                        NULL,
+                        creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT,
+                        NULL,
+                        1
+                 FROM creators
+                          INNER JOIN demons
+                                     ON demons.id = creators.demon
+             ) AS pseudo_records
+                 INNER JOIN lists
+                            ON lists.id = pseudo_records.list
+        GROUP BY pseudo_records.list, lists.scoring_formula, pseudo_records.player
+    ) scores
+        INNER JOIN players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+
+CREATE VIEW nations_with_score AS
+    SELECT RANK() OVER(PARTITION BY scores.list ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           nationalities.iso_country_code,
+           nationalities.nation,
+           nationalities.continent,
+           scores.list
+    FROM (
+          SELECT list,
+                 nationality,
+                 SUM(record_score_with(scoring_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT,
+                                       100::FLOAT, pseudo_records.requirement)
+                     * time_factor(scoring_formula, pseudo_records.demon, pseudo_records.completion_time)
+                     * credit_share(scoring_formula, pseudo_records.holder_count)) as total_score
+          FROM (
+                   select distinct on (nationality, demon)
+                       demons.list,
+                       lists.scoring_formula,
+                       nationality,
+                       demon,
+                       progress,
+                       position,
+                       CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement,
+                       completion_time,
+                       holder_count
+                   from (
+                       select demon, record_holders.player, progress, completion_time, record_holders.holder_count
+                       from records
+                       inner join record_holders
+                           on record_holders.record = records.id
+                       where status_='APPROVED'
+
+                       union
+
+                       select id, verifier, 100, NULL, 1
+                       from demons
+                   ) records
+                       inner join demons
+                           on demons.id = records.demon
+                       inner join lists
+                           on lists.id = demons.list
+                       inner join players
+                           on players.id=records.player
+                       inner join nationalities
+                           on iso_country_code=players.nationality
+                   where position <= lists.extended_list_size and not players.banned
+                   order by nationality, demon, progress desc, completion_time asc nulls first, holder_count asc
+               ) AS pseudo_records
+          GROUP BY list, scoring_formula, nationality
+   ) scores
+INNER JOIN nationalities
+        ON nationalities.iso_country_code = scores.nationality;
+
+CREATE FUNCTION subdivision_ranking_of(the_list INTEGER, country VARCHAR(2))
+    RETURNS TABLE (
+        rank BIGINT,
+        score FLOAT,
+        subdivision_code VARCHAR(3),
+        name TEXT
+    )
+AS
+    $body$
+    SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           iso_code,
+           name
+    FROM (
+        SELECT iso_code, name,
+                SUM(record_score_with((SELECT scoring_formula FROM lists WHERE id = the_list), pseudo_records.progress::FLOAT,
+                                      pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)
+                    * time_factor((SELECT scoring_formula FROM lists WHERE id = the_list), pseudo_records.demon, pseudo_records.completion_time)
+                    * credit_share((SELECT scoring_formula FROM lists WHERE id = the_list), pseudo_records.holder_count)) as total_score
+         FROM (
+                  select distinct on (iso_code, demon)
+                      iso_code,
+                      subdivisions.name,
+                      demon,
+                      progress,
+                      position,
+                      CASE WHEN demons.position > lists.list_size THEN 100 ELSE requirement END AS requirement,
+                      completion_time,
+                      holder_count
+                  from (
+                           select demon, record_holders.player, progress, completion_time, record_holders.holder_count
+                           from records
+                           inner join record_holders
+                                      on record_holders.record = records.id
+                           where status_='APPROVED'
+
+                           union
+
+                           select id, verifier, 100, NULL, 1
+                           from demons
+                       ) records
+                           inner join demons
+                                      on demons.id = records.demon
+                           inner join lists
+                                      on lists.id = demons.list
+                           inner join players
+                                      on players.id=records.player
+                           inner join subdivisions
+                                      on (iso_code=players.subdivision and players.nationality = nation)
+                  where demons.list = the_list and position <= lists.extended_list_size and not players.banned and nation = country
+                  order by iso_code, demon, progress desc, completion_time asc nulls first, holder_count asc
+              ) AS pseudo_records
+         GROUP BY iso_code, name
+     ) scores;
+    $body$
+LANGUAGE SQL;
+
+DROP INDEX record_progress_history_record;
+DROP INDEX record_partner_deletions_time;
+DROP INDEX record_partner_additions_time;
+DROP INDEX player_deletions_time;
+DROP INDEX player_modifications_time;
+DROP INDEX player_additions_time;
+DROP INDEX record_deletions_time;
+DROP INDEX record_additions_time;
+DROP INDEX demon_additions_time;
+
+CREATE OR REPLACE FUNCTION audit_demon_modification() RETURNS trigger AS $demon_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    position_change SMALLINT;
+    requirement_change SMALLINT;
+    video_change VARCHAR(200);
+    thumbnail_change TEXT;
+    verifier_change INT;
+    publisher_change INT;
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.position <> NEW.position) THEN
+        position_change = OLD.position;
+    END IF;
+
+    IF (OLD.requirement <> NEW.requirement) THEN
+        requirement_change = OLD.requirement;
+    END IF;
+
+    IF (OLD.video <> NEW.video) THEN
+        video_change = OLD.video;
+    END IF;
+
+    IF (OLD.thumbnail <> NEW.thumbnail) THEN
+        thumbnail_change = OLD.thumbnail;
+    END IF;
+
+    IF (OLD.verifier <> NEW.verifier) THEN
+        verifier_change = OLD.verifier;
+    END IF;
+
+    IF (OLD.publisher <> NEW.publisher) THEN
+        publisher_change = OLD.publisher;
+    END IF;
+
+    INSERT INTO demon_modifications (userid, name, position, requirement, video, verifier, publisher, thumbnail, id)
+        (SELECT id, name_change, position_change, requirement_change, video_change, verifier_change, publisher_change, thumbnail_change, NEW.id
+         FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$demon_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_record_modification() RETURNS trigger AS $record_modification_trigger$
+    DECLARE
+        progress_change SMALLINT;
+        video_change VARCHAR(200);
+        status_change RECORD_STATUS;
+        player_change INT;
+        demon_change INTEGER;
+    BEGIN
+        if (OLD.progress <> NEW.progress) THEN
+            progress_change = OLD.progress;
+        END IF;
+
+        IF (OLD.video <> NEW.video) THEN
+            video_change = OLD.video;
+        END IF;
+
+        IF (OLD.status_ <> NEW.status_) THEN
+            status_change = OLD.status_;
+        END IF;
+
+        IF (OLD.player <> NEW.player) THEN
+            player_change = OLD.player;
+        END IF;
+
+        IF (OLD.demon <> NEW.demon) THEN
+            demon_change = OLD.demon;
+        END IF;
+
+        INSERT INTO record_modifications (userid, id, progress, video, status_, player, demon)
+            (SELECT id, NEW.id, progress_change, video_change, status_change, player_change, demon_change
+            FROM active_user LIMIT 1);
+
+        RETURN NEW;
+    END;
+$record_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_record_deletion() RETURNS trigger AS $record_deletion_trigger$
+    BEGIN
+        INSERT INTO record_modifications (userid, id, progress, video, status_, player, demon)
+            (SELECT id, OLD.id, OLD.progress, OLD.video, OLD.status_, OLD.player, OLD.demon
+            FROM active_user LIMIT 1);
+
+        INSERT INTO record_deletions (userid, id)
+            (SELECT id, OLD.id FROM active_user LIMIT 1);
+
+        RETURN NULL;
+    END;
+$record_deletion_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_player_modification() RETURNS trigger as $player_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    banned_change BOOLEAN;
+    nationality_change VARCHAR(2);
+    subdivision_change VARCHAR(3);
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.banned <> NEW.banned) THEN
+        banned_change = OLD.banned;
+    END IF;
+
+    IF (OLD.nationality <> NEW.nationality) THEN
+        nationality_change = OLD.nationality;
+    end if;
+
+    IF (OLD.subdivision <> NEW.subdivision) THEN
+        subdivision_change = OLD.subdivision;
+    end if;
+
+    INSERT INTO player_modifications (userid, id, name, banned, nationality, subdivision)
+        (SELECT id, NEW.id, name_change, banned_change, nationality_change, subdivision_change FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$player_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_player_deletion() RETURNS trigger AS $player_deletion_trigger$
+BEGIN
+    INSERT INTO player_modifications (userid, id, name, banned, nationality, subdivision)
+        (SELECT id, OLD.id, OLD.name, OLD.banned, OLD.nationality, OLD.subdivision
+         FROM active_user LIMIT 1);
+
+    INSERT INTO player_deletions (userid, id)
+        (SELECT id, OLD.id FROM active_user LIMIT 1);
+
+    RETURN NULL;
+END;
+$player_deletion_trigger$ LANGUAGE plpgsql;
+
+ALTER TABLE player_modifications DROP COLUMN previously_null;
+ALTER TABLE record_modifications DROP COLUMN previously_null;
+ALTER TABLE demon_modifications DROP COLUMN previously_null;
@@ -0,0 +1,699 @@
+-- Your SQL goes here
+
+-- The audit triggers below used to compare old and new values with `<>`, which is NULL (and thus not true) if either
+-- side is NULL. Changes from or to NULL were therefore never logged. Additionally, a NULL in a `*_modifications` row
+-- means "unchanged", so even if they had been logged, a change _from_ NULL could not be told apart from no change at
+-- all. `previously_null` lists the nullable columns whose old value was NULL. History logged before this migration
+-- cannot be repaired, changes from or to NULL made before it are lost.
+ALTER TABLE demon_modifications ADD COLUMN previously_null TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE record_modifications ADD COLUMN previously_null TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE player_modifications ADD COLUMN previously_null TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE OR REPLACE FUNCTION audit_demon_modification() RETURNS trigger AS $demon_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    position_change SMALLINT;
+    requirement_change SMALLINT;
+    video_change VARCHAR(200);
+    thumbnail_change TEXT;
+    verifier_change INT;
+    publisher_change INT;
+    previously_null TEXT[] = '{}';
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.position <> NEW.position) THEN
+        position_change = OLD.position;
+    END IF;
+
+    IF (OLD.requirement <> NEW.requirement) THEN
+        requirement_change = OLD.requirement;
+    END IF;
+
+    IF (OLD.video IS DISTINCT FROM NEW.video) THEN
+        video_change = OLD.video;
+
+        IF (OLD.video IS NULL) THEN
+            previously_null = array_append(previously_null, 'video');
+        END IF;
+    END IF;
+
+    IF (OLD.thumbnail <> NEW.thumbnail) THEN
+        thumbnail_change = OLD.thumbnail;
+    END IF;
+
+    IF (OLD.verifier <> NEW.verifier) THEN
+        verifier_change = OLD.verifier;
+    END IF;
+
+    IF (OLD.publisher <> NEW.publisher) THEN
+        publisher_change = OLD.publisher;
+    END IF;
+
+    INSERT INTO demon_modifications (userid, name, position, requirement, video, verifier, publisher, thumbnail, id, previously_null)
+        (SELECT id, name_change, position_change, requirement_change, video_change, verifier_change, publisher_change, thumbnail_change, NEW.id, previously_null
+         FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$demon_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_record_modification() RETURNS trigger AS $record_modification_trigger$
+    DECLARE
+        progress_change SMALLINT;
+        video_change VARCHAR(200);
+        status_change RECORD_STATUS;
+        player_change INT;
+        demon_change INTEGER;
+        previously_null TEXT[] = '{}';
+    BEGIN
+        if (OLD.progress <> NEW.progress) THEN
+            progress_change = OLD.progress;
+        END IF;
+
+        IF (OLD.video IS DISTINCT FROM NEW.video) THEN
+            video_change = OLD.video;
+
+            IF (OLD.video IS NULL) THEN
+                previously_null = array_append(previously_null, 'video');
+            END IF;
+        END IF;
+
+        IF (OLD.status_ <> NEW.status_) THEN
+            status_change = OLD.status_;
+        END IF;
+
+        IF (OLD.player <> NEW.player) THEN
+            player_change = OLD.player;
+        END IF;
+
+        IF (OLD.demon <> NEW.demon) THEN
+            demon_change = OLD.demon;
+        END IF;
+
+        INSERT INTO record_modifications (userid, id, progress, video, status_, player, demon, previously_null)
+            (SELECT id, NEW.id, progress_change, video_change, status_change, player_change, demon_change, previously_null
+            FROM active_user LIMIT 1);
+
+        RETURN NEW;
+    END;
+$record_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_record_deletion() RETURNS trigger AS $record_deletion_trigger$
+    BEGIN
+        INSERT INTO record_modifications (userid, id, progress, video, status_, player, demon, previously_null)
+            (SELECT id, OLD.id, OLD.progress, OLD.video, OLD.status_, OLD.player, OLD.demon,
+                    CASE WHEN OLD.video IS NULL THEN ARRAY['video'] ELSE '{}' END
+            FROM active_user LIMIT 1);
+
+        INSERT INTO record_deletions (userid, id)
+            (SELECT id, OLD.id FROM active_user LIMIT 1);
+
+        RETURN NULL;
+    END;
+$record_deletion_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_player_modification() RETURNS trigger as $player_modification_trigger$
+DECLARE
+    name_change CITEXT;
+    banned_change BOOLEAN;
+    nationality_change VARCHAR(2);
+    subdivision_change VARCHAR(3);
+    previously_null TEXT[] = '{}';
+BEGIN
+    IF (OLD.name <> NEW.name) THEN
+        name_change = OLD.name;
+    END IF;
+
+    IF (OLD.banned <> NEW.banned) THEN
+        banned_change = OLD.banned;
+    END IF;
+
+    IF (OLD.nationality IS DISTINCT FROM NEW.nationality) THEN
+        nationality_change = OLD.nationality;
+
+        IF (OLD.nationality IS NULL) THEN
+            previously_null = array_append(previously_null, 'nationality');
+        END IF;
+    end if;
+
+    IF (OLD.subdivision IS DISTINCT FROM NEW.subdivision) THEN
+        subdivision_change = OLD.subdivision;
+
+        IF (OLD.subdivision IS NULL) THEN
+            previously_null = array_append(previously_null, 'subdivision');
+        END IF;
+    end if;
+
+    INSERT INTO player_modifications (userid, id, name, banned, nationality, subdivision, previously_null)
+        (SELECT id, NEW.id, name_change, banned_change, nationality_change, subdivision_change, previously_null FROM active_user LIMIT 1);
+
+    RETURN NEW;
+END;
+$player_modification_trigger$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_player_deletion() RETURNS trigger AS $player_deletion_trigger$
+BEGIN
+    INSERT INTO player_modifications (userid, id, name, banned, nationality, subdivision, previously_null)
+        (SELECT id, OLD.id, OLD.name, OLD.banned, OLD.nationality, OLD.subdivision,
+                array_remove(ARRAY[CASE WHEN OLD.nationality IS NULL THEN 'nationality' END,
+                                   CASE WHEN OLD.subdivision IS NULL THEN 'subdivision' END], NULL)
+         FROM active_user LIMIT 1);
+
+    INSERT INTO player_deletions (userid, id)
+        (SELECT id, OLD.id FROM active_user LIMIT 1);
+
+    RETURN NULL;
+END;
+$player_deletion_trigger$ LANGUAGE plpgsql;
+
+CREATE INDEX demon_additions_time ON demon_additions(time);
+CREATE INDEX record_additions_time ON record_additions(time);
+CREATE INDEX record_deletions_time ON record_deletions(time);
+CREATE INDEX player_additions_time ON player_additions(time);
+CREATE INDEX player_modifications_time ON player_modifications(time);
+CREATE INDEX player_deletions_time ON player_deletions(time);
+CREATE INDEX record_partner_additions_time ON record_partner_additions(time);
+CREATE INDEX record_partner_deletions_time ON record_partner_deletions(time);
+CREATE INDEX record_progress_history_record ON record_progress_history(record, superseded_at);
+
+-- For nullable columns, `<column>_changed` tells whether the column was changed at all, in which case the value to
+-- revert to is the first one logged (which may be NULL).
+CREATE OR REPLACE FUNCTION list_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      name CITEXT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      video VARCHAR(200),
+                      thumbnail TEXT,
+                      verifier INTEGER,
+                      publisher INTEGER,
+                      id INTEGER,
+                      level_id BIGINT,
+                      current_position SMALLINT
+                  )
+AS $$
+SELECT COALESCE(changes.name, demons.name),
+       COALESCE(changes.position, demons.position),
+       COALESCE(changes.requirement, demons.requirement),
+       CASE WHEN changes.video_changed THEN changes.video ELSE demons.video END,
+       COALESCE(changes.thumbnail, demons.thumbnail),
+       COALESCE(changes.verifier, demons.verifier),
+       COALESCE(changes.publisher, demons.publisher),
+       demons.id,
+       level_id,
+       demons.position AS current_position
+FROM demons
+         LEFT OUTER JOIN (
+    SELECT id,
+           (array_agg(name ORDER BY audit_id) FILTER (WHERE name IS NOT NULL))[1] AS name,
+           (array_agg(position ORDER BY audit_id) FILTER (WHERE position IS NOT NULL AND position != -1))[1] AS position,
+           (array_agg(requirement ORDER BY audit_id) FILTER (WHERE requirement IS NOT NULL))[1] AS requirement,
+           bool_or(video IS NOT NULL OR 'video' = ANY(previously_null)) AS video_changed,
+           (array_agg(video ORDER BY audit_id) FILTER (WHERE video IS NOT NULL OR 'video' = ANY(previously_null)))[1] AS video,
+           (array_agg(thumbnail ORDER BY audit_id) FILTER (WHERE thumbnail IS NOT NULL))[1] AS thumbnail,
+           (array_agg(verifier ORDER BY audit_id) FILTER (WHERE verifier IS NOT NULL))[1] AS verifier,
+           (array_agg(publisher ORDER BY audit_id) FILTER (WHERE publisher IS NOT NULL))[1] AS publisher
+    FROM demon_modifications
+    WHERE time >= $2
+    GROUP BY id
+) changes
+                         ON demons.id = changes.id
+WHERE demons.list = $1 AND NOT EXISTS (SELECT 1 FROM demon_additions WHERE demon_additions.id = demons.id AND time >= $2)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+CREATE OR REPLACE FUNCTION records_at(the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      id INTEGER,
+                      progress SMALLINT,
+                      video VARCHAR(200),
+                      status_ RECORD_STATUS,
+                      player INTEGER,
+                      demon INTEGER,
+                      completion_time INTEGER
+                  )
+AS $$
+SELECT ids.id,
+       COALESCE(changes.progress, records.progress),
+       CASE WHEN changes.video_changed THEN changes.video ELSE records.video END,
+       COALESCE(changes.status_, records.status_),
+       COALESCE(changes.player, records.player),
+       COALESCE(changes.demon, records.demon),
+       COALESCE((SELECT completion_time
+                 FROM record_progress_history
+                 WHERE record_progress_history.record = ids.id AND superseded_at >= the_time
+                 ORDER BY superseded_at, record_progress_history.id
+                 LIMIT 1), records.completion_time)
+FROM (
+    SELECT id FROM records
+
+    UNION
+
+    SELECT id FROM record_deletions WHERE time >= the_time
+) ids
+         LEFT OUTER JOIN records
+                         ON records.id = ids.id
+         LEFT OUTER JOIN (
+    SELECT id,
+           (array_agg(progress ORDER BY audit_id) FILTER (WHERE progress IS NOT NULL))[1] AS progress,
+           bool_or(video IS NOT NULL OR 'video' = ANY(previously_null)) AS video_changed,
+           (array_agg(video ORDER BY audit_id) FILTER (WHERE video IS NOT NULL OR 'video' = ANY(previously_null)))[1] AS video,
+           (array_agg(status_ ORDER BY audit_id) FILTER (WHERE status_ IS NOT NULL))[1] AS status_,
+           (array_agg(player ORDER BY audit_id) FILTER (WHERE player IS NOT NULL))[1] AS player,
+           (array_agg(demon ORDER BY audit_id) FILTER (WHERE demon IS NOT NULL))[1] AS demon
+    FROM record_modifications
+    WHERE time >= the_time
+    GROUP BY id
+) changes
+                         ON changes.id = ids.id
+WHERE NOT EXISTS (SELECT 1 FROM record_additions WHERE record_additions.id = ids.id AND time >= the_time)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- All players (including since deleted ones) as they were at the given point in time
+CREATE FUNCTION players_at(the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      id INTEGER,
+                      name CITEXT,
+                      banned BOOLEAN,
+                      nationality VARCHAR(2),
+                      subdivision VARCHAR(3)
+                  )
+AS $$
+SELECT ids.id,
+       COALESCE(changes.name, players.name),
+       COALESCE(changes.banned, players.banned),
+       CASE WHEN changes.nationality_changed THEN changes.nationality ELSE players.nationality END,
+       CASE WHEN changes.subdivision_changed THEN changes.subdivision ELSE players.subdivision END
+FROM (
+    SELECT id FROM players
+
+    UNION
+
+    SELECT id FROM player_deletions WHERE time >= the_time
+) ids
+         LEFT OUTER JOIN players
+                         ON players.id = ids.id
+         LEFT OUTER JOIN (
+    SELECT id,
+           (array_agg(name ORDER BY audit_id) FILTER (WHERE name IS NOT NULL))[1] AS name,
+           (array_agg(banned ORDER BY audit_id) FILTER (WHERE banned IS NOT NULL))[1] AS banned,
+           bool_or(nationality IS NOT NULL OR 'nationality' = ANY(previously_null)) AS nationality_changed,
+           (array_agg(nationality ORDER BY audit_id) FILTER (WHERE nationality IS NOT NULL OR 'nationality' = ANY(previously_null)))[1] AS nationality,
+           bool_or(subdivision IS NOT NULL OR 'subdivision' = ANY(previously_null)) AS subdivision_changed,
+           (array_agg(subdivision ORDER BY audit_id) FILTER (WHERE subdivision IS NOT NULL OR 'subdivision' = ANY(previously_null)))[1] AS subdivision
+    FROM player_modifications
+    WHERE time >= the_time
+    GROUP BY id
+) changes
+                         ON changes.id = ids.id
+WHERE NOT EXISTS (SELECT 1 FROM player_additions WHERE player_additions.id = ids.id AND time >= the_time)
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- Previously, this replayed every partner addition and deletion ever logged. Instead, only the ones made since the
+-- given point in time are reverted: the first of them decides whether a pair existed back then.
+CREATE OR REPLACE FUNCTION record_partners_at(the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      record INTEGER,
+                      player INTEGER
+                  )
+AS $$
+WITH events AS (
+    SELECT audit_id, record, player, TRUE AS added FROM record_partner_additions WHERE time >= the_time
+
+    UNION ALL
+
+    SELECT audit_id, record, player, FALSE FROM record_partner_deletions WHERE time >= the_time
+)
+SELECT record, player
+FROM record_partners
+WHERE NOT EXISTS (SELECT 1 FROM events WHERE events.record = record_partners.record AND events.player = record_partners.player)
+
+UNION ALL
+
+SELECT record, player
+FROM (
+    SELECT DISTINCT ON (record, player) record, player, added
+    FROM events
+    ORDER BY record, player, audit_id
+) earliest
+WHERE NOT added
+$$
+    LANGUAGE SQL
+    STABLE;
+
+DROP VIEW players_with_score;
+DROP VIEW nations_with_score;
+DROP FUNCTION subdivision_ranking_of(INTEGER, VARCHAR(2));
+DROP FUNCTION nations_with_score_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION players_with_score_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+DROP FUNCTION scorable_records_at(INTEGER, TIMESTAMP WITHOUT TIME ZONE);
+
+-- Every player credited with an approved record on the given list at the given point in time, together with everything
+-- needed to score that record. The time factor depends on the scoring formula, so only the fastest completion time
+-- approved on the demon at that point in time is returned (see `time_factor_with`).
+CREATE FUNCTION scorable_records_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      demon INTEGER,
+                      player INTEGER,
+                      progress SMALLINT,
+                      position_ SMALLINT,
+                      requirement SMALLINT,
+                      completion_time INTEGER,
+                      fastest_time INTEGER,
+                      holder_count BIGINT
+                  )
+AS $$
+WITH approved AS (
+    SELECT *, MIN(completion_time) OVER (PARTITION BY demon) AS fastest_time FROM records_at(the_time) WHERE status_ = 'APPROVED'
+), holders AS (
+    SELECT record, player, COUNT(*) OVER (PARTITION BY record) AS holder_count
+    FROM (
+        SELECT id AS record, player FROM approved
+
+        UNION
+
+        SELECT record, player FROM record_partners_at(the_time)
+    ) holders
+)
+SELECT demons.id,
+       holders.player,
+       approved.progress,
+       demons.position_,
+       CASE WHEN demons.position_ > lists.list_size THEN 100 ELSE demons.requirement END::SMALLINT,
+       approved.completion_time,
+       approved.fastest_time,
+       holders.holder_count
+FROM approved
+         INNER JOIN holders
+                    ON holders.record = approved.id
+         INNER JOIN list_at(the_list, the_time) AS demons
+                    ON demons.id = approved.demon
+         INNER JOIN lists
+                    ON lists.id = the_list
+WHERE demons.position_ <= lists.extended_list_size
+$$
+    LANGUAGE SQL
+    STABLE;
+
+CREATE FUNCTION time_factor_with(the_formula INTEGER, fastest_time FLOAT, completion_time FLOAT) RETURNS FLOAT AS
+$time_factor_with$
+SELECT CASE
+           WHEN completion_time IS NULL THEN
+               1.0
+           ELSE
+               POWER(fastest_time / completion_time, (SELECT time_exponent FROM scoring_formulas WHERE id = the_formula))
+       END;
+$time_factor_with$
+    LANGUAGE SQL STABLE;
+
+-- The player ranking of the given list as it was at the given point in time, scored using the given formula. This is
+-- the only definition of how players are scored, everything else (the `players_with_score` view, the time machine,
+-- scoring formula previews) goes through here. Note that the list's size and extended size are not part of the audit
+-- log, so the current ones are used.
+CREATE FUNCTION player_ranking_with(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE, the_formula INTEGER)
+    RETURNS TABLE (
+                      id INTEGER,
+                      name CITEXT,
+                      rank BIGINT,
+                      score FLOAT,
+                      index BIGINT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      subdivision VARCHAR(3),
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+WITH demons_then AS (
+    SELECT * FROM list_at(the_list, the_time)
+)
+SELECT players.id,
+       players.name,
+       RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent,
+       the_list
+FROM
+    (
+        SELECT pseudo_records.player,
+               SUM(record_score_with(the_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)
+                   * time_factor_with(the_formula, pseudo_records.fastest_time, pseudo_records.completion_time)
+                   * credit_share(the_formula, pseudo_records.holder_count)) as total_score
+        FROM (
+                 SELECT demon,
+                        player,
+                        progress,
+                        position_ AS position,
+                        requirement,
+                        completion_time,
+                        fastest_time,
+                        holder_count
+                 FROM scorable_records_at(the_list, the_time) AS records
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+                 WHERE records.position_ <= lists.list_size OR progress = 100
+
+                 UNION
+
+                 SELECT NULL,
+                        verifier as player,
+                        CASE WHEN demons_then.position_ > lists.extended_list_size THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                        position_,
+                        100.0::FLOAT,
+                        NULL,
+                        NULL,
+                        1
+                 FROM demons_then
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+
+                 UNION
+
+                 SELECT NULL,
+                        publisher as player,
+                        0.0::FLOAT as progress,
+                        position_,
+                        100.0::FLOAT,
+                        NULL,
+                        NULL,
+                        1
+                 FROM demons_then
+
+                 UNION
+
+                 SELECT NULL,
+                        creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT,
+                        NULL,
+                        NULL,
+                        1
+                 FROM creators
+                          INNER JOIN demons_then
+                                     ON demons_then.id = creators.demon
+             ) AS pseudo_records
+        GROUP BY pseudo_records.player
+    ) scores
+        INNER JOIN players_at(the_time) AS players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- The player ranking of the given list as it was at the given point in time, scored using the formula the list used
+-- back then
+CREATE FUNCTION players_with_score_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      id INTEGER,
+                      name CITEXT,
+                      rank BIGINT,
+                      score FLOAT,
+                      index BIGINT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      subdivision VARCHAR(3),
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+SELECT * FROM player_ranking_with(the_list, the_time, scoring_formula_at(the_list, the_time))
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- The nation ranking of the given list as it was at the given point in time. A nation gets the score of the best record
+-- any of its players holds on each demon.
+CREATE FUNCTION nations_with_score_at(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+                      rank BIGINT,
+                      score FLOAT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       scores.total_score AS score,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       nationalities.continent,
+       the_list
+FROM (
+      SELECT nationality,
+             SUM(record_score_with(formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT,
+                                   100::FLOAT, pseudo_records.requirement)
+                 * time_factor_with(formula, pseudo_records.fastest_time, pseudo_records.completion_time)
+                 * credit_share(formula, pseudo_records.holder_count)) as total_score
+      FROM (
+               select distinct on (nationality, demon)
+                   nationality,
+                   demon,
+                   progress,
+                   position,
+                   requirement,
+                   completion_time,
+                   fastest_time,
+                   holder_count
+               from (
+                   select demon, player, progress, position_ AS position, requirement, completion_time, fastest_time, holder_count
+                   from scorable_records_at(the_list, the_time)
+
+                   union
+
+                   select demons_then.id, verifier, 100, position_,
+                          CASE WHEN position_ > lists.list_size THEN 100 ELSE demons_then.requirement END, NULL, NULL, 1
+                   from list_at(the_list, the_time) AS demons_then
+                   inner join lists
+                       on lists.id = the_list
+                   where position_ <= lists.extended_list_size
+               ) records
+                   inner join players_at(the_time) AS players
+                       on players.id=records.player
+                   inner join nationalities
+                       on iso_country_code=players.nationality
+               where not players.banned
+               order by nationality, demon, progress desc, completion_time asc nulls first, holder_count asc
+           ) AS pseudo_records
+               CROSS JOIN scoring_formula_at(the_list, the_time) AS formula
+      GROUP BY formula, nationality
+) scores
+INNER JOIN nationalities
+        ON nationalities.iso_country_code = scores.nationality;
+$$
+    LANGUAGE SQL
+    STABLE;
+
+-- The ranking of the given country's subdivisions on the given list, as it was at the given point in time
+CREATE FUNCTION subdivision_ranking_at(the_list INTEGER, country VARCHAR(2), the_time TIMESTAMP WITHOUT TIME ZONE)
+    RETURNS TABLE (
+        rank BIGINT,
+        score FLOAT,
+        subdivision_code VARCHAR(3),
+        name TEXT
+    )
+AS
+    $body$
+    SELECT RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+           scores.total_score AS score,
+           iso_code,
+           name
+    FROM (
+        SELECT iso_code, name,
+                SUM(record_score_with(formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT,
+                                      pseudo_records.requirement)
+                    * time_factor_with(formula, pseudo_records.fastest_time, pseudo_records.completion_time)
+                    * credit_share(formula, pseudo_records.holder_count)) as total_score
+         FROM (
+                  select distinct on (iso_code, demon)
+                      iso_code,
+                      subdivisions.name,
+                      demon,
+                      progress,
+                      position,
+                      requirement,
+                      completion_time,
+                      fastest_time,
+                      holder_count
+                  from (
+                           select demon, player, progress, position_ AS position, requirement, completion_time, fastest_time, holder_count
+                           from scorable_records_at(the_list, the_time)
+
+                           union
+
+                           select demons_then.id, verifier, 100, position_,
+                                  CASE WHEN position_ > lists.list_size THEN 100 ELSE demons_then.requirement END, NULL, NULL, 1
+                           from list_at(the_list, the_time) AS demons_then
+                           inner join lists
+                               on lists.id = the_list
+                           where position_ <= lists.extended_list_size
+                       ) records
+                           inner join players_at(the_time) AS players
+                                      on players.id=records.player
+                           inner join subdivisions
+                                      on (iso_code=players.subdivision and players.nationality = nation)
+                  where not players.banned and nation = country
+                  order by iso_code, demon, progress desc, completion_time asc nulls first, holder_count asc
+              ) AS pseudo_records
+                  CROSS JOIN scoring_formula_at(the_list, the_time) AS formula
+         GROUP BY formula, iso_code, name
+     ) scores;
+    $body$
+    LANGUAGE SQL
+    STABLE;
+
+-- The live rankings are the rankings as of 'infinity' rather than NOW(): the latter is the start of the current
+-- transaction, so changes made earlier in the same transaction would not be taken into account.
+CREATE VIEW players_with_score AS
+SELECT ranking.id,
+       ranking.name,
+       ranking.rank,
+       ranking.score,
+       ranking.index,
+       ranking.iso_country_code,
+       ranking.nation,
+       ranking.subdivision,
+       ranking.continent,
+       lists.id AS list
+FROM lists
+         CROSS JOIN LATERAL players_with_score_at(lists.id, 'infinity') AS ranking;
+
+CREATE VIEW nations_with_score AS
+SELECT ranking.rank,
+       ranking.score,
+       ranking.iso_country_code,
+       ranking.nation,
+       ranking.continent,
+       lists.id AS list
+FROM lists
+         CROSS JOIN LATERAL nations_with_score_at(lists.id, 'infinity') AS ranking;
+
+CREATE FUNCTION subdivision_ranking_of(the_list INTEGER, country VARCHAR(2))
+    RETURNS TABLE (
+        rank BIGINT,
+        score FLOAT,
+        subdivision_code VARCHAR(3),
+        name TEXT
+    )
+AS
+    $body$
+    SELECT * FROM subdivision_ranking_at(the_list, country, 'infinity')
+    $body$
+    LANGUAGE SQL
+    STABLE;
@@ -0,0 +1,99 @@
+-- This file should undo anything in `up.sql`
+
+CREATE OR REPLACE FUNCTION player_ranking_with(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE, the_formula INTEGER)
+    RETURNS TABLE (
+                      id INTEGER,
+                      name CITEXT,
+                      rank BIGINT,
+                      score FLOAT,
+                      index BIGINT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      subdivision VARCHAR(3),
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+WITH demons_then AS (
+    SELECT * FROM list_at(the_list, the_time)
+)
+SELECT players.id,
+       players.name,
+       RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent,
+       the_list
+FROM
+    (
+        SELECT pseudo_records.player,
+               SUM(record_score_with(the_formula, pseudo_records.progress::FLOAT, pseudo_records.position::FLOAT, 100::FLOAT, pseudo_records.requirement)
+                   * time_factor_with(the_formula, pseudo_records.fastest_time, pseudo_records.completion_time)
+                   * credit_share(the_formula, pseudo_records.holder_count)) as total_score
+        FROM (
+                 SELECT demon,
+                        player,
+                        progress,
+                        position_ AS position,
+                        requirement,
+                        completion_time,
+                        fastest_time,
+                        holder_count
+                 FROM scorable_records_at(the_list, the_time) AS records
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+                 WHERE records.position_ <= lists.list_size OR progress = 100
+
+                 UNION
+
+                 SELECT NULL,
+                        verifier as player,
+                        CASE WHEN demons_then.position_ > lists.extended_list_size THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                        position_,
+                        100.0::FLOAT,
+                        NULL,
+                        NULL,
+                        1
+                 FROM demons_then
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+
+                 UNION
+
+                 SELECT NULL,
+                        publisher as player,
+                        0.0::FLOAT as progress,
+                        position_,
+                        100.0::FLOAT,
+                        NULL,
+                        NULL,
+                        1
+                 FROM demons_then
+
+                 UNION
+
+                 SELECT NULL,
+                        creator as player,
+                        0.0::FLOAT as progress,
+                        1.0::FLOAT as position, -- doesn't matter
+                        100.0::FLOAT,
+                        NULL,
+                        NULL,
+                        1
+                 FROM creators
+                          INNER JOIN demons_then
+                                     ON demons_then.id = creators.demon
+             ) AS pseudo_records
+        GROUP BY pseudo_records.player
+    ) scores
+        INNER JOIN players_at(the_time) AS players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+$$
+    LANGUAGE SQL
+    STABLE;
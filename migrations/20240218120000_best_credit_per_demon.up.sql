@@ -0,0 +1,88 @@
+-- Your SQL goes here
+
+-- A player credited with several approved records on the same demon (e.g. with their own record and as partner on
+-- someone else's) used to be scored for all of them, unless the records happened to produce identical rows in the
+-- UNION below. Now only the best of them counts. Verifier, publisher and creator pseudo-records are unaffected.
+CREATE OR REPLACE FUNCTION player_ranking_with(the_list INTEGER, the_time TIMESTAMP WITHOUT TIME ZONE, the_formula INTEGER)
+    RETURNS TABLE (
+                      id INTEGER,
+                      name CITEXT,
+                      rank BIGINT,
+                      score FLOAT,
+                      index BIGINT,
+                      iso_country_code VARCHAR(2),
+                      nation CITEXT,
+                      subdivision VARCHAR(3),
+                      continent continent,
+                      list INTEGER
+                  )
+AS $$
+WITH demons_then AS (
+    SELECT * FROM list_at(the_list, the_time)
+)
+SELECT players.id,
+       players.name,
+       RANK() OVER(ORDER BY scores.total_score DESC) AS rank,
+       CASE WHEN scores.total_score IS NULL THEN 0.0::FLOAT ELSE scores.total_score END AS score,
+       ROW_NUMBER() OVER(ORDER BY scores.total_score DESC) AS index,
+       nationalities.iso_country_code,
+       nationalities.nation,
+       players.subdivision,
+       nationalities.continent,
+       the_list
+FROM
+    (
+        SELECT scored_records.player, SUM(scored_records.score) AS total_score
+        FROM (
+                 SELECT records.player,
+                        MAX(record_score_with(the_formula, records.progress::FLOAT, records.position_::FLOAT, 100::FLOAT, records.requirement)
+                            * time_factor_with(the_formula, records.fastest_time, records.completion_time)
+                            * credit_share(the_formula, records.holder_count)) AS score
+                 FROM scorable_records_at(the_list, the_time) AS records
+                          INNER JOIN lists
+                                     ON lists.id = the_list
+                 WHERE records.position_ <= lists.list_size OR records.progress = 100
+                 GROUP BY records.player, records.demon
+
+                 UNION ALL
+
+                 SELECT pseudo_records.player,
+                        record_score_with(the_formula, pseudo_records.progress, pseudo_records.position, 100::FLOAT, pseudo_records.requirement)
+                 FROM (
+                          SELECT verifier as player,
+                                 CASE WHEN demons_then.position_ > lists.extended_list_size THEN 0.0::FLOAT ELSE 100.0::FLOAT END as progress,
+                                 position_::FLOAT AS position,
+                                 100.0::FLOAT AS requirement
+                          FROM demons_then
+                                   INNER JOIN lists
+                                              ON lists.id = the_list
+
+                          UNION
+
+                          SELECT publisher as player,
+                                 0.0::FLOAT as progress,
+                                 position_,
+                                 100.0::FLOAT
+                          FROM demons_then
+
+                          UNION
+
+                          SELECT creator as player,
+                                 0.0::FLOAT as progress,
+                                 1.0::FLOAT as position, -- doesn't matter
+                                 100.0::FLOAT
+                          FROM creators
+                                   INNER JOIN demons_then
+                                              ON demons_then.id = creators.demon
+                      ) AS pseudo_records
+             ) AS scored_records
+        GROUP BY scored_records.player
+    ) scores
+        INNER JOIN players_at(the_time) AS players
+                   ON scores.player = players.id
+        LEFT OUTER JOIN nationalities
+                        ON players.nationality = nationalities.iso_country_code
+WHERE NOT players.banned AND players.id != 1534;
+$$
+    LANGUAGE SQL
+    STABLE;
@@ -0,0 +1,23 @@
+-- This file should undo anything in `up.sql`
+
+CREATE FUNCTION time_factor(the_formula INTEGER, the_demon INTEGER, completion_time FLOAT) RETURNS FLOAT AS
+$time_factor$
+SELECT CASE
+           WHEN completion_time IS NULL THEN
+               1.0
+           ELSE
+               POWER((SELECT MIN(records.completion_time) FROM records WHERE records.demon = the_demon AND records.status_ = 'APPROVED') / completion_time,
+                     (SELECT time_exponent FROM scoring_formulas WHERE id = the_formula))
+       END;
+$time_factor$
+    LANGUAGE SQL STABLE;
+
+CREATE VIEW record_holders AS
+SELECT record, player, COUNT(*) OVER (PARTITION BY record) AS holder_count
+FROM (
+    SELECT id AS record, player FROM records
+
+    UNION
+
+    SELECT record, player FROM record_partners
+) holders;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Scoring formula previews now go through `player_ranking_with` as well, so nothing uses these anymore
+DROP VIEW record_holders;
+DROP FUNCTION time_factor(INTEGER, INTEGER, FLOAT);
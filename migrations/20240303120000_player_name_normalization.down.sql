@@ -0,0 +1,16 @@
+-- This file should undo anything in `up.sql`
+
+CREATE OR REPLACE FUNCTION player_name_skeleton(name TEXT) RETURNS TEXT AS $$
+    SELECT translate(
+        regexp_replace(lower(normalize(name, NFKC)), '[\s_.\u00AD\u180E\u200B-\u200F\u202A-\u202E\u2060-\u2064\uFEFF-]', '', 'g'),
+        'аеорсухіјѕԁһӏԛԝквнмтαβεζηικμορτυχ01',
+        'aeopcyxijsdhlqwkbhmtabezhikmoptyxol'
+    )
+$$ LANGUAGE sql IMMUTABLE STRICT;
+
+ALTER TABLE players DROP COLUMN name_skeleton;
+ALTER TABLE players ADD COLUMN name_skeleton TEXT GENERATED ALWAYS AS (player_name_skeleton(name::text)) STORED;
+
+CREATE INDEX players_name_skeleton ON players(name_skeleton);
+
+DROP FUNCTION normalize_player_name(TEXT);
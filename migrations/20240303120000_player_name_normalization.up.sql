@@ -0,0 +1,33 @@
+-- Your SQL goes here
+
+-- The form player names are stored in: their NFKC normal form, without invisible formatting characters (such as
+-- zero-width spaces), and with each run of whitespace collapsed into a single space. This is the only place defining
+-- which characters count as invisible, both player lookups and `player_name_skeleton` go through this function.
+--
+-- NFKC already turns most unicode spaces into plain ones, the remaining ones are listed explicitly so that the result
+-- does not depend on the database's locale.
+CREATE FUNCTION normalize_player_name(name TEXT) RETURNS TEXT AS $$
+    SELECT btrim(
+        regexp_replace(
+            regexp_replace(normalize(name, NFKC), '[\u00AD\u180E\u200B-\u200F\u202A-\u202E\u2060-\u2064\uFEFF]', '', 'g'),
+            '[\s\u0085\u1680\u2028\u2029]+', ' ', 'g'
+        ),
+        ' '
+    )
+$$ LANGUAGE sql IMMUTABLE STRICT;
+
+-- Invisible characters are now removed by `normalize_player_name`, which also treats a few more unicode spaces as
+-- whitespace. Skeletons can therefore differ from the ones computed before.
+CREATE OR REPLACE FUNCTION player_name_skeleton(name TEXT) RETURNS TEXT AS $$
+    SELECT translate(
+        regexp_replace(lower(normalize_player_name(name)), '[\s_.-]', '', 'g'),
+        'аеорсухіјѕԁһӏԛԝквнмтαβεζηικμορτυχ01',
+        'aeopcyxijsdhlqwkbhmtabezhikmoptyxol'
+    )
+$$ LANGUAGE sql IMMUTABLE STRICT;
+
+-- Stored generated columns are not recomputed when the function behind them changes, so re-add the column
+ALTER TABLE players DROP COLUMN name_skeleton;
+ALTER TABLE players ADD COLUMN name_skeleton TEXT GENERATED ALWAYS AS (player_name_skeleton(name::text)) STORED;
+
+CREATE INDEX players_name_skeleton ON players(name_skeleton);
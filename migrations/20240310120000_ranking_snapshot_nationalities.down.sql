@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE player_ranking_snapshots DROP COLUMN nation, DROP COLUMN subdivision;
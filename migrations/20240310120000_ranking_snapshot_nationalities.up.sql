@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+-- The nation and subdivision each player belonged to when a snapshot was taken, so that a player's nation and
+-- subdivision ranking history follows the player's nationality over time, instead of only the current one.
+ALTER TABLE player_ranking_snapshots ADD COLUMN nation VARCHAR(2) NULL DEFAULT NULL,
+                                     ADD COLUMN subdivision VARCHAR(3) NULL DEFAULT NULL;
+
+-- Existing snapshots get the nationality the audit log says each player had at the start of the day (which is when the
+-- snapshots are taken)
+UPDATE player_ranking_snapshots
+SET nation = back_then.nationality, subdivision = back_then.subdivision
+FROM (
+    SELECT days.taken_on, players.id, players.nationality, players.subdivision
+    FROM (SELECT DISTINCT taken_on FROM player_ranking_snapshots) days
+             CROSS JOIN LATERAL players_at(days.taken_on::TIMESTAMP) players
+) back_then
+WHERE back_then.taken_on = player_ranking_snapshots.taken_on AND back_then.id = player_ranking_snapshots.player;
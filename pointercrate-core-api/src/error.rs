@@ -19,6 +19,8 @@ pub struct ErrorResponder {
     #[serde(rename = "code")]
     error_code: u16,
     data: Value,
+    #[serde(skip)]
+    headers: Vec<(&'static str, String)>,
 }
 
 impl<'r> Responder<'r, 'static> for ErrorResponder {
@@ -38,7 +40,7 @@ impl<'r> Responder<'r, 'static> for ErrorResponder {
             error!("Encountered an internal server error: {:?}", self);
         }
 
-        if accept == MediaType::HTML {
+        let mut response = if accept == MediaType::HTML {
             Response::build_from(
                 Page::new(ErrorFragment {
                     status: self.error_code / 100,
@@ -48,10 +50,16 @@ impl<'r> Responder<'r, 'static> for ErrorResponder {
                 .respond_to(request)?,
             )
             .status(status)
-            .ok()
+            .finalize()
         } else {
-            Response::build_from(Json(self).respond_to(request)?).status(status).ok()
+            Response::build_from(Json(&self).respond_to(request)?).status(status).finalize()
+        };
+
+        for (name, value) in self.headers {
+            response.set_raw_header(name, value);
         }
+
+        Ok(response)
     }
 }
 
@@ -60,7 +68,8 @@ impl<E: PointercrateError> From<E> for ErrorResponder {
         ErrorResponder {
             message: error.to_string(),
             error_code: error.error_code(),
-            data: serde_json::to_value(error).expect("failed to serialize error to json"),
+            data: serde_json::to_value(&error).expect("failed to serialize error to json"),
+            headers: error.headers(),
         }
     }
 }
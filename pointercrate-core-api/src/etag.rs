@@ -1,5 +1,8 @@
 use crate::response::Response2;
-use pointercrate_core::{error::CoreError, etag::Taggable};
+use pointercrate_core::{
+    error::CoreError,
+    etag::{ParsedEtag, Taggable, ETAG_VERSION},
+};
 use rocket::{
     http::{Method, Status},
     request::{FromRequest, Outcome},
@@ -14,18 +17,23 @@ pub struct Precondition(Vec<String> /* ensure private constructor for type level
 
 impl Precondition {
     pub fn require_etag_match<T: Taggable>(&self, taggable: &T) -> Result<(), CoreError> {
-        let patch_etag = taggable.patch_part().to_string();
+        let patch_etag = format!("{:016x}", taggable.patch_part());
+        let mut outdated_version = None;
+
+        for etag in self.0.iter().filter_map(|if_match| ParsedEtag::parse(if_match)) {
+            if etag.version != ETAG_VERSION {
+                outdated_version = Some(etag.version);
+            } else if etag.patch_part == patch_etag {
+                return Ok(());
+            }
+        }
 
-        if self
-            .0
-            .iter()
-            .filter_map(|if_match| if_match.split(';').next())
-            .filter(|etag_fragment| etag_fragment.len() > 3)
-            .any(|e| e[3..] == patch_etag)
-        {
-            Ok(())
-        } else {
-            Err(CoreError::PreconditionFailed)
+        match outdated_version {
+            Some(version) => Err(CoreError::OutdatedEtag {
+                version,
+                current: ETAG_VERSION,
+            }),
+            None => Err(CoreError::PreconditionFailed),
         }
     }
 }
@@ -0,0 +1,325 @@
+//! Serialization of paginated objects into the bulk export formats
+//!
+//! Exports are written row by row, so that entire tables can be streamed to the client without
+//! ever holding them in memory.
+
+use rocket::http::ContentType;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject, SingleOrVec},
+    JsonSchema, Map,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::marker::PhantomData;
+
+/// A format, other than plain JSON, that paginated endpoints can return their objects in
+///
+/// Should an export fail after streaming has begun, it ends with an error marker (see
+/// [`ExportWriter::write_error`]) instead of simply stopping, so that clients cannot mistake a
+/// partial export for a complete one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma separated values (RFC 4180), with a header row. Nested objects are flattened into
+    /// columns whose names are the dot-separated paths of their fields (e.g. `player.name`)
+    Csv,
+
+    /// Newline delimited JSON, one object per line
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// Determines the export format requested by a client from its preferred media type
+    pub fn from_media_type(top: &str, sub: &str) -> Option<ExportFormat> {
+        match (&top.to_ascii_lowercase()[..], &sub.to_ascii_lowercase()[..]) {
+            ("text", "csv") => Some(ExportFormat::Csv),
+            ("application", "x-ndjson") => Some(ExportFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> ContentType {
+        match self {
+            ExportFormat::Csv => ContentType::CSV,
+            ExportFormat::Ndjson => ContentType::new("application", "x-ndjson"),
+        }
+    }
+}
+
+/// Writes objects of type `T` in some [`ExportFormat`]
+pub struct ExportWriter<T> {
+    format: ExportFormat,
+
+    /// The columns of the CSV export, as paths into the (JSON representation of the) exported
+    /// objects. Derived from the schema of `T`, so that they are the same for every export of a
+    /// given endpoint, no matter which objects it contains.
+    columns: Vec<Vec<String>>,
+
+    exported: PhantomData<fn(&T)>,
+}
+
+impl<T: Serialize + JsonSchema> ExportWriter<T> {
+    pub fn new(format: ExportFormat) -> Self {
+        let columns = match format {
+            ExportFormat::Csv => columns_of::<T>(),
+            ExportFormat::Ndjson => Vec::new(),
+        };
+
+        ExportWriter {
+            format,
+            columns,
+            exported: PhantomData,
+        }
+    }
+
+    /// Appends everything preceding the first object (the header row of a CSV export) to the given
+    /// buffer
+    pub fn write_header(&self, buffer: &mut Vec<u8>) {
+        if self.format == ExportFormat::Csv {
+            write_csv_row(self.columns.iter().map(|column| column.join(".")), buffer);
+        }
+    }
+
+    /// Appends the representation of the given object to the given buffer
+    pub fn write(&self, object: &T, buffer: &mut Vec<u8>) -> Result<(), serde_json::Error> {
+        match self.format {
+            ExportFormat::Ndjson => {
+                serde_json::to_writer(&mut *buffer, object)?;
+                buffer.push(b'\n');
+            },
+            ExportFormat::Csv => {
+                let value = serde_json::to_value(object)?;
+
+                write_csv_row(self.columns.iter().map(|column| csv_cell(&value, column)), buffer);
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Appends a marker telling the client that the export was aborted to the given buffer
+    ///
+    /// For NDJSON, this is a final line of the form `{"error": "<message>"}`, which cannot be
+    /// confused with an exported object since none of them has an `error` field. For CSV, it is a
+    /// final row consisting of a single cell starting with `#error: `.
+    pub fn write_error(&self, message: &str, buffer: &mut Vec<u8>) {
+        match self.format {
+            ExportFormat::Ndjson => {
+                buffer.extend_from_slice(serde_json::json!({ "error": message }).to_string().as_bytes());
+                buffer.push(b'\n');
+            },
+            ExportFormat::Csv => write_csv_row(std::iter::once(format!("#error: {}", message)), buffer),
+        }
+    }
+}
+
+/// Derives the columns of a CSV export of objects of type `T` from its JSON schema
+fn columns_of<T: JsonSchema>() -> Vec<Vec<String>> {
+    let root = SchemaGenerator::default().into_root_schema_for::<T>();
+    let mut columns = Vec::new();
+
+    collect_columns(&root.schema, &root.definitions, &mut Vec::new(), &mut columns);
+
+    columns
+}
+
+/// Flattens objects into one column per (nested) property
+///
+/// Nested objects are flattened even if they are optional. Everything else (including arrays)
+/// becomes a single column.
+fn collect_columns(schema: &SchemaObject, definitions: &Map<String, Schema>, path: &mut Vec<String>, columns: &mut Vec<Vec<String>>) {
+    match schema.object {
+        Some(ref object) if !object.properties.is_empty() => {
+            for (name, property) in &object.properties {
+                path.push(name.clone());
+
+                match resolve(property, definitions) {
+                    Some(property) => collect_columns(property, definitions, path, columns),
+                    None => columns.push(path.clone()),
+                }
+
+                path.pop();
+            }
+        },
+        _ => columns.push(path.clone()),
+    }
+}
+
+/// Follows references to other schemas, and looks through the wrappers `schemars` puts around them
+/// (for `Option`s and for documented fields)
+fn resolve<'a>(schema: &'a Schema, definitions: &'a Map<String, Schema>) -> Option<&'a SchemaObject> {
+    let schema = match schema {
+        Schema::Object(schema) => schema,
+        Schema::Bool(_) => return None,
+    };
+
+    if let Some(ref reference) = schema.reference {
+        return definitions
+            .get(reference.trim_start_matches("#/definitions/"))
+            .and_then(|schema| resolve(schema, definitions));
+    }
+
+    if let Some(ref subschemas) = schema.subschemas {
+        let variants = subschemas
+            .all_of
+            .iter()
+            .chain(subschemas.any_of.iter())
+            .flatten()
+            .filter(|variant| !is_null(variant))
+            .collect::<Vec<_>>();
+
+        if let [variant] = variants[..] {
+            return resolve(variant, definitions);
+        }
+    }
+
+    Some(schema)
+}
+
+fn is_null(schema: &Schema) -> bool {
+    match schema {
+        Schema::Object(SchemaObject {
+            instance_type: Some(SingleOrVec::Single(instance_type)),
+            ..
+        }) => **instance_type == InstanceType::Null,
+        _ => false,
+    }
+}
+
+/// Gets the value at the given path in the given object, formatted for a CSV cell
+///
+/// Missing values and `null` result in empty cells.
+fn csv_cell(value: &Value, path: &[String]) -> String {
+    match path.iter().try_fold(value, |value, segment| value.get(segment)) {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(string)) => string.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn write_csv_row(cells: impl Iterator<Item = String>, buffer: &mut Vec<u8>) {
+    for (idx, cell) in cells.enumerate() {
+        if idx != 0 {
+            buffer.push(b',');
+        }
+
+        if cell.contains([',', '"', '\n', '\r']) {
+            buffer.push(b'"');
+            buffer.extend_from_slice(cell.replace('"', "\"\"").as_bytes());
+            buffer.push(b'"');
+        } else {
+            buffer.extend_from_slice(cell.as_bytes());
+        }
+    }
+
+    buffer.extend_from_slice(b"\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportFormat, ExportWriter};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+
+    #[derive(Serialize, JsonSchema)]
+    struct Player {
+        id: i32,
+        name: String,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    struct Nationality {
+        nation: String,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    struct Record {
+        id: i32,
+
+        /// The player holding this record
+        player: Player,
+        video: Option<String>,
+        nationality: Option<Nationality>,
+    }
+
+    fn record(id: i32, name: &str, video: Option<&str>, nation: Option<&str>) -> Record {
+        Record {
+            id,
+            player: Player {
+                id: id + 4,
+                name: name.to_string(),
+            },
+            video: video.map(ToString::to_string),
+            nationality: nation.map(|nation| Nationality {
+                nation: nation.to_string(),
+            }),
+        }
+    }
+
+    fn export(format: ExportFormat, objects: &[Record]) -> String {
+        let writer = ExportWriter::new(format);
+        let mut buffer = Vec::new();
+
+        writer.write_header(&mut buffer);
+
+        for object in objects {
+            writer.write(object, &mut buffer).unwrap();
+        }
+
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_csv_flattens_nested_objects() {
+        let csv = export(
+            ExportFormat::Csv,
+            &[
+                record(1, "stardust1971", None, Some("Germany")),
+                record(2, "Aquatias, \"the\" legend", Some("https://youtu.be/a"), None),
+            ],
+        );
+
+        assert_eq!(
+            csv,
+            "id,nationality.nation,player.id,player.name,video\r\n1,Germany,5,stardust1971,\r\n2,,6,\"Aquatias, \"\"the\"\" \
+             legend\",https://youtu.be/a\r\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_columns_independent_of_objects() {
+        // Neither the first object having 'null' where others have nested objects, nor there being
+        // no objects at all changes the columns
+        assert_eq!(
+            export(ExportFormat::Csv, &[record(1, "Aquatias", None, None)]),
+            "id,nationality.nation,player.id,player.name,video\r\n1,,5,Aquatias,\r\n"
+        );
+        assert_eq!(
+            export(ExportFormat::Csv, &[]),
+            "id,nationality.nation,player.id,player.name,video\r\n"
+        );
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let ndjson = export(ExportFormat::Ndjson, &[record(1, "Aquatias", None, None)]);
+
+        assert_eq!(
+            ndjson,
+            "{\"id\":1,\"player\":{\"id\":5,\"name\":\"Aquatias\"},\"video\":null,\"nationality\":null}\n"
+        );
+    }
+
+    #[test]
+    fn test_error_marker() {
+        let mut buffer = Vec::new();
+
+        ExportWriter::<Record>::new(ExportFormat::Ndjson).write_error("Database error", &mut buffer);
+        ExportWriter::<Record>::new(ExportFormat::Csv).write_error("Database error", &mut buffer);
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"error\":\"Database error\"}\n#error: Database error\r\n"
+        );
+    }
+}
@@ -1,5 +1,8 @@
 pub mod error;
 pub mod etag;
+pub mod export;
+pub mod openapi;
+pub mod pagination;
 pub mod query;
-#[macro_use]
+pub mod ratelimits;
 pub mod response;
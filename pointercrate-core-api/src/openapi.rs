@@ -0,0 +1,468 @@
+//! Generation of an [OpenAPI 3](https://spec.openapis.org/oas/v3.0.3) document describing all mounted API routes
+//!
+//! Each API crate describes its routes in an [`ApiDocumentation`], which [`setup`] both mounts and
+//! registers, so that documented and mounted routes cannot diverge. Paths, methods and parameters
+//! are then read off the routes mounted on the running rocket instance, so every API route shows up
+//! in the document. Everything that cannot be derived from a route alone (the types of query
+//! parameters, request and response bodies, pagination, `If-Match` requirements) is provided by the
+//! route's [`Operation`]. Schemas are generated from the serde definitions of the involved types via
+//! [`JsonSchema`].
+
+use pointercrate_core::{
+    error::{CoreError, PointercrateError},
+    pagination::{Paginator, DEFAULT_LIMIT},
+};
+use rocket::{http::Status, response::Responder, route::Route, serde::json::Json, Build, Request, Rocket};
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+/// Documentation for a single API operation, supplementing the information contained in its route
+pub struct Operation {
+    routes: Vec<Route>,
+    summary: &'static str,
+    request_body: Option<SchemaFn>,
+    response: Option<SchemaFn>,
+    query: Option<SchemaFn>,
+    paginated: bool,
+    conditional: bool,
+    tagged: bool,
+}
+
+impl Operation {
+    /// Documents the operation performed by the given routes, as created by [`rocket::routes`]
+    ///
+    /// Usually this is a single route. Multiple ranked routes handling the same requests (e.g. for
+    /// authenticated and unauthenticated access) are documented as one operation.
+    pub fn new(routes: Vec<Route>, summary: &'static str) -> Self {
+        Operation {
+            routes,
+            summary,
+            request_body: None,
+            response: None,
+            query: None,
+            paginated: false,
+            conditional: false,
+            tagged: false,
+        }
+    }
+
+    /// Sets the type of this operation's JSON request body
+    pub fn with_body<T: JsonSchema>(mut self) -> Self {
+        self.request_body = Some(SchemaGenerator::subschema_for::<T>);
+        self
+    }
+
+    /// Sets the type of this operation's JSON response body
+    pub fn with_response<T: JsonSchema>(mut self) -> Self {
+        self.response = Some(SchemaGenerator::subschema_for::<T>);
+        self
+    }
+
+    /// Sets the struct this operation's query string is deserialized into
+    ///
+    /// Each of its fields is documented as a query parameter. This also provides the types of
+    /// query parameters bound directly in the route's URI (e.g. `?<list>`).
+    pub fn with_query<T: JsonSchema>(mut self) -> Self {
+        self.query = Some(T::json_schema);
+        self
+    }
+
+    /// Marks this operation as paginated via the given [`Paginator`], see
+    /// [`pagination_response`](crate::pagination::pagination_response)
+    ///
+    /// The paginator's filters are documented as query parameters, and its items as the response.
+    pub fn paginated<P: Paginator + JsonSchema>(mut self) -> Self {
+        self.query = Some(P::json_schema);
+        self.response = Some(SchemaGenerator::subschema_for::<Vec<P::Item>>);
+        self.paginated = true;
+        self
+    }
+
+    /// Marks this operation as requiring an `If-Match` header, see [`Precondition`](crate::etag::Precondition)
+    pub fn conditional(mut self) -> Self {
+        self.conditional = true;
+        self
+    }
+
+    /// Marks this operation as returning an `ETag` header, see [`Tagged`](crate::etag::Tagged)
+    pub fn tagged(mut self) -> Self {
+        self.tagged = true;
+        self
+    }
+}
+
+/// The routes an API crate mounts, together with their documentation
+#[derive(Default)]
+pub struct ApiDocumentation {
+    /// The operations to mount, grouped by their mount point
+    mounts: Vec<(&'static str, Vec<Operation>)>,
+
+    /// Maps error codes to the messages of all errors with that code
+    errors: BTreeMap<u16, Vec<String>>,
+}
+
+impl ApiDocumentation {
+    pub fn new() -> Self {
+        ApiDocumentation::default()
+    }
+
+    /// Adds the given operations, whose routes [`setup`] mounts at the given base
+    pub fn mount(mut self, base: &'static str, operations: Vec<Operation>) -> Self {
+        self.mounts.push((base, operations));
+        self
+    }
+
+    /// Adds all errors in the [catalogue](PointercrateError::catalogue) of the given error type to
+    /// the documented error codes
+    pub fn errors<E: PointercrateError>(mut self) -> Self {
+        for error in E::catalogue() {
+            self.errors.entry(error.error_code()).or_default().push(error.to_string());
+        }
+        self
+    }
+
+    fn merge(&mut self, other: ApiDocumentation) {
+        self.mounts.extend(other.mounts);
+
+        for (code, messages) in other.errors {
+            self.errors.entry(code).or_default().extend(messages);
+        }
+    }
+
+    fn document<'a>(&self, routes: impl Iterator<Item = &'a Route>) -> Value {
+        let mut generator = SchemaSettings::openapi3().into_generator();
+        let mut paths = BTreeMap::<String, Map<String, Value>>::new();
+        let mut operations = HashMap::new();
+
+        for (base, mounted) in &self.mounts {
+            for documented in mounted {
+                for route in &documented.routes {
+                    let (path, _) = openapi_path(&format!("{}{}", base.trim_end_matches('/'), route.uri.path()));
+
+                    operations.insert((route.method, path), documented);
+                }
+            }
+        }
+
+        for route in routes {
+            if !route.uri.path().starts_with("/api/") {
+                continue;
+            }
+
+            let (path, path_parameters) = openapi_path(route.uri.path());
+            let method = route.method.as_str().to_ascii_lowercase();
+            let item = paths.entry(path.clone()).or_default();
+
+            // Multiple ranked routes can handle the same endpoint (e.g. for authenticated and
+            // unauthenticated access), they all get documented as one operation
+            if item.contains_key(&method) {
+                continue;
+            }
+
+            let documentation = operations.get(&(route.method, path)).copied();
+
+            item.insert(method, operation(route, &path_parameters, documentation, &mut generator));
+        }
+
+        let mut schemas: Map<String, Value> = generator
+            .take_definitions()
+            .into_iter()
+            .map(|(name, schema)| (name, serde_json::to_value(schema).unwrap_or_default()))
+            .collect();
+
+        schemas.insert("Error".to_string(), self.error_schema());
+
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "API",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "paths": paths,
+            "components": {
+                "schemas": schemas,
+                "responses": {
+                    "Error": {
+                        "description": "The request failed. The `code` field identifies the exact error, its first three digits are \
+                                        the HTTP status code",
+                        "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Error"}}}
+                    }
+                }
+            },
+            "x-error-codes": self.errors.iter().map(|(code, messages)| json!({
+                "code": code,
+                "status": code / 100,
+                "messages": messages,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    fn error_schema(&self) -> Value {
+        let mut description = "| Code | Meaning |\n| --- | --- |\n".to_string();
+
+        for (code, messages) in &self.errors {
+            description.push_str(&format!("| {} | {} |\n", code, messages.join("<br>")));
+        }
+
+        json!({
+            "type": "object",
+            "description": description,
+            "required": ["message", "code", "data"],
+            "properties": {
+                "message": {"type": "string"},
+                "code": {"type": "integer", "enum": self.errors.keys().collect::<Vec<_>>()},
+                "data": {"description": "Additional, error specific information"}
+            }
+        })
+    }
+}
+
+struct DocumentationRegistry(Mutex<ApiDocumentation>);
+
+/// Mounts the routes of the given documentation and registers it, and mounts the route serving
+/// the OpenAPI document at `/api/openapi.json` if that has not yet happened
+pub fn setup(mut rocket: Rocket<Build>, documentation: ApiDocumentation) -> Rocket<Build> {
+    for (base, operations) in &documentation.mounts {
+        let routes = operations.iter().flat_map(|operation| operation.routes.clone()).collect::<Vec<_>>();
+
+        rocket = rocket.mount(*base, routes);
+    }
+
+    if let Some(registry) = rocket.state::<DocumentationRegistry>() {
+        if let Ok(mut registered) = registry.0.lock() {
+            registered.merge(documentation);
+        }
+
+        return rocket;
+    }
+
+    let mut registered = ApiDocumentation::new().errors::<CoreError>();
+    registered.merge(documentation);
+
+    rocket
+        .manage(DocumentationRegistry(Mutex::new(registered)))
+        .mount("/api/", rocket::routes![openapi])
+}
+
+#[rocket::get("/openapi.json")]
+fn openapi() -> OpenApiDocument {
+    OpenApiDocument
+}
+
+struct OpenApiDocument;
+
+impl<'r> Responder<'r, 'static> for OpenApiDocument {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'static> {
+        let registry = request
+            .rocket()
+            .state::<DocumentationRegistry>()
+            .ok_or(Status::InternalServerError)?;
+        let document = registry
+            .0
+            .lock()
+            .map_err(|_| Status::InternalServerError)?
+            .document(request.rocket().routes());
+
+        Json(document).respond_to(request)
+    }
+}
+
+/// Converts a rocket route path into OpenAPI syntax, returning the names of its path parameters
+fn openapi_path(path: &str) -> (String, Vec<String>) {
+    let mut parameters = Vec::new();
+
+    let segments = path
+        .split('/')
+        .map(|segment| match segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            Some(parameter) => {
+                let parameter = parameter.trim_end_matches("..");
+                parameters.push(parameter.to_string());
+                format!("{{{}}}", parameter)
+            },
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    let path = segments.join("/");
+
+    // rocket normalizes away trailing slashes of routes such as 'GET /api/v1/records/', so we do the same
+    (path.trim_end_matches('/').to_string(), parameters)
+}
+
+fn operation(route: &Route, path_parameters: &[String], documentation: Option<&Operation>, generator: &mut SchemaGenerator) -> Value {
+    let mut parameters = path_parameters
+        .iter()
+        .map(|name| {
+            // all our numeric path parameters are database IDs
+            let schema = if name.ends_with("_id") {
+                json!({"type": "integer"})
+            } else {
+                json!({"type": "string"})
+            };
+
+            json!({"name": name, "in": "path", "required": true, "schema": schema})
+        })
+        .collect::<Vec<_>>();
+
+    let query = documentation
+        .and_then(|documentation| documentation.query)
+        .map(|schema| schema(generator).into_object());
+    let query_fields = query.as_ref().and_then(|query| query.object.as_ref());
+
+    // Query parameters bound in the route's URI, the structs deserialized from multi-segment
+    // parameters ('<pagination..>') are covered by the query documentation below
+    for segment in route.uri.query().into_iter().flat_map(|query| query.split('&')) {
+        if let Some(name) = segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            if !name.ends_with("..") {
+                let schema = query_fields.and_then(|fields| fields.properties.get(name));
+                let required = query_fields.map_or(false, |fields| fields.required.contains(name));
+
+                parameters.push(query_parameter(name, schema, required));
+            }
+        }
+    }
+
+    let mut response_headers = Map::new();
+    let mut response_content = Map::new();
+    let mut responses = Map::new();
+    let mut operation = Map::new();
+
+    if let Some(documentation) = documentation {
+        operation.insert("summary".to_string(), json!(documentation.summary));
+
+        if let Some(schema) = documentation.response {
+            response_content.insert("application/json".to_string(), json!({ "schema": schema(generator) }));
+        }
+
+        if let Some(schema) = documentation.request_body {
+            operation.insert(
+                "requestBody".to_string(),
+                json!({"required": true, "content": {"application/json": {"schema": schema(generator)}}}),
+            );
+        }
+
+        if documentation.paginated {
+            parameters.extend(pagination_parameters());
+
+            response_content.insert("text/csv".to_string(), json!({}));
+            response_content.insert("application/x-ndjson".to_string(), json!({}));
+
+            response_headers.insert(
+                "Link".to_string(),
+                json!({
+                    "description": "RFC 8288 links to the first, last and, where applicable, next and previous page",
+                    "schema": {"type": "string"},
+                }),
+            );
+            response_headers.insert(
+                "X-Total-Count".to_string(),
+                json!({
+                    "description": "The total number of matching objects, if requested via `Prefer: count=exact`",
+                    "schema": {"type": "integer"},
+                }),
+            );
+        }
+
+        if let Some(fields) = query_fields {
+            for (name, schema) in &fields.properties {
+                if !parameters
+                    .iter()
+                    .any(|parameter| parameter["name"] == *name && parameter["in"] == "query")
+                {
+                    parameters.push(query_parameter(name, Some(schema), fields.required.contains(name)));
+                }
+            }
+        }
+
+        if documentation.tagged {
+            response_headers.insert("ETag".to_string(), json!({"schema": {"type": "string"}}));
+        }
+
+        if documentation.conditional {
+            parameters.push(json!({
+                "name": "If-Match",
+                "in": "header",
+                "required": true,
+                "description": "The ETag of the current version of the object, as returned by a previous request",
+                "schema": {"type": "string"},
+            }));
+
+            responses.insert("412".to_string(), json!({"$ref": "#/components/responses/Error"}));
+            responses.insert("428".to_string(), json!({"$ref": "#/components/responses/Error"}));
+        }
+    }
+
+    let mut success = json!({"description": "Success", "headers": response_headers});
+
+    if !response_content.is_empty() {
+        success["content"] = Value::Object(response_content);
+    }
+
+    responses.insert("2XX".to_string(), success);
+    responses.insert("default".to_string(), json!({"$ref": "#/components/responses/Error"}));
+
+    operation.insert("parameters".to_string(), json!(parameters));
+    operation.insert("responses".to_string(), json!(responses));
+
+    Value::Object(operation)
+}
+
+/// Documents the query parameter of the given name, whose type is described by the given schema if
+/// known
+fn query_parameter(name: &str, schema: Option<&Schema>, required: bool) -> Value {
+    let mut schema = schema
+        .and_then(|schema| serde_json::to_value(schema).ok())
+        .unwrap_or_else(|| json!({"type": "string"}));
+    let mut parameter = json!({"name": name, "in": "query", "required": required});
+
+    // Doc comments of query struct fields describe the parameter itself
+    if let Some(description) = schema.as_object_mut().and_then(|schema| schema.remove("description")) {
+        parameter["description"] = description;
+    }
+
+    parameter["schema"] = schema;
+    parameter
+}
+
+fn pagination_parameters() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "limit",
+            "in": "query",
+            "required": false,
+            "description": "The maximal number of objects to return, between 1 and 100",
+            "schema": {"type": "integer", "default": DEFAULT_LIMIT, "minimum": 1, "maximum": 100},
+        }),
+        json!({
+            "name": "before",
+            "in": "query",
+            "required": false,
+            "description": "Only return objects whose pagination key (usually their ID) is smaller than this value",
+            "schema": {"type": "integer"},
+        }),
+        json!({
+            "name": "after",
+            "in": "query",
+            "required": false,
+            "description": "Only return objects whose pagination key (usually their ID) is greater than this value",
+            "schema": {"type": "integer"},
+        }),
+        json!({
+            "name": "Prefer",
+            "in": "header",
+            "required": false,
+            "description": "`count=exact` to receive the total number of matching objects in the `X-Total-Count` header",
+            "schema": {"type": "string"},
+        }),
+    ]
+}
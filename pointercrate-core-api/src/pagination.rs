@@ -0,0 +1,234 @@
+use crate::{
+    export::{ExportFormat, ExportWriter},
+    response::Response2,
+};
+use pointercrate_core::pagination::{PaginationParameters, Paginator, DEFAULT_LIMIT};
+use rocket::{
+    futures::{stream::BoxStream, StreamExt},
+    request::{FromRequest, Outcome},
+    response::{stream::ReaderStream, Responder},
+    serde::json::Json,
+    Request, Response,
+};
+use serde::Serialize;
+use sqlx::PgConnection;
+use std::{convert::Infallible, io::Cursor, ops::DerefMut};
+
+/// The number of bytes of an export that are buffered before being sent to the client
+const EXPORT_CHUNK_SIZE: usize = 16 * 1024;
+
+/// The message of the error marker ending exports that could not be completed
+const EXPORT_ABORTED: &str = "An internal server error occurred, the export is incomplete";
+
+/// Request guard for the ways in which a client can ask for the response of a paginated endpoint
+/// to deviate from a plain page of JSON objects
+pub struct PaginationPreferences {
+    /// Whether the client wants to know the total number of objects matching its pagination request
+    ///
+    /// Counting requires an additional query over the entire filtered table, so it is only done if
+    /// the client explicitly asks for it via a `Prefer: count=exact` header (the syntax PostgREST
+    /// uses for the same purpose).
+    pub exact_count: bool,
+
+    /// The bulk export format the client asked for via its `Accept` header, if any
+    pub export: Option<ExportFormat>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for PaginationPreferences {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let exact_count = request
+            .headers()
+            .get("Prefer")
+            .flat_map(|value| value.split(','))
+            .any(|preference| preference.trim().eq_ignore_ascii_case("count=exact"));
+
+        let export = request.accept().and_then(|accept| {
+            let media_type = accept.preferred().media_type();
+
+            ExportFormat::from_media_type(media_type.top().as_str(), media_type.sub().as_str())
+        });
+
+        Outcome::Success(PaginationPreferences { exact_count, export })
+    }
+}
+
+/// Response of a paginated endpoint
+pub enum Paginated<T> {
+    Page(Response2<Json<Vec<T>>>),
+    Export {
+        format: ExportFormat,
+        body: BoxStream<'static, Vec<u8>>,
+    },
+}
+
+impl<'r, 'o: 'r, T: Serialize> Responder<'r, 'o> for Paginated<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match self {
+            Paginated::Page(page) => page.respond_to(request),
+            Paginated::Export { format, body } => Response::build()
+                .header(format.content_type())
+                .streamed_body(ReaderStream::from(body.map(Cursor::new)))
+                .ok(),
+        }
+    }
+}
+
+/// Retrieves the page described by the given paginator and turns it into a response
+///
+/// Navigation between pages is done via a [RFC 8288](https://www.rfc-editor.org/rfc/rfc8288) `Link` header, which always
+/// contains `first` and `last` relations, and `next` and `prev` relations where applicable. If
+/// requested, the total number of objects matching the paginator's filters is returned in the
+/// `X-Total-Count` header.
+///
+/// If the client requested a bulk export, all objects matching the paginator are instead streamed
+/// in the requested format, see [`export`].
+pub async fn pagination_response<P, C>(
+    endpoint: &str, pagination: P, preferences: PaginationPreferences, mut connection: C,
+) -> Result<Paginated<P::Item>, P::Error>
+where
+    P: Paginator + 'static,
+    C: DerefMut<Target = PgConnection> + Send + 'static,
+{
+    log::debug!("Received pagination request {:?}", pagination);
+
+    if let Some(format) = preferences.export {
+        return export(pagination, format, connection).map(|body| Paginated::Export { format, body });
+    }
+
+    let parameters = pagination.parameters();
+    let mut objects = pagination.page(&mut connection).await?;
+
+    let more_exist = objects.len() > parameters.limit as usize;
+
+    if more_exist {
+        // remove the object from the next page
+        objects.pop();
+    }
+
+    if parameters.is_descending() {
+        // the page was retrieved using 'ORDER BY ... DESC', but we always return objects in ascending order
+        objects.reverse();
+    }
+
+    let link = |before: Option<i64>, after: Option<i64>, rel: &str| {
+        let linked = pagination.with_parameters(PaginationParameters {
+            before,
+            after,
+            ..parameters
+        });
+
+        format!(
+            "<{}?{}>; rel=\"{}\"",
+            endpoint,
+            serde_urlencoded::to_string(&linked).unwrap_or_default(),
+            rel
+        )
+    };
+
+    // If the request is bounded by both 'before' and 'after', the 'before' value is an upper bound that we keep
+    // for all the links pointing into the bounded range
+    let upper_bound = parameters.before.filter(|_| parameters.after.is_some());
+
+    let mut links = vec![
+        match upper_bound {
+            None => link(None, None, "first"),
+            Some(before) => link(Some(before), Some(i64::MIN), "first"),
+        },
+        link(Some(upper_bound.unwrap_or(i64::MAX)), None, "last"),
+    ];
+
+    if let (Some(first), Some(last)) = (objects.first(), objects.last()) {
+        let (first, last) = (P::pagination_key(first), P::pagination_key(last));
+
+        if parameters.is_descending() {
+            // We went backwards from 'before', so objects following this page might exist (we do not know without another
+            // query), while objects preceding it only exist if we had to cut the page off
+            links.push(link(None, Some(last), "next"));
+
+            if more_exist {
+                links.push(link(Some(first), None, "prev"));
+            }
+        } else {
+            if more_exist {
+                links.push(link(upper_bound, Some(last), "next"));
+            }
+
+            if parameters.after.is_some() {
+                links.push(link(Some(first), None, "prev"));
+            }
+        }
+    }
+
+    let links = links.join(", ");
+
+    log::debug!("Link header has value '{}'", links);
+
+    let mut response = Response2::json(objects).with_header("Link", links);
+
+    if preferences.exact_count {
+        let total = pagination.total_count(&mut connection).await?;
+
+        response = response
+            .with_header("X-Total-Count", total.to_string())
+            .with_header("Preference-Applied", "count=exact");
+    }
+
+    Ok(Paginated::Page(response))
+}
+
+/// Streams all objects matching the given paginator in the given export format
+///
+/// The objects are read from the database as the response body is sent, so the export is not
+/// subject to the maximal page size. Since the response status has already been sent once
+/// streaming begins, errors occurring during the export are logged and reported to the client by
+/// ending the export with an error marker, see [`ExportWriter::write_error`].
+pub fn export<P, C>(pagination: P, format: ExportFormat, mut connection: C) -> Result<BoxStream<'static, Vec<u8>>, P::Error>
+where
+    P: Paginator + 'static,
+    C: DerefMut<Target = PgConnection> + Send + 'static,
+{
+    // Only the bounds are checked, 'limit' does not apply to exports
+    PaginationParameters {
+        limit: DEFAULT_LIMIT,
+        ..pagination.parameters()
+    }
+    .validate()?;
+
+    Ok(Box::pin(async_stream::stream! {
+        let writer = ExportWriter::new(format);
+        let mut buffer = Vec::new();
+        let mut objects = pagination.stream(&mut connection);
+
+        writer.write_header(&mut buffer);
+
+        while let Some(object) = objects.next().await {
+            let written = match object {
+                Ok(object) => writer.write(&object, &mut buffer),
+                Err(err) => {
+                    log::error!("Error during export of {:?}, aborting: {}", pagination, err);
+
+                    writer.write_error(EXPORT_ABORTED, &mut buffer);
+                    break
+                },
+            };
+
+            if let Err(err) = written {
+                log::error!("Failed to serialize object during export of {:?}: {}", pagination, err);
+
+                writer.write_error(EXPORT_ABORTED, &mut buffer);
+                break
+            }
+
+            if buffer.len() >= EXPORT_CHUNK_SIZE {
+                yield std::mem::take(&mut buffer);
+            }
+        }
+
+        if !buffer.is_empty() {
+            yield buffer;
+        }
+    }))
+}
@@ -0,0 +1,73 @@
+use pointercrate_core::{error::CoreError, ratelimits::RatelimitInfo};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    request::{FromRequest, Outcome},
+    Request, Response,
+};
+use std::{convert::Infallible, sync::Mutex};
+
+/// The most restrictive ratelimit checked while handling the current request
+#[derive(Default)]
+struct CheckedRatelimit(Mutex<Option<RatelimitInfo>>);
+
+/// Request guard through which handlers report the outcome of the ratelimits they check
+///
+/// The [`RatelimitHeaders`] fairing then describes the most restrictive of them (the one letting
+/// through the fewest further requests) in the `X-RateLimit-*` headers of the response.
+pub struct RatelimitTracker<'r>(&'r CheckedRatelimit);
+
+impl RatelimitTracker<'_> {
+    /// Records the state of a ratelimit that let the request through, and passes on the error of
+    /// one that did not
+    ///
+    /// Errors carry the headers of the exceeded ratelimit themselves.
+    pub fn track(&self, outcome: Result<RatelimitInfo, CoreError>) -> Result<(), CoreError> {
+        let info = outcome?;
+        let mut checked = self.0 .0.lock().unwrap();
+
+        match *checked {
+            Some(ref most_restrictive) if most_restrictive.remaining <= info.remaining => (),
+            _ => *checked = Some(info),
+        }
+
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RatelimitTracker<'r> {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RatelimitTracker(request.local_cache(CheckedRatelimit::default)))
+    }
+}
+
+/// Fairing adding the `X-RateLimit-*` headers to responses of requests that passed ratelimits
+/// reported via [`RatelimitTracker`]
+pub struct RatelimitHeaders;
+
+#[rocket::async_trait]
+impl Fairing for RatelimitHeaders {
+    fn info(&self) -> Info {
+        Info {
+            name: "Ratelimit headers",
+            kind: Kind::Response | Kind::Singleton,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        // Responses to ratelimited requests already describe the exceeded ratelimit
+        if response.headers().contains("X-RateLimit-Limit") {
+            return;
+        }
+
+        let checked = *request.local_cache(CheckedRatelimit::default).0.lock().unwrap();
+
+        if let Some(info) = checked {
+            for (name, value) in info.headers() {
+                response.set_raw_header(name, value);
+            }
+        }
+    }
+}
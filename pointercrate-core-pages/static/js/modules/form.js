@@ -373,7 +373,7 @@ export class Paginator extends Output {
     // external selection listeners
     this.selectionListeners = [];
 
-    // The endpoint which will be paginated. By storing this, we assume that the 'Links' header never redirects
+    // The endpoint which will be paginated. By storing this, we assume that the 'Link' header never redirects
     // us to a different endpoint (this is the case with the pointercrate API)
     this.endpoint = this.html.dataset.endpoint;
     // The endpoint from which the actual objects will be retrieved. By default equal to the pagination endpoint.
@@ -383,11 +383,11 @@ export class Paginator extends Output {
     this.currentLink = this.endpoint + "?" + $.param(queryData);
     // The query data for the first request. Pagination may only update the 'before' and 'after' parameter,
     // meaning everything else will always stay the same.
-    // Storing this means we won't have to parse the query data of the links from the 'Links' header, and allows
+    // Storing this means we won't have to parse the query data of the links from the 'Link' header, and allows
     // us to easily update some parameters later on
     this.queryData = queryData;
 
-    // The (parsed) values of the HTTP 'Links' header, telling us how what requests to make then next or prev is clicked
+    // The (parsed) values of the HTTP 'Link' header, telling us how what requests to make then next or prev is clicked
     this.links = undefined;
     // The callback that constructs list entries for us
     this.itemConstructor = itemConstructor;
@@ -473,7 +473,7 @@ export class Paginator extends Output {
   }
 
   handleResponse(response) {
-    this.links = parsePagination(response.headers["links"]);
+    this.links = parsePagination(response.headers["link"]);
     this.list.scrollTop = 0;
 
     // Clear the current list.
@@ -562,9 +562,12 @@ function parsePagination(linkHeader) {
   var links = {};
   if (linkHeader) {
     for (var link of linkHeader.split(",")) {
+      // Each link is of the form '<url>; rel="relation"' (RFC 8288)
       var s = link.split(";");
+      var url = s[0].trim();
+      var rel = s[1].trim().substring(4).replace(/"/g, "");
 
-      links[s[1].substring(5)] = s[0].substring(1, s[0].length - 1);
+      links[rel] = url.substring(1, url.length - 1);
     }
   }
   return links;
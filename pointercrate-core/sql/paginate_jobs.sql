@@ -0,0 +1,8 @@
+SELECT id, kind, payload, state, attempts, max_attempts, run_at, last_error, created_at, finished_at
+FROM jobs
+WHERE (id < $1 OR $1 IS NULL)
+  AND (id > $2 OR $2 IS NULL)
+  AND (state = $3 OR $3 IS NULL)
+  AND (kind = $4 OR $4 IS NULL)
+ORDER BY id {}
+LIMIT $5
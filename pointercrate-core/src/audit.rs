@@ -1,15 +1,16 @@
 //! Module containing some basic structures for dealing with audit logs
 
 use chrono::NaiveDateTime;
+use schemars::JsonSchema;
 use serde::Serialize;
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, JsonSchema, Debug, Clone)]
 pub struct NamedId {
     pub id: i32,
     pub name: Option<String>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, JsonSchema, Debug)]
 pub struct AuditLogEntry<T> {
     pub time: NaiveDateTime,
     pub entry_id: i32,
@@ -18,7 +19,7 @@ pub struct AuditLogEntry<T> {
     pub r#type: AuditLogEntryType<T>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, JsonSchema, Debug)]
 pub enum AuditLogEntryType<T> {
     Addition,
     Modification(T),
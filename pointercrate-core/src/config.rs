@@ -6,6 +6,16 @@ pub fn database_url() -> String {
     std::env::var("DATABASE_URL").expect("DATABASE_URL is not set")
 }
 
+/// Either `memory` or `postgres`, see [`RatelimitStorage`](crate::ratelimits::RatelimitStorage)
+pub fn ratelimit_storage() -> String {
+    from_env_or_default("RATELIMIT_STORAGE", "memory".into())
+}
+
+/// The number of workers processing background jobs, see [`JobQueue`](crate::job::JobQueue)
+pub fn job_workers() -> usize {
+    from_env_or_default("JOB_WORKERS", 2)
+}
+
 pub fn secret() -> Vec<u8> {
     let path: String = from_env_or_default("SECRET_FILE", ".secret".into());
 
@@ -1,4 +1,8 @@
-use crate::permission::Permission;
+use crate::{
+    job::JobState,
+    permission::Permission,
+    ratelimits::{ceil_secs, RatelimitInfo},
+};
 use derive_more::Display;
 use log::error;
 use serde::Serialize;
@@ -12,6 +16,24 @@ pub trait PointercrateError: Error + Serialize + From<CoreError> {
     fn status_code(&self) -> u16 {
         self.error_code() / 100
     }
+
+    /// Additional HTTP headers to include in the response for this error
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// One instance of every kind of error this type represents, with placeholder data where
+    /// needed
+    ///
+    /// Used to generate the catalogue of error codes in the API documentation. Errors wrapping
+    /// another [`PointercrateError`] (such as [`CoreError`]) should not repeat that type's
+    /// catalogue.
+    fn catalogue() -> Vec<Self>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
 }
 
 #[derive(Serialize, Display, Debug, Eq, PartialEq, Clone)]
@@ -71,6 +93,15 @@ pub enum CoreError {
     )]
     NotFound,
 
+    /// `404 NOT FOUND` error returned if a background job with the given ID does not exist
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No job with id {} found", job_id)]
+    JobNotFound {
+        /// The ID of the requested job
+        job_id: i32,
+    },
+
     /// `405 METHOD NOT ALLOWED`
     ///
     /// Error Code `40500`
@@ -88,6 +119,16 @@ pub enum CoreError {
     )]
     Conflict,
 
+    /// `409 CONFLICT` variant returned when trying to retry a background job that has not yet
+    /// exhausted its automatic retries
+    ///
+    /// Error Code `40911`
+    #[display(fmt = "Only dead jobs can be retried, but this job is {}", state)]
+    JobNotDead {
+        /// The current state of the job
+        state: JobState,
+    },
+
     /// `411 LENGTH REQUIRED`
     ///
     /// Error Code `41100`
@@ -102,6 +143,24 @@ pub enum CoreError {
     #[display(fmt = "The precondition on the request for the URL failed positive evaluation")]
     PreconditionFailed,
 
+    /// `412 PRECONDITION FAILED`. This variant is returned if the `If-Match` header of a `DELETE` or
+    /// `PATCH` request only contains ETags of a format no longer in use, meaning they can never match
+    ///
+    /// Error Code `41201`
+    #[display(
+        fmt = "The ETag provided in the 'If-Match' header is of an outdated format (version {}, current version is {}). Please re-fetch \
+               the object to obtain an up-to-date ETag",
+        version,
+        current
+    )]
+    OutdatedEtag {
+        /// The version of the ETag provided by the client
+        version: u32,
+
+        /// The ETag version currently in use
+        current: u32,
+    },
+
     /// `413 PAYLOAD TOO LARGE`
     ///
     /// Error Code `41300`
@@ -186,7 +245,16 @@ pub enum CoreError {
         #[serde(skip)]
         message: String,
 
+        /// The time until the next request will be let through
         remaining: Duration,
+
+        /// The number of requests allowed per period
+        #[serde(skip)]
+        limit: u32,
+
+        /// The time until the ratelimit has fully recovered
+        #[serde(skip)]
+        reset: Duration,
     },
 
     /// `500 INTERNAL SERVER ERROR`
@@ -227,10 +295,13 @@ impl PointercrateError for CoreError {
             CoreError::Forbidden => 40300,
             CoreError::MissingPermissions { .. } => 40301,
             CoreError::NotFound => 40400,
+            CoreError::JobNotFound { .. } => 40401,
             CoreError::MethodNotAllowed => 40500,
             CoreError::Conflict => 40900,
+            CoreError::JobNotDead { .. } => 40911,
             CoreError::LengthRequired => 41200,
             CoreError::PreconditionFailed => 41200,
+            CoreError::OutdatedEtag { .. } => 41201,
             CoreError::PayloadTooLarge => 41300,
             CoreError::UnsupportedMediaType { .. } => 41500,
             CoreError::UnprocessableEntity => 42200,
@@ -247,6 +318,68 @@ impl PointercrateError for CoreError {
             CoreError::DatabaseConnectionError => 50005,
         }
     }
+
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            CoreError::Ratelimited {
+                remaining, limit, reset, ..
+            } => {
+                let mut headers = RatelimitInfo {
+                    limit: *limit,
+                    remaining: 0,
+                    reset: *reset,
+                }
+                .headers();
+
+                headers.push(("Retry-After", ceil_secs(*remaining).to_string()));
+                headers
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    fn catalogue() -> Vec<Self> {
+        vec![
+            CoreError::BadRequest,
+            CoreError::InvalidHeaderValue { header: "If-Match" },
+            CoreError::Unauthorized,
+            CoreError::Forbidden,
+            CoreError::MissingPermissions {
+                required: Permission::new("Moderator"),
+            },
+            CoreError::NotFound,
+            CoreError::JobNotFound { job_id: 0 },
+            CoreError::MethodNotAllowed,
+            CoreError::Conflict,
+            CoreError::JobNotDead { state: JobState::Pending },
+            CoreError::LengthRequired,
+            CoreError::PreconditionFailed,
+            CoreError::OutdatedEtag { version: 0, current: 1 },
+            CoreError::PayloadTooLarge,
+            CoreError::UnsupportedMediaType {
+                expected: "application/json",
+            },
+            CoreError::UnprocessableEntity,
+            CoreError::InvalidPaginationLimit,
+            CoreError::InvalidUrlScheme,
+            CoreError::UrlAuthenticated,
+            CoreError::InvalidUrlFormat {
+                expected: "https://www.youtube.com/watch?v=",
+            },
+            CoreError::AfterSmallerBefore,
+            CoreError::MutuallyExclusive,
+            CoreError::PreconditionRequired,
+            CoreError::Ratelimited {
+                message: "You're doing that too often.".to_string(),
+                remaining: Duration::from_secs(60),
+                limit: 1,
+                reset: Duration::from_secs(60),
+            },
+            CoreError::InternalServerError { message: String::new() },
+            CoreError::DatabaseError,
+            CoreError::DatabaseConnectionError,
+        ]
+    }
 }
 
 impl From<sqlx::Error> for CoreError {
@@ -2,39 +2,229 @@
 //!
 //! Note that the format described here is **not part of the public API**.
 //!
-//! A pointercrate ETag value has two parts: A part relevant for `PATCH` requests, which is a hash
-//! of all fields that can be modified via a direct `PATCH` request to the object represented, and a
-//! part relevant for `GET` requests, which is generally just a hash of the complete objects.
+//! A pointercrate ETag value has three parts: The version of the ETag format, a part relevant for
+//! `PATCH` requests, which is a hash of all fields that can be modified via a direct `PATCH` request
+//! to the object represented, and a part relevant for `GET` requests, which is a hash of the
+//! complete object.
 //!
-//! These two parts are unsigned 64 bit integers separated by a semicolon (`;`)
+//! These three parts are separated by semicolons (`;`), e.g. `"2;9f86d081884c7d65;2c26b46b68ffc68f"`.
 //!
-//! The idea is that for `GET` requests only the second part of the ETag is used to determine if a
-//! 304 response should be generated, while for `PATCH` requests only the first part is used to
+//! The idea is that for `GET` requests only the last part of the ETag is used to determine if a
+//! 304 response should be generated, while for `PATCH` requests only the second part is used to
 //! determine whether a `412` should be returned.
 //!
 //! The difference between `GET` and `PATCH` ETag is important for objects where specific subfields
 //! are not modifiable via `PATCH` (e.g. the record list of a player), so having changes to them
 //! cause a `412` is silly, yet for caching purposes, those parts are obviously important.
+//!
+//! Both hashes are (truncated) SHA-256 hashes of the object's JSON representation, meaning they are
+//! stable across restarts and toolchain upgrades, and automatically cover any field added to an
+//! object in the future. Should the way hashes are computed ever change, [`ETAG_VERSION`] needs to
+//! be bumped, so that clients holding ETags computed the old way get a meaningful error instead of a
+//! generic precondition failure.
+//!
+//! Version 1 ETags (those computed before versioning was introduced) had the form `W/"<patch>;<get>"`.
 
 use serde::Serialize;
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// The version of the ETag format currently in use
+pub const ETAG_VERSION: u32 = 2;
+
+/// Trait defining methods for producing the parts of the pointercrate ETag format
+pub trait Taggable: Serialize {
+    /// The top level fields of this object's serialized representation that cannot be modified via
+    /// a direct `PATCH` request (for instance because they are managed via sub-endpoints), and thus
+    /// are not part of the `PATCH` part of the ETag.
+    const UNPATCHABLE_FIELDS: &'static [&'static str] = &[];
+
+    /// The top level fields of this object's serialized representation that embed other objects
+    /// (such as the demon a record is on). Only the `id` of such objects is part of the `PATCH` part
+    /// of the ETag, since changes to the embedded object itself do not change which object is
+    /// referenced.
+    const REFERENCE_FIELDS: &'static [&'static str] = &[];
 
-/// Trait defining methods for producing the two parts of the pointercrate ETag format
-pub trait Taggable: Hash + Serialize {
     fn patch_part(&self) -> u64 {
-        self.get_part()
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+
+        if let Value::Object(ref mut fields) = value {
+            for field in Self::UNPATCHABLE_FIELDS {
+                fields.remove(*field);
+            }
+
+            for field in Self::REFERENCE_FIELDS {
+                if let Some(referenced) = fields.get_mut(*field) {
+                    if let Some(id) = referenced.get("id").cloned() {
+                        *referenced = id;
+                    }
+                }
+            }
+        }
+
+        stable_hash(&value)
     }
 
     fn get_part(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.hash(&mut hasher);
-        hasher.finish()
+        stable_hash(&serde_json::to_value(self).unwrap_or(Value::Null))
     }
 
     fn etag_string(&self) -> String {
-        format!("W/\"{};{}\"", self.patch_part(), self.get_part())
+        format!("\"{};{:016x};{:016x}\"", ETAG_VERSION, self.patch_part(), self.get_part())
+    }
+}
+
+/// Hashes the given JSON value by taking the first 8 bytes of the SHA-256 digest of its serialized
+/// form
+///
+/// Since [`serde_json`]'s maps are ordered by key, this is independent of the order in which object
+/// fields were serialized.
+fn stable_hash(value: &Value) -> u64 {
+    let digest = Sha256::digest(value.to_string().as_bytes());
+    let mut bytes = [0u8; 8];
+
+    bytes.copy_from_slice(&digest[..8]);
+
+    u64::from_be_bytes(bytes)
+}
+
+/// A single entity tag from an `If-Match` or `If-None-Match` header, parsed into its parts
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsedEtag {
+    pub version: u32,
+    pub patch_part: String,
+    pub get_part: String,
+}
+
+impl ParsedEtag {
+    /// Parses a single entity tag. Returns [`None`] if the given value isn't of a format ever
+    /// produced by pointercrate
+    pub fn parse(etag: &str) -> Option<ParsedEtag> {
+        let etag = etag.trim();
+
+        // Version 1 ETags were weak and had no version field
+        if let Some(legacy) = etag.strip_prefix("W/\"").and_then(|etag| etag.strip_suffix('"')) {
+            return match legacy.split(';').collect::<Vec<_>>()[..] {
+                [patch_part, get_part] => Some(ParsedEtag {
+                    version: 1,
+                    patch_part: patch_part.to_string(),
+                    get_part: get_part.to_string(),
+                }),
+                _ => None,
+            };
+        }
+
+        match etag.strip_prefix('"')?.strip_suffix('"')?.split(';').collect::<Vec<_>>()[..] {
+            [version, patch_part, get_part] => Some(ParsedEtag {
+                version: version.parse().ok()?,
+                patch_part: patch_part.to_string(),
+                get_part: get_part.to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::etag::{ParsedEtag, Taggable, ETAG_VERSION};
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Object {
+        name: &'static str,
+        records: Vec<i32>,
+    }
+
+    impl Taggable for Object {
+        const UNPATCHABLE_FIELDS: &'static [&'static str] = &["records"];
+    }
+
+    #[derive(Serialize)]
+    struct Referenced {
+        id: i32,
+        name: &'static str,
+    }
+
+    #[derive(Serialize)]
+    struct Referencing {
+        progress: i16,
+        referenced: Referenced,
+    }
+
+    impl Taggable for Referencing {
+        const REFERENCE_FIELDS: &'static [&'static str] = &["referenced"];
+    }
+
+    #[test]
+    fn test_hash_is_stable() {
+        let object = Object {
+            name: "Bloodbath",
+            records: vec![1, 2, 3],
+        };
+
+        // If this test fails, the way ETags are computed changed, and ETAG_VERSION needs to be bumped
+        assert_eq!(object.etag_string(), "\"2;be49e661b21c963e;f6867213089fcc01\"");
+    }
+
+    #[test]
+    fn test_unpatchable_fields() {
+        let object = Object {
+            name: "Bloodbath",
+            records: vec![],
+        };
+        let other = Object {
+            name: "Bloodbath",
+            records: vec![1],
+        };
+
+        assert_eq!(object.patch_part(), other.patch_part());
+        assert_ne!(object.get_part(), other.get_part());
+    }
+
+    #[test]
+    fn test_reference_fields() {
+        let object = Referencing {
+            progress: 100,
+            referenced: Referenced { id: 1, name: "Bloodbath" },
+        };
+        let renamed = Referencing {
+            progress: 100,
+            referenced: Referenced { id: 1, name: "Bloodlust" },
+        };
+        let other = Referencing {
+            progress: 100,
+            referenced: Referenced { id: 2, name: "Bloodbath" },
+        };
+
+        assert_eq!(object.patch_part(), renamed.patch_part());
+        assert_ne!(object.get_part(), renamed.get_part());
+        assert_ne!(object.patch_part(), other.patch_part());
+    }
+
+    #[test]
+    fn test_parse() {
+        let object = Object {
+            name: "Bloodbath",
+            records: vec![],
+        };
+
+        assert_eq!(
+            ParsedEtag::parse(&object.etag_string()),
+            Some(ParsedEtag {
+                version: ETAG_VERSION,
+                patch_part: format!("{:016x}", object.patch_part()),
+                get_part: format!("{:016x}", object.get_part()),
+            })
+        );
+        assert_eq!(
+            ParsedEtag::parse(" W/\"123;456\""),
+            Some(ParsedEtag {
+                version: 1,
+                patch_part: "123".to_string(),
+                get_part: "456".to_string(),
+            })
+        );
+        assert_eq!(ParsedEtag::parse("\"garbage\""), None);
     }
 }
@@ -0,0 +1,179 @@
+//! Durable background jobs
+//!
+//! Work that should not hold up a request, but also must not get lost if the process dies (such as
+//! validating the video of a record submission), is stored as a job in the `jobs` table. Jobs are
+//! picked up by the worker pool of a [`JobQueue`], which retries failing jobs with exponential
+//! backoff. Jobs that fail too often are declared dead, and stay around until an administrator
+//! retries them. Succeeded jobs are purged after a while.
+
+pub use self::{paginate::JobPagination, worker::JobQueue};
+use crate::error::{CoreError, Result};
+use chrono::NaiveDateTime;
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+mod paginate;
+mod worker;
+
+/// Trait implemented by the payloads of all jobs
+///
+/// The payload is stored as JSON in the database, so it should only contain what is needed to
+/// look up the current state of the involved objects once the job is run.
+pub trait Job: Serialize + DeserializeOwned + Send + 'static {
+    /// Identifies which handler processes jobs of this type
+    const KIND: &'static str;
+
+    /// The number of times the job is attempted before it is declared dead
+    const MAX_ATTEMPTS: i32 = 5;
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// The job is waiting to be run (possibly again, after a failed attempt)
+    Pending,
+
+    /// A worker is currently processing the job
+    Running,
+
+    Succeeded,
+
+    /// All attempts at running the job failed
+    Dead,
+}
+
+impl JobState {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            JobState::Pending => "pending",
+            JobState::Running => "running",
+            JobState::Succeeded => "succeeded",
+            JobState::Dead => "dead",
+        }
+    }
+
+    pub fn from_sql(state: &str) -> Self {
+        match state {
+            "pending" => JobState::Pending,
+            "running" => JobState::Running,
+            "succeeded" => JobState::Succeeded,
+            _ => JobState::Dead,
+        }
+    }
+}
+
+impl Display for JobState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_sql())
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema, Clone, PartialEq)]
+pub struct JobRecord {
+    pub id: i32,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub state: JobState,
+    pub attempts: i32,
+    pub max_attempts: i32,
+
+    /// The earliest time at which the job will be (re)attempted
+    pub run_at: NaiveDateTime,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// Adds a job to the queue
+///
+/// If this happens inside a transaction, workers only see the job once the transaction commits.
+/// Enqueueing a job identical to one that is still pending or currently running does nothing.
+pub async fn enqueue<J: Job>(job: &J, connection: &mut PgConnection) -> Result<()> {
+    enqueue_in(job, Duration::ZERO, connection).await
+}
+
+/// Adds a job to the queue that is not run before the given delay has passed
+///
+/// Behaves like [`enqueue`] otherwise.
+pub async fn enqueue_in<J: Job>(job: &J, delay: Duration, connection: &mut PgConnection) -> Result<()> {
+    insert(job, delay, &["pending", "running"], connection).await
+}
+
+/// Schedules the next run of a periodic job from within the handler of its current run
+///
+/// Unlike [`enqueue_in`], this is not prevented by the current (running) job itself, only by an
+/// identical job that is already pending.
+pub async fn enqueue_successor<J: Job>(job: &J, delay: Duration, connection: &mut PgConnection) -> Result<()> {
+    insert(job, delay, &["pending"], connection).await
+}
+
+/// Inserts the given job, unless an identical job is in one of the given states
+async fn insert<J: Job>(job: &J, delay: Duration, deduplicate: &[&str], connection: &mut PgConnection) -> Result<()> {
+    let payload = serde_json::to_value(job).map_err(|err| CoreError::InternalServerError {
+        message: format!("Failed to serialize payload of {} job: {}", J::KIND, err),
+    })?;
+
+    sqlx::query!(
+        "INSERT INTO jobs (kind, payload, max_attempts, run_at) SELECT $1, $2, $3, NOW() AT TIME ZONE 'utc' + make_interval(secs => $4) \
+         WHERE NOT EXISTS (SELECT 1 FROM jobs WHERE kind = $1 AND payload = $2 AND state = ANY($5))",
+        J::KIND,
+        payload,
+        J::MAX_ATTEMPTS,
+        delay.as_secs_f64(),
+        deduplicate as &[&str]
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+impl JobRecord {
+    pub async fn by_id(job_id: i32, connection: &mut PgConnection) -> Result<JobRecord> {
+        let row = sqlx::query!(
+            "SELECT id, kind, payload, state, attempts, max_attempts, run_at, last_error, created_at, finished_at FROM jobs WHERE id = $1",
+            job_id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match row {
+            Ok(row) => Ok(JobRecord {
+                id: row.id,
+                kind: row.kind,
+                payload: row.payload,
+                state: JobState::from_sql(&row.state),
+                attempts: row.attempts,
+                max_attempts: row.max_attempts,
+                run_at: row.run_at,
+                last_error: row.last_error,
+                created_at: row.created_at,
+                finished_at: row.finished_at,
+            }),
+            Err(sqlx::Error::RowNotFound) => Err(CoreError::JobNotFound { job_id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gives this dead job another full set of attempts, starting right away
+    pub async fn retry(self, connection: &mut PgConnection) -> Result<JobRecord> {
+        if self.state != JobState::Dead {
+            return Err(CoreError::JobNotDead { state: self.state });
+        }
+
+        sqlx::query!(
+            "UPDATE jobs SET state = 'pending', attempts = 0, run_at = NOW() AT TIME ZONE 'utc', locked_until = NULL, finished_at = NULL \
+             WHERE id = $1",
+            self.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        JobRecord::by_id(self.id, connection).await
+    }
+}
@@ -0,0 +1,84 @@
+use super::{JobRecord, JobState};
+use crate::{
+    error::CoreError,
+    pagination::{clamp_i32, PaginationParameters, Paginator, DEFAULT_LIMIT},
+    util::non_nullable,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
+
+#[derive(Deserialize, Debug, Clone, Serialize, JsonSchema)]
+pub struct JobPagination {
+    #[serde(rename = "before", default, deserialize_with = "non_nullable")]
+    pub before_id: Option<i32>,
+
+    #[serde(rename = "after", default, deserialize_with = "non_nullable")]
+    pub after_id: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub limit: Option<u8>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub state: Option<JobState>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub kind: Option<String>,
+}
+
+impl Paginator for JobPagination {
+    type Error = CoreError;
+    type Item = JobRecord;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
+        }
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        JobPagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
+        }
+    }
+
+    fn pagination_key(item: &JobRecord) -> i64 {
+        item.id as i64
+    }
+
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../sql/paginate_jobs.sql"), order)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.before_id)
+            .bind(self.after_id)
+            .bind(self.state.map(JobState::to_sql))
+            .bind(self.kind.as_ref())
+    }
+
+    fn from_row(row: &PgRow) -> Result<JobRecord, sqlx::Error> {
+        Ok(JobRecord {
+            id: row.try_get("id")?,
+            kind: row.try_get("kind")?,
+            payload: row.try_get("payload")?,
+            state: JobState::from_sql(row.try_get("state")?),
+            attempts: row.try_get("attempts")?,
+            max_attempts: row.try_get("max_attempts")?,
+            run_at: row.try_get("run_at")?,
+            last_error: row.try_get("last_error")?,
+            created_at: row.try_get("created_at")?,
+            finished_at: row.try_get("finished_at")?,
+        })
+    }
+}
@@ -0,0 +1,258 @@
+use super::Job;
+use crate::error::Result;
+use futures::future::BoxFuture;
+use log::{debug, error, info, warn};
+use sqlx::{Pool, Postgres};
+use std::{any::Any, collections::HashMap, future::Future, sync::Arc, time::Duration};
+
+/// How long a worker waits before looking for new jobs after finding none
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a single attempt at running a job may take before it is considered failed
+const JOB_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The delay before the first retry of a failed job. Each further retry doubles it
+const BACKOFF_BASE: Duration = Duration::from_secs(30);
+
+const BACKOFF_MAX: Duration = Duration::from_secs(3600);
+
+/// How long succeeded jobs are kept around (e.g. for inspection via the API) before being purged
+const SUCCEEDED_RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// How often succeeded jobs past their retention are purged
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+type Handler = Box<dyn Fn(serde_json::Value) -> BoxFuture<'static, std::result::Result<(), String>> + Send + Sync>;
+
+/// A pool of workers processing the jobs of all kinds for which a handler has been registered
+///
+/// Jobs of kinds without a handler stay in the queue untouched, so multiple processes can share
+/// a database while each processing only the jobs they know about.
+pub struct JobQueue {
+    pool: Pool<Postgres>,
+    handlers: HashMap<&'static str, Handler>,
+}
+
+impl JobQueue {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        JobQueue {
+            pool,
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Registers the function processing jobs of type `J`
+    ///
+    /// An `Err` returned from the handler (or a handler panicking or taking longer than 5 minutes)
+    /// causes the job to be retried later. The error message is stored with the job.
+    pub fn register<J, F, Fut>(mut self, handler: F) -> Self
+    where
+        J: Job,
+        F: Fn(J) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), String>> + Send + 'static,
+    {
+        self.handlers.insert(
+            J::KIND,
+            Box::new(move |payload| match serde_json::from_value::<J>(payload) {
+                Ok(job) => Box::pin(handler(job)),
+                Err(err) => Box::pin(futures::future::ready(Err(format!("Malformed payload: {}", err)))),
+            }),
+        );
+        self
+    }
+
+    /// Spawns the given number of workers onto the current tokio runtime
+    pub fn start(self, workers: usize) {
+        info!(
+            "Starting {} job workers for job kinds {:?}",
+            workers,
+            self.handlers.keys().collect::<Vec<_>>()
+        );
+
+        let queue = Arc::new(self);
+        let purger = Arc::clone(&queue);
+
+        tokio::spawn(async move {
+            loop {
+                match purger.purge_succeeded().await {
+                    Ok(0) => (),
+                    Ok(purged) => info!("Purged {} succeeded jobs", purged),
+                    Err(err) => error!("Failed to purge succeeded jobs: {:?}", err),
+                }
+
+                tokio::time::sleep(PURGE_INTERVAL).await
+            }
+        });
+
+        for _ in 0..workers {
+            let queue = Arc::clone(&queue);
+
+            tokio::spawn(async move {
+                loop {
+                    match queue.run_next().await {
+                        Ok(true) => (),
+                        Ok(false) => tokio::time::sleep(POLL_INTERVAL).await,
+                        Err(err) => {
+                            error!("Job worker failed to access the job queue: {:?}", err);
+
+                            tokio::time::sleep(POLL_INTERVAL).await
+                        },
+                    }
+                }
+            });
+        }
+    }
+
+    /// Claims and runs a single job, if one is due
+    ///
+    /// Returns whether a job was run.
+    pub async fn run_next(&self) -> Result<bool> {
+        let kinds = self.handlers.keys().map(|kind| kind.to_string()).collect::<Vec<_>>();
+
+        // Jobs stuck in 'running' with an expired lock were claimed by a worker that died. Those that
+        // already used up all their attempts are not reclaimed, but declared dead.
+        let abandoned = sqlx::query!(
+            "UPDATE jobs SET state = 'dead', locked_until = NULL, last_error = 'The worker running the job died', finished_at = NOW() AT \
+             TIME ZONE 'utc' WHERE kind = ANY($1) AND state = 'running' AND locked_until <= NOW() AT TIME ZONE 'utc' AND attempts >= \
+             max_attempts RETURNING id, kind",
+            &kinds[..]
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for job in abandoned {
+            error!("{} job {} was abandoned on its last attempt, giving up", job.kind, job.id);
+        }
+
+        let claimed = sqlx::query!(
+            r#"UPDATE jobs SET state = 'running', attempts = attempts + 1, locked_until = NOW() AT TIME ZONE 'utc' + make_interval(secs => $2)
+            WHERE id = (
+                SELECT id FROM jobs
+                WHERE kind = ANY($1)
+                  AND ((state = 'pending' AND run_at <= NOW() AT TIME ZONE 'utc')
+                    OR (state = 'running' AND locked_until <= NOW() AT TIME ZONE 'utc' AND attempts < max_attempts))
+                ORDER BY run_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, kind, payload, attempts, max_attempts"#,
+            &kinds[..],
+            (JOB_TIMEOUT + POLL_INTERVAL).as_secs_f64()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let job = match claimed {
+            Some(job) => job,
+            None => return Ok(false),
+        };
+
+        debug!("Running {} job {} (attempt {})", job.kind, job.id, job.attempts);
+
+        let outcome = match self.handlers.get(&job.kind[..]) {
+            Some(handler) => {
+                // Handlers run in their own task, so that a panicking handler only fails its job instead of
+                // taking down the worker
+                let mut task = tokio::spawn(handler(job.payload));
+
+                match tokio::time::timeout(JOB_TIMEOUT, &mut task).await {
+                    Ok(Ok(outcome)) => outcome,
+                    Ok(Err(err)) if err.is_panic() => Err(format!("Panicked: {}", panic_message(err.into_panic()))),
+                    Ok(Err(err)) => Err(err.to_string()),
+                    Err(_) => {
+                        task.abort();
+
+                        Err(format!("Timed out after {:?}", JOB_TIMEOUT))
+                    },
+                }
+            },
+            None => Err(format!("No handler registered for jobs of kind '{}'", job.kind)),
+        };
+
+        match outcome {
+            Ok(()) => {
+                sqlx::query!(
+                    "UPDATE jobs SET state = 'succeeded', locked_until = NULL, finished_at = NOW() AT TIME ZONE 'utc' WHERE id = $1",
+                    job.id
+                )
+                .execute(&self.pool)
+                .await?;
+            },
+            Err(message) if job.attempts >= job.max_attempts => {
+                error!("{} job {} failed for the last time, giving up: {}", job.kind, job.id, message);
+
+                sqlx::query!(
+                    "UPDATE jobs SET state = 'dead', locked_until = NULL, last_error = $2, finished_at = NOW() AT TIME ZONE 'utc' WHERE id = $1",
+                    job.id,
+                    message
+                )
+                .execute(&self.pool)
+                .await?;
+            },
+            Err(message) => {
+                let delay = backoff(job.attempts);
+
+                warn!("{} job {} failed, retrying in {:?}: {}", job.kind, job.id, delay, message);
+
+                sqlx::query!(
+                    "UPDATE jobs SET state = 'pending', locked_until = NULL, last_error = $2, run_at = NOW() AT TIME ZONE 'utc' + \
+                     make_interval(secs => $3) WHERE id = $1",
+                    job.id,
+                    message,
+                    delay.as_secs_f64()
+                )
+                .execute(&self.pool)
+                .await?;
+            },
+        }
+
+        Ok(true)
+    }
+
+    /// Deletes all succeeded jobs (of any kind) that finished longer ago than the retention period
+    ///
+    /// Returns the number of deleted jobs. Called periodically once the queue is [started](JobQueue::start).
+    pub async fn purge_succeeded(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM jobs WHERE state = 'succeeded' AND finished_at < NOW() AT TIME ZONE 'utc' - make_interval(secs => $1)",
+            SUCCEEDED_RETENTION.as_secs_f64()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// The message a handler panicked with, if it was a string
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "<non-string panic payload>".to_string(),
+        },
+    }
+}
+
+/// The delay before retrying a job that has failed the given number of times
+fn backoff(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+
+    (BACKOFF_BASE * 2u32.pow(exponent)).min(BACKOFF_MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::backoff;
+    use std::time::Duration;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(30));
+        assert_eq!(backoff(2), Duration::from_secs(60));
+        assert_eq!(backoff(4), Duration::from_secs(240));
+        assert_eq!(backoff(8), Duration::from_secs(3600));
+        assert_eq!(backoff(i32::MAX), Duration::from_secs(3600));
+    }
+}
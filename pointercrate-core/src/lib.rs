@@ -2,8 +2,11 @@ pub mod audit;
 pub mod config;
 pub mod error;
 pub mod etag;
+pub mod job;
+pub mod pagination;
 pub mod permission;
 pub mod pool;
 pub mod util;
+pub mod video;
 #[macro_use]
 pub mod ratelimits;
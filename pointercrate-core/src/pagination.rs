@@ -0,0 +1,202 @@
+//! Cursor based pagination of database objects
+//!
+//! All paginated endpoints page through a table ordered by some integer column (usually the primary
+//! key). A page is selected by giving exclusive `before` and `after` bounds on that column, and a
+//! `limit` on the number of objects returned. If only `before` is given, the objects directly
+//! preceding it are returned (meaning the query runs with descending order). If both are given, the
+//! objects following `after` are returned, but never one at or past `before`.
+
+use crate::error::CoreError;
+use futures::{stream::BoxStream, TryStreamExt};
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgConnection, Postgres, Row,
+};
+use std::fmt::{Debug, Display};
+
+/// The number of objects on a page if no explicit `limit` is requested
+pub const DEFAULT_LIMIT: u8 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaginationParameters {
+    pub before: Option<i64>,
+    pub after: Option<i64>,
+    pub limit: u8,
+}
+
+impl PaginationParameters {
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if !(1..=100).contains(&self.limit) {
+            return Err(CoreError::InvalidPaginationLimit);
+        }
+
+        if let (Some(before), Some(after)) = (self.before, self.after) {
+            if before < after {
+                return Err(CoreError::AfterSmallerBefore);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The order in which the database has to return objects to produce the page described by these
+    /// parameters
+    ///
+    /// Only if just `before` is set do we go backwards from it.
+    pub fn order(&self) -> &'static str {
+        if self.before.is_some() && self.after.is_none() {
+            "DESC"
+        } else {
+            "ASC"
+        }
+    }
+
+    pub fn is_descending(&self) -> bool {
+        self.order() == "DESC"
+    }
+}
+
+/// Converts a pagination bound to a column of type `INT`, saturating at the type's limits
+pub fn clamp_i32(value: Option<i64>) -> Option<i32> {
+    value.map(|value| value.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+}
+
+/// Converts a pagination bound to a column of type `SMALLINT`, saturating at the type's limits
+pub fn clamp_i16(value: Option<i64>) -> Option<i16> {
+    value.map(|value| value.clamp(i16::MIN as i64, i16::MAX as i64) as i16)
+}
+
+/// Trait implemented by the query structs of all paginated endpoints
+///
+/// Implementors provide the SQL query selecting their objects, which needs to
+/// * take the `before` and `after` bounds as its first two parameters,
+/// * contain a single `{}` placeholder in its `ORDER BY` clause, into which the order returned by
+///   [`PaginationParameters::order`] is inserted,
+/// * end with a `LIMIT` on its last parameter (the last line may be an SQL comment).
+///
+/// From this, [`Paginator::page`] and [`Paginator::total_count`] are derived.
+#[async_trait::async_trait]
+pub trait Paginator: Serialize + Debug + Clone + Send + Sync + Sized {
+    type Item: Serialize + JsonSchema + Send;
+    type Error: From<CoreError> + From<sqlx::Error> + Display + Send;
+
+    fn parameters(&self) -> PaginationParameters;
+
+    /// Creates a copy of this paginator that keeps all filters, but has its pagination parameters
+    /// replaced with the given ones
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self;
+
+    /// The value of the column paginated over for the given object
+    fn pagination_key(item: &Self::Item) -> i64;
+
+    /// The SQL query selecting a page, with the ordering inserted
+    fn query(&self, order: &str) -> String;
+
+    /// Binds all parameters of [`Paginator::query`] except for the final limit
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments>;
+
+    fn from_row(row: &PgRow) -> Result<Self::Item, sqlx::Error>;
+
+    /// Retrieves the page of objects matching the pagination data in here
+    ///
+    /// Note that this method returns _one more object than requested_. This is used as a quick and
+    /// dirty way to determine if further pages exist: If the additional object was returned, more
+    /// pages obviously exist. This additional object is the last in the returned vector.
+    ///
+    /// Additionally, if _before_ is set, but not _after_, the page is returned in reverse order
+    /// (the additional object stays the last)
+    async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Self::Item>, Self::Error> {
+        let parameters = self.parameters();
+
+        parameters.validate()?;
+
+        let query = self.query(parameters.order());
+        let rows = self
+            .bind(sqlx::query(&query))
+            .bind(parameters.limit as i32 + 1)
+            .fetch_all(connection)
+            .await?;
+
+        Ok(rows.iter().map(Self::from_row).collect::<Result<_, _>>()?)
+    }
+
+    /// Counts the objects matching the filters of this paginator, regardless of `before`, `after` and
+    /// `limit`
+    async fn total_count(&self, connection: &mut PgConnection) -> Result<i64, Self::Error> {
+        let unbounded = self.with_parameters(PaginationParameters {
+            before: None,
+            after: None,
+            ..self.parameters()
+        });
+
+        // The newline makes sure the closing parenthesis isn't swallowed by a trailing comment
+        let query = format!("SELECT COUNT(*) FROM ({}\n) AS counted", unbounded.query("ASC"));
+        let row = unbounded.bind(sqlx::query(&query)).bind(None::<i32>).fetch_one(connection).await?;
+
+        Ok(row.try_get(0)?)
+    }
+
+    /// Streams all objects matching the filters of this paginator in ascending order
+    ///
+    /// Unlike [`Paginator::page`], this ignores `limit` (and thus also the cap of 100 objects per
+    /// page), and rows are converted as they arrive from the database instead of being collected
+    /// first. The `before` and `after` bounds still apply.
+    fn stream<'a>(&'a self, connection: &'a mut PgConnection) -> BoxStream<'a, Result<Self::Item, Self::Error>> {
+        Box::pin(async_stream::try_stream! {
+            let query = self.query("ASC");
+            let mut rows = self.bind(sqlx::query(&query)).bind(None::<i32>).fetch(connection);
+
+            while let Some(row) = rows.try_next().await? {
+                yield Self::from_row(&row)?;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{clamp_i16, clamp_i32, PaginationParameters};
+    use crate::error::CoreError;
+
+    fn parameters(before: Option<i64>, after: Option<i64>, limit: u8) -> PaginationParameters {
+        PaginationParameters { before, after, limit }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(parameters(None, None, 50).validate().is_ok());
+        assert!(parameters(Some(10), Some(10), 1).validate().is_ok());
+        assert!(matches!(
+            parameters(None, None, 0).validate(),
+            Err(CoreError::InvalidPaginationLimit)
+        ));
+        assert!(matches!(
+            parameters(None, None, 101).validate(),
+            Err(CoreError::InvalidPaginationLimit)
+        ));
+        assert!(matches!(
+            parameters(Some(5), Some(10), 50).validate(),
+            Err(CoreError::AfterSmallerBefore)
+        ));
+    }
+
+    #[test]
+    fn test_order() {
+        assert_eq!(parameters(None, None, 50).order(), "ASC");
+        assert_eq!(parameters(None, Some(1), 50).order(), "ASC");
+        assert_eq!(parameters(Some(10), Some(1), 50).order(), "ASC");
+        assert_eq!(parameters(Some(10), None, 50).order(), "DESC");
+    }
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(clamp_i32(Some(i64::MAX)), Some(i32::MAX));
+        assert_eq!(clamp_i32(Some(i64::MIN)), Some(i32::MIN));
+        assert_eq!(clamp_i16(Some(100_000)), Some(i16::MAX));
+        assert_eq!(clamp_i16(Some(5)), Some(5));
+        assert_eq!(clamp_i16(None), None);
+    }
+}
@@ -1,135 +1,196 @@
 use crate::error::CoreError;
 use derive_more::Display;
 use serde::Serialize;
-use std::collections::{HashMap, HashSet};
-
+use sqlx::PgConnection;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// How long a [`PermissionsCache`] keeps using roles loaded from the database
+const PERMISSIONS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// A permission that is required by some piece of code
+///
+/// Permissions are granted to users via the role of the same name. Roles are stored in the database,
+/// see [`PermissionsManager::load`]
 #[derive(Serialize, Debug, Display, Eq, PartialEq, Clone, Copy, Hash)]
 #[serde(transparent)]
 #[display(fmt = "{}", name)]
 pub struct Permission {
     name: &'static str,
-
-    #[serde(skip)]
-    bit: u16,
 }
 
 impl Permission {
-    pub const fn new(name: &'static str, bit: u16) -> Permission {
-        Permission { name, bit }
+    pub const fn new(name: &'static str) -> Permission {
+        Permission { name }
     }
 
     pub fn name(&self) -> &str {
         self.name
     }
-
-    pub fn bit(&self) -> u16 {
-        self.bit
-    }
-}
-
-impl From<Permission> for u16 {
-    fn from(perm: Permission) -> Self {
-        perm.bit
-    }
 }
 
-#[derive(Clone)]
+/// The roles known to pointercrate, together with the rules of which roles imply which other roles,
+/// and which roles can be assigned to other users by holders of a role
+#[derive(Clone, Default, Debug)]
 pub struct PermissionsManager {
-    permissions: HashSet<Permission>,
-    implication_map: HashMap<Permission, HashSet<Permission>>,
-    assignable_map: HashMap<Permission, HashSet<Permission>>,
+    /// Maps each role to the bit it occupies in the legacy permission bitmask, if any
+    roles: HashMap<String, Option<u16>>,
+    implication_map: HashMap<String, HashSet<String>>,
+    assignable_map: HashMap<String, HashSet<String>>,
 }
 
 impl PermissionsManager {
     pub fn new(permissions: Vec<Permission>) -> Self {
-        let mut permission_set = HashSet::new();
-
-        for perm in permissions {
-            permission_set.insert(perm);
-        }
-
         PermissionsManager {
-            permissions: permission_set,
+            roles: permissions.into_iter().map(|perm| (perm.name.to_string(), None)).collect(),
             implication_map: HashMap::new(),
             assignable_map: HashMap::new(),
         }
     }
 
+    /// Loads all roles and their implication and assignment rules from the database
+    pub async fn load(connection: &mut PgConnection) -> Result<Self, CoreError> {
+        let mut manager = PermissionsManager::default();
+
+        for row in sqlx::query!("SELECT name, legacy_bit FROM roles")
+            .fetch_all(&mut *connection)
+            .await?
+        {
+            manager.roles.insert(row.name, row.legacy_bit.map(|bit| bit as u16));
+        }
+
+        let implications = sqlx::query!(
+            "SELECT r.name AS role, i.name AS implied FROM role_implications INNER JOIN roles r ON r.id = role_implications.role INNER \
+             JOIN roles i ON i.id = role_implications.implied"
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        for row in implications {
+            manager.implication_map.entry(row.role).or_default().insert(row.implied);
+        }
+
+        let assignments = sqlx::query!(
+            "SELECT r.name AS role, a.name AS assignable FROM role_assignments INNER JOIN roles r ON r.id = role_assignments.role INNER \
+             JOIN roles a ON a.id = role_assignments.assignable"
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+
+        for row in assignments {
+            manager.assignable_map.entry(row.role).or_default().insert(row.assignable);
+        }
+
+        Ok(manager)
+    }
+
     // we should probably verify that added permissions are all part of what was in the constructor but
     // whatever
     pub fn assigns(mut self, perm1: Permission, perm2: Permission) -> Self {
-        self.assignable_map.entry(perm1).or_insert_with(HashSet::new).insert(perm2);
+        self.assignable_map
+            .entry(perm1.name.to_string())
+            .or_default()
+            .insert(perm2.name.to_string());
         self
     }
 
     pub fn implies(mut self, perm1: Permission, perm2: Permission) -> Self {
-        self.implication_map.entry(perm1).or_insert_with(HashSet::new).insert(perm2);
+        self.implication_map
+            .entry(perm1.name.to_string())
+            .or_default()
+            .insert(perm2.name.to_string());
         self
     }
 
-    pub fn implied_by(&self, permission: Permission) -> HashSet<Permission> {
+    /// Gets the names of all roles known to this manager
+    pub fn roles(&self) -> impl Iterator<Item = &String> {
+        self.roles.keys()
+    }
+
+    /// Computes the set of roles implied by the given role, including the role itself
+    ///
+    /// Since implication rules can be edited at runtime, they might contain cycles. These are
+    /// tolerated, all roles on a cycle simply imply each other.
+    pub fn implied_by(&self, role: &str) -> HashSet<String> {
         let mut implied = HashSet::new();
-        implied.insert(permission);
+        let mut to_visit = vec![role.to_string()];
 
-        if let Some(set) = self.implication_map.get(&permission) {
-            for perm in set {
-                implied.extend(self.implied_by(*perm));
+        while let Some(role) = to_visit.pop() {
+            if let Some(set) = self.implication_map.get(&role) {
+                to_visit.extend(set.iter().filter(|implied_role| !implied.contains(*implied_role)).cloned());
             }
+
+            implied.insert(role);
         }
 
         implied
     }
 
-    pub fn assignable_by(&self, permission: Permission) -> HashSet<Permission> {
+    pub fn assignable_by(&self, role: &str) -> HashSet<String> {
         let mut assignable = HashSet::new();
 
-        for perm in self.implied_by(permission) {
-            if let Some(set) = self.assignable_map.get(&perm) {
-                for perm in set {
-                    assignable.insert(*perm);
-                }
+        for role in self.implied_by(role) {
+            if let Some(set) = self.assignable_map.get(&role) {
+                assignable.extend(set.iter().cloned());
             }
         }
 
         assignable
     }
 
-    pub fn implied_by_bits(&self, permission_bits: u16) -> HashSet<Permission> {
+    pub fn implied_by_roles(&self, roles: &[String]) -> HashSet<String> {
         let mut implied = HashSet::new();
 
-        for perm in self.bits_to_permissions(permission_bits) {
-            if perm.bit & permission_bits == perm.bit {
-                implied.extend(self.implied_by(perm));
-            }
+        for role in roles {
+            implied.extend(self.implied_by(role));
         }
 
         implied
     }
 
-    pub fn assignable_by_bits(&self, permission_bits: u16) -> HashSet<Permission> {
+    pub fn assignable_by_roles(&self, roles: &[String]) -> HashSet<String> {
         let mut assignable = HashSet::new();
 
-        for perm in self.bits_to_permissions(permission_bits) {
-            assignable.extend(self.assignable_by(perm));
+        for role in roles {
+            assignable.extend(self.assignable_by(role));
         }
 
         assignable
     }
 
-    pub fn bits_to_permissions(&self, bits: u16) -> HashSet<Permission> {
-        let mut perms = HashSet::new();
+    /// Gets the bit the given role occupies in the legacy permission bitmask, if any
+    pub fn legacy_bit(&self, role: &str) -> Option<u16> {
+        self.roles.get(role).copied().flatten()
+    }
 
-        for perm in &self.permissions {
-            if perm.bit() & bits == perm.bit() {
-                perms.insert(*perm);
-            }
-        }
+    /// Converts the given roles into a legacy permission bitmask. Roles without a legacy bit are
+    /// ignored
+    pub fn legacy_bits<'a>(&self, roles: impl IntoIterator<Item = &'a String>) -> u16 {
+        roles
+            .into_iter()
+            .filter_map(|role| self.legacy_bit(role))
+            .fold(0, |mask, bit| mask | bit)
+    }
 
-        perms
+    /// Converts a legacy permission bitmask into the set of roles it represents
+    pub fn bits_to_roles(&self, bits: u16) -> HashSet<String> {
+        self.roles
+            .iter()
+            .filter_map(|(role, bit)| match bit {
+                Some(bit) if bit & bits == *bit => Some(role.clone()),
+                _ => None,
+            })
+            .collect()
     }
 
-    pub fn require_permission(&self, permissions_we_have: u16, permission_required: Permission) -> Result<(), CoreError> {
-        if !self.implied_by_bits(permissions_we_have).contains(&permission_required) {
+    pub fn require_permission(&self, roles_we_have: &[String], permission_required: Permission) -> Result<(), CoreError> {
+        if !self.implied_by_roles(roles_we_have).contains(permission_required.name) {
             return Err(CoreError::MissingPermissions {
                 required: permission_required,
             });
@@ -139,6 +200,56 @@ impl PermissionsManager {
     }
 }
 
+/// Keeps the [`PermissionsManager`] loaded from the database around between requests
+///
+/// Roles rarely change, so they are only reloaded once they are older than a minute, or after
+/// [`PermissionsCache::invalidate`] was called. Changes made by other processes sharing the database
+/// thus take up to a minute to be picked up.
+#[derive(Default)]
+pub struct PermissionsCache {
+    cached: RwLock<Option<(Instant, Arc<PermissionsManager>)>>,
+
+    /// Incremented on every invalidation, so that loads racing with an invalidation do not end up
+    /// in the cache
+    generation: AtomicU64,
+}
+
+impl PermissionsCache {
+    /// Gets the cached [`PermissionsManager`], loading it from the database if needed
+    pub async fn get(&self, connection: &mut PgConnection) -> Result<Arc<PermissionsManager>, CoreError> {
+        let cached = self
+            .cached
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < PERMISSIONS_CACHE_TTL)
+            .map(|(_, manager)| Arc::clone(manager));
+
+        if let Some(manager) = cached {
+            return Ok(manager);
+        }
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let manager = Arc::new(PermissionsManager::load(connection).await?);
+        let mut cached = self.cached.write().unwrap();
+
+        if self.generation.load(Ordering::SeqCst) == generation {
+            *cached = Some((Instant::now(), Arc::clone(&manager)));
+        }
+
+        Ok(manager)
+    }
+
+    /// Makes the next call to [`PermissionsCache::get`] reload all roles. Must be called after roles
+    /// or their implication and assignment rules changed
+    pub fn invalidate(&self) {
+        let mut cached = self.cached.write().unwrap();
+
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *cached = None;
+    }
+}
+
 #[cfg(test)]
 mod test {
     // copied from https://riptutorial.com/rust/example/4149/create-a-hashset-macro because im lazy as fuck
@@ -147,7 +258,7 @@ mod test {
             {
                 let mut temp_set = HashSet::new();  // Create a mutable HashSet
                 $(
-                    temp_set.insert($x); // Insert each item matched into the HashSet
+                    temp_set.insert($x.name().to_string()); // Insert each item matched into the HashSet
                 )*
                 temp_set // Return the populated HashSet
             }
@@ -157,12 +268,12 @@ mod test {
     use crate::permission::{Permission, PermissionsManager};
     use std::collections::HashSet;
 
-    const PERM1: Permission = Permission::new("1", 0x1);
-    const PERM2: Permission = Permission::new("2", 0x2);
-    const PERM3: Permission = Permission::new("3", 0x4);
-    const PERM4: Permission = Permission::new("4", 0x8);
-    const PERM5: Permission = Permission::new("5", 0x10);
-    const PERM6: Permission = Permission::new("6", 0x20);
+    const PERM1: Permission = Permission::new("1");
+    const PERM2: Permission = Permission::new("2");
+    const PERM3: Permission = Permission::new("3");
+    const PERM4: Permission = Permission::new("4");
+    const PERM5: Permission = Permission::new("5");
+    const PERM6: Permission = Permission::new("6");
 
     fn permission_manager() -> PermissionsManager {
         PermissionsManager::new(vec![PERM1, PERM2, PERM3, PERM4, PERM5])
@@ -177,17 +288,24 @@ mod test {
 
     #[test]
     fn test_implication() {
-        assert_eq!(permission_manager().implied_by(PERM1), set![PERM1, PERM2, PERM3]);
-        assert_eq!(permission_manager().implied_by(PERM4), set![PERM4, PERM5]);
+        assert_eq!(permission_manager().implied_by("1"), set![PERM1, PERM2, PERM3]);
+        assert_eq!(permission_manager().implied_by("4"), set![PERM4, PERM5]);
 
         assert_eq!(
-            permission_manager().implied_by_bits(0x1 | 0x8),
+            permission_manager().implied_by_roles(&["1".to_string(), "4".to_string()]),
             set![PERM1, PERM2, PERM3, PERM4, PERM5,]
         );
     }
 
+    #[test]
+    fn test_implication_cycle() {
+        let manager = permission_manager().implies(PERM3, PERM1);
+
+        assert_eq!(manager.implied_by("2"), set![PERM1, PERM2, PERM3]);
+    }
+
     #[test]
     fn test_assignment() {
-        assert_eq!(permission_manager().assignable_by(PERM4), set![PERM2, PERM5, PERM6]);
+        assert_eq!(permission_manager().assignable_by("4"), set![PERM2, PERM5, PERM6]);
     }
 }
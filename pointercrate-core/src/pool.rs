@@ -1,9 +1,10 @@
-use crate::{config, error::Result};
+use crate::{config, error::Result, permission::PermissionsCache};
 use log::trace;
 use sqlx::{pool::PoolConnection, postgres::PgPoolOptions, PgConnection, Pool, Postgres, Transaction};
 
 pub struct PointercratePool {
     connection_pool: Pool<Postgres>,
+    permissions: PermissionsCache,
 }
 
 impl PointercratePool {
@@ -11,6 +12,11 @@ impl PointercratePool {
         self.connection_pool.clone()
     }
 
+    /// The roles stored in this pool's database, see [`PermissionsCache`]
+    pub fn permissions(&self) -> &PermissionsCache {
+        &self.permissions
+    }
+
     pub async fn init() -> Self {
         let pool = PointercratePool {
             connection_pool: PgPoolOptions::default()
@@ -18,6 +24,7 @@ impl PointercratePool {
                 .connect(&config::database_url())
                 .await
                 .expect("Failed to connect to pointercrate database"),
+            permissions: PermissionsCache::default(),
         };
 
         pool.run_migrations().await;
@@ -75,7 +82,10 @@ AND NOT EXISTS (
 // Used for integration tests, when sqlx::test sets up a pool for us
 impl From<Pool<Postgres>> for PointercratePool {
     fn from(connection_pool: Pool<Postgres>) -> Self {
-        PointercratePool { connection_pool }
+        PointercratePool {
+            connection_pool,
+            permissions: PermissionsCache::default(),
+        }
     }
 }
 
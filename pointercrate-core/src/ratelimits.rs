@@ -1,14 +1,180 @@
+use crate::{config, error::CoreError, pool::PointercratePool};
+use derive_more::Display;
+use sqlx::{Pool, Postgres};
+use std::{
+    fmt::{Display, Formatter},
+    net::IpAddr,
+    time::Duration,
+};
+
+/// Where the buckets of the ratelimits generated by [`ratelimits!`] are stored
+pub enum RatelimitStorage {
+    /// Buckets are kept in process memory. They are reset on every restart and not shared between
+    /// processes. This is the default, and what tests use
+    InMemory,
+
+    /// Buckets are persisted in the `ratelimit_buckets` table, meaning they survive restarts and are
+    /// shared between all processes connected to the same database
+    Postgres(Pool<Postgres>),
+}
+
+impl RatelimitStorage {
+    /// Selects the storage backend based on the `RATELIMIT_STORAGE` environment variable
+    ///
+    /// Fails if the variable names an unknown storage, or if `postgres` storage is requested but no
+    /// database pool is given
+    pub fn from_env(pool: Option<&PointercratePool>) -> Result<Self, RatelimitStorageError> {
+        match &config::ratelimit_storage()[..] {
+            "postgres" => pool
+                .map(|pool| RatelimitStorage::Postgres(pool.clone_inner()))
+                .ok_or(RatelimitStorageError::MissingPool),
+            "memory" => Ok(RatelimitStorage::InMemory),
+            other => Err(RatelimitStorageError::UnknownStorage(other.to_string())),
+        }
+    }
+}
+
+/// Error returned by [`RatelimitStorage::from_env`] if the configured storage cannot be set up
+#[derive(Debug, Display, Eq, PartialEq)]
+pub enum RatelimitStorageError {
+    #[display(fmt = "RATELIMIT_STORAGE is set to 'postgres', but no database pool is available during setup")]
+    MissingPool,
+
+    #[display(fmt = "Unknown RATELIMIT_STORAGE '{}', expected 'memory' or 'postgres'", _0)]
+    UnknownStorage(String),
+}
+
+impl std::error::Error for RatelimitStorageError {}
+
+/// Key of ratelimits declared `per user`
+///
+/// Requests by authenticated users are limited per user account, so that users sharing an IP address
+/// do not lock each other out. Anonymous requests fall back to being limited per IP address.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum RatelimitKey {
+    User(i32),
+    Ip(IpAddr),
+}
+
+impl RatelimitKey {
+    pub fn new(user_id: Option<i32>, ip: IpAddr) -> Self {
+        match user_id {
+            Some(user_id) => RatelimitKey::User(user_id),
+            None => RatelimitKey::Ip(ip),
+        }
+    }
+}
+
+impl Display for RatelimitKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RatelimitKey::User(user_id) => write!(f, "user:{}", user_id),
+            RatelimitKey::Ip(ip) => write!(f, "ip:{}", ip),
+        }
+    }
+}
+
+/// The state of a ratelimit after it let a request through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RatelimitInfo {
+    /// The number of requests allowed per period
+    pub limit: u32,
+
+    /// The number of further requests that would be let through right now
+    pub remaining: u32,
+
+    /// The time until the ratelimit has fully recovered, meaning `remaining` is back at `limit`
+    pub reset: Duration,
+}
+
+impl RatelimitInfo {
+    /// The state of a limiter allowing `capacity` requests per `period` seconds, with the given
+    /// number of requests remaining
+    ///
+    /// Each request taken from a limiter is refilled after `period / capacity` seconds.
+    pub fn refilling(capacity: u32, period: u64, remaining: u32) -> Self {
+        RatelimitInfo {
+            limit: capacity,
+            remaining,
+            reset: Duration::from_secs(period) / capacity * capacity.saturating_sub(remaining),
+        }
+    }
+
+    /// The `X-RateLimit-*` headers describing this state
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("X-RateLimit-Limit", self.limit.to_string()),
+            ("X-RateLimit-Remaining", self.remaining.to_string()),
+            ("X-RateLimit-Reset", ceil_secs(self.reset).to_string()),
+        ]
+    }
+}
+
+/// Rounds up to full seconds, so that clients waiting exactly this long do not hit the limit again
+pub(crate) fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+/// The error returned by a limiter allowing `capacity` requests per `period` seconds for a request
+/// that cannot be let through before `retry_after` has passed
+pub fn ratelimited(message: &str, capacity: u32, period: u64, retry_after: Duration) -> CoreError {
+    // Once the next request is allowed, the limiter still needs to refill all other requests
+    let reset = retry_after + RatelimitInfo::refilling(capacity, period, 1).reset;
+
+    CoreError::Ratelimited {
+        message: message.to_string(),
+        remaining: retry_after,
+        limit: capacity,
+        reset,
+    }
+}
+
+/// Takes a token from the bucket with the given key of the persistent limiter with the given name
+///
+/// The limiter allows `capacity` requests per `period` seconds, and behaves exactly like the
+/// in-memory governor limiters generated by [`ratelimits!`]. Each check also deletes a few buckets
+/// that have refilled completely (of any limiter), since those are indistinguishable from buckets
+/// that do not exist. This keeps the `ratelimit_buckets` table from growing without bound.
+pub async fn check_persistent(
+    pool: &Pool<Postgres>, limiter: &str, key: &str, capacity: u32, period: u64, message: &str,
+) -> Result<RatelimitInfo, CoreError> {
+    let outcome = sqlx::query!(
+        r#"SELECT remaining AS "remaining!", reset AS "reset!", retry_after FROM ratelimit_check($1, $2, $3, $4)"#,
+        limiter,
+        key,
+        capacity as i32,
+        period as f64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    match outcome.retry_after {
+        None => Ok(RatelimitInfo {
+            limit: capacity,
+            remaining: outcome.remaining as u32,
+            reset: Duration::from_secs_f64(outcome.reset),
+        }),
+        Some(retry_after) => Err(CoreError::Ratelimited {
+            message: message.to_string(),
+            remaining: Duration::from_secs_f64(retry_after),
+            limit: capacity,
+            reset: Duration::from_secs_f64(outcome.reset),
+        }),
+    }
+}
+
 #[macro_export]
 macro_rules! ratelimits {
     ($struct_name: ident {$($tokens:tt)*}) => {
         use nonzero_ext::nonzero;
-        use pointercrate_core::error::CoreError;
+        use pointercrate_core::{error::CoreError, ratelimits::{self as core_ratelimits, RatelimitInfo, RatelimitKey, RatelimitStorage}};
         use std::{
             net::IpAddr,
             time::{Duration, Instant},
         };
         use governor::{
             clock::{Clock, DefaultClock, Reference},
+            middleware::StateInformationMiddleware,
             state::{direct::NotKeyed, keyed::DefaultKeyedStateStore, InMemoryState},
             Quota, RateLimiter,
         };
@@ -16,7 +182,7 @@ macro_rules! ratelimits {
         ratelimits!(@struct@ $struct_name [] $($tokens)*);
 
         impl $struct_name {
-            ratelimits!(@method@  $($tokens)*);
+            ratelimits!(@method@ $struct_name $($tokens)*);
         }
     };
 
@@ -25,7 +191,7 @@ macro_rules! ratelimits {
         ratelimits!(@struct@
             $struct_name [
                 $($field: $type | $init,)*  // already processed fields
-                $name: RateLimiter<NotKeyed, InMemoryState, DefaultClock> | RateLimiter::direct(Quota::new(nonzero!($capacity), Duration::from_secs($seconds)).unwrap())  // new field
+                $name: RateLimiter<NotKeyed, InMemoryState, DefaultClock, StateInformationMiddleware> | RateLimiter::direct(Quota::new(nonzero!($capacity), Duration::from_secs($seconds)).unwrap()).with_middleware()  // new field
             ] $($remaining)*);  // remaining, unprocessed fields as token stream
     };
 
@@ -33,49 +199,93 @@ macro_rules! ratelimits {
         ratelimits!(@struct@
             $struct_name [
                 $($field: $type | $init,)*
-                $name: RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>, DefaultClock> | RateLimiter::keyed(Quota::new(nonzero!($capacity), Duration::from_secs($seconds)).unwrap())
+                $name: RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>, DefaultClock, StateInformationMiddleware> | RateLimiter::keyed(Quota::new(nonzero!($capacity), Duration::from_secs($seconds)).unwrap()).with_middleware()
+            ] $($remaining)*);
+    };
+
+    (@struct@ $struct_name: ident [$($field: ident: $type: ty | $init: expr),*] $name: ident[$capacity: tt per $seconds: tt per user] => $message: expr, $($remaining: tt)*) => {
+        ratelimits!(@struct@
+            $struct_name [
+                $($field: $type | $init,)*
+                $name: RateLimiter<RatelimitKey, DefaultKeyedStateStore<RatelimitKey>, DefaultClock, StateInformationMiddleware> | RateLimiter::keyed(Quota::new(nonzero!($capacity), Duration::from_secs($seconds)).unwrap()).with_middleware()
             ] $($remaining)*);
     };
 
-    (@method@ $name: ident[$capacity: tt per $seconds: tt] => $message: expr, $($remaining: tt)*) => {
-        pub(crate) fn $name(&self) -> Result<(), CoreError> {
-            let now = DefaultClock::default().now();
+    (@method@ $struct_name: ident $name: ident[$capacity: tt per $seconds: tt] => $message: expr, $($remaining: tt)*) => {
+        pub(crate) async fn $name(&self) -> Result<RatelimitInfo, CoreError> {
+            match self.storage {
+                RatelimitStorage::InMemory => {
+                    let now = DefaultClock::default().now();
 
-            self.$name.check().map_err(|too_early| {
-                CoreError::Ratelimited {
-                    message: $message.to_string(),
-                    remaining: too_early.earliest_possible().duration_since(now).into(),
-                }
-            })
+                    self.$name
+                        .check()
+                        .map(|snapshot| RatelimitInfo::refilling($capacity, $seconds, snapshot.remaining_burst_capacity()))
+                        .map_err(|too_early| core_ratelimits::ratelimited($message, $capacity, $seconds, too_early.earliest_possible().duration_since(now).into()))
+                },
+                RatelimitStorage::Postgres(ref pool) =>
+                    core_ratelimits::check_persistent(pool, concat!(stringify!($struct_name), "::", stringify!($name)), "", $capacity, $seconds, $message).await,
+            }
         }
-        ratelimits!(@method@  $($remaining)*);
+        ratelimits!(@method@ $struct_name $($remaining)*);
     };
 
-    (@method@ $name: ident[$capacity: tt per $seconds: tt per ip] => $message: expr, $($remaining: tt)*) => {
-        pub(crate) fn $name(&self, ip: IpAddr) -> Result<(), CoreError> {
-            let now = DefaultClock::default().now();
+    (@method@ $struct_name: ident $name: ident[$capacity: tt per $seconds: tt per ip] => $message: expr, $($remaining: tt)*) => {
+        pub(crate) async fn $name(&self, ip: IpAddr) -> Result<RatelimitInfo, CoreError> {
+            match self.storage {
+                RatelimitStorage::InMemory => {
+                    let now = DefaultClock::default().now();
 
-            self.$name.check_key(&ip).map_err(|too_early| {
-                CoreError::Ratelimited {
-                    message: $message.to_string(),
-                    remaining: too_early.earliest_possible().duration_since(now).into(),
-                }
-            })
+                    self.$name
+                        .check_key(&ip)
+                        .map(|snapshot| RatelimitInfo::refilling($capacity, $seconds, snapshot.remaining_burst_capacity()))
+                        .map_err(|too_early| core_ratelimits::ratelimited($message, $capacity, $seconds, too_early.earliest_possible().duration_since(now).into()))
+                },
+                RatelimitStorage::Postgres(ref pool) =>
+                    core_ratelimits::check_persistent(pool, concat!(stringify!($struct_name), "::", stringify!($name)), &ip.to_string(), $capacity, $seconds, $message).await,
+            }
         }
-        ratelimits!(@method@  $($remaining)*);
+        ratelimits!(@method@ $struct_name $($remaining)*);
+    };
+
+    (@method@ $struct_name: ident $name: ident[$capacity: tt per $seconds: tt per user] => $message: expr, $($remaining: tt)*) => {
+        pub(crate) async fn $name(&self, user_id: Option<i32>, ip: IpAddr) -> Result<RatelimitInfo, CoreError> {
+            let key = RatelimitKey::new(user_id, ip);
+
+            match self.storage {
+                RatelimitStorage::InMemory => {
+                    let now = DefaultClock::default().now();
+
+                    self.$name
+                        .check_key(&key)
+                        .map(|snapshot| RatelimitInfo::refilling($capacity, $seconds, snapshot.remaining_burst_capacity()))
+                        .map_err(|too_early| core_ratelimits::ratelimited($message, $capacity, $seconds, too_early.earliest_possible().duration_since(now).into()))
+                },
+                RatelimitStorage::Postgres(ref pool) =>
+                    core_ratelimits::check_persistent(pool, concat!(stringify!($struct_name), "::", stringify!($name)), &key.to_string(), $capacity, $seconds, $message).await,
+            }
+        }
+        ratelimits!(@method@ $struct_name $($remaining)*);
     };
 
     (@struct@ $struct_name: ident [$($field: ident: $type: ty | $init: expr),*]) => {
         pub struct $struct_name {
+            storage: RatelimitStorage,
             $(
                 $field: $type,
             )*
         }
 
         impl $struct_name {
+            /// Constructs the ratelimits with in-memory storage
+            #[allow(dead_code)] // only used in tests
             pub(crate) fn new() -> Self {
+                Self::with_storage(RatelimitStorage::InMemory)
+            }
+
+            pub(crate) fn with_storage(storage: RatelimitStorage) -> Self {
                 #[allow(deprecated)] // the governor API mentions that using Quota::new() is fine since our ratelimits are given as "burst per duration"
                 $struct_name {
+                    storage,
                     $(
                         $field: $init,
                     )*
@@ -83,5 +293,5 @@ macro_rules! ratelimits {
             }
         }
     };
-    (@method@) => {};
+    (@method@ $struct_name: ident) => {};
 }
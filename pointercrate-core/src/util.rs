@@ -1,3 +1,4 @@
+use chrono::{DateTime, FixedOffset};
 use serde::{de::Error, Deserialize, Deserializer};
 use std::{fmt::Debug, str::FromStr};
 
@@ -30,3 +31,54 @@ where
         some => Ok(some),
     }
 }
+
+/// Deserializes an RFC 3339 timestamp passed in a query string.
+///
+/// Query strings decode a literal `+` to a space, so unless clients percent-encode it, the offset of
+/// a timestamp such as `2024-01-01T12:00:00+01:00` arrives as `2024-01-01T12:00:00 01:00`. Such a
+/// space in front of the offset is turned back into a `+`.
+pub fn query_timestamp<'de, D>(deserializer: D) -> std::result::Result<DateTime<FixedOffset>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    parse_query_timestamp(String::deserialize(deserializer)?).map_err(D::Error::custom)
+}
+
+/// Like [`query_timestamp`], but for optional fields. Rejects explicit `null`s like [`non_nullable`]
+pub fn non_nullable_query_timestamp<'de, D>(deserializer: D) -> std::result::Result<Option<DateTime<FixedOffset>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match non_nullable::<String, D>(deserializer)? {
+        Some(timestamp) => parse_query_timestamp(timestamp).map(Some).map_err(D::Error::custom),
+        None => Ok(None),
+    }
+}
+
+fn parse_query_timestamp(mut timestamp: String) -> Result<DateTime<FixedOffset>, chrono::ParseError> {
+    if timestamp.len() > 6 && timestamp.as_bytes()[timestamp.len() - 6] == b' ' {
+        let offset = timestamp.len() - 6;
+
+        timestamp.replace_range(offset..=offset, "+");
+    }
+
+    DateTime::parse_from_rfc3339(&timestamp)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_query_timestamp;
+
+    #[test]
+    fn test_parse_query_timestamp() {
+        let expected = parse_query_timestamp("2024-01-01T12:00:00+01:00".to_string()).unwrap();
+
+        assert_eq!(parse_query_timestamp("2024-01-01T12:00:00 01:00".to_string()), Ok(expected));
+        assert_eq!(
+            parse_query_timestamp("2024-01-01T12:00:00-01:00".to_string()).unwrap().to_rfc3339(),
+            "2024-01-01T12:00:00-01:00"
+        );
+        assert_eq!(parse_query_timestamp("2024-01-01T11:00:00Z".to_string()).unwrap(), expected);
+        assert!(parse_query_timestamp("2024-01-01".to_string()).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use crate::video::{format_timestamp, parse_timestamp, VideoHost};
+use url::Url;
+
+fn segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+fn query_parameter(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find_map(|(key, value)| if key == name { Some(value.into_owned()) } else { None })
+}
+
+pub struct YouTube;
+
+impl VideoHost for YouTube {
+    fn name(&self) -> &'static str {
+        "YouTube"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["www.youtube.com", "m.youtube.com", "youtube.com", "youtu.be"]
+    }
+
+    fn video_format(&self) -> &'static str {
+        "https://www.youtube.com/watch?v={video_id}' or 'https://m.youtube.com/watch?v={video_id}' or \
+         'https://youtube.com/watch?v={video_id}' or 'https://youtu.be/{video_id}"
+    }
+
+    fn video_id(&self, url: &Url) -> Option<String> {
+        let video_id = if url.domain() == Some("youtu.be") {
+            match segments(url)[..] {
+                [video_id] => video_id.to_string(),
+                _ => return None,
+            }
+        } else if url.path() == "/watch" {
+            query_parameter(url, "v")?
+        } else {
+            return None;
+        };
+
+        Some(video_id.chars().take(11).collect())
+    }
+
+    fn canonical_url(&self, video_id: &str) -> String {
+        format!("https://www.youtube.com/watch?v={}", video_id)
+    }
+
+    fn timestamp(&self, url: &Url) -> Option<u32> {
+        parse_timestamp(&query_parameter(url, "t")?)
+    }
+
+    fn canonical_url_at(&self, video_id: &str, timestamp: u32) -> String {
+        format!("https://www.youtube.com/watch?v={}&t={}", video_id, timestamp)
+    }
+
+    fn embed_url(&self, video_id: &str, timestamp: Option<u32>) -> Option<String> {
+        Some(match timestamp {
+            Some(timestamp) => format!("https://www.youtube.com/embed/{}?start={}", video_id, timestamp),
+            None => format!("https://www.youtube.com/embed/{}", video_id),
+        })
+    }
+
+    fn oembed_endpoint(&self) -> Option<&'static str> {
+        Some("https://www.youtube.com/oembed")
+    }
+
+    fn channel_format(&self) -> Option<&'static str> {
+        Some(
+            "'youtube.com/channel/{channel_id}' or 'youtube.com/c/{custom_channel_id}/' or 'youtube.com/user/{username}/' or \
+             'youtube.com/@{handle}'",
+        )
+    }
+
+    fn channel_url(&self, url: &Url) -> Option<String> {
+        if url.domain() == Some("youtu.be") {
+            return None;
+        }
+
+        match url.path_segments()?.collect::<Vec<_>>()[..] {
+            ["channel", _] | ["user", _] | ["c", _] => Some(url.to_string()),
+            [handle] if handle.starts_with('@') => Some(url.to_string()),
+            _ => None,
+        }
+    }
+}
+
+pub struct Twitch;
+
+impl VideoHost for Twitch {
+    fn name(&self) -> &'static str {
+        "Twitch"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["www.twitch.tv", "twitch.tv"]
+    }
+
+    fn video_format(&self) -> &'static str {
+        "https://www.twitch.tv/videos/{video_id}' or 'https://twitch.tv/videos/{video_id}' or \
+         'https://www.twitch.tv/{channel_name}/v/{video_id}' or 'https://twitch.tv/{channel_name}/v/{video_id}"
+    }
+
+    fn video_id(&self, url: &Url) -> Option<String> {
+        match segments(url)[..] {
+            ["videos", video_id] | [_, "v", video_id] => Some(video_id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn canonical_url(&self, video_id: &str) -> String {
+        format!("https://www.twitch.tv/videos/{}", video_id)
+    }
+
+    fn timestamp(&self, url: &Url) -> Option<u32> {
+        parse_timestamp(&query_parameter(url, "t")?)
+    }
+
+    fn canonical_url_at(&self, video_id: &str, timestamp: u32) -> String {
+        format!("https://www.twitch.tv/videos/{}?t={}", video_id, format_timestamp(timestamp))
+    }
+
+    fn embed_url(&self, video_id: &str, timestamp: Option<u32>) -> Option<String> {
+        Some(match timestamp {
+            Some(timestamp) => format!(
+                "https://player.twitch.tv/?video={}&autoplay=false&time={}",
+                video_id,
+                format_timestamp(timestamp)
+            ),
+            None => format!("https://player.twitch.tv/?video={}&autoplay=false", video_id),
+        })
+    }
+}
+
+pub struct Vimeo;
+
+impl VideoHost for Vimeo {
+    fn name(&self) -> &'static str {
+        "Vimeo"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["vimeo.com", "www.vimeo.com"]
+    }
+
+    fn video_format(&self) -> &'static str {
+        "https://vimeo.com/{video_id}' or 'https://www.vimeo.com/{video_id}"
+    }
+
+    fn video_id(&self, url: &Url) -> Option<String> {
+        match segments(url)[..] {
+            [video_id] => Some(video_id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn canonical_url(&self, video_id: &str) -> String {
+        format!("https://vimeo.com/{}", video_id)
+    }
+
+    /// Vimeo puts timestamps into the fragment, as in `#t=90s`
+    fn timestamp(&self, url: &Url) -> Option<u32> {
+        parse_timestamp(url.fragment()?.strip_prefix("t=")?)
+    }
+
+    fn canonical_url_at(&self, video_id: &str, timestamp: u32) -> String {
+        format!("https://vimeo.com/{}#t={}s", video_id, timestamp)
+    }
+
+    fn embed_url(&self, video_id: &str, timestamp: Option<u32>) -> Option<String> {
+        Some(match timestamp {
+            Some(timestamp) => format!("https://player.vimeo.com/video/{}#t={}s", video_id, timestamp),
+            None => format!("https://player.vimeo.com/video/{}", video_id),
+        })
+    }
+
+    fn oembed_endpoint(&self) -> Option<&'static str> {
+        Some("https://vimeo.com/api/oembed.json")
+    }
+}
+
+pub struct Bilibili;
+
+impl VideoHost for Bilibili {
+    fn name(&self) -> &'static str {
+        "Bilibili"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["www.bilibili.com", "bilibili.com"]
+    }
+
+    fn video_format(&self) -> &'static str {
+        "https://www.bilibili.com/video/{video_id}' or 'https://bilibili.com/video/{video_id}"
+    }
+
+    fn video_id(&self, url: &Url) -> Option<String> {
+        match segments(url)[..] {
+            ["video", video_id] => Some(video_id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn canonical_url(&self, video_id: &str) -> String {
+        format!("https://www.bilibili.com/video/{}", video_id)
+    }
+
+    fn timestamp(&self, url: &Url) -> Option<u32> {
+        parse_timestamp(&query_parameter(url, "t")?)
+    }
+
+    fn canonical_url_at(&self, video_id: &str, timestamp: u32) -> String {
+        format!("https://www.bilibili.com/video/{}?t={}", video_id, timestamp)
+    }
+}
+
+pub struct Streamable;
+
+impl VideoHost for Streamable {
+    fn name(&self) -> &'static str {
+        "Streamable"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["streamable.com", "www.streamable.com"]
+    }
+
+    fn video_format(&self) -> &'static str {
+        "https://streamable.com/{video_id}"
+    }
+
+    fn video_id(&self, url: &Url) -> Option<String> {
+        match segments(url)[..] {
+            [video_id] | ["e", video_id] => Some(video_id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn canonical_url(&self, video_id: &str) -> String {
+        format!("https://streamable.com/{}", video_id)
+    }
+
+    fn embed_url(&self, video_id: &str, _timestamp: Option<u32>) -> Option<String> {
+        Some(format!("https://streamable.com/e/{}", video_id))
+    }
+
+    fn oembed_endpoint(&self) -> Option<&'static str> {
+        Some("https://api.streamable.com/oembed.json")
+    }
+}
+
+pub struct Medal;
+
+impl VideoHost for Medal {
+    fn name(&self) -> &'static str {
+        "Medal"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["medal.tv", "www.medal.tv"]
+    }
+
+    fn video_format(&self) -> &'static str {
+        "https://medal.tv/clips/{clip_id}' or 'https://medal.tv/games/{game}/clips/{clip_id}"
+    }
+
+    fn video_id(&self, url: &Url) -> Option<String> {
+        match segments(url)[..] {
+            ["clips", clip_id, ..] | ["games", _, "clips", clip_id, ..] => Some(clip_id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn canonical_url(&self, clip_id: &str) -> String {
+        format!("https://medal.tv/clips/{}", clip_id)
+    }
+}
+
+/// Everyplay shut down in 2018, but records with Everyplay videos still exist
+pub struct Everyplay;
+
+impl VideoHost for Everyplay {
+    fn name(&self) -> &'static str {
+        "Everyplay"
+    }
+
+    fn domains(&self) -> &'static [&'static str] {
+        &["everyplay.com", "www.everyplay.com"]
+    }
+
+    fn video_format(&self) -> &'static str {
+        "https://everyplay.com/videos/{video_id}' or 'https://www.everyplay.com/videos/{video_id}"
+    }
+
+    fn video_id(&self, url: &Url) -> Option<String> {
+        match segments(url)[..] {
+            ["videos", video_id] => Some(video_id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn canonical_url(&self, video_id: &str) -> String {
+        format!("https://everyplay.com/videos/{}", video_id)
+    }
+}
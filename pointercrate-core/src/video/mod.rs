@@ -0,0 +1,286 @@
+//! Module for handling links to videos (and channels) on third-party video hosts
+//!
+//! Every supported host implements [`VideoHost`], describing which links belong to it, what their
+//! canonical form is and how its videos can be embedded. All hosts pointercrate knows about are
+//! collected in the [`VideoHostRegistry`] returned by [`hosts`]. Supporting a new host only
+//! requires implementing [`VideoHost`] for it and registering it there.
+//!
+//! If a host supports links to specific points in a video, the timestamp is preserved when
+//! canonicalizing the link.
+
+pub use self::hosts::{Bilibili, Everyplay, Medal, Streamable, Twitch, Vimeo, YouTube};
+use crate::error::CoreError;
+use std::sync::OnceLock;
+use url::Url;
+
+mod hosts;
+
+const SCHEMES: [&str; 2] = ["http", "https"];
+
+/// Reasons a link can fail to validate
+#[derive(Debug, PartialEq, Eq)]
+pub enum VideoUrlError {
+    /// The given string isn't a URL
+    Malformed,
+
+    /// The URL doesn't belong to any registered host, or to a host not supporting the requested kind
+    /// of link
+    UnsupportedHost,
+
+    /// The URL belongs to a supported host, but isn't acceptable for the given reason
+    Invalid(CoreError),
+}
+
+impl From<CoreError> for VideoUrlError {
+    fn from(error: CoreError) -> Self {
+        VideoUrlError::Invalid(error)
+    }
+}
+
+pub trait VideoHost: Send + Sync {
+    /// The name this host is displayed as, e.g. "YouTube"
+    fn name(&self) -> &'static str;
+
+    /// All domains links to this host's videos can use
+    fn domains(&self) -> &'static [&'static str];
+
+    /// A human readable description of the video links this host accepts, used in error messages
+    fn video_format(&self) -> &'static str;
+
+    /// Extracts the id of the video the given link (on one of this host's domains) points to
+    fn video_id(&self, url: &Url) -> Option<String>;
+
+    /// The canonical link to the video with the given id
+    fn canonical_url(&self, video_id: &str) -> String;
+
+    /// Extracts the point in the video, in seconds, the given link starts playback at
+    ///
+    /// Hosts not supporting timestamps never return one
+    fn timestamp(&self, _url: &Url) -> Option<u32> {
+        None
+    }
+
+    /// The canonical link to the video with the given id, starting playback at the given point (in
+    /// seconds)
+    fn canonical_url_at(&self, video_id: &str, _timestamp: u32) -> String {
+        self.canonical_url(video_id)
+    }
+
+    /// The URL of an embeddable player for the video with the given id, if this host supports
+    /// embedding
+    fn embed_url(&self, _video_id: &str, _timestamp: Option<u32>) -> Option<String> {
+        None
+    }
+
+    /// The [oEmbed](https://oembed.com) endpoint metadata about this host's videos can be requested
+    /// from, if the host provides one
+    fn oembed_endpoint(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// A human readable description of the channel links this host accepts, if it supports any
+    fn channel_format(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Validates a link to a channel on this host, returning the link to store
+    fn channel_url(&self, _url: &Url) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Default)]
+pub struct VideoHostRegistry {
+    hosts: Vec<Box<dyn VideoHost>>,
+}
+
+impl VideoHostRegistry {
+    pub fn with_host(mut self, host: impl VideoHost + 'static) -> Self {
+        self.hosts.push(Box::new(host));
+        self
+    }
+
+    /// Gets the host the given link points to
+    pub fn host_of(&self, url: &Url) -> Option<&dyn VideoHost> {
+        let domain = url.domain()?;
+
+        self.hosts
+            .iter()
+            .find(|host| host.domains().contains(&domain))
+            .map(|host| host.as_ref())
+    }
+
+    /// Validates the given link to a video, and returns its canonical form
+    pub fn validate_video(&self, url: &str) -> Result<String, VideoUrlError> {
+        let url = parse(url)?;
+        let host = self.host_of(&url).ok_or(VideoUrlError::UnsupportedHost)?;
+        let video_id = host.video_id(&url).ok_or(CoreError::InvalidUrlFormat {
+            expected: host.video_format(),
+        })?;
+
+        Ok(match host.timestamp(&url) {
+            Some(timestamp) if timestamp > 0 => host.canonical_url_at(&video_id, timestamp),
+            _ => host.canonical_url(&video_id),
+        })
+    }
+
+    /// Validates the given link to a channel
+    pub fn validate_channel(&self, url: &str) -> Result<String, VideoUrlError> {
+        let url = parse(url)?;
+        let host = self.host_of(&url).ok_or(VideoUrlError::UnsupportedHost)?;
+        let format = host.channel_format().ok_or(VideoUrlError::UnsupportedHost)?;
+
+        Ok(host.channel_url(&url).ok_or(CoreError::InvalidUrlFormat { expected: format })?)
+    }
+
+    /// The name of the host the given (previously validated) video link points to
+    pub fn host_name(&self, video: &str) -> Option<&'static str> {
+        Some(self.host_of(&Url::parse(video).ok()?)?.name())
+    }
+
+    /// The URL of an embeddable player for the given (previously validated) video link, if its host
+    /// supports embedding
+    pub fn embed(&self, video: &str) -> Option<String> {
+        let url = Url::parse(video).ok()?;
+        let host = self.host_of(&url)?;
+
+        host.embed_url(&host.video_id(&url)?, host.timestamp(&url))
+    }
+
+    /// The oEmbed endpoint of the host the given (previously validated) video link points to, if
+    /// that host provides one
+    pub fn oembed_endpoint(&self, video: &str) -> Option<&'static str> {
+        self.host_of(&Url::parse(video).ok()?)?.oembed_endpoint()
+    }
+}
+
+/// All video hosts supported by pointercrate
+pub fn hosts() -> &'static VideoHostRegistry {
+    static HOSTS: OnceLock<VideoHostRegistry> = OnceLock::new();
+
+    HOSTS.get_or_init(|| {
+        VideoHostRegistry::default()
+            .with_host(YouTube)
+            .with_host(Twitch)
+            .with_host(Vimeo)
+            .with_host(Bilibili)
+            .with_host(Streamable)
+            .with_host(Medal)
+            .with_host(Everyplay)
+    })
+}
+
+fn parse(url: &str) -> Result<Url, VideoUrlError> {
+    let url = Url::parse(url).map_err(|_| VideoUrlError::Malformed)?;
+
+    if !SCHEMES.contains(&url.scheme()) {
+        return Err(CoreError::InvalidUrlScheme.into());
+    }
+
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(CoreError::UrlAuthenticated.into());
+    }
+
+    if url.domain().is_none() {
+        return Err(CoreError::UnprocessableEntity.into());
+    }
+
+    Ok(url)
+}
+
+/// Parses timestamps of the forms `90`, `90s`, `1m30s` and `1h1m30s` into seconds
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<u32> {
+    if let Ok(seconds) = timestamp.parse() {
+        return Some(seconds);
+    }
+
+    let mut seconds = 0u32;
+    let mut number = String::new();
+
+    for c in timestamp.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+
+        seconds = seconds.checked_add(number.parse::<u32>().ok()?.checked_mul(unit)?)?;
+        number.clear();
+    }
+
+    if number.is_empty() {
+        Some(seconds)
+    } else {
+        None
+    }
+}
+
+/// Formats the given number of seconds as `1h1m30s`
+pub(crate) fn format_timestamp(seconds: u32) -> String {
+    format!("{}h{}m{}s", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_timestamp, hosts, parse_timestamp, VideoUrlError};
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("90"), Some(90));
+        assert_eq!(parse_timestamp("90s"), Some(90));
+        assert_eq!(parse_timestamp("1m30s"), Some(90));
+        assert_eq!(parse_timestamp("1h1m"), Some(3660));
+        assert_eq!(parse_timestamp("1x"), None);
+        assert_eq!(parse_timestamp("m"), None);
+        assert_eq!(format_timestamp(3690), "1h1m30s");
+    }
+
+    #[test]
+    fn test_canonicalize_videos() {
+        let hosts = hosts();
+
+        assert_eq!(
+            hosts.validate_video("https://youtu.be/dQw4w9WgXcQ?t=42"),
+            Ok("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42".to_string())
+        );
+        assert_eq!(
+            hosts.validate_video("https://m.youtube.com/watch?v=dQw4w9WgXcQ&feature=share"),
+            Ok("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string())
+        );
+        assert_eq!(
+            hosts.validate_video("https://twitch.tv/stardust1971/v/123456?t=1m30s"),
+            Ok("https://www.twitch.tv/videos/123456?t=0h1m30s".to_string())
+        );
+        assert_eq!(
+            hosts.validate_video("https://streamable.com/abcdef"),
+            Ok("https://streamable.com/abcdef".to_string())
+        );
+        assert_eq!(
+            hosts.validate_video("https://medal.tv/games/geometry-dash/clips/abc123/some-title"),
+            Ok("https://medal.tv/clips/abc123".to_string())
+        );
+        assert_eq!(
+            hosts.validate_video("https://example.com/video"),
+            Err(VideoUrlError::UnsupportedHost)
+        );
+        assert_eq!(hosts.validate_video("not a url"), Err(VideoUrlError::Malformed));
+    }
+
+    #[test]
+    fn test_embed() {
+        let hosts = hosts();
+
+        assert_eq!(
+            hosts.embed("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42"),
+            Some("https://www.youtube.com/embed/dQw4w9WgXcQ?start=42".to_string())
+        );
+        assert_eq!(hosts.host_name("https://vimeo.com/1234"), Some("Vimeo"));
+        assert_eq!(hosts.embed("https://everyplay.com/videos/1234"), None);
+    }
+}
@@ -1,3 +1,7 @@
+/// The URL of the Discord webhook notified of new submissions
+///
+/// Deprecated: on startup, this is registered as a Discord-format webhook (unless a webhook with
+/// this URL already exists), which can then be managed via `/api/v1/webhooks/` like any other.
 pub fn submission_webhook() -> Option<String> {
     std::env::var("DISCORD_WEBHOOK").ok()
 }
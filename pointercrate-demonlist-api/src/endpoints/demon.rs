@@ -1,56 +1,67 @@
 use crate::ratelimits::DemonlistRatelimits;
+use chrono::Utc;
 use pointercrate_core::{audit::AuditLogEntry, pool::PointercratePool};
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
-    pagination_response,
+    pagination::{pagination_response, Paginated, PaginationPreferences},
     query::Query,
+    ratelimits::RatelimitTracker,
     response::Response2,
 };
 use pointercrate_demonlist::{
     creator::{Creator, PostCreator},
     demon::{
         audit::{DemonModificationData, MovementLogEntry},
+        diff::{DiffWindow, ListDiff},
         Demon, DemonIdPagination, DemonPositionPagination, FullDemon, PatchDemon, PostDemon,
     },
     error::DemonlistError,
+    list::List,
     player::DatabasePlayer,
+    webhook::{self, WebhookEvent},
     LIST_ADMINISTRATOR, LIST_MODERATOR,
 };
 use pointercrate_user_api::auth::TokenAuth;
 use rocket::{http::Status, serde::json::Json, State};
+use std::net::IpAddr;
 
 #[rocket::get("/")]
-pub async fn paginate(pool: &State<PointercratePool>, pagination: Query<DemonIdPagination>) -> Result<Response2<Json<Vec<Demon>>>> {
-    let mut pagination = pagination.0;
-    let mut connection = pool.connection().await?;
+pub async fn paginate(
+    ip: IpAddr, pool: &State<PointercratePool>, pagination: Query<DemonIdPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<Demon>> {
+    if preferences.export.is_some() {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
 
-    let mut demons = pagination.page(&mut *connection).await?;
-    let (max_id, min_id) = Demon::extremal_demon_ids(&mut *connection).await?;
+    let connection = pool.connection().await?;
 
-    pagination_response!("/api/v2/demons/", demons, pagination, min_id, max_id, before_id, after_id, base.id)
+    Ok(pagination_response("/api/v2/demons/", pagination.0, preferences, connection).await?)
 }
 
 #[rocket::get("/listed")]
 pub async fn paginate_listed(
-    pool: &State<PointercratePool>, pagination: Query<DemonPositionPagination>,
-) -> Result<Response2<Json<Vec<Demon>>>> {
-    let mut pagination = pagination.0;
+    ip: IpAddr, pool: &State<PointercratePool>, pagination: Query<DemonPositionPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<Demon>> {
+    if preferences.export.is_some() {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
+
+    let connection = pool.connection().await?;
+
+    Ok(pagination_response("/api/v2/demons/listed/", pagination.0, preferences, connection).await?)
+}
+
+/// Compares the given list at the start and the end of the given window. The end defaults to now
+#[rocket::get("/diff")]
+pub async fn diff(pool: &State<PointercratePool>, window: Query<DiffWindow>) -> Result<Json<ListDiff>> {
     let mut connection = pool.connection().await?;
+    let list = List::by_id_or_default(window.0.list, &mut *connection).await?;
+    let to = window.0.to.unwrap_or_else(|| Utc::now().into());
 
-    let mut demons = pagination.page(&mut *connection).await?;
-    let max_position = Demon::max_position(&mut *connection).await?;
-
-    pagination_response!(
-        "/api/v2/demons/listed/",
-        demons,
-        pagination,
-        1,
-        max_position,
-        before_position,
-        after_position,
-        base.position
-    )
+    Ok(Json(ListDiff::between(&list, window.0.from, to, &mut *connection).await?))
 }
 
 #[rocket::get("/<demon_id>")]
@@ -84,14 +95,16 @@ pub async fn movement_log(demon_id: i32, pool: &State<PointercratePool>) -> Resu
 
 #[rocket::post("/", data = "<data>")]
 pub async fn post(
-    mut auth: TokenAuth, data: Json<PostDemon>, ratelimits: &State<DemonlistRatelimits>,
+    mut auth: TokenAuth, data: Json<PostDemon>, ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
 ) -> Result<Response2<Tagged<FullDemon>>> {
     auth.require_permission(LIST_MODERATOR)?;
 
-    ratelimits.add_demon()?;
+    tracker.track(ratelimits.add_demon().await)?;
 
     let demon = FullDemon::create_from(data.0, &mut auth.connection).await?;
 
+    webhook::dispatch(WebhookEvent::DemonAdded, &demon.demon, &mut auth.connection).await?;
+
     auth.commit().await?;
 
     let demon_id = demon.demon.base.id;
@@ -107,9 +120,18 @@ pub async fn patch(demon_id: i32, mut auth: TokenAuth, precondition: Preconditio
 
     let demon = FullDemon::by_id(demon_id, &mut auth.connection)
         .await?
-        .require_match(precondition)?
-        .apply_patch(patch.0, &mut auth.connection)
-        .await?;
+        .require_match(precondition)?;
+    let old_position = demon.position();
+    let demon = demon.apply_patch(patch.0, &mut auth.connection).await?;
+
+    if demon.position() != old_position {
+        let payload = serde_json::json!({
+            "demon": demon.demon,
+            "previous_position": old_position,
+        });
+
+        webhook::dispatch(WebhookEvent::DemonMoved, &payload, &mut auth.connection).await?;
+    }
 
     auth.commit().await?;
 
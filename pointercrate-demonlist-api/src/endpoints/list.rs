@@ -0,0 +1,47 @@
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::{error::Result, response::Response2};
+use pointercrate_demonlist::{
+    list::{List, PatchList, PostList},
+    LIST_ADMINISTRATOR,
+};
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::{http::Status, serde::json::Json, State};
+
+#[rocket::get("/")]
+pub async fn list(pool: &State<PointercratePool>) -> Result<Json<Vec<List>>> {
+    Ok(Json(List::all(&mut *pool.connection().await?).await?))
+}
+
+#[rocket::post("/", data = "<data>")]
+pub async fn post(mut auth: TokenAuth, data: Json<PostList>) -> Result<Response2<Json<List>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let list = List::create_from(data.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    let list_id = list.id;
+
+    Ok(Response2::json(list)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v2/lists/{}/", list_id)))
+}
+
+#[rocket::get("/<list_id>")]
+pub async fn get(list_id: i32, pool: &State<PointercratePool>) -> Result<Json<List>> {
+    Ok(Json(List::by_id(list_id, &mut *pool.connection().await?).await?))
+}
+
+#[rocket::patch("/<list_id>", data = "<patch>")]
+pub async fn patch(list_id: i32, mut auth: TokenAuth, patch: Json<PatchList>) -> Result<Json<List>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let list = List::by_id(list_id, &mut auth.connection)
+        .await?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Json(list))
+}
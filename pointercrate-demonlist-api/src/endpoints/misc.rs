@@ -1,15 +1,19 @@
-use pointercrate_demonlist::config;
-use rocket::serde::json::Json;
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::error::Result;
+use pointercrate_demonlist::list::List;
+use rocket::{serde::json::Json, State};
 use serde_json::{json, Value};
 
-#[rocket::get("/")]
-pub fn list_information() -> Json<Value> {
+#[rocket::get("/?<list>")]
+pub async fn list_information(list: Option<i32>, pool: &State<PointercratePool>) -> Result<Json<Value>> {
+    let list = List::by_id_or_default(list, &mut *pool.connection().await?).await?;
+
     let data = json! {
         {
-            "list_size": config::list_size(),
-            "extended_list_size": config::extended_list_size()
+            "list_size": list.list_size,
+            "extended_list_size": list.extended_list_size
         }
     };
 
-    Json(data)
+    Ok(Json(data))
 }
@@ -1,6 +1,13 @@
 pub(crate) mod demon;
+pub(crate) mod list;
 pub(crate) mod misc;
 pub(crate) mod nationality;
 pub(crate) mod player;
 pub(crate) mod record;
+pub(crate) mod rejection_reason;
+pub(crate) mod review;
+pub(crate) mod scoring;
+pub(crate) mod status_transition;
 pub(crate) mod submitter;
+pub(crate) mod video;
+pub(crate) mod webhook;
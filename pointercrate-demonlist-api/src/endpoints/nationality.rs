@@ -1,7 +1,15 @@
+use crate::ratelimits::DemonlistRatelimits;
 use pointercrate_core::pool::PointercratePool;
-use pointercrate_core_api::{error::Result, etag::Tagged, query::Query};
+use pointercrate_core_api::{
+    error::Result,
+    etag::Tagged,
+    pagination::{pagination_response, Paginated, PaginationPreferences},
+    query::Query,
+    ratelimits::RatelimitTracker,
+};
 use pointercrate_demonlist::nationality::{Nationality, NationalityRankingPagination, NationalityRecord, RankedNation, Subdivision};
 use rocket::{serde::json::Json, State};
+use std::net::IpAddr;
 
 #[rocket::get("/<iso_code>/subdivisions")]
 pub async fn subdivisions(pool: &State<PointercratePool>, iso_code: String) -> Result<Json<Vec<Subdivision>>> {
@@ -14,8 +22,17 @@ pub async fn subdivisions(pool: &State<PointercratePool>, iso_code: String) -> R
 }
 
 #[rocket::get("/ranking")]
-pub async fn ranking(pool: &State<PointercratePool>, pagination: Query<NationalityRankingPagination>) -> Result<Json<Vec<RankedNation>>> {
-    Ok(Json(pagination.0.page(&mut *pool.connection().await?).await?))
+pub async fn ranking(
+    ip: IpAddr, pool: &State<PointercratePool>, query: Query<NationalityRankingPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<RankedNation>> {
+    if preferences.export.is_some() {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
+
+    let connection = pool.connection().await?;
+
+    Ok(pagination_response("/api/v1/nationalities/ranking/", query.0, preferences, connection).await?)
 }
 
 #[rocket::get("/<iso_code>")]
@@ -3,8 +3,9 @@ use pointercrate_core::{error::CoreError, pool::PointercratePool};
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
-    pagination_response,
+    pagination::{pagination_response, Paginated, PaginationPreferences},
     query::Query,
+    ratelimits::RatelimitTracker,
     response::Response2,
 };
 use pointercrate_demonlist::{
@@ -12,9 +13,13 @@ use pointercrate_demonlist::{
     nationality::Nationality,
     player::{
         claim::{ListedClaim, PatchPlayerClaim, PlayerClaim, PlayerClaimPagination},
-        DatabasePlayer, FullPlayer, PatchPlayer, Player, PlayerPagination, RankedPlayer, RankingPagination,
+        merge::{MergePlayers, MergePreview, PlayerMerge},
+        name::SimilarPlayer,
+        DatabasePlayer, FullPlayer, PatchPlayer, Player, PlayerMergePagination, PlayerPagination, RankedPlayer, RankingPagination,
     },
-    LIST_HELPER,
+    ranking_history::{PlayerRankingHistory, RankingHistoryFilter},
+    webhook::{self, WebhookEvent},
+    LIST_HELPER, LIST_MODERATOR,
 };
 use pointercrate_user::MODERATOR;
 use pointercrate_user_api::auth::TokenAuth;
@@ -24,10 +29,15 @@ use std::net::IpAddr;
 
 #[rocket::get("/")]
 pub async fn paginate(
-    pool: &State<PointercratePool>, query: Query<PlayerPagination>, auth: Option<TokenAuth>,
-) -> Result<Response2<Json<Vec<Player>>>> {
+    ip: IpAddr, pool: &State<PointercratePool>, query: Query<PlayerPagination>, auth: Option<TokenAuth>,
+    preferences: PaginationPreferences, ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<Player>> {
+    if preferences.export.is_some() {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
+
     let mut pagination = query.0;
-    let mut connection = pool.connection().await?;
+    let connection = pool.connection().await?;
 
     if let Some(auth) = auth {
         if !auth.has_permission(LIST_HELPER) {
@@ -37,39 +47,36 @@ pub async fn paginate(
         pagination.banned = Some(false);
     }
 
-    let mut players = pagination.page(&mut *connection).await?;
-    let (max_id, min_id) = Player::extremal_player_ids(&mut *connection).await?;
-
-    pagination_response!(
-        "/api/v1/players/",
-        players,
-        pagination,
-        min_id,
-        max_id,
-        before_id,
-        after_id,
-        base.id
-    )
+    Ok(pagination_response("/api/v1/players/", pagination, preferences, connection).await?)
 }
 
 #[rocket::get("/ranking")]
-pub async fn ranking(pool: &State<PointercratePool>, query: Query<RankingPagination>) -> Result<Response2<Json<Vec<RankedPlayer>>>> {
-    let mut pagination = query.0;
+pub async fn ranking(
+    ip: IpAddr, pool: &State<PointercratePool>, query: Query<RankingPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<RankedPlayer>> {
+    if preferences.export.is_some() {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
+
+    let connection = pool.connection().await?;
+
+    Ok(pagination_response("/api/v1/players/ranking/", query.0, preferences, connection).await?)
+}
+
+/// Searches for players whose names look like the given name, or differ from it by a single character
+///
+/// Banned players are only included for users with `LIST_HELPER` permissions.
+#[rocket::get("/similar?<name>")]
+pub async fn similar(name: String, pool: &State<PointercratePool>, auth: Option<TokenAuth>) -> Result<Json<Vec<SimilarPlayer>>> {
     let mut connection = pool.connection().await?;
+    let mut similar = DatabasePlayer::similar_to(&name, &mut *connection).await?;
 
-    let mut players = pagination.page(&mut *connection).await?;
-    let max_index = RankedPlayer::max_index(&mut *connection).await?;
+    if !matches!(auth, Some(ref auth) if auth.has_permission(LIST_HELPER)) {
+        similar.retain(|similar| !similar.player.banned);
+    }
 
-    pagination_response!(
-        "/api/v1/players/ranking/",
-        players,
-        pagination,
-        1,
-        max_index,
-        before_index,
-        after_index,
-        index
-    )
+    Ok(Json(similar))
 }
 
 #[rocket::get("/<player_id>")]
@@ -81,6 +88,18 @@ pub async fn get(player_id: i32, pool: &State<PointercratePool>) -> Result<Tagge
     ))
 }
 
+/// The daily history of a player's rank and score, as well as of those of the player's nation and
+/// subdivision
+#[rocket::get("/<player_id>/history")]
+pub async fn history(
+    player_id: i32, query: Query<RankingHistoryFilter>, pool: &State<PointercratePool>,
+) -> Result<Json<PlayerRankingHistory>> {
+    let mut connection = pool.connection().await?;
+    let player = Player::by_id(player_id, &mut *connection).await?;
+
+    Ok(Json(PlayerRankingHistory::of(&player, &query.0, &mut *connection).await?))
+}
+
 #[rocket::patch("/<player_id>", data = "<patch>")]
 pub async fn patch(
     player_id: i32, mut auth: TokenAuth, precondition: Precondition, patch: Json<PatchPlayer>,
@@ -89,15 +108,81 @@ pub async fn patch(
         .await?
         .upgrade(&mut auth.connection)
         .await?
-        .require_match(precondition)?
-        .apply_patch(patch.0, &mut auth.connection)
-        .await?;
+        .require_match(precondition)?;
+    let was_banned = player.player.base.banned;
+    let player = player.apply_patch(patch.0, &mut auth.connection).await?;
+
+    if player.player.base.banned && !was_banned {
+        webhook::dispatch(WebhookEvent::PlayerBanned, &player.player, &mut auth.connection).await?;
+    }
 
     auth.commit().await?;
 
     Ok(Tagged(player))
 }
 
+/// Merges the player given in the body into the player with the given id, deleting the former
+///
+/// Responds with a description of what the merge did. If `dry_run` is set, only reports what the
+/// merge would do, without actually merging.
+#[rocket::post("/<player_id>/merge", data = "<data>")]
+pub async fn merge(player_id: i32, mut auth: TokenAuth, data: Json<MergePlayers>) -> Result<Response2<Json<MergePreview>>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let mut player = Player::by_id(player_id, &mut auth.connection)
+        .await?
+        .upgrade(&mut auth.connection)
+        .await?;
+    let merged = DatabasePlayer::by_id(data.player, &mut auth.connection).await?;
+
+    if data.dry_run {
+        return Ok(Response2::json(
+            MergePreview::of(&player.player.base, &merged, &mut auth.connection).await?,
+        ));
+    }
+
+    let (merge, preview) = player.merge(merged, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Response2::json(preview)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v1/players/merges/{}/", merge.id)))
+}
+
+#[rocket::get("/merges")]
+pub async fn merges(
+    ip: IpAddr, mut auth: TokenAuth, query: Query<PlayerMergePagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<PlayerMerge>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    if preferences.export.is_some() && !auth.has_permission(LIST_HELPER) {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
+
+    Ok(pagination_response("/api/v1/players/merges/", query.0, preferences, auth.connection).await?)
+}
+
+#[rocket::get("/merges/<merge_id>")]
+pub async fn get_merge(merge_id: i32, mut auth: TokenAuth) -> Result<Json<PlayerMerge>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    Ok(Json(PlayerMerge::by_id(merge_id, &mut auth.connection).await?))
+}
+
+#[rocket::post("/merges/<merge_id>/revert")]
+pub async fn revert_merge(merge_id: i32, mut auth: TokenAuth) -> Result<Json<PlayerMerge>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    let mut merge = PlayerMerge::by_id(merge_id, &mut auth.connection).await?;
+
+    merge.revert(&mut auth.connection).await?;
+    auth.commit().await?;
+
+    Ok(Json(merge))
+}
+
 #[rocket::put("/<player_id>/claims")]
 pub async fn put_claim(player_id: i32, mut auth: TokenAuth) -> Result<Response2<Json<PlayerClaim>>> {
     let user_id = auth.user.inner().id;
@@ -148,8 +233,13 @@ pub async fn patch_claim(player_id: i32, user_id: i32, mut auth: TokenAuth, data
         },
     };
 
+    let was_verified = claim.verified;
     let claim = claim.apply_patch(data.0, &mut auth.connection).await?;
 
+    if claim.verified && !was_verified {
+        webhook::dispatch(WebhookEvent::ClaimVerified, &claim, &mut auth.connection).await?;
+    }
+
     auth.commit().await?;
 
     Ok(Json(claim))
@@ -168,27 +258,17 @@ pub async fn delete_claim(player_id: i32, user_id: i32, mut auth: TokenAuth) ->
 }
 
 #[rocket::get("/claims")]
-pub async fn paginate_claims(mut auth: TokenAuth, pagination: Query<PlayerClaimPagination>) -> Result<Response2<Json<Vec<ListedClaim>>>> {
+pub async fn paginate_claims(
+    ip: IpAddr, auth: TokenAuth, pagination: Query<PlayerClaimPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<ListedClaim>> {
     auth.require_permission(MODERATOR)?;
 
-    let mut pagination = pagination.0;
-
-    let mut claims = pagination.page(&mut auth.connection).await?;
-    let (max_id, min_id) = match ListedClaim::extremal_ids(&mut auth.connection).await {
-        Err(_) => return Ok(Response2::json(Vec::new())), // handle empty table case!
-        Ok(data) => data,
-    };
+    if preferences.export.is_some() && !auth.has_permission(LIST_HELPER) {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
 
-    pagination_response!(
-        "/api/v1/players/claims/",
-        claims,
-        pagination,
-        min_id,
-        max_id,
-        before_id,
-        after_id,
-        id
-    )
+    Ok(pagination_response("/api/v1/players/claims/", pagination.0, preferences, auth.connection).await?)
 }
 
 #[derive(Deserialize, Debug)]
@@ -205,7 +285,7 @@ struct GeolocationResponse {
 
 #[rocket::post("/<player_id>/geolocate")]
 pub async fn geolocate_nationality(
-    player_id: i32, ip: IpAddr, mut auth: TokenAuth, ratelimits: &State<DemonlistRatelimits>,
+    player_id: i32, ip: IpAddr, mut auth: TokenAuth, ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
 ) -> Result<Json<Nationality>> {
     let mut player = Player::by_id(player_id, &mut auth.connection).await?;
     let claim = PlayerClaim::get(auth.user.inner().id, player_id, &mut auth.connection).await?;
@@ -214,7 +294,7 @@ pub async fn geolocate_nationality(
         return Err(DemonlistError::ClaimUnverified.into());
     }
 
-    ratelimits.geolocate(ip)?;
+    tracker.track(ratelimits.geolocate(Some(auth.user.inner().id), ip).await)?;
 
     let response = reqwest::get(format!(
         "https://ipgeolocation.abstractapi.com/v1/?api_key={}&ip_address={}&fields=security,country_code,region_iso_code",
@@ -1,27 +1,31 @@
-use crate::ratelimits::DemonlistRatelimits;
-use log::{debug, error, warn};
-use pointercrate_core::{audit::AuditLogEntry, error::CoreError, pool::PointercratePool};
+use crate::{jobs::ValidateSubmission, ratelimits::DemonlistRatelimits};
+use pointercrate_core::{audit::AuditLogEntry, error::CoreError, job, pool::PointercratePool};
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
-    pagination_response,
+    pagination::{pagination_response, Paginated, PaginationPreferences},
     query::Query,
+    ratelimits::RatelimitTracker,
     response::Response2,
 };
 use pointercrate_demonlist::{
     error::DemonlistError,
+    list::List,
     player::claim::PlayerClaim,
+    player::DatabasePlayer,
     record::{
         audit::RecordModificationData,
+        metadata::VideoMetadata,
         note::{notes_on, NewNote, Note, PatchNote},
-        FullRecord, MinimalRecordPD, PatchRecord, RecordPagination, RecordStatus, Submission,
+        FullRecord, MinimalRecordPD, PatchRecord, PostPartner, RecordPagination, RecordStatus, Submission,
     },
+    review::{self, ReviewAssignment},
     submitter::Submitter,
+    webhook::{self, WebhookEvent},
     LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use pointercrate_user_api::auth::TokenAuth;
-use rocket::{http::Status, serde::json::Json, tokio, State};
-use sqlx::{pool::PoolConnection, Postgres};
+use rocket::{http::Status, serde::json::Json, State};
 use std::net::IpAddr;
 
 /// Pagination endpoint for records in case authentication is provided
@@ -33,8 +37,16 @@ use std::net::IpAddr;
 /// `APPROVED` is allowed, UNLESS we also filter by player and the player we filter by match a
 /// verified claim of the user making the request, in which case access to all records is allowed
 /// (the `status` property does not get defaulted, and filtering on it is allowed)
+/// + Exports are ratelimited for users without `LIST_HELPER` permissions
 #[rocket::get("/")]
-pub async fn paginate(mut auth: TokenAuth, query: Query<RecordPagination>) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
+pub async fn paginate(
+    ip: IpAddr, mut auth: TokenAuth, query: Query<RecordPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<MinimalRecordPD>> {
+    if preferences.export.is_some() && !auth.has_permission(LIST_HELPER) {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
+
     let mut pagination = query.0;
 
     if pagination.submitter.is_some() {
@@ -53,18 +65,19 @@ pub async fn paginate(mut auth: TokenAuth, query: Query<RecordPagination>) -> Re
         pagination.status = Some(RecordStatus::Approved);
     }
 
-    let mut records = pagination.page(&mut auth.connection).await?;
-
-    let (max_id, min_id) = FullRecord::extremal_record_ids(&mut auth.connection).await?;
-
-    pagination_response!("/api/v1/records/", records, pagination, min_id, max_id, before_id, after_id, id)
+    Ok(pagination_response("/api/v1/records/", pagination, preferences, auth.connection).await?)
 }
 
 #[rocket::get("/", rank = 1)]
 pub async fn unauthed_pagination(
-    pool: &State<PointercratePool>, query: Query<RecordPagination>,
-) -> Result<Response2<Json<Vec<MinimalRecordPD>>>> {
-    let mut connection = pool.connection().await?;
+    ip: IpAddr, pool: &State<PointercratePool>, query: Query<RecordPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<MinimalRecordPD>> {
+    if preferences.export.is_some() {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
+
+    let connection = pool.connection().await?;
     let mut pagination = query.0;
 
     if pagination.submitter.is_some() {
@@ -77,17 +90,13 @@ pub async fn unauthed_pagination(
 
     pagination.status = Some(RecordStatus::Approved);
 
-    let mut records = pagination.page(&mut *connection).await?;
-
-    let (max_id, min_id) = FullRecord::extremal_record_ids(&mut *connection).await?;
-
-    pagination_response!("/api/v1/records/", records, pagination, min_id, max_id, before_id, after_id, id)
+    Ok(pagination_response("/api/v1/records/", pagination, preferences, connection).await?)
 }
 
 #[rocket::post("/", data = "<submission>")]
 pub async fn submit(
     ip: IpAddr, auth: Option<TokenAuth>, submission: Json<Submission>, pool: &State<PointercratePool>,
-    ratelimits: &State<DemonlistRatelimits>,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
 ) -> Result<Tagged<FullRecord>> {
     let submission = submission.0;
     let (is_team_member, user_id) = match auth {
@@ -95,7 +104,7 @@ pub async fn submit(
         None => (false, None),
     };
 
-    if submission.status() != RecordStatus::Submitted || !submission.has_video() {
+    if submission.status() != RecordStatus::Submitted || !submission.has_video() || submission.creates_new_players() {
         match auth {
             Some(ref auth) => auth.require_permission(LIST_HELPER)?,
             None => return Err(CoreError::Unauthorized.into()),
@@ -110,7 +119,7 @@ pub async fn submit(
     let submitter = match Submitter::by_ip(ip, &mut *connection).await? {
         Some(submitter) => submitter,
         None => {
-            ratelimits.new_submitters()?;
+            tracker.track(ratelimits.new_submitters().await)?;
 
             Submitter::create_submitter(ip, &mut *connection).await?
         },
@@ -123,8 +132,8 @@ pub async fn submit(
 
     let normalized = submission.normalize(&mut *connection).await?;
 
-    // check if the player is claimed with submissions locked
-    if let Some(claim) = normalized.verified_player_claim(&mut *connection).await? {
+    // check if any credited player is claimed with submissions locked
+    for claim in normalized.verified_player_claims(&mut *connection).await? {
         if claim.lock_submissions {
             match user_id {
                 Some(user_id) if user_id == claim.user_id => (),
@@ -140,26 +149,21 @@ pub async fn submit(
         // easier.
 
         // Also check the local ratelimit first since that one expires earlier
-        ratelimits.record_submission(ip)?;
-        ratelimits.record_submission_global()?;
+        tracker.track(ratelimits.record_submission(user_id, ip).await)?;
+        tracker.track(ratelimits.record_submission_global().await)?;
     }
 
     let mut record = validated.create(submitter, &mut *connection).await?;
 
-    connection.commit().await.map_err(DemonlistError::from)?;
-
-    // FIXME: This is fucking stupid
-    if record.status == RecordStatus::Submitted {
-        if let Some(ref video) = record.video {
-            tokio::spawn(validate(
-                record.id,
-                video.to_string(),
-                webhook_embed(&record),
-                pool.connection().await?,
-            ));
-        }
+    // Submissions with a video only get announced once the video has been validated
+    if record.status == RecordStatus::Submitted && record.video.is_some() {
+        job::enqueue(&ValidateSubmission { record_id: record.id }, &mut *connection).await?;
+    } else if let Some(event) = WebhookEvent::for_record_status(record.status) {
+        webhook::dispatch(event, &record, &mut *connection).await?;
     }
 
+    connection.commit().await.map_err(DemonlistError::from)?;
+
     if !is_team_member {
         record.submitter = None;
     }
@@ -167,11 +171,16 @@ pub async fn submit(
     Ok(Tagged(record))
 }
 
+/// Retrieves a record
+///
+/// Users without `LIST_HELPER` permissions can only retrieve approved records, unless they hold a
+/// verified claim on the record's player or one of its partners, in which case they can track their
+/// submissions (including the reason for a rejection).
 #[rocket::get("/<record_id>")]
 pub async fn get(record_id: i32, auth: Option<TokenAuth>, pool: &State<PointercratePool>) -> Result<Tagged<FullRecord>> {
-    let is_helper = match auth {
-        Some(ref auth) => auth.has_permission(LIST_HELPER),
-        _ => false,
+    let (is_helper, user_id) = match auth {
+        Some(ref auth) => (auth.has_permission(LIST_HELPER), Some(auth.user.inner().id)),
+        _ => (false, None),
     };
 
     let mut connection = match auth {
@@ -181,10 +190,16 @@ pub async fn get(record_id: i32, auth: Option<TokenAuth>, pool: &State<Pointercr
 
     let mut record = FullRecord::by_id(record_id, &mut *connection).await?;
 
-    // TODO: allow access if auth is provided and a verified claim on the record's player is given
     if !is_helper {
         if record.status != RecordStatus::Approved {
-            return Err(DemonlistError::RecordNotFound { record_id }.into());
+            let is_claimant = match user_id {
+                Some(user_id) => PlayerClaim::verified_claim_on_record(user_id, record.id, &mut *connection).await?,
+                None => false,
+            };
+
+            if !is_claimant {
+                return Err(DemonlistError::RecordNotFound { record_id }.into());
+            }
         }
         record.submitter = None;
     }
@@ -211,22 +226,85 @@ pub async fn patch(
 ) -> Result<Tagged<FullRecord>> {
     let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
 
-    if record.demon.position > pointercrate_demonlist::config::extended_list_size() {
-        auth.require_permission(LIST_MODERATOR)?;
-    } else {
-        auth.require_permission(LIST_HELPER)?;
-    }
+    require_modification_permission(&record, &mut auth).await?;
 
+    let old_status = record.status;
     let record = record
         .require_match(precondition)?
         .apply_patch(patch.0, &mut auth.connection)
         .await?;
 
+    if record.status != old_status {
+        if old_status == RecordStatus::Submitted {
+            review::complete_review(record.id, auth.user.inner().id, record.status, &mut auth.connection).await?;
+        }
+
+        if let Some(event) = WebhookEvent::for_record_status(record.status) {
+            webhook::dispatch(event, &record, &mut auth.connection).await?;
+        }
+    }
+
     auth.commit().await?;
 
     Ok(Tagged(record))
 }
 
+#[rocket::post("/<record_id>/partners", data = "<partner>")]
+pub async fn post_partner(record_id: i32, mut auth: TokenAuth, partner: Json<PostPartner>) -> Result<Response2<Tagged<FullRecord>>> {
+    let mut record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+
+    require_modification_permission(&record, &mut auth).await?;
+
+    let player = DatabasePlayer::by_name_or_create(&partner.player, &mut auth.connection).await?;
+    let player_id = player.id;
+
+    record.add_partner(player, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Response2::tagged(record)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v1/records/{}/partners/{}/", record_id, player_id)))
+}
+
+#[rocket::delete("/<record_id>/partners/<player_id>")]
+pub async fn delete_partner(record_id: i32, player_id: i32, mut auth: TokenAuth) -> Result<Status> {
+    let mut record = FullRecord::by_id(record_id, &mut auth.connection).await?;
+
+    require_modification_permission(&record, &mut auth).await?;
+
+    record.remove_partner(player_id, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
+/// Records on demons beyond the extended list can only be modified by list moderators
+///
+/// Submissions currently assigned to a list helper via the review queue can only be modified by
+/// that helper (and list moderators)
+async fn require_modification_permission(record: &FullRecord, auth: &mut TokenAuth) -> Result<()> {
+    let list = List::of_demon(record.demon.id, &mut auth.connection).await?;
+
+    if record.demon.position > list.extended_list_size {
+        auth.require_permission(LIST_MODERATOR)?;
+    } else {
+        auth.require_permission(LIST_HELPER)?;
+    }
+
+    if let Some(assignment) = ReviewAssignment::of_record(record.id, &mut auth.connection).await? {
+        if assignment.reviewer != auth.user.inner().id && !auth.has_permission(LIST_MODERATOR) {
+            return Err(DemonlistError::RecordUnderReview {
+                reviewer: assignment.reviewer,
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
 #[rocket::delete("/<record_id>")]
 pub async fn delete(record_id: i32, mut auth: TokenAuth, precondition: Precondition) -> Result<Status> {
     let record = FullRecord::by_id(record_id, &mut auth.connection).await?;
@@ -245,9 +323,17 @@ pub async fn delete(record_id: i32, mut auth: TokenAuth, precondition: Precondit
     Ok(Status::NoContent)
 }
 
+/// The metadata fetched about a record's video, including any flags raised while checking it
+#[rocket::get("/<record_id>/video")]
+pub async fn get_video_metadata(record_id: i32, mut auth: TokenAuth) -> Result<Json<VideoMetadata>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(VideoMetadata::of_record(record_id, &mut auth.connection).await?))
+}
+
 #[rocket::get("/<record_id>/notes")]
 pub async fn get_notes(record_id: i32, mut auth: TokenAuth) -> Result<Response2<Json<Vec<Note>>>> {
-    let record_holder_id = sqlx::query!("SELECT player FROM records WHERE id = $1", record_id)
+    sqlx::query!("SELECT player FROM records WHERE id = $1", record_id)
         .fetch_one(&mut *auth.connection)
         .await
         .map_err(|err| {
@@ -256,17 +342,14 @@ pub async fn get_notes(record_id: i32, mut auth: TokenAuth) -> Result<Response2<
             } else {
                 err.into()
             }
-        })?
-        .player;
+        })?;
 
     let notes = if auth.has_permission(LIST_HELPER) {
         notes_on(record_id, false, &mut auth.connection).await?
+    } else if PlayerClaim::verified_claim_on_record(auth.user.inner().id, record_id, &mut auth.connection).await? {
+        notes_on(record_id, true, &mut auth.connection).await?
     } else {
-        match PlayerClaim::get(auth.user.inner().id, record_holder_id, &mut auth.connection).await {
-            Ok(claim) if claim.verified => notes_on(record_id, true, &mut auth.connection).await?,
-            Ok(_) | Err(DemonlistError::ClaimNotFound { .. }) => return Err(DemonlistError::RecordNotFound { record_id }.into()),
-            Err(err) => return Err(err.into()),
-        }
+        return Err(DemonlistError::RecordNotFound { record_id }.into());
     };
 
     Ok(Response2::json(notes))
@@ -324,88 +407,3 @@ pub async fn delete_note(record_id: i32, note_id: i32, mut auth: TokenAuth) -> R
 
     Ok(Status::NoContent)
 }
-
-async fn validate(record_id: i32, video: String, body: serde_json::Value, mut connection: PoolConnection<Postgres>) {
-    debug!("Verifying that submission {} with video {} actually is valid", record_id, video);
-
-    match reqwest::get(&video).await {
-        Ok(response) => {
-            let status = response.status().as_u16();
-
-            if (200..400).contains(&status) {
-                debug!("GET request yielded some sort of successful response, executing webhook");
-
-                execute_webhook(body).await;
-            } else {
-                warn!("Server response to 'GET {}' was {:?}, deleting submission!", video, response);
-
-                match FullRecord::delete_by_id(record_id, &mut *connection).await {
-                    Ok(_) => (),
-                    Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-                }
-            }
-        },
-        Err(error) => {
-            error!(
-                "INTERNAL SERVER ERROR: GET request to verify video failed: {:?}. Deleting submission",
-                error
-            );
-
-            match FullRecord::delete_by_id(record_id, &mut *connection).await {
-                Ok(_) => (),
-                Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-            }
-        },
-    }
-}
-
-async fn execute_webhook(body: serde_json::Value) {
-    if let Some(ref webhook_url) = crate::config::submission_webhook() {
-        match reqwest::Client::new()
-            .post(webhook_url)
-            .header("Content-Type", "application/json")
-            .body(body.to_string())
-            .send()
-            .await
-        {
-            Err(error) => error!("INTERNAL SERVER ERROR: Failure to execute discord webhook: {:?}", error),
-            Ok(_) => debug!("Successfully executed discord webhook"),
-        }
-    } else {
-        warn!("Trying to execute webhook, though no link was configured!");
-    }
-}
-
-fn webhook_embed(record: &FullRecord) -> serde_json::Value {
-    let mut payload = serde_json::json!({
-        "content": format!("**New record submitted! ID: {}**", record.id),
-        "embeds": [
-            {
-                "type": "rich",
-                "title": format!("{}% on {}", record.progress, record.demon.name),
-                "description": format!("{} just got {}% on {}! Go add their record!", record.player.name, record.progress, record.demon.name),
-                "footer": {
-                    "text": format!("This record has been submitted by submitter #{}", record.submitter.map(|s|s.id).unwrap_or(1))
-                },
-                "author": {
-                    "name": format!("{} (ID: {})", record.player.name, record.player.id),
-                    "url": record.video
-                },
-                "thumbnail": {
-                    "url": "https://cdn.discordapp.com/avatars/277391246035648512/b03c85d94dc02084c413a7fdbe2cea79.webp?size=1024"
-                },
-            }
-        ]
-    });
-
-    if let Some(ref video) = record.video {
-        payload["embeds"][0]["fields"] = serde_json::json! {
-            [{
-                "name": "Video Proof:",
-                "value": video
-            }]
-        };
-    }
-
-    payload
-}
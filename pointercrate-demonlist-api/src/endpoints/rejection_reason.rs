@@ -0,0 +1,47 @@
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::{error::Result, response::Response2};
+use pointercrate_demonlist::{
+    record::rejection::{PatchRejectionReason, PostRejectionReason, RejectionReason},
+    LIST_ADMINISTRATOR,
+};
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::{http::Status, serde::json::Json, State};
+
+#[rocket::get("/")]
+pub async fn list(pool: &State<PointercratePool>) -> Result<Json<Vec<RejectionReason>>> {
+    Ok(Json(RejectionReason::all(&mut *pool.connection().await?).await?))
+}
+
+#[rocket::post("/", data = "<data>")]
+pub async fn post(mut auth: TokenAuth, data: Json<PostRejectionReason>) -> Result<Response2<Json<RejectionReason>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let reason = RejectionReason::create_from(data.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    let reason_id = reason.id;
+
+    Ok(Response2::json(reason)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v2/rejection-reasons/{}/", reason_id)))
+}
+
+#[rocket::get("/<reason_id>")]
+pub async fn get(reason_id: i32, pool: &State<PointercratePool>) -> Result<Json<RejectionReason>> {
+    Ok(Json(RejectionReason::by_id(reason_id, &mut *pool.connection().await?).await?))
+}
+
+#[rocket::patch("/<reason_id>", data = "<patch>")]
+pub async fn patch(reason_id: i32, mut auth: TokenAuth, patch: Json<PatchRejectionReason>) -> Result<Json<RejectionReason>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let reason = RejectionReason::by_id(reason_id, &mut auth.connection)
+        .await?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Json(reason))
+}
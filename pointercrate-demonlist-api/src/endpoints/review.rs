@@ -0,0 +1,85 @@
+use pointercrate_core_api::error::Result;
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    list::List,
+    review::{ReviewAssignment, ReviewStatistics},
+    LIST_HELPER, LIST_MODERATOR,
+};
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::{http::Status, serde::json::Json};
+
+#[rocket::get("/")]
+pub async fn list(mut auth: TokenAuth) -> Result<Json<Vec<ReviewAssignment>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(ReviewAssignment::all_active(&mut auth.connection).await?))
+}
+
+/// Assigns the oldest submission nobody is currently reviewing to the requesting list helper
+///
+/// If the helper already has a submission assigned, that assignment is returned instead
+#[rocket::post("/next?<list>")]
+pub async fn next(list: Option<i32>, mut auth: TokenAuth) -> Result<Json<ReviewAssignment>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    if let Some(list_id) = list {
+        // Errors out if the list doesn't exist
+        List::by_id(list_id, &mut auth.connection).await?;
+    }
+
+    let assignment = ReviewAssignment::next_for(auth.user.inner().id, list, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(assignment))
+}
+
+#[rocket::post("/<record_id>/renew")]
+pub async fn renew(record_id: i32, mut auth: TokenAuth) -> Result<Json<ReviewAssignment>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let mut assignment = match ReviewAssignment::of_record(record_id, &mut auth.connection).await? {
+        Some(assignment) if assignment.reviewer == auth.user.inner().id => assignment,
+        _ => return Err(DemonlistError::ReviewNotFound { record_id }.into()),
+    };
+
+    assignment.renew(&mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(assignment))
+}
+
+/// Puts a submission back into the review queue
+///
+/// List moderators can release submissions assigned to any helper
+#[rocket::delete("/<record_id>")]
+pub async fn release(record_id: i32, mut auth: TokenAuth) -> Result<Status> {
+    auth.require_permission(LIST_HELPER)?;
+
+    let assignment = match ReviewAssignment::of_record(record_id, &mut auth.connection).await? {
+        Some(assignment) => assignment,
+        None => return Err(DemonlistError::ReviewNotFound { record_id }.into()),
+    };
+
+    if assignment.reviewer != auth.user.inner().id {
+        auth.require_permission(LIST_MODERATOR)?;
+    }
+
+    assignment.release(&mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
+/// Statistics about the review queue, and the reviews completed during the last `days` days
+/// (default 30)
+#[rocket::get("/statistics?<days>")]
+pub async fn statistics(days: Option<i32>, mut auth: TokenAuth) -> Result<Json<ReviewStatistics>> {
+    auth.require_permission(LIST_MODERATOR)?;
+
+    Ok(Json(
+        ReviewStatistics::compute(days.unwrap_or(30).max(1), &mut auth.connection).await?,
+    ))
+}
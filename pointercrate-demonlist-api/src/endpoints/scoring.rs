@@ -0,0 +1,67 @@
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::{error::Result, response::Response2};
+use pointercrate_demonlist::{
+    list::List,
+    scoring::{PostScoringFormula, ScoringFormula, ScoringPreviewEntry},
+    LIST_ADMINISTRATOR,
+};
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::{http::Status, serde::json::Json, State};
+
+#[rocket::get("/")]
+pub async fn list(pool: &State<PointercratePool>) -> Result<Json<Vec<ScoringFormula>>> {
+    Ok(Json(ScoringFormula::all(&mut *pool.connection().await?).await?))
+}
+
+#[rocket::get("/active?<list>")]
+pub async fn active(list: Option<i32>, pool: &State<PointercratePool>) -> Result<Json<ScoringFormula>> {
+    let mut connection = pool.connection().await?;
+
+    let list = List::by_id_or_default(list, &mut *connection).await?;
+
+    Ok(Json(ScoringFormula::of_list(&list, &mut *connection).await?))
+}
+
+#[rocket::get("/<formula_id>")]
+pub async fn get(formula_id: i32, pool: &State<PointercratePool>) -> Result<Json<ScoringFormula>> {
+    Ok(Json(ScoringFormula::by_id(formula_id, &mut *pool.connection().await?).await?))
+}
+
+#[rocket::post("/", data = "<data>")]
+pub async fn post(mut auth: TokenAuth, data: Json<PostScoringFormula>) -> Result<Response2<Json<ScoringFormula>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let formula = ScoringFormula::create_from(data.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    let formula_id = formula.id;
+
+    Ok(Response2::json(formula)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v2/scoring/{}/", formula_id)))
+}
+
+#[rocket::post("/<formula_id>/activate?<list>")]
+pub async fn activate(formula_id: i32, list: Option<i32>, mut auth: TokenAuth) -> Result<Json<ScoringFormula>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let formula = ScoringFormula::by_id(formula_id, &mut auth.connection).await?;
+    let mut list = List::by_id_or_default(list, &mut auth.connection).await?;
+
+    formula.activate(&mut list, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(formula))
+}
+
+#[rocket::get("/<formula_id>/preview?<limit>&<list>")]
+pub async fn preview(formula_id: i32, limit: Option<u8>, list: Option<i32>, mut auth: TokenAuth) -> Result<Json<Vec<ScoringPreviewEntry>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let formula = ScoringFormula::by_id(formula_id, &mut auth.connection).await?;
+    let list = List::by_id_or_default(list, &mut auth.connection).await?;
+
+    Ok(Json(formula.preview(&list, limit, &mut auth.connection).await?))
+}
@@ -0,0 +1,23 @@
+use pointercrate_core_api::error::Result;
+use pointercrate_demonlist::{record::transition::StatusTransition, LIST_ADMINISTRATOR, LIST_HELPER};
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::serde::json::Json;
+
+#[rocket::get("/")]
+pub async fn list(mut auth: TokenAuth) -> Result<Json<Vec<StatusTransition>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(StatusTransition::all(&mut auth.connection).await?))
+}
+
+/// Replaces the set of allowed status transitions with the given one
+#[rocket::put("/", data = "<transitions>")]
+pub async fn put(mut auth: TokenAuth, transitions: Json<Vec<StatusTransition>>) -> Result<Json<Vec<StatusTransition>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let transitions = StatusTransition::replace_all(transitions.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Json(transitions))
+}
@@ -1,37 +1,31 @@
+use crate::ratelimits::DemonlistRatelimits;
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, TaggableExt, Tagged},
-    pagination_response,
+    pagination::{pagination_response, Paginated, PaginationPreferences},
     query::Query,
-    response::Response2,
+    ratelimits::RatelimitTracker,
 };
 use pointercrate_demonlist::{
     submitter::{PatchSubmitter, Submitter, SubmitterPagination},
-    LIST_ADMINISTRATOR, LIST_MODERATOR,
+    LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use pointercrate_user_api::auth::TokenAuth;
-use rocket::serde::json::Json;
+use rocket::{serde::json::Json, State};
+use std::net::IpAddr;
 
 #[rocket::get("/")]
-pub async fn paginate(mut auth: TokenAuth, pagination: Query<SubmitterPagination>) -> Result<Response2<Json<Vec<Submitter>>>> {
+pub async fn paginate(
+    ip: IpAddr, auth: TokenAuth, pagination: Query<SubmitterPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<Submitter>> {
     auth.require_permission(LIST_MODERATOR)?;
 
-    let mut pagination = pagination.0;
+    if preferences.export.is_some() && !auth.has_permission(LIST_HELPER) {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
 
-    let mut submitters = pagination.page(&mut auth.connection).await?;
-
-    let (max_id, min_id) = Submitter::extremal_submitter_ids(&mut auth.connection).await?;
-
-    pagination_response!(
-        "/api/v1/submitters/",
-        submitters,
-        pagination,
-        min_id,
-        max_id,
-        before_id,
-        after_id,
-        id
-    )
+    Ok(pagination_response("/api/v1/submitters/", pagination.0, preferences, auth.connection).await?)
 }
 
 #[rocket::get("/<submitter_id>")]
@@ -0,0 +1,13 @@
+use pointercrate_core_api::error::Result;
+use pointercrate_demonlist::{link_health::BrokenLink, LIST_HELPER};
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::serde::json::Json;
+
+/// All videos of approved records and demons that were found to be deleted or private during the
+/// last periodic check
+#[rocket::get("/broken")]
+pub async fn broken(mut auth: TokenAuth) -> Result<Json<Vec<BrokenLink>>> {
+    auth.require_permission(LIST_HELPER)?;
+
+    Ok(Json(BrokenLink::all(&mut auth.connection).await?))
+}
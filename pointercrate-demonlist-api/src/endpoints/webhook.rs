@@ -0,0 +1,99 @@
+use crate::ratelimits::DemonlistRatelimits;
+use pointercrate_core_api::{
+    error::Result,
+    pagination::{pagination_response, Paginated, PaginationPreferences},
+    query::Query,
+    ratelimits::RatelimitTracker,
+    response::Response2,
+};
+use pointercrate_demonlist::{
+    webhook::{PatchWebhook, PostWebhook, Webhook, WebhookDelivery, WebhookDeliveryPagination},
+    LIST_ADMINISTRATOR, LIST_HELPER,
+};
+use pointercrate_user_api::auth::TokenAuth;
+use rocket::{http::Status, serde::json::Json, State};
+use std::net::IpAddr;
+
+#[rocket::get("/")]
+pub async fn list(mut auth: TokenAuth) -> Result<Json<Vec<Webhook>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(Webhook::all(&mut auth.connection).await?))
+}
+
+#[rocket::post("/", data = "<data>")]
+pub async fn post(mut auth: TokenAuth, data: Json<PostWebhook>) -> Result<Response2<Json<Webhook>>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let webhook = Webhook::create_from(data.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    let webhook_id = webhook.id;
+
+    Ok(Response2::json(webhook)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v1/webhooks/{}/", webhook_id)))
+}
+
+#[rocket::get("/<webhook_id>")]
+pub async fn get(webhook_id: i32, mut auth: TokenAuth) -> Result<Json<Webhook>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Ok(Json(Webhook::by_id(webhook_id, &mut auth.connection).await?))
+}
+
+#[rocket::patch("/<webhook_id>", data = "<patch>")]
+pub async fn patch(webhook_id: i32, mut auth: TokenAuth, patch: Json<PatchWebhook>) -> Result<Json<Webhook>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    let webhook = Webhook::by_id(webhook_id, &mut auth.connection)
+        .await?
+        .apply_patch(patch.0, &mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Json(webhook))
+}
+
+#[rocket::delete("/<webhook_id>")]
+pub async fn delete(webhook_id: i32, mut auth: TokenAuth) -> Result<Status> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    Webhook::by_id(webhook_id, &mut auth.connection)
+        .await?
+        .delete(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Status::NoContent)
+}
+
+#[rocket::get("/<webhook_id>/deliveries")]
+pub async fn deliveries(
+    ip: IpAddr, webhook_id: i32, mut auth: TokenAuth, query: Query<WebhookDeliveryPagination>, preferences: PaginationPreferences,
+    ratelimits: &State<DemonlistRatelimits>, tracker: RatelimitTracker<'_>,
+) -> Result<Paginated<WebhookDelivery>> {
+    auth.require_permission(LIST_ADMINISTRATOR)?;
+
+    if preferences.export.is_some() && !auth.has_permission(LIST_HELPER) {
+        tracker.track(ratelimits.export(ip).await)?;
+    }
+
+    // Makes requests for the delivery log of non-existing webhooks 404 instead of returning an empty list
+    let webhook = Webhook::by_id(webhook_id, &mut auth.connection).await?;
+
+    let mut pagination = query.0;
+
+    pagination.webhook_id = webhook.id;
+
+    Ok(pagination_response(
+        &format!("/api/v1/webhooks/{}/deliveries/", webhook.id),
+        pagination,
+        preferences,
+        auth.connection,
+    )
+    .await?)
+}
@@ -0,0 +1,379 @@
+//! Background jobs of the demonlist, see [`pointercrate_core::job`]
+
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use pointercrate_core::{
+    job::{self, Job, JobQueue},
+    pool::audit_connection,
+    video,
+};
+use pointercrate_demonlist::{
+    error::DemonlistError,
+    link_health::{self, LinkHealth},
+    ranking_history,
+    record::{
+        metadata::{OEmbedResponse, VideoMetadata},
+        FullRecord, RecordStatus,
+    },
+    webhook::{self, DeliverWebhook, Webhook, WebhookDelivery, WebhookEvent},
+};
+use pointercrate_integrate::gd::{DownloadDemon, FindDemon, PgCache};
+use reqwest::{Client, StatusCode};
+use rocket::{futures::future::join_all, tokio::time::sleep};
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, Pool, Postgres};
+use std::{collections::HashMap, time::Duration};
+
+/// Job fetching metadata about the video of a newly submitted record
+///
+/// The metadata is stored as [`VideoMetadata`], flagging submissions whose video is unavailable or
+/// otherwise suspicious for list helpers. Afterwards, a [`WebhookEvent::RecordSubmitted`] event is
+/// dispatched.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidateSubmission {
+    pub record_id: i32,
+}
+
+impl Job for ValidateSubmission {
+    const KIND: &'static str = "validate_submission";
+}
+
+/// Job checking a batch of record and demon videos for dead links, see [`link_health`]
+///
+/// Each run schedules the next one, so after being enqueued once, checks continue indefinitely.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckVideoLinks {}
+
+impl Job for CheckVideoLinks {
+    const KIND: &'static str = "check_video_links";
+}
+
+/// Job taking the daily snapshots of all rankings, see [`ranking_history`]
+///
+/// Each run schedules the next one for shortly after the following midnight (UTC) before taking
+/// any snapshots, so after being enqueued once, snapshots continue to be taken every day, even if
+/// some run fails for good.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TakeRankingSnapshots {}
+
+impl Job for TakeRankingSnapshots {
+    const KIND: &'static str = "take_ranking_snapshots";
+}
+
+/// The number of links checked by a single [`CheckVideoLinks`] job
+const LINK_CHECK_BATCH: i64 = 50;
+
+/// The minimum time between two requests to the same video host while checking links
+const HOST_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait before checking the next batch of links if there is nothing left to check
+const LINK_CHECK_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// How long any single request for video metadata may take
+const VIDEO_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where [`ValidateSubmission`] and [`CheckVideoLinks`] jobs request video metadata from
+#[derive(Debug, Clone)]
+pub enum OEmbedSource {
+    /// The oEmbed endpoint of each video's host, see [`VideoHost::oembed_endpoint`](video::VideoHost::oembed_endpoint)
+    Hosts,
+
+    /// A single endpoint queried for all videos, such as a local stand-in during tests
+    Endpoint(String),
+}
+
+impl OEmbedSource {
+    fn endpoint_for(&self, video: &str) -> Option<&str> {
+        match self {
+            OEmbedSource::Hosts => video::hosts().oembed_endpoint(video),
+            OEmbedSource::Endpoint(endpoint) => Some(endpoint.as_str()),
+        }
+    }
+}
+
+/// Creates the queue processing all jobs related to the demonlist
+pub fn queue(pool: Pool<Postgres>, gd: PgCache) -> JobQueue {
+    let http_client = Client::new();
+    let (validation_pool, validation_client) = (pool.clone(), http_client.clone());
+    let (delivery_pool, delivery_client) = (pool.clone(), http_client.clone());
+    let (link_pool, link_client) = (pool.clone(), http_client.clone());
+    let snapshot_pool = pool.clone();
+    let (find_cache, find_client) = (gd.clone(), http_client.clone());
+
+    JobQueue::new(pool)
+        .register(move |job: ValidateSubmission| {
+            validate_submission(job, validation_pool.clone(), validation_client.clone(), OEmbedSource::Hosts)
+        })
+        .register(move |job: DeliverWebhook| deliver_webhook(job, delivery_pool.clone(), delivery_client.clone()))
+        .register(move |job: CheckVideoLinks| check_video_links(job, link_pool.clone(), link_client.clone(), OEmbedSource::Hosts))
+        .register(move |job: TakeRankingSnapshots| take_ranking_snapshots(job, snapshot_pool.clone()))
+        .register(move |job: FindDemon| find_cache.clone().find_demon(find_client.clone(), job.name, job.demon_id))
+        .register(move |job: DownloadDemon| gd.clone().download_demon(http_client.clone(), job.level_id.into(), job.demon_id))
+}
+
+pub async fn validate_submission(
+    job: ValidateSubmission, pool: Pool<Postgres>, http_client: Client, oembed: OEmbedSource,
+) -> Result<(), String> {
+    let mut connection = pool.begin().await.map_err(|err| err.to_string())?;
+
+    audit_connection(&mut *connection, 0).await.map_err(|err| err.to_string())?;
+
+    let record = match FullRecord::by_id(job.record_id, &mut *connection).await {
+        Ok(record) => record,
+        // The submission has already been dealt with (e.g. rejected and deleted) by a list moderator
+        Err(DemonlistError::RecordNotFound { .. }) => return Ok(()),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    let video = match record.video {
+        Some(ref video) if record.status == RecordStatus::Submitted => video,
+        _ => return Ok(()),
+    };
+
+    debug!("Fetching metadata of video {} of submission {}", video, record.id);
+
+    let response = fetch_video_metadata(video, &http_client, &oembed).await?;
+
+    let metadata = VideoMetadata::store(&record, response, &mut *connection)
+        .await
+        .map_err(|err| format!("Failure to store video metadata: {:?}", err))?;
+
+    if !metadata.flags.is_empty() {
+        warn!("Video {} of submission {} was flagged: {:?}", video, record.id, metadata.flags);
+    }
+
+    webhook::dispatch(WebhookEvent::RecordSubmitted, &record, &mut *connection)
+        .await
+        .map_err(|err| format!("Failure to dispatch webhooks: {:?}", err))?;
+
+    connection.commit().await.map_err(|err| err.to_string())
+}
+
+/// Requests metadata about the given video, returning `None` if its host reports the video as
+/// unavailable
+///
+/// Failure to even get a response is most likely a problem on our side (or a temporary outage of
+/// the video host), so it is reported as an error instead.
+async fn fetch_video_metadata(video: &str, http_client: &Client, oembed: &OEmbedSource) -> Result<Option<OEmbedResponse>, String> {
+    match oembed.endpoint_for(video) {
+        Some(endpoint) => {
+            let response = http_client
+                .get(endpoint)
+                .query(&[("url", video), ("format", "json")])
+                .timeout(VIDEO_REQUEST_TIMEOUT)
+                .send()
+                .await
+                .map_err(|err| format!("oEmbed request for {} failed: {:?}", video, err))?;
+
+            match response.status() {
+                status if status.is_success() => Ok(Some(
+                    response
+                        .json::<OEmbedResponse>()
+                        .await
+                        .map_err(|err| format!("Malformed oEmbed response for {}: {:?}", video, err))?,
+                )),
+                // Hosts answer with these for deleted and private videos
+                StatusCode::NOT_FOUND | StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Ok(None),
+                status => Err(format!("oEmbed request for {} failed with {}", video, status)),
+            }
+        },
+        // Without oEmbed, all we can do is check whether the video leads to an error page
+        None => {
+            let response = http_client
+                .get(video)
+                .timeout(VIDEO_REQUEST_TIMEOUT)
+                .send()
+                .await
+                .map_err(|err| format!("GET request to verify video failed: {:?}", err))?;
+            let status = response.status();
+
+            if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+                return Err(format!("GET request to verify video {} failed with {}", video, status));
+            }
+
+            if status.is_success() || status.is_redirection() {
+                Ok(Some(OEmbedResponse::default()))
+            } else {
+                Ok(None)
+            }
+        },
+    }
+}
+
+/// Enqueues the first [`CheckVideoLinks`] job, unless one is already waiting to be run or running
+pub async fn schedule_link_checks(pool: &Pool<Postgres>) {
+    schedule_periodic(&CheckVideoLinks {}, pool).await
+}
+
+/// Enqueues the first [`TakeRankingSnapshots`] job, unless one is already waiting to be run or running
+pub async fn schedule_ranking_snapshots(pool: &Pool<Postgres>) {
+    schedule_periodic(&TakeRankingSnapshots {}, pool).await
+}
+
+/// Enqueues the given job, which is expected to schedule its own successor
+async fn schedule_periodic<J: Job>(job: &J, pool: &Pool<Postgres>) {
+    let result = match pool.acquire().await {
+        Ok(mut connection) => job::enqueue(job, &mut *connection).await.map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+
+    if let Err(err) = result {
+        error!("Failed to schedule periodic {} jobs: {}", J::KIND, err);
+    }
+}
+
+/// Schedules the snapshots of the following day, and takes today's snapshots of all rankings
+pub async fn take_ranking_snapshots(job: TakeRankingSnapshots, pool: Pool<Postgres>) -> Result<(), String> {
+    let now = Utc::now().naive_utc();
+
+    // A few minutes of leeway, so a job run slightly early still files its snapshots under the right day
+    let next = (now.date() + chrono::Duration::days(1)).and_hms_opt(0, 5, 0).unwrap_or(now);
+
+    // Scheduled outside of the transaction below, so that the successor is not lost if taking the
+    // snapshots fails. Retries of this run don't schedule it a second time.
+    let mut connection = pool.acquire().await.map_err(|err| err.to_string())?;
+
+    job::enqueue_successor(&job, (next - now).to_std().unwrap_or_default(), &mut *connection)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let mut transaction = connection.begin().await.map_err(|err| err.to_string())?;
+
+    ranking_history::take_snapshots(now.date(), &mut *transaction)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    transaction.commit().await.map_err(|err| err.to_string())
+}
+
+/// Checks the next batch of video links due for a check, and schedules the next batch
+///
+/// Links to different hosts are checked concurrently, but requests to the same host are spaced out
+/// by at least [`HOST_REQUEST_INTERVAL`], so we don't get rate limited by the hosts.
+pub async fn check_video_links(
+    job: CheckVideoLinks, pool: Pool<Postgres>, http_client: Client, oembed: OEmbedSource,
+) -> Result<(), String> {
+    let mut connection = pool.acquire().await.map_err(|err| err.to_string())?;
+
+    let videos = link_health::due_for_check(LINK_CHECK_BATCH, &mut *connection)
+        .await
+        .map_err(|err| err.to_string())?;
+    let batch_size = videos.len() as i64;
+
+    let mut by_host: HashMap<&str, Vec<String>> = HashMap::new();
+
+    for video in videos {
+        by_host
+            .entry(video::hosts().host_name(&video).unwrap_or_default())
+            .or_default()
+            .push(video);
+    }
+
+    let outcomes = join_all(by_host.into_values().map(|videos| check_host(videos, &http_client, &oembed))).await;
+
+    for (video, health) in outcomes.into_iter().flatten() {
+        link_health::record_check(&video, health, &mut *connection)
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+
+    info!("Checked {} video links", batch_size);
+
+    // If the batch was full, there are probably more links due for a check. These are checked as soon as the hosts'
+    // rate limits allow it.
+    let delay = if batch_size == LINK_CHECK_BATCH {
+        HOST_REQUEST_INTERVAL
+    } else {
+        LINK_CHECK_PERIOD
+    };
+
+    job::enqueue_successor(&job, delay, &mut *connection)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Checks the given links, all of which belong to the same host, one after the other
+async fn check_host(videos: Vec<String>, http_client: &Client, oembed: &OEmbedSource) -> Vec<(String, LinkHealth)> {
+    let mut outcomes = Vec::new();
+
+    for (index, video) in videos.into_iter().enumerate() {
+        if index > 0 {
+            sleep(HOST_REQUEST_INTERVAL).await;
+        }
+
+        let health = match fetch_video_metadata(&video, http_client, oembed).await {
+            Ok(Some(_)) => LinkHealth::Healthy,
+            Ok(None) => {
+                warn!("Video {} seems to have been deleted or made private", video);
+
+                LinkHealth::Broken
+            },
+            Err(err) => {
+                warn!("Failed to check video link: {}", err);
+
+                LinkHealth::Unknown
+            },
+        };
+
+        outcomes.push((video, health));
+    }
+
+    outcomes
+}
+
+/// Makes a single attempt at delivering an event to a webhook, recording the outcome in the
+/// delivery log
+///
+/// Non-2xx responses count as failed attempts, and cause the delivery to be retried.
+pub async fn deliver_webhook(job: DeliverWebhook, pool: Pool<Postgres>, http_client: Client) -> Result<(), String> {
+    let mut connection = pool.acquire().await.map_err(|err| err.to_string())?;
+
+    let mut delivery = match WebhookDelivery::by_id(job.delivery_id, &mut *connection).await {
+        Ok(delivery) => delivery,
+        // The webhook has been deleted in the meantime
+        Err(DemonlistError::WebhookDeliveryNotFound { .. }) => return Ok(()),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    let webhook = Webhook::by_id(delivery.webhook_id, &mut *connection)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    if !webhook.active {
+        debug!("Dropping delivery {} to deactivated webhook {}", delivery.id, webhook.id);
+
+        return Ok(());
+    }
+
+    let body = webhook.body(&delivery).to_string();
+
+    let result = http_client
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Event", delivery.event.to_sql())
+        .header("X-Webhook-Delivery", delivery.id.to_string())
+        .header("X-Webhook-Signature", webhook.signature(body.as_bytes()))
+        .body(body)
+        .send()
+        .await;
+
+    let (status, response) = match result {
+        Ok(response) => (Some(response.status()), response.text().await.unwrap_or_default()),
+        Err(err) => (None, err.to_string()),
+    };
+
+    delivery
+        .record_attempt(status.map(|status| status.as_u16()), response, &mut *connection)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    match status {
+        Some(status) if status.is_success() => Ok(()),
+        Some(status) => Err(format!("Webhook {} responded with {}", webhook.id, status)),
+        None => Err(format!(
+            "Failed to reach webhook {}: {}",
+            webhook.id,
+            delivery.response.unwrap_or_default()
+        )),
+    }
+}
@@ -1,82 +1,48 @@
-use crate::{endpoints::misc, ratelimits::DemonlistRatelimits};
+use crate::ratelimits::DemonlistRatelimits;
 use chrono::Duration;
-use pointercrate_core::pool::PointercratePool;
+use log::{error, warn};
+use pointercrate_core::{config as core_config, pool::PointercratePool, ratelimits::RatelimitStorage};
+use pointercrate_core_api::ratelimits::RatelimitHeaders;
+use pointercrate_demonlist::webhook::Webhook;
 use pointercrate_integrate::gd::PgCache;
-use rocket::{Build, Rocket};
+use rocket::{fairing::AdHoc, Build, Rocket};
+use sqlx::{Pool, Postgres};
 
 pub(crate) mod config;
 mod endpoints;
+pub mod jobs;
+mod openapi;
 pub(crate) mod pages;
 pub(crate) mod ratelimits;
 
 pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
-    let ratelimits = DemonlistRatelimits::new();
-    let dash_rs = PgCache::new(rocket.state::<PointercratePool>().unwrap().clone_inner(), Duration::minutes(30));
+    let pool = rocket.state::<PointercratePool>().unwrap().clone_inner();
+    let dash_rs = PgCache::new(pool.clone(), Duration::minutes(30));
+    let job_queue = jobs::queue(pool.clone(), dash_rs.clone());
+
+    let rocket = pointercrate_core_api::openapi::setup(rocket, openapi::documentation());
 
     rocket
-        .manage(ratelimits)
         .manage(dash_rs)
-        .mount("/api/v1/list_information/", rocket::routes![misc::list_information])
-        .mount(
-            "/api/v1/submitters/",
-            rocket::routes![
-                endpoints::submitter::paginate,
-                endpoints::submitter::get,
-                endpoints::submitter::patch
-            ],
-        )
-        .mount(
-            "/api/v1/records/",
-            rocket::routes![
-                endpoints::record::get_notes,
-                endpoints::record::add_note,
-                endpoints::record::audit,
-                endpoints::record::delete,
-                endpoints::record::delete_note,
-                endpoints::record::get,
-                endpoints::record::paginate,
-                endpoints::record::unauthed_pagination,
-                endpoints::record::patch,
-                endpoints::record::patch_note,
-                endpoints::record::submit
-            ],
-        )
-        .mount(
-            "/api/v1/players/",
-            rocket::routes![
-                endpoints::player::get,
-                endpoints::player::paginate,
-                endpoints::player::patch,
-                endpoints::player::ranking,
-                endpoints::player::put_claim,
-                endpoints::player::patch_claim,
-                endpoints::player::paginate_claims,
-                endpoints::player::delete_claim,
-                endpoints::player::geolocate_nationality
-            ],
-        )
-        .mount(
-            "/api/v1/nationalities/",
-            rocket::routes![
-                endpoints::nationality::subdivisions,
-                endpoints::nationality::ranking,
-                endpoints::nationality::nation
-            ],
-        )
-        .mount(
-            "/api/v2/demons/",
-            rocket::routes![
-                endpoints::demon::get,
-                endpoints::demon::paginate,
-                endpoints::demon::paginate_listed,
-                endpoints::demon::audit,
-                endpoints::demon::movement_log,
-                endpoints::demon::patch,
-                endpoints::demon::post,
-                endpoints::demon::post_creator,
-                endpoints::demon::delete_creator
-            ],
-        )
+        .attach(RatelimitHeaders)
+        .attach(AdHoc::try_on_ignite("Demonlist ratelimits", |rocket| async move {
+            match RatelimitStorage::from_env(rocket.state::<PointercratePool>()) {
+                Ok(storage) => Ok(rocket.manage(DemonlistRatelimits::with_storage(storage))),
+                Err(err) => {
+                    error!("Failed to set up demonlist ratelimits: {}", err);
+
+                    Err(rocket)
+                },
+            }
+        }))
+        .attach(AdHoc::on_liftoff("Demonlist job workers", move |_| {
+            Box::pin(async move {
+                adopt_submission_webhook(&pool).await;
+                jobs::schedule_link_checks(&pool).await;
+                jobs::schedule_ranking_snapshots(&pool).await;
+                job_queue.start(core_config::job_workers())
+            })
+        }))
         .mount(
             "/demonlist/",
             rocket::routes![
@@ -84,9 +50,35 @@ pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
                 pages::stats_viewer_redirect,
                 pages::stats_viewer,
                 pages::nation_stats_viewer,
+                pages::changelog,
                 pages::demon_page,
                 pages::demon_permalink,
                 pages::heatmap_css
             ],
         )
 }
+
+/// Turns the deprecated `DISCORD_WEBHOOK` configuration into a webhook, see
+/// [`config::submission_webhook`]
+async fn adopt_submission_webhook(pool: &Pool<Postgres>) {
+    let url = match config::submission_webhook() {
+        Some(url) => url,
+        None => return,
+    };
+
+    let result = match pool.acquire().await {
+        Ok(mut connection) => Webhook::adopt_discord_webhook(url, &mut *connection)
+            .await
+            .map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+
+    match result {
+        Ok(Some(webhook)) => warn!(
+            "DISCORD_WEBHOOK is deprecated, registered it as webhook {}. Manage it via /api/v1/webhooks/ and unset the variable",
+            webhook.id
+        ),
+        Ok(None) => warn!("DISCORD_WEBHOOK is deprecated and already registered as a webhook, unset the variable"),
+        Err(err) => error!("Failed to register DISCORD_WEBHOOK as a webhook: {}", err),
+    }
+}
@@ -0,0 +1,322 @@
+use crate::endpoints::{
+    demon, list, misc, nationality, player, record, rejection_reason, review, scoring, status_transition, submitter, video, webhook,
+};
+use pointercrate_core::audit::AuditLogEntry;
+use pointercrate_core_api::openapi::{ApiDocumentation, Operation};
+use pointercrate_demonlist::{
+    creator::PostCreator,
+    demon::{
+        audit::{DemonModificationData, MovementLogEntry},
+        diff::{DiffWindow, ListDiff},
+        DemonIdPagination, DemonPositionPagination, FullDemon, PatchDemon, PostDemon,
+    },
+    error::DemonlistError,
+    link_health::BrokenLink,
+    list::{List, PatchList, PostList},
+    nationality::{Nationality, NationalityRankingPagination, NationalityRecord, Subdivision},
+    player::{
+        claim::{PatchPlayerClaim, PlayerClaim, PlayerClaimPagination},
+        merge::{MergePlayers, MergePreview, PlayerMerge},
+        name::SimilarPlayer,
+        FullPlayer, PatchPlayer, PlayerMergePagination, PlayerPagination, RankingPagination,
+    },
+    ranking_history::{PlayerRankingHistory, RankingHistoryFilter},
+    record::{
+        audit::RecordModificationData,
+        metadata::VideoMetadata,
+        note::{NewNote, Note, PatchNote},
+        rejection::{PatchRejectionReason, PostRejectionReason, RejectionReason},
+        transition::StatusTransition,
+        FullRecord, PatchRecord, PostPartner, RecordPagination, Submission,
+    },
+    review::{ReviewAssignment, ReviewStatistics},
+    scoring::{PostScoringFormula, ScoringFormula, ScoringPreviewEntry},
+    submitter::{PatchSubmitter, Submitter, SubmitterPagination},
+    webhook::{PatchWebhook, PostWebhook, Webhook, WebhookDeliveryPagination},
+};
+use schemars::JsonSchema;
+use serde_json::Value;
+
+// The following structs only describe the query parameters bound directly in route URIs
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct ListQuery {
+    /// The list to operate on. Defaults to the default list
+    list: Option<i32>,
+}
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct SimilarityQuery {
+    /// The name to find similar player names to
+    name: String,
+}
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct StatisticsQuery {
+    /// The number of days to compute reviewer statistics for. Defaults to 30
+    days: Option<i32>,
+}
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct PreviewQuery {
+    /// The number of players to preview, between 1 and 100. Defaults to 50
+    limit: Option<u8>,
+
+    /// The list whose ranking to preview. Defaults to the default list
+    list: Option<i32>,
+}
+
+pub(crate) fn documentation() -> ApiDocumentation {
+    ApiDocumentation::new()
+        .errors::<DemonlistError>()
+        .mount(
+            "/api/v1/list_information/",
+            vec![
+                Operation::new(rocket::routes![misc::list_information], "Retrieve general information about a list")
+                    .with_query::<ListQuery>()
+                    .with_response::<Value>(),
+            ],
+        )
+        .mount(
+            "/api/v1/submitters/",
+            vec![
+                Operation::new(rocket::routes![submitter::paginate], "List submitters").paginated::<SubmitterPagination>(),
+                Operation::new(rocket::routes![submitter::get], "Retrieve a submitter")
+                    .with_response::<Submitter>()
+                    .tagged(),
+                Operation::new(rocket::routes![submitter::patch], "Modify a submitter")
+                    .with_body::<PatchSubmitter>()
+                    .with_response::<Submitter>()
+                    .conditional()
+                    .tagged(),
+            ],
+        )
+        .mount(
+            "/api/v1/records/",
+            vec![
+                Operation::new(rocket::routes![record::paginate, record::unauthed_pagination], "List records")
+                    .paginated::<RecordPagination>(),
+                Operation::new(rocket::routes![record::submit], "Submit a record")
+                    .with_body::<Submission>()
+                    .with_response::<FullRecord>()
+                    .tagged(),
+                Operation::new(rocket::routes![record::get], "Retrieve a record")
+                    .with_response::<FullRecord>()
+                    .tagged(),
+                Operation::new(rocket::routes![record::patch], "Modify a record")
+                    .with_body::<PatchRecord>()
+                    .with_response::<FullRecord>()
+                    .conditional()
+                    .tagged(),
+                Operation::new(rocket::routes![record::delete], "Delete a record").conditional(),
+                Operation::new(rocket::routes![record::audit], "Retrieve the audit log of a record")
+                    .with_response::<Vec<AuditLogEntry<RecordModificationData>>>(),
+                Operation::new(rocket::routes![record::post_partner], "Credit another player with a record")
+                    .with_body::<PostPartner>()
+                    .with_response::<FullRecord>()
+                    .tagged(),
+                Operation::new(rocket::routes![record::delete_partner], "Remove a partner from a record"),
+                Operation::new(
+                    rocket::routes![record::get_video_metadata],
+                    "Retrieve the metadata of a record's video",
+                )
+                .with_response::<VideoMetadata>(),
+                Operation::new(rocket::routes![record::get_notes], "List the notes on a record").with_response::<Vec<Note>>(),
+                Operation::new(rocket::routes![record::add_note], "Add a note to a record")
+                    .with_body::<NewNote>()
+                    .with_response::<Note>()
+                    .tagged(),
+                Operation::new(rocket::routes![record::patch_note], "Modify a note")
+                    .with_body::<PatchNote>()
+                    .with_response::<Note>()
+                    .tagged(),
+                Operation::new(rocket::routes![record::delete_note], "Delete a note"),
+            ],
+        )
+        .mount(
+            "/api/v1/players/",
+            vec![
+                Operation::new(rocket::routes![player::paginate], "List players").paginated::<PlayerPagination>(),
+                Operation::new(rocket::routes![player::ranking], "List the player ranking").paginated::<RankingPagination>(),
+                Operation::new(rocket::routes![player::similar], "Search for players with similar names")
+                    .with_query::<SimilarityQuery>()
+                    .with_response::<Vec<SimilarPlayer>>(),
+                Operation::new(rocket::routes![player::paginate_claims], "List player claims").paginated::<PlayerClaimPagination>(),
+                Operation::new(rocket::routes![player::get], "Retrieve a player")
+                    .with_response::<FullPlayer>()
+                    .tagged(),
+                Operation::new(rocket::routes![player::patch], "Modify a player")
+                    .with_body::<PatchPlayer>()
+                    .with_response::<FullPlayer>()
+                    .conditional()
+                    .tagged(),
+                Operation::new(rocket::routes![player::history], "Retrieve the ranking history of a player")
+                    .with_query::<RankingHistoryFilter>()
+                    .with_response::<PlayerRankingHistory>(),
+                Operation::new(rocket::routes![player::put_claim], "Claim a player").with_response::<PlayerClaim>(),
+                Operation::new(rocket::routes![player::patch_claim], "Modify a claim")
+                    .with_body::<PatchPlayerClaim>()
+                    .with_response::<PlayerClaim>(),
+                Operation::new(rocket::routes![player::delete_claim], "Delete a claim"),
+                Operation::new(
+                    rocket::routes![player::geolocate_nationality],
+                    "Set a claimed player's nationality via IP geolocation",
+                )
+                .with_response::<Nationality>(),
+                Operation::new(rocket::routes![player::merge], "Merge another player into a player")
+                    .with_body::<MergePlayers>()
+                    .with_response::<MergePreview>(),
+                Operation::new(rocket::routes![player::merges], "List player merges").paginated::<PlayerMergePagination>(),
+                Operation::new(rocket::routes![player::get_merge], "Retrieve a player merge").with_response::<PlayerMerge>(),
+                Operation::new(rocket::routes![player::revert_merge], "Revert a player merge").with_response::<PlayerMerge>(),
+            ],
+        )
+        .mount(
+            "/api/v1/nationalities/",
+            vec![
+                Operation::new(rocket::routes![nationality::ranking], "List the nation ranking")
+                    .paginated::<NationalityRankingPagination>(),
+                Operation::new(rocket::routes![nationality::nation], "Retrieve a nation")
+                    .with_response::<NationalityRecord>()
+                    .tagged(),
+                Operation::new(rocket::routes![nationality::subdivisions], "List the subdivisions of a nation")
+                    .with_response::<Vec<Subdivision>>(),
+            ],
+        )
+        .mount(
+            "/api/v1/webhooks/",
+            vec![
+                Operation::new(rocket::routes![webhook::list], "List webhooks").with_response::<Vec<Webhook>>(),
+                Operation::new(rocket::routes![webhook::post], "Register a webhook")
+                    .with_body::<PostWebhook>()
+                    .with_response::<Webhook>(),
+                Operation::new(rocket::routes![webhook::get], "Retrieve a webhook").with_response::<Webhook>(),
+                Operation::new(rocket::routes![webhook::patch], "Modify a webhook")
+                    .with_body::<PatchWebhook>()
+                    .with_response::<Webhook>(),
+                Operation::new(rocket::routes![webhook::delete], "Delete a webhook"),
+                Operation::new(rocket::routes![webhook::deliveries], "List the delivery log of a webhook")
+                    .paginated::<WebhookDeliveryPagination>(),
+            ],
+        )
+        .mount(
+            "/api/v2/demons/",
+            vec![
+                Operation::new(rocket::routes![demon::paginate], "List demons").paginated::<DemonIdPagination>(),
+                Operation::new(rocket::routes![demon::paginate_listed], "List demons ordered by position")
+                    .paginated::<DemonPositionPagination>(),
+                Operation::new(rocket::routes![demon::diff], "Compare the list at two points in time")
+                    .with_query::<DiffWindow>()
+                    .with_response::<ListDiff>(),
+                Operation::new(rocket::routes![demon::post], "Add a demon")
+                    .with_body::<PostDemon>()
+                    .with_response::<FullDemon>()
+                    .tagged(),
+                Operation::new(rocket::routes![demon::get], "Retrieve a demon")
+                    .with_response::<FullDemon>()
+                    .tagged(),
+                Operation::new(rocket::routes![demon::patch], "Modify a demon")
+                    .with_body::<PatchDemon>()
+                    .with_response::<FullDemon>()
+                    .conditional()
+                    .tagged(),
+                Operation::new(rocket::routes![demon::audit], "Retrieve the audit log of a demon")
+                    .with_response::<Vec<AuditLogEntry<DemonModificationData>>>(),
+                Operation::new(rocket::routes![demon::movement_log], "Retrieve the movement log of a demon")
+                    .with_response::<Vec<MovementLogEntry>>(),
+                Operation::new(rocket::routes![demon::post_creator], "Add a creator to a demon").with_body::<PostCreator>(),
+                Operation::new(rocket::routes![demon::delete_creator], "Remove a creator from a demon"),
+            ],
+        )
+        .mount(
+            "/api/v2/lists/",
+            vec![
+                Operation::new(rocket::routes![list::list], "List all lists").with_response::<Vec<List>>(),
+                Operation::new(rocket::routes![list::post], "Add a list")
+                    .with_body::<PostList>()
+                    .with_response::<List>(),
+                Operation::new(rocket::routes![list::get], "Retrieve a list").with_response::<List>(),
+                Operation::new(rocket::routes![list::patch], "Modify a list")
+                    .with_body::<PatchList>()
+                    .with_response::<List>(),
+            ],
+        )
+        .mount(
+            "/api/v2/rejection-reasons/",
+            vec![
+                Operation::new(rocket::routes![rejection_reason::list], "List all rejection reasons")
+                    .with_response::<Vec<RejectionReason>>(),
+                Operation::new(rocket::routes![rejection_reason::post], "Add a rejection reason")
+                    .with_body::<PostRejectionReason>()
+                    .with_response::<RejectionReason>(),
+                Operation::new(rocket::routes![rejection_reason::get], "Retrieve a rejection reason").with_response::<RejectionReason>(),
+                Operation::new(rocket::routes![rejection_reason::patch], "Modify a rejection reason")
+                    .with_body::<PatchRejectionReason>()
+                    .with_response::<RejectionReason>(),
+            ],
+        )
+        .mount(
+            "/api/v2/review-queue/",
+            vec![
+                Operation::new(rocket::routes![review::list], "List the submissions currently under review")
+                    .with_response::<Vec<ReviewAssignment>>(),
+                Operation::new(rocket::routes![review::next], "Get the next submission to review")
+                    .with_query::<ListQuery>()
+                    .with_response::<ReviewAssignment>(),
+                Operation::new(rocket::routes![review::renew], "Extend the review of a submission").with_response::<ReviewAssignment>(),
+                Operation::new(rocket::routes![review::release], "Put a submission back into the review queue"),
+                Operation::new(rocket::routes![review::statistics], "Retrieve review queue statistics")
+                    .with_query::<StatisticsQuery>()
+                    .with_response::<ReviewStatistics>(),
+            ],
+        )
+        .mount(
+            "/api/v2/videos/",
+            vec![
+                Operation::new(rocket::routes![video::broken], "List the videos found to be deleted or private")
+                    .with_response::<Vec<BrokenLink>>(),
+            ],
+        )
+        .mount(
+            "/api/v2/status-transitions/",
+            vec![
+                Operation::new(
+                    rocket::routes![status_transition::list],
+                    "List the allowed record status transitions",
+                )
+                .with_response::<Vec<StatusTransition>>(),
+                Operation::new(
+                    rocket::routes![status_transition::put],
+                    "Replace the allowed record status transitions",
+                )
+                .with_body::<Vec<StatusTransition>>()
+                .with_response::<Vec<StatusTransition>>(),
+            ],
+        )
+        .mount(
+            "/api/v2/scoring/",
+            vec![
+                Operation::new(rocket::routes![scoring::list], "List scoring formulas").with_response::<Vec<ScoringFormula>>(),
+                Operation::new(rocket::routes![scoring::active], "Retrieve the scoring formula used by a list")
+                    .with_query::<ListQuery>()
+                    .with_response::<ScoringFormula>(),
+                Operation::new(rocket::routes![scoring::get], "Retrieve a scoring formula").with_response::<ScoringFormula>(),
+                Operation::new(rocket::routes![scoring::post], "Add a scoring formula")
+                    .with_body::<PostScoringFormula>()
+                    .with_response::<ScoringFormula>(),
+                Operation::new(rocket::routes![scoring::activate], "Make a list use a scoring formula")
+                    .with_query::<ListQuery>()
+                    .with_response::<ScoringFormula>(),
+                Operation::new(
+                    rocket::routes![scoring::preview],
+                    "Preview the player ranking under a scoring formula",
+                )
+                .with_query::<PreviewQuery>()
+                .with_response::<Vec<ScoringPreviewEntry>>(),
+            ],
+        )
+}
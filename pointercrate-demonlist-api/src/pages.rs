@@ -1,6 +1,6 @@
 use rocket::{response::Redirect, State};
 
-use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, TimeZone, Utc};
 use pointercrate_core::{audit::AuditLogEntryType, pool::PointercratePool};
 use pointercrate_core_api::{
     error::Result,
@@ -8,12 +8,15 @@ use pointercrate_core_api::{
 };
 use pointercrate_core_pages::head::HeadLike;
 use pointercrate_demonlist::{
-    demon::{audit::audit_log_for_demon, current_list, list_at, FullDemon, MinimalDemon},
+    demon::{audit::audit_log_for_demon, current_list, diff::ListDiff, list_at, FullDemon, MinimalDemon},
     error::DemonlistError,
+    list::List,
     nationality::Nationality,
+    scoring::ScoringFormula,
     LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
 };
 use pointercrate_demonlist_pages::{
+    changelog::ChangelogPage,
     components::{team::Team, time_machine::Tardis},
     demon_page::{DemonMovement, DemonPage},
     overview::OverviewPage,
@@ -23,15 +26,25 @@ use pointercrate_integrate::gd::{GDIntegrationResult, PgCache};
 use pointercrate_user::User;
 use pointercrate_user_api::auth::TokenAuth;
 use rocket::{futures::StreamExt, http::CookieJar};
+use sqlx::PgConnection;
 
 #[rocket::get("/?statsviewer=true")]
 pub fn stats_viewer_redirect() -> Redirect {
-    Redirect::to(rocket::uri!(stats_viewer))
+    Redirect::to(rocket::uri!(stats_viewer(_)))
 }
 
-#[rocket::get("/?<timemachine>&<submitter>")]
+/// Gets the list with the given slug, or the default list if no slug is given
+async fn list_by_slug_or_default(slug: Option<String>, connection: &mut PgConnection) -> Result<List> {
+    Ok(match slug {
+        Some(slug) => List::by_slug(&slug, connection).await?,
+        None => List::default(connection).await?,
+    })
+}
+
+#[rocket::get("/?<timemachine>&<submitter>&<list>")]
 pub async fn overview(
-    pool: &State<PointercratePool>, timemachine: Option<bool>, submitter: Option<bool>, cookies: &CookieJar<'_>, auth: Option<TokenAuth>,
+    pool: &State<PointercratePool>, timemachine: Option<bool>, submitter: Option<bool>, list: Option<String>, cookies: &CookieJar<'_>,
+    auth: Option<TokenAuth>,
 ) -> Result<Page> {
     // should be const, but chrono aint const :(
     let beginning_of_time: DateTime<FixedOffset> = FixedOffset::east_opt(0)
@@ -40,7 +53,8 @@ pub async fn overview(
 
     let mut connection = pool.connection().await?;
 
-    let demonlist = current_list(&mut *connection).await?;
+    let list = list_by_slug_or_default(list, &mut *connection).await?;
+    let demonlist = current_list(&list, &mut *connection).await?;
 
     let specified_when = cookies
         .get("when")
@@ -54,7 +68,9 @@ pub async fn overview(
     };
 
     let tardis = match specified_when {
-        Some(destination) => Tardis::new(timemachine.unwrap_or(false)).activate(destination, list_at(&mut *connection, destination).await?),
+        Some(destination) => {
+            Tardis::new(timemachine.unwrap_or(false)).activate(destination, list_at(&list, &mut *connection, destination).await?)
+        },
         _ => Tardis::new(timemachine.unwrap_or(false)),
     };
 
@@ -64,6 +80,8 @@ pub async fn overview(
             moderators: User::by_permission(LIST_MODERATOR, &mut *connection).await?,
             helpers: User::by_permission(LIST_HELPER, &mut *connection).await?,
         },
+        lists: List::all(&mut *connection).await?,
+        list,
         demonlist,
         time_machine: tardis,
         submitter_initially_visible: submitter.unwrap_or(false),
@@ -81,15 +99,23 @@ pub async fn demon_permalink(demon_id: i32, pool: &State<PointercratePool>) -> R
     let mut connection = pool.connection().await?;
 
     let position = MinimalDemon::by_id(demon_id, &mut *connection).await?.position;
+    let list = List::of_demon(demon_id, &mut *connection).await?;
 
-    Ok(Redirect::to(rocket::uri!("/demonlist", demon_page(position))))
+    if list.is_default {
+        Ok(Redirect::to(rocket::uri!("/demonlist", demon_page(position, _))))
+    } else {
+        Ok(Redirect::to(rocket::uri!("/demonlist", demon_page(position, Some(list.slug)))))
+    }
 }
 
-#[rocket::get("/<position>")]
-pub async fn demon_page(position: i16, pool: &State<PointercratePool>, gd: &State<PgCache>, auth: Option<TokenAuth>) -> Result<Page> {
+#[rocket::get("/<position>?<list>")]
+pub async fn demon_page(
+    position: i16, list: Option<String>, pool: &State<PointercratePool>, gd: &State<PgCache>, auth: Option<TokenAuth>,
+) -> Result<Page> {
     let mut connection = pool.connection().await?;
 
-    let full_demon = FullDemon::by_position(position, &mut *connection).await?;
+    let list = list_by_slug_or_default(list, &mut *connection).await?;
+    let full_demon = FullDemon::by_position(&list, position, &mut *connection).await?;
 
     let audit_log = audit_log_for_demon(full_demon.demon.base.id, &mut *connection).await?;
 
@@ -133,11 +159,11 @@ pub async fn demon_page(position: i16, pool: &State<PointercratePool>, gd: &Stat
             moderators: User::by_permission(LIST_MODERATOR, &mut *connection).await?,
             helpers: User::by_permission(LIST_HELPER, &mut *connection).await?,
         },
-        demonlist: current_list(&mut *connection).await?,
+        demonlist: current_list(&list, &mut *connection).await?,
+        lists: List::all(&mut *connection).await?,
         movements: modifications,
         integration: gd
             .data_for_demon(
-                reqwest::Client::new(),
                 full_demon.demon.level_id,
                 full_demon.demon.base.name.clone(),
                 full_demon.demon.base.id,
@@ -145,6 +171,8 @@ pub async fn demon_page(position: i16, pool: &State<PointercratePool>, gd: &Stat
             .await
             .unwrap_or(GDIntegrationResult::LevelDataNotFound),
         data: full_demon,
+        scoring: ScoringFormula::of_list(&list, &mut *connection).await?,
+        list,
     });
 
     if let Some(token_auth) = auth {
@@ -154,18 +182,50 @@ pub async fn demon_page(position: i16, pool: &State<PointercratePool>, gd: &Stat
     Ok(page)
 }
 
-#[rocket::get("/statsviewer")]
-pub async fn stats_viewer(pool: &State<PointercratePool>) -> Result<Page> {
+/// Parses a day given as `YYYY-MM-DD`, as submitted by `<input type="date">`
+fn parse_day(day: Option<String>) -> Option<NaiveDate> {
+    day.and_then(|day| NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok())
+}
+
+/// The start of the given day in UTC
+fn start_of_day(day: NaiveDate) -> DateTime<FixedOffset> {
+    FixedOffset::east_opt(0)
+        .unwrap()
+        .from_utc_datetime(&day.and_hms_opt(0, 0, 0).unwrap())
+}
+
+#[rocket::get("/changelog?<from>&<to>&<list>")]
+pub async fn changelog(from: Option<String>, to: Option<String>, list: Option<String>, pool: &State<PointercratePool>) -> Result<Page> {
+    let mut connection = pool.connection().await?;
+
+    let list = list_by_slug_or_default(list, &mut *connection).await?;
+
+    // Both days are inclusive, and default to the last week
+    let to = parse_day(to).unwrap_or_else(|| Utc::now().date_naive());
+    let from = parse_day(from).unwrap_or(to - Duration::days(7));
+
+    let diff = ListDiff::between(&list, start_of_day(from), start_of_day(to + Duration::days(1)), &mut *connection).await?;
+
+    Ok(Page::new(ChangelogPage { list, from, to, diff }))
+}
+
+#[rocket::get("/statsviewer?<list>")]
+pub async fn stats_viewer(list: Option<String>, pool: &State<PointercratePool>) -> Result<Page> {
     let mut connection = pool.connection().await?;
 
     Ok(Page::new(IndividualStatsViewer {
+        list: list_by_slug_or_default(list, &mut *connection).await?,
         nationalities_in_use: Nationality::used(&mut *connection).await?,
     }))
 }
 
-#[rocket::get("/statsviewer/nations")]
-pub async fn nation_stats_viewer() -> Page {
-    Page::new(pointercrate_demonlist_pages::statsviewer::national::nation_based_stats_viewer())
+#[rocket::get("/statsviewer/nations?<list>")]
+pub async fn nation_stats_viewer(list: Option<String>, pool: &State<PointercratePool>) -> Result<Page> {
+    let list = list_by_slug_or_default(list, &mut *pool.connection().await?).await?;
+
+    Ok(Page::new(
+        pointercrate_demonlist_pages::statsviewer::national::nation_based_stats_viewer(&list),
+    ))
 }
 
 macro_rules! heatmap_query {
@@ -196,15 +256,21 @@ macro_rules! heatmap_query {
 #[rocket::get("/statsviewer/heatmap.css")]
 pub async fn heatmap_css(pool: &State<PointercratePool>) -> Result<Response2<String>> {
     let mut connection = pool.connection().await?;
+
+    // The heatmap is shared by the stats viewers of all lists, so it shows the default one
+    let list = List::default(&mut *connection).await?;
+
     let mut css = heatmap_query!(
         connection,
-        r#"SELECT LOWER(iso_country_code) as "code!", score as "score!" from nations_with_score order by score desc"#,
+        r#"SELECT LOWER(iso_country_code) as "code!", score as "score!" from nations_with_score where list = $1 order by score desc"#,
+        list.id
     );
 
     for nation in ["AU", "CA", "US", "GB"] {
         css.push_str(&heatmap_query!(
             connection,
-            r#"SELECT CONCAT($1, '-', UPPER(subdivision_code)) AS "code!", score AS "score!" FROM subdivision_ranking_of($1) ORDER BY score DESC"#,
+            r#"SELECT CONCAT($2, '-', UPPER(subdivision_code)) AS "code!", score AS "score!" FROM subdivision_ranking_of($1, $2) ORDER BY score DESC"#,
+            list.id,
             nation
         ));
     }
@@ -2,15 +2,17 @@ use pointercrate_core::ratelimits;
 
 ratelimits! {
     DemonlistRatelimits {
-        record_submission[3u32 per 1200 per ip] => "You're submitting too many records too fast!",
+        record_submission[3u32 per 1200 per user] => "You're submitting too many records too fast!",
 
         record_submission_global[20u32 per 3600] => "Too many records are being submitted right now!",
 
         new_submitters[7u32 per 3600] => "DDoS protection ratelimit",
 
-        geolocate[1u32 per 2_678_400 per ip] => "You can only geolocate once per month!",
+        geolocate[1u32 per 2_678_400 per user] => "You can only geolocate once per month!",
 
         add_demon[1u32 per 60] => "Please don't spam the button, rSteel",
+
+        export[10u32 per 3600 per ip] => "You're exporting too much data too fast!",
     }
 }
 
@@ -18,15 +20,16 @@ ratelimits! {
 mod test {
     use crate::ratelimits::DemonlistRatelimits;
     use pointercrate_core::error::CoreError;
+    use std::net::IpAddr;
 
-    #[test]
-    fn test_non_burst_ratelimit() {
+    #[rocket::async_test]
+    async fn test_non_burst_ratelimit() {
         let ratelimits = DemonlistRatelimits::new();
-        let pass = ratelimits.add_demon();
+        let pass = ratelimits.add_demon().await;
 
         assert!(pass.is_ok());
 
-        let fail = ratelimits.add_demon();
+        let fail = ratelimits.add_demon().await;
 
         assert!(fail.is_err());
 
@@ -40,26 +43,50 @@ mod test {
         }
     }
 
-    #[test]
-    fn test_burst_ratelimits() {
+    #[rocket::async_test]
+    async fn test_burst_ratelimits() {
         let ratelimits = DemonlistRatelimits::new();
 
-        for _ in 1..=7 {
-            assert!(ratelimits.new_submitters().is_ok());
+        for remaining in (0..7).rev() {
+            let info = ratelimits.new_submitters().await.unwrap();
+
+            assert_eq!(info.limit, 7);
+            assert_eq!(info.remaining, remaining);
         }
 
-        let fail = ratelimits.new_submitters();
+        let fail = ratelimits.new_submitters().await;
 
         assert!(fail.is_err());
 
         match fail.unwrap_err() {
-            CoreError::Ratelimited { remaining, .. } => {
+            CoreError::Ratelimited {
+                remaining, limit, reset, ..
+            } => {
                 assert!(remaining.as_secs() <= 3600);
                 assert!(remaining.as_secs() >= 500); // 7 tokens per hour -> refresh one every 3600
                                                      // / 7 ~ 514 seconds. Execution of test
                                                      // shouldn't take longer than 14s
+                assert_eq!(limit, 7);
+                // all 7 tokens need to be refilled for the ratelimit to reset
+                assert!(reset.as_secs() >= 3500 && reset.as_secs() <= 3600);
             },
             err => panic!("Got unexpected error {:?}, expected CoreError::RateLimited", err),
         }
     }
+
+    #[rocket::async_test]
+    async fn test_per_user_ratelimits() {
+        let ratelimits = DemonlistRatelimits::new();
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        for _ in 1..=3 {
+            assert!(ratelimits.record_submission(Some(1), ip).await.is_ok());
+        }
+
+        assert!(ratelimits.record_submission(Some(1), ip).await.is_err());
+
+        // Neither a different user nor an anonymous request from the same IP is affected
+        assert!(ratelimits.record_submission(Some(2), ip).await.is_ok());
+        assert!(ratelimits.record_submission(None, ip).await.is_ok());
+    }
 }
@@ -13,7 +13,7 @@ pub struct ListIntegrationTab(#[doc = "discord invite url"] pub &'static str);
 
 #[async_trait::async_trait]
 impl AccountPageTab for ListIntegrationTab {
-    fn should_display_for(&self, _permissions_we_have: u16, _permissions: &PermissionsManager) -> bool {
+    fn should_display_for(&self, _permissions_we_have: &[String], _permissions: &PermissionsManager) -> bool {
         true
     }
 
@@ -49,7 +49,7 @@ impl AccountPageTab for ListIntegrationTab {
                 .body();
             },
         };
-        let is_moderator = permissions.require_permission(user.inner().permissions, MODERATOR).is_ok();
+        let is_moderator = permissions.require_permission(&user.inner().roles, MODERATOR).is_ok();
 
         html! {
             div.left {
@@ -130,11 +130,12 @@ impl AccountPageTab for ListIntegrationTab {
                                 "Your claimed player's records"
                             }
                             p {
-                                "A list of your claimed player's records, including all under consideration and rejected records and all submissions. Use this to track the status of your submissions. Clicking on a record will pull up any public notes a list mod left on the given record. The background color of each record tells you whether the record is "
+                                "A list of your claimed player's records, including all under consideration and rejected records and all submissions. Use this to track the status of your submissions. Clicking on a record will pull up any public notes a list mod left on the given record. Rejected records show the reason they were rejected for. The background color of each record tells you whether the record is "
                                 span  style = "background-color: #E9FAE3" { "Approved"  } ", "
                                 span style = "background-color: #F7F7E0" { "Unchecked" } ", "
-                                span style = "background-color: #F8DCE4" { "Rejected" } " or "
-                                span style = "background-color: #D8EFF3" { "Under Consideration" } "."
+                                span style = "background-color: #F8DCE4" { "Rejected" } ", "
+                                span style = "background-color: #D8EFF3" { "Under Consideration" } " or "
+                                span style = "background-color: #F9E6D2" { "Needs Info" } ". If a record needs info, please check its notes to see what information the list mods need from you."
                             }
                             (paginator("claims-record-pagination", "/api/v1/records/"))
                         }
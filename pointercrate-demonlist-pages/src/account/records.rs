@@ -10,6 +10,9 @@ use pointercrate_core_pages::{
 };
 use pointercrate_demonlist::{
     demon::{current_list, Demon},
+    error::Result,
+    list::List,
+    record::rejection::RejectionReason,
     LIST_HELPER,
 };
 use pointercrate_user::{sqlx::PgConnection, AuthenticatedUser};
@@ -19,7 +22,7 @@ pub struct RecordsPage;
 
 #[async_trait::async_trait]
 impl AccountPageTab for RecordsPage {
-    fn should_display_for(&self, permissions_we_have: u16, permissions: &PermissionsManager) -> bool {
+    fn should_display_for(&self, permissions_we_have: &[String], permissions: &PermissionsManager) -> bool {
         permissions.require_permission(permissions_we_have, LIST_HELPER).is_ok()
     }
 
@@ -42,8 +45,8 @@ impl AccountPageTab for RecordsPage {
     }
 
     async fn content(&self, _user: &AuthenticatedUser, _permissions: &PermissionsManager, connection: &mut PgConnection) -> Markup {
-        let demons = match current_list(connection).await {
-            Ok(demons) => demons,
+        let (lists, demons, reasons) = match manager_data(connection).await {
+            Ok(data) => data,
             Err(err) => {
                 return ErrorFragment {
                     status: err.status_code(),
@@ -56,8 +59,8 @@ impl AccountPageTab for RecordsPage {
 
         html! {
             div.left {
-                (RecordSubmitter::new(false, &demons[..]))
-                (record_manager(&demons[..]))
+                (RecordSubmitter::new(false, &lists[..], &demons[..]))
+                (record_manager(&demons[..], &reasons[..]))
                 (note_adder())
                 div.panel.fade#record-notes-container style = "display:none" {
                     div.white.hover.clickable#add-record-note-open {
@@ -68,6 +71,7 @@ impl AccountPageTab for RecordsPage {
                 (manager_help())
             }
             div.right {
+                (review_queue())
                 (status_selector())
                 (record_selector())
                 (player_selector())
@@ -81,7 +85,21 @@ impl AccountPageTab for RecordsPage {
     }
 }
 
-fn record_manager(demons: &[Demon]) -> Markup {
+/// Gets all lists, together with the demons on all of them, and all rejection reasons
+async fn manager_data(connection: &mut PgConnection) -> Result<(Vec<List>, Vec<Demon>, Vec<RejectionReason>)> {
+    let lists = List::all(&mut *connection).await?;
+    let mut demons = Vec::new();
+
+    for list in &lists {
+        demons.extend(current_list(list, &mut *connection).await?);
+    }
+
+    let reasons = RejectionReason::all(&mut *connection).await?;
+
+    Ok((lists, demons, reasons))
+}
+
+fn record_manager(demons: &[Demon], reasons: &[RejectionReason]) -> Markup {
     html! {
         div.panel.fade#record-manager {
             h2.underlined.pad {
@@ -112,6 +130,7 @@ fn record_manager(demons: &[Demon]) -> Markup {
                                         li.white.hover data-value="approved" {"Approved"}
                                         li.white.hover data-value="rejected" {"Rejected"}
                                         li.white.hover data-value="under consideration" {"Under Consideration"}
+                                        li.white.hover data-value="needs info" {"Needs Info"}
                                         li.white.hover data-value="submitted" {"Submitted"}
                                     }
                                 }
@@ -129,6 +148,13 @@ fn record_manager(demons: &[Demon]) -> Markup {
                                 br;
                                 a.link#record-video-link target = "_blank" {}
                             }
+                            span {
+                                b {
+                                    "Video Flags:"
+                                }
+                                br;
+                                span#record-video-flags {}
+                            }
                         }
                         div.stats-container.flex.space {
                             span {
@@ -162,6 +188,27 @@ fn record_manager(demons: &[Demon]) -> Markup {
                                 span#record-submitter {}
                             }
                         }
+                        div.stats-container.flex.space {
+                            span {
+                                b {
+                                    "Rejection Reason:"
+                                }
+                                br;
+                                div.dropdown-menu.js-search#edit-record-rejection-reason style = "max-width: 220px" {
+                                    div{
+                                        input type="text" style = "color: #444446; font-weight: bold;";
+                                    }
+                                    div.menu {
+                                        ul {
+                                            li.white.hover data-value="none" {"None"}
+                                            @for reason in reasons {
+                                                li.white.hover data-value=(reason.id) title=(reason.description) {(reason.name)}
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
                         span.button.red.hover#record-delete style = "margin: 15px auto 0px" {"Delete Record"};
                     }
                 }
@@ -181,7 +228,7 @@ fn manager_help() -> Markup {
                 "Use the list on the left to select records for editing/viewing. Use the panel on the right to filter the record list by status, player, etc.. Clicking the 'All Demons' field at the top allows to filter by demon."
             }
             p {
-                "There are four possible record states a record can be in: " i { "'rejected', 'approved', 'submitted'" } " and " i { "'under consideration'" } ". For simplicity of explanation we will assume that 'Bob' is a player and 'Cataclysm' is a demon he has a record on."
+                "There are five possible record states a record can be in: " i { "'rejected', 'approved', 'submitted', 'needs info'" } " and " i { "'under consideration'" } ". For simplicity of explanation we will assume that 'Bob' is a player and 'Cataclysm' is a demon he has a record on."
                 ul {
                     li {
                         b{"Rejected: "} "If the record is 'rejected', it means that Bob has no other record in other states on Cataclysm and no submissions for Bob on Cataclysm are possible. Conversely, this means if Bob has a record on Catalysm that's not rejected, we immediately know that no rejected record for Bob on Cataclysm exists. "
@@ -199,8 +246,15 @@ fn manager_help() -> Markup {
                     li {
                         b {"Under Consideration: "} "If the record is 'under consideration' it is conceptually still a submission. The only difference is, that no more submissions for Bob on Cataclysm are allowed now."
                     }
+                    li {
+                        b {"Needs Info: "} "If the record 'needs info', it is conceptually still a submission, but the submitter has been asked to provide additional information (such as raw footage). Please leave a public note explaining what is needed, since Bob can see both the status and public notes of his records once he has claimed his player."
+                    }
                 }
             }
+            p {
+                b { "Note: " }
+                "Not every status change is possible. For instance, approved records cannot be moved back to 'needs info'. The allowed changes are configured by list administrators. When rejecting a record, please also select the reason for the rejection, as it is shown to the player."
+            }
             p {
                 b { "Note: " }
                 "If a player is banned, they cannot have accepted/submitted records on the list. All records marked as 'submitted' are deleted, all others are changed to 'rejected'"
@@ -228,6 +282,9 @@ fn status_selector() -> Markup {
         html! {
             li.white.hover data-value = "under consideration" {"Under Consideration"}
         },
+        html! {
+            li.white.hover data-value = "needs info" {"Needs Info"}
+        },
     ];
 
     html! {
@@ -241,6 +298,19 @@ fn status_selector() -> Markup {
             (dropdown("All", html! {
                 li.white.hover.underlined data-value = "All" {"All"}
             }, dropdown_items.into_iter()))
+            p {
+                "Filter by video problems. Broken videos were found to be deleted or private during the periodic check of all approved records' videos, flagged videos raised suspicion when the record was submitted"
+            }
+            (dropdown("All", html! {
+                li.white.hover.underlined data-value = "All" {"All"}
+            }, vec![
+                html! {
+                    li.white.hover data-value = "broken_video" {"Broken"}
+                },
+                html! {
+                    li.white.hover data-value = "video_flagged" {"Flagged"}
+                },
+            ].into_iter()))
         }
     }
 }
@@ -276,6 +346,23 @@ fn player_selector() -> Markup {
     }
 }
 
+fn review_queue() -> Markup {
+    html! {
+        div.panel.fade#review-queue {
+            h2.underlined.pad {
+                "Review Queue"
+            }
+            p {
+                "Clicking the button below selects the oldest submission nobody else is currently reviewing, and assigns it to you for 30 minutes. While it is assigned to you, other helpers cannot modify it. If you already have a submission assigned, that one is selected instead."
+            }
+            p.info-red.output {}
+            div.button.blue.hover#review-queue-next style = "margin: 15px auto 0px" {
+                "Next submission"
+            }
+        }
+    }
+}
+
 fn record_selector() -> Markup {
     html! {
         div.panel.fade {
@@ -9,7 +9,7 @@ pub struct SubmittersPage;
 
 #[async_trait::async_trait]
 impl AccountPageTab for SubmittersPage {
-    fn should_display_for(&self, permissions_we_have: u16, permissions: &PermissionsManager) -> bool {
+    fn should_display_for(&self, permissions_we_have: &[String], permissions: &PermissionsManager) -> bool {
         permissions.require_permission(permissions_we_have, LIST_MODERATOR).is_ok()
     }
 
@@ -0,0 +1,209 @@
+use crate::{
+    demon_page::{format_completion_time, host},
+    list_query,
+};
+use chrono::NaiveDate;
+use maud::{html, Markup, PreEscaped};
+use pointercrate_core_pages::{head::HeadLike, PageFragment};
+use pointercrate_demonlist::{
+    demon::diff::{ListDiff, PositionChange},
+    list::List,
+};
+
+/// Page listing all changes made to a list between two days, for writing "list changes" posts
+pub struct ChangelogPage {
+    pub list: List,
+
+    /// The first day whose changes are included
+    pub from: NaiveDate,
+
+    /// The last day whose changes are included
+    pub to: NaiveDate,
+    pub diff: ListDiff,
+}
+
+impl From<ChangelogPage> for PageFragment {
+    fn from(page: ChangelogPage) -> Self {
+        PageFragment::new(
+            format!("{} Changelog", page.list.name),
+            format!("All changes made to the {} between {} and {}", page.list.name, page.from, page.to),
+        )
+        .stylesheet("/static/demonlist/css/demonlist.css")
+        .stylesheet("/static/core/css/sidebar.css")
+        .body(page.body())
+    }
+}
+
+impl ChangelogPage {
+    fn body(&self) -> Markup {
+        html! {
+            div.flex.m-center.container {
+                main.left {
+                    (self.window_panel())
+                    (movement_panel("Added", "No demons were added", &self.diff.added))
+                    (movement_panel("Moved", "No demons were moved", &self.diff.moved))
+                    (movement_panel("Removed", "No demons were moved to the legacy list", &self.diff.removed))
+                    (self.requirement_panel())
+                    (self.records_panel())
+                }
+                aside.right {
+                    (super::rules_panel())
+                }
+            }
+        }
+    }
+
+    fn window_panel(&self) -> Markup {
+        html! {
+            section.panel.fade {
+                div.underlined {
+                    h1 {
+                        (self.list.name) " Changelog"
+                    }
+                }
+                p {
+                    "All changes made to the " (self.list.name) " from " b { (self.from.format("%B %e, %Y")) } " up to and including " b { (self.to.format("%B %e, %Y")) } " (UTC). "
+                    "Demons that were only shifted by other demons being added, moved or removed are not listed."
+                }
+                form#changelog-form.flex method = "get" action = "/demonlist/changelog/" {
+                    @if !self.list.is_default {
+                        input type = "hidden" name = "list" value = (self.list.slug);
+                    }
+                    span.form-input.flex.col {
+                        h3 { "From:" }
+                        input type = "date" name = "from" value = (self.from) required;
+                    }
+                    span.form-input.flex.col {
+                        h3 { "To:" }
+                        input type = "date" name = "to" value = (self.to) required;
+                    }
+                    input.button.blue.hover type = "submit" value = "Go!";
+                }
+            }
+        }
+    }
+
+    fn requirement_panel(&self) -> Markup {
+        html! {
+            section.panel.fade {
+                h2.underlined.pad {
+                    "Requirement changes"
+                }
+                @if self.diff.requirement_changes.is_empty() {
+                    p { "No requirements were changed" }
+                }
+                @else {
+                    ul {
+                        @for change in &self.diff.requirement_changes {
+                            li {
+                                a href = {"/demonlist/permalink/" (change.demon.id) "/"} {
+                                    "#" (change.demon.position) (PreEscaped(" &#8211; ")) (change.demon.name)
+                                }
+                                ": " (change.old_requirement) "% " (PreEscaped("&#8594;")) " " (change.new_requirement) "%"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn records_panel(&self) -> Markup {
+        html! {
+            section.records.panel.fade {
+                h2.underlined.pad {
+                    "New records"
+                }
+                @if self.diff.records.is_empty() {
+                    p { "No records were approved" }
+                }
+                @else {
+                    table {
+                        tbody {
+                            tr {
+                                th.blue { "Demon" }
+                                th.blue { "Record Holder" }
+                                th.blue { "Progress" }
+                                th.video-link.blue { "Video Proof" }
+                            }
+                            @for record in &self.diff.records {
+                                tr {
+                                    td {
+                                        a href = {"/demonlist/permalink/" (record.demon.id) "/"} {
+                                            "#" (record.demon.position) (PreEscaped(" &#8211; ")) (record.demon.name)
+                                        }
+                                    }
+                                    td {
+                                        (record.player.name)
+                                    }
+                                    td {
+                                        @if let Some(completion_time) = record.completion_time {
+                                            (format_completion_time(completion_time))
+                                        }
+                                        @else {
+                                            (record.progress) "%"
+                                        }
+                                    }
+                                    td.video-link {
+                                        @if let Some(ref video) = record.video {
+                                            a.link href = (video) target = "_blank" {
+                                                (host(video))
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn movement_panel(title: &str, empty: &str, changes: &[PositionChange]) -> Markup {
+    html! {
+        section.panel.fade {
+            h2.underlined.pad {
+                (title)
+            }
+            @if changes.is_empty() {
+                p { (empty) }
+            }
+            @else {
+                ul {
+                    @for change in changes {
+                        li {
+                            a href = {"/demonlist/permalink/" (change.id) "/"} {
+                                (change.name)
+                            }
+                            ": "
+                            @match change.old_position {
+                                Some(old_position) => {"#" (old_position) " " (PreEscaped("&#8594;")) " #" (change.new_position)},
+                                None => {"new at #" (change.new_position)},
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn changelog_panel(list: &List) -> Markup {
+    html! {
+        section#changelog.panel.fade.js-scroll-anim data-anim = "fade" {
+            div.underlined {
+                h2 {
+                    "Changelog"
+                }
+            }
+            p {
+                "Missed some changes? See which demons were added, moved or removed recently, and which records got approved!"
+            }
+            a.blue.hover.button href = {"/demonlist/changelog/" (list_query(list))} {
+                "Open the changelog!"
+            }
+        }
+    }
+}
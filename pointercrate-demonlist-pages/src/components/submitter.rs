@@ -1,19 +1,29 @@
 use crate::components::{demon_dropdown, player_selection_dialog};
 use maud::{html, Markup, Render};
-use pointercrate_demonlist::{config, demon::Demon};
+use pointercrate_demonlist::{demon::Demon, list::List};
 
 pub struct RecordSubmitter<'a> {
     initially_visible: bool,
+    lists: &'a [List],
     demons: &'a [Demon],
 }
 
-impl RecordSubmitter<'_> {
-    pub fn new(visible: bool, demons: &[Demon]) -> RecordSubmitter {
+impl<'a> RecordSubmitter<'a> {
+    /// `lists` needs to contain the lists of all given demons
+    pub fn new(visible: bool, lists: &'a [List], demons: &'a [Demon]) -> RecordSubmitter<'a> {
         RecordSubmitter {
             initially_visible: visible,
+            lists,
             demons,
         }
     }
+
+    /// Whether records for the given demon can be submitted, meaning it is not a legacy demon
+    fn accepts_submissions_for(&self, demon: &Demon) -> bool {
+        self.lists
+            .iter()
+            .any(|list| list.id == demon.list && demon.base.position <= list.extended_list_size)
+    }
 }
 
 impl Render for RecordSubmitter<'_> {
@@ -31,10 +41,10 @@ impl Render for RecordSubmitter<'_> {
                         "Demon:"
                     }
                     p {
-                        "The demon the record was made on. Only demons in the top " (config::extended_list_size()) " are accepted. This excludes legacy demons!"
+                        "The demon the record was made on. Only demons on the main and extended list are accepted. This excludes legacy demons!"
                     }
                     span.form-input data-type = "dropdown" {
-                        (demon_dropdown("id_demon", self.demons.iter().filter(|demon| demon.base.position <= config::extended_list_size())))
+                        (demon_dropdown("id_demon", self.demons.iter().filter(|demon| self.accepts_submissions_for(demon))))
                         p.error {}
                     }
                     h3 {
@@ -7,13 +7,15 @@ use crate::{
 };
 use chrono::NaiveDateTime;
 use maud::{html, Markup, PreEscaped, Render};
+use pointercrate_core::video;
 use pointercrate_core_pages::{config as page_config, head::HeadLike, PageFragment};
 use pointercrate_demonlist::{
-    config as list_config,
-    demon::{Demon, FullDemon},
+    demon::{Demon, FullDemon, RecordKind},
+    list::List,
+    record::MinimalRecordP,
+    scoring::ScoringFormula,
 };
 use pointercrate_integrate::gd::{DemonRating, GDIntegrationResult, LevelRating, Thunk};
-use url::Url;
 
 #[derive(Debug)]
 pub struct DemonMovement {
@@ -23,10 +25,17 @@ pub struct DemonMovement {
 
 pub struct DemonPage {
     pub team: Team,
+
+    /// The list the demon is on
+    pub list: List,
+
+    /// All lists, for the list selector
+    pub lists: Vec<List>,
     pub demonlist: Vec<Demon>,
     pub data: FullDemon,
     pub movements: Vec<DemonMovement>,
     pub integration: GDIntegrationResult,
+    pub scoring: ScoringFormula,
 }
 
 impl From<DemonPage> for PageFragment {
@@ -103,16 +112,22 @@ impl DemonPage {
             "##, self.data.position(), self.data.name(), self.description().render().0)))
             (PreEscaped(format!("
                 <script>
-                    window.list_length = {0};
-                    window.extended_list_length = {1};
-                    window.demon_id = {2};
-                </script>", list_config::list_size(), list_config::extended_list_size(), self.data.demon.base.id
+                    window.list_id = {0};
+                    window.list_length = {1};
+                    window.extended_list_length = {2};
+                    window.demon_id = {3};
+                </script>", self.list.id, self.list.list_size, self.list.extended_list_size, self.data.demon.base.id
             )))
         }
     }
 
     fn body(&self) -> Markup {
-        let dropdowns = super::dropdowns(&self.demonlist.iter().collect::<Vec<_>>()[..], Some(&self.data.demon));
+        let dropdowns = super::dropdowns(
+            &self.list,
+            &self.lists,
+            &self.demonlist.iter().collect::<Vec<_>>()[..],
+            Some(&self.data.demon),
+        );
 
         let mut labels = Vec::new();
 
@@ -161,7 +176,7 @@ impl DemonPage {
                             "#, publisher_id)))
                         }
                     }
-                    (RecordSubmitter::new(false, &self.demonlist))
+                    (RecordSubmitter::new(false, std::slice::from_ref(&self.list), &self.demonlist))
                     (self.demon_panel())
                     div.panel.fade.js-scroll-anim.js-collapse data-anim = "fade" {
                         h2.underlined.pad {
@@ -217,21 +232,21 @@ impl DemonPage {
         let position = self.data.demon.base.position;
         let name = &self.data.demon.base.name;
 
-        let score100 = self.data.demon.score(100);
-        let score_requirement = self.data.demon.score(self.data.demon.requirement);
+        let score100 = self.data.demon.score(&self.scoring, 100);
+        let score_requirement = self.data.demon.score(&self.scoring, self.data.demon.requirement);
 
         html! {
             section.panel.fade.js-scroll-anim data-anim = "fade" {
                 div.underlined {
                     h1#demon-heading style = "overflow: hidden"{
                         @if self.data.demon.base.position != 1 {
-                            a href=(format!("/demonlist/{:?}", self.data.demon.base.position - 1)) {
+                            a href=(format!("/demonlist/{:?}{}", self.data.demon.base.position - 1, super::list_query(&self.list))) {
                                 i class="fa fa-chevron-left" style="padding-right: 5%" {}
                             }
                         }
                         (name)
                         @if position as usize != self.demonlist.len() {
-                            a href=(format!("/demonlist/{:?}", position + 1)) {
+                            a href=(format!("/demonlist/{:?}{}", position + 1, super::list_query(&self.list))) {
                                 i class="fa fa-chevron-right" style="padding-left: 5%" {}
                             }
                         }
@@ -371,7 +386,7 @@ impl DemonPage {
                             }
                         }
                     }
-                    @if position <= list_config::extended_list_size() {
+                    @if position <= self.list.extended_list_size {
                         span {
                             b {
                                 "Demonlist score (100%): "
@@ -380,7 +395,7 @@ impl DemonPage {
                             (format!("{:.2}", score100))
                         }
                     }
-                    @if position <= list_config::list_size(){
+                    @if position <= self.list.list_size {
                         span {
                             b {
                                 "Demonlist score (" (self.data.demon.requirement) "%): "
@@ -397,25 +412,36 @@ impl DemonPage {
     fn records_panel(&self) -> Markup {
         let position = self.data.demon.base.position;
         let _name = &self.data.demon.base.name;
+        let timed = self.data.demon.record_kind == RecordKind::Time;
 
         html! {
-            @if !self.data.records.is_empty() || position <= list_config::extended_list_size() {
+            @if !self.data.records.is_empty() || position <= self.list.extended_list_size {
                 section.records.panel.fade.js-scroll-anim data-anim = "fade" {
                     div.underlined.pad {
                         h2 {
                             "Records"
                         }
-                        @if position <= list_config::list_size() {
+                        @if timed && position <= self.list.extended_list_size {
+                            h3 {
+                                "A completion is required to qualify, records are ranked by completion time"
+                            }
+                        }
+                        @else if position <= self.list.list_size {
                             h3 {
                                 (self.data.demon.requirement) "% or better required to qualify"
                             }
                         }
-                        @else if position <= list_config::extended_list_size() {
+                        @else if position <= self.list.extended_list_size {
                             h3 {
                                 "100% required to qualify"
                             }
                         }
-                        @if !self.data.records.is_empty() {
+                        @if timed && !self.data.records.is_empty() {
+                            h4 {
+                                (self.data.records.len()) " records registered"
+                            }
+                        }
+                        @else if !self.data.records.is_empty() {
                             h4 {
                                 @let records_registered_100_count = self.data.records.iter().filter(|record| record.progress == 100).count();
                                 (self.data.records.len())
@@ -428,7 +454,7 @@ impl DemonPage {
                     }
                     @if self.data.records.is_empty() {
                         h3 {
-                            @if position > list_config::extended_list_size() {
+                            @if position > self.list.extended_list_size {
                                 "No records!"
                             }
                             @else {
@@ -445,7 +471,7 @@ impl DemonPage {
                                         "Record Holder"
                                     }
                                     th.blue {
-                                        "Progress"
+                                        @if timed { "Time" } @else { "Progress" }
                                     }
                                     th.video-link.blue {
                                         "Video Proof"
@@ -461,15 +487,20 @@ impl DemonPage {
                                         td {
                                             @if let Some(ref video) = record.video {
                                                  a href = (video) target = "_blank"{
-                                                    (record.player.name)
+                                                    (record_holders(record))
                                                  }
                                             }
                                             @else {
-                                                (record.player.name)
+                                                (record_holders(record))
                                             }
                                         }
                                         td {
-                                            (record.progress) "%"
+                                            @if let Some(completion_time) = record.completion_time {
+                                                (format_completion_time(completion_time))
+                                            }
+                                            @else {
+                                                (record.progress) "%"
+                                            }
                                         }
                                         td.video-link {
                                             @if let Some(ref video) = record.video {
@@ -489,37 +520,34 @@ impl DemonPage {
     }
 }
 
-fn host(video: &str) -> &str {
-    match Url::parse(video).unwrap().domain().unwrap() {
-        "www.youtube.com" => "YouTube",
-        "www.twitch.tv" => "Twitch",
-        "everyplay.com" => "Everyplay",
-        "www.bilibili.com" => "Bilibili",
-        "vimeo.com" => "Vimeo",
-        host => panic!("{}", host),
-    }
+/// The names of all players credited with the given record, joined as `A & B`
+fn record_holders(record: &MinimalRecordP) -> String {
+    std::iter::once(&record.player)
+        .chain(record.partners.iter())
+        .map(|player| player.name.as_str())
+        .collect::<Vec<_>>()
+        .join(" & ")
 }
 
-fn embed(video: &str) -> Option<String> {
-    // Video URLs need to be wellformed once we get here!
-    let url = Url::parse(video).unwrap();
+/// Formats a completion time given in milliseconds as `m:ss.mmm`, or `h:mm:ss.mmm` for times of at
+/// least an hour
+pub(crate) fn format_completion_time(completion_time: i32) -> String {
+    let millis = completion_time % 1000;
+    let seconds = completion_time / 1000 % 60;
+    let minutes = completion_time / 60_000 % 60;
+    let hours = completion_time / 3_600_000;
 
-    match url.domain()? {
-        "www.youtube.com" => {
-            let video_id = url
-                .query_pairs()
-                .find_map(|(key, value)| if key == "v" { Some(value) } else { None })?;
+    if hours > 0 {
+        format!("{}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis)
+    } else {
+        format!("{}:{:02}.{:03}", minutes, seconds, millis)
+    }
+}
 
-            Some(format!("https://www.youtube.com/embed/{}", video_id))
-        },
-        "www.twitch.tv" => {
-            // per validation always of the form 'https://www.twitch.tv/videos/[video id]/'
-            let mut url_segment = url.path_segments()?;
-            url_segment.next()?;
-            let video_id = url_segment.next()?;
+pub(crate) fn host(video: &str) -> &'static str {
+    video::hosts().host_name(video).unwrap_or("Video")
+}
 
-            Some(format!("https://player.twitch.tv/?video={}&autoplay=false", video_id))
-        },
-        _ => None,
-    }
+fn embed(video: &str) -> Option<String> {
+    video::hosts().embed(video)
 }
@@ -1,8 +1,9 @@
 use maud::{html, Markup, PreEscaped};
 
-use pointercrate_demonlist::{config, demon::Demon};
+use pointercrate_demonlist::{demon::Demon, list::List};
 
 pub mod account;
+pub mod changelog;
 pub mod components;
 pub mod demon_page;
 pub mod overview;
@@ -41,23 +42,55 @@ static LEGACY_SECTION: ListSection = ListSection {
     numbered: false,
 };
 
-fn dropdowns(all_demons: &[&Demon], current: Option<&Demon>) -> Markup {
-    let (main, extended, legacy) = if all_demons.len() < config::list_size() as usize {
+/// The query string selecting the given list on the overview and demon pages, empty for the default
+/// list
+fn list_query(list: &List) -> String {
+    if list.is_default {
+        String::new()
+    } else {
+        format!("?list={}", list.slug)
+    }
+}
+
+fn list_selector(lists: &[List], current: &List) -> Markup {
+    html! {
+        @if lists.len() > 1 {
+            nav.flex.wrap.m-center.fade#list-selector style="text-align: center;" {
+                @for list in lists {
+                    @if list.id == current.id {
+                        div.button.blue.no-shadow {
+                            (list.name)
+                        }
+                    }
+                    @else {
+                        a.button.white.hover.no-shadow href = {"/demonlist/" (list_query(list))} {
+                            (list.name)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn dropdowns(list: &List, lists: &[List], all_demons: &[&Demon], current: Option<&Demon>) -> Markup {
+    let list_size = list.list_size as usize;
+    let extended_list_size = list.extended_list_size as usize;
+
+    let (main, extended, legacy) = if all_demons.len() < list_size {
         (all_demons, Default::default(), Default::default())
     } else {
-        let (extended, legacy) = if all_demons.len() < config::extended_list_size() as usize {
-            (&all_demons[config::list_size() as usize..], Default::default())
+        let (extended, legacy) = if all_demons.len() < extended_list_size {
+            (&all_demons[list_size..], Default::default())
         } else {
-            (
-                &all_demons[config::list_size() as usize..config::extended_list_size() as usize],
-                &all_demons[config::extended_list_size() as usize..],
-            )
+            (&all_demons[list_size..extended_list_size], &all_demons[extended_list_size..])
         };
 
-        (&all_demons[..config::list_size() as usize], extended, legacy)
+        (&all_demons[..list_size], extended, legacy)
     };
 
     html! {
+        (list_selector(lists, list))
         nav.flex.wrap.m-center.fade#lists style="text-align: center;" {
             // The drop down for the main list:
             (dropdown(&MAIN_SECTION, main, current))
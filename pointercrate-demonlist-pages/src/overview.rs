@@ -1,4 +1,5 @@
 use crate::{
+    changelog::changelog_panel,
     components::{
         submitter::{submit_panel, RecordSubmitter},
         team::Team,
@@ -9,18 +10,24 @@ use crate::{
 use maud::{html, Markup, PreEscaped};
 use pointercrate_core_pages::{config as page_config, head::HeadLike, PageFragment};
 use pointercrate_demonlist::{
-    config as list_config,
     demon::{Demon, TimeShiftedDemon},
+    list::List,
 };
 
 pub struct OverviewPage {
     pub team: Team,
+
+    /// The list being displayed
+    pub list: List,
+
+    /// All lists, for the list selector
+    pub lists: Vec<List>,
     pub demonlist: Vec<Demon>,
     pub time_machine: Tardis,
     pub submitter_initially_visible: bool,
 }
 
-fn demon_panel(demon: &Demon, current_position: Option<i16>) -> Markup {
+fn demon_panel(list: &List, demon: &Demon, current_position: Option<i16>) -> Markup {
     html! {
         section.panel.fade style="overflow:hidden" {
             div.flex style = "align-items: center" {
@@ -44,7 +51,7 @@ fn demon_panel(demon: &Demon, current_position: Option<i16>) -> Markup {
                         }
                         @if let Some(current_position) = current_position {
                             br;
-                            @if current_position > list_config::extended_list_size() {
+                            @if current_position > list.extended_list_size {
                                 "Currently Legacy"
                             }
                             @else {
@@ -145,9 +152,10 @@ impl OverviewPage {
             "#))
             (PreEscaped(format!("
                 <script>
-                    window.list_length = {0};
-                    window.extended_list_length = {1}
-                </script>", list_config::list_size(), list_config::extended_list_size())
+                    window.list_id = {0};
+                    window.list_length = {1};
+                    window.extended_list_length = {2}
+                </script>", self.list.id, self.list.list_size, self.list.extended_list_size)
             ))
             // FIXME: abstract away
             link ref = "canonical" href = "https://pointercrate.com/demonlist/";
@@ -160,7 +168,7 @@ impl OverviewPage {
             _ => self.demonlist.iter().collect(),
         };
 
-        let dropdowns = super::dropdowns(&demons_for_dropdown[..], None);
+        let dropdowns = super::dropdowns(&self.list, &self.lists, &demons_for_dropdown[..], None);
 
         html! {
             (super::besides_sidebar_ad())
@@ -169,20 +177,20 @@ impl OverviewPage {
             div.flex.m-center.container {
                 main.left {
                     (self.time_machine)
-                    (RecordSubmitter::new(self.submitter_initially_visible, &self.demonlist))
+                    (RecordSubmitter::new(self.submitter_initially_visible, std::slice::from_ref(&self.list), &self.demonlist))
 
                     @match &self.time_machine {
                         Tardis::Activated { demons, ..} => {
                             @for TimeShiftedDemon {current_demon, position_now} in demons {
-                                @if current_demon.base.position <= list_config::extended_list_size() {
-                                    (demon_panel(current_demon, Some(*position_now)))
+                                @if current_demon.base.position <= self.list.extended_list_size {
+                                    (demon_panel(&self.list, current_demon, Some(*position_now)))
                                 }
                             }
                         },
                         _ => {
                             @for demon in &self.demonlist {
-                                @if demon.base.position <= list_config::extended_list_size() {
-                                    (demon_panel(demon, None))
+                                @if demon.base.position <= self.list.extended_list_size {
+                                    (demon_panel(&self.list, demon, None))
                                 }
                             }
                         }
@@ -195,6 +203,7 @@ impl OverviewPage {
                     (super::rules_panel())
                     (submit_panel())
                     (stats_viewer_panel())
+                    (changelog_panel(&self.list))
                     (super::discord_panel())
                 }
             }
@@ -1,10 +1,12 @@
-use crate::statsviewer::stats_viewer_html;
+use crate::statsviewer::{stats_viewer_html, StatsViewerRow};
 use maud::{html, Markup, PreEscaped};
 use pointercrate_core_pages::{config, head::HeadLike, PageFragment};
-use pointercrate_demonlist::nationality::Nationality;
+use pointercrate_demonlist::{list::List, nationality::Nationality};
 
 #[derive(Debug)]
 pub struct IndividualStatsViewer {
+    /// The list whose ranking is displayed
+    pub list: List,
     pub nationalities_in_use: Vec<Nationality>,
 }
 
@@ -19,6 +21,7 @@ impl From<IndividualStatsViewer> for PageFragment {
         .module("/static/demonlist/js/statsviewer/individual.js")
         .stylesheet("/static/demonlist/css/statsviewer.css")
         .stylesheet("/static/core/css/sidebar.css")
+        .head(super::list_script(&stats_viewer.list))
         .body(stats_viewer.body())
     }
 }
@@ -55,7 +58,7 @@ impl IndividualStatsViewer {
                             "#, publisher_id)))
                         }
                     }
-                    (stats_viewer_html(Some(&self.nationalities_in_use), super::standard_stats_viewer_rows()))
+                    (stats_viewer_html(Some(&self.nationalities_in_use), individual_stats_viewer_rows()))
                 }
                 aside.right {
                     (super::continent_panel())
@@ -101,3 +104,11 @@ impl IndividualStatsViewer {
         }
     }
 }
+
+fn individual_stats_viewer_rows() -> Vec<StatsViewerRow> {
+    let mut rows = super::standard_stats_viewer_rows();
+
+    rows.push(StatsViewerRow(vec![("Progress history", "progress-history")]));
+    rows.push(StatsViewerRow(vec![("Rank history", "rank-history")]));
+    rows
+}
@@ -1,10 +1,21 @@
 use maud::{html, Markup, PreEscaped};
 use pointercrate_core_pages::util::{dropdown, filtered_paginator, simple_dropdown};
-use pointercrate_demonlist::nationality::Nationality;
+use pointercrate_demonlist::{list::List, nationality::Nationality};
 
 pub mod individual;
 pub mod national;
 
+/// Makes the stats viewer javascript request rankings and list information for the given list
+fn list_script(list: &List) -> Markup {
+    html! {
+        (PreEscaped(format!("
+            <script>
+                window.list_id = {};
+            </script>", list.id
+        )))
+    }
+}
+
 pub(crate) fn stats_viewer_panel() -> Markup {
     html! {
         section#stats.panel.fade.js-scroll-anim data-anim = "fade" {
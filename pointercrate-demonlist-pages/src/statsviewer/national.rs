@@ -1,8 +1,10 @@
 use crate::statsviewer::{stats_viewer_html, StatsViewerRow};
 use maud::{html, Markup, PreEscaped};
 use pointercrate_core_pages::{config, head::HeadLike, PageFragment};
+use pointercrate_demonlist::list::List;
 
-pub fn nation_based_stats_viewer() -> PageFragment {
+/// The nation stats viewer, displaying the nation ranking of the given list
+pub fn nation_based_stats_viewer(list: &List) -> PageFragment {
     PageFragment::new(
         "Nation Stats Viewer",
         "The pointercrate nation stats viewer, ranking how well each nations player's are doing in their quest to collectively complete \
@@ -12,6 +14,7 @@ pub fn nation_based_stats_viewer() -> PageFragment {
     .module("/static/demonlist/js/statsviewer/nation.js")
     .stylesheet("/static/demonlist/css/statsviewer.css")
     .stylesheet("/static/core/css/sidebar.css")
+    .head(super::list_script(list))
     .body(nation_based_stats_viewer_html())
 }
 
@@ -39,6 +39,7 @@ class RecordManager extends Paginator {
 
     this._video = document.getElementById("record-video");
     this._video_link = document.getElementById("record-video-link");
+    this._video_flags = document.getElementById("record-video-flags");
     this._id = document.getElementById("record-id");
     this._demon = document.getElementById("record-demon");
     this._holder = document.getElementById("record-holder");
@@ -63,6 +64,17 @@ class RecordManager extends Paginator {
       else this.updateQueryData("status", selected);
     });
 
+    new Dropdown(
+      document
+        .getElementById("status-filter-panel")
+        .getElementsByClassName("dropdown-menu")[1]
+    ).addEventListener((selected) => {
+      this.updateQueryData2({
+        broken_video: selected === "broken_video" ? true : undefined,
+        video_flagged: selected === "video_flagged" ? true : undefined,
+      });
+    });
+
     this._status = setupDropdownEditor(
       new PaginatorEditorBackend(this, true),
       "edit-record-status",
@@ -70,6 +82,14 @@ class RecordManager extends Paginator {
       this.output
     );
 
+    this._rejectionReason = setupDropdownEditor(
+      new PaginatorEditorBackend(this, true),
+      "edit-record-rejection-reason",
+      "rejection_reason",
+      this.output,
+      rejectionReasonTranslationTable()
+    );
+
     this.initProgressDialog();
     this.initVideoDialog();
 
@@ -166,9 +186,28 @@ class RecordManager extends Paginator {
       this.currentObject.player.id +
       ")";
     this._status.selectSilently(this.currentObject.status);
+    this._rejectionReason.selectSilently(
+      this.currentObject.rejection_reason
+        ? this.currentObject.rejection_reason.id.toString()
+        : "none"
+    );
     this._progress.innerHTML = this.currentObject.progress + "%";
     this._submitter.innerHTML = this.currentObject.submitter.id;
 
+    this._video_flags.innerHTML = "-";
+
+    if (this.currentObject.video !== undefined) {
+      get("/api/v1/records/" + this.currentObject.id + "/video")
+        .then((response) => {
+          let flags = response.data.flags;
+
+          this._video_flags.innerHTML = flags.length
+            ? flags.map((flag) => flag.replace(/_/g, " ")).join(", ")
+            : "None";
+        })
+        .catch(() => (this._video_flags.innerHTML = "Not checked yet"));
+    }
+
     // this is introducing race conditions. Oh well.
     return get("/api/v1/records/" + this.currentObject.id + "/notes").then(response => {
       // clear notes
@@ -185,6 +224,21 @@ class RecordManager extends Paginator {
   }
 }
 
+// Rejection reasons are identified by their (integer) ids, but dropdown values are strings
+function rejectionReasonTranslationTable() {
+  let table = { none: null };
+
+  for (let item of document
+    .getElementById("edit-record-rejection-reason")
+    .getElementsByTagName("li")) {
+    if (item.dataset.value !== "none") {
+      table[item.dataset.value] = parseInt(item.dataset.value);
+    }
+  }
+
+  return table;
+}
+
 function createNoteHtml(note) {
   let noteDiv = document.createElement("div");
 
@@ -312,6 +366,18 @@ function setupRecordSearchRecordIdForm() {
   });
 }
 
+function setupReviewQueue() {
+  let output = new Output(document.getElementById("review-queue"));
+
+  document
+    .getElementById("review-queue-next")
+    .addEventListener("click", () => {
+      post("/api/v2/review-queue/next", {}, {})
+        .then((response) => recordManager.selectArbitrary(response.data.record))
+        .catch(displayError(output));
+    });
+}
+
 function setupRecordFilterPlayerNameForm() {
   var recordFilterPlayerNameForm = new Form(
     document.getElementById("record-filter-by-player-name-form")
@@ -360,6 +426,7 @@ export function initialize() {
   setupAddNote();
   setupEditRecordForm();
   setupRecordSearchRecordIdForm();
+  setupReviewQueue();
 
   initializeRecordSubmitter(true);
 
@@ -17,18 +17,30 @@ import {
 
 export function embedVideo(video) {
   if (!video) return;
-  // welcome to incredibly fragile string parsing with stadust
-  // see pointercrate::video::embed for a proper implementation of this
+  // see pointercrate_core::video for the full set of supported hosts. Video links are in canonical
+  // form here, so we only need to deal with the format each host canonicalizes to.
+  let url = new URL(video);
 
-  if (video.startsWith("https://www.youtube")) {
-    return "https://www.youtube.com/embed/" + video.substring(32);
+  if (url.hostname === "www.youtube.com") {
+    let embed = "https://www.youtube.com/embed/" + url.searchParams.get("v");
+
+    if (url.searchParams.has("t")) embed += "?start=" + url.searchParams.get("t");
+
+    return embed;
   }
 
-  if (video.startsWith("https://www.twitch")) {
-    return (
+  if (url.hostname === "www.twitch.tv") {
+    let embed =
       "https://player.twitch.tv/?autoplay=false&parent=pointercrate.com&video=" +
-      video.substring(29)
-    );
+      url.pathname.substring("/videos/".length);
+
+    if (url.searchParams.has("t")) embed += "&time=" + url.searchParams.get("t");
+
+    return embed;
+  }
+
+  if (url.hostname === "streamable.com") {
+    return "https://streamable.com/e/" + url.pathname.substring(1);
   }
 }
 
@@ -299,6 +311,9 @@ export function generateRecord(record) {
     case "under consideration":
       li.style.backgroundColor = "rgba(142, 230, 230, .3)";
       break;
+    case "needs info":
+      li.style.backgroundColor = "rgba(240, 180, 120, .3)";
+      break;
     default:
       break;
   }
@@ -316,5 +331,17 @@ export function generateRecord(record) {
   );
   li.appendChild(document.createElement("br"));
 
+  if (record.rejection_reason) {
+    let reason = document.createElement("i");
+
+    reason.title = record.rejection_reason.description;
+    reason.appendChild(
+      document.createTextNode("Rejected: " + record.rejection_reason.name)
+    );
+
+    li.appendChild(reason);
+    li.appendChild(document.createElement("br"));
+  }
+
   return li;
 }
\ No newline at end of file
@@ -18,6 +18,11 @@ export class StatsViewer extends FilteredPaginator {
         this.endpoint = statsviewerdata.rankingEndpoint;
         // different from pagination endpoint here!
         this.retrievalEndpoint = statsviewerdata.retrievalEndpoint;
+
+        if (window.list_id !== undefined) {
+            this.queryData["list"] = window.list_id;
+        }
+
         this.currentLink = this.endpoint + "?" + $.param(this.queryData);
 
         this.html = html;
@@ -54,7 +59,9 @@ export class StatsViewer extends FilteredPaginator {
     }
 
     initialize() {
-        return get("/api/v1/list_information/").then(data => {
+        let listQuery = window.list_id === undefined ? "" : "?list=" + window.list_id;
+
+        return get("/api/v1/list_information/" + listQuery).then(data => {
             this.list_size = data.data['list_size'];
             this.extended_list_size = data.data['extended_list_size'];
 
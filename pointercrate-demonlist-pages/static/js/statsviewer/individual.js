@@ -1,4 +1,4 @@
-import {Dropdown} from "/static/core/js/modules/form.js";
+import {Dropdown, get} from "/static/core/js/modules/form.js";
 import {getCountryFlag, populateSubdivisionDropdown} from "/static/demonlist/js/modules/demonlist.js";
 import {formatInto, InteractiveWorldMap, StatsViewer} from "/static/demonlist/js/modules/statsviewer.js";
 
@@ -52,6 +52,50 @@ class IndividualStatsViewer extends StatsViewer {
             .sort((r1, r2) => r1.progress - r2.progress);
 
         this.formatRecordsInto(this._progress, non100Records);
+        this.formatProgressHistoryInto(document.getElementById("progress-history"), playerData);
+        this.formatRankHistoryInto(document.getElementById("rank-history"), playerData.id);
+    }
+
+    // Charts the player's rank over time, from the daily ranking snapshots
+    formatRankHistoryInto(element, playerId) {
+        this._rankHistoryOf = playerId;
+
+        let listQuery = window.list_id === undefined ? "" : "?list=" + window.list_id;
+
+        get("/api/v1/players/" + playerId + "/history/" + listQuery).then(response => {
+            // Some other player was selected while we were waiting for the response
+            if (this._rankHistoryOf !== playerId)
+                return;
+
+            let snapshots = response.data.player;
+
+            formatInto(element, snapshots.length < 2 ? [] : [rankHistoryChart(snapshots)]);
+        });
+    }
+
+    // Lists the progression on every demon the player has superseded records on, e.g. "Bloodbath (60% → 85% → 100%)"
+    formatProgressHistoryInto(element, playerData) {
+        let progressions = new Map();
+
+        for (let entry of playerData.progress_history) {
+            if (!progressions.has(entry.demon.id))
+                progressions.set(entry.demon.id, {demon: entry.demon, steps: []});
+
+            progressions.get(entry.demon.id).steps.push(entry);
+        }
+
+        for (let record of playerData.records) {
+            if (progressions.has(record.demon.id))
+                progressions.get(record.demon.id).steps.push(record);
+        }
+
+        formatInto(element, [...progressions.values()].map(progression => {
+            let demon = this.formatDemon(progression.demon, "/demonlist/permalink/" + progression.demon.id + "/");
+
+            demon.appendChild(document.createTextNode(" (" + progression.steps.map(formatProgress).join(" → ") + ")"));
+
+            return demon;
+        }));
     }
 
     formatDemonsInto(element, demons) {
@@ -141,6 +185,67 @@ $(window).on("load", function () {
     });
 });
 
+const SVG_NAMESPACE = "http://www.w3.org/2000/svg";
+
+// Draws the given ranking snapshots (oldest first, at least two) as a line chart, with better ranks further up
+function rankHistoryChart(snapshots) {
+    const width = 400, height = 120, padding = 20;
+
+    let ranks = snapshots.map(snapshot => snapshot.rank);
+    let best = Math.min(...ranks), worst = Math.max(...ranks);
+    let first = Date.parse(snapshots[0].date), last = Date.parse(snapshots[snapshots.length - 1].date);
+
+    let x = date => padding + (Date.parse(date) - first) / (last - first) * (width - 2 * padding);
+    // If the rank never changed, the line goes through the middle of the chart
+    let y = rank => padding + (best === worst ? 0.5 : (rank - best) / (worst - best)) * (height - 2 * padding);
+
+    let svg = document.createElementNS(SVG_NAMESPACE, "svg");
+
+    svg.setAttribute("viewBox", "0 0 " + width + " " + height);
+    svg.classList.add("rank-history");
+
+    let line = document.createElementNS(SVG_NAMESPACE, "polyline");
+
+    line.setAttribute("points", snapshots.map(snapshot => x(snapshot.date) + "," + y(snapshot.rank)).join(" "));
+    svg.appendChild(line);
+
+    for (let snapshot of snapshots) {
+        let point = document.createElementNS(SVG_NAMESPACE, "circle");
+        let title = document.createElementNS(SVG_NAMESPACE, "title");
+
+        point.setAttribute("cx", x(snapshot.date));
+        point.setAttribute("cy", y(snapshot.rank));
+        point.setAttribute("r", "2");
+        title.textContent = snapshot.date + ": #" + snapshot.rank + " (" + snapshot.score.toFixed(2) + ")";
+
+        point.appendChild(title);
+        svg.appendChild(point);
+    }
+
+    svg.appendChild(chartLabel(0, padding - 6, "#" + best, "start"));
+    svg.appendChild(chartLabel(0, height - 4, "#" + worst, "start"));
+    svg.appendChild(chartLabel(width, padding - 6, snapshots[snapshots.length - 1].date, "end"));
+
+    return svg;
+}
+
+function chartLabel(x, y, text, anchor) {
+    let label = document.createElementNS(SVG_NAMESPACE, "text");
+
+    label.setAttribute("x", x);
+    label.setAttribute("y", y);
+    label.setAttribute("text-anchor", anchor);
+    label.textContent = text;
+
+    return label;
+}
+
+function formatProgress(record) {
+    if (record.completion_time)
+        return (record.completion_time / 1000).toFixed(3) + "s";
+    return record.progress + "%";
+}
+
 function generateStatsViewerPlayer(player) {
     var li = document.createElement("li");
     var b = document.createElement("b");
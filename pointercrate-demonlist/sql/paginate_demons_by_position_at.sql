@@ -0,0 +1,21 @@
+SELECT demons.id AS demon_id, demons.name::text AS demon_name, demons.position_ AS position, current_demons.list, demons.requirement, current_demons.record_kind::text AS record_kind, demons.level_id, CASE WHEN verifiers.link_banned THEN NULL ElSE demons.video::text END,demons.thumbnail,
+       verifiers.id AS verifier_id, verifiers.name::text AS verifier_name, verifiers.banned AS verifier_banned,
+       publishers.id AS publisher_id, publishers.name::text AS publisher_name, publishers.banned AS publisher_banned
+FROM list_at(COALESCE($12, (SELECT id FROM lists WHERE is_default)), $13) AS demons
+INNER JOIN demons AS current_demons ON current_demons.id=demons.id
+INNER JOIN players AS verifiers ON verifiers.id=demons.verifier
+INNER JOIN players AS publishers ON publishers.id=demons.publisher
+WHERE (demons.position_ < $1 OR $1 IS NULL)
+  AND (demons.position_ > $2 OR $2 IS NULL)
+  AND (demons.name::CITEXT = $3 OR $3 IS NULL)
+  AND (demons.requirement = $4 OR $4 IS NULL)
+  AND (demons.requirement < $5 OR $5 IS NULL)
+  AND (demons.requirement > $6 OR $6 IS NULL)
+  AND (verifiers.id = $7 OR $7 IS NULL)
+  AND (verifiers.name::CITEXT = $8 OR $8 IS NULL)
+  AND (publishers.id = $9 OR $9 IS NULL)
+  AND (publishers.name::CITEXT = $10 OR $10 IS NULL)
+  AND (STRPOS(demons.name, $11::CITEXT) > 0 OR $11 is NULL)
+  AND demons.position_ IS NOT NULL
+ORDER BY demons.position_ {}
+LIMIT $14
\ No newline at end of file
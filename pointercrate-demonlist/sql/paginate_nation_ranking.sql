@@ -0,0 +1,11 @@
+SELECT rank, score, nation::TEXT, iso_country_code::TEXT, index
+FROM (
+    SELECT *, ROW_NUMBER() OVER (ORDER BY rank, iso_country_code) AS index
+    FROM nations_with_score_at(COALESCE($5, (SELECT id FROM lists WHERE is_default)), COALESCE($6::TIMESTAMP, 'infinity'))
+) AS ranking
+WHERE (index < $1 OR $1 IS NULL)
+  AND (index > $2 OR $2 IS NULL)
+  AND (STRPOS(nation, $3::CITEXT) > 0 OR $3 is NULL)
+  AND (continent = CAST($4::TEXT AS continent) OR $4 IS NULL)
+ORDER BY index {}
+LIMIT $7
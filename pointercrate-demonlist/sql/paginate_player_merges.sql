@@ -0,0 +1,9 @@
+SELECT id, player, merged, merged_name::TEXT, merged_by, merged_at, reverted_by, reverted_at
+FROM player_merges
+WHERE (id < $1 OR $1 IS NULL)
+  AND (id > $2 OR $2 IS NULL)
+  AND (player = $3 OR $3 IS NULL)
+  AND (merged = $4 OR $4 IS NULL)
+  AND (reverted_at IS NOT NULL = $5 OR $5 IS NULL)
+ORDER BY id {}
+LIMIT $6
@@ -1,5 +1,5 @@
 SELECT id, name::TEXT, rank, score, index, nation::TEXT, iso_country_code::TEXT
-FROM players_with_score
+FROM players_with_score_at(COALESCE($8, (SELECT id FROM lists WHERE is_default)), COALESCE($9::TIMESTAMP, 'infinity'))
 WHERE (index < $1 OR $1 IS NULL)
   AND (index > $2 OR $2 IS NULL)
   AND (STRPOS(name, $3::CITEXT) > 0 OR $3 is NULL)
@@ -7,4 +7,4 @@ WHERE (index < $1 OR $1 IS NULL)
   AND (continent = CAST($6::TEXT AS continent) OR $6 IS NULL)
   AND (subdivision = $7 OR $7 IS NULL)
 ORDER BY rank, id {}
-LIMIT $8
\ No newline at end of file
+LIMIT $10
\ No newline at end of file
@@ -1,9 +1,12 @@
-SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, status_::text AS status,
+SELECT records.id, progress, completion_time, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, status_::text AS status,
        players.id AS player_id, players.name::text AS player_name, players.banned AS player_banned,
-       demons.id AS demon_id, demons.name::text AS demon_name, demons.position
+       demons.id AS demon_id, demons.name::text AS demon_name, demons.position,
+       rejection_reasons.id AS rejection_reason_id, rejection_reasons.name AS rejection_reason_name,
+       rejection_reasons.description AS rejection_reason_description
 FROM records
 INNER JOIN players ON records.player = players.id
 INNER JOIN demons ON records.demon = demons.id
+LEFT OUTER JOIN rejection_reasons ON records.rejection_reason = rejection_reasons.id
 WHERE (records.id < $1 OR $1 IS NULL)
   AND (records.id > $2 OR $2 IS NULL)
   AND (progress = $3 OR $3 IS NULL)
@@ -16,7 +19,12 @@ WHERE (records.id < $1 OR $1 IS NULL)
   AND (demons.name = $10::CITEXT OR $10 IS NULL)
   AND (demons.id = $11 OR $11 IS NULL)
   AND (records.video = $12 OR (records.video IS NULL AND $13) OR ($12 IS NULL AND NOT $13))
-  AND (players.id = $14 OR $14 IS NULL)
+  AND (players.id = $14 OR EXISTS (SELECT 1 FROM record_partners WHERE record_partners.record = records.id AND record_partners.player = $14) OR $14 IS NULL)
   AND (records.submitter = $15 OR $15 IS NULL)
+  AND (demons.list = $16 OR $16 IS NULL)
+  AND (records.rejection_reason = $17 OR $17 IS NULL)
+  AND (EXISTS (SELECT 1 FROM record_reviews WHERE record_reviews.record = records.id AND record_reviews.expires_at > NOW() AT TIME ZONE 'utc') = $18 OR $18 IS NULL)
+  AND (EXISTS (SELECT 1 FROM record_video_metadata WHERE record_video_metadata.record = records.id AND record_video_metadata.flags <> '{{}}') = $19 OR $19 IS NULL)
+  AND (EXISTS (SELECT 1 FROM video_link_checks WHERE video_link_checks.video = records.video AND video_link_checks.health = 'BROKEN') = $20 OR $20 IS NULL)
 ORDER BY id {}
-LIMIT $16
\ No newline at end of file
+LIMIT $21
\ No newline at end of file
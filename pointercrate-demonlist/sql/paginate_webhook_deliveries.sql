@@ -0,0 +1,9 @@
+SELECT id, webhook, event, payload, attempts, response_status, response, created_at, delivered_at
+FROM webhook_deliveries
+WHERE (id < $1 OR $1 IS NULL)
+  AND (id > $2 OR $2 IS NULL)
+  AND webhook = $3
+  AND (event = $4 OR $4 IS NULL)
+  AND (delivered_at IS NOT NULL = $5 OR $5 IS NULL)
+ORDER BY id {}
+LIMIT $6
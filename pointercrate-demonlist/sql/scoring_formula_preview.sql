@@ -0,0 +1,11 @@
+-- Computes the score and rank of every player on the list with id $1 under both the formula currently used by that list
+-- and the one with id $2. Which players are ranked doesn't depend on the formula, so both rankings contain the same players.
+SELECT players.id AS "id!", players.name::TEXT AS "name!", players.banned AS "banned!", current.score AS "current_score!",
+       candidate.score AS "candidate_score!", current.rank AS "current_rank!", candidate.rank AS "candidate_rank!"
+FROM player_ranking_with($1, 'infinity', $2) AS candidate
+         INNER JOIN players_with_score_at($1, 'infinity') AS current
+                    ON current.id = candidate.id
+         INNER JOIN players
+                    ON players.id = candidate.id
+ORDER BY candidate.rank, players.id
+LIMIT $3
\ No newline at end of file
@@ -4,10 +4,11 @@ use crate::{
     error::{DemonlistError, Result},
     player::DatabasePlayer,
 };
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct PostCreator {
     pub creator: String,
 }
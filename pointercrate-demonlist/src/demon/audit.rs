@@ -4,11 +4,12 @@ use crate::demon::MinimalDemon;
 use chrono::{NaiveDateTime, NaiveTime};
 use futures::StreamExt;
 use pointercrate_core::audit::{AuditLogEntry, AuditLogEntryType, NamedId};
+use schemars::JsonSchema;
 use serde::Serialize;
 use sqlx::PgConnection;
 use std::collections::HashMap;
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 pub struct DemonModificationData {
     pub name: Option<String>,
     pub position: Option<i16>,
@@ -18,7 +19,7 @@ pub struct DemonModificationData {
     pub publisher: Option<NamedId>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, JsonSchema, Debug)]
 pub enum MovementReason {
     Added,
     Moved,
@@ -27,7 +28,7 @@ pub enum MovementReason {
     Unknown,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, JsonSchema, Debug)]
 pub struct MovementLogEntry {
     reason: MovementReason,
     time: NaiveDateTime,
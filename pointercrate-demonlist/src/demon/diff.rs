@@ -0,0 +1,212 @@
+//! Module for comparing the state of a list at two points in time
+//!
+//! Both states are reconstructed from the audit log via [`list_at`], so a [`ListDiff`] only
+//! describes the net effect of all changes made within its window. A demon that was moved down and
+//! back up again within the window, for instance, doesn't show up as moved.
+//!
+//! Throughout this module, a demon is "on the list" if it is part of the main or extended list.
+//! Demons falling off the extended list into the legacy list are considered removed.
+
+use crate::{
+    demon::{list_at, Demon, MinimalDemon},
+    error::{DemonlistError, Result},
+    list::List,
+    player::DatabasePlayer,
+    record::{MinimalRecordPD, RecordStatus},
+};
+use chrono::{DateTime, FixedOffset};
+use futures::StreamExt;
+use pointercrate_core::util::{non_nullable_query_timestamp, query_timestamp};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::collections::{HashMap, HashSet};
+
+/// The window of a [`ListDiff`]
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DiffWindow {
+    #[serde(deserialize_with = "query_timestamp")]
+    pub from: DateTime<FixedOffset>,
+
+    /// The end of the window. Defaults to now
+    #[serde(default, deserialize_with = "non_nullable_query_timestamp")]
+    pub to: Option<DateTime<FixedOffset>>,
+
+    /// The list to diff. Defaults to the default list
+    #[serde(default)]
+    pub list: Option<i32>,
+}
+
+/// A demon whose position differs between the start and the end of a [`ListDiff`]'s window
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Eq, Clone)]
+pub struct PositionChange {
+    pub id: i32,
+
+    /// The demon's name at the end of the window
+    pub name: String,
+
+    /// The demon's position at the start of the window, `None` if the demon didn't exist yet
+    pub old_position: Option<i16>,
+    pub new_position: i16,
+}
+
+/// A demon whose requirement differs between the start and the end of a [`ListDiff`]'s window
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Eq, Clone)]
+pub struct RequirementChange {
+    pub demon: MinimalDemon,
+    pub old_requirement: i16,
+    pub new_requirement: i16,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ListDiff {
+    pub from: DateTime<FixedOffset>,
+    pub to: DateTime<FixedOffset>,
+
+    /// Demons that are on the list at the end of the window, but weren't at its start
+    pub added: Vec<PositionChange>,
+
+    /// Demons that were on the list at the start of the window, but aren't at its end
+    pub removed: Vec<PositionChange>,
+
+    /// Demons on the list at both ends of the window that were explicitly moved within it. Demons
+    /// that were only shifted by other demons being added, removed or moved are not included.
+    pub moved: Vec<PositionChange>,
+
+    /// Demons on the list at both ends of the window whose requirement changed within it
+    pub requirement_changes: Vec<RequirementChange>,
+
+    /// Records on demons on the list at the end of the window that got approved within it, or
+    /// whose approved progress changed within it. Demon positions are the ones at the end of the
+    /// window.
+    pub records: Vec<MinimalRecordPD>,
+}
+
+impl ListDiff {
+    pub async fn between(
+        list: &List, from: DateTime<FixedOffset>, to: DateTime<FixedOffset>, connection: &mut PgConnection,
+    ) -> Result<ListDiff> {
+        if from > to {
+            return Err(DemonlistError::InvalidDiffWindow);
+        }
+
+        let before = list_at(list, &mut *connection, from).await?;
+        let after = list_at(list, &mut *connection, to).await?;
+
+        let before = before
+            .into_iter()
+            .map(|shifted| (shifted.current_demon.base.id, shifted.current_demon))
+            .collect::<HashMap<i32, Demon>>();
+
+        // Moving a demon first moves it to position -1 (see also `movement_log_for_demon`), which
+        // is how explicit moves can be told apart from shifts caused by other demons
+        let explicitly_moved = sqlx::query!(
+            "SELECT DISTINCT id FROM demon_modifications WHERE position = -1 AND time >= $1 AND time < $2",
+            from.naive_utc(),
+            to.naive_utc()
+        )
+        .fetch_all(&mut *connection)
+        .await?
+        .into_iter()
+        .map(|row| row.id)
+        .collect::<HashSet<i32>>();
+
+        let mut diff = ListDiff {
+            from,
+            to,
+            added: Vec::new(),
+            removed: Vec::new(),
+            moved: Vec::new(),
+            requirement_changes: Vec::new(),
+            records: Vec::new(),
+        };
+
+        for demon in after.into_iter().map(|shifted| shifted.current_demon) {
+            let old = before.get(&demon.base.id);
+            let change = PositionChange {
+                id: demon.base.id,
+                name: demon.base.name.clone(),
+                old_position: old.map(|old| old.base.position),
+                new_position: demon.base.position,
+            };
+
+            let was_listed = matches!(change.old_position, Some(position) if position <= list.extended_list_size);
+            let is_listed = change.new_position <= list.extended_list_size;
+
+            match (old, was_listed, is_listed) {
+                (_, false, true) => diff.added.push(change),
+                (_, true, false) => diff.removed.push(change),
+                (Some(old), true, true) => {
+                    if change.old_position != Some(change.new_position) && explicitly_moved.contains(&change.id) {
+                        diff.moved.push(change)
+                    }
+
+                    if old.requirement != demon.requirement {
+                        diff.requirement_changes.push(RequirementChange {
+                            old_requirement: old.requirement,
+                            new_requirement: demon.requirement,
+                            demon: demon.base,
+                        })
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        diff.records = approved_between(list, from, to, connection).await?;
+
+        Ok(diff)
+    }
+}
+
+async fn approved_between(
+    list: &List, from: DateTime<FixedOffset>, to: DateTime<FixedOffset>, connection: &mut PgConnection,
+) -> Result<Vec<MinimalRecordPD>> {
+    let mut stream = sqlx::query!(
+        r#"WITH approved_before AS (SELECT id, progress, completion_time FROM records_at($2) WHERE status_ = 'APPROVED')
+        SELECT records.id AS "id!", records.progress AS "progress!", records.completion_time,
+               CASE WHEN players.link_banned THEN NULL ELSE records.video::text END AS video,
+               demons.id AS "demon_id!", demons.name AS "demon_name!: String", demons.position_ AS "position!",
+               players.id AS player_id, players.name AS "player_name: String", players.banned
+        FROM records_at($3) AS records
+        INNER JOIN list_at($1, $3) AS demons ON demons.id = records.demon
+        INNER JOIN players ON players.id = records.player
+        INNER JOIN lists ON lists.id = $1
+        WHERE records.status_ = 'APPROVED'
+          AND demons.position_ <= lists.extended_list_size
+          AND NOT EXISTS (SELECT 1 FROM approved_before WHERE approved_before.id = records.id AND approved_before.progress =
+                          records.progress AND approved_before.completion_time IS NOT DISTINCT FROM records.completion_time)
+        ORDER BY demons.position_, records.progress DESC, records.completion_time, records.id"#,
+        list.id,
+        from.naive_utc(),
+        to.naive_utc()
+    )
+    .fetch(connection);
+
+    let mut records = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        records.push(MinimalRecordPD {
+            id: row.id,
+            progress: row.progress,
+            completion_time: row.completion_time,
+            video: row.video,
+            status: RecordStatus::Approved,
+            demon: MinimalDemon {
+                id: row.demon_id,
+                position: row.position,
+                name: row.demon_name,
+            },
+            player: DatabasePlayer {
+                id: row.player_id,
+                name: row.player_name,
+                banned: row.banned,
+            },
+            rejection_reason: None,
+        })
+    }
+
+    Ok(records)
+}
@@ -1,13 +1,15 @@
 use crate::{
     creator::creators_of,
-    demon::{Demon, FullDemon, MinimalDemon, TimeShiftedDemon},
+    demon::{Demon, FullDemon, MinimalDemon, RecordKind, TimeShiftedDemon},
     error::{DemonlistError, Result},
+    list::List,
     player::DatabasePlayer,
     record::approved_records_on,
 };
 use chrono::{DateTime, FixedOffset};
 use futures::StreamExt;
 use sqlx::{Error, PgConnection};
+use std::convert::TryFrom;
 
 impl MinimalDemon {
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<MinimalDemon> {
@@ -68,8 +70,8 @@ impl FullDemon {
         Demon::by_id(id, connection).await?.upgrade(connection).await
     }
 
-    pub async fn by_position(position: i16, connection: &mut PgConnection) -> Result<FullDemon> {
-        Demon::by_position(position, connection).await?.upgrade(connection).await
+    pub async fn by_position(list: &List, position: i16, connection: &mut PgConnection) -> Result<FullDemon> {
+        Demon::by_position(list, position, connection).await?.upgrade(connection).await
     }
 }
 
@@ -90,18 +92,18 @@ impl Demon {
         sqlx::query_file_as!(FetchedDemon, "sql/demon_by_id.sql", id)
             .fetch_one(connection)
             .await
-            .map(Into::into)
+            .and_then(Demon::try_from)
             .map_err(|err| match err {
                 Error::RowNotFound => DemonlistError::DemonNotFound { demon_id: id },
                 _ => err.into(),
             })
     }
 
-    pub async fn by_position(position: i16, connection: &mut PgConnection) -> Result<Demon> {
-        sqlx::query_file_as!(FetchedDemon, "sql/demon_by_position.sql", position)
+    pub async fn by_position(list: &List, position: i16, connection: &mut PgConnection) -> Result<Demon> {
+        sqlx::query_file_as!(FetchedDemon, "sql/demon_by_position.sql", position, list.id)
             .fetch_one(connection)
             .await
-            .map(Into::into)
+            .and_then(Demon::try_from)
             .map_err(|err| match err {
                 Error::RowNotFound => DemonlistError::DemonNotFoundPosition { demon_position: position },
                 _ => err.into(),
@@ -148,7 +150,9 @@ struct FetchedDemon {
     demon_id: i32,
     demon_name: String,
     position: i16,
+    list: i32,
     requirement: i16,
+    record_kind: String,
     video: Option<String>,
     thumbnail: String,
     publisher_id: i32,
@@ -160,15 +164,19 @@ struct FetchedDemon {
     level_id: Option<i64>,
 }
 
-impl From<FetchedDemon> for Demon {
-    fn from(fetched: FetchedDemon) -> Self {
-        Demon {
+impl TryFrom<FetchedDemon> for Demon {
+    type Error = Error;
+
+    fn try_from(fetched: FetchedDemon) -> std::result::Result<Self, Error> {
+        Ok(Demon {
             base: MinimalDemon {
                 id: fetched.demon_id,
                 name: fetched.demon_name,
                 position: fetched.position,
             },
+            list: fetched.list,
             requirement: fetched.requirement,
+            record_kind: RecordKind::from_sql(&fetched.record_kind)?,
             video: fetched.video,
             thumbnail: fetched.thumbnail,
             publisher: DatabasePlayer {
@@ -182,21 +190,21 @@ impl From<FetchedDemon> for Demon {
                 banned: fetched.verifier_banned,
             },
             level_id: fetched.level_id.map(|id| id as u64),
-        }
+        })
     }
 }
 
-pub async fn current_list(connection: &mut PgConnection) -> Result<Vec<Demon>> {
-    Ok(sqlx::query_file_as!(FetchedDemon, "sql/all_demons.sql")
+pub async fn current_list(list: &List, connection: &mut PgConnection) -> Result<Vec<Demon>> {
+    Ok(sqlx::query_file_as!(FetchedDemon, "sql/all_demons.sql", list.id)
         .fetch_all(connection)
         .await?
         .into_iter()
-        .map(Into::into)
-        .collect())
+        .map(Demon::try_from)
+        .collect::<std::result::Result<_, _>>()?)
 }
 
-pub async fn list_at(connection: &mut PgConnection, at: DateTime<FixedOffset>) -> Result<Vec<TimeShiftedDemon>> {
-    let mut stream = sqlx::query_file!("sql/all_demons_at.sql", at.naive_utc()).fetch(connection);
+pub async fn list_at(list: &List, connection: &mut PgConnection, at: DateTime<FixedOffset>) -> Result<Vec<TimeShiftedDemon>> {
+    let mut stream = sqlx::query_file!("sql/all_demons_at.sql", list.id, at.naive_utc()).fetch(connection);
     let mut demons = Vec::new();
 
     while let Some(row) = stream.next().await {
@@ -209,7 +217,9 @@ pub async fn list_at(connection: &mut PgConnection, at: DateTime<FixedOffset>) -
                     position: row.position,
                     name: row.demon_name,
                 },
+                list: list.id,
                 requirement: row.requirement,
+                record_kind: RecordKind::from_sql(&row.record_kind)?,
                 video: row.video,
                 thumbnail: row.thumbnail,
                 publisher: DatabasePlayer {
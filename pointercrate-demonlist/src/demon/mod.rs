@@ -8,40 +8,80 @@ use crate::{
     error::{DemonlistError, Result},
     player::DatabasePlayer,
     record::MinimalRecordP,
+    scoring::ScoringFormula,
 };
 use derive_more::Display;
 use log::info;
 use pointercrate_core::{error::CoreError, etag::Taggable};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
 
 #[macro_use]
 mod get;
 pub mod audit;
+pub mod diff;
 mod paginate;
 mod patch;
 mod post;
 
+/// What records on a demon are ranked by
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    /// Records are ranked by the percentage reached, with only 100% records being completions
+    Percentage,
+
+    /// Records are completions, ranked by how fast the player completed the level. Used for
+    /// platformer levels
+    Time,
+}
+
+impl Default for RecordKind {
+    fn default() -> Self {
+        RecordKind::Percentage
+    }
+}
+
+impl RecordKind {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            RecordKind::Percentage => "PERCENTAGE",
+            RecordKind::Time => "TIME",
+        }
+    }
+
+    pub(crate) fn from_sql(kind: &str) -> std::result::Result<Self, sqlx::Error> {
+        match kind {
+            "PERCENTAGE" => Ok(RecordKind::Percentage),
+            "TIME" => Ok(RecordKind::Time),
+            _ => Err(sqlx::Error::Decode(format!("invalid record kind: {}", kind).into())),
+        }
+    }
+}
+
 pub struct TimeShiftedDemon {
     pub current_demon: Demon,
     pub position_now: i16,
 }
 
 /// Struct modelling a demon. These objects are returned from the paginating `/demons/` endpoint
-#[derive(Debug, Serialize, Hash, Display, Eq, PartialEq)]
+#[derive(Debug, Serialize, JsonSchema, Hash, Display, Eq, PartialEq)]
 #[display(fmt = "{}", base)]
 pub struct Demon {
     #[serde(flatten)]
     pub base: MinimalDemon,
 
+    /// The id of the [`List`](crate::list::List) this [`Demon`] is on
+    pub list: i32,
+
     /// The minimal progress a [`Player`] must achieve on this [`Demon`] to have their record
     /// accepted
     pub requirement: i16,
 
+    /// Whether records on this [`Demon`] are ranked by percentage or by completion time
+    pub record_kind: RecordKind,
+
     pub video: Option<String>,
 
     pub thumbnail: String,
@@ -60,7 +100,7 @@ pub struct Demon {
 }
 
 /// Absolutely minimal representation of a demon to be sent when a demon is part of another object
-#[derive(Debug, Hash, Serialize, Deserialize, Display, PartialEq, Eq, Clone)]
+#[derive(Debug, Hash, Serialize, Deserialize, JsonSchema, Display, PartialEq, Eq, Clone)]
 #[display(fmt = "{} (at {})", name, position)]
 pub struct MinimalDemon {
     /// The [`Demon`]'s unique internal pointercrate ID
@@ -81,7 +121,7 @@ pub struct MinimalDemon {
 ///
 /// In addition to containing publisher/verifier information it also contains a list of the demon's
 /// creators and a list of accepted records
-#[derive(Debug, Serialize, Display, PartialEq, Eq, Hash)]
+#[derive(Debug, Serialize, JsonSchema, Display, PartialEq, Eq, Hash)]
 #[display(fmt = "{}", demon)]
 pub struct FullDemon {
     #[serde(flatten)]
@@ -91,11 +131,8 @@ pub struct FullDemon {
 }
 
 impl Taggable for FullDemon {
-    fn patch_part(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.demon.hash(&mut hasher);
-        hasher.finish()
-    }
+    // creators have sub-endpoint, records are managed via /records/
+    const UNPATCHABLE_FIELDS: &'static [&'static str] = &["creators", "records"];
 }
 
 impl MinimalDemon {
@@ -107,6 +144,17 @@ impl MinimalDemon {
             .await?
             .requirement)
     }
+
+    /// Queries whether records on this demon are ranked by percentage or by completion time
+    /// without collecting any of the other data
+    pub async fn record_kind(&self, connection: &mut PgConnection) -> Result<RecordKind> {
+        Ok(RecordKind::from_sql(
+            &sqlx::query!(r#"SELECT record_kind::TEXT AS "record_kind!" FROM demons WHERE id = $1"#, self.id)
+                .fetch_one(connection)
+                .await?
+                .record_kind,
+        )?)
+    }
 }
 
 impl FullDemon {
@@ -175,10 +223,10 @@ impl Demon {
         Ok(())
     }
 
-    pub async fn validate_position(position: i16, connection: &mut PgConnection) -> Result<()> {
+    pub async fn validate_position(list: i32, position: i16, connection: &mut PgConnection) -> Result<()> {
         // To prevent holes from being created in the list, the new position must lie between 1 and (current
         // last position + 1), inclusive
-        let maximal_position = Demon::max_position(connection).await.unwrap_or(0) + 1;
+        let maximal_position = Demon::max_position(list, connection).await.unwrap_or(0) + 1;
 
         if position > maximal_position || position < 1 {
             return Err(DemonlistError::InvalidPosition { maximal: maximal_position });
@@ -187,76 +235,57 @@ impl Demon {
         Ok(())
     }
 
-    /// Increments the position of all demons with positions equal to or greater than the given one,
-    /// by one.
-    async fn shift_down(starting_at: i16, connection: &mut PgConnection) -> Result<()> {
-        info!("Shifting down all demons, starting at {}", starting_at);
+    /// Increments the position of all demons on the given list with positions equal to or greater
+    /// than the given one, by one.
+    async fn shift_down(list: i32, starting_at: i16, connection: &mut PgConnection) -> Result<()> {
+        info!("Shifting down all demons on list {}, starting at {}", list, starting_at);
 
-        sqlx::query!("UPDATE demons SET position = position + 1 WHERE position >= $1", starting_at)
-            .execute(connection)
-            .await?;
+        sqlx::query!(
+            "UPDATE demons SET position = position + 1 WHERE list = $1 AND position >= $2",
+            list,
+            starting_at
+        )
+        .execute(connection)
+        .await?;
 
         Ok(())
     }
 
-    /// Decrements the position of all demons with positions equal to or smaller than the given one,
-    /// by one.
-    async fn shift_up(until: i16, connection: &mut PgConnection) -> Result<()> {
-        info!("Shifting up all demons until {}", until);
+    /// Decrements the position of all demons on the given list with positions equal to or smaller
+    /// than the given one, by one.
+    async fn shift_up(list: i32, until: i16, connection: &mut PgConnection) -> Result<()> {
+        info!("Shifting up all demons on list {} until {}", list, until);
 
-        sqlx::query!("UPDATE demons SET position = position - 1 WHERE position <= $1", until)
-            .execute(connection)
-            .await?;
+        sqlx::query!(
+            "UPDATE demons SET position = position - 1 WHERE list = $1 AND position <= $2",
+            list,
+            until
+        )
+        .execute(connection)
+        .await?;
 
         Ok(())
     }
 
-    /// Gets the current max position a demon has, or `CoreError::NotFound` if there are no demons
-    /// in the database
-    pub async fn max_position(connection: &mut PgConnection) -> Result<i16> {
-        sqlx::query!("SELECT MAX(position) as max_position FROM demons")
+    /// Gets the current max position a demon on the given list has, or `CoreError::NotFound` if
+    /// there are no demons on that list
+    pub async fn max_position(list: i32, connection: &mut PgConnection) -> Result<i16> {
+        sqlx::query!("SELECT MAX(position) as max_position FROM demons WHERE list = $1", list)
             .fetch_one(connection)
             .await?
             .max_position
             .ok_or_else(|| CoreError::NotFound.into())
     }
 
-    /// Gets the maximal and minimal submitter id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_demon_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(id) AS "max_id!: i32", MIN(id) AS "min_id!: i32" FROM demons"#)
-            .fetch_one(connection)
-            .await?;
-        Ok((row.max_id, row.min_id))
+    /// Calculates the score a record with the given progress on this demon is worth under the given
+    /// formula
+    pub fn score(&self, formula: &ScoringFormula, progress: i16) -> f64 {
+        formula.score(self.base.position, self.requirement, progress)
     }
 
-    pub fn score(&self, progress: i16) -> f64 {
-        let position = self.base.position;
-
-        let beaten_score = if 55 < position && position <= 150 {
-            let b = 6.273f64;
-            56.191f64 * (2f64.powf((54.147f64 - (position as f64 + 3.2f64) as f64) * ((50f64.ln()) / 99f64))) + b
-        } else if 35 < position && position <= 55 {
-            let g = 1.036f64;
-            let h = 25.071f64;
-            212.61f64 * (g.powf(1f64 - position as f64)) + h
-        } else if 20 < position && position <= 35 {
-            let c = 1.0099685f64;
-            let d = 31.152f64;
-            (250f64 - 83.389f64) * (c.powf(2f64 - position as f64)) - d
-        } else if 0 < position && position <= 20 {
-            let e = 1.168f64;
-            let f = 100.39f64;
-            (250f64 - f) * (e.powf(1f64 - position as f64) as f64) + f
-        } else {
-            0f64
-        };
-
-        if progress != 100 {
-            (beaten_score * (5f64.powf((progress - self.requirement) as f64 / (100f64 - self.requirement as f64)))) / 10f64
-        } else {
-            beaten_score
-        }
+    /// Calculates the score a record with the given completion time on this demon is worth under
+    /// the given formula, if the fastest approved record on this demon has the given time
+    pub fn time_score(&self, formula: &ScoringFormula, completion_time: i32, fastest_time: i32) -> f64 {
+        formula.time_score(self.base.position, completion_time, fastest_time)
     }
 }
@@ -1,14 +1,22 @@
 use crate::{
-    demon::{Demon, MinimalDemon},
-    error::Result,
+    demon::{Demon, MinimalDemon, RecordKind},
+    error::DemonlistError,
     player::DatabasePlayer,
 };
-use futures::stream::StreamExt;
-use pointercrate_core::{error::CoreError, util::non_nullable};
+use chrono::{DateTime, FixedOffset};
+use pointercrate_core::{
+    pagination::{clamp_i16, clamp_i32, PaginationParameters, Paginator, DEFAULT_LIMIT},
+    util::{non_nullable, non_nullable_query_timestamp},
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct DemonIdPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "before")]
@@ -47,32 +55,43 @@ pub struct DemonIdPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "requirement__lt")]
     requirement_lt: Option<i16>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    list: Option<i32>,
 }
 
-impl DemonIdPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Demon>> {
-        if let Some(limit) = self.limit {
-            if !(1..=100).contains(&limit) {
-                return Err(CoreError::InvalidPaginationLimit.into());
-            }
+impl Paginator for DemonIdPagination {
+    type Error = DemonlistError;
+    type Item = Demon;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
         }
+    }
 
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(CoreError::AfterSmallerBefore.into());
-            }
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        DemonIdPagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
         }
+    }
 
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    fn pagination_key(item: &Demon) -> i64 {
+        item.base.id as i64
+    }
 
-        let query = format!(include_str!("../../sql/paginate_demons_by_id.sql"), order);
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../sql/paginate_demons_by_id.sql"), order)
+    }
 
-        // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&query)
+    // FIXME(sqlx) once CITEXT is supported
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
             .bind(self.before_id)
             .bind(self.after_id)
             .bind(self.name.as_deref())
@@ -84,44 +103,15 @@ impl DemonIdPagination {
             .bind(self.publisher_id)
             .bind(self.publisher_name.as_deref())
             .bind(self.name_contains.as_deref())
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
-
-        let mut demons = Vec::new();
-
-        while let Some(row) = stream.next().await {
-            let row = row?;
-
-            let video: Option<String> = row.get("video");
-
-            demons.push(Demon {
-                base: MinimalDemon {
-                    id: row.get("demon_id"),
-                    name: row.get("demon_name"),
-                    position: row.get("position"),
-                },
-                requirement: row.get("requirement"),
-                video,
-                thumbnail: row.get("thumbnail"),
-                publisher: DatabasePlayer {
-                    id: row.get("publisher_id"),
-                    name: row.get("publisher_name"),
-                    banned: row.get("publisher_banned"),
-                },
-                verifier: DatabasePlayer {
-                    id: row.get("verifier_id"),
-                    name: row.get("verifier_name"),
-                    banned: row.get("verifier_banned"),
-                },
-                level_id: row.get::<Option<i64>, _>("level_id").map(|id| id as u64),
-            })
-        }
+            .bind(self.list)
+    }
 
-        Ok(demons)
+    fn from_row(row: &PgRow) -> Result<Demon, sqlx::Error> {
+        demon_from_row(row)
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct DemonPositionPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "before")]
@@ -160,32 +150,53 @@ pub struct DemonPositionPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "requirement__lt")]
     requirement_lt: Option<i16>,
+
+    /// The list to paginate. Positions are only meaningful within a single list, so this defaults to
+    /// the default list
+    #[serde(default, deserialize_with = "non_nullable")]
+    list: Option<i32>,
+
+    /// If set, the list is reconstructed from the audit log as it was at this point in time. All
+    /// other filters then apply to the demons as they were back then.
+    #[serde(default, deserialize_with = "non_nullable_query_timestamp")]
+    at: Option<DateTime<FixedOffset>>,
 }
 
-impl DemonPositionPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Demon>> {
-        if let Some(limit) = self.limit {
-            if !(1..=100).contains(&limit) {
-                return Err(CoreError::InvalidPaginationLimit.into());
-            }
+impl Paginator for DemonPositionPagination {
+    type Error = DemonlistError;
+    type Item = Demon;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_position.map(i64::from),
+            after: self.after_position.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
         }
+    }
 
-        if let (Some(after), Some(before)) = (self.before_position, self.after_position) {
-            if after < before {
-                return Err(CoreError::AfterSmallerBefore.into());
-            }
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        DemonPositionPagination {
+            before_position: clamp_i16(parameters.before),
+            after_position: clamp_i16(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
         }
+    }
 
-        let order = if self.after_position.is_none() && self.before_position.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    fn pagination_key(item: &Demon) -> i64 {
+        item.base.position as i64
+    }
 
-        let query = format!(include_str!("../../sql/paginate_demons_by_position.sql"), order);
+    fn query(&self, order: &str) -> String {
+        match self.at {
+            Some(_) => format!(include_str!("../../sql/paginate_demons_by_position_at.sql"), order),
+            None => format!(include_str!("../../sql/paginate_demons_by_position.sql"), order),
+        }
+    }
 
-        // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&query)
+    // FIXME(sqlx) once CITEXT is supported
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        let query = query
             .bind(self.before_position)
             .bind(self.after_position)
             .bind(self.name.as_deref())
@@ -197,39 +208,41 @@ impl DemonPositionPagination {
             .bind(self.publisher_id)
             .bind(self.publisher_name.as_deref())
             .bind(self.name_contains.as_deref())
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
-
-        let mut demons = Vec::new();
-
-        while let Some(row) = stream.next().await {
-            let row = row?;
-
-            let video: Option<String> = row.get("video");
-
-            demons.push(Demon {
-                base: MinimalDemon {
-                    id: row.get("demon_id"),
-                    name: row.get("demon_name"),
-                    position: row.get("position"),
-                },
-                requirement: row.get("requirement"),
-                video,
-                thumbnail: row.get("thumbnail"),
-                publisher: DatabasePlayer {
-                    id: row.get("publisher_id"),
-                    name: row.get("publisher_name"),
-                    banned: row.get("publisher_banned"),
-                },
-                verifier: DatabasePlayer {
-                    id: row.get("verifier_id"),
-                    name: row.get("verifier_name"),
-                    banned: row.get("verifier_banned"),
-                },
-                level_id: row.get::<Option<i64>, _>("level_id").map(|id| id as u64),
-            })
+            .bind(self.list);
+
+        match self.at {
+            Some(at) => query.bind(at.naive_utc()),
+            None => query,
         }
+    }
 
-        Ok(demons)
+    fn from_row(row: &PgRow) -> Result<Demon, sqlx::Error> {
+        demon_from_row(row)
     }
 }
+
+fn demon_from_row(row: &PgRow) -> Result<Demon, sqlx::Error> {
+    Ok(Demon {
+        base: MinimalDemon {
+            id: row.try_get("demon_id")?,
+            name: row.try_get("demon_name")?,
+            position: row.try_get("position")?,
+        },
+        list: row.try_get("list")?,
+        requirement: row.try_get("requirement")?,
+        record_kind: RecordKind::from_sql(row.try_get("record_kind")?)?,
+        video: row.try_get("video")?,
+        thumbnail: row.try_get("thumbnail")?,
+        publisher: DatabasePlayer {
+            id: row.try_get("publisher_id")?,
+            name: row.try_get("publisher_name")?,
+            banned: row.try_get("publisher_banned")?,
+        },
+        verifier: DatabasePlayer {
+            id: row.try_get("verifier_id")?,
+            name: row.try_get("verifier_name")?,
+            banned: row.try_get("verifier_banned")?,
+        },
+        level_id: row.try_get::<Option<i64>, _>("level_id")?.map(|id| id as u64),
+    })
+}
@@ -5,10 +5,11 @@ use crate::{
 };
 use log::{debug, info, warn};
 use pointercrate_core::util::{non_nullable, nullable};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Deserialize, Debug, Default)]
+#[derive(Deserialize, JsonSchema, Debug, Default)]
 pub struct PatchDemon {
     #[serde(default, deserialize_with = "non_nullable")]
     pub name: Option<String>,
@@ -55,7 +56,7 @@ impl Demon {
         // duplicate names are OK nowadays
 
         if let Some(position) = patch.position {
-            self.base.mv(position, connection).await?;
+            self.mv(position, connection).await?;
         }
 
         if let Some(name) = patch.name {
@@ -180,19 +181,21 @@ impl MinimalDemon {
 
         Ok(())
     }
+}
 
-    /// Moves this demon to the specified position
+impl Demon {
+    /// Moves this demon to the specified position on its list
     ///
     /// Validates that `to` is `> 0` and less than or equal to the currently highest position on the
     /// list (to preven "holes")
     pub async fn mv(&mut self, to: i16, connection: &mut PgConnection) -> Result<()> {
-        let maximal_position = Demon::max_position(connection).await?;
+        let maximal_position = Demon::max_position(self.list, connection).await?;
 
         if to > maximal_position || to < 1 {
             return Err(DemonlistError::InvalidPosition { maximal: maximal_position });
         }
 
-        if to == self.position {
+        if to == self.base.position {
             warn!("No-op move of demon {}", self);
 
             return Ok(());
@@ -201,33 +204,35 @@ impl MinimalDemon {
         // FIXME: Temporarily move the demon somewhere else because otherwise the unique constraints
         // complains. I actually dont know why, its DEFERRABLE INITIALLY IMMEDIATE (whatever the
         // fuck that means, it made it work in the python version)
-        sqlx::query!("UPDATE demons SET position = -1 WHERE id = $1", self.id)
+        sqlx::query!("UPDATE demons SET position = -1 WHERE id = $1", self.base.id)
             .execute(&mut *connection)
             .await?;
 
-        if to > self.position {
+        if to > self.base.position {
             debug!(
                 "Target position {} is greater than current position {}, shifting demons towards lower position",
-                to, self.position
+                to, self.base.position
             );
 
             sqlx::query!(
-                "UPDATE demons SET position = position - 1 WHERE position > $1 AND position <= $2",
-                self.position,
+                "UPDATE demons SET position = position - 1 WHERE list = $1 AND position > $2 AND position <= $3",
+                self.list,
+                self.base.position,
                 to
             )
             .execute(&mut *connection)
             .await?;
-        } else if to < self.position {
+        } else if to < self.base.position {
             debug!(
                 "Target position {} is lesser than current position {}, shifting demons towards higher position",
-                to, self.position
+                to, self.base.position
             );
 
             sqlx::query!(
-                "UPDATE demons SET position = position + 1 WHERE position >= $1 AND position < $2",
+                "UPDATE demons SET position = position + 1 WHERE list = $1 AND position >= $2 AND position < $3",
+                self.list,
                 to,
-                self.position
+                self.base.position
             )
             .execute(&mut *connection)
             .await?;
@@ -235,13 +240,13 @@ impl MinimalDemon {
 
         debug!("Performing actual move to position {}", to);
 
-        sqlx::query!("UPDATE demons SET position = $2 WHERE id = $1", self.id, to)
+        sqlx::query!("UPDATE demons SET position = $2 WHERE id = $1", self.base.id, to)
             .execute(connection)
             .await?;
 
-        info!("Moved demon {} from {} to {} successfully!", self, self.position, to);
+        info!("Moved demon {} from {} to {} successfully!", self, self.base.position, to);
 
-        self.position = to;
+        self.base.position = to;
 
         Ok(())
     }
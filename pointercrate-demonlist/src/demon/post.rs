@@ -1,14 +1,16 @@
 use crate::{
     creator::Creator,
-    demon::{Demon, FullDemon, MinimalDemon},
+    demon::{Demon, FullDemon, MinimalDemon, RecordKind},
     error::Result,
+    list::List,
     player::DatabasePlayer,
 };
 use log::info;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
 pub struct PostDemon {
     name: String,
     position: i16,
@@ -17,6 +19,15 @@ pub struct PostDemon {
     publisher: String,
     creators: Vec<String>,
     video: Option<String>,
+
+    /// The id of the list to add the demon to. Defaults to the default list
+    #[serde(default)]
+    list: Option<i32>,
+
+    /// Whether records on this demon are ranked by percentage or by completion time. Defaults to
+    /// percentage
+    #[serde(default)]
+    record_kind: RecordKind,
 }
 
 impl FullDemon {
@@ -31,19 +42,23 @@ impl FullDemon {
             None => None,
         };
 
-        Demon::validate_position(data.position, connection).await?;
+        let list = List::by_id_or_default(data.list, connection).await?;
+
+        Demon::validate_position(list.id, data.position, connection).await?;
 
         let publisher = DatabasePlayer::by_name_or_create(data.publisher.as_ref(), connection).await?;
         let verifier = DatabasePlayer::by_name_or_create(data.verifier.as_ref(), connection).await?;
 
-        Demon::shift_down(data.position, connection).await?;
+        Demon::shift_down(list.id, data.position, connection).await?;
 
         let created = sqlx::query!(
-            "INSERT INTO demons (name, position, requirement, video, verifier, publisher) VALUES ($1::text,$2,$3,$4::text,$5,$6) \
-             RETURNING id, thumbnail",
+            "INSERT INTO demons (name, position, list, requirement, record_kind, video, verifier, publisher) VALUES \
+             ($1::text,$2,$3,$4,$5::text::record_kind,$6::text,$7,$8) RETURNING id, thumbnail",
             data.name.to_string(),
             data.position,
+            list.id,
             data.requirement,
+            data.record_kind.to_sql(),
             video.as_ref(),
             verifier.id,
             publisher.id
@@ -57,7 +72,9 @@ impl FullDemon {
                 position: data.position,
                 name: data.name,
             },
+            list: list.id,
             requirement: data.requirement,
+            record_kind: data.record_kind,
             video,
             thumbnail: created.thumbnail,
             publisher,
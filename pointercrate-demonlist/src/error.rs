@@ -1,4 +1,4 @@
-use crate::{demon::MinimalDemon, record::RecordStatus};
+use crate::{demon::MinimalDemon, player::name::SimilarPlayer, record::RecordStatus};
 use derive_more::Display;
 
 use pointercrate_core::error::{CoreError, PointercrateError};
@@ -47,6 +47,9 @@ pub enum DemonlistError {
     #[display(fmt = "Player with id {} is no creator of demon with id {}", player_id, demon_id)]
     CreatorNotFound { demon_id: i32, player_id: i32 },
 
+    #[display(fmt = "Player with id {} is no partner on record with id {}", player_id, record_id)]
+    PartnerNotFound { record_id: i32, player_id: i32 },
+
     #[display(fmt = "No nationality with iso code {} found", iso_code)]
     NationalityNotFound { iso_code: String },
 
@@ -74,9 +77,45 @@ pub enum DemonlistError {
     #[display(fmt = "No claim by user {} on player {} found", member_id, player_id)]
     ClaimNotFound { member_id: i32, player_id: i32 },
 
+    #[display(fmt = "No scoring formula with id {} found", formula_id)]
+    ScoringFormulaNotFound { formula_id: i32 },
+
+    #[display(fmt = "No webhook with id {} found", webhook_id)]
+    WebhookNotFound { webhook_id: i32 },
+
+    #[display(fmt = "No webhook delivery with id {} found", delivery_id)]
+    WebhookDeliveryNotFound { delivery_id: i32 },
+
+    #[display(fmt = "No list with id {} found", list_id)]
+    ListNotFound { list_id: i32 },
+
+    #[display(fmt = "No list '{}' found", slug)]
+    ListNotFoundSlug { slug: String },
+
+    #[display(fmt = "No list has been set up as the default list")]
+    NoDefaultList,
+
+    #[display(fmt = "No rejection reason with id {} found", reason_id)]
+    RejectionReasonNotFound { reason_id: i32 },
+
+    #[display(fmt = "No video metadata has been fetched for record {}", record_id)]
+    VideoMetadataNotFound { record_id: i32 },
+
+    #[display(fmt = "Record {} is not currently assigned to you for review", record_id)]
+    ReviewNotFound { record_id: i32 },
+
+    #[display(fmt = "No submissions are waiting for review")]
+    ReviewQueueEmpty,
+
+    #[display(fmt = "No player merge with id {} found", merge_id)]
+    PlayerMergeNotFound { merge_id: i32 },
+
     #[display(fmt = "This player is already registered as a creator on this demon")]
     CreatorExists,
 
+    #[display(fmt = "This player is already credited with this record")]
+    PartnerExists,
+
     /// `409 CONFLICT` variant
     ///
     /// Error Code `40906`
@@ -96,6 +135,81 @@ pub enum DemonlistError {
     )]
     ConflictingClaims { player1: String, player2: String },
 
+    /// `409 CONFLICT` variant
+    ///
+    /// Error Code `40912`
+    #[display(fmt = "Another list already uses this slug")]
+    ListSlugTaken,
+
+    /// `409 CONFLICT` variant
+    ///
+    /// Error Code `40914`
+    #[display(fmt = "Another rejection reason already uses this name")]
+    RejectionReasonExists,
+
+    /// `409 CONFLICT` variant returned if attempted to modify a submission that is currently
+    /// assigned to another list helper via the review queue
+    ///
+    /// Error Code `40915`
+    #[display(fmt = "This record is currently being reviewed by another list helper (member #{})", reviewer)]
+    RecordUnderReview { reviewer: i32 },
+
+    /// `409 CONFLICT` variant
+    ///
+    /// Error Code `40916`
+    #[display(fmt = "This merge has already been reverted")]
+    MergeAlreadyReverted,
+
+    /// `409 CONFLICT` variant returned if a merge cannot be reverted because another player took
+    /// the name of one of the merged players in the meantime
+    ///
+    /// Error Code `40917`
+    #[display(fmt = "The name '{}' is now used by a different player", player_name)]
+    MergeNameTaken { player_name: String },
+
+    /// `409 CONFLICT` variant returned if a submission would create a new player whose name looks
+    /// (nearly) identical to the name of an existing player
+    ///
+    /// Error Code `40918`
+    #[display(
+        fmt = "The player name '{}' looks like the name of an existing player. If this is a different player, ask a list helper to \
+               submit the record",
+        player_name
+    )]
+    SimilarPlayerExists {
+        player_name: String,
+
+        /// The existing players with similar names, see
+        /// [`DatabasePlayer::similar_to`](crate::player::DatabasePlayer::similar_to)
+        candidates: Vec<SimilarPlayer>,
+    },
+
+    /// `409 CONFLICT` variant returned if a player would be credited with two approved records on
+    /// the same demon, e.g. because they are added as partner to a record on a demon they already
+    /// have a record on
+    ///
+    /// Error Code `40919`
+    #[display(
+        fmt = "Player #{} is already credited with the approved record #{} on this demon",
+        player_id,
+        record_id
+    )]
+    AlreadyCredited { player_id: i32, record_id: i32 },
+
+    /// `409 CONFLICT` variant returned if a merge cannot be reverted because the surviving player
+    /// was involved in a later merge, which needs to be reverted first
+    ///
+    /// Error Code `40920`
+    #[display(fmt = "Player merge #{} needs to be reverted first", merge_id)]
+    LaterMergeExists { merge_id: i32 },
+
+    /// `409 CONFLICT` variant returned if a merge cannot be reverted because records or players it
+    /// affected were modified after the merge
+    ///
+    /// Error Code `40921`
+    #[display(fmt = "The merged players or their records have been modified since the merge, so it can no longer be reverted")]
+    MergeOutdated,
+
     /// `422 UNPROCESSABLE ENTITY` variant returned if attempted to create a demon with a record
     /// requirements outside of [0, 100]
     ///
@@ -157,7 +271,9 @@ pub enum DemonlistError {
     /// `422 UNPROCESSABLE ENTITY` variant
     ///
     /// Error Code `42224`
-    #[display(fmt = "The given video host is not supported. Supported are 'youtube', 'vimeo', 'everyplay', 'twitch' and 'bilibili'")]
+    #[display(
+        fmt = "The given video host is not supported. Supported are 'youtube', 'twitch', 'vimeo', 'bilibili', 'streamable' and 'medal'"
+    )]
     UnsupportedVideoHost,
 
     /// `422 UNPROCESSABLE ENTITY` variant
@@ -189,6 +305,92 @@ pub enum DemonlistError {
     /// Error Code `42233`
     #[display(fmt = "Raw footage needs to be a valid URL")]
     MalformedRawUrl,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if attempted to create a scoring formula without
+    /// brackets, with overlapping brackets, or with non-finite or non-positive parameters
+    ///
+    /// Error Code `42234`
+    #[display(fmt = "Scoring formulas need at least one bracket, brackets mustn't overlap and all parameters must be finite")]
+    InvalidScoringFormula,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42237`
+    #[display(fmt = "Webhooks need to subscribe to at least one event")]
+    NoWebhookEvents,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42238`
+    #[display(fmt = "The webhook URL needs to be a valid, absolute URL")]
+    MalformedWebhookUrl,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if attempted to give a list an empty main list, or
+    /// an extended list smaller than its main list
+    ///
+    /// Error Code `42239`
+    #[display(fmt = "The list size must be positive, and the extended list size at least as large as the list size")]
+    InvalidListSize,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42240`
+    #[display(fmt = "List slugs must consist of lowercase letters and digits, with words separated by single dashes")]
+    MalformedListSlug,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a player is credited with a record more than
+    /// once, for instance by being both the record's player and one of its partners
+    ///
+    /// Error Code `42243`
+    #[display(fmt = "Every player can only be credited with a record once")]
+    DuplicatePartner,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record on a demon ranked by completion time
+    /// does not specify a positive completion time, or specifies progress other than 100%
+    ///
+    /// Error Code `42241`
+    #[display(
+        fmt = "Records on this demon are ranked by completion time, and thus need to specify a positive completion time (in milliseconds)"
+    )]
+    InvalidCompletionTime,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if a record on a demon ranked by percentage
+    /// specifies a completion time
+    ///
+    /// Error Code `42242`
+    #[display(fmt = "Records on this demon are ranked by percentage, and thus cannot specify a completion time")]
+    UnexpectedCompletionTime,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if attempted to change a record's status in a way
+    /// not allowed by the configured status transitions
+    ///
+    /// Error Code `42244`
+    #[display(fmt = "Records cannot be moved from '{}' to '{}'", from, to)]
+    InvalidStatusTransition { from: RecordStatus, to: RecordStatus },
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42245`
+    #[display(fmt = "Only rejected records can have a rejection reason")]
+    RejectionReasonOnUnrejected,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42246`
+    #[display(fmt = "Rejection reasons need a non-empty name")]
+    MalformedRejectionReason,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42247`
+    #[display(fmt = "A player cannot be merged with itself")]
+    SelfMerge,
+
+    /// `422 UNPROCESSABLE ENTITY` variant
+    ///
+    /// Error Code `42248`
+    #[display(fmt = "The start of a list diff cannot be after its end")]
+    InvalidDiffWindow,
 }
 
 impl std::error::Error for DemonlistError {}
@@ -203,6 +405,8 @@ impl PointercrateError for DemonlistError {
             NoteNotFound { .. } => 40401,
             CreatorNotFound { .. } => 40401,
             CreatorExists => 40905,
+            PartnerNotFound { .. } => 40401,
+            PartnerExists => 40913,
             InvalidRequirement => 42212,
             InvalidPosition { .. } => 42213,
             NoteEmpty => 42230,
@@ -220,9 +424,29 @@ impl PointercrateError for DemonlistError {
             DemonNotFoundPosition { .. } => 40401,
             RecordNotFound { .. } => 40401,
             ClaimNotFound { .. } => 40401,
+            ScoringFormulaNotFound { .. } => 40401,
+            WebhookNotFound { .. } => 40401,
+            WebhookDeliveryNotFound { .. } => 40401,
+            ListNotFound { .. } => 40401,
+            ListNotFoundSlug { .. } => 40401,
+            NoDefaultList => 40401,
+            RejectionReasonNotFound { .. } => 40401,
+            VideoMetadataNotFound { .. } => 40401,
+            ReviewNotFound { .. } => 40401,
+            ReviewQueueEmpty => 40401,
+            PlayerMergeNotFound { .. } => 40401,
             DuplicateVideo { .. } => 40906,
             NoNationSet => 40907,
             ConflictingClaims { .. } => 40908,
+            ListSlugTaken => 40912,
+            RejectionReasonExists => 40914,
+            RecordUnderReview { .. } => 40915,
+            MergeAlreadyReverted => 40916,
+            MergeNameTaken { .. } => 40917,
+            SimilarPlayerExists { .. } => 40918,
+            AlreadyCredited { .. } => 40919,
+            LaterMergeExists { .. } => 40920,
+            MergeOutdated => 40921,
             InvalidProgress { .. } => 42215,
             SubmissionExists { .. } => 42217,
             PlayerBanned => 42218,
@@ -233,8 +457,138 @@ impl PointercrateError for DemonlistError {
             AlreadyClaimed => 42231,
             RawRequired => 42232,
             MalformedRawUrl => 42233,
+            InvalidScoringFormula => 42234,
+            NoWebhookEvents => 42237,
+            MalformedWebhookUrl => 42238,
+            InvalidListSize => 42239,
+            MalformedListSlug => 42240,
+            InvalidCompletionTime => 42241,
+            UnexpectedCompletionTime => 42242,
+            DuplicatePartner => 42243,
+            InvalidStatusTransition { .. } => 42244,
+            RejectionReasonOnUnrejected => 42245,
+            MalformedRejectionReason => 42246,
+            SelfMerge => 42247,
+            InvalidDiffWindow => 42248,
+        }
+    }
+
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            DemonlistError::Core(core) => core.headers(),
+            _ => Vec::new(),
         }
     }
+
+    fn catalogue() -> Vec<Self> {
+        use DemonlistError::*;
+
+        vec![
+            MalformedVideoUrl,
+            BannedFromSubmissions,
+            ClaimUnverified,
+            VpsDetected,
+            NoThirdPartySubmissions,
+            SubmitterNotFound { id: 1 },
+            NoteNotFound { note_id: 1, record_id: 1 },
+            CreatorNotFound { demon_id: 1, player_id: 1 },
+            PartnerNotFound {
+                record_id: 1,
+                player_id: 1,
+            },
+            NationalityNotFound {
+                iso_code: "XX".to_string(),
+            },
+            SubdivisionNotFound {
+                subdivision_code: "XX".to_string(),
+                nation_code: "XX".to_string(),
+            },
+            PlayerNotFound { player_id: 1 },
+            PlayerNotFoundName {
+                player_name: "stardust1971".to_string(),
+            },
+            DemonNotFound { demon_id: 1 },
+            DemonNotFoundName {
+                demon_name: "Bloodbath".to_string(),
+            },
+            DemonNotFoundPosition { demon_position: 1 },
+            RecordNotFound { record_id: 1 },
+            ClaimNotFound {
+                member_id: 1,
+                player_id: 1,
+            },
+            ScoringFormulaNotFound { formula_id: 1 },
+            WebhookNotFound { webhook_id: 1 },
+            WebhookDeliveryNotFound { delivery_id: 1 },
+            ListNotFound { list_id: 1 },
+            ListNotFoundSlug {
+                slug: "demonlist".to_string(),
+            },
+            NoDefaultList,
+            RejectionReasonNotFound { reason_id: 1 },
+            VideoMetadataNotFound { record_id: 1 },
+            ReviewNotFound { record_id: 1 },
+            ReviewQueueEmpty,
+            PlayerMergeNotFound { merge_id: 1 },
+            CreatorExists,
+            DuplicateVideo { id: 1 },
+            NoNationSet,
+            ConflictingClaims {
+                player1: "stardust1971".to_string(),
+                player2: "stardust1972".to_string(),
+            },
+            ListSlugTaken,
+            PartnerExists,
+            RejectionReasonExists,
+            RecordUnderReview { reviewer: 1 },
+            MergeAlreadyReverted,
+            MergeNameTaken {
+                player_name: "stardust1971".to_string(),
+            },
+            SimilarPlayerExists {
+                player_name: "stardust1971".to_string(),
+                candidates: Vec::new(),
+            },
+            AlreadyCredited {
+                player_id: 1,
+                record_id: 1,
+            },
+            LaterMergeExists { merge_id: 1 },
+            MergeOutdated,
+            InvalidRequirement,
+            InvalidPosition { maximal: 150 },
+            InvalidProgress { requirement: 50 },
+            SubmissionExists {
+                status: RecordStatus::Approved,
+                existing: 1,
+            },
+            PlayerBanned,
+            SubmitLegacy,
+            Non100Extended,
+            UnsupportedVideoHost,
+            DemonNameNotUnique { demons: Vec::new() },
+            NoteEmpty,
+            AlreadyClaimed,
+            RawRequired,
+            MalformedRawUrl,
+            InvalidScoringFormula,
+            NoWebhookEvents,
+            MalformedWebhookUrl,
+            InvalidListSize,
+            MalformedListSlug,
+            InvalidCompletionTime,
+            UnexpectedCompletionTime,
+            DuplicatePartner,
+            InvalidStatusTransition {
+                from: RecordStatus::Approved,
+                to: RecordStatus::NeedsInfo,
+            },
+            RejectionReasonOnUnrejected,
+            MalformedRejectionReason,
+            SelfMerge,
+            InvalidDiffWindow,
+        ]
+    }
 }
 
 impl From<CoreError> for DemonlistError {
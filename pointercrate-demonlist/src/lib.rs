@@ -2,15 +2,20 @@ use pointercrate_core::permission::Permission;
 
 #[macro_use]
 pub mod demon;
-pub mod config;
 pub mod creator;
 pub mod error;
+pub mod link_health;
+pub mod list;
 pub mod nationality;
 pub mod player;
+pub mod ranking_history;
 pub mod record;
+pub mod review;
+pub mod scoring;
 pub mod submitter;
 mod video;
+pub mod webhook;
 
-pub const LIST_HELPER: Permission = Permission::new("List Helper", 0x2);
-pub const LIST_MODERATOR: Permission = Permission::new("List Moderator", 0x4);
-pub const LIST_ADMINISTRATOR: Permission = Permission::new("List Administrator", 0x8);
+pub const LIST_HELPER: Permission = Permission::new("List Helper");
+pub const LIST_MODERATOR: Permission = Permission::new("List Moderator");
+pub const LIST_ADMINISTRATOR: Permission = Permission::new("List Administrator");
@@ -0,0 +1,137 @@
+//! Module for keeping track of dead links to record and demon videos
+//!
+//! Videos get deleted or made private long after the record they prove was approved. A periodic
+//! background job walks the videos of all approved records and of all demons, checks whether they
+//! are still available, and records the outcome here, so that list helpers can ask players for
+//! re-uploads of [`BrokenLink`]s.
+//!
+//! The outcome is stored per link rather than per record or demon, so a video shared by multiple
+//! records is only checked once, and changing a video discards the outcome for the old one.
+
+use crate::error::Result;
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::time::Duration;
+
+/// How long the outcome of checking a link is trusted before the link is checked again
+pub const RECHECK_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkHealth {
+    Healthy,
+
+    /// The video's host reports that the video doesn't exist (anymore), or isn't public
+    Broken,
+
+    /// The video's host could not be reached yet (e.g. because of an outage)
+    Unknown,
+}
+
+impl LinkHealth {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            LinkHealth::Healthy => "HEALTHY",
+            LinkHealth::Broken => "BROKEN",
+            LinkHealth::Unknown => "UNKNOWN",
+        }
+    }
+
+    pub fn from_sql(health: &str) -> Self {
+        match health {
+            "HEALTHY" => LinkHealth::Healthy,
+            "BROKEN" => LinkHealth::Broken,
+            _ => LinkHealth::Unknown,
+        }
+    }
+}
+
+/// A video link whose last check found it to be broken
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Eq, Clone)]
+pub struct BrokenLink {
+    pub video: String,
+    pub last_checked: NaiveDateTime,
+
+    /// The ids of all approved records using this video
+    pub records: Vec<i32>,
+
+    /// The ids of all demons using this video as their showcase video
+    pub demons: Vec<i32>,
+}
+
+impl BrokenLink {
+    /// Gets all broken links still used by an approved record or a demon, least recently checked
+    /// first
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<BrokenLink>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT video AS "video!", last_checked AS "last_checked!", records AS "records!", demons AS "demons!" FROM (
+                SELECT video, last_checked,
+                       ARRAY(SELECT id FROM records WHERE records.video = video_link_checks.video AND status_ = 'APPROVED' ORDER BY id) AS records,
+                       ARRAY(SELECT id FROM demons WHERE demons.video = video_link_checks.video ORDER BY id) AS demons
+                FROM video_link_checks
+                WHERE health = 'BROKEN'
+            ) AS broken
+            WHERE cardinality(records) > 0 OR cardinality(demons) > 0
+            ORDER BY last_checked"#
+        )
+        .fetch(connection);
+
+        let mut links = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            links.push(BrokenLink {
+                video: row.video,
+                last_checked: row.last_checked,
+                records: row.records,
+                demons: row.demons,
+            })
+        }
+
+        Ok(links)
+    }
+}
+
+/// Gets up to `limit` video links of approved records and demons that haven't been checked within
+/// the last [`RECHECK_INTERVAL`], links that were never checked first
+pub async fn due_for_check(limit: i64, connection: &mut PgConnection) -> Result<Vec<String>> {
+    let rows = sqlx::query!(
+        r#"SELECT links.video AS "video!" FROM (
+            SELECT video::text FROM records WHERE status_ = 'APPROVED' AND video IS NOT NULL
+            UNION
+            SELECT video::text FROM demons WHERE video IS NOT NULL
+        ) AS links
+        LEFT OUTER JOIN video_link_checks ON video_link_checks.video = links.video
+        WHERE video_link_checks.last_checked IS NULL OR video_link_checks.last_checked < NOW() AT TIME ZONE 'utc' - make_interval(secs => $1)
+        ORDER BY video_link_checks.last_checked NULLS FIRST
+        LIMIT $2"#,
+        RECHECK_INTERVAL.as_secs_f64(),
+        limit
+    )
+    .fetch_all(connection)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.video).collect())
+}
+
+/// Records the outcome of checking the given video link
+///
+/// An [`LinkHealth::Unknown`] outcome only bumps the time of the last check, keeping the outcome
+/// of the previous check (if any).
+pub async fn record_check(video: &str, health: LinkHealth, connection: &mut PgConnection) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO video_link_checks (video, health) VALUES ($1, cast($2::text as link_health)) ON CONFLICT (video) DO UPDATE SET \
+         health = CASE WHEN EXCLUDED.health = 'UNKNOWN' THEN video_link_checks.health ELSE EXCLUDED.health END, last_checked = \
+         EXCLUDED.last_checked",
+        video,
+        health.to_sql()
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::{
+    error::{DemonlistError, Result},
+    list::List,
+};
+use sqlx::{Error, PgConnection};
+
+impl List {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<List> {
+        let row = sqlx::query!(
+            "SELECT id, slug, name, list_size, extended_list_size, scoring_formula, is_default FROM lists WHERE id = $1",
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match row {
+            Ok(row) => Ok(List {
+                id: row.id,
+                slug: row.slug,
+                name: row.name,
+                list_size: row.list_size,
+                extended_list_size: row.extended_list_size,
+                scoring_formula: row.scoring_formula,
+                is_default: row.is_default,
+            }),
+            Err(Error::RowNotFound) => Err(DemonlistError::ListNotFound { list_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn by_slug(slug: &str, connection: &mut PgConnection) -> Result<List> {
+        let row = sqlx::query!("SELECT id FROM lists WHERE slug = $1", slug)
+            .fetch_one(&mut *connection)
+            .await;
+
+        match row {
+            Ok(row) => List::by_id(row.id, connection).await,
+            Err(Error::RowNotFound) => Err(DemonlistError::ListNotFoundSlug { slug: slug.to_string() }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets the list used whenever no list is explicitly requested
+    pub async fn default(connection: &mut PgConnection) -> Result<List> {
+        let row = sqlx::query!("SELECT id FROM lists WHERE is_default")
+            .fetch_one(&mut *connection)
+            .await;
+
+        match row {
+            Ok(row) => List::by_id(row.id, connection).await,
+            Err(Error::RowNotFound) => Err(DemonlistError::NoDefaultList),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets the list with the given id, or the default list if no id is given
+    pub async fn by_id_or_default(id: Option<i32>, connection: &mut PgConnection) -> Result<List> {
+        match id {
+            Some(id) => List::by_id(id, connection).await,
+            None => List::default(connection).await,
+        }
+    }
+
+    /// Gets the list the demon with the given id is on
+    pub async fn of_demon(demon_id: i32, connection: &mut PgConnection) -> Result<List> {
+        let list_id = sqlx::query!("SELECT list FROM demons WHERE id = $1", demon_id)
+            .fetch_one(&mut *connection)
+            .await
+            .map_err(|err| match err {
+                Error::RowNotFound => DemonlistError::DemonNotFound { demon_id },
+                _ => err.into(),
+            })?
+            .list;
+
+        List::by_id(list_id, connection).await
+    }
+
+    /// Gets all lists, with the default list first and all others in order of creation
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<List>> {
+        let ids = sqlx::query!("SELECT id FROM lists ORDER BY is_default DESC, id")
+            .fetch_all(&mut *connection)
+            .await?;
+
+        let mut lists = Vec::new();
+
+        for row in ids {
+            lists.push(List::by_id(row.id, connection).await?);
+        }
+
+        Ok(lists)
+    }
+}
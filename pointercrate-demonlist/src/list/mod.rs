@@ -0,0 +1,89 @@
+//! Module for the lists hosted by a pointercrate instance
+//!
+//! Every demon belongs to exactly one list. Positions are only unique within a list, and each list
+//! has its own main and extended list size, its own scoring formula and its own rankings. Exactly one
+//! list is the default one, which is used whenever a request does not explicitly select a list.
+
+pub use self::{patch::PatchList, post::PostList};
+use crate::error::{DemonlistError, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+mod get;
+mod patch;
+mod post;
+
+#[derive(Debug, Serialize, JsonSchema, Hash, Eq, PartialEq, Clone)]
+pub struct List {
+    pub id: i32,
+
+    /// The identifier of this list used in URLs, such as `demonlist` in `/demonlist/?list=demonlist`
+    pub slug: String,
+
+    pub name: String,
+
+    /// The number of demons on the main list. Only records on these demons can have progress below
+    /// 100%
+    pub list_size: i16,
+
+    /// The number of demons on the main and extended list combined. Demons beyond this position are
+    /// legacy demons, which do not give any points
+    pub extended_list_size: i16,
+
+    /// The id of the [`ScoringFormula`](crate::scoring::ScoringFormula) used for this list's
+    /// rankings
+    pub scoring_formula: i32,
+
+    /// Whether this is the list used if no list is explicitly requested
+    pub is_default: bool,
+}
+
+/// Ensures the given slug consists of lowercase alphanumeric words separated by single dashes
+///
+/// This mirrors the `CHECK` constraint on the `lists` table
+pub(crate) fn validate_slug(slug: &str) -> Result<()> {
+    let valid = !slug.is_empty()
+        && slug
+            .split('-')
+            .all(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+
+    if !valid {
+        return Err(DemonlistError::MalformedListSlug);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_sizes(list_size: i16, extended_list_size: i16) -> Result<()> {
+    if list_size < 1 || extended_list_size < list_size {
+        return Err(DemonlistError::InvalidListSize);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_sizes, validate_slug};
+
+    #[test]
+    fn test_slug_validation() {
+        assert!(validate_slug("demonlist").is_ok());
+        assert!(validate_slug("platformer-list-2").is_ok());
+
+        assert!(validate_slug("").is_err());
+        assert!(validate_slug("Demonlist").is_err());
+        assert!(validate_slug("-demonlist").is_err());
+        assert!(validate_slug("demon--list").is_err());
+        assert!(validate_slug("demon_list").is_err());
+    }
+
+    #[test]
+    fn test_size_validation() {
+        assert!(validate_sizes(75, 150).is_ok());
+        assert!(validate_sizes(10, 10).is_ok());
+
+        assert!(validate_sizes(0, 150).is_err());
+        assert!(validate_sizes(75, 50).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    error::Result,
+    list::{post::ensure_slug_available, validate_sizes, validate_slug, List},
+};
+use log::info;
+use pointercrate_core::util::non_nullable;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PatchList {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub slug: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub name: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub list_size: Option<i16>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub extended_list_size: Option<i16>,
+}
+
+impl List {
+    /// Must run inside a transaction
+    pub async fn apply_patch(mut self, patch: PatchList, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(slug) = patch.slug {
+            validate_slug(&slug)?;
+            ensure_slug_available(&slug, Some(self.id), connection).await?;
+
+            sqlx::query!("UPDATE lists SET slug = $1 WHERE id = $2", slug, self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.slug = slug;
+        }
+
+        if let Some(name) = patch.name {
+            sqlx::query!("UPDATE lists SET name = $1 WHERE id = $2", name, self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.name = name;
+        }
+
+        if patch.list_size.is_some() || patch.extended_list_size.is_some() {
+            let list_size = patch.list_size.unwrap_or(self.list_size);
+            let extended_list_size = patch.extended_list_size.unwrap_or(self.extended_list_size);
+
+            validate_sizes(list_size, extended_list_size)?;
+
+            info!(
+                "Resizing list {} to {} main and {} extended demons",
+                self.slug, list_size, extended_list_size
+            );
+
+            sqlx::query!(
+                "UPDATE lists SET list_size = $1, extended_list_size = $2 WHERE id = $3",
+                list_size,
+                extended_list_size,
+                self.id
+            )
+            .execute(&mut *connection)
+            .await?;
+
+            self.list_size = list_size;
+            self.extended_list_size = extended_list_size;
+        }
+
+        Ok(self)
+    }
+}
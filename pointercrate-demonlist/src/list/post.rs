@@ -0,0 +1,70 @@
+use crate::{
+    error::{DemonlistError, Result},
+    list::{validate_sizes, validate_slug, List},
+    scoring::ScoringFormula,
+};
+use log::info;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PostList {
+    pub slug: String,
+    pub name: String,
+    pub list_size: i16,
+    pub extended_list_size: i16,
+
+    /// The id of the scoring formula to use for the new list. Defaults to the one used by the
+    /// default list
+    #[serde(default)]
+    pub scoring_formula: Option<i32>,
+}
+
+/// Errors out if a list other than the one with id `except` already uses the given slug
+pub(super) async fn ensure_slug_available(slug: &str, except: Option<i32>, connection: &mut PgConnection) -> Result<()> {
+    let taken = sqlx::query!(
+        r#"SELECT EXISTS (SELECT 1 FROM lists WHERE slug = $1 AND id IS DISTINCT FROM $2) AS "taken!""#,
+        slug,
+        except
+    )
+    .fetch_one(connection)
+    .await?
+    .taken;
+
+    if taken {
+        return Err(DemonlistError::ListSlugTaken);
+    }
+
+    Ok(())
+}
+
+impl List {
+    /// Creates a new, empty list
+    pub async fn create_from(data: PostList, connection: &mut PgConnection) -> Result<List> {
+        validate_slug(&data.slug)?;
+        validate_sizes(data.list_size, data.extended_list_size)?;
+        ensure_slug_available(&data.slug, None, connection).await?;
+
+        let scoring_formula = match data.scoring_formula {
+            Some(formula_id) => ScoringFormula::by_id(formula_id, connection).await?.id,
+            None => List::default(connection).await?.scoring_formula,
+        };
+
+        info!("Creating new list {} ({})", data.name, data.slug);
+
+        let id = sqlx::query!(
+            "INSERT INTO lists (slug, name, list_size, extended_list_size, scoring_formula) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+            data.slug,
+            data.name,
+            data.list_size,
+            data.extended_list_size,
+            scoring_formula
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .id;
+
+        List::by_id(id, connection).await
+    }
+}
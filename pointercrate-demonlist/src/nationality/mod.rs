@@ -2,12 +2,17 @@ use crate::demon::MinimalDemon;
 use derive_more::Constructor;
 pub use paginate::{NationalityRankingPagination, RankedNation};
 use pointercrate_core::etag::Taggable;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject},
+    JsonSchema,
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 mod get;
 mod paginate;
 
-#[derive(Debug, PartialEq, Eq, Serialize, Hash, Constructor, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, JsonSchema, Hash, Constructor, Deserialize)]
 pub struct Nationality {
     #[serde(rename = "country_code")]
     pub iso_country_code: String,
@@ -15,7 +20,7 @@ pub struct Nationality {
     pub subdivision: Option<Subdivision>,
 }
 
-#[derive(Debug, Serialize, Hash)]
+#[derive(Debug, Serialize, JsonSchema, Hash)]
 pub struct BestRecord {
     id: i32,
     demon: String,
@@ -24,7 +29,7 @@ pub struct BestRecord {
     players: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Hash)]
+#[derive(Debug, Serialize, JsonSchema, Hash)]
 pub struct MiniDemon {
     id: i32,
     demon: String,
@@ -32,7 +37,7 @@ pub struct MiniDemon {
     player: String,
 }
 
-#[derive(Debug, Serialize, Hash)]
+#[derive(Debug, Serialize, JsonSchema, Hash)]
 pub struct MiniDemonWithPlayers {
     id: i32,
     demon: String,
@@ -40,7 +45,7 @@ pub struct MiniDemonWithPlayers {
     players: Vec<String>,
 }
 
-#[derive(Debug, Hash, Serialize)]
+#[derive(Debug, Hash, Serialize, JsonSchema)]
 pub struct NationalityRecord {
     pub nation: Nationality,
 
@@ -54,7 +59,7 @@ pub struct NationalityRecord {
 
 impl Taggable for NationalityRecord {}
 
-#[derive(Debug, Eq, PartialEq, Clone, Serialize, Hash, Constructor, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, JsonSchema, Hash, Constructor, Deserialize)]
 pub struct Subdivision {
     pub iso_code: String,
     pub name: String,
@@ -109,6 +114,29 @@ impl<'de> Deserialize<'de> for Continent {
     }
 }
 
+impl JsonSchema for Continent {
+    fn schema_name() -> String {
+        "Continent".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(vec![
+                "asia".into(),
+                "europe".into(),
+                "australia".into(),
+                "africa".into(),
+                "north america".into(),
+                "south america".into(),
+                "central america".into(),
+            ]),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 impl Serialize for Continent {
     fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
     where
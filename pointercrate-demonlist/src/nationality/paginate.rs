@@ -1,55 +1,110 @@
 use crate::{
-    error::Result,
+    error::DemonlistError,
     nationality::{Continent, Nationality},
 };
-use futures::StreamExt;
-use pointercrate_core::util::non_nullable;
+use chrono::{DateTime, FixedOffset};
+use pointercrate_core::{
+    pagination::{PaginationParameters, Paginator, DEFAULT_LIMIT},
+    util::{non_nullable, non_nullable_query_timestamp},
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::PgConnection;
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct NationalityRankingPagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "before")]
+    pub before_index: Option<i64>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "after")]
+    pub after_index: Option<i64>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub limit: Option<u8>,
+
     #[serde(default, deserialize_with = "non_nullable")]
     continent: Option<Continent>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     name_contains: Option<String>,
+
+    /// The list whose ranking to return. Defaults to the default list
+    #[serde(default, deserialize_with = "non_nullable")]
+    list: Option<i32>,
+
+    /// If set, the ranking is computed from the list, the approved records, the players and the
+    /// list's scoring formula as they were at this point in time, reconstructed from the audit log
+    #[serde(default, deserialize_with = "non_nullable_query_timestamp")]
+    at: Option<DateTime<FixedOffset>>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, JsonSchema, Debug)]
 pub struct RankedNation {
     pub rank: i64,
     pub score: f64,
     #[serde(flatten)]
     pub nationality: Nationality,
+
+    /// The position of this nation in the unfiltered ranking, used as pagination key
+    #[serde(skip)]
+    pub index: i64,
 }
 
-impl NationalityRankingPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<RankedNation>> {
-        let mut stream = sqlx::query!(
-            r#"SELECT rank as "rank!", score as "score!", nation::text as "nation!", iso_country_code as "iso_country_code!" FROM nations_with_score WHERE (STRPOS(nation, CAST($1::TEXT AS CITEXT)) > 
-             0 OR $1 is NULL) AND (continent = CAST($2::TEXT AS continent) OR $2 IS NULL)"#,
-            self.name_contains,
-            self.continent.map(|c| c.to_sql())
-        )
-        .fetch(connection);
-
-        let mut nations = Vec::new();
-
-        while let Some(row) = stream.next().await {
-            let row = row?;
-
-            nations.push(RankedNation {
-                rank: row.rank,
-                score: row.score,
-                nationality: Nationality {
-                    iso_country_code: row.iso_country_code,
-                    nation: row.nation,
-                    subdivision: None,
-                },
-            })
+impl Paginator for NationalityRankingPagination {
+    type Error = DemonlistError;
+    type Item = RankedNation;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_index,
+            after: self.after_index,
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
+        }
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        NationalityRankingPagination {
+            before_index: parameters.before,
+            after_index: parameters.after,
+            limit: Some(parameters.limit),
+            ..self.clone()
         }
+    }
+
+    fn pagination_key(item: &RankedNation) -> i64 {
+        item.index
+    }
+
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../sql/paginate_nation_ranking.sql"), order)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.before_index)
+            .bind(self.after_index)
+            .bind(self.name_contains.as_deref())
+            .bind(self.continent.as_ref().map(|c| c.to_sql()))
+            .bind(self.list)
+            .bind(self.at.map(|at| at.naive_utc()))
+    }
 
-        Ok(nations)
+    fn from_row(row: &PgRow) -> Result<RankedNation, sqlx::Error> {
+        Ok(RankedNation {
+            rank: row.try_get("rank")?,
+            score: row.try_get("score")?,
+            nationality: Nationality {
+                iso_country_code: row.try_get("iso_country_code")?,
+                nation: row.try_get("nation")?,
+                subdivision: None,
+            },
+            index: row.try_get("index")?,
+        })
     }
 }
@@ -30,6 +30,20 @@ impl PlayerClaim {
         }
     }
 
+    /// Whether the given user holds a verified claim on any of the players credited with the given
+    /// record, meaning either its player or one of its partners
+    pub async fn verified_claim_on_record(member_id: i32, record_id: i32, connection: &mut PgConnection) -> Result<bool> {
+        Ok(sqlx::query!(
+            r#"SELECT EXISTS (SELECT 1 FROM player_claims WHERE member_id = $1 AND verified AND (player_id = (SELECT player FROM records
+             WHERE id = $2) OR player_id IN (SELECT player FROM record_partners WHERE record = $2))) AS "exists!""#,
+            member_id,
+            record_id
+        )
+        .fetch_one(connection)
+        .await?
+        .exists)
+    }
+
     pub async fn by_user(user_id: i32, connection: &mut PgConnection) -> Result<Option<ClaimBy>> {
         match sqlx::query!(
             r#"SELECT verified, lock_submissions, player_id, players.name::text as "name!", players.banned FROM player_claims INNER JOIN players ON player_id=players.id
@@ -1,5 +1,6 @@
 pub use paginate::{ListedClaim, PlayerClaimPagination};
 pub use patch::PatchPlayerClaim;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 mod delete;
@@ -8,7 +9,7 @@ mod paginate;
 mod patch;
 mod put;
 
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Eq, PartialEq)]
 pub struct PlayerClaim {
     pub user_id: i32,
     pub player_id: i32,
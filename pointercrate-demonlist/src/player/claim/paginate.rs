@@ -1,10 +1,18 @@
-use crate::error::Result;
-use futures::StreamExt;
-use pointercrate_core::{audit::NamedId, error::CoreError, util::non_nullable};
+use crate::error::DemonlistError;
+use pointercrate_core::{
+    audit::NamedId,
+    pagination::{clamp_i32, PaginationParameters, Paginator, DEFAULT_LIMIT},
+    util::non_nullable,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone)]
 pub struct PlayerClaimPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "before")]
@@ -24,7 +32,7 @@ pub struct PlayerClaimPagination {
     verified: Option<bool>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 pub struct ListedClaim {
     #[serde(skip)]
     pub id: i32,
@@ -33,64 +41,55 @@ pub struct ListedClaim {
     verified: bool,
 }
 
-impl ListedClaim {
-    pub async fn extremal_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(id) AS "max_id!: i32", MIN(id) AS "min_id!: i32" FROM player_claims"#)
-            .fetch_one(connection)
-            .await?; // FIXME: crashes on empty table
-        Ok((row.max_id, row.min_id))
-    }
-}
+impl Paginator for PlayerClaimPagination {
+    type Error = DemonlistError;
+    type Item = ListedClaim;
 
-impl PlayerClaimPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<ListedClaim>> {
-        if let Some(limit) = self.limit {
-            if !(1..=100).contains(&limit) {
-                return Err(CoreError::InvalidPaginationLimit.into());
-            }
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
         }
+    }
 
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(CoreError::AfterSmallerBefore.into());
-            }
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        PlayerClaimPagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
         }
+    }
 
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    fn pagination_key(item: &ListedClaim) -> i64 {
+        item.id as i64
+    }
 
-        let query = format!(include_str!("../../../sql/paginate_claims.sql"), order);
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../../sql/paginate_claims.sql"), order)
+    }
 
-        let mut stream = sqlx::query(&query)
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
             .bind(self.before_id)
             .bind(self.after_id)
             .bind(self.any_name_contains.as_ref())
             .bind(self.verified)
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
-
-        let mut claims = Vec::new();
-
-        while let Some(row) = stream.next().await {
-            let row = row?;
-
-            claims.push(ListedClaim {
-                id: row.get("id"),
-                user: NamedId {
-                    id: row.get("mid"),
-                    name: Some(row.get("mname")),
-                },
-                player: NamedId {
-                    id: row.get("pid"),
-                    name: Some(row.get("pname")),
-                },
-                verified: row.get("verified"),
-            })
-        }
+    }
 
-        Ok(claims)
+    fn from_row(row: &PgRow) -> Result<ListedClaim, sqlx::Error> {
+        Ok(ListedClaim {
+            id: row.try_get("id")?,
+            user: NamedId {
+                id: row.try_get("mid")?,
+                name: Some(row.try_get("mname")?),
+            },
+            player: NamedId {
+                id: row.try_get("pid")?,
+                name: Some(row.try_get("pname")?),
+            },
+            verified: row.try_get("verified")?,
+        })
     }
 }
@@ -1,8 +1,9 @@
 use crate::{error::Result, player::claim::PlayerClaim};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct PatchPlayerClaim {
     pub verified: Option<bool>,
     pub lock_submissions: Option<bool>,
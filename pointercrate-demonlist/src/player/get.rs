@@ -3,8 +3,8 @@ use crate::{
     demon::{published_by, verified_by},
     error::{DemonlistError, Result},
     nationality::{Nationality, Subdivision},
-    player::{DatabasePlayer, FullPlayer, Player},
-    record::approved_records_by,
+    player::{name::normalize, DatabasePlayer, FullPlayer, Player, PlayerAlias},
+    record::{approved_records_by, history::progress_history_of},
 };
 use sqlx::{Error, PgConnection};
 
@@ -21,14 +21,18 @@ struct FetchedPlayer {
 
 impl Player {
     pub async fn upgrade(self, connection: &mut PgConnection) -> Result<FullPlayer> {
+        let aliases = aliases_of(&self.base, connection).await?;
         let records = approved_records_by(&self.base, connection).await?;
+        let progress_history = progress_history_of(&self.base, connection).await?;
         let published = published_by(&self.base, connection).await?;
         let verified = verified_by(&self.base, connection).await?;
         let created = created_by(self.base.id, connection).await?;
 
         Ok(FullPlayer {
             player: self,
+            aliases,
             records,
+            progress_history,
             created,
             verified,
             published,
@@ -79,12 +83,16 @@ impl Player {
 }
 
 impl DatabasePlayer {
+    /// Gets the player going by the normalized form of the given name, or, if no player does, the
+    /// player going by exactly the given name (see the [`name`](crate::player::name) module)
     pub async fn by_name(name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
-        let name = name.trim();
+        let normalized = normalize(name, &mut *connection).await?;
 
         let result = sqlx::query!(
-            "SELECT id, name::text, banned FROM players WHERE name = cast($1::text as citext)",
-            name.to_string()
+            "SELECT id, name::text, banned FROM players WHERE name = cast($1::text as citext) OR name = cast($2::text as citext) ORDER BY \
+             name = cast($1::text as citext) DESC LIMIT 1",
+            normalized,
+            name
         ) // FIXME(sqlx) once CITEXT is supported
         .fetch_one(connection)
         .await;
@@ -95,9 +103,7 @@ impl DatabasePlayer {
                 name: row.name.unwrap(), // FIXME(sqlx) casted columns interpreted as nullable
                 banned: row.banned,
             }),
-            Err(Error::RowNotFound) => Err(DemonlistError::PlayerNotFoundName {
-                player_name: name.to_string(),
-            }),
+            Err(Error::RowNotFound) => Err(DemonlistError::PlayerNotFoundName { player_name: normalized }),
             Err(err) => Err(err.into()),
         }
     }
@@ -118,23 +124,61 @@ impl DatabasePlayer {
         }
     }
 
-    pub async fn by_name_or_create(name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
-        let name = name.trim();
+    /// Gets the player going by the given name, or, if no player does, the player having the given
+    /// name as an alias
+    pub async fn by_name_or_alias(name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
+        match Self::by_name(name, &mut *connection).await {
+            Err(DemonlistError::PlayerNotFoundName { player_name }) => {
+                let row = sqlx::query!(
+                    r#"SELECT id, name AS "name: String", banned FROM players INNER JOIN player_aliases ON players.id = player_aliases.player
+                     WHERE alias = cast($1::text as citext) OR alias = cast($2::text as citext) ORDER BY alias = cast($1::text as citext) DESC
+                     LIMIT 1"#,
+                    player_name,
+                    name
+                )
+                .fetch_optional(connection)
+                .await?
+                .ok_or(DemonlistError::PlayerNotFoundName { player_name })?;
+
+                Ok(DatabasePlayer {
+                    id: row.id,
+                    name: row.name,
+                    banned: row.banned,
+                })
+            },
+            result => result,
+        }
+    }
 
-        match Self::by_name(name, connection).await {
-            Err(DemonlistError::PlayerNotFoundName { .. }) => {
+    /// Gets the player going by (or formerly known as) the given name, creating a new player if
+    /// there is none
+    pub async fn by_name_or_create(name: &str, connection: &mut PgConnection) -> Result<DatabasePlayer> {
+        match Self::by_name_or_alias(name, &mut *connection).await {
+            Err(DemonlistError::PlayerNotFoundName { player_name: name }) => {
                 let id = sqlx::query!("INSERT INTO players (name) VALUES ($1::text) RETURNING id", name.to_string())
                     .fetch_one(connection)
                     .await?
                     .id;
 
-                Ok(DatabasePlayer {
-                    id,
-                    name: name.to_owned(),
-                    banned: false,
-                })
+                Ok(DatabasePlayer { id, name, banned: false })
             },
             result => result,
         }
     }
 }
+
+/// Gets the former names of the given player, oldest first
+pub async fn aliases_of(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<PlayerAlias>> {
+    Ok(sqlx::query!(
+        r#"SELECT alias AS "alias: String", created_at FROM player_aliases WHERE player = $1 ORDER BY created_at, alias"#,
+        player.id
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| PlayerAlias {
+        name: row.alias,
+        created_at: row.created_at,
+    })
+    .collect())
+}
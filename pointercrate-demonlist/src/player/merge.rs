@@ -0,0 +1,944 @@
+//! Module for merging players
+//!
+//! Two players are merged if it turns out they are the same person, either explicitly or because
+//! renaming one of them collided with the name of the other. Merging moves everything credited to
+//! the merged player (records, partner entries, creator, verifier and publisher entries, claims and
+//! progress history) over to the surviving player, and then deletes the merged player, keeping its
+//! name as an alias of the surviving player. Records of both players on the same demon can
+//! collide, in which case redundant records are deleted as outlined in the
+//! [`record`](crate::record) module.
+//!
+//! [`MergePreview::of`] reports what a merge would do without changing anything. Each merge is
+//! recorded as a [`PlayerMerge`], together with a snapshot of everything the merge changed or
+//! deleted, so that it can be [reverted](PlayerMerge::revert) later on. Data attached to deleted
+//! records other than their notes and partners (such as their video metadata) is not restored by
+//! reverting.
+
+use crate::{
+    demon::MinimalDemon,
+    error::{DemonlistError, Result},
+    player::{claim::PlayerClaim, DatabasePlayer},
+    record::{FullRecord, MinimalRecordD, RecordStatus},
+};
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use log::info;
+use pointercrate_core::error::CoreError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, PgConnection};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MergePlayers {
+    /// The id of the player to merge into the other one. This player is deleted by the merge
+    pub player: i32,
+
+    /// Whether to only report what merging would do, without actually merging
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// An entry in the audit log of player merges
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Eq, Clone)]
+pub struct PlayerMerge {
+    pub id: i32,
+
+    /// The id of the player the other one was merged into
+    pub player: i32,
+
+    /// The id of the player that was merged (and thus deleted, unless the merge was reverted)
+    pub merged: i32,
+    pub merged_name: String,
+    pub merged_by: i32,
+    pub merged_at: NaiveDateTime,
+    pub reverted_by: Option<i32>,
+    pub reverted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct MergePreview {
+    pub player: DatabasePlayer,
+    pub merged: DatabasePlayer,
+
+    /// Records of `player` that remain after the merge, as they will be after the merge
+    pub kept: Vec<MinimalRecordD>,
+
+    /// Records of `merged` that are moved over to `player`, as they will be after the merge
+    pub moved: Vec<MinimalRecordD>,
+
+    /// Records of either player that are deleted because they are made redundant by a record of the
+    /// other one
+    pub deleted: Vec<MinimalRecordD>,
+
+    /// The verified claims on both players, if both are claimed. Such players cannot be merged.
+    pub conflicting_claims: Vec<PlayerClaim>,
+    pub score_changes: Vec<ScoreChange>,
+}
+
+/// How a merge changes the score of the surviving player on some list
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct ScoreChange {
+    pub list: i32,
+    pub score_before: f64,
+
+    /// The score the merged player had before the merge
+    pub merged_score_before: f64,
+    pub score_after: f64,
+}
+
+/// Everything a merge changes or deletes, as it was right before the merge
+#[derive(Debug, Serialize, Deserialize)]
+struct MergeSnapshot {
+    /// The name of the surviving player, which might be changed to that of the merged player right
+    /// after the merge
+    player_name: String,
+    merged: SnapshotPlayer,
+
+    /// The former names of the merged player
+    #[serde(default)]
+    merged_aliases: Vec<String>,
+
+    /// All records of both players
+    records: Vec<SnapshotRecord>,
+
+    /// `(note, record)` for all notes on the above records
+    notes: Vec<(i32, i32)>,
+
+    /// `(record, player)` for all partner entries of either player, or on any of the above records
+    partners: Vec<(i32, i32)>,
+
+    /// The demons created by the surviving player
+    created: Vec<i32>,
+    merged_created: Vec<i32>,
+    merged_verified: Vec<i32>,
+    merged_published: Vec<i32>,
+
+    /// All claims on both players
+    claims: Vec<PlayerClaim>,
+    merged_history: Vec<i32>,
+
+    /// The progress history entries created by the merge itself
+    archived_history: Vec<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotPlayer {
+    id: i32,
+    name: String,
+    banned: bool,
+    nationality: Option<String>,
+    subdivision: Option<String>,
+    link_banned: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotRecord {
+    id: i32,
+    progress: i16,
+    completion_time: Option<i32>,
+    video: Option<String>,
+    status: String,
+    player: i32,
+    submitter: i32,
+    demon: i32,
+    rejection_reason: Option<i32>,
+}
+
+impl MergeSnapshot {
+    async fn take(player: &DatabasePlayer, merged: &DatabasePlayer, connection: &mut PgConnection) -> Result<MergeSnapshot> {
+        let row = sqlx::query!(
+            r#"SELECT id, name::text AS "name!", banned, nationality::text, subdivision::text, link_banned FROM players WHERE id = $1"#,
+            merged.id
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+        let merged_player = SnapshotPlayer {
+            id: row.id,
+            name: row.name,
+            banned: row.banned,
+            nationality: row.nationality,
+            subdivision: row.subdivision,
+            link_banned: row.link_banned,
+        };
+
+        let records = sqlx::query_as!(
+            SnapshotRecord,
+            r#"SELECT id, progress, completion_time, video::text, status_::text AS "status!", player, submitter, demon, rejection_reason
+             FROM records WHERE player = $1 OR player = $2"#,
+            player.id,
+            merged.id
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+        let record_ids = records.iter().map(|record| record.id).collect::<Vec<_>>();
+
+        let notes = sqlx::query!("SELECT id, record FROM record_notes WHERE record = ANY($1)", &record_ids[..])
+            .fetch_all(&mut *connection)
+            .await?
+            .into_iter()
+            .map(|row| (row.id, row.record))
+            .collect();
+        let partners = sqlx::query!(
+            "SELECT record, player FROM record_partners WHERE player = $1 OR player = $2 OR record = ANY($3)",
+            player.id,
+            merged.id,
+            &record_ids[..]
+        )
+        .fetch_all(&mut *connection)
+        .await?
+        .into_iter()
+        .map(|row| (row.record, row.player))
+        .collect();
+
+        let claims = sqlx::query!(
+            "SELECT member_id, player_id, verified, lock_submissions FROM player_claims WHERE player_id = $1 OR player_id = $2",
+            player.id,
+            merged.id
+        )
+        .fetch_all(&mut *connection)
+        .await?
+        .into_iter()
+        .map(|row| PlayerClaim {
+            user_id: row.member_id,
+            player_id: row.player_id,
+            verified: row.verified,
+            lock_submissions: row.lock_submissions,
+        })
+        .collect();
+
+        Ok(MergeSnapshot {
+            player_name: player.name.clone(),
+            merged: merged_player,
+            merged_aliases: sqlx::query!(
+                r#"SELECT alias AS "alias: String" FROM player_aliases WHERE player = $1"#,
+                merged.id
+            )
+            .fetch_all(&mut *connection)
+            .await?
+            .into_iter()
+            .map(|row| row.alias)
+            .collect(),
+            records,
+            notes,
+            partners,
+            created: demons_created_by(player.id, &mut *connection).await?,
+            merged_created: demons_created_by(merged.id, &mut *connection).await?,
+            merged_verified: sqlx::query!("SELECT id FROM demons WHERE verifier = $1", merged.id)
+                .fetch_all(&mut *connection)
+                .await?
+                .into_iter()
+                .map(|row| row.id)
+                .collect(),
+            merged_published: sqlx::query!("SELECT id FROM demons WHERE publisher = $1", merged.id)
+                .fetch_all(&mut *connection)
+                .await?
+                .into_iter()
+                .map(|row| row.id)
+                .collect(),
+            claims,
+            merged_history: sqlx::query!("SELECT id FROM record_progress_history WHERE player = $1", merged.id)
+                .fetch_all(&mut *connection)
+                .await?
+                .into_iter()
+                .map(|row| row.id)
+                .collect(),
+            archived_history: Vec::new(),
+        })
+    }
+}
+
+async fn demons_created_by(player_id: i32, connection: &mut PgConnection) -> Result<Vec<i32>> {
+    Ok(sqlx::query!("SELECT demon FROM creators WHERE creator = $1", player_id)
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(|row| row.demon)
+        .collect())
+}
+
+/// The state of both players right before a merge, from which [`MergePreview`] describes what
+/// the merge did
+struct MergeBaseline {
+    conflicting_claims: Vec<PlayerClaim>,
+    records: Vec<MinimalRecordD>,
+    merged_records: Vec<MinimalRecordD>,
+    scores: BTreeMap<i32, f64>,
+    merged_scores: BTreeMap<i32, f64>,
+}
+
+impl MergeBaseline {
+    async fn take(player: &DatabasePlayer, merged: &DatabasePlayer, connection: &mut PgConnection) -> Result<MergeBaseline> {
+        let conflicting_claims = match (
+            PlayerClaim::verified_claim_on(player.id, &mut *connection).await?,
+            PlayerClaim::verified_claim_on(merged.id, &mut *connection).await?,
+        ) {
+            (Some(claim_on_player), Some(claim_on_merged)) => vec![claim_on_player, claim_on_merged],
+            _ => Vec::new(),
+        };
+
+        Ok(MergeBaseline {
+            conflicting_claims,
+            records: records_of(player.id, &mut *connection).await?,
+            merged_records: records_of(merged.id, &mut *connection).await?,
+            scores: scores_of(player.id, &mut *connection).await?,
+            merged_scores: scores_of(merged.id, &mut *connection).await?,
+        })
+    }
+
+    /// Describes the merge carried out since this baseline was taken
+    async fn describe(self, player: &DatabasePlayer, merged: &DatabasePlayer, connection: &mut PgConnection) -> Result<MergePreview> {
+        let records_after = records_of(player.id, &mut *connection).await?;
+        let scores_after = scores_of(player.id, &mut *connection).await?;
+
+        let (kept, moved): (Vec<_>, Vec<_>) = records_after
+            .into_iter()
+            .partition(|record| self.records.iter().any(|before| before.id == record.id));
+        let deleted = self
+            .records
+            .into_iter()
+            .chain(self.merged_records)
+            .filter(|before| !kept.iter().chain(moved.iter()).any(|after| after.id == before.id))
+            .collect();
+
+        let mut lists = self
+            .scores
+            .keys()
+            .chain(self.merged_scores.keys())
+            .chain(scores_after.keys())
+            .copied()
+            .collect::<Vec<_>>();
+
+        lists.sort_unstable();
+        lists.dedup();
+
+        let score_changes = lists
+            .into_iter()
+            .map(|list| ScoreChange {
+                list,
+                score_before: self.scores.get(&list).copied().unwrap_or(0.0),
+                merged_score_before: self.merged_scores.get(&list).copied().unwrap_or(0.0),
+                score_after: scores_after.get(&list).copied().unwrap_or(0.0),
+            })
+            .collect();
+
+        Ok(MergePreview {
+            player: player.clone(),
+            merged: merged.clone(),
+            kept,
+            moved,
+            deleted,
+            conflicting_claims: self.conflicting_claims,
+            score_changes,
+        })
+    }
+}
+
+impl MergePreview {
+    /// Reports what merging `merged` into `player` would do, without changing anything
+    ///
+    /// Only scores are computed by the database, so the merge is tried out inside a savepoint that is
+    /// rolled back again. Merges that are actually carried out report what they did by themselves (see
+    /// [`FullPlayer::merge`](crate::player::FullPlayer::merge)), so there is no need to call this
+    /// beforehand.
+    pub async fn of(player: &DatabasePlayer, merged: &DatabasePlayer, connection: &mut PgConnection) -> Result<MergePreview> {
+        if player.id == merged.id {
+            return Err(DemonlistError::SelfMerge);
+        }
+
+        let baseline = MergeBaseline::take(player, merged, &mut *connection).await?;
+        let mut savepoint = connection.begin().await?;
+
+        // Claims don't influence what happens to records, but need to be out of the way for the merged player
+        // to be deletable
+        sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", merged.id)
+            .execute(&mut *savepoint)
+            .await?;
+
+        merge_contents(player, merged, &mut savepoint).await?;
+
+        let preview = baseline.describe(player, merged, &mut savepoint).await?;
+
+        savepoint.rollback().await?;
+
+        Ok(preview)
+    }
+}
+
+/// Gets all records of the given player, regardless of status
+async fn records_of(player_id: i32, connection: &mut PgConnection) -> Result<Vec<MinimalRecordD>> {
+    let mut stream = sqlx::query!(
+        r#"SELECT records.id, progress, completion_time, video::text, status_::text AS "status!", demons.id AS demon_id, demons.name AS
+         "name: String", demons.position FROM records INNER JOIN demons ON records.demon = demons.id WHERE records.player = $1 ORDER BY
+         demons.position, records.id"#,
+        player_id
+    )
+    .fetch(connection);
+
+    let mut records = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        records.push(MinimalRecordD {
+            id: row.id,
+            progress: row.progress,
+            completion_time: row.completion_time,
+            video: row.video,
+            status: RecordStatus::from_sql(&row.status),
+            demon: MinimalDemon {
+                id: row.demon_id,
+                position: row.position,
+                name: row.name,
+            },
+        })
+    }
+
+    Ok(records)
+}
+
+/// Gets the scores of the given player, keyed by list
+async fn scores_of(player_id: i32, connection: &mut PgConnection) -> Result<BTreeMap<i32, f64>> {
+    Ok(sqlx::query!(
+        r#"SELECT list AS "list!", score AS "score!" FROM players_with_score WHERE id = $1"#,
+        player_id
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| (row.list, row.score))
+    .collect())
+}
+
+/// Merges `merged` into `player`, deleting `merged`, records the merge in the audit log and
+/// describes what it did
+///
+/// Note that this **does not** rename `player`
+pub(crate) async fn merge(
+    player: &DatabasePlayer, merged: &DatabasePlayer, connection: &mut PgConnection,
+) -> Result<(PlayerMerge, MergePreview)> {
+    if player.id == merged.id {
+        return Err(DemonlistError::SelfMerge);
+    }
+
+    info!("Merging player {} into player {}", merged, player);
+
+    let baseline = MergeBaseline::take(player, merged, &mut *connection).await?;
+    let mut snapshot = MergeSnapshot::take(player, merged, &mut *connection).await?;
+    let history_watermark = sqlx::query!(r#"SELECT COALESCE(MAX(id), 0) AS "max!" FROM record_progress_history"#)
+        .fetch_one(&mut *connection)
+        .await?
+        .max;
+
+    transfer_claims(player, merged, &mut *connection).await?;
+    merge_contents(player, merged, &mut *connection).await?;
+
+    snapshot.archived_history = sqlx::query!(
+        "SELECT id FROM record_progress_history WHERE id > $1 AND player = $2",
+        history_watermark,
+        player.id
+    )
+    .fetch_all(&mut *connection)
+    .await?
+    .into_iter()
+    .map(|row| row.id)
+    .collect();
+
+    let preview = baseline.describe(player, merged, &mut *connection).await?;
+
+    let snapshot = serde_json::to_value(&snapshot).map_err(|err| CoreError::InternalServerError {
+        message: format!("Failed to serialize snapshot of merging {} into {}: {}", merged, player, err),
+    })?;
+
+    let row = sqlx::query!(
+        "INSERT INTO player_merges (player, merged, merged_name, snapshot, merged_by) VALUES ($1, $2, $3::text, $4, (SELECT id FROM \
+         active_user LIMIT 1)) RETURNING id, merged_by, merged_at",
+        player.id,
+        merged.id,
+        merged.name.to_string(),
+        snapshot
+    )
+    .fetch_one(connection)
+    .await?;
+
+    let merge = PlayerMerge {
+        id: row.id,
+        player: player.id,
+        merged: merged.id,
+        merged_name: merged.name.clone(),
+        merged_by: row.merged_by,
+        merged_at: row.merged_at,
+        reverted_by: None,
+        reverted_at: None,
+    };
+
+    Ok((merge, preview))
+}
+
+async fn transfer_claims(player: &DatabasePlayer, merged: &DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
+    let claim_on_player = PlayerClaim::verified_claim_on(player.id, &mut *connection).await?;
+    let claim_on_merged = PlayerClaim::verified_claim_on(merged.id, &mut *connection).await?;
+
+    match (claim_on_player, claim_on_merged) {
+        (Some(_), Some(_)) => {
+            return Err(DemonlistError::ConflictingClaims {
+                player1: player.name.clone(),
+                player2: merged.name.clone(),
+            })
+        },
+        (Some(_), None) => {
+            sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", merged.id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        (None, Some(_)) => {
+            sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", player.id)
+                .execute(&mut *connection)
+                .await?;
+            sqlx::query!("UPDATE player_claims SET player_id = $1 WHERE player_id = $2", player.id, merged.id)
+                .execute(&mut *connection)
+                .await?;
+        },
+        (None, None) => {
+            sqlx::query!("UPDATE player_claims SET player_id = $1 WHERE player_id = $2", player.id, merged.id)
+                .execute(&mut *connection)
+                .await?;
+        },
+    }
+
+    Ok(())
+}
+
+/// Moves everything but claims over from `merged` to `player`, and deletes `merged`
+async fn merge_contents(player: &DatabasePlayer, merged: &DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
+    // First, delete duplicate creator entries
+    let deleted = sqlx::query!(
+        "DELETE FROM creators AS c1 WHERE c1.creator = $2 AND EXISTS (SELECT 1 FROM creators AS c2 WHERE c2.demon = c1.demon AND \
+         c2.creator = $1)",
+        player.id,
+        merged.id
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    info!(
+        "Deleted {} duplicate creator entries while merging {} and {}",
+        deleted.rows_affected(),
+        player,
+        merged
+    );
+
+    // Transfer all other creator entries over
+    let updated = sqlx::query!("UPDATE creators SET creator = $1 WHERE creator = $2", player.id, merged.id)
+        .execute(&mut *connection)
+        .await?;
+
+    info!(
+        "Transferred {} creator entries from {} to {}",
+        updated.rows_affected(),
+        merged,
+        player
+    );
+
+    // Transfer over verifier and publisher entries
+
+    let updated_verifiers = sqlx::query!("UPDATE demons SET verifier = $1 WHERE verifier = $2", player.id, merged.id)
+        .execute(&mut *connection)
+        .await?;
+    let updated_publishers = sqlx::query!("UPDATE demons SET publisher = $1 WHERE publisher = $2", player.id, merged.id)
+        .execute(&mut *connection)
+        .await?;
+
+    info!(
+        "Transferred over {} verifier and {} publisher entires from {} to {}",
+        updated_verifiers.rows_affected(),
+        updated_publishers.rows_affected(),
+        merged,
+        player
+    );
+
+    // Delete partner entries that would credit the merged player twice with the same record
+    let deleted = sqlx::query!(
+        "DELETE FROM record_partners AS p1 WHERE p1.player = $2 AND (EXISTS (SELECT 1 FROM record_partners AS p2 WHERE p2.record = \
+         p1.record AND p2.player = $1) OR EXISTS (SELECT 1 FROM records WHERE records.id = p1.record AND records.player = $1))",
+        player.id,
+        merged.id
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    info!(
+        "Deleted {} duplicate partner entries while merging {} and {}",
+        deleted.rows_affected(),
+        player,
+        merged
+    );
+
+    let updated = sqlx::query!("UPDATE record_partners SET player = $1 WHERE player = $2", player.id, merged.id)
+        .execute(&mut *connection)
+        .await?;
+
+    info!(
+        "Transferred {} partner entries from {} to {}",
+        updated.rows_affected(),
+        merged,
+        player
+    );
+
+    // Alright so merging records is HARD. We already implemented it over in the record patching, so
+    // while somewhat inefficient maybe, we'll just call that code for each record of the current player
+    for row in sqlx::query!("SELECT id FROM records WHERE player = $1", merged.id)
+        .fetch_all(&mut *connection)
+        .await?
+    {
+        // FIXME: this is really inefficient and can be made a lot faster by simple moving around some code
+        // in the FullRecord impls
+        let mut record = FullRecord::by_id(row.id, &mut *connection).await?;
+        info!("Moving record {} over to new player {}", record, player);
+        record.set_player(player.clone(), &mut *connection).await?
+    }
+
+    let updated = sqlx::query!(
+        "UPDATE record_progress_history SET player = $1 WHERE player = $2",
+        player.id,
+        merged.id
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    info!(
+        "Transferred {} progress history entries from {} to {}",
+        updated.rows_affected(),
+        merged,
+        player
+    );
+
+    // Transfer all records over, now that they're unique
+    let updated = sqlx::query!("UPDATE records SET player = $1 WHERE player = $2", player.id, merged.id)
+        .execute(&mut *connection)
+        .await?;
+
+    info!("Moved {} records from {} to {}", updated.rows_affected(), merged, player);
+
+    // The merged player's names live on as aliases of the surviving player
+    sqlx::query!("UPDATE player_aliases SET player = $1 WHERE player = $2", player.id, merged.id)
+        .execute(&mut *connection)
+        .await?;
+
+    // Delete the second player
+    sqlx::query!("DELETE FROM players WHERE id = $1", merged.id)
+        .execute(&mut *connection)
+        .await?;
+
+    sqlx::query!(
+        "INSERT INTO player_aliases (player, alias) VALUES ($1, $2::text) ON CONFLICT (alias) DO NOTHING",
+        player.id,
+        merged.name.to_string()
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+impl PlayerMerge {
+    pub async fn by_id(merge_id: i32, connection: &mut PgConnection) -> Result<PlayerMerge> {
+        sqlx::query_as!(
+            PlayerMerge,
+            r#"SELECT id, player, merged, merged_name::text AS "merged_name!", merged_by, merged_at, reverted_by, reverted_at FROM
+             player_merges WHERE id = $1"#,
+            merge_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(DemonlistError::PlayerMergeNotFound { merge_id })
+    }
+
+    /// Undoes this merge, recreating the merged player and restoring both players to how they were
+    /// right before the merge
+    ///
+    /// Merges need to be reverted in reverse order, meaning that if the surviving player was involved
+    /// in another merge afterwards, that later merge needs to be reverted first. Since reverting
+    /// restores the snapshot taken right before the merge, merges whose players or records have been
+    /// modified since cannot be reverted at all, as those modifications would silently be lost.
+    ///
+    /// The audit log entries for recreating the merged player and its deleted records are dropped
+    /// again, so that the state of the list in the past can still be reconstructed from the audit
+    /// log after reverting.
+    pub async fn revert(&mut self, connection: &mut PgConnection) -> Result<()> {
+        if self.reverted_at.is_some() {
+            return Err(DemonlistError::MergeAlreadyReverted);
+        }
+
+        let later_merge = sqlx::query!(
+            "SELECT id FROM player_merges WHERE id > $1 AND reverted_at IS NULL AND (player = $2 OR merged = $2) ORDER BY id DESC LIMIT 1",
+            self.id,
+            self.player
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        if let Some(later_merge) = later_merge {
+            return Err(DemonlistError::LaterMergeExists { merge_id: later_merge.id });
+        }
+
+        let player = DatabasePlayer::by_id(self.player, &mut *connection).await?;
+        let snapshot = sqlx::query!("SELECT snapshot FROM player_merges WHERE id = $1", self.id)
+            .fetch_one(&mut *connection)
+            .await?
+            .snapshot;
+        let snapshot: MergeSnapshot = serde_json::from_value(snapshot).map_err(|err| CoreError::InternalServerError {
+            message: format!("Failed to deserialize snapshot of player merge {}: {}", self.id, err),
+        })?;
+        let merged = &snapshot.merged;
+        let record_ids = snapshot.records.iter().map(|record| record.id).collect::<Vec<_>>();
+
+        // Everything the merge itself did was logged at exactly `merged_at`, so anything logged later on
+        // is a modification the snapshot knows nothing about. The exception are later merges that have
+        // been reverted already, as those left everything the way they found it.
+        let outdated = sqlx::query!(
+            r#"WITH changes AS (
+                   SELECT time FROM record_modifications WHERE id = ANY($1)
+                   UNION ALL
+                   SELECT record_additions.time FROM record_additions INNER JOIN records ON records.id = record_additions.id
+                   WHERE records.player = $2
+                   UNION ALL
+                   SELECT time FROM record_partner_additions WHERE record = ANY($1) OR player = $2
+                   UNION ALL
+                   SELECT time FROM record_partner_deletions WHERE record = ANY($1) OR player = $2
+                   UNION ALL
+                   SELECT time FROM player_modifications WHERE id = $2
+               ), reverted_merges AS (
+                   SELECT merged_at AS time FROM player_merges WHERE id > $4 AND reverted_at IS NOT NULL
+                   UNION
+                   SELECT reverted_at FROM player_merges WHERE id > $4 AND reverted_at IS NOT NULL
+               )
+               SELECT EXISTS (SELECT 1 FROM changes WHERE time > $3 AND time NOT IN (SELECT time FROM reverted_merges)) AS "outdated!""#,
+            &record_ids[..],
+            player.id,
+            self.merged_at,
+            self.id
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .outdated;
+
+        if outdated {
+            return Err(DemonlistError::MergeOutdated);
+        }
+
+        info!("Reverting merge of {} (ID: {}) into {}", merged.name, merged.id, player);
+
+        // The surviving player might have been renamed to the merged player's name, so its own name is
+        // restored first
+        for name in [&snapshot.player_name, &merged.name] {
+            match DatabasePlayer::by_name(name, &mut *connection).await {
+                Ok(existing) if existing.id != player.id => return Err(DemonlistError::MergeNameTaken { player_name: name.clone() }),
+                Ok(_) | Err(DemonlistError::PlayerNotFoundName { .. }) => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        sqlx::query!("UPDATE players SET name = $1::text WHERE id = $2", snapshot.player_name, player.id)
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query!(
+            "INSERT INTO players (id, name, banned, nationality, subdivision, link_banned) VALUES ($1, $2::text, $3, $4::text, $5::text, \
+             $6)",
+            merged.id,
+            merged.name,
+            merged.banned,
+            merged.nationality,
+            merged.subdivision,
+            merged.link_banned
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "UPDATE player_aliases SET player = $1 WHERE player = $2 AND alias = ANY($3::text[]::citext[])",
+            merged.id,
+            player.id,
+            &snapshot.merged_aliases[..]
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        // Restore the records that still exist first, so that the deleted ones can get their videos back
+        let mut deleted_records = Vec::new();
+
+        for record in &snapshot.records {
+            let updated = sqlx::query!(
+                "UPDATE records SET progress = $2, completion_time = $3, video = $4::text, status_ = $5::text::record_status, player = $6, \
+                 submitter = $7, demon = $8, rejection_reason = $9 WHERE id = $1",
+                record.id,
+                record.progress,
+                record.completion_time,
+                record.video,
+                record.status,
+                record.player,
+                record.submitter,
+                record.demon,
+                record.rejection_reason
+            )
+            .execute(&mut *connection)
+            .await?;
+
+            if updated.rows_affected() == 0 {
+                deleted_records.push(record);
+            }
+        }
+
+        let recreated_records = deleted_records.iter().map(|record| record.id).collect::<Vec<_>>();
+
+        for record in deleted_records {
+            sqlx::query!(
+                "INSERT INTO records (id, progress, completion_time, video, status_, player, submitter, demon, rejection_reason) VALUES \
+                 ($1, $2, $3, $4::text, $5::text::record_status, $6, $7, $8, $9)",
+                record.id,
+                record.progress,
+                record.completion_time,
+                record.video,
+                record.status,
+                record.player,
+                record.submitter,
+                record.demon,
+                record.rejection_reason
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        info!("Restored {} records of {} and {}", snapshot.records.len(), player, merged.name);
+
+        // Recreating the merged player and its records logged them as new additions, which would hide
+        // their history from before the merge
+        sqlx::query!(
+            "DELETE FROM record_additions WHERE id = ANY($1) AND time > $2",
+            &recreated_records[..],
+            self.merged_at
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM player_additions WHERE id = $1 AND time > $2",
+            merged.id,
+            self.merged_at
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        for &(note, record) in &snapshot.notes {
+            sqlx::query!("UPDATE record_notes SET record = $1 WHERE id = $2", record, note)
+                .execute(&mut *connection)
+                .await?;
+        }
+
+        // Partner entries that were transferred to the surviving player need to be removed from it again
+        let transferred_partners = snapshot
+            .partners
+            .iter()
+            .filter(|&&(record, partner)| partner == merged.id && !snapshot.partners.contains(&(record, player.id)))
+            .map(|&(record, _)| record)
+            .collect::<Vec<_>>();
+
+        sqlx::query!(
+            "DELETE FROM record_partners WHERE player = $1 AND record = ANY($2)",
+            player.id,
+            &transferred_partners[..]
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        for &(record, partner) in &snapshot.partners {
+            sqlx::query!(
+                "INSERT INTO record_partners (record, player) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                record,
+                partner
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        sqlx::query!(
+            "DELETE FROM creators WHERE creator = $1 AND demon = ANY($2) AND NOT demon = ANY($3)",
+            player.id,
+            &snapshot.merged_created[..],
+            &snapshot.created[..]
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO creators (demon, creator) SELECT UNNEST($1::INTEGER[]), $2 ON CONFLICT DO NOTHING",
+            &snapshot.merged_created[..],
+            merged.id
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "UPDATE demons SET verifier = $1 WHERE verifier = $2 AND id = ANY($3)",
+            merged.id,
+            player.id,
+            &snapshot.merged_verified[..]
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "UPDATE demons SET publisher = $1 WHERE publisher = $2 AND id = ANY($3)",
+            merged.id,
+            player.id,
+            &snapshot.merged_published[..]
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        let claimants = snapshot.claims.iter().map(|claim| claim.user_id).collect::<Vec<_>>();
+
+        sqlx::query!(
+            "DELETE FROM player_claims WHERE (player_id = $1 OR player_id = $2) AND member_id = ANY($3)",
+            player.id,
+            merged.id,
+            &claimants[..]
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        for claim in &snapshot.claims {
+            sqlx::query!(
+                "INSERT INTO player_claims (member_id, player_id, verified, lock_submissions) VALUES ($1, $2, $3, $4)",
+                claim.user_id,
+                claim.player_id,
+                claim.verified,
+                claim.lock_submissions
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        sqlx::query!(
+            "DELETE FROM record_progress_history WHERE id = ANY($1)",
+            &snapshot.archived_history[..]
+        )
+        .execute(&mut *connection)
+        .await?;
+        sqlx::query!(
+            "UPDATE record_progress_history SET player = $1 WHERE id = ANY($2)",
+            merged.id,
+            &snapshot.merged_history[..]
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        let row = sqlx::query!(
+            "UPDATE player_merges SET reverted_by = (SELECT id FROM active_user LIMIT 1), reverted_at = NOW() AT TIME ZONE 'utc' WHERE id \
+             = $1 RETURNING reverted_by, reverted_at",
+            self.id
+        )
+        .fetch_one(connection)
+        .await?;
+
+        self.reverted_by = row.reverted_by;
+        self.reverted_at = row.reverted_at;
+
+        Ok(())
+    }
+}
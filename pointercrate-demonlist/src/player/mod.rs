@@ -1,23 +1,26 @@
 pub use self::{
-    paginate::{PlayerPagination, RankingPagination},
+    paginate::{PlayerMergePagination, PlayerPagination, RankingPagination},
     patch::PatchPlayer,
 };
-use crate::{demon::MinimalDemon, error::Result, nationality::Nationality, record::MinimalRecordD};
+use crate::{
+    demon::MinimalDemon,
+    nationality::Nationality,
+    record::{history::ProgressHistoryEntry, MinimalRecordD},
+};
+use chrono::NaiveDateTime;
 use derive_more::Display;
 use pointercrate_core::etag::Taggable;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::PgConnection;
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
 
 pub mod claim;
 mod get;
+pub mod merge;
+pub mod name;
 mod paginate;
 mod patch;
 
-#[derive(Debug, Hash, Eq, PartialEq, Serialize, Display, Clone, Deserialize)]
+#[derive(Debug, Hash, Eq, PartialEq, Serialize, JsonSchema, Display, Clone, Deserialize)]
 #[display(fmt = "{} (ID: {})", name, id)]
 pub struct DatabasePlayer {
     pub id: i32,
@@ -25,18 +28,38 @@ pub struct DatabasePlayer {
     pub banned: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Display, PartialEq, Eq, Hash)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Display, PartialEq, Eq, Hash)]
 #[display(fmt = "{}", player)]
 pub struct FullPlayer {
     #[serde(flatten)]
     pub player: Player,
+
+    /// The former names of this player, oldest first
+    pub aliases: Vec<PlayerAlias>,
     pub records: Vec<MinimalRecordD>,
+
+    /// Approved records of this player that were superseded by better ones, see
+    /// [`history`](crate::record::history)
+    pub progress_history: Vec<ProgressHistoryEntry>,
     pub created: Vec<MinimalDemon>,
     pub verified: Vec<MinimalDemon>,
     pub published: Vec<MinimalDemon>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Display)]
+/// A former name of a player
+///
+/// Referring to a player by one of its aliases (for instance in a submission) resolves to that
+/// player, unless another player now goes by that name.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Display, PartialEq, Eq, Hash, Clone)]
+#[display(fmt = "{}", name)]
+pub struct PlayerAlias {
+    pub name: String,
+
+    /// When the player stopped going by this name
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, PartialEq, Serialize, JsonSchema, Display)]
 #[display(fmt = "{} (ID: {}) at rank {} with score {}", name, id, rank, score)]
 pub struct RankedPlayer {
     pub id: i32,
@@ -48,7 +71,7 @@ pub struct RankedPlayer {
     pub index: i64,
 }
 
-#[derive(Debug, Eq, Hash, PartialEq, Serialize, Display, Deserialize)]
+#[derive(Debug, Eq, Hash, PartialEq, Serialize, JsonSchema, Display, Deserialize)]
 #[display(fmt = "{}", base)]
 pub struct Player {
     #[serde(flatten)]
@@ -58,31 +81,5 @@ pub struct Player {
 }
 
 impl Taggable for FullPlayer {
-    fn patch_part(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.player.hash(&mut hasher);
-        hasher.finish()
-    }
-}
-
-impl RankedPlayer {
-    /// Gets the highest index value generated by the `players_with_score` view
-    pub async fn max_index(connection: &mut PgConnection) -> Result<i64> {
-        Ok(sqlx::query!(r#"SELECT MAX(index) AS "max_index!: i64" FROM players_with_score"#)
-            .fetch_one(connection)
-            .await?
-            .max_index)
-    }
-}
-
-impl Player {
-    /// Gets the maximal and minimal player id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_player_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(id) AS "max_id!: i32", MIN(id) AS "min_id!: i32" FROM players"#)
-            .fetch_one(connection)
-            .await?;
-        Ok((row.max_id, row.min_id))
-    }
+    const UNPATCHABLE_FIELDS: &'static [&'static str] = &["aliases", "records", "progress_history", "created", "verified", "published"];
 }
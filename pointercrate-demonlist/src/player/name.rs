@@ -0,0 +1,126 @@
+//! Module for normalizing and comparing player names
+//!
+//! Names that look alike to a human can still be different strings, for instance because one of
+//! them contains a zero-width space, fullwidth letters or Cyrillic homoglyphs of latin letters.
+//! Looking up players by such a name doesn't find the existing player, which used to silently create
+//! a duplicate that moderators later had to merge by hand.
+//!
+//! To prevent this, names are [`normalize`]d before being stored or looked up, and each player's
+//! name is reduced to a "skeleton" (see the `player_name_skeleton` SQL function) that is equal for
+//! all names that look (nearly) identical. [`DatabasePlayer::similar_to`] uses these skeletons to
+//! find the existing players a given name could be confused with.
+//!
+//! Both are implemented in SQL (see the `normalize_player_name` function), so that names are
+//! normalized the same way everywhere. Players created before names were normalized can still go
+//! by non-normalized names, which is why lookups by name fall back to exact matches.
+
+use crate::{error::Result, player::DatabasePlayer};
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+/// The minimal length of a skeleton for names differing from it by a single character to be
+/// considered similar. For shorter names, only lookalikes are considered similar.
+pub const MIN_NEAR_MATCH_LENGTH: usize = 5;
+
+/// An existing player whose name is similar to some given name
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
+pub struct SimilarPlayer {
+    #[serde(flatten)]
+    pub player: DatabasePlayer,
+
+    /// Whether the player's name looks (nearly) identical to the given name, rather than being off by
+    /// a single character
+    pub confusable: bool,
+}
+
+/// Brings the given player name into the form it is stored in
+///
+/// This is the NFKC normal form of the name, with invisible formatting characters (such as zero-width
+/// spaces) removed, and each run of whitespace collapsed into a single space.
+pub async fn normalize(name: &str, connection: &mut PgConnection) -> Result<String> {
+    Ok(sqlx::query!(r#"SELECT normalize_player_name($1::text) AS "name!""#, name)
+        .fetch_one(connection)
+        .await?
+        .name)
+}
+
+/// The Levenshtein distance of the given strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, &cb) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + (ca != cb) as usize).min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+impl DatabasePlayer {
+    /// Gets all players whose name either looks (nearly) identical to the given name, or differs from
+    /// it by a single character (after reducing both names to their skeletons). Lookalikes come first.
+    ///
+    /// Note that this includes a player going by exactly the given name.
+    pub async fn similar_to(name: &str, connection: &mut PgConnection) -> Result<Vec<SimilarPlayer>> {
+        let skeleton = sqlx::query!(r#"SELECT player_name_skeleton($1::text) AS "skeleton!""#, name)
+            .fetch_one(&mut *connection)
+            .await?
+            .skeleton;
+
+        // Cheap pre-filtering, the edit distances are computed below. A single edit can never change
+        // both the first and the last two characters of a name long enough to be near matched.
+        let mut stream = sqlx::query!(
+            r#"SELECT id, name AS "name: String", banned, name_skeleton AS "name_skeleton!" FROM players WHERE name_skeleton = $1 OR
+             (length($1) >= $2 AND abs(length(name_skeleton) - length($1)) <= 1 AND (left(name_skeleton, 2) = left($1, 2) OR
+             right(name_skeleton, 2) = right($1, 2))) ORDER BY id"#,
+            skeleton,
+            MIN_NEAR_MATCH_LENGTH as i32
+        )
+        .fetch(connection);
+
+        let mut similar = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            if row.name_skeleton == skeleton || edit_distance(&row.name_skeleton, &skeleton) <= 1 {
+                similar.push(SimilarPlayer {
+                    player: DatabasePlayer {
+                        id: row.id,
+                        name: row.name,
+                        banned: row.banned,
+                    },
+                    confusable: row.name_skeleton == skeleton,
+                })
+            }
+        }
+
+        // stable sort, so players are still ordered by id within each group
+        similar.sort_by_key(|similar| !similar.confusable);
+
+        Ok(similar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::edit_distance;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("stardust1971", "stardust1971"), 0);
+        assert_eq!(edit_distance("stardust1971", "stardust1972"), 1);
+        assert_eq!(edit_distance("stardust1971", "stardust197"), 1);
+        assert_eq!(edit_distance("zoink", "zoinks"), 1);
+        assert_eq!(edit_distance("riot", "zoink"), 4);
+        assert_eq!(edit_distance("", "riot"), 4);
+    }
+}
@@ -1,17 +1,22 @@
 use crate::{
-    error::Result,
+    error::DemonlistError,
     nationality::{Continent, Nationality},
-    player::{DatabasePlayer, Player, RankedPlayer},
+    player::{merge::PlayerMerge, DatabasePlayer, Player, RankedPlayer},
 };
-use futures::StreamExt;
+use chrono::{DateTime, FixedOffset};
 use pointercrate_core::{
-    error::CoreError,
-    util::{non_nullable, nullable},
+    pagination::{clamp_i32, PaginationParameters, Paginator, DEFAULT_LIMIT},
+    util::{non_nullable, non_nullable_query_timestamp, nullable},
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct PlayerPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "before")]
@@ -37,24 +42,38 @@ pub struct PlayerPagination {
     nation: Option<Option<String>>,
 }
 
-impl PlayerPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Player>> {
-        if let Some(limit) = self.limit {
-            if !(1..=100).contains(&limit) {
-                return Err(CoreError::InvalidPaginationLimit.into());
-            }
+impl Paginator for PlayerPagination {
+    type Error = DemonlistError;
+    type Item = Player;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
+        }
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        PlayerPagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
         }
+    }
 
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    fn pagination_key(item: &Player) -> i64 {
+        item.base.id as i64
+    }
 
-        let query = format!(include_str!("../../sql/paginate_players_by_id.sql"), order);
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../sql/paginate_players_by_id.sql"), order)
+    }
 
-        // FIXME(sqlx) once CITEXT is supported
-        let mut stream = sqlx::query(&query)
+    // FIXME(sqlx) once CITEXT is supported
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
             .bind(self.before_id)
             .bind(self.after_id)
             .bind(self.name.as_deref())
@@ -62,38 +81,98 @@ impl PlayerPagination {
             .bind(self.banned)
             .bind(&self.nation)
             .bind(self.nation == Some(None))
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
-
-        let mut players = Vec::new();
-
-        while let Some(row) = stream.next().await {
-            let row = row?;
-
-            let nationality = match (row.get("nation"), row.get("iso_country_code")) {
-                (Some(nation), Some(country_code)) => Some(Nationality {
-                    iso_country_code: country_code,
-                    nation,
-                    subdivision: None, // dont include subdivision in pagination data
-                }),
-                _ => None,
-            };
-
-            players.push(Player {
-                base: DatabasePlayer {
-                    id: row.get("id"),
-                    name: row.get("name"),
-                    banned: row.get("banned"),
-                },
-                nationality,
-            })
+    }
+
+    fn from_row(row: &PgRow) -> Result<Player, sqlx::Error> {
+        Ok(Player {
+            base: DatabasePlayer {
+                id: row.try_get("id")?,
+                name: row.try_get("name")?,
+                banned: row.try_get("banned")?,
+            },
+            nationality: nationality_from_row(row)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct PlayerMergePagination {
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "before")]
+    pub before_id: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    #[serde(rename = "after")]
+    pub after_id: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub limit: Option<u8>,
+
+    /// Only merges into the player with this id
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub player: Option<i32>,
+
+    /// Only merges of the player with this id
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub merged: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub reverted: Option<bool>,
+}
+
+impl Paginator for PlayerMergePagination {
+    type Error = DemonlistError;
+    type Item = PlayerMerge;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
+        }
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        PlayerMergePagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
         }
+    }
+
+    fn pagination_key(item: &PlayerMerge) -> i64 {
+        item.id as i64
+    }
+
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../sql/paginate_player_merges.sql"), order)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.before_id)
+            .bind(self.after_id)
+            .bind(self.player)
+            .bind(self.merged)
+            .bind(self.reverted)
+    }
 
-        Ok(players)
+    fn from_row(row: &PgRow) -> Result<PlayerMerge, sqlx::Error> {
+        Ok(PlayerMerge {
+            id: row.try_get("id")?,
+            player: row.try_get("player")?,
+            merged: row.try_get("merged")?,
+            merged_name: row.try_get("merged_name")?,
+            merged_by: row.try_get("merged_by")?,
+            merged_at: row.try_get("merged_at")?,
+            reverted_by: row.try_get("reverted_by")?,
+            reverted_at: row.try_get("reverted_at")?,
+        })
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct RankingPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "before")]
@@ -117,25 +196,48 @@ pub struct RankingPagination {
 
     #[serde(default, deserialize_with = "non_nullable")]
     name_contains: Option<String>,
+
+    /// The list whose ranking to paginate. Defaults to the default list
+    #[serde(default, deserialize_with = "non_nullable")]
+    list: Option<i32>,
+
+    /// If set, the ranking is computed from the list, the approved records, the players and the
+    /// list's scoring formula as they were at this point in time, reconstructed from the audit log
+    #[serde(default, deserialize_with = "non_nullable_query_timestamp")]
+    at: Option<DateTime<FixedOffset>>,
 }
 
-impl RankingPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<RankedPlayer>> {
-        if let Some(limit) = self.limit {
-            if !(1..=100).contains(&limit) {
-                return Err(CoreError::InvalidPaginationLimit.into());
-            }
+impl Paginator for RankingPagination {
+    type Error = DemonlistError;
+    type Item = RankedPlayer;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_index,
+            after: self.after_index,
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
+        }
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        RankingPagination {
+            before_index: parameters.before,
+            after_index: parameters.after,
+            limit: Some(parameters.limit),
+            ..self.clone()
         }
+    }
 
-        let order = if self.before_index.is_some() && self.after_index.is_none() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    fn pagination_key(item: &RankedPlayer) -> i64 {
+        item.index
+    }
 
-        let query = format!(include_str!("../../sql/paginate_player_ranking.sql"), order);
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../sql/paginate_player_ranking.sql"), order)
+    }
 
-        let mut stream = sqlx::query(&query)
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
             .bind(self.before_index)
             .bind(self.after_index)
             .bind(self.name_contains.as_deref())
@@ -143,33 +245,29 @@ impl RankingPagination {
             .bind(self.nation == Some(None))
             .bind(self.continent.as_ref().map(|c| c.to_sql()))
             .bind(&self.subdivision)
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
-
-        let mut players = Vec::new();
-
-        while let Some(row) = stream.next().await {
-            let row = row?;
-
-            let nationality = match (row.get("nation"), row.get("iso_country_code")) {
-                (Some(nation), Some(country_code)) => Some(Nationality {
-                    iso_country_code: country_code,
-                    nation,
-                    subdivision: None, // dont include subdivision in pagination data
-                }),
-                _ => None,
-            };
-
-            players.push(RankedPlayer {
-                id: row.get("id"),
-                name: row.get("name"),
-                rank: row.get("rank"),
-                nationality,
-                score: row.get("score"),
-                index: row.get("index"),
-            })
-        }
+            .bind(self.list)
+            .bind(self.at.map(|at| at.naive_utc()))
+    }
 
-        Ok(players)
+    fn from_row(row: &PgRow) -> Result<RankedPlayer, sqlx::Error> {
+        Ok(RankedPlayer {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            rank: row.try_get("rank")?,
+            nationality: nationality_from_row(row)?,
+            score: row.try_get("score")?,
+            index: row.try_get("index")?,
+        })
     }
 }
+
+fn nationality_from_row(row: &PgRow) -> Result<Option<Nationality>, sqlx::Error> {
+    Ok(match (row.try_get("nation")?, row.try_get("iso_country_code")?) {
+        (Some(nation), Some(country_code)) => Some(Nationality {
+            iso_country_code: country_code,
+            nation,
+            subdivision: None, // dont include subdivision in pagination data
+        }),
+        _ => None,
+    })
+}
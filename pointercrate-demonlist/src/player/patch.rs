@@ -1,15 +1,22 @@
 use crate::{
+    creator::created_by,
+    demon::{published_by, verified_by},
     error::{DemonlistError, Result},
     nationality::{Nationality, Subdivision},
-    player::{claim::PlayerClaim, DatabasePlayer, FullPlayer, Player},
-    record::{approved_records_by, FullRecord},
+    player::{
+        merge::{self, MergePreview, PlayerMerge},
+        name::normalize,
+        DatabasePlayer, FullPlayer, Player,
+    },
+    record::{approved_records_by, history::progress_history_of},
 };
 use log::info;
 use pointercrate_core::util::{non_nullable, nullable};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Default)]
 pub struct PatchPlayer {
     #[serde(default, deserialize_with = "non_nullable")]
     pub name: Option<String>,
@@ -63,153 +70,54 @@ impl FullPlayer {
     }
 
     pub async fn set_name(&mut self, name: String, connection: &mut PgConnection) -> Result<()> {
-        let name = name.trim().to_string();
+        let normalized = normalize(&name, &mut *connection).await?;
 
         // Nothing to be done
-        if name == self.player.base.name.as_ref() {
+        if normalized == self.player.base.name.as_ref() {
             return Ok(());
-        } else if name.to_lowercase() != self.player.base.name.to_lowercase() {
+        } else if normalized.to_lowercase() != self.player.base.name.to_lowercase() {
             // If they are equal case insensitively, we're only doing a cosmetic rename, which won't
             // even require a merge
 
-            // try to see if a player with new name already exists
+            // try to see if a player with new name already exists. This might find ourselves if we go by
+            // exactly the given (non-normalized) name, in which case we're only normalizing our name.
             match DatabasePlayer::by_name(name.as_ref(), &mut *connection).await {
-                Ok(existing) => self.merge(existing, &mut *connection).await?,
-                Err(DemonlistError::PlayerNotFoundName { .. }) => (),
+                Ok(existing) if existing.id != self.player.base.id => {
+                    self.merge(existing, &mut *connection).await?;
+                },
+                Ok(_) | Err(DemonlistError::PlayerNotFoundName { .. }) => (),
                 Err(err) => return Err(err),
             }
         }
 
         sqlx::query!(
             "UPDATE players SET name = $1::text WHERE id = $2",
-            name.to_string(),
+            normalized.to_string(),
             self.player.base.id
         )
         .execute(connection)
         .await?;
 
-        self.player.base.name = name;
+        self.player.base.name = normalized;
 
         Ok(())
     }
 
-    /// Merges the given player into `Self`, deleting `with`.
+    /// Merges the given player into `Self`, deleting `with`, and records the merge in the audit log
+    /// of player merges. See the [`merge`](crate::player::merge) module.
     ///
-    /// Note that this method **does not** rename `Self`
-    pub async fn merge(&mut self, with: DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
-        info!("Merging player {} with player {}", self, with);
-
-        let claim_on_self = PlayerClaim::verified_claim_on(self.player.base.id, &mut *connection).await?;
-        let claim_on_with = PlayerClaim::verified_claim_on(with.id, &mut *connection).await?;
-
-        match (claim_on_self, claim_on_with) {
-            (Some(_), Some(_)) => {
-                return Err(DemonlistError::ConflictingClaims {
-                    player1: self.player.base.name.clone(),
-                    player2: with.name.clone(),
-                })
-            },
-            (Some(_), None) => {
-                sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", with.id)
-                    .execute(&mut *connection)
-                    .await?;
-            },
-            (None, Some(_)) => {
-                sqlx::query!("DELETE FROM player_claims WHERE player_id = $1", self.player.base.id)
-                    .execute(&mut *connection)
-                    .await?;
-                sqlx::query!(
-                    "UPDATE player_claims SET player_id = $1 WHERE player_id = $2",
-                    self.player.base.id,
-                    with.id
-                )
-                .execute(&mut *connection)
-                .await?;
-            },
-            (None, None) => {
-                sqlx::query!(
-                    "UPDATE player_claims SET player_id = $1 WHERE player_id = $2",
-                    self.player.base.id,
-                    with.id
-                )
-                .execute(&mut *connection)
-                .await?;
-            },
-        }
-
-        // First, delete duplicate creator entries
-        let deleted = sqlx::query!(
-            "DELETE FROM creators AS c1 WHERE c1.creator = $2 AND EXISTS (SELECT 1 FROM creators AS c2 WHERE c2.demon = c1.demon AND \
-             c2.creator = $1)",
-            self.player.base.id,
-            with.id
-        )
-        .execute(&mut *connection)
-        .await?;
-
-        info!(
-            "Deleted {} duplicate creator entries while merging {} and {}",
-            deleted.rows_affected(),
-            self,
-            with
-        );
-
-        // Transfer all other creator entries over
-        let updated = sqlx::query!("UPDATE creators SET creator = $1 WHERE creator = $2", self.player.base.id, with.id)
-            .execute(&mut *connection)
-            .await?;
-
-        info!("Transferred {} creator entries from {} to {}", updated.rows_affected(), with, self);
-
-        // Transfer over verifier and publisher entries
-
-        let updated_verifiers = sqlx::query!("UPDATE demons SET verifier = $1 WHERE verifier = $2", self.player.base.id, with.id)
-            .execute(&mut *connection)
-            .await?;
-        let updated_publishers = sqlx::query!(
-            "UPDATE demons SET publisher = $1 WHERE publisher = $2",
-            self.player.base.id,
-            with.id
-        )
-        .execute(&mut *connection)
-        .await?;
-
-        info!(
-            "Transferred over {} verifier and {} publisher entires from {} to {}",
-            updated_verifiers.rows_affected(),
-            updated_publishers.rows_affected(),
-            with,
-            self
-        );
-
-        // Alright so merging records is HARD. We already implemented it over in the record patching, so
-        // while somewhat inefficient maybe, we'll just call that code for each record of the current player
-        for row in sqlx::query!("SELECT id FROM records WHERE player = $1", with.id)
-            .fetch_all(&mut *connection)
-            .await?
-        {
-            // FIXME: this is really inefficient and can be made a lot faster by simple moving around some code
-            // in the FullRecord impls
-            let mut record = FullRecord::by_id(row.id, &mut *connection).await?;
-            info!("Moving record {} over to new player {}", record, self.player.base);
-            record.set_player(self.player.base.clone(), &mut *connection).await?
-        }
+    /// Returns the audit log entry, together with a description of what the merge did. Note that
+    /// this method **does not** rename `Self`
+    pub async fn merge(&mut self, with: DatabasePlayer, connection: &mut PgConnection) -> Result<(PlayerMerge, MergePreview)> {
+        let merge = merge::merge(&self.player.base, &with, &mut *connection).await?;
 
         self.records = approved_records_by(&self.player.base, &mut *connection).await?;
+        self.progress_history = progress_history_of(&self.player.base, &mut *connection).await?;
+        self.created = created_by(self.player.base.id, &mut *connection).await?;
+        self.verified = verified_by(&self.player.base, &mut *connection).await?;
+        self.published = published_by(&self.player.base, connection).await?;
 
-        // Transfer all records over, now that they're unique
-        let updated = sqlx::query!("UPDATE records SET player = $1 WHERE player = $2", self.player.base.id, with.id)
-            .execute(&mut *connection)
-            .await?;
-
-        info!("Moved {} records from {} to {}", updated.rows_affected(), with, self);
-
-        // Delete the second player
-        sqlx::query!("DELETE FROM players WHERE id = $1", with.id)
-            .execute(connection)
-            .await?;
-
-        Ok(())
+        Ok(merge)
     }
 }
 
@@ -309,7 +217,8 @@ impl DatabasePlayer {
     pub async fn ban(&mut self, connection: &mut PgConnection) -> Result<()> {
         // Delete all submissions for this player
         let deleted = sqlx::query!(
-            "DELETE FROM records WHERE player = $1 AND (status_ = 'SUBMITTED' OR status_ = 'UNDER_CONSIDERATION')",
+            "DELETE FROM records WHERE player = $1 AND (status_ = 'SUBMITTED' OR status_ = 'UNDER_CONSIDERATION' OR status_ = \
+             'NEEDS_INFO')",
             self.id
         )
         .execute(&mut *connection)
@@ -0,0 +1,169 @@
+//! Module for the history of the player, nation and subdivision rankings
+//!
+//! Rankings are computed live from the current state of a list (see the `players_with_score` and
+//! `nations_with_score` views and the `subdivision_ranking_of` function), so on their own, they say
+//! nothing about how a rank or score evolved. Therefore, a daily background job takes a snapshot of
+//! all rankings of all lists, from which [`PlayerRankingHistory`]s are built.
+//!
+//! Players without any score are not part of the player ranking snapshots. Each player ranking
+//! snapshot also holds the nation and subdivision the player belonged to at the time, which
+//! decides which nation and subdivision ranking snapshots are part of the player's history.
+
+use crate::{error::Result, list::List, player::Player};
+use chrono::NaiveDate;
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+/// The rank and score of a player, nation or subdivision at the end of some day
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Clone)]
+pub struct RankingSnapshot {
+    /// The day on which the snapshot was taken
+    pub date: NaiveDate,
+    pub rank: i64,
+    pub score: f64,
+}
+
+#[derive(Debug, Deserialize, Default, JsonSchema)]
+pub struct RankingHistoryFilter {
+    /// The list whose rankings to get the history of. Defaults to the default list
+    #[serde(default)]
+    pub list: Option<i32>,
+
+    /// Only include snapshots taken on or after this day
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+
+    /// Only include snapshots taken on or before this day
+    #[serde(default)]
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct PlayerRankingHistory {
+    /// The rank and score of the player, oldest first
+    pub player: Vec<RankingSnapshot>,
+
+    /// The rank and score of the player's nation in the nation ranking, on each day the player was
+    /// ranked with a nationality set. Follows the player's nationality at the time of each snapshot.
+    pub nation: Vec<RankingSnapshot>,
+
+    /// The rank and score of the player's subdivision in the ranking of the subdivisions of its
+    /// nation, on each day the player was ranked with a subdivision set
+    pub subdivision: Vec<RankingSnapshot>,
+}
+
+impl PlayerRankingHistory {
+    pub async fn of(player: &Player, filter: &RankingHistoryFilter, connection: &mut PgConnection) -> Result<PlayerRankingHistory> {
+        let list = List::by_id_or_default(filter.list, &mut *connection).await?;
+
+        let player_history = sqlx::query!(
+            "SELECT taken_on, rank, score FROM player_ranking_snapshots WHERE player = $1 AND list = $2 AND ($3::DATE IS NULL OR taken_on \
+             >= $3) AND ($4::DATE IS NULL OR taken_on <= $4) ORDER BY taken_on",
+            player.base.id,
+            list.id,
+            filter.from,
+            filter.to
+        )
+        .fetch_all(&mut *connection)
+        .await?
+        .into_iter()
+        .map(|row| RankingSnapshot {
+            date: row.taken_on,
+            rank: row.rank,
+            score: row.score,
+        })
+        .collect();
+
+        let nation_history = sqlx::query!(
+            "SELECT nations.taken_on, nations.rank, nations.score FROM player_ranking_snapshots AS players INNER JOIN \
+             nation_ranking_snapshots AS nations ON nations.nation = players.nation AND nations.list = players.list AND nations.taken_on = \
+             players.taken_on WHERE players.player = $1 AND players.list = $2 AND ($3::DATE IS NULL OR players.taken_on >= $3) AND \
+             ($4::DATE IS NULL OR players.taken_on <= $4) ORDER BY nations.taken_on",
+            player.base.id,
+            list.id,
+            filter.from,
+            filter.to
+        )
+        .fetch_all(&mut *connection)
+        .await?
+        .into_iter()
+        .map(|row| RankingSnapshot {
+            date: row.taken_on,
+            rank: row.rank,
+            score: row.score,
+        })
+        .collect();
+
+        let subdivision_history = sqlx::query!(
+            "SELECT subdivisions.taken_on, subdivisions.rank, subdivisions.score FROM player_ranking_snapshots AS players INNER JOIN \
+             subdivision_ranking_snapshots AS subdivisions ON subdivisions.nation = players.nation AND subdivisions.subdivision = \
+             players.subdivision AND subdivisions.list = players.list AND subdivisions.taken_on = players.taken_on WHERE players.player = \
+             $1 AND players.list = $2 AND ($3::DATE IS NULL OR players.taken_on >= $3) AND ($4::DATE IS NULL OR players.taken_on <= $4) \
+             ORDER BY subdivisions.taken_on",
+            player.base.id,
+            list.id,
+            filter.from,
+            filter.to
+        )
+        .fetch_all(&mut *connection)
+        .await?
+        .into_iter()
+        .map(|row| RankingSnapshot {
+            date: row.taken_on,
+            rank: row.rank,
+            score: row.score,
+        })
+        .collect();
+
+        Ok(PlayerRankingHistory {
+            player: player_history,
+            nation: nation_history,
+            subdivision: subdivision_history,
+        })
+    }
+}
+
+/// Takes snapshots of the player, nation and subdivision rankings of all lists as of now, filed
+/// under the given day
+///
+/// Snapshots already taken for the given day are kept, so this can safely be called multiple times
+/// per day.
+pub async fn take_snapshots(day: NaiveDate, connection: &mut PgConnection) -> Result<()> {
+    let players = sqlx::query!(
+        "INSERT INTO player_ranking_snapshots (player, list, taken_on, rank, score, nation, subdivision) SELECT players.id, list, $1, \
+         rank, score, players.nationality, players.subdivision FROM players_with_score INNER JOIN players ON players.id = \
+         players_with_score.id WHERE score > 0 ON CONFLICT DO NOTHING",
+        day
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    let nations = sqlx::query!(
+        "INSERT INTO nation_ranking_snapshots (nation, list, taken_on, rank, score) SELECT iso_country_code, list, $1, rank, score FROM \
+         nations_with_score ON CONFLICT DO NOTHING",
+        day
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    let subdivisions = sqlx::query!(
+        "INSERT INTO subdivision_ranking_snapshots (nation, subdivision, list, taken_on, rank, score) SELECT nations.nation, \
+         ranking.subdivision_code, lists.id, $1, ranking.rank, ranking.score FROM lists CROSS JOIN (SELECT DISTINCT nation FROM \
+         subdivisions) AS nations CROSS JOIN LATERAL subdivision_ranking_of(lists.id, nations.nation) AS ranking ON CONFLICT DO NOTHING",
+        day
+    )
+    .execute(connection)
+    .await?;
+
+    info!(
+        "Took {} player, {} nation and {} subdivision ranking snapshots for {}",
+        players.rows_affected(),
+        nations.rows_affected(),
+        subdivisions.rows_affected(),
+        day
+    );
+
+    Ok(())
+}
@@ -2,16 +2,19 @@ use crate::{error::Result, record::RecordStatus};
 
 use futures::StreamExt;
 use pointercrate_core::audit::{AuditLogEntry, AuditLogEntryType, NamedId};
+use schemars::JsonSchema;
 use serde::Serialize;
 use sqlx::PgConnection;
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 pub struct RecordModificationData {
     progress: Option<i16>,
     video: Option<String>,
     status: Option<RecordStatus>,
     player: Option<NamedId>,
     demon: Option<NamedId>,
+    partner_added: Option<NamedId>,
+    partner_removed: Option<NamedId>,
 }
 
 /// Gets all audit log entries for the given record, in chronological order
@@ -90,6 +93,8 @@ pub async fn audit_log_for_record(record_id: i32, connection: &mut PgConnection)
                         _ => None,
                     },
                     video: modification.video,
+                    partner_added: None,
+                    partner_removed: None,
                 }),
                 user: NamedId {
                     name: modification.username,
@@ -99,6 +104,53 @@ pub async fn audit_log_for_record(record_id: i32, connection: &mut PgConnection)
         }
     }
 
+    // Changes to the record's partners are recorded in separate tables, so we need to merge them
+    // into the modifications
+    let partner_rows = sqlx::query!(
+        r#"SELECT time AS "time!", audit_id AS "audit_id!", userid AS "userid!", members.name AS "username?", player AS "player_id!", 
+                  players.name::TEXT AS player_name, added AS "added!"
+                  FROM (
+                      SELECT time, audit_id, userid, record, player, TRUE AS added FROM record_partner_additions
+                      UNION ALL
+                      SELECT time, audit_id, userid, record, player, FALSE AS added FROM record_partner_deletions
+                  ) partner_changes
+                  LEFT OUTER JOIN members ON members.member_id = userid
+                  LEFT OUTER JOIN players ON players.id = player
+                  WHERE record = $1"#,
+        record_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for row in partner_rows {
+        let partner = Some(NamedId {
+            name: row.player_name,
+            id: row.player_id,
+        });
+
+        entries.push(AuditLogEntry {
+            time: row.time,
+            entry_id: row.audit_id,
+            id: record_id,
+            r#type: AuditLogEntryType::Modification(RecordModificationData {
+                progress: None,
+                video: None,
+                status: None,
+                player: None,
+                demon: None,
+                partner_added: if row.added { partner.clone() } else { None },
+                partner_removed: if row.added { None } else { partner },
+            }),
+            user: NamedId {
+                name: row.username,
+                id: row.userid,
+            },
+        })
+    }
+
+    // stable sort, so the addition stays in front of modifications made in the same transaction
+    entries.sort_by_key(|entry| entry.time);
+
     let deletion_row = sqlx::query!(
         r#"SELECT time, audit_id, 
                   userid,
@@ -3,7 +3,11 @@ use crate::{
     error::{DemonlistError, Result},
     nationality::Nationality,
     player::DatabasePlayer,
-    record::{FullRecord, MinimalRecordD, MinimalRecordP, RecordStatus},
+    record::{
+        partner::{partners_of, partners_on},
+        rejection::RejectionReason,
+        FullRecord, MinimalRecordD, MinimalRecordP, RecordStatus,
+    },
     submitter::Submitter,
 };
 use futures::stream::StreamExt;
@@ -12,6 +16,7 @@ use sqlx::{Error, PgConnection};
 // Required until https://github.com/launchbadge/sqlx/pull/108 is merged
 struct FetchedRecord {
     progress: i16,
+    completion_time: Option<i32>,
     video: Option<String>,
     status: String,
     player_id: i32,
@@ -22,6 +27,9 @@ struct FetchedRecord {
     position: i16,
     submitter_id: i32,
     submitter_banned: bool,
+    rejection_reason_id: Option<i32>,
+    rejection_reason_name: Option<String>,
+    rejection_reason_description: Option<String>,
 }
 
 impl FullRecord {
@@ -32,8 +40,10 @@ impl FullRecord {
 
         match result {
             Ok(row) => Ok(FullRecord {
+                partners: partners_of(id, &mut *connection).await?,
                 id,
                 progress: row.progress,
+                completion_time: row.completion_time,
                 video: row.video,
                 status: RecordStatus::from_sql(&row.status),
                 player: DatabasePlayer {
@@ -50,6 +60,10 @@ impl FullRecord {
                     id: row.submitter_id,
                     banned: row.submitter_banned,
                 }),
+                rejection_reason: match (row.rejection_reason_id, row.rejection_reason_name, row.rejection_reason_description) {
+                    (Some(id), Some(name), Some(description)) => Some(RejectionReason { id, name, description }),
+                    _ => None,
+                },
             }),
 
             Err(Error::RowNotFound) => Err(DemonlistError::RecordNotFound { record_id: id }),
@@ -58,11 +72,14 @@ impl FullRecord {
     }
 }
 
+/// Gets all approved records the given player is credited with, either as the record's player or as
+/// one of its partners
 pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<MinimalRecordD>> {
     let mut stream = sqlx::query!(
-        r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, demons.id AS demon_id, 
+        r#"SELECT records.id, progress, completion_time, CASE WHEN players.link_banned THEN NULL ELSE records.video::text END, demons.id AS demon_id, 
          demons.name as "name: String", demons.position FROM records INNER JOIN demons ON records.demon = demons.id INNER JOIN players ON players.id 
-         = $1 WHERE status_ = 'APPROVED' AND records.player = $1"#,
+         = records.player WHERE status_ = 'APPROVED' AND (records.player = $1 OR EXISTS (SELECT 1 FROM record_partners WHERE record_partners.record 
+         = records.id AND record_partners.player = $1))"#,
         player.id
     )
     .fetch(connection);
@@ -75,6 +92,7 @@ pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgCon
         records.push(MinimalRecordD {
             id: row.id,
             progress: row.progress,
+            completion_time: row.completion_time,
             video: row.video,
             status: RecordStatus::Approved,
             demon: MinimalDemon {
@@ -88,10 +106,13 @@ pub async fn approved_records_by(player: &DatabasePlayer, connection: &mut PgCon
     Ok(records)
 }
 
+/// Gets all approved records on the given demon, best first. For demons ranked by completion time,
+/// that means fastest first
 pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnection) -> Result<Vec<MinimalRecordP>> {
     struct Fetched {
         id: i32,
         progress: i16,
+        completion_time: Option<i32>,
         video: Option<String>,
         player_id: i32,
         name: String,
@@ -100,11 +121,13 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
         iso_country_code: Option<String>,
     }
 
+    let mut partners = partners_on(demon.id, &mut *connection).await?;
+
     let mut stream = sqlx::query_as!(
         Fetched,
-        r#"SELECT records.id, progress, CASE WHEN players.link_banned THEN NULL ELSE video::text END, players.id AS player_id, 
+        r#"SELECT records.id, progress, completion_time, CASE WHEN players.link_banned THEN NULL ELSE video::text END, players.id AS player_id, 
          players.name AS "name: String", players.banned, nation::TEXT, iso_country_code::TEXT FROM records INNER JOIN players ON records.player = players.id LEFT OUTER JOIN nationalities ON nationality = iso_country_code WHERE status_ = 'APPROVED' AND 
-         records.demon = $1 ORDER BY progress DESC, id ASC"#,
+         records.demon = $1 ORDER BY progress DESC, completion_time ASC NULLS LAST, id ASC"#,
         demon.id
     )
     .fetch(connection);
@@ -117,6 +140,7 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
         records.push(MinimalRecordP {
             id: row.id,
             progress: row.progress,
+            completion_time: row.completion_time,
             video: row.video,
             status: RecordStatus::Approved,
             player: DatabasePlayer {
@@ -124,6 +148,7 @@ pub async fn approved_records_on(demon: &MinimalDemon, connection: &mut PgConnec
                 name: row.name,
                 banned: row.banned,
             },
+            partners: partners.remove(&row.id).unwrap_or_default(),
             nationality: match (row.nation, row.iso_country_code) {
                 (Some(nation), Some(code)) => Some(Nationality {
                     iso_country_code: code,
@@ -0,0 +1,123 @@
+//! Module for the progress history of (player, demon)-pairs
+//!
+//! Since an approved (player, demon)-record is unique, approving a record with more progress (or a
+//! faster completion time) removes the previously approved one. To not lose track of a player's
+//! progression on a demon (say 60% → 85% → 100%), the superseded record is archived as a
+//! [`ProgressHistoryEntry`] before it is removed. History entries never count towards a player's
+//! score.
+
+use crate::{demon::MinimalDemon, error::Result, player::DatabasePlayer};
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
+pub struct ProgressHistoryEntry {
+    /// The id of the record this progress was approved as. That record most likely doesn't exist
+    /// anymore
+    pub record_id: i32,
+    pub demon: MinimalDemon,
+    pub progress: i16,
+    pub completion_time: Option<i32>,
+    pub video: Option<String>,
+
+    /// The point in time at which a better record of the same player on the same demon was approved
+    pub superseded_at: NaiveDateTime,
+}
+
+/// Gets the progress history of the given player on all demons, ordered by demon position and
+/// oldest first
+pub async fn progress_history_of(player: &DatabasePlayer, connection: &mut PgConnection) -> Result<Vec<ProgressHistoryEntry>> {
+    let mut stream = sqlx::query!(
+        r#"SELECT record, progress, completion_time, CASE WHEN players.link_banned THEN NULL ELSE record_progress_history.video::text END,
+         superseded_at, demons.id AS demon_id, demons.name AS "name: String", demons.position FROM record_progress_history INNER JOIN demons
+         ON record_progress_history.demon = demons.id INNER JOIN players ON players.id = record_progress_history.player WHERE
+         record_progress_history.player = $1 ORDER BY demons.position, superseded_at, record_progress_history.id"#,
+        player.id
+    )
+    .fetch(connection);
+
+    let mut history = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        history.push(ProgressHistoryEntry {
+            record_id: row.record,
+            demon: MinimalDemon {
+                id: row.demon_id,
+                position: row.position,
+                name: row.name,
+            },
+            progress: row.progress,
+            completion_time: row.completion_time,
+            video: row.video,
+            superseded_at: row.superseded_at,
+        })
+    }
+
+    Ok(history)
+}
+
+/// Archives all approved (player, demon)-records, other than the one with id `superseded_by`, with
+/// at most the given progress (and a completion time at least as slow as the given one)
+///
+/// Needs to be called right before these records are deleted in favor of the record with id
+/// `superseded_by`.
+pub(crate) async fn archive_superseded(
+    superseded_by: i32, player: i32, demon: i32, progress: i16, completion_time: Option<i32>, connection: &mut PgConnection,
+) -> Result<()> {
+    let archived = sqlx::query!(
+        "INSERT INTO record_progress_history (record, player, demon, progress, completion_time, video, superseded_by) SELECT id, $2, $3, \
+         progress, completion_time, video, $1 FROM records WHERE id <> $1 AND player = $2 AND demon = $3 AND status_ = 'APPROVED' AND \
+         progress <= $4 AND ($5::INTEGER IS NULL OR completion_time >= $5)",
+        superseded_by,
+        player,
+        demon,
+        progress,
+        completion_time
+    )
+    .execute(connection)
+    .await?;
+
+    if archived.rows_affected() > 0 {
+        info!(
+            "Archived {} approved records of player {} on demon {} superseded by record {}",
+            archived.rows_affected(),
+            player,
+            demon,
+            superseded_by
+        );
+    }
+
+    Ok(())
+}
+
+/// Archives the given progress of the approved record with the given id, which is about to be
+/// overridden by better progress (or a faster time) of the same player on the same demon
+///
+/// `superseded_by` is the record replacing the archived one, or `None` if the record itself is
+/// being improved.
+pub(crate) async fn archive_progress(
+    record_id: i32, player: i32, demon: i32, progress: i16, completion_time: Option<i32>, video: Option<&str>, superseded_by: Option<i32>,
+    connection: &mut PgConnection,
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO record_progress_history (record, player, demon, progress, completion_time, video, superseded_by) VALUES ($1, $2, $3, \
+         $4, $5, $6::TEXT, $7)",
+        record_id,
+        player,
+        demon,
+        progress,
+        completion_time,
+        video,
+        superseded_by
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+//! Module for the metadata of record videos
+//!
+//! After a record with a video is submitted, a background job requests metadata about the video from
+//! its host (via [oEmbed](https://oembed.com), where supported), and stores it alongside the record.
+//! Rather than deleting submissions whose video looks suspicious, the metadata carries
+//! [`VideoFlag`]s pointing list helpers at potential problems.
+
+use crate::{
+    error::{DemonlistError, Result},
+    record::FullRecord,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoFlag {
+    /// The video was uploaded before the demon was added to the list
+    UploadedBeforeDemon,
+
+    /// The video was uploaded by a different channel than the one linked to the account claiming
+    /// the record's player
+    ChannelMismatch,
+
+    /// The video's host reports that the video doesn't exist, or isn't public
+    VideoUnavailable,
+}
+
+impl VideoFlag {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            VideoFlag::UploadedBeforeDemon => "UPLOADED_BEFORE_DEMON",
+            VideoFlag::ChannelMismatch => "CHANNEL_MISMATCH",
+            VideoFlag::VideoUnavailable => "VIDEO_UNAVAILABLE",
+        }
+    }
+
+    fn from_sql(flag: &str) -> Self {
+        match flag {
+            "UPLOADED_BEFORE_DEMON" => VideoFlag::UploadedBeforeDemon,
+            "CHANNEL_MISMATCH" => VideoFlag::ChannelMismatch,
+            "VIDEO_UNAVAILABLE" => VideoFlag::VideoUnavailable,
+            _ => panic!("invalid video flag: {}", flag),
+        }
+    }
+}
+
+/// The parts of an oEmbed response we are interested in
+///
+/// `upload_date` and `duration` aren't part of the oEmbed specification, but are reported by some
+/// hosts (for instance Vimeo).
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct OEmbedResponse {
+    #[serde(default)]
+    pub title: Option<String>,
+
+    #[serde(default)]
+    pub author_name: Option<String>,
+
+    #[serde(default)]
+    pub author_url: Option<String>,
+
+    #[serde(default)]
+    pub upload_date: Option<String>,
+
+    /// In seconds
+    #[serde(default)]
+    pub duration: Option<i32>,
+}
+
+impl OEmbedResponse {
+    /// The upload date, if reported in any of the formats `2023-12-10 12:00:00`,
+    /// `2023-12-10T12:00:00+00:00` or `2023-12-10`
+    pub fn uploaded_at(&self) -> Option<NaiveDateTime> {
+        let upload_date = self.upload_date.as_deref()?;
+
+        NaiveDateTime::parse_from_str(upload_date, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .or_else(|| DateTime::parse_from_rfc3339(upload_date).ok().map(|date| date.naive_utc()))
+            .or_else(|| NaiveDate::parse_from_str(upload_date, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Clone)]
+pub struct VideoMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub author_url: Option<String>,
+    pub uploaded_at: Option<NaiveDateTime>,
+
+    /// The length of the video, in seconds
+    pub duration: Option<i32>,
+    pub flags: Vec<VideoFlag>,
+    pub fetched_at: NaiveDateTime,
+}
+
+impl VideoMetadata {
+    /// Gets the metadata of the given record's video, erroring out if it hasn't been fetched (yet)
+    pub async fn of_record(record_id: i32, connection: &mut PgConnection) -> Result<VideoMetadata> {
+        let row = sqlx::query!(
+            r#"SELECT title, author, author_url, uploaded_at, duration, flags::text[] AS "flags!", fetched_at FROM record_video_metadata
+             WHERE record = $1"#,
+            record_id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(DemonlistError::VideoMetadataNotFound { record_id })?;
+
+        Ok(VideoMetadata {
+            title: row.title,
+            author: row.author,
+            author_url: row.author_url,
+            uploaded_at: row.uploaded_at,
+            duration: row.duration,
+            flags: row.flags.iter().map(|flag| VideoFlag::from_sql(flag)).collect(),
+            fetched_at: row.fetched_at,
+        })
+    }
+
+    /// Stores the metadata reported for the given record's video, flagging anything suspicious
+    ///
+    /// `None` means the video's host reported the video as unavailable. Replaces any previously
+    /// stored metadata.
+    pub async fn store(record: &FullRecord, response: Option<OEmbedResponse>, connection: &mut PgConnection) -> Result<VideoMetadata> {
+        let mut flags = Vec::new();
+        let response = match response {
+            Some(response) => response,
+            None => {
+                flags.push(VideoFlag::VideoUnavailable);
+
+                OEmbedResponse::default()
+            },
+        };
+        let uploaded_at = response.uploaded_at();
+
+        if let Some(uploaded_at) = uploaded_at {
+            let demon_added = sqlx::query!("SELECT MIN(time) AS added FROM demon_additions WHERE id = $1", record.demon.id)
+                .fetch_one(&mut *connection)
+                .await?
+                .added;
+
+            if matches!(demon_added, Some(added) if uploaded_at < added) {
+                flags.push(VideoFlag::UploadedBeforeDemon);
+            }
+        }
+
+        if let Some(ref author_url) = response.author_url {
+            let channel = sqlx::query!(
+                "SELECT members.youtube_channel FROM members INNER JOIN player_claims ON members.member_id = player_claims.member_id WHERE \
+                 player_claims.player_id = $1 AND player_claims.verified",
+                record.player.id
+            )
+            .fetch_optional(&mut *connection)
+            .await?
+            .and_then(|row| row.youtube_channel);
+
+            if matches!(channel, Some(channel) if normalize_channel(&channel) != normalize_channel(author_url)) {
+                flags.push(VideoFlag::ChannelMismatch);
+            }
+        }
+
+        if !flags.is_empty() {
+            info!("Flagging video of record {} with {:?}", record, flags);
+        }
+
+        let sql_flags = flags.iter().map(|flag| flag.to_sql().to_string()).collect::<Vec<_>>();
+        let fetched_at = sqlx::query!(
+            "INSERT INTO record_video_metadata (record, title, author, author_url, uploaded_at, duration, flags) VALUES ($1, $2, $3, $4, \
+             $5, $6, $7::text[]::video_flag[]) ON CONFLICT (record) DO UPDATE SET title = EXCLUDED.title, author = EXCLUDED.author, \
+             author_url = EXCLUDED.author_url, uploaded_at = EXCLUDED.uploaded_at, duration = EXCLUDED.duration, flags = EXCLUDED.flags, \
+             fetched_at = EXCLUDED.fetched_at RETURNING fetched_at",
+            record.id,
+            response.title,
+            response.author_name,
+            response.author_url,
+            uploaded_at,
+            response.duration,
+            &sql_flags[..]
+        )
+        .fetch_one(connection)
+        .await?
+        .fetched_at;
+
+        Ok(VideoMetadata {
+            title: response.title,
+            author: response.author_name,
+            author_url: response.author_url,
+            uploaded_at,
+            duration: response.duration,
+            flags,
+            fetched_at,
+        })
+    }
+}
+
+/// Strips everything from a channel link that doesn't change which channel it points to
+fn normalize_channel(channel: &str) -> String {
+    let lowercase = channel.trim().trim_end_matches('/').to_lowercase();
+    let channel = lowercase
+        .strip_prefix("https://")
+        .or_else(|| lowercase.strip_prefix("http://"))
+        .unwrap_or(&lowercase);
+
+    channel
+        .strip_prefix("www.")
+        .or_else(|| channel.strip_prefix("m."))
+        .unwrap_or(channel)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_channel, OEmbedResponse};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_normalize_channel() {
+        assert_eq!(
+            normalize_channel("https://www.youtube.com/@stardust1971/"),
+            normalize_channel("http://youtube.com/@Stardust1971")
+        );
+        assert_ne!(
+            normalize_channel("https://www.youtube.com/@stardust1971"),
+            normalize_channel("https://www.youtube.com/@stardust1972")
+        );
+    }
+
+    #[test]
+    fn test_upload_date_formats() {
+        let expected = NaiveDate::from_ymd_opt(2013, 6, 12).unwrap().and_hms_opt(14, 44, 8);
+
+        for upload_date in ["2013-06-12 14:44:08", "2013-06-12T14:44:08+00:00"] {
+            let response = OEmbedResponse {
+                upload_date: Some(upload_date.to_string()),
+                ..Default::default()
+            };
+
+            assert_eq!(response.uploaded_at(), expected);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 //! Module containing all code relating to records on the demonlist
 //!
-//! Each record can have one of four statuses, 'approved', 'rejected', 'under consideration' or
-//! 'submitted'. We will call a record of some player on some demon a (player, demon)-record.
+//! Each record can have one of five statuses, 'approved', 'rejected', 'under consideration',
+//! 'needs info' or 'submitted'. We will call a record of some player on some demon a (player, demon)-record.
 //! We call a (player, demon)-record R _unique_ iff all other records by that player on the demon
 //! have a different status than R. We call it _globally unique_ if R is the only record, regardless
 //! of state, of player on demon.
@@ -9,7 +9,9 @@
 //! * 'approved' means that the record shows up on the demonlist and that further submissions for
 //!   this (player, demon) pair are only allowed with a different video and higher progress. An
 //!   approved record is unique. Whenever a record becomes 'accepted', all 'submitted' or 'under
-//!   consideration' records with lower progress are removed.
+//!   consideration' records with lower progress are removed. A previously approved record with
+//!   lower progress is removed as well, but kept in the (player, demon)'s [progress
+//!   history](history).
 //! * 'rejected' means that the record doesn't show up on the demonlist and that further submissions
 //!   with that (player, demon) pair or that video will not be permitted. A rejected record is
 //!   globally unique
@@ -20,31 +22,48 @@
 //! * 'under consideration' means essentially the same as 'submitted', only that all further
 //!   submissions for this (demon, player) tuple are disallowed. Note that this does not mean that
 //!   the 'under consideration' status makes. A record under consideration IS NOT UNIQUE!
+//! * 'needs info' means essentially the same as 'submitted', only that list staff has asked the
+//!   submitter to provide additional information (such as raw footage) before the record can be
+//!   checked. A record that needs info IS NOT UNIQUE!
+//!
+//! Which status changes are possible is configured via the [`transition`] module. Rejected records
+//! can carry a [`RejectionReason`](rejection::RejectionReason).
 
 pub use self::{
     get::{approved_records_by, approved_records_on},
     paginate::RecordPagination,
+    partner::{partners_of, PostPartner},
     patch::PatchRecord,
     post::Submission,
 };
-use crate::{demon::MinimalDemon, error::Result, nationality::Nationality, player::DatabasePlayer, submitter::Submitter};
+use crate::{
+    demon::MinimalDemon, error::Result, nationality::Nationality, player::DatabasePlayer, record::rejection::RejectionReason,
+    submitter::Submitter,
+};
 use derive_more::Display;
 use pointercrate_core::etag::Taggable;
+use schemars::JsonSchema;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject},
+    JsonSchema,
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sqlx::PgConnection;
-use std::{
-    collections::hash_map::DefaultHasher,
-    fmt::{Display, Formatter},
-    hash::{Hash, Hasher},
-};
+use std::fmt::{Display, Formatter};
 
 pub mod audit;
 mod delete;
 mod get;
+pub mod history;
+pub mod metadata;
 pub mod note;
 mod paginate;
+mod partner;
 mod patch;
 mod post;
+pub mod rejection;
+pub mod transition;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
 pub enum RecordStatus {
@@ -52,6 +71,7 @@ pub enum RecordStatus {
     Approved,
     Rejected,
     UnderConsideration,
+    NeedsInfo,
 }
 
 impl RecordStatus {
@@ -61,16 +81,18 @@ impl RecordStatus {
             RecordStatus::Approved => "APPROVED",
             RecordStatus::Rejected => "REJECTED",
             RecordStatus::UnderConsideration => "UNDER_CONSIDERATION",
+            RecordStatus::NeedsInfo => "NEEDS_INFO",
         }
         .to_owned()
     }
 
-    fn from_sql(sql: &str) -> Self {
+    pub(crate) fn from_sql(sql: &str) -> Self {
         match sql {
             "SUBMITTED" => RecordStatus::Submitted,
             "APPROVED" => RecordStatus::Approved,
             "REJECTED" => RecordStatus::Rejected,
             "UNDER_CONSIDERATION" => RecordStatus::UnderConsideration,
+            "NEEDS_INFO" => RecordStatus::NeedsInfo,
             _ => panic!("invalid record state: {}", sql),
         }
     }
@@ -89,6 +111,7 @@ impl Display for RecordStatus {
             RecordStatus::Approved => write!(f, "approved"),
             RecordStatus::Rejected => write!(f, "rejected"),
             RecordStatus::UnderConsideration => write!(f, "under consideration"),
+            RecordStatus::NeedsInfo => write!(f, "needs info"),
         }
     }
 }
@@ -114,85 +137,117 @@ impl<'de> Deserialize<'de> for RecordStatus {
             "submitted" => Ok(RecordStatus::Submitted),
             "rejected" => Ok(RecordStatus::Rejected),
             "under consideration" => Ok(RecordStatus::UnderConsideration),
+            "needs info" => Ok(RecordStatus::NeedsInfo),
             _ => Err(serde::de::Error::invalid_value(
                 serde::de::Unexpected::Str(&string),
-                &"'approved', 'submitted', 'under consideration' or 'rejected'",
+                &"'approved', 'submitted', 'under consideration', 'needs info' or 'rejected'",
             )),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Display, Hash)]
+impl JsonSchema for RecordStatus {
+    fn schema_name() -> String {
+        "RecordStatus".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(vec![
+                "approved".into(),
+                "submitted".into(),
+                "rejected".into(),
+                "under consideration".into(),
+                "needs info".into(),
+            ]),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Display, Hash)]
 #[display(fmt = "{} {}% on {} (ID: {})", player, progress, demon, id)]
 pub struct FullRecord {
     pub id: i32,
     pub progress: i16,
+
+    /// The time (in milliseconds) the player took to complete the demon, if the demon is ranked by
+    /// completion time
+    pub completion_time: Option<i32>,
     pub video: Option<String>,
     pub status: RecordStatus,
     pub player: DatabasePlayer,
+
+    /// The other players credited with this record, for levels played by several players at once
+    pub partners: Vec<DatabasePlayer>,
+
     pub demon: MinimalDemon,
     pub submitter: Option<Submitter>,
+
+    /// Why this record was rejected. Always `None` for records that aren't rejected
+    pub rejection_reason: Option<RejectionReason>,
 }
 
 impl Taggable for FullRecord {
-    fn patch_part(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.id.hash(&mut hasher);
-        self.progress.hash(&mut hasher);
-        self.video.hash(&mut hasher);
-        self.status.hash(&mut hasher);
-        self.player.id.hash(&mut hasher);
-        self.demon.id.hash(&mut hasher);
-        // notes have sub-endpoint -> no hash
-        // submitter cannot be patched -> no hash
-        // raw footage cannot be patched -> no hash
-        hasher.finish()
-    }
+    // notes have sub-endpoint and aren't part of the serialized representation anyway. Partners are
+    // managed via their sub-endpoint
+    const UNPATCHABLE_FIELDS: &'static [&'static str] = &["submitter", "partners"];
+
+    // Moving the demon or renaming the player must not invalidate the ETags of their records
+    const REFERENCE_FIELDS: &'static [&'static str] = &["player", "demon", "rejection_reason"];
 }
 
-#[derive(Debug, Hash, Serialize, Display)]
+#[derive(Debug, Hash, Serialize, JsonSchema, Display)]
 #[display(fmt = "{} {}% on {} (ID: {})", player, progress, demon, id)]
 pub struct MinimalRecordPD {
     pub id: i32,
     pub progress: i16,
+
+    /// The time (in milliseconds) the player took to complete the demon, if the demon is ranked by
+    /// completion time
+    pub completion_time: Option<i32>,
     pub video: Option<String>,
     pub status: RecordStatus,
     pub demon: MinimalDemon,
     pub player: DatabasePlayer,
+
+    /// Why this record was rejected. Always `None` for records that aren't rejected
+    pub rejection_reason: Option<RejectionReason>,
 }
 
-#[derive(Debug, Hash, Serialize, Deserialize, Display, PartialEq, Eq)]
+#[derive(Debug, Hash, Serialize, Deserialize, JsonSchema, Display, PartialEq, Eq)]
 #[display(fmt = "{}% on {} (ID: {})", progress, demon, id)]
 pub struct MinimalRecordD {
     pub id: i32,
     pub progress: i16,
+
+    /// The time (in milliseconds) the player took to complete the demon, if the demon is ranked by
+    /// completion time
+    pub completion_time: Option<i32>,
     pub video: Option<String>,
     pub status: RecordStatus,
     pub demon: MinimalDemon,
 }
 
-#[derive(Debug, Hash, Serialize, Display, PartialEq, Eq)]
+#[derive(Debug, Hash, Serialize, JsonSchema, Display, PartialEq, Eq)]
 #[display(fmt = "{} - {}% (ID: {})", player, progress, id)]
 pub struct MinimalRecordP {
     pub id: i32,
     pub progress: i16,
+
+    /// The time (in milliseconds) the player took to complete the demon, if the demon is ranked by
+    /// completion time
+    pub completion_time: Option<i32>,
     pub video: Option<String>,
     pub status: RecordStatus,
     pub player: DatabasePlayer,
+    pub partners: Vec<DatabasePlayer>,
     pub nationality: Option<Nationality>,
 }
 
 impl FullRecord {
-    /// Gets the maximal and minimal submitter id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_record_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(id) AS "max_id!: i32", MIN(id) AS "min_id!: i32" FROM records"#)
-            .fetch_one(connection)
-            .await?; // FIXME: crashes on empty table
-        Ok((row.max_id, row.min_id))
-    }
-
     pub async fn was_modified(&self, connection: &mut PgConnection) -> Result<bool> {
         Ok(sqlx::query!(
             r#"SELECT EXISTS (SELECT 1 FROM record_modifications WHERE id = $1 AND status_ IS NOT NULL) AS "was_modified!: bool""#,
@@ -202,126 +257,4 @@ impl FullRecord {
         .await?
         .was_modified)
     }
-
-    /*
-    pub async fn validate(self, state: PointercrateState) {
-        let mut connection = match state.connection().await {
-            Ok(connection) => connection,
-            Err(err) => return error!("INTERNAL SERVER ERROR: failed to acquire database connection: {:?}", err),
-        };
-
-        let video = match self.video {
-            Some(ref video) => video,
-            None => return,
-        };
-
-        debug!("Verifying that submission {} with video {} actually is valid", self, video);
-
-        match state.http_client.head(video).send().await {
-            Ok(response) => {
-                let status = response.status().as_u16();
-
-                if status == 401 || status == 403 || status == 405 {
-                    // Some websites (billibilli) respond unfavorably to HEAD requests. Retry with
-                    // GET
-                    match state.http_client.get(video).send().await {
-                        Ok(response) => {
-                            let status = response.status().as_u16();
-
-                            if status >= 200 && status < 400 {
-                                debug!("HEAD request yielded some sort of successful response, executing webhook");
-
-                                self.execute_webhook(&state).await;
-                            }
-                        },
-                        Err(err) => {
-                            error!(
-                                "INTERNAL SERVER ERROR: HEAD request to verify video failed: {:?}. Deleting submission",
-                                err
-                            );
-
-                            match self.delete(&mut *connection).await {
-                                Ok(_) => (),
-                                Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-                            }
-                        },
-                    }
-                } else if status >= 200 && status < 400 {
-                    debug!("HEAD request yielded some sort of successful response, executing webhook");
-
-                    self.execute_webhook(&state).await;
-                } else {
-                    warn!("Server response to 'HEAD {}' was {:?}, deleting submission!", video, response);
-
-                    match self.delete(&mut *connection).await {
-                        Ok(_) => (),
-                        Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-                    }
-                }
-            },
-            Err(error) => {
-                error!(
-                    "INTERNAL SERVER ERROR: HEAD request to verify video failed: {:?}. Deleting submission",
-                    error
-                );
-
-                match self.delete(&mut *connection).await {
-                    Ok(_) => (),
-                    Err(error) => error!("INTERNAL SERVER ERROR: Failure to delete record - {:?}!", error),
-                }
-            },
-        }
-    }*/
-    /*
-    async fn execute_webhook(&self, state: &PointercrateState) {
-        if let Some(ref webhook_url) = state.webhook_url {
-            match state
-                .http_client
-                .post(&**webhook_url)
-                .header("Content-Type", "application/json")
-                .body(self.webhook_embed().to_string())
-                .send()
-                .await
-            {
-                Err(error) => error!("INTERNAL SERVER ERROR: Failure to execute discord webhook: {:?}", error),
-                Ok(_) => debug!("Successfully executed discord webhook"),
-            }
-        } else {
-            warn!("Trying to execute webhook, though no link was configured!");
-        }
-    }*/
-    /*
-    fn webhook_embed(&self) -> serde_json::Value {
-        let mut payload = json!({
-            "content": format!("**New record submitted! ID: {}**", self.id),
-            "embeds": [
-                {
-                    "type": "rich",
-                    "title": format!("{}% on {}", self.progress, self.demon.name),
-                    "description": format!("{} just got {}% on {}! Go add their record!", self.player.name, self.progress, self.demon.name),
-                    "footer": {
-                        "text": format!("This record has been submitted by submitter #{}", self.submitter.map(|s|s.id).unwrap_or(1))
-                    },
-                    "author": {
-                        "name": format!("{} (ID: {})", self.player.name, self.player.id),
-                        "url": self.video
-                    },
-                    "thumbnail": {
-                        "url": "https://cdn.discordapp.com/avatars/277391246035648512/b03c85d94dc02084c413a7fdbe2cea79.webp?size=1024"
-                    },
-                }
-            ]
-        });
-
-        if let Some(ref video) = self.video {
-            payload["embeds"][0]["fields"] = json! {
-                [{
-                    "name": "Video Proof:",
-                    "value": video
-                }]
-            };
-        }
-
-        payload
-    }*/
 }
@@ -5,14 +5,11 @@ mod post;
 
 pub use self::{get::notes_on, patch::PatchNote, post::NewNote};
 use pointercrate_core::etag::Taggable;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
 
-#[derive(Serialize, Deserialize, Debug, Hash)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Hash)]
 pub struct Note {
     pub id: i32,
 
@@ -39,9 +36,5 @@ pub struct Note {
 }
 
 impl Taggable for Note {
-    fn patch_part(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.content.hash(&mut hasher);
-        hasher.finish()
-    }
+    const UNPATCHABLE_FIELDS: &'static [&'static str] = &["transferred", "author", "editors"];
 }
@@ -3,10 +3,11 @@ use crate::{
     record::note::Note,
 };
 use pointercrate_core::util::non_nullable;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct PatchNote {
     #[serde(default, deserialize_with = "non_nullable")]
     pub content: Option<String>,
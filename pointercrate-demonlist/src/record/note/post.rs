@@ -2,10 +2,11 @@ use crate::{
     error::{DemonlistError, Result},
     record::{note::Note, FullRecord},
 };
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, JsonSchema, Debug)]
 pub struct NewNote {
     content: String,
 
@@ -1,18 +1,22 @@
 use crate::{
     demon::MinimalDemon,
-    error::Result,
+    error::DemonlistError,
     player::DatabasePlayer,
-    record::{MinimalRecordPD, RecordStatus},
+    record::{rejection::RejectionReason, MinimalRecordPD, RecordStatus},
 };
-use futures::StreamExt;
 use pointercrate_core::{
-    error::CoreError,
+    pagination::{clamp_i32, PaginationParameters, Paginator, DEFAULT_LIMIT},
     util::{non_nullable, nullable},
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgRow, PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
 
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Default)]
 pub struct RecordPagination {
     #[serde(default, deserialize_with = "non_nullable")]
     #[serde(rename = "before")]
@@ -62,41 +66,54 @@ pub struct RecordPagination {
 
     #[serde(default, deserialize_with = "non_nullable")]
     pub submitter: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    list: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    rejection_reason: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    under_review: Option<bool>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    video_flagged: Option<bool>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    broken_video: Option<bool>,
 }
 
-impl RecordPagination {
-    /// Retrieves the page of records matching the pagination data in here
-    ///
-    /// Note that this method returns _one more record than requested_. This is used as a quick and
-    /// dirty way to determine if further pages exist: If the additional record was returned, more
-    /// pages obviously exist. This additional object is the last in the returned vector.
-    ///
-    /// Additionally, if _before_ is set, but not _after_, the page is returned in reverse order
-    /// (the additional object stays the last)
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<MinimalRecordPD>> {
-        if let Some(limit) = self.limit {
-            if !(1..=100).contains(&limit) {
-                return Err(CoreError::InvalidPaginationLimit.into());
-            }
-        }
+impl Paginator for RecordPagination {
+    type Error = DemonlistError;
+    type Item = MinimalRecordPD;
 
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(CoreError::AfterSmallerBefore.into());
-            }
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
         }
+    }
 
-        let limit = self.limit.unwrap_or(50) as i32;
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        RecordPagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
+        }
+    }
 
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    fn pagination_key(item: &MinimalRecordPD) -> i64 {
+        item.id as i64
+    }
 
-        let query = format!(include_str!("../../sql/paginate_records.sql"), order);
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../sql/paginate_records.sql"), order)
+    }
 
-        let mut stream = sqlx::query(&query)
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
             .bind(self.before_id)
             .bind(self.after_id)
             .bind(self.progress)
@@ -112,32 +129,38 @@ impl RecordPagination {
             .bind(self.video == Some(None))
             .bind(self.player)
             .bind(self.submitter)
-            .bind(limit + 1)
-            .fetch(&mut *connection);
-
-        let mut records = Vec::new();
-
-        while let Some(row) = stream.next().await {
-            let row: PgRow = row?;
-
-            records.push(MinimalRecordPD {
-                id: row.try_get("id")?,
-                progress: row.try_get("progress")?,
-                video: row.try_get("video")?,
-                status: RecordStatus::from_sql(&row.try_get::<String, _>("status")?),
-                player: DatabasePlayer {
-                    id: row.try_get("player_id")?,
-                    name: row.try_get("player_name")?,
-                    banned: row.try_get("player_banned")?,
-                },
-                demon: MinimalDemon {
-                    id: row.try_get("demon_id")?,
-                    position: row.try_get("position")?,
-                    name: row.try_get("demon_name")?,
-                },
-            })
-        }
+            .bind(self.list)
+            .bind(self.rejection_reason)
+            .bind(self.under_review)
+            .bind(self.video_flagged)
+            .bind(self.broken_video)
+    }
 
-        Ok(records)
+    fn from_row(row: &PgRow) -> Result<MinimalRecordPD, sqlx::Error> {
+        Ok(MinimalRecordPD {
+            id: row.try_get("id")?,
+            progress: row.try_get("progress")?,
+            completion_time: row.try_get("completion_time")?,
+            video: row.try_get("video")?,
+            status: RecordStatus::from_sql(&row.try_get::<String, _>("status")?),
+            player: DatabasePlayer {
+                id: row.try_get("player_id")?,
+                name: row.try_get("player_name")?,
+                banned: row.try_get("player_banned")?,
+            },
+            demon: MinimalDemon {
+                id: row.try_get("demon_id")?,
+                position: row.try_get("position")?,
+                name: row.try_get("demon_name")?,
+            },
+            rejection_reason: match row.try_get::<Option<i32>, _>("rejection_reason_id")? {
+                Some(id) => Some(RejectionReason {
+                    id,
+                    name: row.try_get("rejection_reason_name")?,
+                    description: row.try_get("rejection_reason_description")?,
+                }),
+                None => None,
+            },
+        })
     }
 }
@@ -0,0 +1,150 @@
+//! Module for the additional players credited with a record
+//!
+//! Levels played by several players at once (such as 2-player levels) result in a single record
+//! credited to all of them. The record's `player` is the player the record was submitted for, all
+//! other credited players are the record's _partners_. The (player, demon)-invariants described in
+//! the [record module documentation](super) are only upheld for the record's `player`. However,
+//! no player is ever credited with more than one approved record on the same demon.
+
+use crate::{
+    error::{DemonlistError, Result},
+    player::DatabasePlayer,
+    record::FullRecord,
+};
+use futures::StreamExt;
+use log::info;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use sqlx::PgConnection;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PostPartner {
+    pub player: String,
+}
+
+/// Gets the partners of the record with the given id
+pub async fn partners_of(record_id: i32, connection: &mut PgConnection) -> Result<Vec<DatabasePlayer>> {
+    let mut stream = sqlx::query!(
+        r#"SELECT players.id, players.name AS "name: String", players.banned FROM players INNER JOIN record_partners ON players.id =
+         record_partners.player WHERE record_partners.record = $1 ORDER BY players.id"#,
+        record_id
+    )
+    .fetch(connection);
+
+    let mut partners = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        partners.push(DatabasePlayer {
+            id: row.id,
+            name: row.name,
+            banned: row.banned,
+        })
+    }
+
+    Ok(partners)
+}
+
+/// Gets the partners of all approved records on the demon with the given id, keyed by record id
+pub(crate) async fn partners_on(demon_id: i32, connection: &mut PgConnection) -> Result<HashMap<i32, Vec<DatabasePlayer>>> {
+    let mut stream = sqlx::query!(
+        r#"SELECT record_partners.record, players.id, players.name AS "name: String", players.banned FROM players INNER JOIN record_partners
+         ON players.id = record_partners.player INNER JOIN records ON records.id = record_partners.record WHERE records.demon = $1 AND
+         records.status_ = 'APPROVED' ORDER BY players.id"#,
+        demon_id
+    )
+    .fetch(connection);
+
+    let mut partners: HashMap<i32, Vec<DatabasePlayer>> = HashMap::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        partners.entry(row.record).or_default().push(DatabasePlayer {
+            id: row.id,
+            name: row.name,
+            banned: row.banned,
+        })
+    }
+
+    Ok(partners)
+}
+
+/// Makes sure that none of the given players is credited with an approved record on the given demon
+/// (other than the record with the given id), neither as the record's player nor as one of its
+/// partners.
+///
+/// Approving a (player, demon)-record merges it with the player's existing approved record, but
+/// partners are credited with a record without it being theirs, so there is nothing to merge with.
+/// Crediting a player twice would count both records towards their score, so we error out instead.
+pub(crate) async fn ensure_not_credited(players: &[i32], demon_id: i32, record_id: i32, connection: &mut PgConnection) -> Result<()> {
+    let credited = sqlx::query!(
+        r#"SELECT id AS "record!", player AS "player!" FROM records WHERE demon = $1 AND status_ = 'APPROVED' AND id <> $2 AND player = ANY($3)
+         UNION ALL SELECT records.id, record_partners.player FROM record_partners INNER JOIN records ON records.id = record_partners.record
+         WHERE records.demon = $1 AND records.status_ = 'APPROVED' AND records.id <> $2 AND record_partners.player = ANY($3) LIMIT 1"#,
+        demon_id,
+        record_id,
+        players
+    )
+    .fetch_optional(connection)
+    .await?;
+
+    match credited {
+        Some(row) => Err(DemonlistError::AlreadyCredited {
+            player_id: row.player,
+            record_id: row.record,
+        }),
+        None => Ok(()),
+    }
+}
+
+impl FullRecord {
+    /// Credits the given player with this record as well
+    pub async fn add_partner(&mut self, player: DatabasePlayer, connection: &mut PgConnection) -> Result<()> {
+        if player.banned {
+            return Err(DemonlistError::PlayerBanned);
+        }
+
+        if player.id == self.player.id {
+            return Err(DemonlistError::DuplicatePartner);
+        }
+
+        if self.partners.iter().any(|partner| partner.id == player.id) {
+            return Err(DemonlistError::PartnerExists);
+        }
+
+        ensure_not_credited(&[player.id], self.demon.id, self.id, &mut *connection).await?;
+
+        info!("Adding {} as partner to record {}", player, self);
+
+        sqlx::query!("INSERT INTO record_partners (record, player) VALUES ($1, $2)", self.id, player.id)
+            .execute(connection)
+            .await?;
+
+        self.partners.push(player);
+
+        Ok(())
+    }
+
+    /// Removes the player with the given id from this record's partners
+    pub async fn remove_partner(&mut self, player_id: i32, connection: &mut PgConnection) -> Result<()> {
+        if !self.partners.iter().any(|partner| partner.id == player_id) {
+            return Err(DemonlistError::PartnerNotFound {
+                record_id: self.id,
+                player_id,
+            });
+        }
+
+        info!("Removing partner {} from record {}", player_id, self);
+
+        sqlx::query!("DELETE FROM record_partners WHERE record = $1 AND player = $2", self.id, player_id)
+            .execute(connection)
+            .await?;
+
+        self.partners.retain(|partner| partner.id != player_id);
+
+        Ok(())
+    }
+}
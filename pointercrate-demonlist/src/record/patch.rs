@@ -1,28 +1,37 @@
 use crate::{
-    demon::MinimalDemon,
+    demon::{MinimalDemon, RecordKind},
     error::{DemonlistError, Result},
     player::DatabasePlayer,
-    record::{FullRecord, RecordStatus},
+    record::{history, partner, rejection::RejectionReason, transition::StatusTransition, FullRecord, RecordStatus},
 };
 use log::{info, warn};
 use pointercrate_core::{
     error::CoreError,
     util::{non_nullable, nullable},
 };
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct PatchRecord {
     #[serde(default, deserialize_with = "non_nullable")]
     progress: Option<i16>,
 
+    /// The completion time in milliseconds. Can only be set on records on demons ranked by time
+    #[serde(default, deserialize_with = "non_nullable")]
+    completion_time: Option<i32>,
+
     #[serde(default, deserialize_with = "nullable")]
     video: Option<Option<String>>,
 
     #[serde(default, deserialize_with = "non_nullable")]
     status: Option<RecordStatus>,
 
+    /// The id of the [`RejectionReason`] for this record. Can only be set on rejected records
+    #[serde(default, deserialize_with = "nullable")]
+    rejection_reason: Option<Option<i32>>,
+
     #[serde(default, deserialize_with = "non_nullable")]
     player: Option<String>,
 
@@ -42,6 +51,10 @@ impl FullRecord {
             self.set_progress(progress, connection).await?;
         }
 
+        if let Some(completion_time) = data.completion_time {
+            self.set_completion_time(completion_time, connection).await?;
+        }
+
         if let Some(video) = data.video {
             match video {
                 None => self.delete_video(connection).await?,
@@ -53,6 +66,15 @@ impl FullRecord {
             self.set_status(status, connection).await?
         }
 
+        if let Some(rejection_reason) = data.rejection_reason {
+            let rejection_reason = match rejection_reason {
+                Some(reason_id) => Some(RejectionReason::by_id(reason_id, connection).await?),
+                None => None,
+            };
+
+            self.set_rejection_reason(rejection_reason, connection).await?;
+        }
+
         if let Some(player) = data.player {
             let player = DatabasePlayer::by_name_or_create(player.as_ref(), connection).await?;
 
@@ -111,59 +133,73 @@ impl FullRecord {
             RecordStatus::Approved => {
                 // In this case we have to do multiple things:
                 // * delete all (player, demon)-records that are 'rejected' (at most one) TODO: maybe reconsider?
-                // * if a (player, demon)-record exists that is 'approved' and has higher progress than this one, we
-                //   override our progress and video with the values of that record
+                // * if a (player, demon)-record exists that is 'approved' and has higher progress (or a faster
+                //   completion time) than this one, we override our progress, time and video with the values of that
+                //   record
                 // * delete all (player, demon)-records that are 'submitted' with a progress (potentially as
-                //   determined above) less than or equal to that of this record
+                //   determined above) less than or equal to that of this record (or a time at least as slow)
 
                 struct _Existing {
                     id: i32,
                     progress: i16,
+                    completion_time: Option<i32>,
                     video: Option<String>,
                 }
 
                 let row = sqlx::query_as!(
                     _Existing,
-                    "SELECT id, progress, video::TEXT FROM records WHERE status_ = 'APPROVED' AND demon = $1 AND player = $2 AND progress \
-                     > $3",
+                    "SELECT id, progress, completion_time, video::TEXT FROM records WHERE status_ = 'APPROVED' AND demon = $1 AND player = \
+                     $2 AND (progress > $3 OR completion_time < $4)",
                     demon,
                     player,
-                    self.progress
+                    self.progress,
+                    self.completion_time
                 )
                 .fetch_optional(&mut *connection)
                 .await?;
 
                 if let Some(row) = row {
+                    // The existing record is replaced by this one, which takes over its progress. Neither progress is
+                    // superseded: the existing one lives on in this record, and this record's own progress was never
+                    // approved for this (player, demon)-tuple, so nothing goes into the history.
                     sqlx::query!("DELETE FROM records WHERE id = $1", row.id)
                         .execute(&mut *connection)
                         .await?;
-                    sqlx::query("UPDATE records SET video = $1::TEXT, progress = $2 WHERE id = $3")
+                    sqlx::query("UPDATE records SET video = $1::TEXT, progress = $2, completion_time = $3 WHERE id = $4")
                         .bind(&row.video)
                         .bind(row.progress)
+                        .bind(row.completion_time)
                         .bind(self.id)
                         .execute(&mut *connection)
                         .await?;
 
                     self.progress = row.progress;
+                    self.completion_time = row.completion_time;
                     self.video = row.video;
                 }
 
                 let notes_transferred = sqlx::query!(
                     "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND records.demon = $2 AND \
-                     records.player = $3 AND (records.status_ = 'REJECTED' OR records.progress <= $4)",
+                     records.player = $3 AND (records.status_ = 'REJECTED' OR (records.progress <= $4 AND ($5::INTEGER IS NULL OR \
+                     records.completion_time >= $5)))",
                     self.id,
                     demon,
                     player,
-                    self.progress
+                    self.progress,
+                    self.completion_time
                 )
                 .execute(&mut *connection)
                 .await?;
 
+                history::archive_superseded(self.id, player, demon, self.progress, self.completion_time, &mut *connection).await?;
+
                 let records_deleted = sqlx::query!(
-                    "DELETE FROM records WHERE demon = $1 AND player = $2 AND (status_ = 'REJECTED' OR progress <= $3)",
+                    "DELETE FROM records WHERE demon = $1 AND player = $2 AND (status_ = 'REJECTED' OR (progress <= $3 AND ($4::INTEGER IS \
+                     NULL OR completion_time >= $4)))",
                     demon,
                     player,
-                    self.progress
+                    self.progress,
+                    self.completion_time
                 )
                 .execute(connection)
                 .await?;
@@ -178,7 +214,7 @@ impl FullRecord {
                 );
             },
             // Nothing needed to be done here!
-            RecordStatus::Submitted | RecordStatus::UnderConsideration => {},
+            RecordStatus::Submitted | RecordStatus::UnderConsideration | RecordStatus::NeedsInfo => {},
         }
 
         Ok(())
@@ -209,6 +245,11 @@ impl FullRecord {
         }
 
         sqlx::query!("UPDATE records SET video = $1::text WHERE id = $2", video, self.id)
+            .execute(&mut *connection)
+            .await?;
+
+        // The metadata was fetched for the old video
+        sqlx::query!("DELETE FROM record_video_metadata WHERE record = $1", self.id)
             .execute(connection)
             .await?;
 
@@ -224,8 +265,20 @@ impl FullRecord {
             return Err(DemonlistError::InvalidProgress { requirement });
         }
 
+        match (demon.record_kind(connection).await?, self.completion_time) {
+            (RecordKind::Percentage, Some(_)) => return Err(DemonlistError::UnexpectedCompletionTime),
+            (RecordKind::Time, None) => return Err(DemonlistError::InvalidCompletionTime),
+            _ => (),
+        }
+
         self.ensure_invariants(self.player.id, self.demon.id, connection).await?;
 
+        if self.status == RecordStatus::Approved {
+            let partners = self.partners.iter().map(|partner| partner.id).collect::<Vec<_>>();
+
+            partner::ensure_not_credited(&partners, demon.id, self.id, &mut *connection).await?;
+        }
+
         sqlx::query!("UPDATE records SET demon = $1 WHERE id = $2", demon.id, self.id)
             .execute(connection)
             .await?;
@@ -248,6 +301,15 @@ impl FullRecord {
 
         self.ensure_invariants(player.id, self.demon.id, connection).await?;
 
+        // A player cannot be credited twice with the same record
+        if self.partners.iter().any(|partner| partner.id == player.id) {
+            self.remove_partner(player.id, &mut *connection).await?;
+        }
+
+        if self.status == RecordStatus::Approved {
+            partner::ensure_not_credited(&[player.id], self.demon.id, self.id, &mut *connection).await?;
+        }
+
         sqlx::query!("UPDATE records SET player = $1 WHERE id = $2", player.id, self.id)
             .execute(connection)
             .await?;
@@ -258,7 +320,22 @@ impl FullRecord {
     }
 
     /// Updates this record's status
+    ///
+    /// Errors out if the status change is not allowed by the configured
+    /// [`StatusTransition`](super::transition::StatusTransition)s. Leaving the 'rejected' status
+    /// clears the record's rejection reason.
     pub async fn set_status(&mut self, status: RecordStatus, connection: &mut PgConnection) -> Result<()> {
+        if status == self.status {
+            return Ok(());
+        }
+
+        if !StatusTransition::is_allowed(self.status, status, &mut *connection).await? {
+            return Err(DemonlistError::InvalidStatusTransition {
+                from: self.status,
+                to: status,
+            });
+        }
+
         // To uphold the invariants outlined in the module documentation, we need to do some preparations.
         // What preparation has to be done, depends on what the current and new status are.
         match (self.status, status) {
@@ -291,42 +368,66 @@ impl FullRecord {
             // Nothing needed here, a 'rejected' record is globally unique
             (RecordStatus::Rejected, _) => (),
 
-            (RecordStatus::Submitted, RecordStatus::Approved) | (RecordStatus::UnderConsideration, RecordStatus::Approved) => {
+            (RecordStatus::Submitted, RecordStatus::Approved)
+            | (RecordStatus::UnderConsideration, RecordStatus::Approved)
+            | (RecordStatus::NeedsInfo, RecordStatus::Approved) => {
                 // Since a rejected record is globally unique, we know no other (player,
                 // demon)-record is 'rejected'. We also know that the submission has at least as
-                // much progress as an 'accepted' (player, demon)-record. We can therefore just
-                // delete all other records with less or equal progress to the current one
+                // much progress as an 'accepted' (player, demon)-record (or is at least as fast). We can
+                // therefore just delete all other records with less or equal progress to the current
+                // one (or a time at least as slow), after archiving the 'approved' one
+
+                history::archive_superseded(
+                    self.id,
+                    self.player.id,
+                    self.demon.id,
+                    self.progress,
+                    self.completion_time,
+                    &mut *connection,
+                )
+                .await?;
 
                 sqlx::query!(
                     "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND records.player = $2 AND \
-                     records.demon = $3 AND progress <= $4",
+                     records.demon = $3 AND progress <= $4 AND ($5::INTEGER IS NULL OR completion_time >= $5)",
                     self.id,
                     self.player.id,
                     self.demon.id,
-                    self.progress
+                    self.progress,
+                    self.completion_time
                 )
                 .execute(&mut *connection)
                 .await?;
 
                 sqlx::query!(
-                    "DELETE FROM records WHERE id <> $1 AND records.player = $2 AND records.demon = $3 AND progress <= $4",
+                    "DELETE FROM records WHERE id <> $1 AND records.player = $2 AND records.demon = $3 AND progress <= $4 AND ($5::INTEGER \
+                     IS NULL OR completion_time >= $5)",
                     self.id,
                     self.player.id,
                     self.demon.id,
-                    self.progress
+                    self.progress,
+                    self.completion_time
                 )
                 .execute(&mut *connection)
                 .await?;
+
+                // The player's own approved record is gone now, but any of the credited players might still be a partner on
+                // another approved record (and the partners might even have approved records of their own)
+                let credited = std::iter::once(self.player.id)
+                    .chain(self.partners.iter().map(|partner| partner.id))
+                    .collect::<Vec<_>>();
+
+                partner::ensure_not_credited(&credited, self.demon.id, self.id, &mut *connection).await?;
             },
 
-            // the other cases just convert back and forth between 'submitted' and 'under consideration', which doesn't change anything
+            // the other cases just convert back and forth between 'submitted', 'under consideration' and 'needs info', which doesn't
+            // change anything
             _ => (),
         }
 
+        // FIXME(sqlx) ridiculous query format to trick sqlx into working with custom types
         sqlx::query!(
-            "UPDATE records SET status_ = cast($1::text as record_status) WHERE id = $2", /* FIXME(sqlx) ridiculous query
-                                                                                           * format to trick sqlx into working
-                                                                                           * with custom types */
+            "UPDATE records SET status_ = cast($1::text as record_status), rejection_reason = NULL WHERE id = $2",
             status.to_sql().to_string(),
             self.id
         )
@@ -334,6 +435,28 @@ impl FullRecord {
         .await?;
 
         self.status = status;
+        self.rejection_reason = None;
+
+        Ok(())
+    }
+
+    /// Updates the reason this record was rejected for
+    ///
+    /// Errors out if a reason is given but the record is not rejected
+    pub async fn set_rejection_reason(&mut self, reason: Option<RejectionReason>, connection: &mut PgConnection) -> Result<()> {
+        if reason.is_some() && self.status != RecordStatus::Rejected {
+            return Err(DemonlistError::RejectionReasonOnUnrejected);
+        }
+
+        sqlx::query!(
+            "UPDATE records SET rejection_reason = $1 WHERE id = $2",
+            reason.as_ref().map(|reason| reason.id),
+            self.id
+        )
+        .execute(connection)
+        .await?;
+
+        self.rejection_reason = reason;
 
         Ok(())
     }
@@ -341,7 +464,8 @@ impl FullRecord {
     /// Updates this record's progress
     ///
     /// If this record is approved, all submissions with lower progress of the same (player,
-    /// demon)-tuple are deleted and have their notes transferred to this record.
+    /// demon)-tuple are deleted and have their notes transferred to this record. Increasing the
+    /// progress of an approved record archives the previous progress in the progress history.
     pub async fn set_progress(&mut self, progress: i16, connection: &mut PgConnection) -> Result<()> {
         let requirement = self.demon.requirement(&mut *connection).await?;
 
@@ -349,7 +473,26 @@ impl FullRecord {
             return Err(DemonlistError::InvalidProgress { requirement });
         }
 
+        // Time records are always completions
+        if self.completion_time.is_some() && progress != 100 {
+            return Err(DemonlistError::InvalidCompletionTime);
+        }
+
         if self.status == RecordStatus::Approved {
+            if progress > self.progress {
+                history::archive_progress(
+                    self.id,
+                    self.player.id,
+                    self.demon.id,
+                    self.progress,
+                    self.completion_time,
+                    self.video.as_deref(),
+                    None,
+                    &mut *connection,
+                )
+                .await?;
+            }
+
             // Transfer over all notes from the records deleted below
             sqlx::query!(
                 "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND player = $2 AND demon = $3 \
@@ -387,4 +530,67 @@ impl FullRecord {
 
         Ok(())
     }
+
+    /// Updates this record's completion time
+    ///
+    /// If this record is approved, all submissions with slower (or equal) times of the same (player,
+    /// demon)-tuple are deleted and have their notes transferred to this record. Improving the time
+    /// of an approved record archives the previous time in the progress history.
+    pub async fn set_completion_time(&mut self, completion_time: i32, connection: &mut PgConnection) -> Result<()> {
+        if completion_time <= 0 || self.demon.record_kind(&mut *connection).await? != RecordKind::Time {
+            return Err(DemonlistError::InvalidCompletionTime);
+        }
+
+        if self.status == RecordStatus::Approved {
+            if matches!(self.completion_time, Some(previous) if completion_time < previous) {
+                history::archive_progress(
+                    self.id,
+                    self.player.id,
+                    self.demon.id,
+                    self.progress,
+                    self.completion_time,
+                    self.video.as_deref(),
+                    None,
+                    &mut *connection,
+                )
+                .await?;
+            }
+
+            sqlx::query!(
+                "UPDATE record_notes SET record = $1 FROM records WHERE record_notes.record = records.id AND player = $2 AND demon = $3 \
+                 AND completion_time >= $4 AND status_='SUBMITTED'",
+                self.id,
+                self.player.id,
+                self.demon.id,
+                completion_time
+            )
+            .execute(&mut *connection)
+            .await?;
+
+            let deleted = sqlx::query!(
+                "DELETE FROM records WHERE player = $1 AND demon = $2 AND completion_time >= $3 AND status_='SUBMITTED'",
+                self.player.id,
+                self.demon.id,
+                completion_time
+            )
+            .execute(&mut *connection)
+            .await?;
+
+            info!(
+                "Changing completion time of record {} from {:?} to {} caused the deletion of {} submissions",
+                self,
+                self.completion_time,
+                completion_time,
+                deleted.rows_affected()
+            );
+        }
+
+        sqlx::query!("UPDATE records SET completion_time = $1 WHERE id = $2", completion_time, self.id)
+            .execute(connection)
+            .await?;
+
+        self.completion_time = Some(completion_time);
+
+        Ok(())
+    }
 }
@@ -1,21 +1,37 @@
 use crate::{
-    demon::MinimalDemon,
+    demon::{MinimalDemon, RecordKind},
     error::{DemonlistError, Result},
+    list::List,
     player::{claim::PlayerClaim, DatabasePlayer},
     record::{FullRecord, RecordStatus},
     submitter::Submitter,
 };
-use derive_more::Display;
 use log::debug;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::{PgConnection, Row};
+use std::fmt::{Display, Formatter};
 use url::Url;
 
-#[derive(Deserialize, Debug, Display)]
-#[display(fmt = "{}% on {} by {} [status: {}]", progress, demon, player, status)]
+#[derive(Deserialize, JsonSchema, Debug)]
 pub struct Submission {
-    progress: i16,
+    /// The progress of the record. Required for demons ranked by percentage, and either omitted or
+    /// `100` for demons ranked by completion time
+    #[serde(default)]
+    progress: Option<i16>,
+
+    /// The completion time of the record, in milliseconds. Required for demons ranked by
+    /// completion time, and must be omitted otherwise
+    #[serde(default)]
+    completion_time: Option<i32>,
+
     player: String,
+
+    /// The names of any further players to credit with this record, for levels played by several
+    /// players at once
+    #[serde(default)]
+    partners: Vec<String>,
+
     demon: i32,
     #[serde(default)]
     video: Option<String>,
@@ -27,12 +43,29 @@ pub struct Submission {
     /// An initial, submitter provided note for the demon.
     #[serde(default)]
     note: Option<String>,
+
+    /// Whether to create new players for the player and partners even if their names look like the
+    /// names of existing players. Requires `LIST_HELPER` permissions.
+    #[serde(default)]
+    create_new_players: bool,
+}
+
+impl Display for Submission {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match (self.progress, self.completion_time) {
+            (_, Some(time)) => write!(f, "{}ms on {} by {} [status: {}]", time, self.demon, self.player, self.status),
+            (Some(progress), None) => write!(f, "{}% on {} by {} [status: {}]", progress, self.demon, self.player, self.status),
+            (None, None) => write!(f, "record on {} by {} [status: {}]", self.demon, self.player, self.status),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct NormalizedSubmission {
-    progress: i16,
+    progress: Option<i16>,
+    completion_time: Option<i32>,
     player: DatabasePlayer,
+    partners: Vec<DatabasePlayer>,
     demon: MinimalDemon,
     status: RecordStatus,
 
@@ -44,10 +77,12 @@ pub struct NormalizedSubmission {
 #[derive(Debug)]
 pub struct ValidatedSubmission {
     progress: i16,
+    completion_time: Option<i32>,
     video: Option<String>,
     raw_footage: Option<String>,
     status: RecordStatus,
     player: DatabasePlayer,
+    partners: Vec<DatabasePlayer>,
     demon: MinimalDemon,
     note: Option<String>,
 }
@@ -61,6 +96,10 @@ impl Submission {
         self.status
     }
 
+    pub fn creates_new_players(&self) -> bool {
+        self.create_new_players
+    }
+
     pub async fn normalize(self, connection: &mut PgConnection) -> Result<NormalizedSubmission> {
         // validate video
         let video = match self.video {
@@ -69,12 +108,20 @@ impl Submission {
         };
 
         // Resolve player and demon name against the database
-        let player = DatabasePlayer::by_name_or_create(self.player.as_ref(), connection).await?;
+        let player = resolve_player(self.player.as_ref(), self.create_new_players, connection).await?;
         let demon = MinimalDemon::by_id(self.demon, connection).await?;
 
+        let mut partners = Vec::new();
+
+        for partner in &self.partners {
+            partners.push(resolve_player(partner.as_ref(), self.create_new_players, connection).await?);
+        }
+
         Ok(NormalizedSubmission {
             progress: self.progress,
+            completion_time: self.completion_time,
             player,
+            partners,
             demon,
             status: self.status,
             video,
@@ -84,32 +131,78 @@ impl Submission {
     }
 }
 
+/// Gets the player going by (or formerly known as) the given name, creating a new player if there is
+/// none. Unless `create_new_players` is set, refuses to create a new player whose name looks like the
+/// name of an existing player, since most likely the submitter meant that player.
+async fn resolve_player(name: &str, create_new_players: bool, connection: &mut PgConnection) -> Result<DatabasePlayer> {
+    if !create_new_players {
+        match DatabasePlayer::by_name_or_alias(name, &mut *connection).await {
+            Err(DemonlistError::PlayerNotFoundName { player_name }) => {
+                let candidates = DatabasePlayer::similar_to(&player_name, &mut *connection).await?;
+
+                if candidates.iter().any(|candidate| candidate.confusable) {
+                    return Err(DemonlistError::SimilarPlayerExists { player_name, candidates });
+                }
+            },
+            result => return result,
+        }
+    }
+
+    DatabasePlayer::by_name_or_create(name, connection).await
+}
+
 impl NormalizedSubmission {
-    pub async fn verified_player_claim(&self, connection: &mut PgConnection) -> Result<Option<PlayerClaim>> {
-        PlayerClaim::verified_claim_on(self.player.id, connection).await
+    /// The verified claims on the player and partners credited with this submission
+    pub async fn verified_player_claims(&self, connection: &mut PgConnection) -> Result<Vec<PlayerClaim>> {
+        let mut claims = Vec::new();
+
+        for player in std::iter::once(&self.player).chain(&self.partners) {
+            if let Some(claim) = PlayerClaim::verified_claim_on(player.id, &mut *connection).await? {
+                claims.push(claim);
+            }
+        }
+
+        Ok(claims)
     }
 
     pub async fn validate(self, connection: &mut PgConnection) -> Result<ValidatedSubmission> {
         // Banned player can't have records on the list
-        if self.player.banned {
+        if self.player.banned || self.partners.iter().any(|partner| partner.banned) {
             return Err(DemonlistError::PlayerBanned);
         }
 
+        // Every player can only be credited once
+        for (idx, partner) in self.partners.iter().enumerate() {
+            if partner.id == self.player.id || self.partners[..idx].iter().any(|other| other.id == partner.id) {
+                return Err(DemonlistError::DuplicatePartner);
+            }
+        }
+
+        let list = List::of_demon(self.demon.id, &mut *connection).await?;
+        let requirement = self.demon.requirement(&mut *connection).await?;
+
+        // Records on demons ranked by time are completions, so their progress is always 100%
+        let progress = match (self.demon.record_kind(&mut *connection).await?, self.progress, self.completion_time) {
+            (RecordKind::Percentage, _, Some(_)) => return Err(DemonlistError::UnexpectedCompletionTime),
+            (RecordKind::Percentage, Some(progress), None) => progress,
+            (RecordKind::Percentage, None, None) => return Err(DemonlistError::InvalidProgress { requirement }),
+            (RecordKind::Time, None | Some(100), Some(time)) if time > 0 => 100,
+            (RecordKind::Time, ..) => return Err(DemonlistError::InvalidCompletionTime),
+        };
+
         // Cannot submit records for the legacy list (it is possible to directly add them for list mods)
-        if self.demon.position > crate::config::extended_list_size() && self.status == RecordStatus::Submitted {
+        if self.demon.position > list.extended_list_size && self.status == RecordStatus::Submitted {
             return Err(DemonlistError::SubmitLegacy);
         }
 
         // Can only submit 100% records for the extended list (it is possible to directly add them for list
         // mods)
-        if self.demon.position > crate::config::list_size() && self.progress != 100 && self.status == RecordStatus::Submitted {
+        if self.demon.position > list.list_size && progress != 100 && self.status == RecordStatus::Submitted {
             return Err(DemonlistError::Non100Extended);
         }
 
-        let requirement = self.demon.requirement(&mut *connection).await?;
-
         // Check if the record meets the record requirement for this demon
-        if self.progress > 100 || self.progress < requirement {
+        if progress > 100 || progress < requirement {
             return Err(DemonlistError::InvalidProgress { requirement });
         }
 
@@ -130,12 +223,22 @@ impl NormalizedSubmission {
             }
         }
 
+        // For time records, an approved record is only a duplicate if it is at least as fast as the
+        // submission (all time records have 100% progress). Records of any of the credited players
+        // count, regardless of whether they hold it as the record's player or as one of its partners
+        let credited: Vec<i32> = std::iter::once(self.player.id)
+            .chain(self.partners.iter().map(|partner| partner.id))
+            .collect();
+
         let existing = sqlx::query!(
-            r#"SELECT id, status_::text as "status_!: String" FROM records WHERE demon = $1 AND player = $2 AND (status_ = 'REJECTED' OR status_ = 
-             'UNDER_CONSIDERATION' OR (status_ = 'APPROVED' AND progress >= $3)) LIMIT 1"#,
+            r#"SELECT id, status_::text as "status_!: String" FROM records WHERE demon = $1 AND (player = ANY($2) OR EXISTS (SELECT 1 FROM 
+             record_partners WHERE record_partners.record = records.id AND record_partners.player = ANY($2))) AND (status_ = 'REJECTED' OR 
+             status_ = 'UNDER_CONSIDERATION' OR (status_ = 'APPROVED' AND progress >= $3 AND ($4::INTEGER IS NULL OR completion_time <= $4))) 
+             LIMIT 1"#,
             self.demon.id,
-            self.player.id,
-            self.progress
+            &credited[..],
+            progress,
+            self.completion_time
         )
             .fetch_optional(&mut *connection)
             .await?;
@@ -159,11 +262,13 @@ impl NormalizedSubmission {
         }
 
         Ok(ValidatedSubmission {
-            progress: self.progress,
+            progress,
+            completion_time: self.completion_time,
             video: self.video,
             raw_footage: self.raw_footage,
             status: self.status,
             player: self.player,
+            partners: self.partners,
             demon: self.demon,
             note: self.note,
         })
@@ -173,10 +278,11 @@ impl NormalizedSubmission {
 impl ValidatedSubmission {
     pub async fn create(self, submitter: Submitter, connection: &mut PgConnection) -> Result<FullRecord> {
         let id = sqlx::query(
-            "INSERT INTO records (progress, video, status_, player, submitter, demon) VALUES ($1, $2::TEXT, 'SUBMITTED', $3, $4,$5) \
-             RETURNING id",
+            "INSERT INTO records (progress, completion_time, video, status_, player, submitter, demon) VALUES ($1, $2, $3::TEXT, \
+             'SUBMITTED', $4, $5, $6) RETURNING id",
         )
         .bind(self.progress)
+        .bind(self.completion_time)
         .bind(&self.video)
         .bind(self.player.id)
         .bind(submitter.id)
@@ -188,13 +294,20 @@ impl ValidatedSubmission {
         let mut record = FullRecord {
             id,
             progress: self.progress,
+            completion_time: self.completion_time,
             video: self.video,
             status: RecordStatus::Submitted,
             player: self.player,
+            partners: Vec::new(),
             demon: self.demon,
             submitter: Some(submitter),
+            rejection_reason: None,
         };
 
+        for partner in self.partners {
+            record.add_partner(partner, &mut *connection).await?;
+        }
+
         // Dealing with different status and upholding their invariant is complicated, we should not
         // duplicate that code!
         if self.status != RecordStatus::Submitted {
@@ -245,12 +358,14 @@ mod tests {
         let mut conn = connection().await;
 
         let result = NormalizedSubmission {
-            progress: 100,
+            progress: Some(100),
+            completion_time: None,
             player: DatabasePlayer {
                 id: 1,
                 name: "stardust1971".to_string(),
                 banned: true,
             },
+            partners: Vec::new(),
             demon: MinimalDemon {
                 id: 1,
                 position: 1,
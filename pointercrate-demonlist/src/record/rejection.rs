@@ -0,0 +1,142 @@
+//! Module for the reasons records can be rejected for
+//!
+//! Rejection reasons form a taxonomy managed by list administrators. A rejected record can
+//! optionally reference one of them, which is shown to the player's verified claimant.
+
+use crate::error::{DemonlistError, Result};
+use futures::StreamExt;
+use log::info;
+use pointercrate_core::util::non_nullable;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgConnection};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq, Clone)]
+pub struct RejectionReason {
+    pub id: i32,
+
+    /// A short name for this reason, such as "Insufficient proof"
+    pub name: String,
+
+    /// A longer explanation of this reason, for instance what a submitter can do to avoid it
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PostRejectionReason {
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PatchRejectionReason {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub name: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub description: Option<String>,
+}
+
+/// Errors out if the given name is empty, or a reason other than the one with id `except` already
+/// uses it
+async fn validate_name(name: &str, except: Option<i32>, connection: &mut PgConnection) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(DemonlistError::MalformedRejectionReason);
+    }
+
+    let taken = sqlx::query!(
+        r#"SELECT EXISTS (SELECT 1 FROM rejection_reasons WHERE name = $1 AND id IS DISTINCT FROM $2) AS "taken!""#,
+        name,
+        except
+    )
+    .fetch_one(connection)
+    .await?
+    .taken;
+
+    if taken {
+        return Err(DemonlistError::RejectionReasonExists);
+    }
+
+    Ok(())
+}
+
+impl RejectionReason {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<RejectionReason> {
+        let row = sqlx::query!("SELECT id, name, description FROM rejection_reasons WHERE id = $1", id)
+            .fetch_one(connection)
+            .await;
+
+        match row {
+            Ok(row) => Ok(RejectionReason {
+                id: row.id,
+                name: row.name,
+                description: row.description,
+            }),
+            Err(Error::RowNotFound) => Err(DemonlistError::RejectionReasonNotFound { reason_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<RejectionReason>> {
+        let mut stream = sqlx::query!("SELECT id, name, description FROM rejection_reasons ORDER BY id").fetch(connection);
+        let mut reasons = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            reasons.push(RejectionReason {
+                id: row.id,
+                name: row.name,
+                description: row.description,
+            })
+        }
+
+        Ok(reasons)
+    }
+
+    pub async fn create_from(data: PostRejectionReason, connection: &mut PgConnection) -> Result<RejectionReason> {
+        validate_name(&data.name, None, &mut *connection).await?;
+
+        info!("Creating new rejection reason {}", data.name);
+
+        let id = sqlx::query!(
+            "INSERT INTO rejection_reasons (name, description) VALUES ($1, $2) RETURNING id",
+            data.name,
+            data.description
+        )
+        .fetch_one(connection)
+        .await?
+        .id;
+
+        Ok(RejectionReason {
+            id,
+            name: data.name,
+            description: data.description,
+        })
+    }
+
+    /// Must run inside a transaction
+    pub async fn apply_patch(mut self, patch: PatchRejectionReason, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(name) = patch.name {
+            validate_name(&name, Some(self.id), &mut *connection).await?;
+
+            sqlx::query!("UPDATE rejection_reasons SET name = $1 WHERE id = $2", name, self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.name = name;
+        }
+
+        if let Some(description) = patch.description {
+            sqlx::query!("UPDATE rejection_reasons SET description = $1 WHERE id = $2", description, self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.description = description;
+        }
+
+        Ok(self)
+    }
+}
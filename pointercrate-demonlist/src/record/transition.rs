@@ -0,0 +1,92 @@
+//! Module for the status changes list staff is allowed to make to records
+//!
+//! The record status workflow is a state machine whose edges are stored in the database. A record's
+//! status can only be changed if a [`StatusTransition`] between its current and the requested
+//! status exists. List administrators can reconfigure the set of allowed transitions at any time.
+//! Note that this only restricts what status changes can be made through
+//! [`FullRecord::set_status`](super::FullRecord::set_status). The invariants described in the
+//! [record module documentation](super) are independent of the configured transitions.
+
+use crate::{
+    error::{DemonlistError, Result},
+    record::RecordStatus,
+};
+use futures::StreamExt;
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Clone, Copy)]
+pub struct StatusTransition {
+    pub from: RecordStatus,
+    pub to: RecordStatus,
+}
+
+impl StatusTransition {
+    /// Gets all currently allowed status transitions
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<StatusTransition>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT from_status::text AS "from_status!", to_status::text AS "to_status!" FROM record_status_transitions ORDER BY
+             from_status, to_status"#
+        )
+        .fetch(connection);
+
+        let mut transitions = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            transitions.push(StatusTransition {
+                from: RecordStatus::from_sql(&row.from_status),
+                to: RecordStatus::from_sql(&row.to_status),
+            })
+        }
+
+        Ok(transitions)
+    }
+
+    /// Checks whether records can currently be moved from status `from` to status `to`
+    pub async fn is_allowed(from: RecordStatus, to: RecordStatus, connection: &mut PgConnection) -> Result<bool> {
+        Ok(sqlx::query!(
+            r#"SELECT EXISTS (SELECT 1 FROM record_status_transitions WHERE from_status = cast($1::text as record_status) AND to_status =
+             cast($2::text as record_status)) AS "allowed!""#,
+            from.to_sql(),
+            to.to_sql()
+        )
+        .fetch_one(connection)
+        .await?
+        .allowed)
+    }
+
+    /// Replaces the allowed status transitions with the given ones
+    ///
+    /// Must run inside a transaction
+    pub async fn replace_all(transitions: Vec<StatusTransition>, connection: &mut PgConnection) -> Result<Vec<StatusTransition>> {
+        if let Some(transition) = transitions.iter().find(|transition| transition.from == transition.to) {
+            return Err(DemonlistError::InvalidStatusTransition {
+                from: transition.from,
+                to: transition.to,
+            });
+        }
+
+        info!("Reconfiguring record status transitions to {:?}", transitions);
+
+        sqlx::query!("DELETE FROM record_status_transitions")
+            .execute(&mut *connection)
+            .await?;
+
+        for transition in transitions {
+            sqlx::query!(
+                "INSERT INTO record_status_transitions (from_status, to_status) VALUES (cast($1::text as record_status), cast($2::text as \
+                 record_status)) ON CONFLICT DO NOTHING",
+                transition.from.to_sql(),
+                transition.to.to_sql()
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        StatusTransition::all(connection).await
+    }
+}
@@ -0,0 +1,201 @@
+//! Module for the review queue, which hands out submissions to list helpers
+//!
+//! Instead of picking submissions from the record manager (and potentially racing other helpers for
+//! the same one), helpers can ask the queue for the next submission. The queue assigns the oldest
+//! submission nobody else is currently reviewing to the requesting helper, for the duration of a
+//! [`REVIEW_LEASE`]. While the lease is active, other helpers cannot modify the submission. Leases
+//! can be renewed while a review takes longer, and expired leases simply put the submission back
+//! into the queue. Once a submission leaves the 'submitted' state, its review is
+//! [completed](complete_review) and counts towards the reviewer's [`ReviewStatistics`].
+
+pub use self::statistics::{QueueStatistics, ReviewStatistics, ReviewerStatistics};
+use crate::{
+    error::{DemonlistError, Result},
+    record::RecordStatus,
+};
+use chrono::NaiveDateTime;
+use futures::StreamExt;
+use log::info;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::PgConnection;
+use std::time::Duration;
+
+mod statistics;
+
+/// How long a submission stays assigned to a list helper, unless the assignment is renewed
+pub const REVIEW_LEASE: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Eq, Clone)]
+pub struct ReviewAssignment {
+    /// The id of the submission under review
+    pub record: i32,
+
+    /// The member id of the list helper reviewing the submission
+    pub reviewer: i32,
+    pub assigned_at: NaiveDateTime,
+
+    /// Once this point in time has passed, the submission is handed out to other helpers again
+    pub expires_at: NaiveDateTime,
+}
+
+impl ReviewAssignment {
+    /// Gets the active assignment of the record with the given id, if anyone is currently reviewing
+    /// it
+    pub async fn of_record(record_id: i32, connection: &mut PgConnection) -> Result<Option<ReviewAssignment>> {
+        let row = sqlx::query!(
+            "SELECT record, reviewer, assigned_at, expires_at FROM record_reviews WHERE record = $1 AND expires_at > NOW() AT TIME ZONE \
+             'utc'",
+            record_id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        Ok(row.map(|row| ReviewAssignment {
+            record: row.record,
+            reviewer: row.reviewer,
+            assigned_at: row.assigned_at,
+            expires_at: row.expires_at,
+        }))
+    }
+
+    /// Gets all active assignments, oldest first
+    pub async fn all_active(connection: &mut PgConnection) -> Result<Vec<ReviewAssignment>> {
+        let mut stream = sqlx::query!(
+            "SELECT record, reviewer, assigned_at, expires_at FROM record_reviews WHERE expires_at > NOW() AT TIME ZONE 'utc' ORDER BY \
+             assigned_at"
+        )
+        .fetch(connection);
+
+        let mut assignments = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            assignments.push(ReviewAssignment {
+                record: row.record,
+                reviewer: row.reviewer,
+                assigned_at: row.assigned_at,
+                expires_at: row.expires_at,
+            })
+        }
+
+        Ok(assignments)
+    }
+
+    /// Hands out the next submission to the list helper with the given member id
+    ///
+    /// If the helper is already reviewing a submission, that assignment is returned instead. If a
+    /// list id is given, only submissions on that list are considered.
+    pub async fn next_for(reviewer: i32, list_id: Option<i32>, connection: &mut PgConnection) -> Result<ReviewAssignment> {
+        let current = sqlx::query!(
+            "SELECT record, assigned_at, expires_at FROM record_reviews INNER JOIN records ON records.id = record_reviews.record WHERE \
+             reviewer = $1 AND expires_at > NOW() AT TIME ZONE 'utc' AND records.status_ = 'SUBMITTED' ORDER BY assigned_at LIMIT 1",
+            reviewer
+        )
+        .fetch_optional(&mut *connection)
+        .await?;
+
+        if let Some(current) = current {
+            return Ok(ReviewAssignment {
+                record: current.record,
+                reviewer,
+                assigned_at: current.assigned_at,
+                expires_at: current.expires_at,
+            });
+        }
+
+        loop {
+            // Submissions whose assignment expired are up for grabs again. Rows locked by a concurrent
+            // call are skipped, so two helpers never get handed the same submission.
+            let candidate = sqlx::query!(
+                "SELECT records.id FROM records INNER JOIN demons ON demons.id = records.demon WHERE records.status_ = 'SUBMITTED' AND \
+                 ($1::INTEGER IS NULL OR demons.list = $1) AND NOT EXISTS (SELECT 1 FROM record_reviews WHERE record_reviews.record = \
+                 records.id AND record_reviews.expires_at > NOW() AT TIME ZONE 'utc') ORDER BY records.id LIMIT 1 FOR UPDATE OF records \
+                 SKIP LOCKED",
+                list_id
+            )
+            .fetch_optional(&mut *connection)
+            .await?;
+
+            let record_id = match candidate {
+                Some(candidate) => candidate.id,
+                None => return Err(DemonlistError::ReviewQueueEmpty),
+            };
+
+            // Only fails to return a row if someone else was assigned the submission in between the
+            // two queries, in which case we just try the next one
+            let assigned = sqlx::query!(
+                "INSERT INTO record_reviews (record, reviewer, expires_at) VALUES ($1, $2, NOW() AT TIME ZONE 'utc' + make_interval(secs \
+                 => $3)) ON CONFLICT (record) DO UPDATE SET reviewer = EXCLUDED.reviewer, assigned_at = EXCLUDED.assigned_at, expires_at \
+                 = EXCLUDED.expires_at WHERE record_reviews.expires_at <= NOW() AT TIME ZONE 'utc' RETURNING assigned_at, expires_at",
+                record_id,
+                reviewer,
+                REVIEW_LEASE.as_secs_f64()
+            )
+            .fetch_optional(&mut *connection)
+            .await?;
+
+            if let Some(assigned) = assigned {
+                info!("Assigned submission {} to member {} for review", record_id, reviewer);
+
+                return Ok(ReviewAssignment {
+                    record: record_id,
+                    reviewer,
+                    assigned_at: assigned.assigned_at,
+                    expires_at: assigned.expires_at,
+                });
+            }
+        }
+    }
+
+    /// Extends this assignment by another [`REVIEW_LEASE`], counted from now
+    pub async fn renew(&mut self, connection: &mut PgConnection) -> Result<()> {
+        self.expires_at = sqlx::query!(
+            "UPDATE record_reviews SET expires_at = NOW() AT TIME ZONE 'utc' + make_interval(secs => $2) WHERE record = $1 RETURNING \
+             expires_at",
+            self.record,
+            REVIEW_LEASE.as_secs_f64()
+        )
+        .fetch_one(connection)
+        .await?
+        .expires_at;
+
+        Ok(())
+    }
+
+    /// Ends this assignment without completing the review, putting the submission back into the
+    /// queue
+    pub async fn release(self, connection: &mut PgConnection) -> Result<()> {
+        info!("Releasing submission {} from review by member {}", self.record, self.reviewer);
+
+        sqlx::query!("DELETE FROM record_reviews WHERE record = $1", self.record)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Notes that the member with the given id moved the record with the given id out of the
+/// 'submitted' state, ending any assignment of the record
+///
+/// The time the review took is only tracked if the member had the record assigned to them via the
+/// queue.
+pub async fn complete_review(record_id: i32, reviewer: i32, outcome: RecordStatus, connection: &mut PgConnection) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO completed_reviews (record, reviewer, outcome, assigned_at) VALUES ($1, $2, cast($3::text as record_status), (SELECT \
+         assigned_at FROM record_reviews WHERE record = $1 AND reviewer = $2))",
+        record_id,
+        reviewer,
+        outcome.to_sql()
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query!("DELETE FROM record_reviews WHERE record = $1", record_id)
+        .execute(connection)
+        .await?;
+
+    Ok(())
+}
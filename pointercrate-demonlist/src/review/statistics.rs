@@ -0,0 +1,95 @@
+use crate::error::Result;
+use futures::StreamExt;
+use pointercrate_core::audit::NamedId;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReviewStatistics {
+    pub queue: QueueStatistics,
+
+    /// The reviews completed by each list staff member during the considered period, most active
+    /// reviewer first
+    pub reviewers: Vec<ReviewerStatistics>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct QueueStatistics {
+    /// The number of submissions waiting for review
+    pub size: i64,
+
+    /// How many of the waiting submissions are currently assigned to a list helper
+    pub assigned: i64,
+
+    /// How long, in seconds, the oldest submission has been waiting for review
+    pub oldest_age: Option<f64>,
+
+    /// How long, in seconds, submissions have been waiting for review on average
+    pub average_age: Option<f64>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReviewerStatistics {
+    pub reviewer: NamedId,
+    pub reviews: i64,
+    pub approved: i64,
+    pub rejected: i64,
+
+    /// The average time, in seconds, between a submission being handed out to this reviewer via
+    /// the queue and its review being completed. Reviews done outside the queue are not considered.
+    pub average_review_time: Option<f64>,
+}
+
+impl ReviewStatistics {
+    /// Computes the current queue statistics, together with the statistics of all reviews
+    /// completed during the past `days` days
+    pub async fn compute(days: i32, connection: &mut PgConnection) -> Result<ReviewStatistics> {
+        let queue = sqlx::query!(
+            r#"SELECT COUNT(*) AS "size!", COUNT(record_reviews.record) AS "assigned!", EXTRACT(EPOCH FROM MAX(NOW() AT TIME ZONE 'utc' -
+             record_additions.time))::FLOAT AS oldest_age, EXTRACT(EPOCH FROM AVG(NOW() AT TIME ZONE 'utc' - record_additions.time))::FLOAT
+             AS average_age FROM records LEFT OUTER JOIN record_additions ON record_additions.id = records.id LEFT OUTER JOIN record_reviews
+             ON record_reviews.record = records.id AND record_reviews.expires_at > NOW() AT TIME ZONE 'utc' WHERE records.status_ =
+             'SUBMITTED'"#
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        let mut stream = sqlx::query!(
+            r#"SELECT reviewer, members.name, COUNT(*) AS "reviews!", COUNT(*) FILTER (WHERE outcome = 'APPROVED') AS "approved!", COUNT(*)
+             FILTER (WHERE outcome = 'REJECTED') AS "rejected!", EXTRACT(EPOCH FROM AVG(completed_at - assigned_at))::FLOAT AS
+             average_review_time FROM completed_reviews INNER JOIN members ON members.member_id = completed_reviews.reviewer WHERE
+             completed_at > NOW() AT TIME ZONE 'utc' - make_interval(days => $1) GROUP BY reviewer, members.name ORDER BY COUNT(*) DESC,
+             reviewer"#,
+            days
+        )
+        .fetch(connection);
+
+        let mut reviewers = Vec::new();
+
+        while let Some(row) = stream.next().await {
+            let row = row?;
+
+            reviewers.push(ReviewerStatistics {
+                reviewer: NamedId {
+                    id: row.reviewer,
+                    name: Some(row.name),
+                },
+                reviews: row.reviews,
+                approved: row.approved,
+                rejected: row.rejected,
+                average_review_time: row.average_review_time,
+            })
+        }
+
+        Ok(ReviewStatistics {
+            queue: QueueStatistics {
+                size: queue.size,
+                assigned: queue.assigned,
+                oldest_age: queue.oldest_age,
+                average_age: queue.average_age,
+            },
+            reviewers,
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    error::{DemonlistError, Result},
+    list::List,
+    scoring::{ScoringBracket, ScoringFormula},
+};
+use futures::StreamExt;
+use sqlx::{Error, PgConnection};
+
+impl ScoringFormula {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<ScoringFormula> {
+        let row = sqlx::query!(
+            "SELECT id, name, partial_base, partial_divisor, time_exponent, divide_shared_credit, created_at FROM scoring_formulas WHERE id = \
+             $1",
+            id
+        )
+        .fetch_one(&mut *connection)
+        .await;
+
+        match row {
+            Ok(row) => Ok(ScoringFormula {
+                id: row.id,
+                name: row.name,
+                partial_base: row.partial_base,
+                partial_divisor: row.partial_divisor,
+                time_exponent: row.time_exponent,
+                divide_shared_credit: row.divide_shared_credit,
+                created_at: row.created_at,
+                brackets: brackets_of(row.id, connection).await?,
+            }),
+            Err(Error::RowNotFound) => Err(DemonlistError::ScoringFormulaNotFound { formula_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets the formula currently used for calculating the scores on the given list
+    pub async fn of_list(list: &List, connection: &mut PgConnection) -> Result<ScoringFormula> {
+        ScoringFormula::by_id(list.scoring_formula, connection).await
+    }
+
+    /// Gets all scoring formulas ever defined, ordered by version
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<ScoringFormula>> {
+        let ids = sqlx::query!("SELECT id FROM scoring_formulas ORDER BY id")
+            .fetch_all(&mut *connection)
+            .await?;
+
+        let mut formulas = Vec::new();
+
+        for row in ids {
+            formulas.push(ScoringFormula::by_id(row.id, connection).await?);
+        }
+
+        Ok(formulas)
+    }
+}
+
+async fn brackets_of(formula_id: i32, connection: &mut PgConnection) -> Result<Vec<ScoringBracket>> {
+    let mut stream = sqlx::query!(
+        "SELECT lower_bound, upper_bound, scale, exponent, shift, constant FROM scoring_formula_brackets WHERE formula = $1 ORDER BY \
+         lower_bound",
+        formula_id
+    )
+    .fetch(connection);
+
+    let mut brackets = Vec::new();
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        brackets.push(ScoringBracket {
+            lower_bound: row.lower_bound,
+            upper_bound: row.upper_bound,
+            scale: row.scale,
+            exponent: row.exponent,
+            shift: row.shift,
+            constant: row.constant,
+        })
+    }
+
+    Ok(brackets)
+}
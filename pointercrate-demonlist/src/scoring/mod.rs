@@ -0,0 +1,188 @@
+//! Module for the formulas used to calculate demonlist scores
+//!
+//! Formulas are stored in the database (tables `scoring_formulas` and `scoring_formula_brackets`).
+//! All rankings, as well as [formula previews](ScoringFormula::preview), are computed by the
+//! `player_ranking_with` SQL function, which evaluates a list's formula (see
+//! [`List::scoring_formula`]) via the `record_score_with` SQL function. The
+//! [`ScoringFormula::score`] implementation here evaluates the same data, so changing the formula
+//! never requires touching either the SQL or the rust side.
+//!
+//! Which formula a list used at which point in time is recorded in the `scoring_formula_assignments`
+//! table whenever a list's formula changes.
+//!
+//! [`List::scoring_formula`]: crate::list::List::scoring_formula
+
+pub use self::{post::PostScoringFormula, preview::ScoringPreviewEntry};
+use chrono::NaiveDateTime;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+mod get;
+mod patch;
+mod post;
+mod preview;
+
+/// A versioned scoring formula
+///
+/// The formula's `id` serves as its version number. Formulas are immutable once created, the only
+/// thing that can change is which lists use them.
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Clone)]
+pub struct ScoringFormula {
+    pub id: i32,
+
+    pub name: String,
+
+    /// The base of the exponential used to scale down the score of non-100% records
+    pub partial_base: f64,
+
+    /// The factor by which the score of non-100% records is divided
+    pub partial_divisor: f64,
+
+    /// The exponent to which the ratio between the fastest completion time on a demon and a time
+    /// record's completion time is raised to scale that record's score
+    pub time_exponent: f64,
+
+    /// Whether the score of a record credited to several players is divided evenly between them,
+    /// instead of each of them receiving the full score
+    pub divide_shared_credit: bool,
+
+    pub created_at: NaiveDateTime,
+
+    /// The position brackets making up this formula, ordered by position
+    pub brackets: Vec<ScoringBracket>,
+}
+
+/// A range of list positions `(lower_bound, upper_bound]` in which the score of a 100% record is
+/// `scale * e^(exponent * (shift - position)) + constant`
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Clone, Copy)]
+pub struct ScoringBracket {
+    /// Exclusive lower bound of this bracket
+    pub lower_bound: i16,
+
+    /// Inclusive upper bound of this bracket
+    pub upper_bound: i16,
+
+    pub scale: f64,
+    pub exponent: f64,
+    pub shift: f64,
+    pub constant: f64,
+}
+
+impl ScoringBracket {
+    pub fn contains(&self, position: i16) -> bool {
+        self.lower_bound < position && position <= self.upper_bound
+    }
+
+    fn beaten_score(&self, position: i16) -> f64 {
+        self.scale * (self.exponent * (self.shift - position as f64)).exp() + self.constant
+    }
+}
+
+impl ScoringFormula {
+    /// Calculates the score a record with the given progress on a demon at the given position with
+    /// the given requirement is worth
+    ///
+    /// This mirrors the `record_score_with` SQL function exactly.
+    pub fn score(&self, position: i16, requirement: i16, progress: i16) -> f64 {
+        let beaten_score = match self.brackets.iter().find(|bracket| bracket.contains(position)) {
+            Some(bracket) => bracket.beaten_score(position),
+            None => return 0f64,
+        };
+
+        if progress == 100 {
+            beaten_score
+        } else if progress < requirement {
+            0f64
+        } else {
+            beaten_score * (self.partial_base.ln() * (progress - requirement) as f64 / (100f64 - requirement as f64)).exp()
+                / self.partial_divisor
+        }
+    }
+
+    /// Calculates the score a record with the given completion time (in milliseconds) on a demon at
+    /// the given position is worth, if the fastest approved record on that demon has the given time
+    ///
+    /// This mirrors the `record_score_with` and `time_factor_with` SQL functions exactly.
+    pub fn time_score(&self, position: i16, completion_time: i32, fastest_time: i32) -> f64 {
+        self.score(position, 100, 100) * (fastest_time as f64 / completion_time as f64).powf(self.time_exponent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScoringBracket, ScoringFormula};
+    use chrono::NaiveDateTime;
+
+    fn extended_list_buff() -> ScoringFormula {
+        ScoringFormula {
+            id: 1,
+            name: "Extended list buff (June 2022)".to_string(),
+            partial_base: 5f64,
+            partial_divisor: 10f64,
+            time_exponent: 1f64,
+            divide_shared_credit: false,
+            created_at: NaiveDateTime::default(),
+            brackets: vec![
+                ScoringBracket {
+                    lower_bound: 0,
+                    upper_bound: 20,
+                    scale: 250f64 - 100.39f64,
+                    exponent: 1.168f64.ln(),
+                    shift: 1f64,
+                    constant: 100.39f64,
+                },
+                ScoringBracket {
+                    lower_bound: 20,
+                    upper_bound: 35,
+                    scale: 250f64 - 83.389f64,
+                    exponent: 1.0099685f64.ln(),
+                    shift: 2f64,
+                    constant: -31.152f64,
+                },
+                ScoringBracket {
+                    lower_bound: 35,
+                    upper_bound: 55,
+                    scale: 212.61f64,
+                    exponent: 1.036f64.ln(),
+                    shift: 1f64,
+                    constant: 25.071f64,
+                },
+                ScoringBracket {
+                    lower_bound: 55,
+                    upper_bound: 150,
+                    scale: 56.191f64,
+                    exponent: 2f64.ln() * 50f64.ln() / 99f64,
+                    shift: 54.147f64 - 3.2f64,
+                    constant: 6.273f64,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_score_matches_legacy_formula() {
+        let formula = extended_list_buff();
+
+        assert!((formula.score(1, 50, 100) - 250f64).abs() < 1e-9);
+        assert!((formula.score(1, 50, 50) - 25f64).abs() < 1e-9);
+        assert!((formula.score(40, 60, 100) - 78.59557).abs() < 1e-4);
+        assert!((formula.score(100, 100, 100) - 20.93408).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_time_score() {
+        let formula = extended_list_buff();
+
+        assert!((formula.time_score(1, 60_000, 60_000) - 250f64).abs() < 1e-9);
+        assert!((formula.time_score(1, 120_000, 60_000) - 125f64).abs() < 1e-9);
+        assert_eq!(formula.time_score(151, 60_000, 60_000), 0f64);
+    }
+
+    #[test]
+    fn test_score_outside_brackets() {
+        let formula = extended_list_buff();
+
+        assert_eq!(formula.score(151, 100, 100), 0f64);
+        assert_eq!(formula.score(10, 50, 49), 0f64);
+    }
+}
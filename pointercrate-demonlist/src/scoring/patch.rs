@@ -0,0 +1,19 @@
+use crate::{error::Result, list::List, scoring::ScoringFormula};
+use log::info;
+use sqlx::PgConnection;
+
+impl ScoringFormula {
+    /// Makes this formula the one used to calculate all scores on the given list, replacing the
+    /// formula it previously used
+    pub async fn activate(&self, list: &mut List, connection: &mut PgConnection) -> Result<()> {
+        info!("Activating scoring formula {} ({}) for list {}", self.id, self.name, list.slug);
+
+        sqlx::query!("UPDATE lists SET scoring_formula = $1 WHERE id = $2", self.id, list.id)
+            .execute(connection)
+            .await?;
+
+        list.scoring_formula = self.id;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    error::{DemonlistError, Result},
+    scoring::{ScoringBracket, ScoringFormula},
+};
+use log::info;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PostScoringFormula {
+    pub name: String,
+    pub partial_base: f64,
+    pub partial_divisor: f64,
+
+    /// Defaults to `1`, meaning a time record's score is proportional to how close it is to the
+    /// fastest time
+    #[serde(default = "default_time_exponent")]
+    pub time_exponent: f64,
+
+    /// Whether records credited to several players have their score divided between them. Defaults
+    /// to `false`, meaning every credited player receives the full score
+    #[serde(default)]
+    pub divide_shared_credit: bool,
+
+    pub brackets: Vec<ScoringBracket>,
+}
+
+fn default_time_exponent() -> f64 {
+    1f64
+}
+
+impl PostScoringFormula {
+    /// Ensures the brackets are non-empty, non-overlapping position ranges, and that all parameters
+    /// are finite. Sorts the brackets by position as a side effect
+    fn validate(&mut self) -> Result<()> {
+        self.brackets.sort_by_key(|bracket| bracket.lower_bound);
+
+        if self.brackets.is_empty() {
+            return Err(DemonlistError::InvalidScoringFormula);
+        }
+
+        for parameter in [self.partial_base, self.partial_divisor] {
+            if !parameter.is_finite() || parameter <= 0f64 {
+                return Err(DemonlistError::InvalidScoringFormula);
+            }
+        }
+
+        if !self.time_exponent.is_finite() || self.time_exponent < 0f64 {
+            return Err(DemonlistError::InvalidScoringFormula);
+        }
+
+        for bracket in &self.brackets {
+            let parameters = [bracket.scale, bracket.exponent, bracket.shift, bracket.constant];
+
+            if bracket.lower_bound < 0 || bracket.lower_bound >= bracket.upper_bound || parameters.iter().any(|p| !p.is_finite()) {
+                return Err(DemonlistError::InvalidScoringFormula);
+            }
+        }
+
+        if self.brackets.windows(2).any(|pair| pair[0].upper_bound > pair[1].lower_bound) {
+            return Err(DemonlistError::InvalidScoringFormula);
+        }
+
+        Ok(())
+    }
+}
+
+impl ScoringFormula {
+    /// Creates a new scoring formula, not yet used by any list
+    pub async fn create_from(mut data: PostScoringFormula, connection: &mut PgConnection) -> Result<ScoringFormula> {
+        info!("Creating new scoring formula from {:?}", data);
+
+        data.validate()?;
+
+        let row = sqlx::query!(
+            "INSERT INTO scoring_formulas (name, partial_base, partial_divisor, time_exponent, divide_shared_credit) VALUES ($1, $2, $3, \
+             $4, $5) RETURNING id, created_at",
+            data.name,
+            data.partial_base,
+            data.partial_divisor,
+            data.time_exponent,
+            data.divide_shared_credit
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+
+        for bracket in &data.brackets {
+            sqlx::query!(
+                "INSERT INTO scoring_formula_brackets (formula, lower_bound, upper_bound, scale, exponent, shift, constant) VALUES ($1, $2, $3, \
+                 $4, $5, $6, $7)",
+                row.id,
+                bracket.lower_bound,
+                bracket.upper_bound,
+                bracket.scale,
+                bracket.exponent,
+                bracket.shift,
+                bracket.constant
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        Ok(ScoringFormula {
+            id: row.id,
+            name: data.name,
+            partial_base: data.partial_base,
+            partial_divisor: data.partial_divisor,
+            time_exponent: data.time_exponent,
+            divide_shared_credit: data.divide_shared_credit,
+            created_at: row.created_at,
+            brackets: data.brackets,
+        })
+    }
+}
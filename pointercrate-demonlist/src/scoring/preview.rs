@@ -0,0 +1,47 @@
+use crate::{error::Result, list::List, player::DatabasePlayer, scoring::ScoringFormula};
+use pointercrate_core::error::CoreError;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::PgConnection;
+
+/// How a single player's position in a list's stats viewer would change if a candidate formula were
+/// made active for that list
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct ScoringPreviewEntry {
+    pub player: DatabasePlayer,
+    pub current_rank: i64,
+    pub current_score: f64,
+    pub candidate_rank: i64,
+    pub candidate_score: f64,
+}
+
+impl ScoringFormula {
+    /// Computes the `limit` highest ranked players of the given list under this formula, together
+    /// with their rank and score under the formula the list currently uses
+    pub async fn preview(&self, list: &List, limit: Option<u8>, connection: &mut PgConnection) -> Result<Vec<ScoringPreviewEntry>> {
+        if let Some(limit) = limit {
+            if !(1..=100).contains(&limit) {
+                return Err(CoreError::InvalidPaginationLimit.into());
+            }
+        }
+
+        Ok(
+            sqlx::query_file!("sql/scoring_formula_preview.sql", list.id, self.id, limit.unwrap_or(50) as i64)
+                .fetch_all(connection)
+                .await?
+                .into_iter()
+                .map(|row| ScoringPreviewEntry {
+                    player: DatabasePlayer {
+                        id: row.id,
+                        name: row.name,
+                        banned: row.banned,
+                    },
+                    current_rank: row.current_rank,
+                    current_score: row.current_score,
+                    candidate_rank: row.candidate_rank,
+                    candidate_score: row.candidate_score,
+                })
+                .collect(),
+        )
+    }
+}
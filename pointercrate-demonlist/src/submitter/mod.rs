@@ -1,8 +1,7 @@
-use crate::error::Result;
 use derive_more::Display;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
-use sqlx::PgConnection;
 
 pub use paginate::SubmitterPagination;
 pub use patch::PatchSubmitter;
@@ -13,7 +12,7 @@ mod paginate;
 mod patch;
 mod post;
 
-#[derive(Debug, Deserialize, Serialize, Hash, Display, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Hash, Display, Copy, Clone, PartialEq, Eq)]
 #[display(fmt = "{} (Banned: {})", id, banned)]
 pub struct Submitter {
     pub id: i32,
@@ -21,15 +20,3 @@ pub struct Submitter {
 }
 
 impl Taggable for Submitter {}
-
-impl Submitter {
-    /// Gets the maximal and minimal submitter id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_submitter_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(submitter_id) AS "max_id!: i32", MIN(submitter_id) AS "min_id!: i32" FROM submitters"#)
-            .fetch_one(connection)
-            .await?; // FIXME: crashes on empty table
-        Ok((row.max_id, row.min_id))
-    }
-}
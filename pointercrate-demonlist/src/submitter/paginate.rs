@@ -1,10 +1,17 @@
-use crate::{error::Result, submitter::Submitter};
-use futures::StreamExt;
-use pointercrate_core::{error::CoreError, util::non_nullable};
+use crate::{error::DemonlistError, submitter::Submitter};
+use pointercrate_core::{
+    pagination::{clamp_i32, PaginationParameters, Paginator, DEFAULT_LIMIT},
+    util::non_nullable,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
 
-#[derive(Deserialize, Debug, Clone, Serialize)]
+#[derive(Deserialize, Debug, Clone, Serialize, JsonSchema)]
 pub struct SubmitterPagination {
     #[serde(rename = "before", default, deserialize_with = "non_nullable")]
     pub before_id: Option<i32>,
@@ -19,46 +26,47 @@ pub struct SubmitterPagination {
     banned: Option<bool>,
 }
 
-impl SubmitterPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<Submitter>> {
-        if let Some(limit) = self.limit {
-            if !(1..=100).contains(&limit) {
-                return Err(CoreError::InvalidPaginationLimit.into());
-            }
-        }
+impl Paginator for SubmitterPagination {
+    type Error = DemonlistError;
+    type Item = Submitter;
 
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(CoreError::AfterSmallerBefore.into());
-            }
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
         }
+    }
 
-        let query = if self.before_id.is_some() && self.after_id.is_none() {
-            "SELECT submitter_id, banned FROM submitters WHERE (submitter_id < $1 OR $1 IS NULL) AND (submitter_id > $2 OR $2 IS NULL) AND \
-             (banned = $3 OR $3 IS NULL) ORDER BY submitter_id DESC LIMIT $4 "
-        } else {
-            "SELECT submitter_id, banned FROM submitters WHERE (submitter_id < $1 OR $1 IS NULL) AND (submitter_id > $2 OR $2 IS NULL) AND \
-             (banned = $3 OR $3 IS NULL) ORDER BY submitter_id ASC LIMIT $4 "
-        };
-
-        let mut stream = sqlx::query(query)
-            .bind(self.before_id)
-            .bind(self.after_id)
-            .bind(self.banned)
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        SubmitterPagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
+        }
+    }
 
-        let mut submitters = Vec::new();
+    fn pagination_key(item: &Submitter) -> i64 {
+        item.id as i64
+    }
 
-        while let Some(row) = stream.next().await {
-            let row = row?;
+    fn query(&self, order: &str) -> String {
+        format!(
+            "SELECT submitter_id, banned FROM submitters WHERE (submitter_id < $1 OR $1 IS NULL) AND (submitter_id > $2 OR $2 IS NULL) AND \
+             (banned = $3 OR $3 IS NULL) ORDER BY submitter_id {} LIMIT $4",
+            order
+        )
+    }
 
-            submitters.push(Submitter {
-                id: row.get("submitter_id"),
-                banned: row.get("banned"),
-            })
-        }
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query.bind(self.before_id).bind(self.after_id).bind(self.banned)
+    }
 
-        Ok(submitters)
+    fn from_row(row: &PgRow) -> Result<Submitter, sqlx::Error> {
+        Ok(Submitter {
+            id: row.try_get("submitter_id")?,
+            banned: row.try_get("banned")?,
+        })
     }
 }
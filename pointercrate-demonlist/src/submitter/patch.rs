@@ -1,10 +1,11 @@
 use crate::{error::Result, submitter::Submitter};
 use log::info;
 use pointercrate_core::util::non_nullable;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct PatchSubmitter {
     #[serde(default, deserialize_with = "non_nullable")]
     banned: Option<bool>,
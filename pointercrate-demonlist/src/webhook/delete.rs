@@ -0,0 +1,18 @@
+use crate::{error::Result, webhook::Webhook};
+use log::info;
+use sqlx::PgConnection;
+
+impl Webhook {
+    /// Deletes this webhook, together with its delivery log
+    ///
+    /// Pending deliveries are dropped.
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        info!("Deleting webhook {} ({})", self.id, self.url);
+
+        sqlx::query!("DELETE FROM webhooks WHERE id = $1", self.id)
+            .execute(connection)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! Rendering of events as Discord webhook messages, see [`WebhookFormat::Discord`]
+//!
+//! [`WebhookFormat::Discord`]: super::WebhookFormat::Discord
+
+use crate::{
+    record::FullRecord,
+    webhook::{WebhookDelivery, WebhookEvent},
+};
+use serde_json::{json, Value};
+
+/// The maximal number of characters Discord allows in the description of an embed
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+/// The Discord message announcing the given delivery's event
+///
+/// Record events get a dedicated embed, all other events are described by their raw payload.
+pub(super) fn message(delivery: &WebhookDelivery) -> Value {
+    let record = match delivery.event {
+        WebhookEvent::RecordSubmitted | WebhookEvent::RecordApproved | WebhookEvent::RecordRejected => {
+            serde_json::from_value::<FullRecord>(delivery.payload.clone()).ok()
+        },
+        _ => None,
+    };
+
+    match record {
+        Some(record) => record_message(delivery.event, &record),
+        None => generic_message(delivery),
+    }
+}
+
+fn record_message(event: WebhookEvent, record: &FullRecord) -> Value {
+    let (content, description) = match event {
+        WebhookEvent::RecordApproved => (
+            format!("**Record approved! ID: {}**", record.id),
+            format!(
+                "{}'s {}% on {} has been approved!",
+                record.player.name, record.progress, record.demon.name
+            ),
+        ),
+        WebhookEvent::RecordRejected => (
+            format!("**Record rejected! ID: {}**", record.id),
+            format!(
+                "{}'s {}% on {} has been rejected.",
+                record.player.name, record.progress, record.demon.name
+            ),
+        ),
+        _ => (
+            format!("**New record submitted! ID: {}**", record.id),
+            format!(
+                "{} just got {}% on {}! Go add their record!",
+                record.player.name, record.progress, record.demon.name
+            ),
+        ),
+    };
+
+    let mut message = json!({
+        "content": content,
+        "embeds": [
+            {
+                "type": "rich",
+                "title": format!("{}% on {}", record.progress, record.demon.name),
+                "description": description,
+                "footer": {
+                    "text": format!("This record has been submitted by submitter #{}", record.submitter.map(|s| s.id).unwrap_or(1))
+                },
+                "author": {
+                    "name": format!("{} (ID: {})", record.player.name, record.player.id),
+                    "url": record.video
+                },
+            }
+        ]
+    });
+
+    if let Some(ref video) = record.video {
+        message["embeds"][0]["fields"] = json! {
+            [{
+                "name": "Video Proof:",
+                "value": video
+            }]
+        };
+    }
+
+    message
+}
+
+fn generic_message(delivery: &WebhookDelivery) -> Value {
+    let mut payload = serde_json::to_string_pretty(&delivery.payload).unwrap_or_default();
+
+    // Leave room for the code block around the payload
+    if let Some((index, _)) = payload.char_indices().nth(MAX_DESCRIPTION_LENGTH - 16) {
+        payload.truncate(index);
+    }
+
+    json!({
+        "content": format!("**{}**", title(delivery.event)),
+        "embeds": [
+            {
+                "type": "rich",
+                "description": format!("```json\n{}\n```", payload),
+            }
+        ]
+    })
+}
+
+fn title(event: WebhookEvent) -> &'static str {
+    match event {
+        WebhookEvent::RecordSubmitted => "New record submitted!",
+        WebhookEvent::RecordApproved => "Record approved!",
+        WebhookEvent::RecordRejected => "Record rejected!",
+        WebhookEvent::DemonAdded => "Demon added!",
+        WebhookEvent::DemonMoved => "Demon moved!",
+        WebhookEvent::ClaimVerified => "Claim verified!",
+        WebhookEvent::PlayerBanned => "Player banned!",
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    error::{DemonlistError, Result},
+    webhook::{Webhook, WebhookDelivery, WebhookEvent, WebhookFormat},
+};
+use sqlx::{Error, PgConnection};
+
+impl Webhook {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Webhook> {
+        let row = sqlx::query!(
+            "SELECT id, url, secret, events, format, active, created_at FROM webhooks WHERE id = $1",
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match row {
+            Ok(row) => Ok(Webhook {
+                id: row.id,
+                url: row.url,
+                secret: row.secret,
+                events: row
+                    .events
+                    .iter()
+                    .map(|event| WebhookEvent::from_sql(event))
+                    .collect::<std::result::Result<_, _>>()?,
+                format: WebhookFormat::from_sql(&row.format)?,
+                active: row.active,
+                created_at: row.created_at,
+            }),
+            Err(Error::RowNotFound) => Err(DemonlistError::WebhookNotFound { webhook_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<Webhook>> {
+        let ids = sqlx::query!("SELECT id FROM webhooks ORDER BY id")
+            .fetch_all(&mut *connection)
+            .await?;
+
+        let mut webhooks = Vec::new();
+
+        for row in ids {
+            webhooks.push(Webhook::by_id(row.id, connection).await?);
+        }
+
+        Ok(webhooks)
+    }
+}
+
+impl WebhookDelivery {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<WebhookDelivery> {
+        let row = sqlx::query!(
+            "SELECT id, webhook, event, payload, attempts, response_status, response, created_at, delivered_at FROM webhook_deliveries \
+             WHERE id = $1",
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match row {
+            Ok(row) => Ok(WebhookDelivery {
+                id: row.id,
+                webhook_id: row.webhook,
+                event: WebhookEvent::from_sql(&row.event)?,
+                payload: row.payload,
+                attempts: row.attempts,
+                response_status: row.response_status,
+                response: row.response,
+                created_at: row.created_at,
+                delivered_at: row.delivered_at,
+            }),
+            Err(Error::RowNotFound) => Err(DemonlistError::WebhookDeliveryNotFound { delivery_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
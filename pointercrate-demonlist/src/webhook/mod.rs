@@ -0,0 +1,222 @@
+//! Module for outbound webhooks
+//!
+//! Administrators can register any number of webhooks, each subscribing to a set of
+//! [`WebhookEvent`]s. Whenever such an event happens, [`dispatch`] adds an entry to the delivery log
+//! of every subscribed webhook, and enqueues a [`DeliverWebhook`] job for it. The job POSTs the
+//! event to the webhook's URL, with the body signed via HMAC-SHA256 using the webhook's secret,
+//! or, for webhooks in the [`WebhookFormat::Discord`] format, a Discord message describing it.
+//! Since dispatching happens in the same transaction as the change triggering the event, events
+//! are only delivered if that change actually happens.
+
+pub use self::{paginate::WebhookDeliveryPagination, patch::PatchWebhook, post::PostWebhook};
+use crate::{error::Result, record::RecordStatus};
+use chrono::NaiveDateTime;
+use hmac::{Hmac, Mac};
+use pointercrate_core::{
+    error::CoreError,
+    job::{self, Job},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::PgConnection;
+
+mod delete;
+mod discord;
+mod get;
+mod paginate;
+mod patch;
+mod post;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    RecordSubmitted,
+    RecordApproved,
+    RecordRejected,
+    DemonAdded,
+    DemonMoved,
+    ClaimVerified,
+    PlayerBanned,
+}
+
+impl WebhookEvent {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            WebhookEvent::RecordSubmitted => "record_submitted",
+            WebhookEvent::RecordApproved => "record_approved",
+            WebhookEvent::RecordRejected => "record_rejected",
+            WebhookEvent::DemonAdded => "demon_added",
+            WebhookEvent::DemonMoved => "demon_moved",
+            WebhookEvent::ClaimVerified => "claim_verified",
+            WebhookEvent::PlayerBanned => "player_banned",
+        }
+    }
+
+    fn from_sql(event: &str) -> std::result::Result<Self, sqlx::Error> {
+        Ok(match event {
+            "record_submitted" => WebhookEvent::RecordSubmitted,
+            "record_approved" => WebhookEvent::RecordApproved,
+            "record_rejected" => WebhookEvent::RecordRejected,
+            "demon_added" => WebhookEvent::DemonAdded,
+            "demon_moved" => WebhookEvent::DemonMoved,
+            "claim_verified" => WebhookEvent::ClaimVerified,
+            "player_banned" => WebhookEvent::PlayerBanned,
+            _ => return Err(sqlx::Error::Decode(format!("invalid webhook event: {}", event).into())),
+        })
+    }
+
+    /// The event fired when a record enters the given status, if any
+    pub fn for_record_status(status: RecordStatus) -> Option<Self> {
+        match status {
+            RecordStatus::Submitted => Some(WebhookEvent::RecordSubmitted),
+            RecordStatus::Approved => Some(WebhookEvent::RecordApproved),
+            RecordStatus::Rejected => Some(WebhookEvent::RecordRejected),
+            RecordStatus::UnderConsideration | RecordStatus::NeedsInfo => None,
+        }
+    }
+}
+
+/// The shape of the requests made to a webhook
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// The event itself, with the body signed using the webhook's secret
+    Signed,
+
+    /// A Discord webhook message describing the event
+    Discord,
+}
+
+impl Default for WebhookFormat {
+    fn default() -> Self {
+        WebhookFormat::Signed
+    }
+}
+
+impl WebhookFormat {
+    pub fn to_sql(self) -> &'static str {
+        match self {
+            WebhookFormat::Signed => "signed",
+            WebhookFormat::Discord => "discord",
+        }
+    }
+
+    fn from_sql(format: &str) -> std::result::Result<Self, sqlx::Error> {
+        match format {
+            "signed" => Ok(WebhookFormat::Signed),
+            "discord" => Ok(WebhookFormat::Discord),
+            _ => Err(sqlx::Error::Decode(format!("invalid webhook format: {}", format).into())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Clone)]
+pub struct Webhook {
+    pub id: i32,
+    pub url: String,
+
+    /// The key used to sign the bodies of all requests made to this webhook
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub format: WebhookFormat,
+
+    /// Whether events are currently being delivered to this webhook
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+}
+
+impl Webhook {
+    /// The value of the `X-Webhook-Signature` header of a request to this webhook with the given
+    /// body
+    ///
+    /// This is the hex-encoded HMAC-SHA256 of the body, keyed with the webhook's secret, and
+    /// prefixed with `sha256=`.
+    pub fn signature(&self, body: &[u8]) -> String {
+        signature(&self.secret, body)
+    }
+
+    /// The body of the request delivering the given event to this webhook
+    pub fn body(&self, delivery: &WebhookDelivery) -> serde_json::Value {
+        match self.format {
+            WebhookFormat::Signed => delivery.body(),
+            WebhookFormat::Discord => discord::message(delivery),
+        }
+    }
+}
+
+/// See [`Webhook::signature`]
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// An entry in the delivery log of a webhook
+#[derive(Debug, Serialize, JsonSchema, PartialEq, Clone)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook_id: i32,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+
+    /// The HTTP status code of the response to the latest delivery attempt, if any was received
+    pub response_status: Option<i16>,
+
+    /// The (truncated) response body of the latest delivery attempt, or the reason it failed
+    pub response: Option<String>,
+    pub created_at: NaiveDateTime,
+
+    /// When the webhook acknowledged the delivery with a successful response
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+impl WebhookDelivery {
+    /// The body of the request delivering this event
+    pub fn body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "delivery": self.id,
+            "event": self.event,
+            "created_at": self.created_at,
+            "data": self.payload,
+        })
+    }
+}
+
+/// Job delivering an entry of a webhook's delivery log
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliverWebhook {
+    pub delivery_id: i32,
+}
+
+impl Job for DeliverWebhook {
+    const KIND: &'static str = "deliver_webhook";
+    const MAX_ATTEMPTS: i32 = 8;
+}
+
+/// Schedules delivery of the given event to all active webhooks subscribed to it
+///
+/// `data` becomes the `data` field of the delivered request body.
+pub async fn dispatch(event: WebhookEvent, data: &impl Serialize, connection: &mut PgConnection) -> Result<()> {
+    let payload = serde_json::to_value(data).map_err(|err| CoreError::InternalServerError {
+        message: format!("Failed to serialize payload of {} event: {}", event.to_sql(), err),
+    })?;
+
+    let deliveries = sqlx::query!(
+        "INSERT INTO webhook_deliveries (webhook, event, payload) SELECT id, $1, $2 FROM webhooks WHERE active AND $1 = ANY(events) \
+         RETURNING id",
+        event.to_sql(),
+        payload
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for delivery in deliveries {
+        job::enqueue(&DeliverWebhook { delivery_id: delivery.id }, &mut *connection).await?;
+    }
+
+    Ok(())
+}
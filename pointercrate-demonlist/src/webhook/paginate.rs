@@ -0,0 +1,90 @@
+use crate::{
+    error::DemonlistError,
+    webhook::{WebhookDelivery, WebhookEvent},
+};
+use pointercrate_core::{
+    pagination::{clamp_i32, PaginationParameters, Paginator, DEFAULT_LIMIT},
+    util::non_nullable,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    Postgres, Row,
+};
+
+#[derive(Deserialize, Debug, Clone, Serialize, JsonSchema)]
+pub struct WebhookDeliveryPagination {
+    /// The webhook whose delivery log is paginated. Set by the endpoint, not the client
+    #[serde(skip)]
+    pub webhook_id: i32,
+
+    #[serde(rename = "before", default, deserialize_with = "non_nullable")]
+    pub before_id: Option<i32>,
+
+    #[serde(rename = "after", default, deserialize_with = "non_nullable")]
+    pub after_id: Option<i32>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub limit: Option<u8>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub event: Option<WebhookEvent>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub delivered: Option<bool>,
+}
+
+impl Paginator for WebhookDeliveryPagination {
+    type Error = DemonlistError;
+    type Item = WebhookDelivery;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
+        }
+    }
+
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        WebhookDeliveryPagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
+        }
+    }
+
+    fn pagination_key(item: &WebhookDelivery) -> i64 {
+        item.id as i64
+    }
+
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../../sql/paginate_webhook_deliveries.sql"), order)
+    }
+
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
+            .bind(self.before_id)
+            .bind(self.after_id)
+            .bind(self.webhook_id)
+            .bind(self.event.map(WebhookEvent::to_sql))
+            .bind(self.delivered)
+    }
+
+    fn from_row(row: &PgRow) -> Result<WebhookDelivery, sqlx::Error> {
+        Ok(WebhookDelivery {
+            id: row.try_get("id")?,
+            webhook_id: row.try_get("webhook")?,
+            event: WebhookEvent::from_sql(row.try_get("event")?)?,
+            payload: row.try_get("payload")?,
+            attempts: row.try_get("attempts")?,
+            response_status: row.try_get("response_status")?,
+            response: row.try_get("response")?,
+            created_at: row.try_get("created_at")?,
+            delivered_at: row.try_get("delivered_at")?,
+        })
+    }
+}
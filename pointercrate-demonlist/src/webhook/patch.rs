@@ -0,0 +1,109 @@
+use crate::{
+    error::Result,
+    webhook::{
+        post::{events_to_sql, validate_url},
+        Webhook, WebhookDelivery, WebhookEvent, WebhookFormat,
+    },
+};
+use pointercrate_core::util::non_nullable;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+/// The maximal number of characters of a webhook's response stored in the delivery log
+const MAX_RESPONSE_LENGTH: usize = 1000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PatchWebhook {
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub url: Option<String>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub events: Option<Vec<WebhookEvent>>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub format: Option<WebhookFormat>,
+
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub active: Option<bool>,
+}
+
+impl Webhook {
+    pub async fn apply_patch(mut self, patch: PatchWebhook, connection: &mut PgConnection) -> Result<Self> {
+        if let Some(url) = patch.url {
+            validate_url(&url)?;
+
+            sqlx::query!("UPDATE webhooks SET url = $1 WHERE id = $2", url, self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.url = url;
+        }
+
+        if let Some(events) = patch.events {
+            let events = events_to_sql(&events)?;
+
+            sqlx::query!("UPDATE webhooks SET events = $1 WHERE id = $2", &events[..], self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.events = events
+                .iter()
+                .map(|event| WebhookEvent::from_sql(event))
+                .collect::<std::result::Result<_, _>>()?;
+        }
+
+        if let Some(format) = patch.format {
+            sqlx::query!("UPDATE webhooks SET format = $1 WHERE id = $2", format.to_sql(), self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.format = format;
+        }
+
+        if let Some(active) = patch.active {
+            sqlx::query!("UPDATE webhooks SET active = $1 WHERE id = $2", active, self.id)
+                .execute(&mut *connection)
+                .await?;
+
+            self.active = active;
+        }
+
+        Ok(self)
+    }
+}
+
+impl WebhookDelivery {
+    /// Adds the outcome of an attempt at delivering this event to the delivery log
+    ///
+    /// `response_status` is `None` if no response was received at all, in which case `response`
+    /// should describe what went wrong.
+    pub async fn record_attempt(
+        &mut self, response_status: Option<u16>, mut response: String, connection: &mut PgConnection,
+    ) -> Result<()> {
+        if let Some((index, _)) = response.char_indices().nth(MAX_RESPONSE_LENGTH) {
+            response.truncate(index);
+        }
+
+        let succeeded = matches!(response_status, Some(200..=299));
+        let response_status = response_status.map(|status| status as i16);
+
+        let row = sqlx::query!(
+            "UPDATE webhook_deliveries SET attempts = attempts + 1, response_status = $1, response = $2, delivered_at = CASE WHEN $3 \
+             THEN NOW() AT TIME ZONE 'utc' END WHERE id = $4 RETURNING attempts, delivered_at",
+            response_status,
+            response,
+            succeeded,
+            self.id
+        )
+        .fetch_one(connection)
+        .await?;
+
+        self.attempts = row.attempts;
+        self.response_status = response_status;
+        self.response = Some(response);
+        self.delivered_at = row.delivered_at;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{
+    error::{DemonlistError, Result},
+    webhook::{Webhook, WebhookEvent, WebhookFormat},
+};
+use log::info;
+use pointercrate_core::error::CoreError;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use sqlx::PgConnection;
+use url::Url;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PostWebhook {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+
+    #[serde(default)]
+    pub format: WebhookFormat,
+}
+
+/// Ensures the given URL is an absolute `http` or `https` URL without credentials
+pub(super) fn validate_url(url: &str) -> Result<()> {
+    let url = Url::parse(url).map_err(|_| DemonlistError::MalformedWebhookUrl)?;
+
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(CoreError::InvalidUrlScheme.into());
+    }
+
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(CoreError::UrlAuthenticated.into());
+    }
+
+    Ok(())
+}
+
+/// Converts the given events into the representation stored in the database, rejecting empty
+/// subscriptions
+pub(super) fn events_to_sql(events: &[WebhookEvent]) -> Result<Vec<String>> {
+    if events.is_empty() {
+        return Err(DemonlistError::NoWebhookEvents);
+    }
+
+    let mut events = events.iter().map(|event| event.to_sql().to_string()).collect::<Vec<_>>();
+
+    events.sort();
+    events.dedup();
+
+    Ok(events)
+}
+
+impl Webhook {
+    pub async fn create_from(data: PostWebhook, connection: &mut PgConnection) -> Result<Webhook> {
+        validate_url(&data.url)?;
+
+        let events = events_to_sql(&data.events)?;
+
+        info!("Registering {} webhook {} for events {:?}", data.format.to_sql(), data.url, events);
+
+        let id = sqlx::query!(
+            "INSERT INTO webhooks (url, events, format) VALUES ($1, $2, $3) RETURNING id",
+            data.url,
+            &events[..],
+            data.format.to_sql()
+        )
+        .fetch_one(&mut *connection)
+        .await?
+        .id;
+
+        Webhook::by_id(id, connection).await
+    }
+
+    /// Registers the given URL of a Discord webhook (as formerly configured via the
+    /// `DISCORD_WEBHOOK` environment variable) as a [`WebhookFormat::Discord`] webhook notified of
+    /// new submissions
+    ///
+    /// Does nothing if a webhook with this URL already exists, so that administrators can freely
+    /// modify it afterwards.
+    pub async fn adopt_discord_webhook(url: String, connection: &mut PgConnection) -> Result<Option<Webhook>> {
+        let exists = sqlx::query!(r#"SELECT EXISTS (SELECT 1 FROM webhooks WHERE url = $1) AS "exists!""#, url)
+            .fetch_one(&mut *connection)
+            .await?
+            .exists;
+
+        if exists {
+            return Ok(None);
+        }
+
+        Webhook::create_from(
+            PostWebhook {
+                url,
+                events: vec![WebhookEvent::RecordSubmitted],
+                format: WebhookFormat::Discord,
+            },
+            connection,
+        )
+        .await
+        .map(Some)
+    }
+}
@@ -10,9 +10,11 @@ use dash_rs::{
     response::ResponseError,
     ProcessError,
 };
-use futures::{FutureExt, StreamExt};
+use futures::StreamExt;
 use log::{error, info, trace};
+use pointercrate_core::job::{self, Job};
 use reqwest::{header::CONTENT_TYPE, Client};
+use serde::{Deserialize, Serialize};
 use sqlx::{Error, Pool, Postgres};
 use std::{
     borrow::Cow,
@@ -27,6 +29,28 @@ pub use dash_rs::{
 use reqwest::header::HeaderMap;
 use std::cmp::Ordering;
 
+/// Job looking up the level of a demon whose level ID is not yet known, see [`PgCache::find_demon`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FindDemon {
+    pub name: String,
+    pub demon_id: i32,
+}
+
+impl Job for FindDemon {
+    const KIND: &'static str = "gd_find_demon";
+}
+
+/// Job refreshing the cached level data of a demon, see [`PgCache::download_demon`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DownloadDemon {
+    pub level_id: u64,
+    pub demon_id: i32,
+}
+
+impl Job for DownloadDemon {
+    const KIND: &'static str = "gd_download_demon";
+}
+
 #[derive(Debug)]
 pub enum GDIntegrationResult {
     Success(Level<'static, ()>, LevelData<'static>, Option<NewgroundsSong<'static>>),
@@ -37,9 +61,11 @@ pub enum GDIntegrationResult {
 }
 
 impl PgCache {
-    pub async fn data_for_demon(
-        &self, http_client: Client, level_id: Option<u64>, name: String, demon_id: i32,
-    ) -> Result<GDIntegrationResult, ()> {
+    /// Looks up the cached data for the given demon
+    ///
+    /// Missing or expired data is (re)downloaded in the background by [`FindDemon`] and
+    /// [`DownloadDemon`] jobs.
+    pub async fn data_for_demon(&self, level_id: Option<u64>, name: String, demon_id: i32) -> Result<GDIntegrationResult, ()> {
         trace!("Retrieving data for demon {:?}", name);
 
         match level_id {
@@ -62,7 +88,7 @@ impl PgCache {
                     CacheEntry::Absent => Ok(GDIntegrationResult::DemonNotFoundByName),
                     // Okay we _could_ do something more elaborate here if we dont have a "Missing" variant, but honestly I dont care
                     _ => {
-                        tokio::spawn(self.clone().find_demon(http_client, name.clone(), demon_id).map(|_| ()));
+                        self.schedule(&FindDemon { name, demon_id }).await;
 
                         Ok(GDIntegrationResult::DemonNotYetCached)
                     },
@@ -87,7 +113,11 @@ impl PgCache {
                     CacheEntry::Missing => return Ok(GDIntegrationResult::LevelDataNotCached),
                     CacheEntry::Absent => return Ok(GDIntegrationResult::LevelDataNotFound),
                     CacheEntry::Expired(level, _) => {
-                        tokio::spawn(self.clone().find_demon(http_client.clone(), name.clone(), demon_id).map(|_| ()));
+                        self.schedule(&FindDemon {
+                            name: name.clone(),
+                            demon_id,
+                        })
+                        .await;
 
                         level
                     },
@@ -100,11 +130,11 @@ impl PgCache {
                     Ok(CacheEntry::Missing) => return Ok(GDIntegrationResult::LevelDataNotCached),
                     Ok(CacheEntry::Absent) => return Ok(GDIntegrationResult::LevelDataNotFound),
                     Ok(CacheEntry::Expired(level_data, _)) => {
-                        tokio::spawn(
-                            self.clone()
-                                .download_demon(http_client, level.level_id.into(), demon_id)
-                                .map(|_| ()),
-                        );
+                        self.schedule(&DownloadDemon {
+                            level_id: level.level_id,
+                            demon_id,
+                        })
+                        .await;
 
                         level_data
                     },
@@ -124,7 +154,20 @@ impl PgCache {
         }
     }
 
-    async fn find_demon(self, http_client: Client, demon_name: String, demon: i32) -> Result<(), ()> {
+    async fn schedule<J: Job>(&self, job: &J) {
+        let result = match self.pool.acquire().await {
+            Ok(mut connection) => job::enqueue(job, &mut connection).await,
+            Err(err) => Err(err.into()),
+        };
+
+        if let Err(err) = result {
+            error!("Failed to enqueue {} job {:?}: {:?}", J::KIND, job, err);
+        }
+    }
+
+    /// Looks up the level of the given demon by name on the GD servers, and downloads the hardest
+    /// one with a matching name
+    pub async fn find_demon(self, http_client: Client, demon_name: String, demon: i32) -> Result<(), String> {
         let request = LevelsRequest::default()
             .request_type(LevelRequestType::MostLiked)
             .search(&demon_name)
@@ -149,20 +192,20 @@ impl PgCache {
                     Err(ResponseError::NotFound) => self
                         .mark_levels_request_result_as_absent(&request)
                         .await
-                        .map_err(|err| error!("Error marking result to {:?} as absent:  {:?}", request, err))
+                        .map_err(|err| format!("Error marking result to {:?} as absent:  {:?}", request, err))
                         .map(|_| ()),
                     Ok(mut demons) => {
                         if demons.is_empty() {
                             self.mark_levels_request_result_as_absent(&request)
                                 .await
-                                .map_err(|err| error!("Error marking result to {:?} as absent:  {:?}", request, err))
+                                .map_err(|err| format!("Error marking result to {:?} as absent:  {:?}", request, err))
                                 .map(|_| ())
                         } else {
                             trace!("Request to find demon {} yielded result {:?}", demon_name, demons);
 
                             self.store_levels_request(&request, &mut demons)
                                 .await
-                                .map_err(|err| error!("Error storing levels request result: {:?}", err))?;
+                                .map_err(|err| format!("Error storing levels request result: {:?}", err))?;
 
                             let hardest = demons
                                 .into_iter()
@@ -175,23 +218,27 @@ impl PgCache {
 
                                     self.download_demon(http_client, hardest.level_id.into(), demon).await
                                 },
+                                // Retrying won't make a matching level appear, so remember that there is none instead of failing
                                 None => {
-                                    error!("Could not find a level whose name matches '{}'", demon_name);
+                                    info!("Could not find a level whose name matches '{}'", demon_name);
 
-                                    Err(())
+                                    self.mark_levels_request_result_as_absent(&request)
+                                        .await
+                                        .map_err(|err| format!("Error marking result to {:?} as absent:  {:?}", request, err))
+                                        .map(|_| ())
                                 },
                             }
                         }
                     },
-                    Err(err) => Err(error!("Error processing response to request {:?}: {:?}", request, err)),
+                    Err(err) => Err(format!("Error processing response to request {:?}: {:?}", request, err)),
                 },
-                Err(error) => Err(error!("Error reading server response: {:?}", error)),
+                Err(error) => Err(format!("Error reading server response: {:?}", error)),
             },
-            Err(error) => Err(error!("Error making request: {:?}", error)),
+            Err(error) => Err(format!("Error making request: {:?}", error)),
         }
     }
 
-    async fn download_demon(self, http_client: Client, request: LevelRequest<'static>, demon_id: i32) -> Result<(), ()> {
+    pub async fn download_demon(self, http_client: Client, request: LevelRequest<'static>, demon_id: i32) -> Result<(), String> {
         trace!("Downloading demon with id {}", request.level_id);
 
         let request_result = http_client
@@ -211,31 +258,31 @@ impl PgCache {
                         Ok(mut demon) => {
                             self.store_level_data(demon.level_id, &mut demon.level_data)
                                 .await
-                                .map_err(|err| error!("Error storing demon '{}': {:?}", demon.name, err))?;
+                                .map_err(|err| format!("Error storing demon '{}': {:?}", demon.name, err))?;
 
                             sqlx::query!("UPDATE demons SET level_id = $1 WHERE id = $2", request.level_id as i64, demon_id)
                                 .execute(&self.pool)
                                 .await
-                                .map_err(|err| error!("Error updating level_id: {:?}", err))?;
+                                .map_err(|err| format!("Error updating level_id: {:?}", err))?;
 
                             sqlx::query!("DELETE FROM download_lock WHERE level_id = $1", request.level_id as i64)
                                 .execute(&self.pool)
                                 .await
-                                .map_err(|err| error!("Error freeing download lock: {:?}", err))?;
+                                .map_err(|err| format!("Error freeing download lock: {:?}", err))?;
 
                             Ok(info!("Successfully retrieved demon data!"))
                         },
                         Err(ResponseError::NotFound) => self
                             .mark_level_data_as_absent(request.level_id)
                             .await
-                            .map_err(|err| error!("Error marking level as absent: {:?}", err))
+                            .map_err(|err| format!("Error marking level as absent: {:?}", err))
                             .map(|_| ()),
-                        Err(err) => Err(error!("Error processing response: {:?}", err)),
+                        Err(err) => Err(format!("Error processing response: {:?}", err)),
                     },
-                    Err(err) => Err(error!("Error making http request: {:?}", err)),
+                    Err(err) => Err(format!("Error making http request: {:?}", err)),
                 }
             },
-            Err(err) => Err(error!("Error making http request: {:?}", err)),
+            Err(err) => Err(format!("Error making http request: {:?}", err)),
         }
     }
 }
@@ -1,8 +1,6 @@
 use crate::TestClient;
-use pointercrate_core::{permission::PermissionsManager, pool::PointercratePool};
-use pointercrate_demonlist::{
-    player::claim::PlayerClaim, record::RecordStatus, submitter::Submitter, LIST_ADMINISTRATOR, LIST_HELPER, LIST_MODERATOR,
-};
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_demonlist::{player::claim::PlayerClaim, record::RecordStatus, submitter::Submitter};
 use pointercrate_user_pages::account::AccountPageConfig;
 use rocket::local::asynchronous::Client;
 use sqlx::{pool::PoolConnection, PgConnection, Pool, Postgres};
@@ -13,14 +11,8 @@ pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<P
 
     let mut connection = pool.acquire().await.unwrap();
 
-    let permissions = PermissionsManager::new(vec![LIST_HELPER, LIST_MODERATOR, LIST_ADMINISTRATOR])
-        .assigns(LIST_ADMINISTRATOR, LIST_MODERATOR)
-        .implies(LIST_ADMINISTRATOR, LIST_MODERATOR)
-        .implies(LIST_MODERATOR, LIST_HELPER);
-
-    let rocket = pointercrate_demonlist_api::setup(rocket::build().manage(PointercratePool::from(pool)))
-        .manage(permissions)
-        .manage(AccountPageConfig::default());
+    let rocket =
+        pointercrate_demonlist_api::setup(rocket::build().manage(PointercratePool::from(pool))).manage(AccountPageConfig::default());
 
     // generate some data
     Submitter::create_submitter(IpAddr::from_str("127.0.0.1").unwrap(), &mut *connection)
@@ -34,7 +26,8 @@ pub async fn add_demon(
     name: impl Into<String>, position: i16, requirement: i16, verifier_id: i32, publisher_id: i32, connection: &mut PgConnection,
 ) -> i32 {
     sqlx::query!(
-        "INSERT INTO demons (name, position, requirement, verifier, publisher) VALUES ($1::TEXT::CITEXT, $2, $3, $4, $5) RETURNING id",
+        "INSERT INTO demons (name, position, list, requirement, verifier, publisher) VALUES ($1::TEXT::CITEXT, $2, (SELECT id FROM lists \
+         WHERE is_default), $3, $4, $5) RETURNING id",
         name.into(),
         position,
         requirement,
@@ -11,7 +11,9 @@ use serde::{de::DeserializeOwned, Serialize};
 use std::{collections::HashMap, fmt::Debug};
 
 pub mod demonlist;
+pub mod oembed;
 pub mod user;
+pub mod webhook;
 
 pub struct TestClient(Client);
 
@@ -58,8 +60,17 @@ impl<'c> TestRequest<'c> {
         .header("Accept", "application/json")
     }
 
+    /// Sets the given header, replacing any previous value (such as the default `Accept` header)
     pub fn header(mut self, header_name: impl Into<String>, header_value: impl Into<String>) -> Self {
-        self.request = self.request.header(Header::new(header_name.into(), header_value.into()));
+        self.request
+            .inner_mut()
+            .replace_header(Header::new(header_name.into(), header_value.into()));
+        self
+    }
+
+    /// Sets the given value as the request's JSON body
+    pub fn json(mut self, body: &impl Serialize) -> Self {
+        self.request = self.request.json(body);
         self
     }
 
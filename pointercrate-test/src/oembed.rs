@@ -0,0 +1,40 @@
+//! A local HTTP server standing in for the oEmbed endpoint of a video host
+
+use crate::webhook::{handle, ReceivedRequest};
+use rocket::tokio::net::TcpListener;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+/// Answers every request made to it with the same status code and JSON body
+pub struct OEmbedProvider {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+impl OEmbedProvider {
+    pub async fn start(status: u16, body: serde_json::Value) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&requests);
+        let body = body.to_string();
+
+        rocket::tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                handle(stream, status, &body, &received).await;
+            }
+        });
+
+        OEmbedProvider { address, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/oembed", self.address)
+    }
+
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
@@ -1,9 +1,6 @@
 use crate::TestClient;
-use pointercrate_core::{
-    permission::{Permission, PermissionsManager},
-    pool::PointercratePool,
-};
-use pointercrate_user::{AuthenticatedUser, Registration, ADMINISTRATOR, MODERATOR};
+use pointercrate_core::{permission::Permission, pool::PointercratePool};
+use pointercrate_user::{AuthenticatedUser, Registration};
 use pointercrate_user_pages::account::AccountPageConfig;
 use rocket::local::asynchronous::Client;
 use sqlx::{pool::PoolConnection, PgConnection, Pool, Postgres};
@@ -13,13 +10,8 @@ pub async fn setup_rocket(pool: Pool<Postgres>) -> (TestClient, PoolConnection<P
 
     let connection = pool.acquire().await.unwrap();
 
-    let permissions = PermissionsManager::new(vec![MODERATOR, ADMINISTRATOR])
-        .assigns(ADMINISTRATOR, MODERATOR)
-        .implies(ADMINISTRATOR, MODERATOR);
-
     let rocket = pointercrate_user_api::setup(rocket::build())
         .manage(PointercratePool::from(pool))
-        .manage(permissions)
         .manage(AccountPageConfig::default());
 
     (TestClient::new(Client::tracked(rocket).await.unwrap()), connection)
@@ -37,9 +29,9 @@ pub async fn system_user_with_perms(perm: Permission, connection: &mut PgConnect
     .unwrap();
 
     sqlx::query!(
-        "UPDATE members SET permissions = $2::INTEGER::BIT(16) WHERE member_id = $1",
+        "INSERT INTO member_roles (member, role) SELECT $1, id FROM roles WHERE name = $2",
         user.inner().id,
-        perm.bit() as i16
+        perm.name()
     )
     .execute(connection)
     .await
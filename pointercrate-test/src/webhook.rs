@@ -0,0 +1,125 @@
+//! A local HTTP server standing in for the receiving end of outbound webhooks
+
+use rocket::tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+/// A request received by a [`WebhookReceiver`]
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    /// The request target, including the query string
+    pub path: String,
+
+    /// The request headers, with lowercase names
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).unwrap()
+    }
+}
+
+/// Records every request made to it, answering each with the same status code
+pub struct WebhookReceiver {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+impl WebhookReceiver {
+    pub async fn start(status: u16) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&requests);
+
+        rocket::tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                handle(stream, status, "ok", &received).await;
+            }
+        });
+
+        WebhookReceiver { address, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Reads a single request from the given stream, and answers it with the given status and body
+///
+/// The request is recorded before the response is sent, so that it is visible to the test as soon as
+/// the sender has received the response.
+pub(crate) async fn handle(mut stream: TcpStream, status: u16, body: &str, received: &Mutex<Vec<ReceivedRequest>>) -> Option<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+
+        match stream.read(&mut chunk).await.ok()? {
+            0 => return None,
+            read => buffer.extend_from_slice(&chunk[..read]),
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&buffer[..header_end])
+        .lines()
+        .skip(1) // the request line
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut request_body = buffer.split_off(header_end + 4);
+
+    while request_body.len() < content_length {
+        match stream.read(&mut chunk).await.ok()? {
+            0 => return None,
+            read => request_body.extend_from_slice(&chunk[..read]),
+        }
+    }
+
+    let path = String::from_utf8_lossy(&buffer[..header_end])
+        .lines()
+        .next()
+        .and_then(|request_line| request_line.split(' ').nth(1))
+        .unwrap_or_default()
+        .to_string();
+
+    received.lock().unwrap().push(ReceivedRequest {
+        path,
+        headers,
+        body: request_body,
+    });
+
+    let response = format!(
+        "HTTP/1.1 {} Stand-In\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+
+    stream.write_all(response.as_bytes()).await.ok()
+}
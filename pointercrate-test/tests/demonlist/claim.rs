@@ -1,4 +1,5 @@
 use pointercrate_demonlist::player::{claim::PlayerClaim, DatabasePlayer};
+use pointercrate_user::MODERATOR;
 use rocket::http::Status;
 use sqlx::{Pool, Postgres};
 
@@ -33,3 +34,26 @@ async fn test_put_claim(pool: Pool<Postgres>) {
         }
     );
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_paginate_claims_empty_table(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(MODERATOR, &mut *connection).await;
+
+    let response = client
+        .get("/api/v1/players/claims")
+        .authorize_as(&moderator)
+        .header("Prefer", "count=exact")
+        .expect_status(Status::Ok)
+        .expect_header("X-Total-Count", "0")
+        .execute()
+        .await;
+
+    let link = response.headers().get_one("Link").unwrap().to_string();
+
+    assert!(link.contains("rel=\"first\""));
+    assert!(link.contains("rel=\"last\""));
+    assert!(!link.contains("rel=\"next\""));
+    assert!(!link.contains("rel=\"prev\""));
+    assert_eq!(response.into_string().await.unwrap(), "[]");
+}
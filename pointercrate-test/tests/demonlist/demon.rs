@@ -1,6 +1,10 @@
-use pointercrate_demonlist::LIST_MODERATOR;
+use pointercrate_core::pool::audit_connection;
+use pointercrate_demonlist::{player::DatabasePlayer, record::RecordStatus, LIST_MODERATOR};
 use rocket::http::Status;
-use sqlx::{Pool, Postgres};
+use sqlx::{
+    types::chrono::{Duration, SecondsFormat, Utc},
+    Pool, Postgres,
+};
 
 const DEFAULT_THUMBNAIL: &str = "https://i.ytimg.com/vi/zebrafishes/mqdefault.jpg";
 
@@ -100,3 +104,107 @@ async fn test_default_thumbnail_with_video(pool: Pool<Postgres>) {
         Some("https://i.ytimg.com/vi/dQw4w9WgXcQ/mqdefault.jpg")
     )
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_listed_at(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    // Make sure the changes below end up in the audit log
+    audit_connection(&mut *connection, 0).await.unwrap();
+
+    let player = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap().id;
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player, player, &mut *connection).await;
+
+    sqlx::query!("UPDATE audit_log2 SET time = time - interval '2 days'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let then = (Utc::now() - Duration::days(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    sqlx::query!("UPDATE demons SET position = 2, requirement = 60 WHERE id = $1", bloodbath)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    let sonic_wave = pointercrate_test::demonlist::add_demon("Sonic Wave", 1, 100, player, player, &mut *connection).await;
+
+    let now: Vec<serde_json::Value> = clnt.get("/api/v2/demons/listed/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(now.len(), 2);
+    assert_eq!(now[0]["id"].as_i64(), Some(sonic_wave as i64));
+
+    let back_then: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/listed/?at={}", then))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(back_then.len(), 1);
+    assert_eq!(back_then[0]["id"].as_i64(), Some(bloodbath as i64));
+    assert_eq!(back_then[0]["position"].as_i64(), Some(1));
+    assert_eq!(back_then[0]["requirement"].as_i64(), Some(50));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_diff(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    audit_connection(&mut *connection, 0).await.unwrap();
+
+    let player = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap().id;
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player, player, &mut *connection).await;
+    let sonic_wave = pointercrate_test::demonlist::add_demon("Sonic Wave", 2, 100, player, player, &mut *connection).await;
+
+    sqlx::query!("UPDATE audit_log2 SET time = time - interval '2 days'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let from = (Utc::now() - Duration::days(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    // Move Sonic Wave above Bloodbath the way the API does it, which only shifts Bloodbath
+    for (demon, position) in [(sonic_wave, -1), (bloodbath, 2), (sonic_wave, 1)] {
+        sqlx::query!("UPDATE demons SET position = $1 WHERE id = $2", position, demon)
+            .execute(&mut *connection)
+            .await
+            .unwrap();
+    }
+    sqlx::query!("UPDATE demons SET requirement = 60 WHERE id = $1", bloodbath)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let tartarus = pointercrate_test::demonlist::add_demon("Tartarus", 3, 55, player, player, &mut *connection).await;
+    let record = pointercrate_test::demonlist::add_simple_record(100, player, bloodbath, RecordStatus::Approved, &mut *connection).await;
+
+    let diff: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/diff/?from={}", from))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(diff["added"].as_array().unwrap().len(), 1);
+    assert_eq!(diff["added"][0]["id"].as_i64(), Some(tartarus as i64));
+    assert_eq!(diff["added"][0]["old_position"], serde_json::Value::Null);
+    assert_eq!(diff["removed"].as_array().unwrap().len(), 0);
+    assert_eq!(diff["moved"].as_array().unwrap().len(), 1);
+    assert_eq!(diff["moved"][0]["id"].as_i64(), Some(sonic_wave as i64));
+    assert_eq!(diff["moved"][0]["old_position"].as_i64(), Some(2));
+    assert_eq!(diff["moved"][0]["new_position"].as_i64(), Some(1));
+    assert_eq!(diff["requirement_changes"].as_array().unwrap().len(), 1);
+    assert_eq!(diff["requirement_changes"][0]["demon"]["id"].as_i64(), Some(bloodbath as i64));
+    assert_eq!(diff["requirement_changes"][0]["old_requirement"].as_i64(), Some(50));
+    assert_eq!(diff["requirement_changes"][0]["new_requirement"].as_i64(), Some(60));
+    assert_eq!(diff["records"].as_array().unwrap().len(), 1);
+    assert_eq!(diff["records"][0]["id"].as_i64(), Some(record as i64));
+
+    let to = (Utc::now() - Duration::days(2)).to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let result: serde_json::Value = clnt
+        .get(format!("/api/v2/demons/diff/?from={}&to={}", from, to))
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(42248));
+}
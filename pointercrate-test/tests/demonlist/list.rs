@@ -0,0 +1,135 @@
+use pointercrate_demonlist::{player::DatabasePlayer, LIST_ADMINISTRATOR};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_positions_are_per_list(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap().id;
+
+    let list: serde_json::Value = clnt
+        .post(
+            "/api/v2/lists/",
+            &serde_json::json! {{"slug": "platformer", "name": "Platformer List", "list_size": 10, "extended_list_size": 20}},
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let list_id = list["id"].as_i64().unwrap() as i32;
+
+    assert_eq!(list["is_default"].as_bool(), Some(false));
+
+    let bloodbath = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player, player, &mut *connection).await;
+    let sonic_wave = sqlx::query!(
+        "INSERT INTO demons (name, position, list, requirement, verifier, publisher) VALUES ('Sonic Wave', 1, $1, 100, $2, $2) RETURNING id",
+        list_id,
+        player
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .unwrap()
+    .id;
+
+    let default_list: Vec<serde_json::Value> = clnt.get("/api/v2/demons/listed/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(default_list.len(), 1);
+    assert_eq!(default_list[0]["id"].as_i64(), Some(bloodbath as i64));
+
+    let other_list: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/demons/listed/?list={}", list_id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(other_list.len(), 1);
+    assert_eq!(other_list[0]["id"].as_i64(), Some(sonic_wave as i64));
+    assert_eq!(other_list[0]["position"].as_i64(), Some(1));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_list_validation(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let result: serde_json::Value = clnt
+        .post(
+            "/api/v2/lists/",
+            &serde_json::json! {{"slug": "Not A Slug", "name": "Platformer List", "list_size": 10, "extended_list_size": 20}},
+        )
+        .authorize_as(&user)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(42240));
+
+    let result: serde_json::Value = clnt
+        .post(
+            "/api/v2/lists/",
+            &serde_json::json! {{"slug": "platformer", "name": "Platformer List", "list_size": 20, "extended_list_size": 10}},
+        )
+        .authorize_as(&user)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(42239));
+
+    let result: serde_json::Value = clnt
+        .post(
+            "/api/v2/lists/",
+            &serde_json::json! {{"slug": "demonlist", "name": "Another Demonlist", "list_size": 10, "extended_list_size": 20}},
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(40912));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_scoring_formula_history(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let formula: serde_json::Value = clnt
+        .post(
+            "/api/v2/scoring/",
+            &serde_json::json! {{"name": "Linear", "partial_base": 5, "partial_divisor": 10, "brackets": [{"lower_bound": 0, "upper_bound": 150, "scale": 0, "exponent": 0, "shift": 0, "constant": 100}]}},
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let formula_id = formula["id"].as_i64().unwrap() as i32;
+
+    let default_list = sqlx::query!("SELECT id, scoring_formula FROM lists WHERE is_default")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    clnt.post(format!("/api/v2/scoring/{}/activate", formula_id), &())
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let history = sqlx::query!(
+        r#"SELECT scoring_formula_at($1, '2020-01-01') AS "then!", scoring_formula_at($1, 'infinity') AS "now!""#,
+        default_list.id
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .unwrap();
+
+    assert_eq!(history.then, default_list.scoring_formula);
+    assert_eq!(history.now, formula_id);
+}
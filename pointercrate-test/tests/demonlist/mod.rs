@@ -1,4 +1,10 @@
 mod claim;
 mod demon;
+mod list;
+mod openapi;
 mod player;
 mod record;
+mod review;
+mod scoring;
+mod video;
+mod webhook;
@@ -0,0 +1,72 @@
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_openapi_document(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let document: serde_json::Value = clnt.get("/api/openapi.json").get_result().await;
+
+    let patch_record = &document["paths"]["/api/v1/records/{record_id}"]["patch"];
+
+    assert_eq!(
+        patch_record["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/PatchRecord"
+    );
+    assert!(patch_record["parameters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|parameter| parameter["name"] == "If-Match" && parameter["required"] == true));
+    assert!(document["components"]["schemas"]["PatchRecord"]["properties"]["progress"].is_object());
+
+    let paginate_demons = &document["paths"]["/api/v2/demons"]["get"];
+
+    assert!(paginate_demons["parameters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|parameter| parameter["name"] == "after"));
+
+    // filters are documented with the types of the pagination struct's fields
+    assert!(paginate_demons["parameters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|parameter| parameter["name"] == "requirement__gt" && parameter["in"] == "query" && parameter["schema"]["type"] == "integer"));
+    assert_eq!(
+        paginate_demons["responses"]["2XX"]["content"]["application/json"]["schema"]["items"]["$ref"],
+        "#/components/schemas/Demon"
+    );
+
+    let get_record = &document["paths"]["/api/v1/records/{record_id}"]["get"];
+
+    assert_eq!(
+        get_record["responses"]["2XX"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/FullRecord"
+    );
+    assert!(document["components"]["schemas"]["FullRecord"]["properties"]["player"].is_object());
+
+    let nation_ranking = &document["paths"]["/api/v1/nationalities/ranking"]["get"];
+    let parameters = nation_ranking["parameters"].as_array().unwrap();
+
+    assert!(parameters.iter().any(|parameter| parameter["name"] == "after"));
+    assert!(parameters.iter().any(|parameter| parameter["name"] == "continent"));
+    assert!(nation_ranking["responses"]["2XX"]["content"]["text/csv"].is_object());
+
+    // query parameters bound in route URIs are typed as well
+    let similar = &document["paths"]["/api/v1/players/similar"]["get"];
+
+    assert!(similar["parameters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|parameter| parameter["name"] == "name" && parameter["required"] == true));
+
+    // the catalogue contains both core and demonlist specific errors
+    let error_codes = document["components"]["schemas"]["Error"]["properties"]["code"]["enum"]
+        .as_array()
+        .unwrap();
+
+    assert!(error_codes.contains(&serde_json::json!(41200)));
+    assert!(error_codes.contains(&serde_json::json!(42217)));
+}
@@ -1,12 +1,19 @@
-use pointercrate_core::etag::Taggable;
+use pointercrate_core::{etag::Taggable, pool::audit_connection};
 use pointercrate_demonlist::{
+    error::DemonlistError,
     nationality::{Nationality, Subdivision},
-    player::{DatabasePlayer, FullPlayer, PatchPlayer, Player},
-    LIST_HELPER,
+    player::{name::normalize, DatabasePlayer, FullPlayer, PatchPlayer, Player},
+    ranking_history,
+    record::{FullRecord, RecordStatus},
+    LIST_HELPER, LIST_MODERATOR,
 };
+use pointercrate_demonlist_api::jobs::{take_ranking_snapshots, TakeRankingSnapshots};
 use pointercrate_test::TestClient;
 use rocket::http::Status;
-use sqlx::{PgConnection, Pool, Postgres};
+use sqlx::{
+    types::chrono::{Duration, FixedOffset, NaiveDate, SecondsFormat, Utc},
+    PgConnection, Pool, Postgres,
+};
 
 async fn create_players(connection: &mut PgConnection) -> (DatabasePlayer, DatabasePlayer) {
     let mut banned = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
@@ -103,3 +110,538 @@ async fn test_patch_player_nationality(pool: Pool<Postgres>) {
         })
     );
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_merge_preview_and_revert(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let merged = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, merged.id, merged.id, &mut *connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, player.id, player.id, &mut *connection).await;
+
+    let superseded = pointercrate_test::demonlist::add_simple_record(60, player.id, demon1, RecordStatus::Approved, &mut *connection).await;
+    let better = pointercrate_test::demonlist::add_simple_record(80, merged.id, demon1, RecordStatus::Approved, &mut *connection).await;
+    let moved = pointercrate_test::demonlist::add_simple_record(100, merged.id, demon2, RecordStatus::Approved, &mut *connection).await;
+
+    let preview: serde_json::Value = client
+        .post(
+            format!("/api/v1/players/{}/merge", player.id),
+            &serde_json::json!({"player": merged.id, "dry_run": true}),
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    let ids = |records: &serde_json::Value| {
+        let mut ids = records
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| record["id"].as_i64().unwrap())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    };
+
+    assert_eq!(ids(&preview["kept"]), Vec::<i64>::new());
+    assert_eq!(ids(&preview["moved"]), vec![better as i64, moved as i64]);
+    assert_eq!(ids(&preview["deleted"]), vec![superseded as i64]);
+    assert_eq!(preview["conflicting_claims"].as_array().unwrap().len(), 0);
+    assert!(!preview["score_changes"].as_array().unwrap().is_empty());
+
+    // A dry run must not change anything
+    assert!(DatabasePlayer::by_id(merged.id, &mut *connection).await.is_ok());
+    assert_eq!(FullRecord::by_id(superseded, &mut *connection).await.unwrap().player.id, player.id);
+    assert_eq!(FullRecord::by_id(better, &mut *connection).await.unwrap().player.id, merged.id);
+
+    client
+        .post(
+            format!("/api/v1/players/{}/merge", player.id),
+            &serde_json::json!({"player": merged.id}),
+        )
+        .authorize_as(&user)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    assert_eq!(
+        DatabasePlayer::by_id(merged.id, &mut *connection).await,
+        Err(DemonlistError::PlayerNotFound { player_id: merged.id })
+    );
+
+    let full_player = Player::by_id(player.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(full_player.records.len(), 2);
+    assert_eq!(full_player.progress_history.len(), 1);
+    assert_eq!(full_player.verified.len(), 2);
+
+    let merges: serde_json::Value = client
+        .get("/api/v1/players/merges")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(merges.as_array().unwrap().len(), 1);
+    assert_eq!(merges[0]["merged"].as_i64(), Some(merged.id as i64));
+
+    let merge_id = merges[0]["id"].as_i64().unwrap();
+    let merge: serde_json::Value = client
+        .get(format!("/api/v1/players/merges/{}/", merge_id))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(merge, merges[0]);
+
+    let reverted: serde_json::Value = client
+        .post(format!("/api/v1/players/merges/{}/revert", merge_id), &())
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(!reverted["reverted_at"].is_null());
+    assert_eq!(DatabasePlayer::by_id(merged.id, &mut *connection).await, Ok(merged.clone()));
+    assert_eq!(FullRecord::by_id(superseded, &mut *connection).await.unwrap().progress, 60);
+    assert_eq!(FullRecord::by_id(better, &mut *connection).await.unwrap().player.id, merged.id);
+    assert_eq!(FullRecord::by_id(moved, &mut *connection).await.unwrap().player.id, merged.id);
+
+    let full_player = Player::by_id(player.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(full_player.records.len(), 1);
+    assert!(full_player.progress_history.is_empty());
+    assert_eq!(full_player.verified.len(), 1);
+
+    // The audit log must not claim that the recreated record and player were only added by the revert
+    let additions = sqlx::query!(
+        r#"SELECT (SELECT COUNT(*) FROM record_additions WHERE id = $1) AS "records!", (SELECT COUNT(*) FROM player_additions WHERE id = $2) AS "players!""#,
+        superseded,
+        merged.id
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .unwrap();
+
+    assert_eq!(additions.records, 1);
+    assert_eq!(additions.players, 1);
+
+    let json: serde_json::Value = client
+        .post(format!("/api/v1/players/merges/{}/revert", merge_id), &())
+        .authorize_as(&user)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40916));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_revert_requires_unchanged_players(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let merged = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let survivor = DatabasePlayer::by_name_or_create("stardust1973", &mut *connection).await.unwrap();
+    let user = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player.id, player.id, &mut *connection).await;
+    let record = pointercrate_test::demonlist::add_simple_record(80, merged.id, demon, RecordStatus::Approved, &mut *connection).await;
+
+    for (into, from) in [(player.id, merged.id), (survivor.id, player.id)] {
+        client
+            .post(format!("/api/v1/players/{}/merge", into), &serde_json::json!({ "player": from }))
+            .authorize_as(&user)
+            .expect_status(Status::Created)
+            .execute()
+            .await;
+    }
+
+    let merges: serde_json::Value = client
+        .get(format!("/api/v1/players/merges/?merged={}", merged.id))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(merges.as_array().unwrap().len(), 1);
+
+    let first_merge = merges[0]["id"].as_i64().unwrap();
+    let json: serde_json::Value = client
+        .post(format!("/api/v1/players/merges/{}/revert", first_merge), &())
+        .authorize_as(&user)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40920));
+    assert_eq!(json["data"]["merge_id"].as_i64(), Some(first_merge + 1));
+
+    client
+        .post(format!("/api/v1/players/merges/{}/revert", first_merge + 1), &())
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // Reverting now would throw away the modification
+    sqlx::query!("UPDATE records SET progress = 90 WHERE id = $1", record)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let json: serde_json::Value = client
+        .post(format!("/api/v1/players/merges/{}/revert", first_merge), &())
+        .authorize_as(&user)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40921));
+    assert_eq!(FullRecord::by_id(record, &mut *connection).await.unwrap().progress, 90);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_normalize_name(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    assert_eq!(normalize("  Zoink\u{200B} ", &mut *connection).await.unwrap(), "Zoink");
+    assert_eq!(
+        normalize("\u{FF3A}\u{FF4F}\u{FF49}\u{FF4E}\u{FF4B}", &mut *connection)
+            .await
+            .unwrap(),
+        "Zoink"
+    );
+    assert_eq!(normalize("Star\u{00A0}\u{2028}dust", &mut *connection).await.unwrap(), "Star dust");
+    assert_eq!(normalize("Stardust1971", &mut *connection).await.unwrap(), "Stardust1971");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_lookup_unnormalized_name(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    // Players created before names were normalized
+    let legacy = sqlx::query!("INSERT INTO players (name) VALUES ('Zoink' || chr(8203)) RETURNING id")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .id;
+    let other_legacy = sqlx::query!("INSERT INTO players (name) VALUES ('Riot' || chr(8203)) RETURNING id")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .id;
+
+    assert_eq!(DatabasePlayer::by_name("Zoink\u{200B}", &mut *connection).await.unwrap().id, legacy);
+    assert_eq!(
+        DatabasePlayer::by_name_or_create("Zoink\u{200B}", &mut *connection)
+            .await
+            .unwrap()
+            .id,
+        legacy
+    );
+    assert_eq!(
+        DatabasePlayer::similar_to("zoink", &mut *connection).await.unwrap()[0].player.id,
+        legacy
+    );
+
+    // An exact match only takes effect if no player goes by the normalized name
+    let zoink = DatabasePlayer::by_name_or_create("Zoink", &mut *connection).await.unwrap();
+
+    assert_ne!(zoink.id, legacy);
+    assert_eq!(
+        DatabasePlayer::by_name("Zoink\u{200B}", &mut *connection).await.unwrap().id,
+        zoink.id
+    );
+
+    // Renaming a player to a legacy name merges the legacy player
+    let mut player = Player::by_id(zoink.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap();
+
+    player.set_name("Riot\u{200B}".to_string(), &mut *connection).await.unwrap();
+
+    assert_eq!(player.player.base.name, "Riot");
+    assert_eq!(
+        DatabasePlayer::by_id(other_legacy, &mut *connection).await,
+        Err(DemonlistError::PlayerNotFound { player_id: other_legacy })
+    );
+
+    // Renaming a legacy player to its own name normalizes it
+    let mut player = Player::by_id(legacy, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap();
+
+    player.set_name("Zoink\u{200B}".to_string(), &mut *connection).await.unwrap();
+
+    assert_eq!(DatabasePlayer::by_id(legacy, &mut *connection).await.unwrap().name, "Zoink");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_rename_keeps_alias(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let user = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+
+    let etag = Player::by_id(player.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap()
+        .etag_string();
+
+    let json: FullPlayer = client
+        .patch(
+            format!("/api/v1/players/{}/", player.id),
+            &serde_json::json!({"name": "stardust1972"}),
+        )
+        .authorize_as(&user)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(json.aliases.len(), 1);
+    assert_eq!(json.aliases[0].name, "stardust1971");
+
+    // Referring to the player by its old name must not create a new player
+    let resolved = DatabasePlayer::by_name_or_create("Stardust1971", &mut *connection).await.unwrap();
+
+    assert_eq!(resolved.id, player.id);
+    assert_eq!(resolved.name, "stardust1972");
+
+    let json: Vec<Player> = client
+        .get("/api/v1/players?name=stardust1971")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json.len(), 1);
+    assert_eq!(json[0].base.id, player.id);
+
+    // Once another player goes by the old name, the alias no longer applies
+    let other = DatabasePlayer::by_name_or_create("stardust1973", &mut *connection).await.unwrap();
+    let mut other = Player::by_id(other.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap();
+
+    other.set_name("stardust1971".to_string(), &mut *connection).await.unwrap();
+
+    let resolved = DatabasePlayer::by_name_or_alias("stardust1971", &mut *connection).await.unwrap();
+
+    assert_eq!(resolved.id, other.player.base.id);
+    assert!(Player::by_id(player.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap()
+        .aliases
+        .is_empty());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_ranking_history(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+
+    pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id, player.id, &mut *connection).await;
+
+    sqlx::query!(
+        "UPDATE players SET nationality = 'GB', subdivision = 'ENG' WHERE id = $1",
+        player.id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    let day1 = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let day2 = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+    let day3 = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+    ranking_history::take_snapshots(day1, &mut *connection).await.unwrap();
+    // Taking the snapshots of some day a second time must not fail
+    ranking_history::take_snapshots(day1, &mut *connection).await.unwrap();
+    ranking_history::take_snapshots(day2, &mut *connection).await.unwrap();
+
+    let json: serde_json::Value = client
+        .get(format!("/api/v1/players/{}/history/", player.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json["player"].as_array().map(Vec::len), Some(2));
+    assert_eq!(json["player"][0]["date"], "2024-01-01");
+    assert_eq!(json["player"][0]["rank"], 1);
+    assert_eq!(json["nation"].as_array().map(Vec::len), Some(2));
+    assert_eq!(json["subdivision"].as_array().map(Vec::len), Some(2));
+
+    let json: serde_json::Value = client
+        .get(format!("/api/v1/players/{}/history/?from=2024-01-02", player.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json["player"].as_array().map(Vec::len), Some(1));
+    assert_eq!(json["player"][0]["date"], "2024-01-02");
+
+    // Changing nationalities doesn't rewrite the history of the previous one
+    sqlx::query!("UPDATE players SET nationality = 'DE', subdivision = NULL WHERE id = $1", player.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    ranking_history::take_snapshots(day3, &mut *connection).await.unwrap();
+
+    let json: serde_json::Value = client
+        .get(format!("/api/v1/players/{}/history/", player.id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json["player"].as_array().map(Vec::len), Some(3));
+    assert_eq!(json["nation"].as_array().map(Vec::len), Some(3));
+    assert_eq!(json["subdivision"].as_array().map(Vec::len), Some(2));
+    assert_eq!(json["subdivision"][1]["date"], "2024-01-02");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_failed_ranking_snapshots_still_scheduled(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    sqlx::query!("ALTER TABLE nation_ranking_snapshots RENAME TO broken_nation_ranking_snapshots")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    assert!(take_ranking_snapshots(TakeRankingSnapshots {}, pool.clone()).await.is_err());
+
+    let scheduled = sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM jobs WHERE kind = 'take_ranking_snapshots' AND state = 'pending' AND run_at > NOW() AT TIME ZONE 'utc'"#
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .unwrap()
+    .count;
+
+    assert_eq!(scheduled, 1);
+
+    // A retry must not schedule the following day a second time
+    assert!(take_ranking_snapshots(TakeRankingSnapshots {}, pool.clone()).await.is_err());
+
+    let scheduled = sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM jobs WHERE kind = 'take_ranking_snapshots'"#)
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .count;
+
+    assert_eq!(scheduled, 1);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_rankings_at(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    // Make sure the changes below end up in the audit log
+    audit_connection(&mut *connection, 0).await.unwrap();
+
+    let verifier = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+
+    sqlx::query!("UPDATE players SET nationality = 'GB' WHERE id = $1", player.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, verifier.id, verifier.id, &mut *connection).await;
+    let record = pointercrate_test::demonlist::add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut *connection).await;
+
+    sqlx::query!("UPDATE audit_log2 SET time = time - interval '2 days'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let then = (Utc::now() - Duration::days(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    sqlx::query!("DELETE FROM records WHERE id = $1", record)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    // Bans and nationalities are reverted as well
+    sqlx::query!("UPDATE players SET banned = TRUE, nationality = NULL WHERE id = $1", player.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let ranking: Vec<serde_json::Value> = client.get("/api/v1/players/ranking/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(ranking.len(), 1);
+    assert_eq!(ranking[0]["id"].as_i64(), Some(verifier.id as i64));
+
+    let ranking: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/players/ranking/?at={}", then))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(ranking.len(), 2);
+    assert_eq!(ranking[0]["rank"].as_i64(), Some(1));
+    assert_eq!(ranking[1]["rank"].as_i64(), Some(1));
+
+    let nations: Vec<serde_json::Value> = client
+        .get("/api/v1/nationalities/ranking/")
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert!(nations.is_empty());
+
+    let nations: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/nationalities/ranking/?at={}", then))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(nations.len(), 1);
+    assert_eq!(nations[0]["country_code"], "GB");
+
+    // An unencoded '+' in the offset is decoded to a space, which is accepted as well
+    let then = (Utc::now() - Duration::days(1))
+        .with_timezone(&FixedOffset::east_opt(3600).unwrap())
+        .to_rfc3339_opts(SecondsFormat::Secs, false);
+
+    let nations: Vec<serde_json::Value> = client
+        .get(format!("/api/v1/nationalities/ranking/?at={}", then))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(nations.len(), 1);
+}
@@ -1,9 +1,9 @@
-use pointercrate_core::error::PointercrateError;
+use pointercrate_core::{error::PointercrateError, etag::Taggable};
 use pointercrate_demonlist::{
     error::DemonlistError,
-    player::DatabasePlayer,
-    record::{note::Note, FullRecord, RecordStatus},
-    LIST_HELPER,
+    player::{DatabasePlayer, FullPlayer, Player},
+    record::{note::Note, rejection::RejectionReason, FullRecord, RecordStatus},
+    LIST_ADMINISTRATOR, LIST_HELPER,
 };
 use pointercrate_test::{demonlist::add_simple_record, user::system_user_with_perms};
 use rocket::http::Status;
@@ -117,6 +117,76 @@ async fn unauthed_submit_for_player_with_locked_submission(pool: Pool<Postgres>)
     )
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn unauthed_submit_for_partner_with_locked_submission(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player1.id, player1.id, &mut *connection).await;
+
+    pointercrate_test::demonlist::put_claim(user.inner().id, player2.id, true, true, &mut *connection).await;
+
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971", "partners": ["stardust1972"], "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(
+        json["code"].as_i64(),
+        Some(DemonlistError::NoThirdPartySubmissions.error_code() as i64)
+    )
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn get_record_as_claimed_partner(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player1.id, player1.id, &mut *connection).await;
+    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Rejected, &mut *connection).await;
+
+    sqlx::query!("INSERT INTO record_partners (record, player) VALUES ($1, $2)", record, player2.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    // Unverified claims don't give access
+    pointercrate_test::demonlist::put_claim(user.inner().id, player2.id, false, false, &mut *connection).await;
+
+    clnt.get(format!("/api/v1/records/{}/", record))
+        .authorize_as(&user)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    sqlx::query!("UPDATE player_claims SET verified = TRUE WHERE member_id = $1", user.inner().id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let json: serde_json::Value = clnt
+        .get(format!("/api/v1/records/{}/", record))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(json["id"].as_i64(), Some(record as i64));
+
+    clnt.get(format!("/api/v1/records/{}/notes", record))
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn submit_existing_record(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -138,6 +208,364 @@ async fn submit_existing_record(pool: Pool<Postgres>) {
     assert_eq!(json["data"]["existing"].as_i64(), Some(existing as i64));
 }
 
+#[sqlx::test(migrations = "../migrations")]
+async fn submit_completion_time_on_percentage_demon(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+
+    let submission = serde_json::json! {{"progress": 100, "completion_time": 60000, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42242i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn submit_time_records(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("The Tower", 1, 100, player1.id, player1.id, &mut *connection).await;
+
+    sqlx::query!("UPDATE demons SET record_kind = 'TIME' WHERE id = $1", demon1)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let existing = add_simple_record(100, player1.id, demon1, RecordStatus::Approved, &mut *connection).await;
+
+    sqlx::query!("UPDATE records SET completion_time = 60000 WHERE id = $1", existing)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    // Time records need a completion time
+    let submission =
+        serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42241i64));
+
+    // A slower time than the approved record is a duplicate
+    let submission = serde_json::json! {{"completion_time": 70000, "demon": demon1, "player": "stardust1971", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42217i64));
+    assert_eq!(json["data"]["existing"].as_i64(), Some(existing as i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn submit_record_with_partners(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+
+    // A player cannot be credited twice
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971", "partners": ["stardust1971"], "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42243i64));
+
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971", "partners": ["Riot"], "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let record: FullRecord = clnt.post("/api/v1/records/", &submission).get_success_result().await;
+
+    assert_eq!(record.partners.len(), 1);
+    assert_eq!(record.partners[0].name, "Riot");
+
+    // The partner already holds a pending record on this demon
+    let submission =
+        serde_json::json! {{"progress": 100, "demon": demon1, "player": "Riot", "video": "https://youtube.com/watch?v=1234567890"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42217i64));
+    assert_eq!(json["data"]["existing"].as_i64(), Some(record.id as i64));
+
+    let record: FullRecord = clnt
+        .post(
+            format!("/api/v1/records/{}/partners", record.id),
+            &serde_json::json! {{"player": "Zoink"}},
+        )
+        .authorize_as(&helper)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.partners.len(), 2);
+
+    clnt.delete(format!("/api/v1/records/{}/partners/{}", record.id, record.partners[0].id))
+        .authorize_as(&helper)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    let json: serde_json::Value = clnt
+        .delete(format!("/api/v1/records/{}/partners/{}", record.id, record.partners[0].id))
+        .authorize_as(&helper)
+        .expect_status(Status::NotFound)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40401i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn partner_already_credited(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let partner = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+
+    let existing = add_simple_record(100, partner.id, demon, RecordStatus::Approved, &mut *connection).await;
+    let record = add_simple_record(100, player2.id, demon, RecordStatus::Submitted, &mut *connection).await;
+
+    // The partner already holds an approved record on this demon
+    let json: serde_json::Value = clnt
+        .post(
+            format!("/api/v1/records/{}/partners", record),
+            &serde_json::json! {{"player": "Riot"}},
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40919i64));
+    assert_eq!(json["data"]["record_id"].as_i64(), Some(existing as i64));
+
+    sqlx::query!("DELETE FROM records WHERE id = $1", existing)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let with_partner: FullRecord = clnt
+        .post(
+            format!("/api/v1/records/{}/partners", record),
+            &serde_json::json! {{"player": "Riot"}},
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    clnt.patch(format!("/api/v1/records/{}/", record), &serde_json::json! {{"status": "approved"}})
+        .authorize_as(&admin)
+        .header("If-Match", with_partner.etag_string())
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // Approving the partner's own record would credit them twice
+    let own = add_simple_record(100, partner.id, demon, RecordStatus::Submitted, &mut *connection).await;
+    let etag = FullRecord::by_id(own, &mut *connection).await.unwrap().etag_string();
+
+    let json: serde_json::Value = clnt
+        .patch(format!("/api/v1/records/{}/", own), &serde_json::json! {{"status": "approved"}})
+        .authorize_as(&admin)
+        .header("If-Match", etag)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40919i64));
+    assert_eq!(json["data"]["record_id"].as_i64(), Some(record as i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn submit_for_lookalike_player(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+
+    // Invisible characters are stripped before looking up the player
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "stardust1971\u{200B}", "video": "https://youtube.com/watch?v=1234567890", "raw_footage": "https://pointercrate.com"}};
+
+    let record: FullRecord = clnt.post("/api/v1/records/", &submission).get_success_result().await;
+
+    assert_eq!(record.player.id, player1.id);
+
+    // The 'а' is cyrillic
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "st\u{0430}rdust1971", "video": "https://youtube.com/watch?v=1234567891", "raw_footage": "https://pointercrate.com"}};
+
+    let json: serde_json::Value = clnt
+        .post("/api/v1/records/", &submission)
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40918i64));
+    assert_eq!(json["data"]["candidates"][0]["id"].as_i64(), Some(player1.id as i64));
+    assert_eq!(json["data"]["candidates"][0]["confusable"].as_bool(), Some(true));
+
+    // Only list helpers can insist on creating a new player
+    let submission = serde_json::json! {{"progress": 100, "demon": demon1, "player": "st\u{0430}rdust1971", "video": "https://youtube.com/watch?v=1234567891", "raw_footage": "https://pointercrate.com", "create_new_players": true}};
+
+    clnt.post("/api/v1/records/", &submission)
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    let record: FullRecord = clnt
+        .post("/api/v1/records/", &submission)
+        .authorize_as(&helper)
+        .get_success_result()
+        .await;
+
+    assert_ne!(record.player.id, player1.id);
+
+    let similar: Vec<serde_json::Value> = clnt.get("/api/v1/players/similar?name=STARDUST1971").get_result().await;
+
+    assert_eq!(similar.len(), 2);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_status_workflow(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut *connection).await;
+
+    let reason: RejectionReason = clnt
+        .post(
+            "/api/v2/rejection-reasons/",
+            &serde_json::json! {{"name": "Insufficient proof", "description": "Please provide raw footage"}},
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    // Only rejected records can have a rejection reason
+    let etag = FullRecord::by_id(record, &mut *connection).await.unwrap().etag_string();
+    let json: serde_json::Value = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record),
+            &serde_json::json! {{"rejection_reason": reason.id}},
+        )
+        .authorize_as(&admin)
+        .header("If-Match", etag.clone())
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42245i64));
+
+    let patched: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record),
+            &serde_json::json! {{"status": "rejected", "rejection_reason": reason.id}},
+        )
+        .authorize_as(&admin)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.status, RecordStatus::Rejected);
+    assert_eq!(patched.rejection_reason, Some(reason.clone()));
+
+    let rejected: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v1/records/?rejection_reason={}", reason.id))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0]["rejection_reason"]["name"].as_str(), Some("Insufficient proof"));
+
+    // Rejected records cannot be sent back to their submitter by default
+    let json: serde_json::Value = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record),
+            &serde_json::json! {{"status": "needs info"}},
+        )
+        .authorize_as(&admin)
+        .header("If-Match", patched.etag_string())
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42244i64));
+
+    // Leaving the 'rejected' state clears the reason
+    let patched: FullRecord = clnt
+        .patch(format!("/api/v1/records/{}/", record), &serde_json::json! {{"status": "submitted"}})
+        .authorize_as(&admin)
+        .header("If-Match", patched.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.rejection_reason, None);
+
+    let patched: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}/", record),
+            &serde_json::json! {{"status": "needs info"}},
+        )
+        .authorize_as(&admin)
+        .header("If-Match", patched.etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(patched.status, RecordStatus::NeedsInfo);
+
+    // Disallow everything but approving records that need more info
+    clnt.put("/api/v2/status-transitions/")
+        .json(&serde_json::json! {[{"from": "needs info", "to": "approved"}]})
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let json: serde_json::Value = clnt
+        .patch(format!("/api/v1/records/{}/", record), &serde_json::json! {{"status": "rejected"}})
+        .authorize_as(&admin)
+        .header("If-Match", patched.etag_string())
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42244i64));
+}
+
 #[sqlx::test(migrations = "../migrations")]
 async fn test_no_submitter_info_on_unauthed_get(pool: Pool<Postgres>) {
     let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
@@ -183,3 +611,238 @@ async fn test_record_note_creation_and_deletion(pool: Pool<Postgres>) {
         .execute()
         .await;
 }
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_export_records_applies_pagination_filters(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id, player.id, &mut *connection).await;
+
+    let approved = add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut *connection).await;
+    add_simple_record(90, player.id, demon, RecordStatus::Rejected, &mut *connection).await;
+
+    let response = clnt
+        .get("/api/v1/records/?limit=1")
+        .header("Accept", "application/x-ndjson")
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let ndjson = response.into_string().await.unwrap();
+    let records: Vec<serde_json::Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["id"], approved);
+
+    // Filters that require permissions in the JSON path require them for exports, too
+    clnt.get("/api/v1/records/?status=REJECTED")
+        .header("Accept", "text/csv")
+        .expect_status(Status::Unauthorized)
+        .execute()
+        .await;
+
+    let csv = clnt
+        .get("/api/v1/records/")
+        .header("Accept", "text/csv")
+        .expect_status(Status::Ok)
+        .execute()
+        .await
+        .into_string()
+        .await
+        .unwrap();
+
+    let mut lines = csv.lines();
+    let columns: Vec<&str> = lines.next().unwrap().split(',').collect();
+
+    assert!(columns.contains(&"player.name"));
+    assert!(columns.contains(&"demon.name"));
+    assert_eq!(lines.count(), 1);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_authenticated_export_ratelimits(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+
+    // Helpers are exempt from the export ratelimit, so they do not eat into the budget shared by the ip
+    for _ in 0..11 {
+        clnt.get("/api/v1/records/")
+            .authorize_as(&helper)
+            .header("Accept", "text/csv")
+            .expect_status(Status::Ok)
+            .execute()
+            .await;
+    }
+
+    // Logging in does not lift the "10 per hour" ratelimit for everyone else
+    for _ in 0..10 {
+        clnt.get("/api/v1/records/")
+            .authorize_as(&user)
+            .header("Accept", "text/csv")
+            .expect_status(Status::Ok)
+            .execute()
+            .await;
+    }
+
+    let result: serde_json::Value = clnt
+        .get("/api/v1/records/")
+        .authorize_as(&user)
+        .header("Accept", "text/csv")
+        .expect_status(Status::TooManyRequests)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(42900));
+
+    // Plain pagination is not an export
+    clnt.get("/api/v1/records/")
+        .authorize_as(&user)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_progress_history(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+    let first = add_simple_record(60, player1.id, demon1, RecordStatus::Approved, &mut *connection).await;
+    let second = add_simple_record(85, player1.id, demon1, RecordStatus::Submitted, &mut *connection).await;
+
+    let etag = FullRecord::by_id(second, &mut *connection).await.unwrap().etag_string();
+
+    clnt.patch(format!("/api/v1/records/{}/", second), &serde_json::json! {{"status": "approved"}})
+        .authorize_as(&admin)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    // The 60% record is gone, but still part of the player's progress history
+    assert!(FullRecord::by_id(first, &mut *connection).await.is_err());
+
+    let player: FullPlayer = clnt
+        .get(format!("/api/v1/players/{}/", player1.id))
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(player.records.len(), 1);
+    assert_eq!(player.records[0].progress, 85);
+    assert_eq!(player.progress_history.len(), 1);
+    assert_eq!(player.progress_history[0].record_id, first);
+    assert_eq!(player.progress_history[0].progress, 60);
+
+    // Merging in a player with a better record keeps the history of both. The merged player's 85% record takes over
+    // the 100% of the surviving player's record it replaces, so that 100% is still live and not part of the history
+    add_simple_record(100, player2.id, demon1, RecordStatus::Approved, &mut *connection).await;
+
+    let mut merged = Player::by_id(player2.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap();
+
+    merged.set_name("stardust1971".to_string(), &mut *connection).await.unwrap();
+
+    let progress = merged.progress_history.iter().map(|entry| entry.progress).collect::<Vec<_>>();
+
+    assert_eq!(merged.records.len(), 1);
+    assert_eq!(merged.records[0].progress, 100);
+    assert_eq!(progress, vec![60]);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_reassign_worse_record_keeps_progress_history(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let player2 = DatabasePlayer::by_name_or_create("stardust1972", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+
+    add_simple_record(60, player1.id, demon, RecordStatus::Approved, &mut *connection).await;
+    let best = add_simple_record(100, player1.id, demon, RecordStatus::Submitted, &mut *connection).await;
+
+    let etag = FullRecord::by_id(best, &mut *connection).await.unwrap().etag_string();
+
+    clnt.patch(format!("/api/v1/records/{}/", best), &serde_json::json! {{"status": "approved"}})
+        .authorize_as(&admin)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let history_before = Player::by_id(player1.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap()
+        .progress_history;
+
+    assert_eq!(history_before.len(), 1);
+
+    // Reassigning a worse record to the player makes it take over their 100%, which stays their live record
+    let worse = add_simple_record(85, player2.id, demon, RecordStatus::Approved, &mut *connection).await;
+    let etag = FullRecord::by_id(worse, &mut *connection).await.unwrap().etag_string();
+
+    let record: FullRecord = clnt
+        .patch(
+            format!("/api/v1/records/{}/", worse),
+            &serde_json::json! {{"player": "stardust1971"}},
+        )
+        .authorize_as(&admin)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(record.progress, 100);
+
+    let player = Player::by_id(player1.id, &mut *connection)
+        .await
+        .unwrap()
+        .upgrade(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(player.records.len(), 1);
+    assert_eq!(player.records[0].progress, 100);
+    assert_eq!(player.progress_history, history_before);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_record_etag_ignores_referenced_objects(pool: Pool<Postgres>) {
+    let (_, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 87, player.id, player.id, &mut *connection).await;
+    let record = add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut *connection).await;
+
+    let before = FullRecord::by_id(record, &mut *connection).await.unwrap();
+
+    sqlx::query!("UPDATE players SET name = 'stardust1972' WHERE id = $1", player.id)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE demons SET position = 2 WHERE id = $1", demon)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let after = FullRecord::by_id(record, &mut *connection).await.unwrap();
+
+    assert_eq!(after.player.name, "stardust1972");
+    assert_eq!(after.demon.position, 2);
+    assert_eq!(before.patch_part(), after.patch_part());
+    assert_ne!(before.get_part(), after.get_part());
+}
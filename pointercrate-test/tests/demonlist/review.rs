@@ -0,0 +1,160 @@
+use pointercrate_core::etag::Taggable;
+use pointercrate_demonlist::{
+    player::DatabasePlayer,
+    record::{FullRecord, RecordStatus},
+    LIST_HELPER, LIST_MODERATOR,
+};
+use pointercrate_test::{demonlist::add_simple_record, user::system_user_with_perms};
+use pointercrate_user::{AuthenticatedUser, Registration};
+use rocket::http::Status;
+use sqlx::{PgConnection, Pool, Postgres};
+
+async fn grant(user: &AuthenticatedUser, role: &str, connection: &mut PgConnection) {
+    sqlx::query!(
+        "INSERT INTO member_roles (member, role) SELECT $1, id FROM roles WHERE name = $2",
+        user.inner().id,
+        role
+    )
+    .execute(connection)
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_review_queue(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let helper1 = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let helper2 = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    grant(&helper2, LIST_HELPER.name(), &mut *connection).await;
+
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+    let demon2 = pointercrate_test::demonlist::add_demon("Bloodlust", 2, 50, player1.id, player1.id, &mut *connection).await;
+    let record1 = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut *connection).await;
+    let record2 = add_simple_record(100, player1.id, demon2, RecordStatus::Submitted, &mut *connection).await;
+
+    let assignment: serde_json::Value = clnt
+        .post("/api/v2/review-queue/next", &())
+        .authorize_as(&helper1)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(assignment["record"].as_i64(), Some(record1 as i64));
+    assert_eq!(assignment["reviewer"].as_i64(), Some(helper1.inner().id as i64));
+
+    // Asking again hands out the same submission
+    let again: serde_json::Value = clnt
+        .post("/api/v2/review-queue/next", &())
+        .authorize_as(&helper1)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(again["record"].as_i64(), Some(record1 as i64));
+
+    // Another helper gets the next submission
+    let assignment: serde_json::Value = clnt
+        .post("/api/v2/review-queue/next", &())
+        .authorize_as(&helper2)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(assignment["record"].as_i64(), Some(record2 as i64));
+
+    let under_review: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/?under_review=true")
+        .authorize_as(&helper1)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(under_review.len(), 2);
+
+    // ... and cannot modify the submission assigned to the first helper
+    let etag = FullRecord::by_id(record1, &mut *connection).await.unwrap().etag_string();
+    let json: serde_json::Value = clnt
+        .patch(format!("/api/v1/records/{}/", record1), &serde_json::json! {{"status": "approved"}})
+        .authorize_as(&helper2)
+        .header("If-Match", etag.clone())
+        .expect_status(Status::Conflict)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(40915));
+
+    clnt.patch(format!("/api/v1/records/{}/", record1), &serde_json::json! {{"status": "approved"}})
+        .authorize_as(&helper1)
+        .header("If-Match", etag)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let active: Vec<serde_json::Value> = clnt
+        .get("/api/v2/review-queue/")
+        .authorize_as(&helper1)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0]["record"].as_i64(), Some(record2 as i64));
+
+    clnt.post("/api/v2/review-queue/next", &())
+        .authorize_as(&helper1)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    // Only list moderators can release submissions assigned to other helpers
+    clnt.delete(format!("/api/v2/review-queue/{}", record2))
+        .authorize_as(&helper1)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    clnt.delete(format!("/api/v2/review-queue/{}", record2))
+        .authorize_as(&helper2)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+
+    let assignment: serde_json::Value = clnt
+        .post("/api/v2/review-queue/next", &())
+        .authorize_as(&helper1)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(assignment["record"].as_i64(), Some(record2 as i64));
+
+    grant(&helper2, LIST_MODERATOR.name(), &mut *connection).await;
+
+    let statistics: serde_json::Value = clnt
+        .get("/api/v2/review-queue/statistics?days=7")
+        .authorize_as(&helper2)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(statistics["queue"]["size"].as_i64(), Some(1));
+    assert_eq!(statistics["queue"]["assigned"].as_i64(), Some(1));
+    assert_eq!(
+        statistics["reviewers"][0]["reviewer"]["id"].as_i64(),
+        Some(helper1.inner().id as i64)
+    );
+    assert_eq!(statistics["reviewers"][0]["reviews"].as_i64(), Some(1));
+    assert_eq!(statistics["reviewers"][0]["approved"].as_i64(), Some(1));
+    assert!(statistics["reviewers"][0]["average_review_time"].is_number());
+}
@@ -0,0 +1,129 @@
+use pointercrate_demonlist::{player::DatabasePlayer, record::RecordStatus, LIST_ADMINISTRATOR, LIST_HELPER};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+const LINEAR: &str = r#"{"name": "Linear", "partial_base": 5, "partial_divisor": 10, "brackets": [{"lower_bound": 0, "upper_bound": 150, "scale": 0, "exponent": 0, "shift": 0, "constant": 100}]}"#;
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_scoring_formula_endpoints(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let helper = pointercrate_test::user::system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let linear: serde_json::Value = serde_json::from_str(LINEAR).unwrap();
+
+    let formulas: Vec<serde_json::Value> = clnt.get("/api/v2/scoring/").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(formulas.len(), 1);
+
+    let active: serde_json::Value = clnt.get("/api/v2/scoring/active").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(active, formulas[0]);
+
+    let result: serde_json::Value = clnt.get("/api/v2/scoring/1000").expect_status(Status::NotFound).get_result().await;
+
+    assert_eq!(result["code"].as_i64(), Some(40401));
+
+    clnt.post("/api/v2/scoring/", &linear)
+        .authorize_as(&helper)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let formula: serde_json::Value = clnt
+        .post("/api/v2/scoring/", &linear)
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let formula_id = formula["id"].as_i64().unwrap();
+
+    assert_eq!(formula["brackets"].as_array().map(Vec::len), Some(1));
+
+    let fetched: serde_json::Value = clnt
+        .get(format!("/api/v2/scoring/{}", formula_id))
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(fetched, formula);
+
+    // Previewing compares against the formula the list currently uses
+    let verifier = DatabasePlayer::by_name_or_create("Riot", &mut *connection).await.unwrap();
+    let player = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, verifier.id, verifier.id, &mut *connection).await;
+
+    pointercrate_test::demonlist::add_simple_record(100, player.id, demon, RecordStatus::Approved, &mut *connection).await;
+
+    clnt.get(format!("/api/v2/scoring/{}/preview", formula_id))
+        .authorize_as(&helper)
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+
+    let preview: Vec<serde_json::Value> = clnt
+        .get(format!("/api/v2/scoring/{}/preview", formula_id))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(preview.len(), 2);
+
+    for entry in &preview {
+        assert_eq!(entry["current_rank"].as_i64(), Some(1));
+        assert_eq!(entry["candidate_rank"].as_i64(), Some(1));
+        assert!((entry["current_score"].as_f64().unwrap() - 250.0).abs() < 1e-9);
+        assert!((entry["candidate_score"].as_f64().unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    let result: serde_json::Value = clnt
+        .get(format!("/api/v2/scoring/{}/preview?limit=0", formula_id))
+        .authorize_as(&admin)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(42207));
+
+    clnt.post(format!("/api/v2/scoring/{}/activate", formula_id), &())
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let active: serde_json::Value = clnt.get("/api/v2/scoring/active").expect_status(Status::Ok).get_result().await;
+
+    assert_eq!(active["id"].as_i64(), Some(formula_id));
+
+    let ranking: Vec<serde_json::Value> = clnt.get("/api/v1/players/ranking/").expect_status(Status::Ok).get_result().await;
+
+    assert!((ranking[0]["score"].as_f64().unwrap() - 100.0).abs() < 1e-9);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_active_formula_without_default_list(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    sqlx::query!("UPDATE lists SET is_default = FALSE")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let result: serde_json::Value = clnt
+        .get("/api/v2/scoring/active")
+        .expect_status(Status::NotFound)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(40401));
+
+    let result: serde_json::Value = clnt
+        .get("/api/v2/scoring/active?list=1000")
+        .expect_status(Status::NotFound)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(40401));
+}
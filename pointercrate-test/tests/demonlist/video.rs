@@ -0,0 +1,200 @@
+use pointercrate_core::job::{self, JobQueue};
+use pointercrate_demonlist::{
+    player::DatabasePlayer,
+    record::{FullRecord, RecordStatus},
+    LIST_HELPER,
+};
+use pointercrate_demonlist_api::jobs::{check_video_links, validate_submission, CheckVideoLinks, OEmbedSource, ValidateSubmission};
+use pointercrate_test::{demonlist::add_simple_record, oembed::OEmbedProvider, user::system_user_with_perms};
+use rocket::http::Status;
+use sqlx::{PgConnection, Pool, Postgres};
+
+fn validation_queue(pool: Pool<Postgres>, provider: &OEmbedProvider) -> JobQueue {
+    let http_client = reqwest::Client::new();
+    let endpoint = provider.url();
+
+    JobQueue::new(pool.clone()).register(move |job: ValidateSubmission| {
+        validate_submission(job, pool.clone(), http_client.clone(), OEmbedSource::Endpoint(endpoint.clone()))
+    })
+}
+
+async fn submit_with_video(record_id: i32, connection: &mut PgConnection) {
+    sqlx::query!(
+        "UPDATE records SET video = 'https://www.youtube.com/watch?v=dQw4w9WgXcQ' WHERE id = $1",
+        record_id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    job::enqueue(&ValidateSubmission { record_id }, connection).await.unwrap();
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_video_metadata_flags(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut *connection).await;
+
+    pointercrate_test::demonlist::put_claim(helper.inner().id, player1.id, true, false, &mut *connection).await;
+
+    sqlx::query!(
+        "UPDATE members SET youtube_channel = 'https://www.youtube.com/@stardust1971' WHERE member_id = $1",
+        helper.inner().id
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+
+    submit_with_video(record, &mut *connection).await;
+
+    clnt.get(format!("/api/v1/records/{}/video", record))
+        .authorize_as(&helper)
+        .expect_status(Status::NotFound)
+        .execute()
+        .await;
+
+    let provider = OEmbedProvider::start(
+        200,
+        serde_json::json! {{
+            "title": "Bloodbath 100%",
+            "author_name": "Someone Else",
+            "author_url": "https://www.youtube.com/@someoneelse",
+            "upload_date": "2013-06-12 14:44:08"
+        }},
+    )
+    .await;
+
+    assert!(validation_queue(pool, &provider).run_next().await.unwrap());
+
+    let requests = provider.requests();
+
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].path.contains("format=json"));
+
+    let metadata: serde_json::Value = clnt
+        .get(format!("/api/v1/records/{}/video", record))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(metadata["title"], "Bloodbath 100%");
+    assert_eq!(metadata["flags"], serde_json::json!(["uploaded_before_demon", "channel_mismatch"]));
+
+    let flagged: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/?video_flagged=true")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(flagged[0]["id"].as_i64(), Some(record as i64));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_unavailable_video_is_flagged_not_deleted(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+    let record = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut *connection).await;
+
+    submit_with_video(record, &mut *connection).await;
+
+    let provider = OEmbedProvider::start(404, serde_json::json!("Not Found")).await;
+
+    assert!(validation_queue(pool, &provider).run_next().await.unwrap());
+    assert!(FullRecord::by_id(record, &mut *connection).await.is_ok());
+
+    let metadata: serde_json::Value = clnt
+        .get(format!("/api/v1/records/{}/video", record))
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(metadata["flags"], serde_json::json!(["video_unavailable"]));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_dead_link_detection(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let helper = system_user_with_perms(LIST_HELPER, &mut *connection).await;
+    let player1 = DatabasePlayer::by_name_or_create("stardust1971", &mut *connection).await.unwrap();
+    let demon1 = pointercrate_test::demonlist::add_demon("Bloodbath", 1, 50, player1.id, player1.id, &mut *connection).await;
+    let approved = add_simple_record(100, player1.id, demon1, RecordStatus::Approved, &mut *connection).await;
+    let submitted = add_simple_record(100, player1.id, demon1, RecordStatus::Submitted, &mut *connection).await;
+
+    sqlx::query!(
+        "UPDATE records SET video = 'https://www.youtube.com/watch?v=dQw4w9WgXcQ' WHERE id = $1",
+        approved
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE records SET video = 'https://www.youtube.com/watch?v=9bZkp7q19f0' WHERE id = $1",
+        submitted
+    )
+    .execute(&mut *connection)
+    .await
+    .unwrap();
+    sqlx::query!("UPDATE demons SET video = 'https://vimeo.com/123456' WHERE id = $1", demon1)
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    job::enqueue(&CheckVideoLinks {}, &mut *connection).await.unwrap();
+
+    let provider = OEmbedProvider::start(404, serde_json::json!("Not Found")).await;
+    let endpoint = provider.url();
+    let link_pool = pool.clone();
+    let queue = JobQueue::new(pool).register(move |job: CheckVideoLinks| {
+        check_video_links(
+            job,
+            link_pool.clone(),
+            reqwest::Client::new(),
+            OEmbedSource::Endpoint(endpoint.clone()),
+        )
+    });
+
+    assert!(queue.run_next().await.unwrap());
+
+    // Submissions aren't checked, their video is validated on submission
+    assert_eq!(provider.requests().len(), 2);
+
+    // The next batch is only checked once the next check period starts
+    assert!(!queue.run_next().await.unwrap());
+
+    let broken: Vec<serde_json::Value> = clnt
+        .get("/api/v2/videos/broken")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(broken.len(), 2);
+    assert!(broken
+        .iter()
+        .any(|link| link["video"] == "https://vimeo.com/123456" && link["demons"] == serde_json::json!([demon1])));
+    assert!(broken
+        .iter()
+        .any(|link| link["video"] == "https://www.youtube.com/watch?v=dQw4w9WgXcQ" && link["records"] == serde_json::json!([approved])));
+
+    let records: Vec<serde_json::Value> = clnt
+        .get("/api/v1/records/?broken_video=true")
+        .authorize_as(&helper)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["id"].as_i64(), Some(approved as i64));
+}
@@ -0,0 +1,217 @@
+use pointercrate_core::job::JobQueue;
+use pointercrate_demonlist::{
+    webhook::{self, DeliverWebhook, Webhook, WebhookEvent, WebhookFormat},
+    LIST_ADMINISTRATOR, LIST_MODERATOR,
+};
+use pointercrate_demonlist_api::jobs::deliver_webhook;
+use pointercrate_test::webhook::WebhookReceiver;
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+fn delivery_queue(pool: Pool<Postgres>) -> JobQueue {
+    let http_client = reqwest::Client::new();
+
+    JobQueue::new(pool.clone()).register(move |job: DeliverWebhook| deliver_webhook(job, pool.clone(), http_client.clone()))
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_webhook_delivery(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+
+    let receiver = WebhookReceiver::start(200).await;
+
+    let webhook: serde_json::Value = clnt
+        .post(
+            "/api/v1/webhooks/",
+            &serde_json::json!({"url": receiver.url(), "events": ["demon_added"]}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    let demon = serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": []}};
+
+    clnt.post("/api/v2/demons/", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    let queue = delivery_queue(pool);
+
+    assert!(queue.run_next().await.unwrap());
+
+    let requests = receiver.requests();
+
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].header("X-Webhook-Event"), Some("demon_added"));
+    assert_eq!(
+        requests[0].header("X-Webhook-Signature"),
+        Some(webhook::signature(webhook["secret"].as_str().unwrap(), &requests[0].body).as_str())
+    );
+
+    let body = requests[0].json();
+
+    assert_eq!(body["event"], "demon_added");
+    assert_eq!(body["data"]["name"], "Bloodbath");
+
+    let deliveries: serde_json::Value = clnt
+        .get(format!("/api/v1/webhooks/{}/deliveries/", webhook["id"]))
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+    let deliveries = deliveries.as_array().unwrap();
+
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0]["attempts"], 1);
+    assert_eq!(deliveries[0]["response_status"], 200);
+    assert!(!deliveries[0]["delivered_at"].is_null());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_failed_webhook_delivery_is_logged(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+
+    let failing = WebhookReceiver::start(500).await;
+    let unsubscribed = WebhookReceiver::start(200).await;
+
+    let webhook: serde_json::Value = clnt
+        .post(
+            "/api/v1/webhooks/",
+            &serde_json::json!({"url": failing.url(), "events": ["demon_added"]}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_result()
+        .await;
+
+    clnt.post(
+        "/api/v1/webhooks/",
+        &serde_json::json!({"url": unsubscribed.url(), "events": ["player_banned"]}),
+    )
+    .authorize_as(&admin)
+    .expect_status(Status::Created)
+    .execute()
+    .await;
+
+    let demon = serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": []}};
+
+    clnt.post("/api/v2/demons/", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    let queue = delivery_queue(pool);
+
+    assert!(queue.run_next().await.unwrap());
+    // The failed delivery is only retried after a backoff
+    assert!(!queue.run_next().await.unwrap());
+
+    assert_eq!(failing.requests().len(), 1);
+    assert!(unsubscribed.requests().is_empty());
+
+    let deliveries: serde_json::Value = clnt
+        .get(format!("/api/v1/webhooks/{}/deliveries/?delivered=false", webhook["id"]))
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+    let deliveries = deliveries.as_array().unwrap();
+
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0]["response_status"], 500);
+    assert!(deliveries[0]["delivered_at"].is_null());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_webhook_requires_events(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let result: serde_json::Value = clnt
+        .post(
+            "/api/v1/webhooks/",
+            &serde_json::json!({"url": "https://example.com/hook", "events": []}),
+        )
+        .authorize_as(&admin)
+        .expect_status(Status::UnprocessableEntity)
+        .get_result()
+        .await;
+
+    assert_eq!(result["code"].as_i64(), Some(42237));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_discord_webhook_delivery(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool.clone()).await;
+
+    let moderator = pointercrate_test::user::system_user_with_perms(LIST_MODERATOR, &mut *connection).await;
+
+    let receiver = WebhookReceiver::start(200).await;
+
+    // as done on startup for a configured DISCORD_WEBHOOK
+    let webhook = Webhook::adopt_discord_webhook(receiver.url(), &mut *connection)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(webhook.format, WebhookFormat::Discord);
+    assert_eq!(webhook.events, vec![WebhookEvent::RecordSubmitted]);
+
+    // adopting it again does nothing
+    assert!(Webhook::adopt_discord_webhook(receiver.url(), &mut *connection)
+        .await
+        .unwrap()
+        .is_none());
+
+    sqlx::query!("UPDATE webhooks SET events = ARRAY['demon_added']")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let demon = serde_json::json! {{"name": "Bloodbath", "requirement": 90, "position": 1, "verifier": "Riot", "publisher": "Riot", "creators": []}};
+
+    clnt.post("/api/v2/demons/", &demon)
+        .authorize_as(&moderator)
+        .expect_status(Status::Created)
+        .execute()
+        .await;
+
+    assert!(delivery_queue(pool).run_next().await.unwrap());
+
+    let requests = receiver.requests();
+
+    assert_eq!(requests.len(), 1);
+
+    let body = requests[0].json();
+
+    assert_eq!(body["content"], "**Demon added!**");
+    assert!(body["embeds"][0]["description"].as_str().unwrap().contains("Bloodbath"));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn test_unknown_webhook_event_in_database(pool: Pool<Postgres>) {
+    let (clnt, mut connection) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(LIST_ADMINISTRATOR, &mut *connection).await;
+
+    let id = sqlx::query!("INSERT INTO webhooks (url, events) VALUES ('https://example.com/hook', ARRAY['record_deleted']) RETURNING id")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .id;
+
+    clnt.get(format!("/api/v1/webhooks/{}", id))
+        .authorize_as(&admin)
+        .expect_status(Status::InternalServerError)
+        .execute()
+        .await;
+}
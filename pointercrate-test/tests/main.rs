@@ -1,2 +1,3 @@
 mod demonlist;
+mod ratelimits;
 mod user;
@@ -0,0 +1,97 @@
+use pointercrate_core::{error::CoreError, ratelimits::check_persistent};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_persistent_ratelimit(pool: Pool<Postgres>) {
+    for remaining in (0..3).rev() {
+        let info = check_persistent(&pool, "test", "ip:127.0.0.1", 3, 1800, "Too many!").await.unwrap();
+
+        assert_eq!(info.limit, 3);
+        assert_eq!(info.remaining, remaining);
+        // one token is refilled every 600 seconds
+        assert!(info.reset.as_secs_f64() <= 600.0 * (3 - remaining) as f64);
+    }
+
+    match check_persistent(&pool, "test", "ip:127.0.0.1", 3, 1800, "Too many!").await {
+        Err(CoreError::Ratelimited {
+            message,
+            remaining,
+            limit,
+            reset,
+        }) => {
+            assert_eq!(message, "Too many!");
+            assert_eq!(limit, 3);
+            assert!(0.0 < remaining.as_secs_f64() && remaining.as_secs_f64() <= 600.0);
+            assert!(1200.0 < reset.as_secs_f64() && reset.as_secs_f64() <= 1800.0);
+        },
+        other => panic!("Expected request to be ratelimited, got {:?}", other),
+    }
+
+    // other keys and other limiters have their own buckets
+    check_persistent(&pool, "test", "ip:127.0.0.2", 3, 1800, "Too many!").await.unwrap();
+    check_persistent(&pool, "other", "ip:127.0.0.1", 3, 1800, "Too many!")
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_ratelimit_check_purges_full_buckets(pool: Pool<Postgres>) {
+    sqlx::query!(
+        "INSERT INTO ratelimit_buckets (limiter, key, theoretical_arrival) VALUES \
+         ('test', 'refilled', NOW() AT TIME ZONE 'utc' - INTERVAL '1 minute'), \
+         ('test', 'limited', NOW() AT TIME ZONE 'utc' + INTERVAL '1 hour')"
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let retry_after = sqlx::query!("SELECT retry_after FROM ratelimit_check('other', '', 1, 60)")
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .retry_after;
+
+    assert_eq!(retry_after, None);
+
+    let mut buckets = sqlx::query!("SELECT limiter, key FROM ratelimit_buckets")
+        .fetch_all(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| (row.limiter, row.key))
+        .collect::<Vec<_>>();
+
+    buckets.sort();
+
+    assert_eq!(
+        buckets,
+        vec![("other".to_string(), "".to_string()), ("test".to_string(), "limited".to_string())]
+    );
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_unauthenticated_exports_ratelimited(pool: Pool<Postgres>) {
+    let (clnt, _) = pointercrate_test::demonlist::setup_rocket(pool).await;
+
+    for remaining in (0..10).rev() {
+        clnt.get("/api/v1/players/ranking/")
+            .header("Accept", "text/csv")
+            .expect_status(Status::Ok)
+            .expect_header("X-RateLimit-Remaining", remaining.to_string())
+            .execute()
+            .await;
+    }
+
+    let json: serde_json::Value = clnt
+        .get("/api/v2/demons/listed/")
+        .header("Accept", "application/x-ndjson")
+        .expect_status(Status::TooManyRequests)
+        .get_result()
+        .await;
+
+    assert_eq!(json["code"].as_i64(), Some(42900));
+
+    // Paging through the same data is not affected
+    clnt.get("/api/v1/players/ranking/").expect_status(Status::Ok).execute().await;
+}
@@ -0,0 +1,213 @@
+use pointercrate_core::job::{self, Job, JobQueue};
+use pointercrate_user::ADMINISTRATOR;
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize)]
+struct FailingJob {
+    value: i32,
+}
+
+impl Job for FailingJob {
+    const KIND: &'static str = "test_failing";
+    const MAX_ATTEMPTS: i32 = 1;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_dead_job_retry(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool.clone()).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+
+    job::enqueue(&FailingJob { value: 1 }, &mut *connection).await.unwrap();
+    // identical to a pending job, so nothing happens
+    job::enqueue(&FailingJob { value: 1 }, &mut *connection).await.unwrap();
+
+    let queue = JobQueue::new(pool).register(|job: FailingJob| async move { Err(format!("failed on {}", job.value)) });
+
+    assert!(queue.run_next().await.unwrap());
+    assert!(!queue.run_next().await.unwrap());
+
+    let jobs: serde_json::Value = client.get("/api/v1/jobs/?state=dead").authorize_as(&admin).get_result().await;
+    let jobs = jobs.as_array().unwrap();
+
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0]["kind"], "test_failing");
+    assert_eq!(jobs[0]["attempts"], 1);
+    assert_eq!(jobs[0]["last_error"], "failed on 1");
+
+    let job_id = jobs[0]["id"].as_i64().unwrap();
+
+    let retried: serde_json::Value = client
+        .post(format!("/api/v1/jobs/{}/retry", job_id), &())
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+
+    assert_eq!(retried["state"], "pending");
+    assert_eq!(retried["attempts"], 0);
+
+    // only dead jobs can be retried
+    client
+        .post(format!("/api/v1/jobs/{}/retry", job_id), &())
+        .authorize_as(&admin)
+        .expect_status(Status::Conflict)
+        .execute()
+        .await;
+
+    // the job is picked up again
+    assert!(queue.run_next().await.unwrap());
+}
+
+#[derive(Serialize, Deserialize)]
+struct PanickingJob {
+    value: i32,
+}
+
+impl Job for PanickingJob {
+    const KIND: &'static str = "test_panicking";
+    const MAX_ATTEMPTS: i32 = 2;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_panicking_job(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    job::enqueue(&PanickingJob { value: 1 }, &mut *connection).await.unwrap();
+
+    let queue = JobQueue::new(pool)
+        .register(|job: PanickingJob| async move {
+            if job.value > 0 {
+                panic!("panicked on {}", job.value)
+            }
+
+            Ok(())
+        })
+        .register(|_: FailingJob| async move { Ok(()) });
+
+    // The panic fails the job like any error would, and the worker carries on
+    assert!(queue.run_next().await.unwrap());
+
+    let row = sqlx::query!("SELECT state, attempts, last_error FROM jobs WHERE kind = 'test_panicking'")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(row.state, "pending");
+    assert_eq!(row.attempts, 1);
+    assert_eq!(row.last_error.as_deref(), Some("Panicked: panicked on 1"));
+
+    sqlx::query!("UPDATE jobs SET run_at = NOW() AT TIME ZONE 'utc' WHERE kind = 'test_panicking'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    assert!(queue.run_next().await.unwrap());
+
+    let row = sqlx::query!("SELECT state, attempts FROM jobs WHERE kind = 'test_panicking'")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(row.state, "dead");
+    assert_eq!(row.attempts, 2);
+
+    // Later jobs still run
+    job::enqueue(&FailingJob { value: 2 }, &mut *connection).await.unwrap();
+
+    assert!(queue.run_next().await.unwrap());
+
+    let row = sqlx::query!("SELECT state FROM jobs WHERE kind = 'test_failing'")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(row.state, "succeeded");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_abandoned_job_at_max_attempts(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    job::enqueue(&FailingJob { value: 1 }, &mut *connection).await.unwrap();
+
+    // simulate a worker dying while running the job on its last attempt
+    sqlx::query!("UPDATE jobs SET state = 'running', attempts = 1, locked_until = NOW() AT TIME ZONE 'utc' - INTERVAL '1 minute'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    let queue = JobQueue::new(pool).register(|_: FailingJob| async move { Ok(()) });
+
+    assert!(!queue.run_next().await.unwrap());
+
+    let row = sqlx::query!("SELECT state, attempts FROM jobs")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(row.state, "dead");
+    assert_eq!(row.attempts, 1);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_enqueue_deduplicates_running_jobs(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    job::enqueue(&FailingJob { value: 1 }, &mut *connection).await.unwrap();
+
+    sqlx::query!("UPDATE jobs SET state = 'running', attempts = 1, locked_until = NOW() AT TIME ZONE 'utc' + INTERVAL '1 minute'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    // identical to a running job, so nothing happens
+    job::enqueue(&FailingJob { value: 1 }, &mut *connection).await.unwrap();
+
+    let count = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM jobs")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+    assert_eq!(count.count, 1);
+
+    // but the running job can schedule its successor
+    job::enqueue_successor(&FailingJob { value: 1 }, Duration::ZERO, &mut *connection)
+        .await
+        .unwrap();
+
+    let count = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM jobs")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap();
+    assert_eq!(count.count, 2);
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_purge_succeeded_jobs(pool: Pool<Postgres>) {
+    let mut connection = pool.acquire().await.unwrap();
+
+    job::enqueue(&FailingJob { value: 1 }, &mut *connection).await.unwrap();
+    job::enqueue(&FailingJob { value: 2 }, &mut *connection).await.unwrap();
+
+    let queue = JobQueue::new(pool).register(|_: FailingJob| async move { Ok(()) });
+
+    assert!(queue.run_next().await.unwrap());
+    assert!(queue.run_next().await.unwrap());
+
+    sqlx::query!("UPDATE jobs SET finished_at = NOW() AT TIME ZONE 'utc' - INTERVAL '30 days' WHERE payload->>'value' = '1'")
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(queue.purge_succeeded().await.unwrap(), 1);
+
+    let remaining = sqlx::query!("SELECT payload->>'value' AS value FROM jobs")
+        .fetch_all(&mut *connection)
+        .await
+        .unwrap();
+
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].value.as_deref(), Some("2"));
+}
@@ -1,2 +1,6 @@
+mod jobs;
 mod login;
+mod paginate;
+mod patch;
 mod register;
+mod roles;
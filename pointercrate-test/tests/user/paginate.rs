@@ -0,0 +1,188 @@
+use pointercrate_user::{AuthenticatedUser, Registration, ADMINISTRATOR};
+use rocket::http::{ContentType, Status};
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+
+fn parse_link_header(header: &str) -> HashMap<String, String> {
+    header
+        .split(", ")
+        .map(|link| {
+            let (url, rel) = link.split_once("; ").unwrap();
+
+            (
+                rel.trim_start_matches("rel=\"").trim_end_matches('"').to_string(),
+                url.trim_start_matches('<').trim_end_matches('>').to_string(),
+            )
+        })
+        .collect()
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_user_pagination_links(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+
+    for name in ["Jacob", "Jacob2", "Jacob3"] {
+        AuthenticatedUser::register(
+            Registration {
+                name: name.to_string(),
+                password: "bad password".to_string(),
+            },
+            &mut *connection,
+        )
+        .await
+        .unwrap();
+    }
+
+    let response = client
+        .get("/api/v1/users/?limit=3")
+        .authorize_as(&admin)
+        .header("Prefer", "count=exact")
+        .expect_status(Status::Ok)
+        .expect_header("X-Total-Count", "4")
+        .execute()
+        .await;
+
+    let links = parse_link_header(response.headers().get_one("Link").unwrap());
+    let users: Vec<serde_json::Value> = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+
+    assert_eq!(users.len(), 3);
+    assert!(links.contains_key("first"));
+    assert!(links.contains_key("last"));
+    assert!(!links.contains_key("prev"));
+    assert_eq!(links["next"], format!("/api/v1/users/?after={}&limit=3", users[2]["id"]));
+
+    let response = client
+        .get(&links["next"])
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    assert!(response.headers().get_one("X-Total-Count").is_none());
+
+    let links = parse_link_header(response.headers().get_one("Link").unwrap());
+    let next_page: Vec<serde_json::Value> = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+
+    assert_eq!(next_page.len(), 1);
+    assert!(!links.contains_key("next"));
+    assert_eq!(links["prev"], format!("/api/v1/users/?before={}&limit=3", next_page[0]["id"]));
+
+    // Going back should give us the first page again, in ascending order
+    let previous_page: Vec<serde_json::Value> = client
+        .get(&links["prev"])
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(previous_page, users);
+
+    // The 'last' link has to work even though it lies past the largest member id
+    let last_page: Vec<serde_json::Value> = client
+        .get(&links["last"])
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_result()
+        .await;
+
+    assert_eq!(last_page.last(), next_page.last());
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_user_pagination_bounded(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+
+    let users: Vec<serde_json::Value> = client.get("/api/v1/users/").authorize_as(&admin).get_result().await;
+    let id = users[0]["id"].as_i64().unwrap();
+
+    let response = client
+        .get(format!("/api/v1/users/?after={}&before={}", id - 1, id + 1))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let links = parse_link_header(response.headers().get_one("Link").unwrap());
+
+    assert_eq!(links["last"], format!("/api/v1/users/?before={}&limit=50", id + 1));
+    assert!(!links.contains_key("next"));
+
+    client
+        .get(format!("/api/v1/users/?after={}&before={}", id + 1, id - 1))
+        .authorize_as(&admin)
+        .expect_status(Status::UnprocessableEntity)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_user_export(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+
+    for name in ["Jacob", "Jacob, \"the\" second"] {
+        AuthenticatedUser::register(
+            Registration {
+                name: name.to_string(),
+                password: "bad password".to_string(),
+            },
+            &mut *connection,
+        )
+        .await
+        .unwrap();
+    }
+
+    // 'limit' does not apply to exports
+    let response = client
+        .get("/api/v1/users/?limit=1")
+        .authorize_as(&admin)
+        .header("Accept", "text/csv")
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    assert_eq!(response.content_type(), Some(ContentType::CSV));
+
+    let csv = response.into_string().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].split(',').any(|column| column == "name"));
+    assert!(lines[3].contains("\"Jacob, \"\"the\"\" second\""));
+
+    let response = client
+        .get("/api/v1/users/?limit=1&name=Jacob")
+        .authorize_as(&admin)
+        .header("Accept", "application/x-ndjson")
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    assert_eq!(response.content_type(), Some(ContentType::new("application", "x-ndjson")));
+
+    let ndjson = response.into_string().await.unwrap();
+    let users: Vec<serde_json::Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0]["name"], "Jacob");
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_user_export_requires_permissions(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let user = pointercrate_test::user::add_normal_user(&mut *connection).await;
+
+    client
+        .get("/api/v1/users/")
+        .authorize_as(&user)
+        .header("Accept", "text/csv")
+        .expect_status(Status::Forbidden)
+        .execute()
+        .await;
+}
@@ -0,0 +1,59 @@
+use pointercrate_core::etag::Taggable;
+use pointercrate_user::{AuthenticatedUser, Registration, ADMINISTRATOR};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_patch_user_with_outdated_etag(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+    let jacob = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    let error: serde_json::Value = client
+        .patch(
+            format!("/api/v1/users/{}", jacob.inner().id),
+            &serde_json::json!({"display_name": "Jacob2"}),
+        )
+        .authorize_as(&admin)
+        .header("If-Match", "W/\"5678;1234\"")
+        .expect_status(Status::PreconditionFailed)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], 41201);
+
+    let error: serde_json::Value = client
+        .patch(
+            format!("/api/v1/users/{}", jacob.inner().id),
+            &serde_json::json!({"display_name": "Jacob2"}),
+        )
+        .authorize_as(&admin)
+        .header("If-Match", "\"2;5678;1234\"")
+        .expect_status(Status::PreconditionFailed)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], 41200);
+
+    let user: serde_json::Value = client
+        .patch(
+            format!("/api/v1/users/{}", jacob.inner().id),
+            &serde_json::json!({"display_name": "Jacob2"}),
+        )
+        .authorize_as(&admin)
+        .header("If-Match", jacob.inner().etag_string())
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(user["display_name"], "Jacob2");
+}
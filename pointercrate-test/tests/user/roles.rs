@@ -0,0 +1,202 @@
+use pointercrate_user::{AuthenticatedUser, Registration, ADMINISTRATOR};
+use rocket::http::Status;
+use sqlx::{Pool, Postgres};
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_legacy_permissions_migrated_to_roles(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+
+    let roles: serde_json::Value = client.get("/api/v1/roles/").authorize_as(&admin).get_result().await;
+    let roles = roles.as_array().unwrap();
+
+    let administrator = roles.iter().find(|role| role["name"] == "Administrator").unwrap();
+
+    assert_eq!(administrator["legacy_bit"], 0x4000);
+    assert_eq!(administrator["implies"], serde_json::json!(["Moderator"]));
+    assert_eq!(administrator["assigns"], serde_json::json!(["List Administrator", "Moderator"]));
+
+    // the legacy bitmask is kept in sync with the roles
+    let me: serde_json::Value = client.get("/api/v1/auth/me").authorize_as(&admin).get_success_result().await;
+
+    assert_eq!(me["permissions"], 0x4000);
+    assert_eq!(me["roles"], serde_json::json!(["Administrator"]));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_assign_new_role(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+    let jacob = AuthenticatedUser::register(
+        Registration {
+            name: "Jacob".to_string(),
+            password: "bad password".to_string(),
+        },
+        &mut *connection,
+    )
+    .await
+    .unwrap();
+
+    let role: serde_json::Value = client
+        .post("/api/v1/roles/", &serde_json::json!({"name": "Video Checker"}))
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+    let role_id = role["id"].as_i64().unwrap();
+
+    assert_eq!(role["legacy_bit"], serde_json::Value::Null);
+
+    // Nobody is allowed to assign the new role yet
+    let error: serde_json::Value = client
+        .put(format!("/api/v1/users/{}/roles/{}", jacob.inner().id, role_id))
+        .authorize_as(&admin)
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], 40305);
+
+    let administrator_id = sqlx::query!("SELECT id FROM roles WHERE name = 'Administrator'")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .id;
+
+    client
+        .put(format!("/api/v1/roles/{}/assigns/{}", administrator_id, role_id))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let user: serde_json::Value = client
+        .put(format!("/api/v1/users/{}/roles/{}", jacob.inner().id, role_id))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(user["roles"], serde_json::json!(["Video Checker"]));
+    assert_eq!(user["permissions"], 0);
+
+    let user: serde_json::Value = client
+        .delete(format!("/api/v1/users/{}/roles/{}", jacob.inner().id, role_id))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .get_success_result()
+        .await;
+
+    assert_eq!(user["roles"], serde_json::json!([]));
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_builtin_roles_cannot_be_deleted(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+
+    let administrator_id = sqlx::query!("SELECT id FROM roles WHERE name = 'Administrator'")
+        .fetch_one(&mut *connection)
+        .await
+        .unwrap()
+        .id;
+
+    let error: serde_json::Value = client
+        .delete(format!("/api/v1/roles/{}", administrator_id))
+        .authorize_as(&admin)
+        .expect_status(Status::Forbidden)
+        .get_result()
+        .await;
+
+    assert_eq!(error["code"], 40309);
+
+    let role: serde_json::Value = client
+        .post("/api/v1/roles/", &serde_json::json!({"name": "Video Checker"}))
+        .authorize_as(&admin)
+        .expect_status(Status::Created)
+        .get_success_result()
+        .await;
+
+    assert_eq!(role["builtin"], false);
+
+    client
+        .delete(format!("/api/v1/roles/{}", role["id"]))
+        .authorize_as(&admin)
+        .expect_status(Status::NoContent)
+        .execute()
+        .await;
+}
+
+#[sqlx::test(migrations = "../migrations")]
+pub async fn test_paginate_users_by_assignable_role(pool: Pool<Postgres>) {
+    let (client, mut connection) = pointercrate_test::user::setup_rocket(pool).await;
+
+    let admin = pointercrate_test::user::system_user_with_perms(ADMINISTRATOR, &mut *connection).await;
+
+    let mut role_ids = Vec::new();
+
+    // Neither of these roles occupies a bit in the legacy permission bitmask
+    for name in ["Video Checker", "Head Video Checker"] {
+        let role: serde_json::Value = client
+            .post("/api/v1/roles/", &serde_json::json!({ "name": name }))
+            .authorize_as(&admin)
+            .expect_status(Status::Created)
+            .get_success_result()
+            .await;
+
+        role_ids.push(role["id"].as_i64().unwrap());
+    }
+
+    client
+        .put(format!("/api/v1/roles/{}/assigns/{}", role_ids[1], role_ids[0]))
+        .authorize_as(&admin)
+        .expect_status(Status::Ok)
+        .execute()
+        .await;
+
+    let mut users = Vec::new();
+
+    for name in ["Jacob", "Stardust", "Aquatias"] {
+        let user = AuthenticatedUser::register(
+            Registration {
+                name: name.to_string(),
+                password: "bad password".to_string(),
+            },
+            &mut *connection,
+        )
+        .await
+        .unwrap();
+
+        users.push(user);
+    }
+
+    for (user, role_id) in users.iter().zip(&role_ids) {
+        sqlx::query!(
+            "INSERT INTO member_roles (member, role) VALUES ($1, $2)",
+            user.inner().id,
+            *role_id as i32
+        )
+        .execute(&mut *connection)
+        .await
+        .unwrap();
+    }
+
+    // Stardust can only see users holding roles they can assign
+    let visible: serde_json::Value = client.get("/api/v1/users/").authorize_as(&users[1]).get_result().await;
+
+    assert_eq!(visible.as_array().unwrap().len(), 1);
+    assert_eq!(visible[0]["name"], "Jacob");
+
+    // Administrators can see everyone, and filter by any role
+    let filtered: serde_json::Value = client
+        .get("/api/v1/users/?role=Head%20Video%20Checker")
+        .authorize_as(&admin)
+        .get_result()
+        .await;
+
+    assert_eq!(filtered.as_array().unwrap().len(), 1);
+    assert_eq!(filtered[0]["name"], "Stardust");
+}
@@ -11,12 +11,12 @@ use rocket::{
     Request, State,
 };
 use sqlx::{Postgres, Transaction};
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
 pub struct Auth<const IsToken: bool> {
     pub user: AuthenticatedUser,
     pub connection: Transaction<'static, Postgres>,
-    pub permissions: PermissionsManager,
+    pub permissions: Arc<PermissionsManager>,
 
     /* The secret, either token or password */
     pub(crate) secret: String,
@@ -28,7 +28,7 @@ impl<const IsToken: bool> Auth<IsToken> {
     }
 
     pub fn require_permission(&self, permission: Permission) -> Result<(), UserError> {
-        self.permissions.require_permission(self.user.inner().permissions, permission)?;
+        self.permissions.require_permission(&self.user.inner().roles, permission)?;
 
         Ok(())
     }
@@ -37,8 +37,9 @@ impl<const IsToken: bool> Auth<IsToken> {
         self.require_permission(permission).is_ok()
     }
 
-    pub fn assignable_permissions(&self) -> HashSet<Permission> {
-        self.permissions.assignable_by_bits(self.user.inner().permissions)
+    /// The names of all roles the authenticated user can assign to other users
+    pub fn assignable_permissions(&self) -> HashSet<String> {
+        self.permissions.assignable_by_roles(&self.user.inner().roles)
     }
 }
 
@@ -64,23 +65,8 @@ impl<'r> FromRequest<'r> for Auth<true> {
             return Outcome::Forward(Status::NotFound);
         }
 
-        let pool = request.guard::<&State<PointercratePool>>().await;
-        let permission_manager = match request.guard::<&State<PermissionsManager>>().await {
-            Outcome::Success(manager) => manager.inner().clone(),
-            Outcome::Error(err) => {
-                return Outcome::Error((
-                    Status::InternalServerError,
-                    CoreError::InternalServerError {
-                        message: format!("PermissionManager not retrievable from rocket state: {:?}", err),
-                    }
-                    .into(),
-                ))
-            },
-            Outcome::Forward(_) => unreachable!(), // by impl FromRequest for State
-        };
-
-        let mut connection = match pool {
-            Outcome::Success(pool) => try_outcome!(pool.transaction().await),
+        let pool = match request.guard::<&State<PointercratePool>>().await {
+            Outcome::Success(pool) => pool,
             Outcome::Error(err) => {
                 error!("Could not retrieve database pool from shared state. Did you correctly configure rocket state?");
 
@@ -94,6 +80,7 @@ impl<'r> FromRequest<'r> for Auth<true> {
             },
             Outcome::Forward(_) => unreachable!(), // by impl FromRequest for State
         };
+        let mut connection = try_outcome!(pool.transaction().await);
 
         for authorization in request.headers().get("Authorization") {
             if let ["Bearer", token] = authorization.split(' ').collect::<Vec<_>>()[..] {
@@ -101,6 +88,8 @@ impl<'r> FromRequest<'r> for Auth<true> {
 
                 try_outcome!(audit_connection(&mut *connection, user.inner().id).await);
 
+                let permission_manager = try_outcome!(pool.permissions().get(&mut *connection).await);
+
                 return Outcome::Success(Auth {
                     user,
                     connection,
@@ -121,6 +110,8 @@ impl<'r> FromRequest<'r> for Auth<true> {
 
                 try_outcome!(audit_connection(&mut *connection, user.inner().id).await);
 
+                let permission_manager = try_outcome!(pool.permissions().get(&mut *connection).await);
+
                 return Outcome::Success(Auth {
                     user,
                     connection,
@@ -139,6 +130,8 @@ impl<'r> FromRequest<'r> for Auth<true> {
 
                 try_outcome!(audit_connection(&mut *connection, user.inner().id).await);
 
+                let permission_manager = try_outcome!(pool.permissions().get(&mut *connection).await);
+
                 return Outcome::Success(Auth {
                     user,
                     connection,
@@ -164,23 +157,8 @@ impl<'r> FromRequest<'r> for Auth<false> {
             return Outcome::Forward(Status::NotFound);
         }
 
-        let pool = request.guard::<&State<PointercratePool>>().await;
-        let permission_manager = match request.guard::<&State<PermissionsManager>>().await {
-            Outcome::Success(manager) => manager.inner().clone(),
-            Outcome::Error(err) => {
-                return Outcome::Error((
-                    Status::InternalServerError,
-                    CoreError::InternalServerError {
-                        message: format!("PermissionManager not retrievable from rocket state: {:?}", err),
-                    }
-                    .into(),
-                ))
-            },
-            Outcome::Forward(_) => unreachable!(), // by impl FromRequest for State
-        };
-
-        let mut connection = match pool {
-            Outcome::Success(pool) => try_outcome!(pool.transaction().await),
+        let pool = match request.guard::<&State<PointercratePool>>().await {
+            Outcome::Success(pool) => pool,
             Outcome::Error(err) => {
                 error!("Could not retrieve database pool from shared state. Did you correctly configure rocket state?");
 
@@ -194,6 +172,7 @@ impl<'r> FromRequest<'r> for Auth<false> {
             },
             Outcome::Forward(_) => unreachable!(), // by impl FromRequest for State
         };
+        let mut connection = try_outcome!(pool.transaction().await);
 
         for authorization in request.headers().get("Authorization") {
             if let ["Basic", basic_auth] = authorization.split(' ').collect::<Vec<_>>()[..] {
@@ -211,6 +190,8 @@ impl<'r> FromRequest<'r> for Auth<false> {
 
                     try_outcome!(audit_connection(&mut *connection, user.inner().id).await);
 
+                    let permission_manager = try_outcome!(pool.permissions().get(&mut *connection).await);
+
                     return Outcome::Success(Auth {
                         user,
                         connection,
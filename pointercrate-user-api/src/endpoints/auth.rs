@@ -6,6 +6,7 @@ use pointercrate_core::{etag::Taggable, pool::PointercratePool};
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, Tagged},
+    ratelimits::RatelimitTracker,
     response::Response2,
 };
 use pointercrate_user::{error::UserError, AuthenticatedUser, PatchMe, Registration, User};
@@ -18,18 +19,18 @@ use std::net::IpAddr;
 
 #[rocket::post("/register", data = "<body>")]
 pub async fn register(
-    ip: IpAddr, body: Json<Registration>, ratelimits: &State<UserRatelimits>, pool: &State<PointercratePool>,
+    ip: IpAddr, body: Json<Registration>, ratelimits: &State<UserRatelimits>, tracker: RatelimitTracker<'_>, pool: &State<PointercratePool>,
 ) -> Result<Response2<Tagged<User>>> {
     let mut connection = pool.transaction().await.map_err(UserError::from)?;
 
-    ratelimits.soft_registrations(ip)?;
+    tracker.track(ratelimits.soft_registrations(ip).await)?;
 
     AuthenticatedUser::validate_password(&body.password)?;
     User::validate_name(&body.name)?;
 
     let user = AuthenticatedUser::register(body.0, &mut *connection).await?;
 
-    ratelimits.registrations(ip)?;
+    tracker.track(ratelimits.registrations(ip).await)?;
 
     connection.commit().await.map_err(UserError::from)?;
 
@@ -40,9 +41,9 @@ pub async fn register(
 
 #[rocket::post("/")]
 pub async fn login(
-    auth: std::result::Result<BasicAuth, UserError>, ip: IpAddr, ratelimits: &State<UserRatelimits>,
+    auth: std::result::Result<BasicAuth, UserError>, ip: IpAddr, ratelimits: &State<UserRatelimits>, tracker: RatelimitTracker<'_>,
 ) -> Result<Response2<Json<serde_json::Value>>> {
-    ratelimits.login_attempts(ip)?;
+    tracker.track(ratelimits.login_attempts(ip).await)?;
     let auth = auth?;
 
     Ok(Response2::json(serde_json::json! {
@@ -80,13 +81,14 @@ pub fn get_me(auth: TokenAuth) -> Tagged<User> {
 #[rocket::patch("/me", data = "<patch>")]
 pub async fn patch_me(
     mut auth: BasicAuth, patch: Json<PatchMe>, pred: Precondition, ip: IpAddr, ratelimits: &State<UserRatelimits>,
+    tracker: RatelimitTracker<'_>,
 ) -> Result<std::result::Result<Tagged<User>, Status>> {
     pred.require_etag_match(auth.user.inner())?;
 
     let changes_password = patch.changes_password();
 
     if patch.initiates_email_change() {
-        ratelimits.change_email(ip)?;
+        tracker.track(ratelimits.change_email(Some(auth.user.inner().id), ip).await)?;
     }
 
     let updated_user = auth.user.apply_patch(patch.0, &mut auth.connection).await?;
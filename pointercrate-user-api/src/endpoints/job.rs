@@ -0,0 +1,37 @@
+use crate::auth::TokenAuth;
+use pointercrate_core::job::{JobPagination, JobRecord};
+use pointercrate_core_api::{
+    error::Result,
+    pagination::{pagination_response, Paginated, PaginationPreferences},
+    query::Query,
+};
+use pointercrate_user::ADMINISTRATOR;
+use rocket::serde::json::Json;
+
+#[rocket::get("/")]
+pub async fn paginate(auth: TokenAuth, query: Query<JobPagination>, preferences: PaginationPreferences) -> Result<Paginated<JobRecord>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Ok(pagination_response("/api/v1/jobs/", query.0, preferences, auth.connection).await?)
+}
+
+#[rocket::get("/<job_id>")]
+pub async fn get(mut auth: TokenAuth, job_id: i32) -> Result<Json<JobRecord>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Ok(Json(JobRecord::by_id(job_id, &mut auth.connection).await?))
+}
+
+#[rocket::post("/<job_id>/retry")]
+pub async fn retry(mut auth: TokenAuth, job_id: i32) -> Result<Json<JobRecord>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let job = JobRecord::by_id(job_id, &mut auth.connection)
+        .await?
+        .retry(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+
+    Ok(Json(job))
+}
@@ -1,2 +1,4 @@
 pub(crate) mod auth;
+pub(crate) mod job;
+pub(crate) mod role;
 pub(crate) mod user;
@@ -0,0 +1,110 @@
+use crate::auth::TokenAuth;
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::{error::Result, etag::Tagged, response::Response2};
+use pointercrate_user::{PostRole, Role, ADMINISTRATOR};
+use rocket::{http::Status, serde::json::Json, State};
+
+#[rocket::get("/")]
+pub async fn list(mut auth: TokenAuth) -> Result<Json<Vec<Role>>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Ok(Json(Role::all(&mut auth.connection).await?))
+}
+
+#[rocket::get("/<role_id>")]
+pub async fn get(mut auth: TokenAuth, role_id: i32) -> Result<Tagged<Role>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Ok(Tagged(Role::by_id(role_id, &mut auth.connection).await?))
+}
+
+#[rocket::post("/", data = "<data>")]
+pub async fn post(mut auth: TokenAuth, pool: &State<PointercratePool>, data: Json<PostRole>) -> Result<Response2<Tagged<Role>>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let role = Role::create_from(data.0, &mut auth.connection).await?;
+
+    auth.commit().await?;
+    pool.permissions().invalidate();
+
+    let role_id = role.id;
+
+    Ok(Response2::tagged(role)
+        .status(Status::Created)
+        .with_header("Location", format!("/api/v1/roles/{}/", role_id)))
+}
+
+#[rocket::delete("/<role_id>")]
+pub async fn delete(mut auth: TokenAuth, pool: &State<PointercratePool>, role_id: i32) -> Result<Status> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    Role::by_id(role_id, &mut auth.connection)
+        .await?
+        .delete(&mut auth.connection)
+        .await?;
+
+    auth.commit().await?;
+    pool.permissions().invalidate();
+
+    Ok(Status::NoContent)
+}
+
+#[rocket::put("/<role_id>/implies/<implied_id>")]
+pub async fn add_implication(mut auth: TokenAuth, pool: &State<PointercratePool>, role_id: i32, implied_id: i32) -> Result<Tagged<Role>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let role = Role::by_id(role_id, &mut auth.connection).await?;
+    let implied = Role::by_id(implied_id, &mut auth.connection).await?;
+    let role = role.add_implication(&implied, &mut auth.connection).await?;
+
+    auth.commit().await?;
+    pool.permissions().invalidate();
+
+    Ok(Tagged(role))
+}
+
+#[rocket::delete("/<role_id>/implies/<implied_id>")]
+pub async fn remove_implication(
+    mut auth: TokenAuth, pool: &State<PointercratePool>, role_id: i32, implied_id: i32,
+) -> Result<Tagged<Role>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let role = Role::by_id(role_id, &mut auth.connection).await?;
+    let implied = Role::by_id(implied_id, &mut auth.connection).await?;
+    let role = role.remove_implication(&implied, &mut auth.connection).await?;
+
+    auth.commit().await?;
+    pool.permissions().invalidate();
+
+    Ok(Tagged(role))
+}
+
+#[rocket::put("/<role_id>/assigns/<assignable_id>")]
+pub async fn add_assignable(mut auth: TokenAuth, pool: &State<PointercratePool>, role_id: i32, assignable_id: i32) -> Result<Tagged<Role>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let role = Role::by_id(role_id, &mut auth.connection).await?;
+    let assignable = Role::by_id(assignable_id, &mut auth.connection).await?;
+    let role = role.add_assignable(&assignable, &mut auth.connection).await?;
+
+    auth.commit().await?;
+    pool.permissions().invalidate();
+
+    Ok(Tagged(role))
+}
+
+#[rocket::delete("/<role_id>/assigns/<assignable_id>")]
+pub async fn remove_assignable(
+    mut auth: TokenAuth, pool: &State<PointercratePool>, role_id: i32, assignable_id: i32,
+) -> Result<Tagged<Role>> {
+    auth.require_permission(ADMINISTRATOR)?;
+
+    let role = Role::by_id(role_id, &mut auth.connection).await?;
+    let assignable = Role::by_id(assignable_id, &mut auth.connection).await?;
+    let role = role.remove_assignable(&assignable, &mut auth.connection).await?;
+
+    auth.commit().await?;
+    pool.permissions().invalidate();
+
+    Ok(Tagged(role))
+}
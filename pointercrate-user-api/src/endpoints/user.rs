@@ -4,15 +4,15 @@ use pointercrate_core::error::CoreError;
 use pointercrate_core_api::{
     error::Result,
     etag::{Precondition, Tagged},
-    pagination_response,
+    pagination::{pagination_response, Paginated, PaginationPreferences},
     query::Query,
-    response::Response2,
 };
-use pointercrate_user::{error::UserError, PatchUser, User, UserPagination, ADMINISTRATOR, MODERATOR};
+use pointercrate_user::{error::UserError, PatchUser, Role, User, UserPagination, ADMINISTRATOR, MODERATOR};
 use rocket::{http::Status, serde::json::Json};
+use std::collections::HashSet;
 
 #[rocket::get("/")]
-pub async fn paginate(mut auth: TokenAuth, data: Query<UserPagination>) -> Result<Response2<Json<Vec<User>>>> {
+pub async fn paginate(auth: TokenAuth, data: Query<UserPagination>, preferences: PaginationPreferences) -> Result<Paginated<User>> {
     let mut pagination = data.0;
     // Rule of thumb: If you can assign permissions, you can see all users that currently have those
     // permissions
@@ -24,19 +24,10 @@ pub async fn paginate(mut auth: TokenAuth, data: Query<UserPagination>) -> Resul
     // Pointercrate staff need to be able to see all users, not only those whose permissions they can
     // assign
     if !auth.has_permission(MODERATOR) {
-        let assignable_bitmask = auth.assignable_permissions().iter().fold(0x0, |mask, perm| mask | perm.bit());
-
-        pagination.any_permissions = match pagination.any_permissions {
-            Some(perms) => Some(perms & assignable_bitmask),
-            None => Some(assignable_bitmask),
-        }
+        pagination.any_roles = Some(auth.assignable_permissions().into_iter().collect());
     }
 
-    let mut users = pagination.page(&mut auth.connection).await?;
-
-    let (max_id, min_id) = User::extremal_member_ids(&mut auth.connection).await?;
-
-    pagination_response!("/api/v1/users/", users, pagination, min_id, max_id, before_id, after_id, id)
+    Ok(pagination_response("/api/v1/users/", pagination, preferences, auth.connection).await?)
 }
 
 #[rocket::get("/<user_id>")]
@@ -45,7 +36,7 @@ pub async fn get_user(mut auth: TokenAuth, user_id: i32) -> Result<Tagged<User>>
 
     // We are only allowed to retrieve users who already have permissions we can set.
     if !auth.has_permission(MODERATOR) && !auth.has_permission(ADMINISTRATOR) {
-        let can_assign_any = auth.assignable_permissions().iter().any(|perm| user.has_permission(*perm));
+        let can_assign_any = auth.assignable_permissions().iter().any(|role| user.roles.contains(role));
 
         if !can_assign_any {
             // don't leak information about what users exist
@@ -61,7 +52,7 @@ pub async fn patch_user(mut auth: TokenAuth, precondition: Precondition, user_id
     let user = User::by_id(user_id, &mut auth.connection).await?;
 
     if !auth.has_permission(MODERATOR) && !auth.has_permission(ADMINISTRATOR) {
-        let can_assign_any = auth.assignable_permissions().iter().any(|perm| user.has_permission(*perm));
+        let can_assign_any = auth.assignable_permissions().iter().any(|role| user.roles.contains(role));
 
         if !can_assign_any {
             // don't leak information about what users exist
@@ -74,25 +65,24 @@ pub async fn patch_user(mut auth: TokenAuth, precondition: Precondition, user_id
     }
 
     if let Some(ref mut permissions) = patch.permissions {
-        let assignable_bitmask = auth.assignable_permissions().iter().fold(0x0, |mask, perm| mask | perm.bit());
+        let assignable = auth.assignable_permissions();
+        let requested = auth.permissions.bits_to_roles(*permissions);
 
-        if *permissions & assignable_bitmask != *permissions {
-            let unassignable_permissions = (*permissions & assignable_bitmask) ^ *permissions;
+        let non_assignable = requested.difference(&assignable).cloned().collect::<HashSet<_>>();
 
-            return Err(UserError::PermissionNotAssignable {
-                non_assignable: auth.permissions.bits_to_permissions(unassignable_permissions),
-            }
-            .into());
+        if !non_assignable.is_empty() {
+            return Err(UserError::PermissionNotAssignable { non_assignable }.into());
         }
 
-        info!("assignable permissions are {:b}", assignable_bitmask);
-        info!("assigned permissions are {:b}", permissions);
-        info!("User currently has permissions {:b}", user.permissions);
+        info!("assignable roles are {:?}", assignable);
+        info!("assigned roles are {:?}", requested);
+        info!("User currently has roles {:?}", user.roles);
+
+        // The user keeps all roles we cannot assign. Since we already verified that all requested roles
+        // are assignable, the new roles are simply the union of the two
+        let kept = user.roles.iter().filter(|role| !assignable.contains(*role));
 
-        // we clear all the assignable bits in the user's permissions bitstring. Since we already verified
-        // that permissions is a subset of assignable_permissions, we can then set the new permissions via
-        // simple OR
-        *permissions |= user.permissions & !assignable_bitmask;
+        *permissions = auth.permissions.legacy_bits(kept.chain(&requested));
     }
 
     if user_id == auth.user.inner().id {
@@ -108,6 +98,55 @@ pub async fn patch_user(mut auth: TokenAuth, precondition: Precondition, user_id
     Ok(Tagged(user))
 }
 
+#[rocket::put("/<user_id>/roles/<role_id>")]
+pub async fn add_role(mut auth: TokenAuth, user_id: i32, role_id: i32) -> Result<Tagged<User>> {
+    let (mut user, role) = role_assignment_targets(&mut auth, user_id, role_id).await?;
+
+    user.add_role(role.id, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Tagged(user))
+}
+
+#[rocket::delete("/<user_id>/roles/<role_id>")]
+pub async fn remove_role(mut auth: TokenAuth, user_id: i32, role_id: i32) -> Result<Tagged<User>> {
+    let (mut user, role) = role_assignment_targets(&mut auth, user_id, role_id).await?;
+
+    user.remove_role(role.id, &mut auth.connection).await?;
+
+    auth.commit().await?;
+
+    Ok(Tagged(user))
+}
+
+/// Retrieves the user and role involved in a role assignment, and checks that the authenticated user
+/// is allowed to assign the role to the user
+async fn role_assignment_targets(auth: &mut TokenAuth, user_id: i32, role_id: i32) -> Result<(User, Role)> {
+    let assignable = auth.assignable_permissions();
+
+    if assignable.is_empty() {
+        return Err(CoreError::Forbidden.into());
+    }
+
+    let user = User::by_id(user_id, &mut auth.connection).await?;
+
+    if user_id == auth.user.inner().id {
+        return Err(UserError::PatchSelf.into());
+    }
+
+    let role = Role::by_id(role_id, &mut auth.connection).await?;
+
+    if !assignable.contains(&role.name) {
+        let mut non_assignable = HashSet::new();
+        non_assignable.insert(role.name);
+
+        return Err(UserError::PermissionNotAssignable { non_assignable }.into());
+    }
+
+    Ok((user, role))
+}
+
 #[rocket::delete("/<user_id>")]
 pub async fn delete_user(mut auth: TokenAuth, precondition: Precondition, user_id: i32) -> Result<Status> {
     auth.require_permission(ADMINISTRATOR)?;
@@ -1,38 +1,31 @@
 use crate::ratelimits::UserRatelimits;
+use log::error;
+use pointercrate_core::{pool::PointercratePool, ratelimits::RatelimitStorage};
+use pointercrate_core_api::ratelimits::RatelimitHeaders;
 
-use rocket::{Build, Rocket};
+use rocket::{fairing::AdHoc, Build, Rocket};
 
 pub mod auth;
 mod endpoints;
+mod openapi;
 mod pages;
 mod ratelimits;
 
 pub fn setup(rocket: Rocket<Build>) -> Rocket<Build> {
-    let ratelimits = UserRatelimits::new();
+    let rocket = pointercrate_core_api::openapi::setup(rocket, openapi::documentation());
 
     rocket
-        .manage(ratelimits)
-        .mount(
-            "/api/v1/auth/",
-            rocket::routes![
-                endpoints::auth::register,
-                endpoints::auth::login,
-                endpoints::auth::invalidate,
-                endpoints::auth::get_me,
-                endpoints::auth::patch_me,
-                endpoints::auth::delete_me,
-                endpoints::auth::verify_email,
-            ],
-        )
-        .mount(
-            "/api/v1/users/",
-            rocket::routes![
-                endpoints::user::paginate,
-                endpoints::user::get_user,
-                endpoints::user::patch_user,
-                endpoints::user::delete_user
-            ],
-        )
+        .attach(RatelimitHeaders)
+        .attach(AdHoc::try_on_ignite("User ratelimits", |rocket| async move {
+            match RatelimitStorage::from_env(rocket.state::<PointercratePool>()) {
+                Ok(storage) => Ok(rocket.manage(UserRatelimits::with_storage(storage))),
+                Err(err) => {
+                    error!("Failed to set up user ratelimits: {}", err);
+
+                    Err(rocket)
+                },
+            }
+        }))
         .mount(
             "/",
             rocket::routes![pages::login_page, pages::account_page, pages::login, pages::register],
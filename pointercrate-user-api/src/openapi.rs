@@ -0,0 +1,99 @@
+use crate::endpoints::{auth, job, role, user};
+use pointercrate_core::job::{JobPagination, JobRecord};
+use pointercrate_core_api::openapi::{ApiDocumentation, Operation};
+use pointercrate_user::{error::UserError, PatchMe, PatchUser, PostRole, Registration, Role, User, UserPagination};
+use rocket::serde::json::Value;
+use schemars::JsonSchema;
+
+/// Describes the query parameter bound directly in the URI of the email verification route
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct VerificationQuery {
+    /// The token sent to the new email address
+    token: String,
+}
+
+pub(crate) fn documentation() -> ApiDocumentation {
+    ApiDocumentation::new()
+        .errors::<UserError>()
+        .mount(
+            "/api/v1/auth/",
+            vec![
+                Operation::new(rocket::routes![auth::register], "Register a new account")
+                    .with_body::<Registration>()
+                    .with_response::<User>()
+                    .tagged(),
+                Operation::new(
+                    rocket::routes![auth::login],
+                    "Log in via basic authentication to obtain an access token",
+                )
+                .with_response::<Value>(),
+                Operation::new(rocket::routes![auth::invalidate], "Invalidate all access tokens"),
+                Operation::new(rocket::routes![auth::verify_email], "Verify a new email address").with_query::<VerificationQuery>(),
+                Operation::new(rocket::routes![auth::get_me], "Retrieve the logged in account")
+                    .with_response::<User>()
+                    .tagged(),
+                Operation::new(rocket::routes![auth::patch_me], "Modify the logged in account")
+                    .with_body::<PatchMe>()
+                    .with_response::<User>()
+                    .conditional()
+                    .tagged(),
+                Operation::new(rocket::routes![auth::delete_me], "Delete the logged in account").conditional(),
+            ],
+        )
+        .mount(
+            "/api/v1/users/",
+            vec![
+                Operation::new(rocket::routes![user::paginate], "List users").paginated::<UserPagination>(),
+                Operation::new(rocket::routes![user::get_user], "Retrieve a user")
+                    .with_response::<User>()
+                    .tagged(),
+                Operation::new(rocket::routes![user::patch_user], "Modify a user")
+                    .with_body::<PatchUser>()
+                    .with_response::<User>()
+                    .conditional()
+                    .tagged(),
+                Operation::new(rocket::routes![user::delete_user], "Delete a user").conditional(),
+                Operation::new(rocket::routes![user::add_role], "Assign a role to a user")
+                    .with_response::<User>()
+                    .tagged(),
+                Operation::new(rocket::routes![user::remove_role], "Remove a role from a user")
+                    .with_response::<User>()
+                    .tagged(),
+            ],
+        )
+        .mount(
+            "/api/v1/roles/",
+            vec![
+                Operation::new(rocket::routes![role::list], "List roles").with_response::<Vec<Role>>(),
+                Operation::new(rocket::routes![role::post], "Add a role")
+                    .with_body::<PostRole>()
+                    .with_response::<Role>()
+                    .tagged(),
+                Operation::new(rocket::routes![role::get], "Retrieve a role")
+                    .with_response::<Role>()
+                    .tagged(),
+                Operation::new(rocket::routes![role::delete], "Delete a role"),
+                Operation::new(rocket::routes![role::add_implication], "Make a role imply another")
+                    .with_response::<Role>()
+                    .tagged(),
+                Operation::new(rocket::routes![role::remove_implication], "Remove a role implication")
+                    .with_response::<Role>()
+                    .tagged(),
+                Operation::new(rocket::routes![role::add_assignable], "Allow a role to assign another")
+                    .with_response::<Role>()
+                    .tagged(),
+                Operation::new(rocket::routes![role::remove_assignable], "Disallow a role to assign another")
+                    .with_response::<Role>()
+                    .tagged(),
+            ],
+        )
+        .mount(
+            "/api/v1/jobs/",
+            vec![
+                Operation::new(rocket::routes![job::paginate], "List background jobs").paginated::<JobPagination>(),
+                Operation::new(rocket::routes![job::get], "Retrieve a background job").with_response::<JobRecord>(),
+                Operation::new(rocket::routes![job::retry], "Retry a dead background job").with_response::<JobRecord>(),
+            ],
+        )
+}
@@ -2,8 +2,8 @@ use crate::{
     auth::{BasicAuth, TokenAuth},
     ratelimits::UserRatelimits,
 };
-use pointercrate_core::{permission::PermissionsManager, pool::PointercratePool};
-use pointercrate_core_api::response::Page;
+use pointercrate_core::pool::PointercratePool;
+use pointercrate_core_api::{ratelimits::RatelimitTracker, response::Page};
 use pointercrate_core_pages::head::HeadLike;
 use pointercrate_user::{error::UserError, AuthenticatedUser, Registration, User};
 use pointercrate_user_pages::account::AccountPageConfig;
@@ -23,9 +23,10 @@ pub async fn login_page(auth: Option<TokenAuth>) -> Result<Redirect, Page> {
 
 #[rocket::post("/login")]
 pub async fn login(
-    auth: Result<BasicAuth, UserError>, ip: IpAddr, ratelimits: &State<UserRatelimits>, cookies: &CookieJar<'_>,
+    auth: Result<BasicAuth, UserError>, ip: IpAddr, ratelimits: &State<UserRatelimits>, tracker: RatelimitTracker<'_>,
+    cookies: &CookieJar<'_>,
 ) -> pointercrate_core_api::error::Result<Status> {
-    ratelimits.login_attempts(ip)?;
+    tracker.track(ratelimits.login_attempts(ip).await)?;
 
     let auth = auth?;
 
@@ -45,17 +46,17 @@ pub async fn login(
 
 #[rocket::post("/register", data = "<registration>")]
 pub async fn register(
-    ip: IpAddr, ratelimits: &State<UserRatelimits>, cookies: &CookieJar<'_>, registration: Json<Registration>,
-    pool: &State<PointercratePool>,
+    ip: IpAddr, ratelimits: &State<UserRatelimits>, tracker: RatelimitTracker<'_>, cookies: &CookieJar<'_>,
+    registration: Json<Registration>, pool: &State<PointercratePool>,
 ) -> pointercrate_core_api::error::Result<Status> {
     let mut connection = pool.transaction().await.map_err(UserError::from)?;
 
-    ratelimits.soft_registrations(ip)?;
+    tracker.track(ratelimits.soft_registrations(ip).await)?;
 
     AuthenticatedUser::validate_password(&registration.password)?;
     User::validate_name(&registration.name)?;
 
-    ratelimits.registrations(ip)?;
+    tracker.track(ratelimits.registrations(ip).await)?;
 
     let user = AuthenticatedUser::register(registration.0, &mut *connection).await?;
 
@@ -76,14 +77,12 @@ pub async fn register(
 }
 
 #[rocket::get("/account")]
-pub async fn account_page(
-    auth: Option<TokenAuth>, permissions: &State<PermissionsManager>, tabs: &State<AccountPageConfig>,
-) -> Result<Page, Redirect> {
+pub async fn account_page(auth: Option<TokenAuth>, tabs: &State<AccountPageConfig>) -> Result<Page, Redirect> {
     match auth {
         Some(mut auth) => {
             let csrf_token = auth.user.generate_csrf_token();
 
-            Ok(Page::new(tabs.account_page(auth.user, permissions, &mut auth.connection).await).meta("csrf_token", csrf_token))
+            Ok(Page::new(tabs.account_page(auth.user, &auth.permissions, &mut auth.connection).await).meta("csrf_token", csrf_token))
         },
         None => Err(Redirect::to(rocket::uri!(login_page))),
     }
@@ -11,7 +11,7 @@ pub mod users;
 
 #[async_trait::async_trait]
 pub trait AccountPageTab {
-    fn should_display_for(&self, permissions_we_have: u16, permission_manager: &PermissionsManager) -> bool;
+    fn should_display_for(&self, permissions_we_have: &[String], permission_manager: &PermissionsManager) -> bool;
     fn initialization_script(&self) -> String;
     fn additional_scripts(&self) -> Vec<Script> {
         vec![]
@@ -48,7 +48,7 @@ impl AccountPageConfig {
         };
 
         for tab_config in &self.tabs {
-            if tab_config.should_display_for(page.user.inner().permissions, permissions) {
+            if tab_config.should_display_for(&page.user.inner().roles, permissions) {
                 let tab = tab_config.tab();
                 let content = tab_config.content(&page.user, permissions, connection).await;
 
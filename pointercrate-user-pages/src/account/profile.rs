@@ -7,7 +7,7 @@ pub struct ProfileTab;
 
 #[async_trait::async_trait]
 impl AccountPageTab for ProfileTab {
-    fn should_display_for(&self, _permissions_we_have: u16, _permissions: &PermissionsManager) -> bool {
+    fn should_display_for(&self, _permissions_we_have: &[String], _permissions: &PermissionsManager) -> bool {
         true
     }
 
@@ -30,12 +30,11 @@ impl AccountPageTab for ProfileTab {
     }
 
     async fn content(
-        &self, authenticated_user: &AuthenticatedUser, permissions: &PermissionsManager, _connection: &mut PgConnection,
+        &self, authenticated_user: &AuthenticatedUser, _permissions: &PermissionsManager, _connection: &mut PgConnection,
     ) -> Markup {
         let user = authenticated_user.inner();
 
-        let permissions = permissions.bits_to_permissions(user.permissions);
-        let permission_string = permissions.iter().map(|perm| perm.name()).collect::<Vec<_>>().join(", ");
+        let permission_string = user.roles.join(", ");
 
         html! {
             div.left {
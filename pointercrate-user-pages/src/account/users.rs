@@ -8,7 +8,7 @@ pub struct UsersTab(pub Vec<Permission>);
 
 #[async_trait::async_trait]
 impl AccountPageTab for UsersTab {
-    fn should_display_for(&self, permissions_we_have: u16, permissions: &PermissionsManager) -> bool {
+    fn should_display_for(&self, permissions_we_have: &[String], permissions: &PermissionsManager) -> bool {
         for perm in &self.0 {
             if permissions.require_permission(permissions_we_have, *perm).is_ok() {
                 return true;
@@ -37,11 +37,14 @@ impl AccountPageTab for UsersTab {
     }
 
     async fn content(&self, user: &AuthenticatedUser, permissions: &PermissionsManager, _connection: &mut PgConnection) -> Markup {
+        // Only roles with a legacy bit can be edited here, since the account manager still operates on
+        // permission bitmasks. All other roles are managed via /api/v1/users/<id>/roles/
         let mut assignable_permissions = permissions
-            .assignable_by_bits(user.inner().permissions)
+            .assignable_by_roles(&user.inner().roles)
             .into_iter()
+            .filter_map(|role| permissions.legacy_bit(&role).map(|bit| (role, bit)))
             .collect::<Vec<_>>();
-        assignable_permissions.sort_by_key(|perm| perm.bit());
+        assignable_permissions.sort_by_key(|(_, bit)| *bit);
 
         html! {
             div.left {
@@ -88,12 +91,12 @@ impl AccountPageTab for UsersTab {
                                         b {
                                             "Permissions:"
                                         }
-                                        @for permission in assignable_permissions {
-                                            @let name_in_snake_case = permission.name().to_lowercase().replace(' ', "-");
+                                        @for (role, bit) in assignable_permissions {
+                                            @let name_in_snake_case = role.to_lowercase().replace(' ', "-");
 
-                                            label.cb-container.form-input#(name_in_snake_case) for = (name_in_snake_case) data-bit = (permission.bit()) {
+                                            label.cb-container.form-input#(name_in_snake_case) for = (name_in_snake_case) data-bit = (bit) {
                                                 i {
-                                                    (permission.name())
+                                                    (role)
                                                 }
                                                 input type = "checkbox" name = (name_in_snake_case);
                                                 span.checkmark {}
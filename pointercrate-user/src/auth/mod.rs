@@ -247,6 +247,7 @@ mod tests {
                 id: 0,
                 name: "Patrick".to_string(),
                 permissions: 0,
+                roles: Vec::new(),
                 display_name: None,
                 youtube_channel: None,
             },
@@ -261,6 +262,7 @@ mod tests {
                 id: 1,
                 name: "Jacob".to_string(),
                 permissions: 0,
+                roles: Vec::new(),
                 display_name: None,
                 youtube_channel: None,
             },
@@ -1,11 +1,12 @@
 use crate::{auth::AuthenticatedUser, error::Result, patch::PatchUser};
 use log::info;
 use pointercrate_core::util::{non_nullable, nullable};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 use std::fmt::{Debug, Formatter};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct PatchMe {
     #[serde(default, deserialize_with = "non_nullable")]
     pub(super) password: Option<String>,
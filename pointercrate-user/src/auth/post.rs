@@ -4,10 +4,11 @@ use crate::{
     User,
 };
 use log::{info, trace, warn};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, JsonSchema, Serialize)]
 pub struct Registration {
     pub name: String,
     pub password: String,
@@ -40,6 +41,7 @@ impl AuthenticatedUser {
                         id,
                         name: registration.name,
                         permissions: 0,
+                        roles: Vec::new(),
                         display_name: None,
                         youtube_channel: None,
                     },
@@ -1,9 +1,6 @@
 use derive_more::Display;
 
-use pointercrate_core::{
-    error::{CoreError, PointercrateError},
-    permission::Permission,
-};
+use pointercrate_core::error::{CoreError, PointercrateError};
 use serde::Serialize;
 use std::collections::HashSet;
 
@@ -31,8 +28,14 @@ pub enum UserError {
     #[display(fmt = "You cannot modify your own account via this endpoint. Use PATCH /api/v1/auth/me/")]
     PatchSelf,
 
-    #[display(fmt = "You cannot assign the following permissions: {:?}", non_assignable)]
-    PermissionNotAssignable { non_assignable: HashSet<Permission> },
+    #[display(fmt = "You cannot assign the following roles: {:?}", non_assignable)]
+    PermissionNotAssignable { non_assignable: HashSet<String> },
+
+    /// `403 FORBIDDEN` error returned when trying to delete a role pointercrate itself relies on
+    ///
+    /// Error Code `40309`
+    #[display(fmt = "The role '{}' is built into pointercrate and cannot be deleted", role)]
+    BuiltinRole { role: String },
 
     #[display(fmt = "No user with id {} found", user_id)]
     UserNotFound { user_id: i32 },
@@ -40,12 +43,31 @@ pub enum UserError {
     #[display(fmt = "No user with name {} found", user_name)]
     UserNotFoundName { user_name: String },
 
+    /// `404 NOT FOUND` variant
+    ///
+    /// Error Code `40401`
+    #[display(fmt = "No role with id {} found", role_id)]
+    RoleNotFound { role_id: i32 },
+
     /// `409 CONFLICT` error returned if a user tries to register with a name that's already taken
     ///
     /// Error Code `40902`
     #[display(fmt = "The chosen username is already taken")]
     NameTaken,
 
+    /// `409 CONFLICT` error returned if a role is created with a name that's already taken
+    ///
+    /// Error Code `40909`
+    #[display(fmt = "A role with the given name already exists")]
+    RoleNameTaken,
+
+    /// `409 CONFLICT` error returned if a role is created with a legacy permission bit that's already
+    /// in use by another role
+    ///
+    /// Error Code `40910`
+    #[display(fmt = "The legacy permission bit {:#x} is already in use by role '{}'", bit, role)]
+    LegacyBitTaken { bit: u16, role: String },
+
     /// `422 UNPROCESSABLE ENTITIY` variant returned if the username provided during registration
     /// is either shorter than 3 letters of contains trailing or leading whitespaces
     ///
@@ -65,6 +87,19 @@ pub enum UserError {
     /// Error Code `42226`
     #[display(fmt = "The given URL is no YouTube URL")]
     NotYouTube,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if the legacy permission bit of a newly created
+    /// role isn't a single bit
+    ///
+    /// Error Code `42235`
+    #[display(fmt = "A legacy permission bit must have exactly one bit set")]
+    InvalidLegacyBit,
+
+    /// `422 UNPROCESSABLE ENTITY` variant returned if one attempts to make a role imply itself
+    ///
+    /// Error Code `42236`
+    #[display(fmt = "A role cannot imply itself")]
+    SelfImplication,
 }
 
 impl std::error::Error for UserError {}
@@ -86,14 +121,59 @@ impl PointercrateError for UserError {
             DeleteSelf => 40302,
             PatchSelf => 40303,
             PermissionNotAssignable { .. } => 40305,
+            BuiltinRole { .. } => 40309,
             UserNotFound { .. } => 40401,
             UserNotFoundName { .. } => 40401,
+            RoleNotFound { .. } => 40401,
             NameTaken => 40902,
+            RoleNameTaken => 40909,
+            LegacyBitTaken { .. } => 40910,
             InvalidUsername => 42202,
             InvalidPassword => 42204,
             NotYouTube => 42226,
+            InvalidLegacyBit => 42235,
+            SelfImplication => 42236,
+        }
+    }
+
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            UserError::Core(core) => core.headers(),
+            _ => Vec::new(),
         }
     }
+
+    fn catalogue() -> Vec<Self> {
+        use UserError::*;
+
+        vec![
+            MalformedChannelUrl,
+            DeleteSelf,
+            PatchSelf,
+            PermissionNotAssignable {
+                non_assignable: HashSet::new(),
+            },
+            BuiltinRole {
+                role: "Administrator".to_string(),
+            },
+            UserNotFound { user_id: 1 },
+            UserNotFoundName {
+                user_name: "stadust".to_string(),
+            },
+            RoleNotFound { role_id: 1 },
+            NameTaken,
+            RoleNameTaken,
+            LegacyBitTaken {
+                bit: 0x1,
+                role: "Moderator".to_string(),
+            },
+            InvalidUsername,
+            InvalidPassword,
+            NotYouTube,
+            InvalidLegacyBit,
+            SelfImplication,
+        ]
+    }
 }
 
 impl From<sqlx::Error> for UserError {
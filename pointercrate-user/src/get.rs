@@ -10,6 +10,7 @@ macro_rules! construct_from_row {
             id: $row.member_id,
             name: $row.name,
             permissions: $row.permissions.unwrap() as u16,
+            roles: $row.roles,
             display_name: $row.display_name,
             youtube_channel: $row.youtube_channel,
         }
@@ -19,7 +20,7 @@ macro_rules! construct_from_row {
 impl User {
     pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<User> {
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, permissions::integer, display_name, youtube_channel::text FROM members WHERE member_id = $1"#,
+            r#"SELECT member_id, members.name, permissions::integer, roles_of(member_id) AS "roles!", display_name, youtube_channel::text FROM members WHERE member_id = $1"#,
             id
         )
         .fetch_one(connection)
@@ -34,7 +35,7 @@ impl User {
 
     pub async fn by_name(name: &str, connection: &mut PgConnection) -> Result<User> {
         let row = sqlx::query!(
-            r#"SELECT member_id, members.name, CAST(permissions AS integer), display_name, youtube_channel::text FROM members WHERE members.name = $1"#,
+            r#"SELECT member_id, members.name, CAST(permissions AS integer), roles_of(member_id) AS "roles!", display_name, youtube_channel::text FROM members WHERE members.name = $1"#,
             name
         )
         .fetch_one(connection)
@@ -4,17 +4,19 @@
 //! * Deleting other accounts
 //! * Modifying other people's accounts (assign permissions, change offensive names, etc)
 //! * Querying account information
+//! * Managing the roles that can be assigned to users
 
 pub use self::{
     auth::{AuthenticatedUser, PatchMe, Registration},
     paginate::UserPagination,
     patch::PatchUser,
+    role::{PostRole, Role},
 };
 use crate::error::{Result, UserError};
 use pointercrate_core::{etag::Taggable, permission::Permission};
+use schemars::JsonSchema;
 use serde::Serialize;
 pub use sqlx;
-use sqlx::PgConnection;
 use std::{
     fmt::{Display, Formatter},
     hash::Hash,
@@ -27,13 +29,14 @@ mod delete;
 pub mod error;
 mod paginate;
 mod patch;
+mod role;
 mod video;
 
-pub const ADMINISTRATOR: Permission = Permission::new("Administrator", 0x4000);
-pub const MODERATOR: Permission = Permission::new("Moderator", 0x2000);
+pub const ADMINISTRATOR: Permission = Permission::new("Administrator");
+pub const MODERATOR: Permission = Permission::new("Moderator");
 
 /// Model representing a user in the database
-#[derive(Debug, Serialize, Hash, Eq, PartialEq)]
+#[derive(Debug, Serialize, JsonSchema, Hash, Eq, PartialEq)]
 pub struct User {
     /// The [`User`]'s unique ID. This is used to identify users and cannot be changed.
     pub id: i32,
@@ -41,8 +44,14 @@ pub struct User {
     /// The [`User`]'s unique username. This is used to log-in and cannot be changed.
     pub name: String,
 
+    /// The [`User`]'s roles in the legacy bitmask format. Only roles that have a legacy bit assigned
+    /// are contained in here, see [`User::roles`] for the full list
     pub permissions: u16,
 
+    /// The names of the roles assigned to this [`User`]. Note that roles can imply further roles,
+    /// which are not contained in this list
+    pub roles: Vec<String>,
+
     /// A user-customizable name for each [`User`].
     ///
     /// If set to anything other than [`None`], the value set here will be displayed everywhere the
@@ -66,8 +75,10 @@ impl Display for User {
 }
 
 impl User {
+    /// Checks whether this user was directly assigned the role corresponding to the given
+    /// permission. Roles implied by the user's roles are not considered.
     pub fn has_permission(&self, permission: Permission) -> bool {
-        self.has_permissions(permission.bit())
+        self.roles.iter().any(|role| role == permission.name())
     }
 
     pub fn has_permissions(&self, perms: u16) -> bool {
@@ -92,14 +103,4 @@ impl User {
             None => self.name.as_ref(),
         }
     }
-
-    /// Gets the maximal and minimal member id currently in use
-    ///
-    /// The returned tuple is of the form (max, min)
-    pub async fn extremal_member_ids(connection: &mut PgConnection) -> Result<(i32, i32)> {
-        let row = sqlx::query!(r#"SELECT MAX(member_id) AS "max_id!: i32", MIN(member_id) AS "min_id!: i32" FROM members"#)
-            .fetch_one(connection)
-            .await?; // FIXME: crashes on empty table
-        Ok((row.max_id, row.min_id))
-    }
 }
@@ -1,14 +1,22 @@
-use crate::{error::Result, User};
+use crate::{
+    error::{Result, UserError},
+    User,
+};
 use futures::StreamExt;
 use pointercrate_core::{
-    error::CoreError,
+    pagination::{clamp_i32, PaginationParameters, Paginator, DEFAULT_LIMIT},
     permission::Permission,
     util::{non_nullable, nullable},
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgRow, PgConnection, Row};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgConnection, Postgres, Row,
+};
 
-#[derive(Deserialize, Debug, Clone, Serialize)]
+#[derive(Deserialize, Debug, Clone, Serialize, JsonSchema)]
 pub struct UserPagination {
     #[serde(rename = "before", default, deserialize_with = "non_nullable")]
     pub before_id: Option<i32>,
@@ -28,36 +36,56 @@ pub struct UserPagination {
     #[serde(default, deserialize_with = "nullable")]
     pub display_name: Option<Option<String>>,
 
+    /// Only users holding all roles in this legacy permission bitmask
     #[serde(default, deserialize_with = "non_nullable")]
     pub has_permissions: Option<u16>,
 
+    /// Only users holding any role in this legacy permission bitmask
     #[serde(default, deserialize_with = "non_nullable")]
     pub any_permissions: Option<u16>,
+
+    /// Only users that were directly assigned the role with this name
+    #[serde(default, deserialize_with = "non_nullable")]
+    pub role: Option<String>,
+
+    /// Only users that were directly assigned any of the roles with these names
+    ///
+    /// Cannot be set via the query string. Used to restrict which users are visible to whom.
+    #[serde(skip)]
+    pub any_roles: Option<Vec<String>>,
 }
 
-impl UserPagination {
-    pub async fn page(&self, connection: &mut PgConnection) -> Result<Vec<User>> {
-        if let Some(limit) = self.limit {
-            if !(1..=100).contains(&limit) {
-                return Err(CoreError::InvalidPaginationLimit.into());
-            }
+impl Paginator for UserPagination {
+    type Error = UserError;
+    type Item = User;
+
+    fn parameters(&self) -> PaginationParameters {
+        PaginationParameters {
+            before: self.before_id.map(i64::from),
+            after: self.after_id.map(i64::from),
+            limit: self.limit.unwrap_or(DEFAULT_LIMIT),
         }
+    }
 
-        if let (Some(after), Some(before)) = (self.before_id, self.after_id) {
-            if after < before {
-                return Err(CoreError::AfterSmallerBefore.into());
-            }
+    fn with_parameters(&self, parameters: PaginationParameters) -> Self {
+        UserPagination {
+            before_id: clamp_i32(parameters.before),
+            after_id: clamp_i32(parameters.after),
+            limit: Some(parameters.limit),
+            ..self.clone()
         }
+    }
 
-        let order = if self.after_id.is_none() && self.before_id.is_some() {
-            "DESC"
-        } else {
-            "ASC"
-        };
+    fn pagination_key(item: &User) -> i64 {
+        item.id as i64
+    }
 
-        let query = format!(include_str!("../sql/paginate_users.sql"), order);
+    fn query(&self, order: &str) -> String {
+        format!(include_str!("../sql/paginate_users.sql"), order)
+    }
 
-        let mut stream = sqlx::query(&query)
+    fn bind<'q>(&'q self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        query
             .bind(self.before_id)
             .bind(self.after_id)
             .bind(self.name.as_ref())
@@ -66,39 +94,51 @@ impl UserPagination {
             .bind(self.has_permissions.map(|p| p as i32))
             .bind(self.any_permissions.map(|p| p as i32))
             .bind(self.name_contains.as_ref())
-            .bind(self.limit.unwrap_or(50) as i32 + 1)
-            .fetch(connection);
+            .bind(self.role.as_ref())
+            .bind(self.any_roles.as_ref())
+    }
+
+    fn from_row(row: &PgRow) -> std::result::Result<User, sqlx::Error> {
+        let perms_as_i32: i32 = row.try_get("permissions")?;
+
+        Ok(User {
+            id: row.try_get("member_id")?,
+            name: row.try_get("name")?,
+            permissions: perms_as_i32 as u16,
+            roles: row.try_get("roles")?,
+            display_name: row.try_get("display_name")?,
+            youtube_channel: row.try_get("youtube_channel")?,
+        })
+    }
+}
+
+impl User {
+    /// Gets all users that have directly been assigned the role corresponding to the given permission
+    pub async fn by_permission(permission: Permission, connection: &mut PgConnection) -> Result<Vec<User>> {
+        let mut stream = sqlx::query!(
+            r#"SELECT member_id, members.name, permissions::integer, roles_of(member_id) AS "roles!", display_name, youtube_channel::text
+            FROM members INNER JOIN member_roles ON member_roles.member = member_id INNER JOIN roles ON roles.id = member_roles.role
+            WHERE roles.name = $1"#,
+            permission.name()
+        )
+        .fetch(connection);
 
         let mut users = Vec::new();
 
         while let Some(row) = stream.next().await {
-            let row: PgRow = row?;
-
-            let perms_as_i32: i32 = row.get("permissions");
+            let row = row?;
 
-            users.push(User {
-                id: row.get("member_id"),
-                name: row.get("name"),
-                permissions: perms_as_i32 as u16,
-                display_name: row.get("display_name"),
-                youtube_channel: row.get("youtube_channel"),
-            })
+            users.push(construct_from_row!(row))
         }
 
         Ok(users)
     }
-}
-
-impl User {
-    pub async fn by_permission(permission: Permission, connection: &mut PgConnection) -> Result<Vec<User>> {
-        User::by_permissions(permission.bit(), connection).await
-    }
 
     /// Gets all users that have the given permission bits all set
     pub async fn by_permissions(permissions: u16, connection: &mut PgConnection) -> Result<Vec<User>> {
         let mut stream = sqlx::query!(
-            "SELECT member_id, name, permissions::integer, display_name, youtube_channel::text FROM members WHERE permissions & \
-             CAST($1::INTEGER AS BIT(16)) = CAST($1::INTEGER AS BIT(16))",
+            r#"SELECT member_id, name, permissions::integer, roles_of(member_id) AS "roles!", display_name, youtube_channel::text FROM members
+            WHERE permissions & CAST($1::INTEGER AS BIT(16)) = CAST($1::INTEGER AS BIT(16))"#,
             permissions as i32
         )
         .fetch(connection);
@@ -108,13 +148,7 @@ impl User {
         while let Some(row) = stream.next().await {
             let row = row?;
 
-            users.push(User {
-                id: row.member_id,
-                name: row.name,
-                permissions: row.permissions.unwrap() as u16,
-                display_name: row.display_name,
-                youtube_channel: row.youtube_channel,
-            })
+            users.push(construct_from_row!(row))
         }
 
         Ok(users)
@@ -1,10 +1,11 @@
 use crate::{error::Result, User};
 use log::info;
 use pointercrate_core::util::{non_nullable, nullable};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use sqlx::PgConnection;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct PatchUser {
     #[serde(default, deserialize_with = "nullable")]
     pub display_name: Option<Option<String>>,
@@ -44,16 +45,63 @@ impl User {
         Ok(self)
     }
 
+    /// Sets this user's roles from a legacy permission bitmask
+    ///
+    /// Only roles that have a legacy bit assigned are affected. Roles without one are left untouched.
+    /// Bits that do not correspond to any role are ignored.
     pub async fn set_permissions(&mut self, permissions: u16, connection: &mut PgConnection) -> Result<()> {
         sqlx::query!(
-            "UPDATE members SET permissions = cast($1::integer as BIT(16)) WHERE member_id = $2", // FIXME(sqlx)
-            permissions as i32,
+            "DELETE FROM member_roles USING roles WHERE member_roles.member = $1 AND member_roles.role = roles.id AND roles.legacy_bit & \
+             $2 = 0",
+            self.id,
+            permissions as i32
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO member_roles (member, role) SELECT $1, id FROM roles WHERE legacy_bit & $2 <> 0 ON CONFLICT DO NOTHING",
+            self.id,
+            permissions as i32
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        self.reload_roles(connection).await
+    }
+
+    /// Assigns the given role to this user. Does nothing if the user already has the role
+    pub async fn add_role(&mut self, role_id: i32, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO member_roles (member, role) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            self.id,
+            role_id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        self.reload_roles(connection).await
+    }
+
+    /// Takes the given role away from this user. Does nothing if the user doesn't have the role
+    pub async fn remove_role(&mut self, role_id: i32, connection: &mut PgConnection) -> Result<()> {
+        sqlx::query!("DELETE FROM member_roles WHERE member = $1 AND role = $2", self.id, role_id)
+            .execute(&mut *connection)
+            .await?;
+
+        self.reload_roles(connection).await
+    }
+
+    async fn reload_roles(&mut self, connection: &mut PgConnection) -> Result<()> {
+        let row = sqlx::query!(
+            r#"SELECT permissions::integer AS "permissions!", roles_of(member_id) AS "roles!" FROM members WHERE member_id = $1"#,
             self.id
         )
-        .execute(connection)
+        .fetch_one(connection)
         .await?;
 
-        self.permissions = permissions;
+        self.permissions = row.permissions as u16;
+        self.roles = row.roles;
 
         Ok(())
     }
@@ -0,0 +1,24 @@
+use crate::{
+    error::{Result, UserError},
+    role::Role,
+};
+use log::info;
+use sqlx::PgConnection;
+
+impl Role {
+    /// Deletes this role. It is taken away from all users holding it, and all implication and
+    /// assignment rules involving it are removed
+    ///
+    /// Built-in roles cannot be deleted, since pointercrate would keep checking for them
+    pub async fn delete(self, connection: &mut PgConnection) -> Result<()> {
+        if self.builtin {
+            return Err(UserError::BuiltinRole { role: self.name });
+        }
+
+        info!("Deleting role {} (ID: {})", self.name, self.id);
+
+        sqlx::query!("DELETE FROM roles WHERE id = $1", self.id).execute(connection).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{
+    error::{Result, UserError},
+    role::Role,
+};
+use sqlx::{Error, PgConnection};
+
+impl Role {
+    pub async fn by_id(id: i32, connection: &mut PgConnection) -> Result<Role> {
+        let row = sqlx::query!(
+            r#"SELECT id, name, legacy_bit, builtin, ARRAY(SELECT roles.name FROM role_implications INNER JOIN roles ON roles.id = implied WHERE role = $1 ORDER BY roles.id) AS "implies!",
+            ARRAY(SELECT roles.name FROM role_assignments INNER JOIN roles ON roles.id = assignable WHERE role = $1 ORDER BY roles.id) AS "assigns!"
+            FROM roles WHERE id = $1"#,
+            id
+        )
+        .fetch_one(connection)
+        .await;
+
+        match row {
+            Ok(row) => Ok(Role {
+                id: row.id,
+                name: row.name,
+                legacy_bit: row.legacy_bit.map(|bit| bit as u16),
+                builtin: row.builtin,
+                implies: row.implies,
+                assigns: row.assigns,
+            }),
+            Err(Error::RowNotFound) => Err(UserError::RoleNotFound { role_id: id }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Gets all roles, ordered by ID
+    pub async fn all(connection: &mut PgConnection) -> Result<Vec<Role>> {
+        let ids = sqlx::query!("SELECT id FROM roles ORDER BY id").fetch_all(&mut *connection).await?;
+
+        let mut roles = Vec::new();
+
+        for row in ids {
+            roles.push(Role::by_id(row.id, connection).await?);
+        }
+
+        Ok(roles)
+    }
+}
@@ -0,0 +1,38 @@
+//! Module for managing the roles that can be assigned to users
+//!
+//! Roles are stored in the database (table `roles`). Each role grants the permission of the same
+//! name, and can imply other roles (table `role_implications`). Users holding a role can hand out the
+//! roles listed in `role_assignments` to other users. All of these rules are editable at runtime,
+//! see [`pointercrate_core::permission::PermissionsManager::load`]. Roles pointercrate itself checks
+//! for are marked as built-in and cannot be deleted.
+
+pub use self::post::PostRole;
+use pointercrate_core::etag::Taggable;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+mod delete;
+mod get;
+mod patch;
+mod post;
+
+#[derive(Debug, Serialize, JsonSchema, Hash, Eq, PartialEq, Clone)]
+pub struct Role {
+    pub id: i32,
+
+    pub name: String,
+
+    /// The bit this role occupies in the legacy permission bitmask (`User::permissions`), if any
+    pub legacy_bit: Option<u16>,
+
+    /// Whether pointercrate itself checks for this role. Built-in roles cannot be deleted
+    pub builtin: bool,
+
+    /// The names of the roles directly implied by this role
+    pub implies: Vec<String>,
+
+    /// The names of the roles holders of this role can assign to other users
+    pub assigns: Vec<String>,
+}
+
+impl Taggable for Role {}
@@ -0,0 +1,71 @@
+use crate::{
+    error::{Result, UserError},
+    role::Role,
+};
+use log::info;
+use sqlx::PgConnection;
+
+impl Role {
+    /// Makes this role imply the given role. Does nothing if the rule already exists
+    pub async fn add_implication(self, implied: &Role, connection: &mut PgConnection) -> Result<Role> {
+        if self.id == implied.id {
+            return Err(UserError::SelfImplication);
+        }
+
+        info!("Making role {} imply role {}", self.name, implied.name);
+
+        sqlx::query!(
+            "INSERT INTO role_implications (role, implied) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            self.id,
+            implied.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        Role::by_id(self.id, connection).await
+    }
+
+    pub async fn remove_implication(self, implied: &Role, connection: &mut PgConnection) -> Result<Role> {
+        info!("Removing implication of role {} by role {}", implied.name, self.name);
+
+        sqlx::query!(
+            "DELETE FROM role_implications WHERE role = $1 AND implied = $2",
+            self.id,
+            implied.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        Role::by_id(self.id, connection).await
+    }
+
+    /// Allows holders of this role to assign the given role to other users. Does nothing if the
+    /// rule already exists
+    pub async fn add_assignable(self, assignable: &Role, connection: &mut PgConnection) -> Result<Role> {
+        info!("Allowing holders of role {} to assign role {}", self.name, assignable.name);
+
+        sqlx::query!(
+            "INSERT INTO role_assignments (role, assignable) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            self.id,
+            assignable.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        Role::by_id(self.id, connection).await
+    }
+
+    pub async fn remove_assignable(self, assignable: &Role, connection: &mut PgConnection) -> Result<Role> {
+        info!("Disallowing holders of role {} to assign role {}", self.name, assignable.name);
+
+        sqlx::query!(
+            "DELETE FROM role_assignments WHERE role = $1 AND assignable = $2",
+            self.id,
+            assignable.id
+        )
+        .execute(&mut *connection)
+        .await?;
+
+        Role::by_id(self.id, connection).await
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{
+    error::{Result, UserError},
+    role::Role,
+};
+use log::info;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PostRole {
+    pub name: String,
+
+    #[serde(default)]
+    pub legacy_bit: Option<u16>,
+}
+
+impl Role {
+    pub async fn create_from(data: PostRole, connection: &mut PgConnection) -> Result<Role> {
+        info!("Creating new role from {:?}", data);
+
+        if sqlx::query!("SELECT id FROM roles WHERE name = $1", data.name)
+            .fetch_optional(&mut *connection)
+            .await?
+            .is_some()
+        {
+            return Err(UserError::RoleNameTaken);
+        }
+
+        if let Some(bit) = data.legacy_bit {
+            if !bit.is_power_of_two() {
+                return Err(UserError::InvalidLegacyBit);
+            }
+
+            if let Some(row) = sqlx::query!("SELECT name FROM roles WHERE legacy_bit = $1", bit as i32)
+                .fetch_optional(&mut *connection)
+                .await?
+            {
+                return Err(UserError::LegacyBitTaken { bit, role: row.name });
+            }
+        }
+
+        let id = sqlx::query!(
+            "INSERT INTO roles (name, legacy_bit) VALUES ($1, $2) RETURNING id",
+            data.name,
+            data.legacy_bit.map(|bit| bit as i32)
+        )
+        .fetch_one(connection)
+        .await?
+        .id;
+
+        Ok(Role {
+            id,
+            name: data.name,
+            legacy_bit: data.legacy_bit,
+            builtin: false,
+            implies: Vec::new(),
+            assigns: Vec::new(),
+        })
+    }
+}